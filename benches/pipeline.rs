@@ -0,0 +1,143 @@
+//! Benchmarks of the grid generation pipeline on synthetic data
+//!
+//! Run with `cargo bench`; compare against a saved baseline with
+//! `cargo bench -- --save-baseline main` and `cargo bench -- --baseline main`.
+
+use std::hint::black_box;
+
+use bevy_osm_tiles::{
+    BoundingBox, DefaultGridGenerator, OsmConfig, OsmConfigBuilder, OsmData, OsmDataFormat,
+    OsmMetadata, OsmParser, SyntheticCity,
+};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::json;
+
+/// Element counts of the synthetic cities
+const ELEMENT_COUNTS: [usize; 3] = [1_000, 10_000, 50_000];
+
+/// Grid resolutions (tiles per degree), giving 100, 250 and 500 tiles per side
+const RESOLUTIONS: [u32; 3] = [2_000, 5_000, 10_000];
+
+/// Seed shared by all benchmarks, so runs are comparable
+const SEED: u64 = 42;
+
+fn bbox() -> BoundingBox {
+    BoundingBox::new(52.50, 13.40, 52.55, 13.45)
+}
+
+fn osm_data(raw_data: String) -> OsmData {
+    OsmData {
+        raw_data,
+        format: OsmDataFormat::Json,
+        bounding_box: bbox(),
+        metadata: OsmMetadata::new("benchmark", "synthetic"),
+    }
+}
+
+fn city(element_count: usize) -> OsmData {
+    osm_data(SyntheticCity::new(SEED, element_count).generate(&bbox()))
+}
+
+fn config(resolution: u32) -> OsmConfig {
+    OsmConfigBuilder::new()
+        .bbox(52.50, 13.40, 52.55, 13.45)
+        .grid_resolution(resolution)
+        .build()
+}
+
+/// Land-use polygons with detailed outlines covering most of the bounding box, stressing polygon fill
+fn large_polygons() -> OsmData {
+    let bbox = bbox();
+    let (step_lat, step_lon) = (bbox.height() / 4.0, bbox.width() / 4.0);
+    let mut elements = Vec::new();
+    for row in 0..4 {
+        for column in 0..4 {
+            let center_lat = bbox.south + (row as f64 + 0.5) * step_lat;
+            let center_lon = bbox.west + (column as f64 + 0.5) * step_lon;
+            // A wobbly ring of 256 vertices, like a traced park or forest
+            let geometry: Vec<_> = (0..=256)
+                .map(|step| {
+                    let angle = (step % 256) as f64 / 256.0 * std::f64::consts::TAU;
+                    let radius = 0.45 + 0.03 * (angle * 7.0).sin();
+                    json!({
+                        "lat": center_lat + radius * step_lat * angle.sin(),
+                        "lon": center_lon + radius * step_lon * angle.cos(),
+                    })
+                })
+                .collect();
+            elements.push(json!({
+                "type": "way",
+                "id": row * 4 + column + 1,
+                "tags": {"landuse": "residential"},
+                "geometry": geometry,
+            }));
+        }
+    }
+    osm_data(json!({"version": 0.6, "elements": elements}).to_string())
+}
+
+fn parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parsing");
+    for count in ELEMENT_COUNTS {
+        let data = city(count);
+        group.throughput(Throughput::Bytes(data.raw_data.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &data, |b, data| {
+            b.iter(|| OsmParser.parse(black_box(data)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn rasterization(c: &mut Criterion) {
+    let generator = DefaultGridGenerator::new();
+    let mut group = c.benchmark_group("rasterization");
+    group.sample_size(20);
+    for count in ELEMENT_COUNTS {
+        let data = city(count);
+        for resolution in RESOLUTIONS {
+            let config = config(resolution);
+            group.throughput(Throughput::Elements(count as u64));
+            group.bench_with_input(
+                BenchmarkId::new(count.to_string(), resolution),
+                &data,
+                |b, data| b.iter(|| generator.generate_grid_sync(black_box(data), &config)),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn polygon_fill(c: &mut Criterion) {
+    let generator = DefaultGridGenerator::new();
+    let data = large_polygons();
+    let mut group = c.benchmark_group("polygon_fill");
+    group.sample_size(20);
+    for resolution in RESOLUTIONS {
+        let config = config(resolution);
+        group.bench_with_input(BenchmarkId::from_parameter(resolution), &data, |b, data| {
+            b.iter(|| generator.generate_grid_sync(black_box(data), &config))
+        });
+    }
+    group.finish();
+}
+
+fn serialization(c: &mut Criterion) {
+    let generator = DefaultGridGenerator::new();
+    let data = city(10_000);
+    let mut group = c.benchmark_group("serialization");
+    for resolution in RESOLUTIONS {
+        let grid = generator
+            .generate_grid_sync(&data, &config(resolution))
+            .unwrap();
+        group.bench_with_input(BenchmarkId::new("json", resolution), &grid, |b, grid| {
+            b.iter(|| serde_json::to_string(black_box(grid)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("compact", resolution), &grid, |b, grid| {
+            b.iter(|| black_box(grid).to_compact())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parsing, rasterization, polygon_fill, serialization);
+criterion_main!(benches);
@@ -3,7 +3,7 @@ use clap::Parser;
 use tracing::{error, info, warn};
 use tracing_subscriber;
 
-use bevy_osm_tiles::{FeatureSet, OsmFeature, TileType, bevy_plugin::*};
+use bevy_osm_tiles::{FeatureSet, TileType, bevy_plugin::*};
 
 #[derive(Parser)]
 #[command(name = "osm-3d-city-loader-plugin")]
@@ -129,6 +129,7 @@ fn main() {
                 handle_map_failed,
                 update_camera,
                 update_loading_ui,
+                handle_tile_clicked,
             ),
         )
         .run();
@@ -146,6 +147,9 @@ struct LoadingText;
 #[derive(Component)]
 struct StatusText;
 
+#[derive(Component)]
+struct TileInfoText;
+
 fn setup(mut commands: Commands) {
     // Create camera
     commands.spawn((
@@ -198,9 +202,24 @@ fn setup(mut commands: Commands) {
         StatusText,
     ));
 
+    // Create tile info text, filled when a tile is clicked
+    commands.spawn((
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(80.0),
+            left: px(20.0),
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+        TileInfoText,
+    ));
+
     // Create controls text
     commands.spawn((
-        Text::new("Controls: WASD to move, Alt + Mouse to look around, Ctrl for boost"),
+        Text::new(
+            "Controls: WASD to move, Alt + Mouse to look around, Ctrl for boost, click a tile to inspect it",
+        ),
         Node {
             position_type: PositionType::Absolute,
             bottom: px(20.0),
@@ -225,22 +244,13 @@ fn request_map_load(
     };
 
     // Parse feature preset
-    let feature_set = match config.features.as_str() {
-        "urban" => FeatureSet::urban(),
-        "transportation" => FeatureSet::transportation(),
-        "natural" => FeatureSet::natural(),
-        "comprehensive" => FeatureSet::comprehensive(),
-        "gaming" => FeatureSet::urban()
-            .with_feature(OsmFeature::Amenities)
-            .with_feature(OsmFeature::Tourism),
-        _ => {
-            warn!(
-                "Unknown feature preset: {}. Using 'urban' instead.",
-                config.features
-            );
-            FeatureSet::urban()
-        }
-    };
+    let feature_set = FeatureSet::from_preset_name(&config.features).unwrap_or_else(|| {
+        warn!(
+            "Unknown feature preset: {}. Using 'urban' instead.",
+            config.features
+        );
+        FeatureSet::urban()
+    });
 
     // Create load request
     let mut request = MapLoadRequest::new(&config.city)
@@ -263,6 +273,8 @@ fn handle_map_loaded(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tile_cache: ResMut<TileAssetCache>,
+    settings: Res<TileRenderSettings>,
     mut loading_text: Query<&mut Text, (With<LoadingText>, Without<StatusText>)>,
     mut status_text: Query<&mut Text, (With<StatusText>, Without<LoadingText>)>,
     config: Res<AppConfig>,
@@ -295,7 +307,17 @@ fn handle_map_loaded(
         show_grid_stats(&message.grid);
 
         // Spawn 3D visualization
-        render_3d_map(&mut commands, &message.grid, &mut meshes, &mut materials);
+        if let Some(map_entity) = message.entity {
+            render_3d_map(
+                &mut commands,
+                map_entity,
+                &message.grid,
+                settings.tile_size,
+                &mut meshes,
+                &mut materials,
+                &mut tile_cache,
+            );
+        }
     }
 }
 
@@ -329,12 +351,15 @@ fn update_loading_ui(
                 LoadingStage::Complete => "Complete!",
             };
 
-            **text = format!(
-                "Loading {}: {} ({:.0}%)",
-                message.request.city_name,
-                stage_text,
-                message.progress * 100.0
-            );
+            **text = match &message.retry {
+                Some(retry) => format!("Loading {}: {}", message.request.city_name, retry),
+                None => format!(
+                    "Loading {}: {} ({:.0}%)",
+                    message.request.city_name,
+                    stage_text,
+                    message.progress * 100.0
+                ),
+            };
         }
     }
 }
@@ -364,19 +389,22 @@ fn show_grid_stats(grid: &bevy_osm_tiles::TileGrid) {
 
 fn render_3d_map(
     commands: &mut Commands,
+    map_entity: Entity,
     grid: &bevy_osm_tiles::TileGrid,
+    tile_size: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
+    tile_cache: &mut TileAssetCache,
 ) {
     let (grid_width, grid_height) = grid.dimensions();
-    let tile_size = 2.0;
+    let mapping = GridWorldMapping::new(grid, tile_size);
 
-    // Get mesh assets
+    // Get shared mesh assets
     let (cube_mesh, road_mesh, building_mesh, water_mesh) = (
-        meshes.add(Cuboid::new(tile_size, 1.0, tile_size)),
-        meshes.add(Cuboid::new(tile_size, 0.2, tile_size)),
-        meshes.add(Cuboid::new(tile_size, 4.0, tile_size)),
-        meshes.add(Cuboid::new(tile_size, 0.1, tile_size)),
+        tile_cache.mesh(tile_size, 1.0, meshes),
+        tile_cache.mesh(tile_size, 0.2, meshes),
+        tile_cache.mesh(tile_size, 4.0, meshes),
+        tile_cache.mesh(tile_size, 0.1, meshes),
     );
 
     info!("🎨 Rendering 3D map: {}x{} tiles", grid_width, grid_height);
@@ -408,26 +436,31 @@ fn render_3d_map(
                     TileType::Custom(_) => (cube_mesh.clone(), 0.8, Color::srgb(0.8, 0.8, 0.8)),
                 };
 
-                // Calculate world position
-                let world_x = (x as f32 - grid_width as f32 / 2.0) * tile_size;
-                let world_z = (z as f32 - grid_height as f32 / 2.0) * tile_size;
-
-                // Create material
-                let material_handle = materials.add(StandardMaterial {
-                    base_color: color,
-                    metallic: match tile.tile_type {
-                        TileType::Water => 0.8,
-                        TileType::Building | TileType::Commercial | TileType::Industrial => 0.2,
-                        _ => 0.1,
+                // Tiles sit on the map entity, laid out like the plugin's picking expects
+                let position = mapping.grid_to_local(x, z);
+
+                // Reuse one material per tile type
+                let material_handle = tile_cache.material_with(
+                    &tile.tile_type,
+                    TileStyle::Standard,
+                    materials,
+                    || StandardMaterial {
+                        base_color: color,
+                        metallic: match tile.tile_type {
+                            TileType::Water => 0.8,
+                            TileType::Building | TileType::Commercial | TileType::Industrial => 0.2,
+                            _ => 0.1,
+                        },
+                        ..default()
                     },
-                    ..default()
-                });
+                );
 
                 // Spawn the tile entity
                 commands.spawn((
                     Mesh3d(mesh_handle),
                     MeshMaterial3d(material_handle),
-                    Transform::from_xyz(world_x, height, world_z),
+                    Transform::from_translation(position.with_y(height)),
+                    ChildOf(map_entity),
                     MapTile {
                         tile_type: tile.tile_type.clone(),
                         grid_pos: (x, z),
@@ -439,7 +472,37 @@ fn render_3d_map(
         }
     }
 
-    info!("✅ Rendered {} 3D tiles", rendered_count);
+    info!(
+        "✅ Rendered {} 3D tiles with {} shared materials",
+        rendered_count,
+        tile_cache.material_count()
+    );
+}
+
+fn handle_tile_clicked(
+    mut clicked_reader: MessageReader<TileClickedMessage>,
+    mut tile_info: Query<&mut Text, With<TileInfoText>>,
+) {
+    for message in clicked_reader.read() {
+        let (x, y) = message.grid_pos;
+        let mut info = format!("Tile ({}, {}): {}", x, y, message.tile.tile_type.name());
+        if let Some(name) = message
+            .tile
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.tags.get("name"))
+        {
+            info.push_str(&format!(" \"{}\"", name));
+        }
+        if let Some((lat, lon)) = message.location {
+            info.push_str(&format!(" at {:.5}, {:.5}", lat, lon));
+        }
+
+        info!("🖱️ {}", info);
+        if let Ok(mut text) = tile_info.single_mut() {
+            **text = info;
+        }
+    }
 }
 
 fn update_camera(
@@ -3,8 +3,8 @@ use tracing::{error, info, warn};
 use tracing_subscriber;
 
 use bevy_osm_tiles::{
-    DefaultGridGenerator, FeatureSet, GridGenerator, OsmConfigBuilder, OsmDataProvider, OsmFeature,
-    ProviderFactory, TileGrid, TileType,
+    DefaultGridGenerator, ExportMetadata, FeatureSet, GridGenerator, OsmConfigBuilder,
+    OsmDataProvider, ProviderFactory, TileGrid, TileType,
 };
 
 use image::{ImageBuffer, Rgb, RgbImage};
@@ -107,23 +107,15 @@ pub async fn load_city_data(config: CityLoadConfig) -> Result<TileGrid, String>
     }
 
     // Create the appropriate provider
-    let provider: Box<dyn OsmDataProvider> = match config.provider.as_str() {
-        "overpass" => Box::new(ProviderFactory::overpass()),
-        "mock" => {
-            if let Some(delay_ms) = config.delay {
-                Box::new(ProviderFactory::mock_with_delay(delay_ms))
-            } else {
-                Box::new(ProviderFactory::mock())
+    let provider: Box<dyn OsmDataProvider> = match (config.provider.as_str(), config.delay) {
+        ("mock", Some(delay_ms)) => Box::new(ProviderFactory::mock_with_delay(delay_ms)),
+        (name, _) => match ProviderFactory::create_provider(name) {
+            Ok(provider) => provider,
+            Err(e) => {
+                error!("{}", e);
+                return Err("Invalid provider".to_string());
             }
-        }
-        _ => {
-            let available = ProviderFactory::available_providers();
-            error!(
-                "Unknown provider: {}. Available providers: {:?}",
-                config.provider, available
-            );
-            return Err("Invalid provider".to_string());
-        }
+        },
     };
 
     // Show provider capabilities if verbose
@@ -149,22 +141,13 @@ pub async fn load_city_data(config: CityLoadConfig) -> Result<TileGrid, String>
     }
 
     // Create configuration with selected feature preset
-    let feature_set = match config.features.as_str() {
-        "urban" => FeatureSet::urban(),
-        "transportation" => FeatureSet::transportation(),
-        "natural" => FeatureSet::natural(),
-        "comprehensive" => FeatureSet::comprehensive(),
-        "gaming" => FeatureSet::urban()
-            .with_feature(OsmFeature::Amenities)
-            .with_feature(OsmFeature::Tourism),
-        _ => {
-            warn!(
-                "Unknown feature preset: {}. Using 'urban' instead.",
-                config.features
-            );
-            FeatureSet::urban()
-        }
-    };
+    let feature_set = FeatureSet::from_preset_name(&config.features).unwrap_or_else(|| {
+        warn!(
+            "Unknown feature preset: {}. Using 'urban' instead.",
+            config.features
+        );
+        FeatureSet::urban()
+    });
 
     let osm_config = OsmConfigBuilder::new()
         .city(&config.city)
@@ -333,6 +316,13 @@ pub fn show_grid_stats(grid: &TileGrid) {
             );
         }
     }
+
+    info!(
+        "📏 Roads: ~{:.1} km, buildings: {:.3} km², water: {:.1}%",
+        stats.road_length_km,
+        stats.building_footprint_km2,
+        stats.water_coverage_ratio * 100.0
+    );
 }
 
 /// Show detailed grid analysis including sample areas and tile locations
@@ -455,7 +445,18 @@ pub fn generate_png(grid: &TileGrid, output_path: &str) -> Result<(), String> {
     img.save(output_path)
         .map_err(|e| format!("Failed to save PNG: {}", e))?;
 
-    info!("💾 PNG saved to: {}", output_path);
+    // The image crate does not write text chunks, so ship attribution alongside
+    let sidecar_path = ExportMetadata::sidecar_path(output_path);
+    std::fs::write(
+        &sidecar_path,
+        ExportMetadata::from_grid(grid).to_sidecar_json(),
+    )
+    .map_err(|e| format!("Failed to save attribution: {}", e))?;
+
+    info!(
+        "💾 PNG saved to: {} (attribution: {})",
+        output_path, sidecar_path
+    );
     Ok(())
 }
 
@@ -477,11 +478,8 @@ async fn main() -> Result<(), String> {
     // Test connection only
     if args.test {
         let config = CityLoadConfig::from(&args);
-        let provider: Box<dyn OsmDataProvider> = match config.provider.as_str() {
-            "overpass" => Box::new(ProviderFactory::overpass()),
-            "mock" => Box::new(ProviderFactory::mock()),
-            _ => return Err("Invalid provider".to_string()),
-        };
+        let provider = ProviderFactory::create_provider(&config.provider)
+            .map_err(|_| "Invalid provider".to_string())?;
 
         info!("🔍 Testing provider availability...");
         match provider.test_availability().await {
@@ -3,7 +3,7 @@ use bevy::prelude::*;
 #[cfg(not(target_arch = "wasm32"))]
 use tracing_subscriber;
 
-use bevy_osm_tiles::{FeatureSet, OsmFeature, TileType, bevy_plugin::*};
+use bevy_osm_tiles::{FeatureSet, TileType, bevy_plugin::*};
 
 fn main() {
     // Initialize tracing
@@ -42,13 +42,14 @@ fn main() {
         .add_systems(
             Update,
             (
-                handle_keyboard_input,
+                handle_keyboard_input.run_if(not(map_loading())),
                 handle_ui_interactions,
                 handle_map_loaded,
                 handle_map_failed,
                 update_loading_ui,
                 update_camera,
                 update_input_display,
+                handle_tile_clicked,
             ),
         )
         .run();
@@ -57,7 +58,6 @@ fn main() {
 #[derive(Resource)]
 struct AppState {
     current_input: String,
-    loading: bool,
     last_loaded_city: Option<String>,
     current_feature_set: FeaturePreset,
     current_resolution: u32,
@@ -67,7 +67,6 @@ impl Default for AppState {
     fn default() -> Self {
         Self {
             current_input: "Berlin".to_string(),
-            loading: false,
             last_loaded_city: None,
             current_feature_set: FeaturePreset::Urban,
             current_resolution: 250,
@@ -111,9 +110,7 @@ impl FeaturePreset {
             Self::Transportation => FeatureSet::transportation(),
             Self::Natural => FeatureSet::natural(),
             Self::Comprehensive => FeatureSet::comprehensive(),
-            Self::Gaming => FeatureSet::urban()
-                .with_feature(OsmFeature::Amenities)
-                .with_feature(OsmFeature::Tourism),
+            Self::Gaming => FeatureSet::gaming(),
         }
     }
 
@@ -186,6 +183,9 @@ struct InputDisplay;
 #[derive(Component)]
 struct StatusDisplay;
 
+#[derive(Component)]
+struct TileInfoDisplay;
+
 #[derive(Component)]
 struct LoadButton;
 
@@ -460,7 +460,7 @@ fn setup_status_panel(spawner: &mut ChildSpawnerCommands) {
         .spawn((
             Node {
                 width: Val::Percent(100.0),
-                height: Val::Px(80.0), // Increased height for warning text
+                height: Val::Px(100.0), // Increased height for warning and tile info text
                 padding: UiRect::all(Val::Px(20.0)),
                 align_items: AlignItems::Center,
                 flex_direction: FlexDirection::Column,
@@ -479,6 +479,15 @@ fn setup_status_panel(spawner: &mut ChildSpawnerCommands) {
                 TextColor(Color::srgb(0.8, 0.8, 0.8)),
                 StatusDisplay,
             ));
+            spawner.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                TileInfoDisplay,
+            ));
         });
 }
 
@@ -497,7 +506,7 @@ fn setup_controls_panel(spawner: &mut ChildSpawnerCommands) {
         ))
         .with_children(|spawner| {
             spawner.spawn((
-                Text::new("Controls: Type to enter city name | Enter/Load to load | Feature/Resolution buttons to cycle | ←→↑↓ to move camera | Alt+Mouse to look | PageUp/PageDown up/down | Ctrl speed boost"),
+                Text::new("Controls: Type to enter city name | Enter/Load to load | Feature/Resolution buttons to cycle | Click a tile to inspect it | ←→↑↓ to move camera | Alt+Mouse to look | PageUp/PageDown up/down | Ctrl speed boost"),
                 TextFont {
                     font_size: 14.0,
                     ..default()
@@ -514,10 +523,6 @@ fn handle_keyboard_input(
     mut load_writer: MessageWriter<LoadMapMessage>,
     map_container: Query<Entity, With<MapContainer>>,
 ) {
-    if app_state.loading {
-        return;
-    }
-
     for keyboard_input in keyboard_input_reader.read() {
         if !keyboard_input.state.is_pressed() {
             continue;
@@ -570,6 +575,7 @@ fn handle_ui_interactions(
     mut load_writer: MessageWriter<LoadMapMessage>,
     map_container: Query<Entity, With<MapContainer>>,
     mut text_query: Query<&mut Text>,
+    load_state: Res<MapLoadState>,
 ) {
     for (interaction, mut color, load_btn, feature_btn, resolution_btn, children) in
         &mut interaction_query
@@ -577,7 +583,8 @@ fn handle_ui_interactions(
         handle_button_interaction(
             interaction,
             &mut color,
-            load_btn,
+            // The load button does nothing while a map is loading
+            load_btn.filter(|_| !load_state.is_loading()),
             feature_btn,
             resolution_btn,
             children,
@@ -633,7 +640,7 @@ fn handle_button_press(
     map_container: &Query<Entity, With<MapContainer>>,
     text_query: &mut Query<&mut Text>,
 ) {
-    if load_btn.is_some() && !app_state.loading && !app_state.current_input.trim().is_empty() {
+    if load_btn.is_some() && !app_state.current_input.trim().is_empty() {
         load_city(app_state, load_writer, map_container);
     } else if feature_btn.is_some() {
         cycle_feature_preset(app_state, children, text_query);
@@ -742,7 +749,6 @@ fn load_city(
             .for_entity(container_entity);
 
         load_writer.load_map_with_request(request);
-        app_state.loading = true;
 
         let warning = app_state.resolution_warning().unwrap_or("");
         info!(
@@ -775,6 +781,8 @@ fn handle_map_loaded(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tile_cache: ResMut<TileAssetCache>,
+    settings: Res<TileRenderSettings>,
     mut status_display: Query<&mut Text, With<StatusDisplay>>,
     existing_tiles: Query<Entity, With<MapTile>>,
 ) {
@@ -785,6 +793,8 @@ fn handle_map_loaded(
             &mut commands,
             &mut meshes,
             &mut materials,
+            &mut tile_cache,
+            settings.tile_size,
             &mut status_display,
             &existing_tiles,
         );
@@ -797,16 +807,27 @@ fn process_map_loaded(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
+    tile_cache: &mut TileAssetCache,
+    tile_size: f32,
     status_display: &mut Query<&mut Text, With<StatusDisplay>>,
     existing_tiles: &Query<Entity, With<MapTile>>,
 ) {
-    app_state.loading = false;
     app_state.last_loaded_city = Some(message.request.city_name.clone());
 
     clear_existing_tiles(commands, existing_tiles);
     update_status_display(message, status_display, app_state);
     show_detailed_grid_stats(&message.grid); // Add detailed stats
-    render_3d_map(commands, &message.grid, meshes, materials);
+    if let Some(map_entity) = message.entity {
+        render_3d_map(
+            commands,
+            map_entity,
+            &message.grid,
+            tile_size,
+            meshes,
+            materials,
+            tile_cache,
+        );
+    }
 
     let (width, height) = message.grid.dimensions();
     info!(
@@ -878,12 +899,9 @@ fn show_detailed_grid_stats(grid: &bevy_osm_tiles::TileGrid) {
 
 fn handle_map_failed(
     mut failed_reader: MessageReader<MapLoadFailedMessage>,
-    mut app_state: ResMut<AppState>,
     mut status_display: Query<&mut Text, With<StatusDisplay>>,
 ) {
     for message in failed_reader.read() {
-        app_state.loading = false;
-
         if let Ok(mut text) = status_display.single_mut() {
             **text = format!(
                 "❌ Failed to load {}: {}",
@@ -911,26 +929,33 @@ fn update_loading_ui(
                 LoadingStage::Complete => "✅ Complete!",
             };
 
-            **text = format!(
-                "Loading {}: {} ({:.0}%)",
-                message.request.city_name,
-                stage_text,
-                message.progress * 100.0
-            );
+            **text = match &message.retry {
+                Some(retry) => format!("Loading {}: {}", message.request.city_name, retry),
+                None => format!(
+                    "Loading {}: {} ({:.0}%)",
+                    message.request.city_name,
+                    stage_text,
+                    message.progress * 100.0
+                ),
+            };
         }
     }
 }
 
+// Tiles are children of the map entity and laid out with the same mapping the plugin picks with
 fn render_3d_map(
     commands: &mut Commands,
+    map_entity: Entity,
     grid: &bevy_osm_tiles::TileGrid,
+    tile_size: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
+    tile_cache: &mut TileAssetCache,
 ) {
     let (grid_width, grid_height) = grid.dimensions();
-    let tile_size = 2.0;
+    let mapping = GridWorldMapping::new(grid, tile_size);
 
-    let mesh_handles = create_mesh_handles(meshes, tile_size);
+    let mesh_handles = create_mesh_handles(meshes, tile_cache, tile_size);
 
     info!("🎨 Rendering 3D map: {}×{} tiles", grid_width, grid_height);
 
@@ -944,16 +969,16 @@ fn render_3d_map(
                 {
                     spawn_tile_entity(
                         commands,
+                        map_entity,
                         mesh_handle,
                         height,
                         color,
                         x,
                         z,
-                        grid_width,
-                        grid_height,
-                        tile_size,
+                        &mapping,
                         tile,
                         materials,
+                        tile_cache,
                     );
                     rendered_count += 1;
                 }
@@ -971,12 +996,16 @@ struct MeshHandles {
     water: Handle<Mesh>,
 }
 
-fn create_mesh_handles(meshes: &mut ResMut<Assets<Mesh>>, tile_size: f32) -> MeshHandles {
+fn create_mesh_handles(
+    meshes: &mut ResMut<Assets<Mesh>>,
+    tile_cache: &mut TileAssetCache,
+    tile_size: f32,
+) -> MeshHandles {
     MeshHandles {
-        cube: meshes.add(Cuboid::new(tile_size, 1.0, tile_size)),
-        road: meshes.add(Cuboid::new(tile_size, 0.2, tile_size)),
-        building: meshes.add(Cuboid::new(tile_size, 4.0, tile_size)),
-        water: meshes.add(Cuboid::new(tile_size, 0.1, tile_size)),
+        cube: tile_cache.mesh(tile_size, 1.0, meshes),
+        road: tile_cache.mesh(tile_size, 0.2, meshes),
+        building: tile_cache.mesh(tile_size, 4.0, meshes),
+        water: tile_cache.mesh(tile_size, 0.1, meshes),
     }
 }
 
@@ -1019,31 +1048,35 @@ fn get_tile_render_info(
 
 fn spawn_tile_entity(
     commands: &mut Commands,
+    map_entity: Entity,
     mesh_handle: Handle<Mesh>,
     height: f32,
     color: Color,
     x: usize,
     z: usize,
-    grid_width: usize,
-    grid_height: usize,
-    tile_size: f32,
+    mapping: &GridWorldMapping,
     tile: &bevy_osm_tiles::Tile,
     materials: &mut ResMut<Assets<StandardMaterial>>,
+    tile_cache: &mut TileAssetCache,
 ) {
-    let world_x = (x as f32 - grid_width as f32 / 2.0) * tile_size;
-    let world_z = (z as f32 - grid_height as f32 / 2.0) * tile_size;
-
-    let material_handle = materials.add(StandardMaterial {
-        base_color: color,
-        metallic: get_tile_metallic(&tile.tile_type),
-        perceptual_roughness: get_tile_roughness(&tile.tile_type),
-        ..default()
-    });
+    let position = mapping.grid_to_local(x, z);
+
+    // Tiles of the same type share one material
+    let material_handle =
+        tile_cache.material_with(&tile.tile_type, TileStyle::Standard, materials, || {
+            StandardMaterial {
+                base_color: color,
+                metallic: get_tile_metallic(&tile.tile_type),
+                perceptual_roughness: get_tile_roughness(&tile.tile_type),
+                ..default()
+            }
+        });
 
     commands.spawn((
         Mesh3d(mesh_handle),
         MeshMaterial3d(material_handle),
-        Transform::from_xyz(world_x, height, world_z),
+        Transform::from_translation(position.with_y(height)),
+        ChildOf(map_entity),
         MapTile {
             tile_type: tile.tile_type.clone(),
             grid_pos: (x, z),
@@ -1051,6 +1084,36 @@ fn spawn_tile_entity(
     ));
 }
 
+fn handle_tile_clicked(
+    mut clicked_reader: MessageReader<TileClickedMessage>,
+    mut tile_info: Query<&mut Text, With<TileInfoDisplay>>,
+) {
+    for message in clicked_reader.read() {
+        let info = describe_clicked_tile(message);
+        info!("🖱️ {}", info);
+        if let Ok(mut text) = tile_info.single_mut() {
+            **text = info;
+        }
+    }
+}
+
+fn describe_clicked_tile(message: &TileClickedMessage) -> String {
+    let (x, y) = message.grid_pos;
+    let mut info = format!("Tile ({}, {}): {}", x, y, message.tile.tile_type.name());
+    if let Some(name) = message
+        .tile
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.tags.get("name"))
+    {
+        info.push_str(&format!(" \"{}\"", name));
+    }
+    if let Some((lat, lon)) = message.location {
+        info.push_str(&format!(" at {:.5}, {:.5}", lat, lon));
+    }
+    info
+}
+
 fn get_tile_metallic(tile_type: &TileType) -> f32 {
     match tile_type {
         TileType::Water => 0.8, // Match CLI example
@@ -0,0 +1,37 @@
+/*
+ * C interface of bevy-osm-tiles, built with the `ffi` feature.
+ *
+ * Grids are returned in the packed format documented in src/export/packed.rs.
+ * Every buffer must be released with osm_tiles_free_buffer, so out_len must
+ * not be NULL. On failure, including a panic inside the library, the
+ * functions return NULL, set *out_len to 0, and osm_tiles_last_error
+ * describes the problem until the next call on the same thread.
+ */
+#ifndef OSM_TILES_H
+#define OSM_TILES_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Download the region of a JSON configuration from Overpass and generate its grid. */
+uint8_t *osm_tiles_generate(const char *config_json, size_t *out_len);
+
+/* Generate the grid of a saved download; config_json may be NULL. */
+uint8_t *osm_tiles_generate_from_data(const char *data_json, const char *config_json,
+                                      size_t *out_len);
+
+/* Release a buffer returned by this library. */
+void osm_tiles_free_buffer(uint8_t *buffer, size_t len);
+
+/* Describe why the last call on this thread failed, or NULL if it succeeded. */
+const char *osm_tiles_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OSM_TILES_H */
@@ -1,6 +1,9 @@
 use super::{LoadingStage, MapLoadRequest};
-use crate::TileGrid;
+use crate::{BoundingBox, Crs, GeocodeCandidate, GridOrigin, RetryStatus, TileGrid};
 use bevy::{ecs::world::CommandQueue, prelude::*, tasks::Task};
+use std::sync::{Arc, Mutex};
+
+pub use crate::http::CancellationToken;
 
 /// Component to hold loaded map data
 #[derive(Component, Debug)]
@@ -11,6 +14,164 @@ pub struct MapTiles {
     pub loaded_at: std::time::Instant,
 }
 
+/// Component mapping a map's grid and geographic coordinates to Bevy space
+///
+/// Tiles are centered on the map root entity on the XZ plane, one `tile_size`
+/// per tile: east is +X and north is -Z (Bevy's forward), whatever the grid's
+/// origin. The plugin attaches it to every entity a map is loaded onto. Local
+/// methods work in the map entity's space; world methods take its
+/// `GlobalTransform`, so maps can be moved, rotated and scaled freely.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct GridWorldMapping {
+    pub tile_size: f32,
+    pub width: usize,
+    pub height: usize,
+    pub origin: GridOrigin,
+    /// Geographic area covered by the grid
+    pub bounding_box: BoundingBox,
+    /// Coordinate reference system the grid is laid out in
+    pub crs: Crs,
+}
+
+/// Former name of [`GridWorldMapping`]
+#[deprecated(note = "renamed to `GridWorldMapping`")]
+pub type GridTransform = GridWorldMapping;
+
+impl GridWorldMapping {
+    /// Create the mapping for a grid rendered with the given tile size
+    pub fn new(grid: &TileGrid, tile_size: f32) -> Self {
+        let (width, height) = grid.dimensions();
+        Self {
+            tile_size,
+            width,
+            height,
+            origin: grid.origin,
+            bounding_box: grid.bounding_box.clone(),
+            crs: grid.crs,
+        }
+    }
+
+    /// Get the extent of the map on the XZ plane in local units
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32) * self.tile_size
+    }
+
+    /// Get the local position of a tile's center on the map plane
+    pub fn grid_to_local(&self, x: usize, y: usize) -> Vec3 {
+        let row = self.origin.to_top_left(y, self.height);
+        self.image_to_local(x as f64 + 0.5, row as f64 + 0.5)
+    }
+
+    /// Get the tile containing a local position, ignoring its height
+    pub fn local_to_grid(&self, local: Vec3) -> Option<(usize, usize)> {
+        let (x, y) = self.local_to_image(local);
+        let (x, y) = (x.floor(), y.floor());
+
+        if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+            return None;
+        }
+        Some((
+            x as usize,
+            self.origin.from_top_left(y as usize, self.height),
+        ))
+    }
+
+    /// Get the local position of a geographic location on the map plane
+    ///
+    /// Locations outside the grid are extrapolated rather than rejected, so
+    /// a player can walk off the edge of the map.
+    pub fn geo_to_local(&self, lat: f64, lon: f64) -> Vec3 {
+        let (min_x, min_y, max_x, max_y) = self.projected_bounds();
+        let (px, py) = self.crs.projection().project(lat, lon);
+        self.image_to_local(
+            (px - min_x) / (max_x - min_x) * self.width as f64,
+            (max_y - py) / (max_y - min_y) * self.height as f64,
+        )
+    }
+
+    /// Get the geographic location (lat, lon) of a local position, ignoring its height
+    pub fn local_to_geo(&self, local: Vec3) -> (f64, f64) {
+        let (min_x, min_y, max_x, max_y) = self.projected_bounds();
+        let (x, y) = self.local_to_image(local);
+        let px = min_x + x / self.width as f64 * (max_x - min_x);
+        let py = max_y - y / self.height as f64 * (max_y - min_y);
+        self.crs.projection().unproject(px, py)
+    }
+
+    /// Get the world position of a tile's center for a map entity at `transform`
+    pub fn grid_to_world(&self, x: usize, y: usize, transform: &GlobalTransform) -> Vec3 {
+        transform.transform_point(self.grid_to_local(x, y))
+    }
+
+    /// Get the tile below a world position for a map entity at `transform`
+    pub fn world_to_grid(
+        &self,
+        world: Vec3,
+        transform: &GlobalTransform,
+    ) -> Option<(usize, usize)> {
+        self.local_to_grid(Self::to_local(world, transform))
+    }
+
+    /// Get the world position of a geographic location for a map entity at `transform`
+    pub fn geo_to_world(&self, lat: f64, lon: f64, transform: &GlobalTransform) -> Vec3 {
+        transform.transform_point(self.geo_to_local(lat, lon))
+    }
+
+    /// Get the geographic location (lat, lon) below a world position for a map entity at `transform`
+    pub fn world_to_geo(&self, world: Vec3, transform: &GlobalTransform) -> (f64, f64) {
+        self.local_to_geo(Self::to_local(world, transform))
+    }
+
+    /// Convert fractional top-left image coordinates to a local position
+    fn image_to_local(&self, x: f64, y: f64) -> Vec3 {
+        // Tile centers sit at whole multiples of the tile size
+        Vec3::new(
+            (x - 0.5 - self.width as f64 / 2.0) as f32 * self.tile_size,
+            0.0,
+            (y - 0.5 - self.height as f64 / 2.0) as f32 * self.tile_size,
+        )
+    }
+
+    /// Convert a local position to fractional top-left image coordinates
+    fn local_to_image(&self, local: Vec3) -> (f64, f64) {
+        (
+            (local.x / self.tile_size) as f64 + self.width as f64 / 2.0 + 0.5,
+            (local.z / self.tile_size) as f64 + self.height as f64 / 2.0 + 0.5,
+        )
+    }
+
+    /// Get the projected corners of the bounding box
+    fn projected_bounds(&self) -> (f64, f64, f64, f64) {
+        let projection = self.crs.projection();
+        let bbox = &self.bounding_box;
+        let (min_x, min_y) = projection.project(bbox.south, bbox.west);
+        let (max_x, max_y) = projection.project(bbox.north, bbox.east);
+        (min_x, min_y, max_x, max_y)
+    }
+
+    /// Convert a world position into the map entity's local space
+    fn to_local(world: Vec3, transform: &GlobalTransform) -> Vec3 {
+        transform.affine().inverse().transform_point3(world)
+    }
+
+    /// Intersect a world-space ray with the map plane of an entity at `transform`
+    ///
+    /// Returns the hit tile, the world-space hit point and the distance along the ray.
+    pub fn ray_hit(
+        &self,
+        ray: Ray3d,
+        transform: &GlobalTransform,
+    ) -> Option<((usize, usize), Vec3, f32)> {
+        let plane = InfinitePlane3d::new(transform.up());
+        let distance = ray.intersect_plane(transform.translation(), plane)?;
+        let point = ray.get_point(distance);
+        let local = Self::to_local(point, transform);
+
+        self.local_to_grid(local)
+            .map(|grid_pos| (grid_pos, point, distance))
+    }
+}
+
 /// Component indicating a map is currently being loaded
 #[derive(Component, Debug)]
 pub struct MapLoading {
@@ -26,6 +187,24 @@ pub struct MapLoading {
 pub struct LoadingTask {
     pub request: MapLoadRequest,
     pub task: Task<CommandQueue>,
+    /// Latest retry status reported by the provider, taken once it has been published
+    pub retry_status: Arc<Mutex<Option<RetryStatus>>>,
+    /// Latest stage entered by the task, taken once it has been published
+    pub stage: Arc<Mutex<Option<LoadingStage>>>,
+    /// Places an ambiguous city name matched, taken once they have been published
+    pub candidates: Arc<Mutex<Option<Vec<GeocodeCandidate>>>>,
+    /// Token aborting the task's requests, also checked between loading stages
+    pub cancellation: CancellationToken,
+    /// Real time (in seconds since startup) after which the load is abandoned
+    pub deadline: Option<f64>,
     #[cfg(not(target_arch = "wasm32"))]
     pub started_at: std::time::Instant,
 }
+
+/// Component for a running partial reload, see `ReloadRegionMessage`
+#[derive(Component)]
+pub struct RegionReloadTask {
+    /// Map entity the regenerated chunk is merged into
+    pub entity: Entity,
+    pub task: Task<CommandQueue>,
+}
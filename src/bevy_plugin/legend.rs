@@ -0,0 +1,76 @@
+use crate::{Legend, LegendEntry};
+use bevy::prelude::*;
+
+/// Component marking the root node of a spawned map legend
+#[derive(Component, Debug, Clone)]
+pub struct MapLegend {
+    /// The legend the node shows
+    pub legend: Legend,
+}
+
+/// Component marking one legend row
+#[derive(Component, Debug, Clone)]
+pub struct MapLegendRow {
+    /// The legend entry the row shows
+    pub entry: LegendEntry,
+}
+
+/// Spawn a UI node listing a swatch, label and tile count per legend entry
+///
+/// The node is a column with no positioning of its own; insert a `Node` with
+/// absolute positioning or make it the child of a panel to place it. Returns
+/// the root entity.
+pub fn spawn_legend(commands: &mut Commands, legend: &Legend) -> Entity {
+    let root = commands
+        .spawn((
+            MapLegend {
+                legend: legend.clone(),
+            },
+            Name::new("Map legend"),
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .id();
+
+    for entry in &legend.entries {
+        let (r, g, b) = entry.color;
+        commands
+            .spawn((
+                MapLegendRow {
+                    entry: entry.clone(),
+                },
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ChildOf(root),
+            ))
+            .with_children(|row| {
+                row.spawn((
+                    Node {
+                        width: Val::Px(14.0),
+                        height: Val::Px(14.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb_u8(r, g, b)),
+                ));
+                row.spawn((
+                    Text::new(format!("{} ({})", entry.label, entry.count)),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            });
+    }
+
+    root
+}
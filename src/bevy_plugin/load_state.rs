@@ -0,0 +1,110 @@
+use super::resources::MapLoadQueue;
+use super::{
+    LoadingStage, MapLoadCancelledMessage, MapLoadFailedMessage, MapLoadProgressMessage,
+    MapLoadedMessage,
+};
+use crate::GridStatistics;
+use bevy::prelude::*;
+
+/// Resource tracking where map loading currently stands
+///
+/// Kept in sync by the plugin from its loading messages, so games can branch
+/// on it or use the run conditions [`map_ready`], [`map_loading`] and
+/// [`map_failed`] instead of reconstructing the state from messages. When
+/// several maps load at once, the most recent event wins.
+#[derive(Resource, Debug, Clone, Default)]
+pub enum MapLoadState {
+    /// Nothing loaded or loading yet
+    #[default]
+    Idle,
+    /// A load is queued or resolving its city
+    Resolving,
+    /// Map data is downloading
+    Fetching { progress: f32 },
+    /// The grid is being generated
+    Generating { progress: f32 },
+    /// The last load finished
+    Ready { stats: Box<GridStatistics> },
+    /// The last load failed
+    Failed { error: String },
+}
+
+impl MapLoadState {
+    /// Check whether a load is in progress
+    pub fn is_loading(&self) -> bool {
+        matches!(
+            self,
+            Self::Resolving | Self::Fetching { .. } | Self::Generating { .. }
+        )
+    }
+
+    /// Check whether the last load finished
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Ready { .. })
+    }
+
+    /// Get the overall progress from 0.0 to 1.0, if loading or done
+    pub fn progress(&self) -> Option<f32> {
+        match self {
+            Self::Resolving => Some(LoadingStage::ResolvingCity.progress()),
+            Self::Fetching { progress } | Self::Generating { progress } => Some(*progress),
+            Self::Ready { .. } => Some(1.0),
+            Self::Idle | Self::Failed { .. } => None,
+        }
+    }
+}
+
+/// Run condition: the last map load finished
+pub fn map_ready() -> impl FnMut(Res<MapLoadState>) -> bool + Clone {
+    |state: Res<MapLoadState>| state.is_ready()
+}
+
+/// Run condition: a map load is in progress
+pub fn map_loading() -> impl FnMut(Res<MapLoadState>) -> bool + Clone {
+    |state: Res<MapLoadState>| state.is_loading()
+}
+
+/// Run condition: the last map load failed
+pub fn map_failed() -> impl FnMut(Res<MapLoadState>) -> bool + Clone {
+    |state: Res<MapLoadState>| matches!(*state, MapLoadState::Failed { .. })
+}
+
+/// System keeping `MapLoadState` in sync with the loading messages
+pub fn update_map_load_state(
+    mut state: ResMut<MapLoadState>,
+    queue: Res<MapLoadQueue>,
+    mut progress_reader: MessageReader<MapLoadProgressMessage>,
+    mut loaded_reader: MessageReader<MapLoadedMessage>,
+    mut failed_reader: MessageReader<MapLoadFailedMessage>,
+    mut cancelled_reader: MessageReader<MapLoadCancelledMessage>,
+) {
+    let queued = !queue.active.is_empty() || !queue.pending.is_empty();
+    if let Some(message) = progress_reader.read().last() {
+        let progress = message.progress;
+        *state = match message.stage {
+            LoadingStage::ResolvingCity => MapLoadState::Resolving,
+            LoadingStage::FetchingData => MapLoadState::Fetching { progress },
+            LoadingStage::GeneratingGrid | LoadingStage::Complete => {
+                MapLoadState::Generating { progress }
+            }
+        };
+    }
+    if let Some(message) = loaded_reader.read().last() {
+        *state = MapLoadState::Ready {
+            stats: Box::new(message.grid.statistics()),
+        };
+    }
+    if let Some(message) = failed_reader.read().last() {
+        *state = MapLoadState::Failed {
+            error: message.error.clone(),
+        };
+    }
+
+    // Queued loads count as resolving before their task reports anything
+    let cancelled = cancelled_reader.read().count() > 0;
+    if cancelled && !queued {
+        *state = MapLoadState::Idle;
+    } else if queued && !state.is_loading() {
+        *state = MapLoadState::Resolving;
+    }
+}
@@ -1,4 +1,4 @@
-use crate::TileGrid;
+use crate::{BoundingBox, GeocodeCandidate, GridRect, RetryStatus, Tile, TileGrid, normalize_name};
 use bevy::prelude::*;
 
 /// Event to request loading a map
@@ -15,6 +15,10 @@ pub struct MapLoadRequest {
     pub grid_resolution: u32,
     pub target_entity: Option<Entity>,
     pub provider_override: Option<String>,
+    /// Overall time allowed for the load, overriding the plugin default
+    pub deadline: Option<std::time::Duration>,
+    /// Extent to load instead of geocoding `city_name`, e.g. a picked candidate
+    pub bounding_box: Option<BoundingBox>,
 }
 
 impl MapLoadRequest {
@@ -26,6 +30,8 @@ impl MapLoadRequest {
             grid_resolution: 200,
             target_entity: None,
             provider_override: None,
+            deadline: None,
+            bounding_box: None,
         }
     }
 
@@ -52,6 +58,49 @@ impl MapLoadRequest {
         self.provider_override = Some(provider.into());
         self
     }
+
+    /// Abandon the load if it has not finished within `deadline`
+    pub fn with_deadline(mut self, deadline: std::time::Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Load `bounding_box` instead of geocoding the city name
+    ///
+    /// The city name is still used to label the map and its messages.
+    pub fn with_bounding_box(mut self, bounding_box: BoundingBox) -> Self {
+        self.bounding_box = Some(bounding_box);
+        self
+    }
+
+    /// Get the key under which the generated grid is cached in `LoadedMaps`
+    ///
+    /// Requests for the same city, resolution, provider and features share a
+    /// key; city names are compared after [`normalize_name`].
+    pub fn cache_key(&self) -> String {
+        let mut features: Vec<String> = self
+            .features
+            .to_osm_queries()
+            .iter()
+            .map(|query| query.to_overpass_filter())
+            .collect();
+        features.sort();
+        features.dedup();
+        let mut key = format!(
+            "{}|{}|{}|{}",
+            normalize_name(&self.city_name),
+            self.grid_resolution,
+            self.provider_override.as_deref().unwrap_or("default"),
+            features.join(",")
+        );
+        if let Some(bbox) = &self.bounding_box {
+            key.push_str(&format!(
+                "|{},{},{},{}",
+                bbox.south, bbox.west, bbox.north, bbox.east
+            ));
+        }
+        key
+    }
 }
 
 /// Event sent when a map has been successfully loaded
@@ -69,16 +118,135 @@ pub struct MapLoadFailedMessage {
     pub error: String,
 }
 
+/// Event to refetch and regenerate part of a loaded map
+///
+/// Only the cells overlapping `bbox` are replaced; the rest of the map's
+/// `MapTiles` grid is kept. A `MapRegionUpdatedMessage` reports the changed
+/// cells, or a `MapLoadFailedMessage` the error.
+#[derive(Message, Clone, Debug)]
+pub struct ReloadRegionMessage {
+    /// Map entity holding the `MapTiles` to update
+    pub entity: Entity,
+    /// Area to reload
+    pub bbox: BoundingBox,
+}
+
+/// Event sent when part of a loaded map was regenerated
+///
+/// Auto-rendered tiles are not respawned; editing tools refresh the cells in
+/// `cells` themselves.
+#[derive(Message, Debug, Clone)]
+pub struct MapRegionUpdatedMessage {
+    /// Map entity whose grid changed
+    pub entity: Entity,
+    /// Geographic bounds of the regenerated cells
+    pub bbox: BoundingBox,
+    /// Regenerated cells of the grid
+    pub cells: GridRect,
+    /// Number of tiles whose content changed
+    pub tiles_changed: usize,
+}
+
+/// Event sent when a failed load is scheduled to run again, see `MapLoadRetries`
+#[derive(Message, Debug, Clone)]
+pub struct MapLoadRetryMessage {
+    pub request: MapLoadRequest,
+    /// Failed attempt, total attempts and time until the next one
+    pub status: RetryStatus,
+}
+
+/// Event sent when the city name of a load matches several places
+///
+/// The load goes on with the first candidate. To load another one, send the
+/// request returned by [`choose`](Self::choose).
+#[derive(Message, Debug, Clone)]
+pub struct CityDisambiguationMessage {
+    pub request: MapLoadRequest,
+    /// Places the city name may refer to, best match first
+    pub candidates: Vec<GeocodeCandidate>,
+}
+
+impl CityDisambiguationMessage {
+    /// Get a request loading the candidate at `index`, `None` if there is none
+    pub fn choose(&self, index: usize) -> Option<MapLoadRequest> {
+        let candidate = self.candidates.get(index)?;
+        Some(
+            self.request
+                .clone()
+                .with_bounding_box(candidate.bounding_box.clone()),
+        )
+    }
+}
+
+/// Event to cancel queued and running map loads
+#[derive(Message, Clone, Debug, PartialEq)]
+pub enum CancelMapLoadMessage {
+    /// Cancel the loads targeting an entity
+    Entity(Entity),
+    /// Cancel the loads of a city
+    City(String),
+    /// Cancel every load
+    All,
+}
+
+impl CancelMapLoadMessage {
+    /// Check whether a request is affected by this cancellation
+    pub fn matches(&self, request: &MapLoadRequest) -> bool {
+        match self {
+            Self::Entity(entity) => request.target_entity == Some(*entity),
+            Self::City(city_name) => {
+                normalize_name(&request.city_name) == normalize_name(city_name)
+            }
+            Self::All => true,
+        }
+    }
+}
+
+/// Why a map load was cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelReason {
+    /// A `CancelMapLoadMessage` asked for it
+    Requested,
+    /// The entity the map was loaded for was despawned
+    TargetDespawned,
+}
+
+/// Event sent when a queued or running map load was cancelled
+///
+/// No `MapLoadedMessage` or `MapLoadFailedMessage` follows for the request.
+#[derive(Message, Debug, Clone)]
+pub struct MapLoadCancelledMessage {
+    pub request: MapLoadRequest,
+    pub reason: CancelReason,
+}
+
 /// Event sent to report loading progress
 #[derive(Message, Debug)]
 pub struct MapLoadProgressMessage {
     pub request: MapLoadRequest,
     pub stage: LoadingStage,
     pub progress: f32, // 0.0 to 1.0
+    /// Set while a failed download is waiting to be retried
+    pub retry: Option<RetryStatus>,
+}
+
+/// Event sent when a tile of a loaded map is clicked
+#[derive(Message, Debug, Clone)]
+pub struct TileClickedMessage {
+    /// Map entity holding the clicked grid
+    pub entity: Entity,
+    /// Grid position (x, y) of the clicked tile
+    pub grid_pos: (usize, usize),
+    /// World-space point where the cursor ray hit the map plane
+    pub world_position: Vec3,
+    /// Geographic location (lat, lon) of the tile
+    pub location: Option<(f64, f64)>,
+    /// The clicked tile, including its OSM metadata
+    pub tile: Tile,
 }
 
 /// Stages of the loading process
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LoadingStage {
     ResolvingCity,
     FetchingData,
@@ -86,6 +254,28 @@ pub enum LoadingStage {
     Complete,
 }
 
+impl LoadingStage {
+    /// Get a short description of the stage for status displays
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ResolvingCity => "Resolving city location",
+            Self::FetchingData => "Fetching OSM data",
+            Self::GeneratingGrid => "Generating grid",
+            Self::Complete => "Complete",
+        }
+    }
+
+    /// Get the overall progress reported when the stage begins
+    pub fn progress(&self) -> f32 {
+        match self {
+            Self::ResolvingCity => 0.0,
+            Self::FetchingData => 0.25,
+            Self::GeneratingGrid => 0.75,
+            Self::Complete => 1.0,
+        }
+    }
+}
+
 /// Helper trait for loading maps
 pub trait MapLoadingExt {
     /// Request loading a map for a city
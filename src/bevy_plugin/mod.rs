@@ -1,11 +1,23 @@
 mod components;
+mod legend;
+mod load_state;
 mod messages;
+mod picking;
 mod plugin;
+mod poi;
+mod rendering;
 mod resources;
+mod status_ui;
 mod systems;
 
 pub use components::*;
+pub use legend::*;
+pub use load_state::*;
 pub use messages::*;
+pub use picking::*;
 pub use plugin::*;
+pub use poi::*;
+pub use rendering::*;
 pub use resources::*;
+pub use status_ui::*;
 pub use systems::*;
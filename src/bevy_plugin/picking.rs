@@ -0,0 +1,88 @@
+use super::{GridWorldMapping, MapTiles, TileClickedMessage};
+use bevy::{prelude::*, window::PrimaryWindow};
+
+/// A tile hit by a picking ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileHit {
+    /// Map entity holding the hit grid
+    pub entity: Entity,
+    /// Grid position (x, y) of the hit tile
+    pub grid_pos: (usize, usize),
+    /// World-space point where the ray hit the map plane
+    pub world_position: Vec3,
+    /// Distance along the ray
+    pub distance: f32,
+}
+
+/// Find the closest map tile hit by a world-space ray
+pub fn pick_tile<'a>(
+    ray: Ray3d,
+    maps: impl IntoIterator<Item = (Entity, &'a GridWorldMapping, &'a GlobalTransform)>,
+) -> Option<TileHit> {
+    maps.into_iter()
+        .filter_map(|(entity, layout, transform)| {
+            layout
+                .ray_hit(ray, transform)
+                .map(|(grid_pos, world_position, distance)| TileHit {
+                    entity,
+                    grid_pos,
+                    world_position,
+                    distance,
+                })
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// Cast a ray from the cursor through the first active camera
+pub fn cursor_ray(window: &Window, cameras: &Query<(&Camera, &GlobalTransform)>) -> Option<Ray3d> {
+    let cursor = window.cursor_position()?;
+    cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .find_map(|(camera, transform)| camera.viewport_to_world(transform, cursor).ok())
+}
+
+/// System sending a [`TileClickedMessage`] when the left mouse button is pressed over a map
+pub fn pick_clicked_tiles(
+    mouse: Option<Res<ButtonInput<MouseButton>>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    maps: Query<(Entity, &MapTiles, &GridWorldMapping, &GlobalTransform)>,
+    mut clicked: MessageWriter<TileClickedMessage>,
+) {
+    if !mouse.is_some_and(|mouse| mouse.just_pressed(MouseButton::Left)) {
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(ray) = cursor_ray(window, &cameras) else {
+        return;
+    };
+
+    let hit = pick_tile(
+        ray,
+        maps.iter()
+            .map(|(entity, _, layout, transform)| (entity, layout, transform)),
+    );
+    let Some(hit) = hit else {
+        return;
+    };
+
+    let Ok((_, map, _, _)) = maps.get(hit.entity) else {
+        return;
+    };
+    let (x, y) = hit.grid_pos;
+    let Some(tile) = map.grid.get_tile(x, y) else {
+        return;
+    };
+
+    clicked.write(TileClickedMessage {
+        entity: hit.entity,
+        grid_pos: hit.grid_pos,
+        world_position: hit.world_position,
+        location: map.grid.grid_to_geo(x, y),
+        tile: tile.clone(),
+    });
+}
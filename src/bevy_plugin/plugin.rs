@@ -1,14 +1,24 @@
 use bevy::prelude::*;
 
+use crate::RetryPolicy;
+
 use super::{
-    LoadMapMessage, MapLoadFailedMessage, MapLoadProgressMessage, MapLoadedMessage, resources::*,
-    systems::*,
+    CancelMapLoadMessage, CityDisambiguationMessage, LoadMapMessage, MapLoadCancelledMessage,
+    MapLoadFailedMessage, MapLoadProgressMessage, MapLoadRequest, MapLoadRetryMessage,
+    MapLoadState, MapLoadedMessage, MapRegionUpdatedMessage, ReloadRegionMessage, TileAssetCache,
+    TileClickedMessage, TileRenderMode, TileRenderSettings, attach_world_mapping,
+    pick_clicked_tiles, render_loaded_maps, resources::*, spawn_loaded_pois, systems::*,
+    update_map_error_toast, update_map_load_state, update_map_status_ui,
 };
 
 /// Bevy plugin for loading OpenStreetMap data dynamically
 pub struct OsmTilesPlugin {
     default_provider: String,
     max_concurrent_loads: usize,
+    load_deadline: Option<std::time::Duration>,
+    load_retries: MapLoadRetries,
+    render_settings: TileRenderSettings,
+    preload_requests: Vec<MapLoadRequest>,
 }
 
 impl OsmTilesPlugin {
@@ -17,6 +27,10 @@ impl OsmTilesPlugin {
         Self {
             default_provider: "overpass".to_string(),
             max_concurrent_loads: 2,
+            load_deadline: None,
+            load_retries: MapLoadRetries::default(),
+            render_settings: TileRenderSettings::default(),
+            preload_requests: Vec::new(),
         }
     }
 
@@ -37,6 +51,72 @@ impl OsmTilesPlugin {
         self.max_concurrent_loads = max;
         self
     }
+
+    /// Abandon loads that have not finished within `deadline`
+    ///
+    /// Requests can override this with [`MapLoadRequest::with_deadline`].
+    /// Loads past their deadline are reported with a `MapLoadFailedMessage`.
+    pub fn with_load_deadline(mut self, deadline: std::time::Duration) -> Self {
+        self.load_deadline = Some(deadline);
+        self
+    }
+
+    /// Restart failed loads with retryable errors according to `policy`
+    ///
+    /// Each scheduled attempt is reported with a `MapLoadRetryMessage`; see
+    /// [`MapLoadRetries`] for the details.
+    pub fn with_load_retries(mut self, policy: RetryPolicy) -> Self {
+        self.load_retries.policy = policy;
+        self
+    }
+
+    /// Retry failed loads whatever their error, e.g. for flaky custom providers
+    pub fn retry_all_errors(mut self) -> Self {
+        self.load_retries.retryable_only = false;
+        self
+    }
+
+    /// Spawn tile meshes automatically for maps loaded onto a target entity
+    pub fn with_auto_render(mut self, tile_size: f32) -> Self {
+        self.render_settings.auto_render = true;
+        self.render_settings.tile_size = tile_size;
+        self
+    }
+
+    /// Start loading cities at startup and keep their grids in `LoadedMaps`
+    ///
+    /// Later requests for a preloaded city are answered from the cache, so
+    /// gameplay can switch to it instantly. Track the progress of a loading
+    /// screen with `LoadedMaps::preload_progress`.
+    pub fn preload(self, cities: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.preload_requests(cities.into_iter().map(MapLoadRequest::new))
+    }
+
+    /// Start loading the given requests at startup, see [`Self::preload`]
+    pub fn preload_requests(mut self, requests: impl IntoIterator<Item = MapLoadRequest>) -> Self {
+        // Preloaded grids are not attached to an entity until requested
+        self.preload_requests
+            .extend(requests.into_iter().map(|mut request| {
+                request.target_entity = None;
+                request
+            }));
+        self
+    }
+
+    /// Merge auto-rendered tiles into one mesh per tile type and `chunk_size`×`chunk_size` chunk
+    ///
+    /// Cuts entity and draw-call counts drastically for large grids, at the
+    /// cost of per-tile `RenderedTile` entities.
+    pub fn with_chunked_rendering(mut self, chunk_size: usize) -> Self {
+        self.render_settings.mode = TileRenderMode::Chunked { chunk_size };
+        self
+    }
+
+    /// Spawn `OsmPoi` entities for the points of interest of maps loaded onto a target entity
+    pub fn with_poi_markers(mut self) -> Self {
+        self.render_settings.spawn_pois = true;
+        self
+    }
 }
 
 impl Default for OsmTilesPlugin {
@@ -47,29 +127,63 @@ impl Default for OsmTilesPlugin {
 
 impl Plugin for OsmTilesPlugin {
     fn build(&self, app: &mut App) {
+        let mut loaded_maps = LoadedMaps::default();
+        for request in &self.preload_requests {
+            loaded_maps.mark_preloading(request);
+        }
+
         app
             // Resources
             .insert_resource(MapLoadQueue {
-                pending: std::collections::VecDeque::new(),
+                pending: self.preload_requests.iter().cloned().collect(),
                 active: std::collections::HashMap::new(),
                 max_concurrent: self.max_concurrent_loads,
+                default_deadline: self.load_deadline,
             })
             .insert_resource(OsmProviderRegistry {
                 providers: std::collections::HashMap::new(),
                 default_provider: self.default_provider.clone(),
             })
+            .insert_resource(loaded_maps)
+            .insert_resource(self.load_retries.clone())
+            .init_resource::<MapLoadState>()
+            .insert_resource(self.render_settings.clone())
+            .init_resource::<TileAssetCache>()
             // Messages (buffered events)
             .add_message::<LoadMapMessage>()
+            .add_message::<CancelMapLoadMessage>()
+            .add_message::<MapLoadCancelledMessage>()
             .add_message::<MapLoadedMessage>()
             .add_message::<MapLoadFailedMessage>()
+            .add_message::<MapLoadRetryMessage>()
             .add_message::<MapLoadProgressMessage>()
+            .add_message::<CityDisambiguationMessage>()
+            .add_message::<ReloadRegionMessage>()
+            .add_message::<MapRegionUpdatedMessage>()
+            .add_message::<TileClickedMessage>()
             // Systems
             .add_systems(
                 Update,
                 (
                     handle_load_requests,
+                    requeue_load_retries.before(process_loading_tasks),
+                    cancel_map_loads
+                        .after(handle_load_requests)
+                        .before(process_loading_tasks),
                     process_loading_tasks,
+                    report_retry_status,
+                    report_loading_stages,
+                    report_city_candidates,
                     handle_completed_tasks,
+                    start_region_reloads,
+                    handle_completed_region_reloads,
+                    attach_world_mapping,
+                    render_loaded_maps,
+                    spawn_loaded_pois,
+                    pick_clicked_tiles,
+                    update_map_load_state,
+                    update_map_status_ui,
+                    update_map_error_toast,
                 ),
             )
             // Setup
@@ -0,0 +1,90 @@
+use super::{GridWorldMapping, MapTiles, TileRenderSettings};
+use crate::{Poi, TileGrid};
+use bevy::prelude::*;
+
+/// Component marking an entity spawned for one point of interest of a map
+#[derive(Component, Debug, Clone)]
+pub struct OsmPoi {
+    /// Map entity the POI belongs to
+    pub map: Entity,
+    /// The point of interest, including its name, category and OSM tags
+    pub poi: Poi,
+}
+
+impl OsmPoi {
+    /// Get the POI name, if tagged
+    pub fn name(&self) -> Option<&str> {
+        self.poi.name.as_deref()
+    }
+
+    /// Get the POI category (e.g., "amenity")
+    pub fn category(&self) -> &str {
+        &self.poi.category
+    }
+
+    /// Get the POI kind (e.g., "cafe")
+    pub fn kind(&self) -> &str {
+        &self.poi.kind
+    }
+}
+
+/// Spawn one `OsmPoi` entity per point of interest as children of `parent`
+///
+/// Each POI sits on top of the tile containing it, using the same layout as
+/// the tile renderer. Named POIs also get a `Name`. Returns the number of
+/// spawned entities.
+pub fn spawn_poi_markers(
+    commands: &mut Commands,
+    parent: Entity,
+    grid: &TileGrid,
+    tile_size: f32,
+) -> usize {
+    let layout = GridWorldMapping::new(grid, tile_size);
+
+    for poi in grid.pois.iter() {
+        let (x, y) = poi.grid_pos;
+        let height = grid
+            .get_tile(x, y)
+            .map_or(0.0, |tile| tile.tile_type.default_height());
+        let center = layout.grid_to_local(x, y);
+
+        let mut entity = commands.spawn((
+            OsmPoi {
+                map: parent,
+                poi: poi.clone(),
+            },
+            Transform::from_xyz(center.x, height, center.z),
+            Visibility::default(),
+            ChildOf(parent),
+        ));
+        if let Some(name) = &poi.name {
+            entity.insert(Name::new(name.clone()));
+        }
+    }
+
+    grid.pois.len()
+}
+
+/// System spawning POI entities as soon as a map is attached to its target entity
+pub fn spawn_loaded_pois(
+    mut commands: Commands,
+    settings: Res<TileRenderSettings>,
+    loaded_maps: Query<(Entity, &MapTiles), Added<MapTiles>>,
+) {
+    if !settings.spawn_pois {
+        return;
+    }
+
+    for (entity, map) in &loaded_maps {
+        // POI transforms are relative to the map entity
+        commands
+            .entity(entity)
+            .insert_if_new((Transform::default(), Visibility::default()));
+
+        let spawned = spawn_poi_markers(&mut commands, entity, &map.grid, settings.tile_size);
+        debug!(
+            "Spawned {} POI entities for {}",
+            spawned, map.request.city_name
+        );
+    }
+}
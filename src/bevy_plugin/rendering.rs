@@ -0,0 +1,429 @@
+use super::{GridWorldMapping, MapTiles};
+use crate::{TileGrid, TilePalette, TileType};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Visual variant a tile material is rendered in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TileStyle {
+    /// Regular appearance
+    #[default]
+    Standard,
+    /// Emissive variant for hovered or selected tiles
+    Highlighted,
+    /// Semi-transparent variant for previews and overlays
+    Translucent,
+}
+
+/// Key identifying a cached tile material
+pub type TileMaterialKey = (TileType, TileStyle);
+
+/// Resource caching tile materials and meshes so identical tiles share GPU assets
+///
+/// The built-in renderer uses this cache, and custom spawners can use it too
+/// instead of allocating a new material or mesh per tile.
+#[derive(Resource, Default)]
+pub struct TileAssetCache {
+    materials: HashMap<TileMaterialKey, Handle<StandardMaterial>>,
+    meshes: HashMap<(u32, u32), Handle<Mesh>>,
+}
+
+impl TileAssetCache {
+    /// Get the shared default material for a tile type and style
+    pub fn material(
+        &mut self,
+        tile_type: &TileType,
+        style: TileStyle,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        self.material_with(tile_type, style, materials, || {
+            default_tile_material(tile_type, style)
+        })
+    }
+
+    /// Get the shared material for a tile type and style, creating it with `create` on first use
+    pub fn material_with(
+        &mut self,
+        tile_type: &TileType,
+        style: TileStyle,
+        materials: &mut Assets<StandardMaterial>,
+        create: impl FnOnce() -> StandardMaterial,
+    ) -> Handle<StandardMaterial> {
+        self.materials
+            .entry((tile_type.clone(), style))
+            .or_insert_with(|| materials.add(create()))
+            .clone()
+    }
+
+    /// Get a shared cuboid mesh with a square footprint of `tile_size` and the given height
+    pub fn mesh(&mut self, tile_size: f32, height: f32, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        self.meshes
+            .entry((tile_size.to_bits(), height.to_bits()))
+            .or_insert_with(|| meshes.add(Cuboid::new(tile_size, height, tile_size)))
+            .clone()
+    }
+
+    /// Get the shared mesh for a tile type, using its default height
+    pub fn tile_mesh(
+        &mut self,
+        tile_type: &TileType,
+        tile_size: f32,
+        meshes: &mut Assets<Mesh>,
+    ) -> Handle<Mesh> {
+        self.mesh(tile_size, tile_type.default_height(), meshes)
+    }
+
+    /// Get the number of cached materials
+    pub fn material_count(&self) -> usize {
+        self.materials.len()
+    }
+
+    /// Get the number of cached meshes
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.len()
+    }
+
+    /// Drop all cached handles, releasing assets no longer referenced elsewhere
+    pub fn clear(&mut self) {
+        self.materials.clear();
+        self.meshes.clear();
+    }
+}
+
+/// Create the default material for a tile type and style
+pub fn default_tile_material(tile_type: &TileType, style: TileStyle) -> StandardMaterial {
+    palette_tile_material(tile_type, style, &TilePalette::default())
+}
+
+/// Create the material for a tile type and style using the colors of `palette`
+pub fn palette_tile_material(
+    tile_type: &TileType,
+    style: TileStyle,
+    palette: &TilePalette,
+) -> StandardMaterial {
+    let (r, g, b) = palette.color(tile_type);
+    let base_color = Color::srgb_u8(r, g, b);
+
+    let mut material = StandardMaterial {
+        base_color,
+        metallic: match tile_type {
+            TileType::Water => 0.8,
+            TileType::Building | TileType::Commercial | TileType::Industrial => 0.2,
+            _ => 0.1,
+        },
+        perceptual_roughness: match tile_type {
+            TileType::Water => 0.1,
+            _ => 0.8,
+        },
+        ..default()
+    };
+
+    match style {
+        TileStyle::Standard => {}
+        TileStyle::Highlighted => {
+            material.emissive = LinearRgba::from(base_color) * 0.5;
+        }
+        TileStyle::Translucent => {
+            material.base_color = base_color.with_alpha(0.5);
+            material.alpha_mode = AlphaMode::Blend;
+        }
+    }
+
+    material
+}
+
+/// How the built-in renderer turns tiles into entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileRenderMode {
+    /// One entity per non-empty tile, carrying a `RenderedTile`
+    #[default]
+    PerTile,
+    /// One merged mesh entity per tile type within each square chunk of tiles
+    ///
+    /// Adjacent tiles of the same type are merged into single boxes, so
+    /// large grids need a few thousand entities instead of millions.
+    Chunked { chunk_size: usize },
+}
+
+/// Resource configuring the built-in tile renderer
+#[derive(Resource, Debug, Clone)]
+pub struct TileRenderSettings {
+    /// Spawn tile meshes automatically when a map is attached to an entity
+    pub auto_render: bool,
+    /// Size of a tile in world units
+    pub tile_size: f32,
+    /// Whether tiles are spawned individually or merged per chunk
+    pub mode: TileRenderMode,
+    /// Spawn `OsmPoi` entities for the points of interest of loaded maps
+    pub spawn_pois: bool,
+}
+
+impl Default for TileRenderSettings {
+    fn default() -> Self {
+        Self {
+            auto_render: false,
+            tile_size: 2.0,
+            mode: TileRenderMode::PerTile,
+            spawn_pois: false,
+        }
+    }
+}
+
+/// Component marking an entity spawned by the built-in renderer for one grid tile
+#[derive(Component, Debug, Clone)]
+pub struct RenderedTile {
+    pub tile_type: TileType,
+    pub grid_pos: (usize, usize),
+}
+
+/// Component marking a merged mesh spawned by the chunked renderer
+#[derive(Component, Debug, Clone)]
+pub struct RenderedChunk {
+    pub tile_type: TileType,
+    /// Chunk coordinates, i.e. the grid position of the chunk's first tile divided by the chunk size
+    pub chunk: (usize, usize),
+    /// Number of tiles merged into the mesh
+    pub tile_count: usize,
+}
+
+/// Spawn one mesh entity per non-empty tile as children of `parent`
+///
+/// The grid is centered on the parent with `x` along the X axis and `y` along
+/// the Z axis. Returns the number of spawned tiles.
+pub fn spawn_tile_grid(
+    commands: &mut Commands,
+    parent: Entity,
+    grid: &TileGrid,
+    tile_size: f32,
+    cache: &mut TileAssetCache,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) -> usize {
+    let layout = GridWorldMapping::new(grid, tile_size);
+    let mut spawned = 0;
+
+    for (x, y, tile) in grid.iter_tiles() {
+        if tile.tile_type == TileType::Empty {
+            continue;
+        }
+
+        let height = tile.tile_type.default_height();
+        let center = layout.grid_to_local(x, y);
+
+        commands.spawn((
+            Mesh3d(cache.tile_mesh(&tile.tile_type, tile_size, meshes)),
+            MeshMaterial3d(cache.material(&tile.tile_type, TileStyle::Standard, materials)),
+            Transform::from_xyz(center.x, height / 2.0, center.z),
+            RenderedTile {
+                tile_type: tile.tile_type.clone(),
+                grid_pos: (x, y),
+            },
+            ChildOf(parent),
+        ));
+        spawned += 1;
+    }
+
+    spawned
+}
+
+/// Spawn one merged mesh entity per tile type and chunk as children of `parent`
+///
+/// Within each `chunk_size`×`chunk_size` chunk, runs of same-type tiles are
+/// greedily merged into rectangles, each rendered as a single box. Meshes are
+/// specific to the grid and not shared through the cache; materials are.
+/// Returns the number of spawned chunk meshes.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_chunked_tile_grid(
+    commands: &mut Commands,
+    parent: Entity,
+    grid: &TileGrid,
+    tile_size: f32,
+    chunk_size: usize,
+    cache: &mut TileAssetCache,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) -> usize {
+    let layout = GridWorldMapping::new(grid, tile_size);
+    let (width, height) = grid.dimensions();
+    let chunk_size = chunk_size.max(1);
+    let mut spawned = 0;
+
+    for chunk_y in (0..height).step_by(chunk_size) {
+        for chunk_x in (0..width).step_by(chunk_size) {
+            let (end_x, end_y) = (
+                (chunk_x + chunk_size).min(width),
+                (chunk_y + chunk_size).min(height),
+            );
+            for (tile_type, rects) in merge_tile_rects(grid, chunk_x..end_x, chunk_y..end_y) {
+                let box_height = tile_type.default_height();
+                let mut mesh: Option<Mesh> = None;
+                let mut tile_count = 0;
+
+                for (x, y, w, h) in rects {
+                    // Averaging opposite corner tiles handles every grid origin
+                    let center = (layout.grid_to_local(x, y)
+                        + layout.grid_to_local(x + w - 1, y + h - 1))
+                        / 2.0;
+                    let part = Mesh::from(Cuboid::new(
+                        w as f32 * tile_size,
+                        box_height,
+                        h as f32 * tile_size,
+                    ))
+                    .translated_by(center.with_y(box_height / 2.0));
+                    match &mut mesh {
+                        Some(mesh) => {
+                            if let Err(e) = mesh.merge(&part) {
+                                warn!("Failed to merge tile mesh: {}", e);
+                            }
+                        }
+                        None => mesh = Some(part),
+                    }
+                    tile_count += w * h;
+                }
+
+                let Some(mesh) = mesh else {
+                    continue;
+                };
+                commands.spawn((
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(cache.material(&tile_type, TileStyle::Standard, materials)),
+                    Transform::default(),
+                    RenderedChunk {
+                        tile_type,
+                        chunk: (chunk_x / chunk_size, chunk_y / chunk_size),
+                        tile_count,
+                    },
+                    ChildOf(parent),
+                ));
+                spawned += 1;
+            }
+        }
+    }
+
+    spawned
+}
+
+/// Greedily cover the non-empty tiles of a grid region with same-type rectangles
+///
+/// Rectangles are (x, y, width, height) in grid coordinates, grown along x
+/// first and then along y.
+fn merge_tile_rects(
+    grid: &TileGrid,
+    xs: std::ops::Range<usize>,
+    ys: std::ops::Range<usize>,
+) -> HashMap<TileType, Vec<(usize, usize, usize, usize)>> {
+    let (region_width, region_height) = (xs.len(), ys.len());
+    let mut covered = vec![false; region_width * region_height];
+    let mut rects: HashMap<TileType, Vec<_>> = HashMap::new();
+    let tile_type_at = |x: usize, y: usize| {
+        grid.get_tile(xs.start + x, ys.start + y)
+            .map(|tile| &tile.tile_type)
+            .filter(|tile_type| **tile_type != TileType::Empty)
+    };
+
+    for y in 0..region_height {
+        for x in 0..region_width {
+            if covered[y * region_width + x] {
+                continue;
+            }
+            let Some(tile_type) = tile_type_at(x, y) else {
+                continue;
+            };
+            let extends = |x: usize, y: usize, covered: &[bool]| {
+                !covered[y * region_width + x] && tile_type_at(x, y) == Some(tile_type)
+            };
+
+            let mut w = 1;
+            while x + w < region_width && extends(x + w, y, &covered) {
+                w += 1;
+            }
+            let mut h = 1;
+            while y + h < region_height && (x..x + w).all(|x| extends(x, y + h, &covered)) {
+                h += 1;
+            }
+
+            for row in y..y + h {
+                covered[row * region_width + x..row * region_width + x + w].fill(true);
+            }
+            rects
+                .entry(tile_type.clone())
+                .or_default()
+                .push((xs.start + x, ys.start + y, w, h));
+        }
+    }
+
+    rects
+}
+
+/// System keeping the `GridWorldMapping` of map entities in sync with their grid
+///
+/// Runs whether or not auto-rendering is enabled, so games spawning their
+/// own meshes can place them with the same mapping.
+pub fn attach_world_mapping(
+    mut commands: Commands,
+    settings: Res<TileRenderSettings>,
+    loaded_maps: Query<(Entity, &MapTiles), Changed<MapTiles>>,
+) {
+    for (entity, map) in &loaded_maps {
+        commands
+            .entity(entity)
+            .insert(GridWorldMapping::new(&map.grid, settings.tile_size));
+    }
+}
+
+/// System rendering maps as soon as they are attached to their target entity
+pub fn render_loaded_maps(
+    mut commands: Commands,
+    settings: Res<TileRenderSettings>,
+    mut cache: ResMut<TileAssetCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    loaded_maps: Query<(Entity, &MapTiles), Added<MapTiles>>,
+) {
+    if !settings.auto_render {
+        return;
+    }
+
+    for (entity, map) in &loaded_maps {
+        // Tile transforms are relative to the map entity
+        commands
+            .entity(entity)
+            .insert_if_new((Transform::default(), Visibility::default()));
+
+        match settings.mode {
+            TileRenderMode::PerTile => {
+                let spawned = spawn_tile_grid(
+                    &mut commands,
+                    entity,
+                    &map.grid,
+                    settings.tile_size,
+                    &mut cache,
+                    &mut meshes,
+                    &mut materials,
+                );
+                debug!(
+                    "Rendered {} tiles for {} using {} shared materials",
+                    spawned,
+                    map.request.city_name,
+                    cache.material_count()
+                );
+            }
+            TileRenderMode::Chunked { chunk_size } => {
+                let spawned = spawn_chunked_tile_grid(
+                    &mut commands,
+                    entity,
+                    &map.grid,
+                    settings.tile_size,
+                    chunk_size,
+                    &mut cache,
+                    &mut meshes,
+                    &mut materials,
+                );
+                debug!(
+                    "Rendered {} as {} chunk meshes of {}×{} tiles",
+                    map.request.city_name, spawned, chunk_size, chunk_size
+                );
+            }
+        }
+    }
+}
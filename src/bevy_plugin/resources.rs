@@ -1,7 +1,7 @@
 use super::MapLoadRequest;
-use crate::{OsmDataProvider, ProviderFactory};
+use crate::{OsmDataProvider, OsmTilesError, ProviderRegistry, RetryPolicy, RetryStatus, TileGrid};
 use bevy::prelude::*;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Resource managing the map loading queue
 #[derive(Resource)]
@@ -9,6 +9,8 @@ pub struct MapLoadQueue {
     pub pending: VecDeque<MapLoadRequest>,
     pub active: HashMap<String, Entity>, // city_name -> entity with LoadingTask
     pub max_concurrent: usize,
+    /// Deadline applied to requests that do not set their own
+    pub default_deadline: Option<std::time::Duration>,
 }
 
 /// Resource managing available OSM data providers
@@ -33,14 +35,195 @@ impl OsmProviderRegistry {
     }
 }
 
-/// Setup the provider registry with default providers
+/// Resource caching the grids of completed loads, including preloaded regions
+///
+/// Requests whose [`MapLoadRequest::cache_key`] is cached are answered on the
+/// next frame without fetching again. Grids stay cached until removed.
+#[derive(Resource, Default)]
+pub struct LoadedMaps {
+    grids: HashMap<String, TileGrid>,
+    /// Keys of preload requests that have not finished yet
+    preloading: HashSet<String>,
+    /// Number of preload requests issued
+    preload_total: usize,
+}
+
+impl LoadedMaps {
+    /// Get the cached grid for a request
+    pub fn get(&self, request: &MapLoadRequest) -> Option<&TileGrid> {
+        self.grids.get(&request.cache_key())
+    }
+
+    /// Check whether a grid is cached for a request
+    pub fn contains(&self, request: &MapLoadRequest) -> bool {
+        self.grids.contains_key(&request.cache_key())
+    }
+
+    /// Cache the grid generated for a request
+    pub fn insert(&mut self, request: &MapLoadRequest, grid: TileGrid) {
+        let key = request.cache_key();
+        self.preloading.remove(&key);
+        self.grids.insert(key, grid);
+    }
+
+    /// Remove the cached grid for a request
+    pub fn remove(&mut self, request: &MapLoadRequest) -> Option<TileGrid> {
+        self.grids.remove(&request.cache_key())
+    }
+
+    /// Drop all cached grids
+    pub fn clear(&mut self) {
+        self.grids.clear();
+    }
+
+    /// Get the number of cached grids
+    pub fn len(&self) -> usize {
+        self.grids.len()
+    }
+
+    /// Check whether no grids are cached
+    pub fn is_empty(&self) -> bool {
+        self.grids.is_empty()
+    }
+
+    /// Record a request as being preloaded
+    pub fn mark_preloading(&mut self, request: &MapLoadRequest) {
+        if self.preloading.insert(request.cache_key()) {
+            self.preload_total += 1;
+        }
+    }
+
+    /// Mark a request as finished without a grid (e.g., after a failed load)
+    pub fn mark_failed(&mut self, request: &MapLoadRequest) {
+        self.preloading.remove(&request.cache_key());
+    }
+
+    /// Check whether preload requests are still running
+    pub fn is_preloading(&self) -> bool {
+        !self.preloading.is_empty()
+    }
+
+    /// Get the share of finished preload requests, from 0.0 to 1.0
+    pub fn preload_progress(&self) -> f32 {
+        if self.preload_total == 0 {
+            return 1.0;
+        }
+        1.0 - self.preloading.len() as f32 / self.preload_total as f32
+    }
+}
+
+/// Resource retrying failed loads, configured with `OsmTilesPlugin::with_load_retries`
+///
+/// Providers already retry individual requests; this restarts the whole
+/// load, e.g. after Overpass stayed overloaded for longer than the provider
+/// retries. A `MapLoadRetryMessage` reports each scheduled attempt, and
+/// `MapLoadFailedMessage` is only sent once no attempt is left.
+#[derive(Resource, Debug, Clone)]
+pub struct MapLoadRetries {
+    /// Number of attempts and backoff between them
+    pub policy: RetryPolicy,
+    /// Only retry errors for which [`OsmTilesError::is_retryable`] holds
+    pub retryable_only: bool,
+    /// Failed attempts per request cache key
+    attempts: HashMap<String, u32>,
+    /// Requests waiting for their retry, with the real time they are due at
+    scheduled: Vec<(f64, MapLoadRequest)>,
+}
+
+impl Default for MapLoadRetries {
+    fn default() -> Self {
+        Self::new(RetryPolicy::none())
+    }
+}
+
+impl MapLoadRetries {
+    /// Retry failed loads according to `policy`
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            retryable_only: true,
+            attempts: HashMap::new(),
+            scheduled: Vec::new(),
+        }
+    }
+
+    /// Schedule another attempt of a failed request, if the policy allows one
+    ///
+    /// `now` is the real time in seconds since startup.
+    pub fn schedule(
+        &mut self,
+        request: &MapLoadRequest,
+        error: &OsmTilesError,
+        now: f64,
+    ) -> Option<RetryStatus> {
+        let key = request.cache_key();
+        let attempt = self.attempts.get(&key).copied().unwrap_or(0) + 1;
+        let retry =
+            attempt < self.policy.max_attempts && (!self.retryable_only || error.is_retryable());
+        // Servers asking for a longer wait than the policy allows end the load
+        let Some(delay_ms) = self.policy.retry_delay(attempt, error).filter(|_| retry) else {
+            self.attempts.remove(&key);
+            return None;
+        };
+        self.attempts.insert(key, attempt);
+        self.scheduled
+            .push((now + delay_ms as f64 / 1000.0, request.clone()));
+        Some(RetryStatus {
+            attempt,
+            max_attempts: self.policy.max_attempts,
+            next_retry_in_ms: delay_ms,
+            error: error.to_string(),
+        })
+    }
+
+    /// Forget the failed attempts of a request that finished
+    pub fn succeeded(&mut self, request: &MapLoadRequest) {
+        self.attempts.remove(&request.cache_key());
+    }
+
+    /// Take the requests whose retry is due at `now`
+    pub fn take_due(&mut self, now: f64) -> Vec<MapLoadRequest> {
+        let (due, waiting) = std::mem::take(&mut self.scheduled)
+            .into_iter()
+            .partition(|(at, _)| *at <= now);
+        self.scheduled = waiting;
+        due.into_iter().map(|(_, request)| request).collect()
+    }
+
+    /// Drop scheduled retries matching `cancel`, returning them
+    pub fn cancel(
+        &mut self,
+        mut cancel: impl FnMut(&MapLoadRequest) -> bool,
+    ) -> Vec<MapLoadRequest> {
+        let (cancelled, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.scheduled)
+            .into_iter()
+            .partition(|(_, request)| cancel(request));
+        self.scheduled = waiting;
+        cancelled
+            .into_iter()
+            .map(|(_, request)| {
+                self.attempts.remove(&request.cache_key());
+                request
+            })
+            .collect()
+    }
+
+    /// Get the number of requests waiting for a retry
+    pub fn scheduled(&self) -> usize {
+        self.scheduled.len()
+    }
+}
+
+/// Setup the provider registry with every provider of the [`ProviderRegistry`]
+///
+/// Providers registered before the app starts can be requested by name.
 pub fn setup_providers(mut registry: ResMut<OsmProviderRegistry>) {
-    // Add available providers
-    registry.providers.insert(
-        "overpass".to_string(),
-        Box::new(ProviderFactory::overpass()),
-    );
-    registry
-        .providers
-        .insert("mock".to_string(), Box::new(ProviderFactory::mock()));
+    for name in ProviderRegistry::names() {
+        match ProviderRegistry::create(&name) {
+            Ok(provider) => {
+                registry.providers.insert(name, provider);
+            }
+            Err(error) => warn!("Skipping provider '{}': {}", name, error),
+        }
+    }
 }
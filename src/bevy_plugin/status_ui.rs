@@ -0,0 +1,166 @@
+use super::{MapLoadFailedMessage, MapLoadProgressMessage, MapLoadQueue};
+use bevy::prelude::*;
+
+/// How long the error toast stays visible (in seconds)
+const TOAST_SECONDS: f32 = 6.0;
+
+/// Component marking the loading status panel spawned by [`spawn_map_status_ui`]
+///
+/// The panel is visible while maps are loading and hidden otherwise.
+#[derive(Component, Debug, Default)]
+pub struct MapStatusUi;
+
+/// Component marking the stage text of the loading status panel
+#[derive(Component, Debug, Default)]
+pub struct MapStatusText;
+
+/// Component marking the filled part of the loading progress bar
+#[derive(Component, Debug, Default)]
+pub struct MapStatusProgressBar;
+
+/// Component marking the toast showing the most recent loading error
+#[derive(Component, Debug)]
+pub struct MapErrorToast {
+    /// Time left until the toast hides again
+    timer: Timer,
+}
+
+/// Startup system spawning a loading status panel and an error toast
+///
+/// Add it with `app.add_systems(Startup, spawn_map_status_ui)`; the plugin
+/// keeps both up to date from its loading messages. The panel sits in the
+/// bottom-left corner and the toast in the top-right corner.
+pub fn spawn_map_status_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            MapStatusUi,
+            Name::new("Map status"),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(16.0),
+                bottom: Val::Px(16.0),
+                width: Val::Px(320.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(6.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            Visibility::Hidden,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                MapStatusText,
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            panel
+                .spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(8.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.25, 0.25, 0.25)),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        MapStatusProgressBar,
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.7, 0.3)),
+                    ));
+                });
+        });
+
+    commands.spawn((
+        MapErrorToast {
+            timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+        },
+        Name::new("Map error toast"),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(16.0),
+            top: Val::Px(16.0),
+            max_width: Val::Px(400.0),
+            padding: UiRect::all(Val::Px(10.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.6, 0.1, 0.1, 0.9)),
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+/// System updating the stage text and progress bar of the loading status panel
+pub fn update_map_status_ui(
+    mut progress_reader: MessageReader<MapLoadProgressMessage>,
+    queue: Res<MapLoadQueue>,
+    mut panels: Query<&mut Visibility, With<MapStatusUi>>,
+    mut texts: Query<&mut Text, With<MapStatusText>>,
+    mut bars: Query<&mut Node, With<MapStatusProgressBar>>,
+) {
+    let loading = !queue.active.is_empty() || !queue.pending.is_empty();
+    for mut visibility in &mut panels {
+        visibility.set_if_neq(if loading {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    // Several maps may load at once; the most recent report wins
+    let Some(message) = progress_reader.read().last() else {
+        return;
+    };
+
+    for mut text in &mut texts {
+        **text = match &message.retry {
+            Some(retry) => format!("{}: {}", message.request.city_name, retry),
+            None => format!(
+                "{}: {}... ({:.0}%)",
+                message.request.city_name,
+                message.stage.label(),
+                message.progress * 100.0
+            ),
+        };
+    }
+    for mut bar in &mut bars {
+        bar.width = Val::Percent(message.progress.clamp(0.0, 1.0) * 100.0);
+    }
+}
+
+/// System showing loading errors in the error toast and hiding it after a few seconds
+pub fn update_map_error_toast(
+    time: Res<Time>,
+    mut failed_reader: MessageReader<MapLoadFailedMessage>,
+    mut toasts: Query<(&mut MapErrorToast, &mut Text, &mut Visibility)>,
+) {
+    let failure = failed_reader.read().last();
+
+    for (mut toast, mut text, mut visibility) in &mut toasts {
+        if let Some(message) = failure {
+            **text = format!(
+                "Failed to load {}: {}",
+                message.request.city_name, message.error
+            );
+            toast.timer.reset();
+            *visibility = Visibility::Inherited;
+        } else if *visibility != Visibility::Hidden && toast.timer.tick(time.delta()).is_finished()
+        {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
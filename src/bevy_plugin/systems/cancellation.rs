@@ -0,0 +1,99 @@
+use super::super::resources::{LoadedMaps, MapLoadQueue, MapLoadRetries};
+use super::super::{
+    CancelMapLoadMessage, CancelReason, LoadingTask, MapLoadCancelledMessage, MapLoadFailedMessage,
+    MapLoadRequest, MapLoading,
+};
+use bevy::{ecs::entity::Entities, prelude::*};
+
+/// System cancelling loads that were asked to stop, lost their target entity or ran past their deadline
+///
+/// Cancelled tasks are dropped, which stops them at their next await point,
+/// so a late result never touches a despawned entity.
+#[allow(clippy::too_many_arguments)]
+pub fn cancel_map_loads(
+    mut cancel_reader: MessageReader<CancelMapLoadMessage>,
+    mut queue: ResMut<MapLoadQueue>,
+    mut retries: ResMut<MapLoadRetries>,
+    mut loaded_maps: ResMut<LoadedMaps>,
+    loading_tasks: Query<(Entity, &LoadingTask)>,
+    entities: &Entities,
+    time: Res<Time<Real>>,
+    mut cancelled_events: MessageWriter<MapLoadCancelledMessage>,
+    mut failed_events: MessageWriter<MapLoadFailedMessage>,
+    mut commands: Commands,
+) {
+    let cancellations: Vec<CancelMapLoadMessage> = cancel_reader.read().cloned().collect();
+    let cancel_reason = |request: &MapLoadRequest| {
+        if cancellations.iter().any(|cancel| cancel.matches(request)) {
+            Some(CancelReason::Requested)
+        } else if request
+            .target_entity
+            .is_some_and(|entity| !entities.contains(entity))
+        {
+            Some(CancelReason::TargetDespawned)
+        } else {
+            None
+        }
+    };
+
+    let mut cancelled = Vec::new();
+    queue
+        .pending
+        .retain(|request| match cancel_reason(request) {
+            Some(reason) => {
+                cancelled.push((request.clone(), reason));
+                false
+            }
+            None => true,
+        });
+    for request in retries.cancel(|request| cancel_reason(request).is_some()) {
+        let reason = cancel_reason(&request).unwrap_or(CancelReason::Requested);
+        cancelled.push((request, reason));
+    }
+
+    let now = time.elapsed_secs_f64();
+    for (task_entity, loading_task) in &loading_tasks {
+        let request = &loading_task.request;
+        if let Some(reason) = cancel_reason(request) {
+            cancelled.push((request.clone(), reason));
+        } else if loading_task
+            .deadline
+            .is_some_and(|deadline| now >= deadline)
+        {
+            release_request(request, &mut loaded_maps, &mut commands);
+            failed_events.write(MapLoadFailedMessage {
+                request: request.clone(),
+                error: format!(
+                    "Loading {} did not finish within its deadline",
+                    request.city_name
+                ),
+            });
+        } else {
+            continue;
+        }
+
+        loading_task.cancellation.cancel();
+        commands.entity(task_entity).despawn();
+        queue.active.remove(&request.city_name);
+    }
+
+    for (request, reason) in cancelled {
+        debug!("Cancelled loading {}: {:?}", request.city_name, reason);
+        release_request(&request, &mut loaded_maps, &mut commands);
+        cancelled_events.write(MapLoadCancelledMessage { request, reason });
+    }
+}
+
+/// Clear the loading state of an abandoned request
+fn release_request(
+    request: &MapLoadRequest,
+    loaded_maps: &mut LoadedMaps,
+    commands: &mut Commands,
+) {
+    loaded_maps.mark_failed(request);
+    if let Some(target_entity) = request.target_entity
+        && let Ok(mut target) = commands.get_entity(target_entity)
+    {
+        target.remove::<MapLoading>();
+    }
+}
@@ -1,7 +1,11 @@
+mod cancellation;
 mod completion_handler;
+mod region_reload;
 mod request_handler;
 mod task_processor;
 
+pub use cancellation::*;
 pub use completion_handler::*;
+pub use region_reload::*;
 pub use request_handler::*;
 pub use task_processor::*;
@@ -0,0 +1,133 @@
+use super::super::resources::{LoadedMaps, OsmProviderRegistry};
+use super::super::{
+    MapLoadFailedMessage, MapLoadRequest, MapRegionUpdatedMessage, MapTiles, RegionReloadTask,
+    ReloadRegionMessage,
+};
+use crate::{
+    BoundingBox, DefaultGridGenerator, GridGenerator, MergeStrategy, OsmConfigBuilder,
+    OsmDataProvider, ProviderFactory, RetryPolicy, RetryingProvider,
+};
+use bevy::{
+    ecs::{system::SystemState, world::CommandQueue},
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, block_on, futures_lite::future},
+};
+
+/// System to start partial reloads of loaded maps
+pub fn start_region_reloads(
+    mut reload_reader: MessageReader<ReloadRegionMessage>,
+    maps: Query<&MapTiles>,
+    registry: Res<OsmProviderRegistry>,
+    mut failed_events: MessageWriter<MapLoadFailedMessage>,
+    mut commands: Commands,
+) {
+    let thread_pool = AsyncComputeTaskPool::get();
+
+    for reload in reload_reader.read() {
+        let Ok(map) = maps.get(reload.entity) else {
+            warn!("Cannot reload region of {:?}: no map loaded", reload.entity);
+            continue;
+        };
+
+        // Snap the area to whole cells so the chunk lines up with the grid
+        let Some((cells, bbox)) = map
+            .grid
+            .cells_in(&reload.bbox)
+            .and_then(|cells| Some((cells, map.grid.rect_bounds(&cells)?)))
+        else {
+            failed_events.write(MapLoadFailedMessage {
+                request: map.request.clone(),
+                error: format!("Region {:?} is outside the loaded map", reload.bbox),
+            });
+            continue;
+        };
+
+        let entity = reload.entity;
+        let request = map.request.clone();
+        let provider_type = request
+            .provider_override
+            .clone()
+            .unwrap_or_else(|| registry.default_provider.clone());
+
+        let task = thread_pool.spawn(async move {
+            let result = load_region_async(&request, provider_type, &bbox).await;
+
+            let mut command_queue = CommandQueue::default();
+            command_queue.push(move |world: &mut World| {
+                let mut system_state = SystemState::<(
+                    MessageWriter<MapRegionUpdatedMessage>,
+                    MessageWriter<MapLoadFailedMessage>,
+                    Query<&mut MapTiles>,
+                    ResMut<LoadedMaps>,
+                )>::new(world);
+                let (mut updated_events, mut failed_events, mut maps, mut loaded_maps) =
+                    system_state.get_mut(world);
+
+                let chunk = match result {
+                    Ok(chunk) => chunk,
+                    Err(error) => {
+                        failed_events.write(MapLoadFailedMessage {
+                            request,
+                            error: error.to_string(),
+                        });
+                        return;
+                    }
+                };
+                // The map was despawned or reloaded for another city meanwhile
+                let Ok(mut map) = maps.get_mut(entity) else {
+                    return;
+                };
+                if map.request.cache_key() != request.cache_key() {
+                    return;
+                }
+
+                let tiles_changed = map.grid.merge(&chunk, &MergeStrategy::Replace);
+                loaded_maps.insert(&request, map.grid.clone());
+                updated_events.write(MapRegionUpdatedMessage {
+                    entity,
+                    bbox,
+                    cells,
+                    tiles_changed,
+                });
+            });
+
+            command_queue
+        });
+
+        commands.spawn(RegionReloadTask { entity, task });
+    }
+}
+
+/// System to apply finished partial reloads
+pub fn handle_completed_region_reloads(
+    mut commands: Commands,
+    mut reload_tasks: Query<(Entity, &mut RegionReloadTask)>,
+) {
+    for (entity, mut reload_task) in &mut reload_tasks {
+        if let Some(mut commands_queue) = block_on(future::poll_once(&mut reload_task.task)) {
+            commands.append(&mut commands_queue);
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Fetch and generate the grid of `bbox` with the features of the map's request
+async fn load_region_async(
+    request: &MapLoadRequest,
+    provider_type: String,
+    bbox: &BoundingBox,
+) -> crate::Result<crate::TileGrid> {
+    let provider = ProviderFactory::create_provider(&provider_type)?;
+    let provider = RetryingProvider::new(provider, RetryPolicy::default());
+
+    let config = OsmConfigBuilder::new()
+        .bbox(bbox.south, bbox.west, bbox.north, bbox.east)
+        .features(request.features.clone())
+        .grid_resolution(request.grid_resolution)
+        .build();
+
+    let osm_data = provider.fetch_data(&config).await?;
+    DefaultGridGenerator::new()
+        .generate_grid(&osm_data, &config)
+        .await
+}
@@ -1,4 +1,4 @@
-use super::super::resources::MapLoadQueue;
+use super::super::resources::{MapLoadQueue, MapLoadRetries};
 use super::super::{LoadMapMessage, LoadingStage, MapLoading};
 use bevy::prelude::*;
 
@@ -26,3 +26,15 @@ pub fn handle_load_requests(
         queue.pending.push_back(request);
     }
 }
+
+/// System queueing failed loads again once their retry is due
+pub fn requeue_load_retries(
+    mut retries: ResMut<MapLoadRetries>,
+    mut queue: ResMut<MapLoadQueue>,
+    time: Res<Time<Real>>,
+) {
+    for request in retries.take_due(time.elapsed_secs_f64()) {
+        debug!("Retrying load of {}", request.city_name);
+        queue.pending.push_back(request);
+    }
+}
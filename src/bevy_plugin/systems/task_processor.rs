@@ -1,24 +1,60 @@
-use super::super::resources::{MapLoadQueue, OsmProviderRegistry};
+use super::super::resources::{LoadedMaps, MapLoadQueue, MapLoadRetries, OsmProviderRegistry};
 use super::super::{
-    LoadingStage, LoadingTask, MapLoadFailedMessage, MapLoadProgressMessage, MapLoadedMessage,
-    MapLoading, MapTiles,
+    CancellationToken, CityDisambiguationMessage, LoadingStage, LoadingTask, MapLoadFailedMessage,
+    MapLoadProgressMessage, MapLoadRetryMessage, MapLoadedMessage, MapLoading, MapTiles,
+};
+use crate::{
+    DefaultGridGenerator, GeocodeCandidate, GridGenerator, OsmConfigBuilder, OsmDataProvider,
+    OsmTilesError, ProviderFactory, Region, RetryPolicy, RetryStatus, RetryingProvider,
 };
-use crate::{DefaultGridGenerator, GridGenerator, OsmConfigBuilder, ProviderFactory};
 use bevy::{
     ecs::{system::SystemState, world::CommandQueue},
     prelude::*,
     tasks::AsyncComputeTaskPool,
 };
+use std::sync::{Arc, Mutex};
 
 /// System to start new loading tasks using Bevy's AsyncComputeTaskPool
 pub fn process_loading_tasks(
     mut queue: ResMut<MapLoadQueue>,
     registry: Res<OsmProviderRegistry>,
+    loaded_maps: Res<LoadedMaps>,
     mut progress_events: MessageWriter<MapLoadProgressMessage>,
+    mut loaded_events: MessageWriter<MapLoadedMessage>,
+    time: Res<Time<Real>>,
     mut commands: Commands,
 ) {
     let thread_pool = AsyncComputeTaskPool::get();
 
+    // Answer cached requests right away, they need no loading slot
+    let mut pending = std::mem::take(&mut queue.pending);
+    pending.retain(|request| {
+        let Some(grid) = loaded_maps.get(request) else {
+            return true;
+        };
+        loaded_events.write(MapLoadedMessage {
+            request: request.clone(),
+            grid: grid.clone(),
+            entity: request.target_entity,
+        });
+        if let Some(target_entity) = request.target_entity {
+            commands
+                .entity(target_entity)
+                .remove::<MapLoading>()
+                .insert(MapTiles {
+                    grid: grid.clone(),
+                    request: request.clone(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    loaded_at: std::time::Instant::now(),
+                });
+        }
+        false
+    });
+    queue.pending = pending;
+
+    // Requests for a city that is still loading wait for its result in the cache
+    let mut waiting = Vec::new();
+
     // Start new tasks if we have capacity
     while queue.active.len() < queue.max_concurrent && !queue.pending.is_empty() {
         if let Some(request) = queue.pending.pop_front() {
@@ -26,6 +62,7 @@ pub fn process_loading_tasks(
 
             // Skip if already loading this city
             if queue.active.contains_key(&city_key) {
+                waiting.push(request);
                 continue;
             }
 
@@ -37,29 +74,69 @@ pub fn process_loading_tasks(
 
             let provider_type = provider_name.clone();
             let request_clone = request.clone();
+            let retry_status = Arc::new(Mutex::new(None));
+            let retry_slot = retry_status.clone();
+            let stage = Arc::new(Mutex::new(None));
+            let stage_slot = stage.clone();
+            let candidates = Arc::new(Mutex::new(None));
+            let candidates_slot = candidates.clone();
+            let cancellation = CancellationToken::new();
+            let task_cancellation = cancellation.clone();
+            let deadline = request
+                .deadline
+                .or(queue.default_deadline)
+                .map(|deadline| time.elapsed_secs_f64() + deadline.as_secs_f64());
 
             // Spawn new task on the AsyncComputeTaskPool - exactly like Bevy example
             let task = thread_pool.spawn(async move {
                 // Do the async work
-                let result = load_map_async(request_clone.clone(), provider_type).await;
+                let result = load_map_async(
+                    request_clone.clone(),
+                    provider_type,
+                    retry_slot,
+                    stage_slot,
+                    candidates_slot,
+                    task_cancellation.clone(),
+                )
+                .await;
 
                 let mut command_queue = CommandQueue::default();
 
                 // Use a raw command queue to pass results back to be applied in a deferred manner
                 command_queue.push(move |world: &mut World| {
+                    // The load was cancelled after the task finished but before this ran
+                    if task_cancellation.is_cancelled() {
+                        return;
+                    }
+
                     // Create a system state to access the ECS resources we need
                     let mut system_state = SystemState::<(
                         MessageWriter<MapLoadedMessage>,
                         MessageWriter<MapLoadFailedMessage>,
+                        MessageWriter<MapLoadRetryMessage>,
                         Query<&mut MapLoading>,
+                        ResMut<LoadedMaps>,
+                        ResMut<MapLoadRetries>,
+                        Res<Time<Real>>,
                         Commands,
                     )>::new(world);
 
-                    let (mut loaded_events, mut failed_events, mut loading_query, mut commands) =
-                        system_state.get_mut(world);
+                    let (
+                        mut loaded_events,
+                        mut failed_events,
+                        mut retry_events,
+                        mut loading_query,
+                        mut loaded_maps,
+                        mut retries,
+                        time,
+                        mut commands,
+                    ) = system_state.get_mut(world);
 
                     match result {
                         Ok(grid) => {
+                            retries.succeeded(&request_clone);
+                            loaded_maps.insert(&request_clone, grid.clone());
+
                             // Send loaded event
                             loaded_events.write(MapLoadedMessage {
                                 request: request_clone.clone(),
@@ -68,21 +145,34 @@ pub fn process_loading_tasks(
                             });
 
                             // Update entity if specified
-                            if let Some(target_entity) = request_clone.target_entity {
-                                if loading_query.get_mut(target_entity).is_ok() {
-                                    commands
-                                        .entity(target_entity)
-                                        .remove::<MapLoading>()
-                                        .insert(MapTiles {
-                                            grid,
-                                            request: request_clone,
-                                            #[cfg(not(target_arch = "wasm32"))]
-                                            loaded_at: std::time::Instant::now(),
-                                        });
-                                }
+                            if let Some(target_entity) = request_clone.target_entity
+                                && loading_query.get_mut(target_entity).is_ok()
+                            {
+                                commands
+                                    .entity(target_entity)
+                                    .remove::<MapLoading>()
+                                    .insert(MapTiles {
+                                        grid,
+                                        request: request_clone,
+                                        #[cfg(not(target_arch = "wasm32"))]
+                                        loaded_at: std::time::Instant::now(),
+                                    });
                             }
                         }
                         Err(error) => {
+                            // The target keeps its `MapLoading` while waiting for the retry
+                            if let Some(status) =
+                                retries.schedule(&request_clone, &error, time.elapsed_secs_f64())
+                            {
+                                warn!("Loading {} failed: {}", request_clone.city_name, status);
+                                retry_events.write(MapLoadRetryMessage {
+                                    request: request_clone,
+                                    status,
+                                });
+                                return;
+                            }
+                            loaded_maps.mark_failed(&request_clone);
+
                             // Send failed event
                             failed_events.write(MapLoadFailedMessage {
                                 request: request_clone.clone(),
@@ -90,8 +180,10 @@ pub fn process_loading_tasks(
                             });
 
                             // Remove loading component from entity
-                            if let Some(target_entity) = request_clone.target_entity {
-                                commands.entity(target_entity).remove::<MapLoading>();
+                            if let Some(target_entity) = request_clone.target_entity
+                                && let Ok(mut target) = commands.get_entity(target_entity)
+                            {
+                                target.remove::<MapLoading>();
                             }
                         }
                     }
@@ -105,6 +197,11 @@ pub fn process_loading_tasks(
                 .spawn(LoadingTask {
                     request: request.clone(),
                     task,
+                    retry_status,
+                    stage,
+                    candidates,
+                    cancellation,
+                    deadline,
                     #[cfg(not(target_arch = "wasm32"))]
                     started_at: std::time::Instant::now(),
                 })
@@ -115,35 +212,126 @@ pub fn process_loading_tasks(
             progress_events.write(MapLoadProgressMessage {
                 request,
                 stage: LoadingStage::ResolvingCity,
-                progress: 0.0,
+                progress: LoadingStage::ResolvingCity.progress(),
+                retry: None,
             });
         }
     }
+
+    queue.pending.extend(waiting);
+}
+
+/// System publishing retry status reported by running tasks as progress messages
+pub fn report_retry_status(
+    loading_tasks: Query<&LoadingTask>,
+    mut progress_events: MessageWriter<MapLoadProgressMessage>,
+) {
+    for loading_task in &loading_tasks {
+        let Some(status) = loading_task.retry_status.lock().unwrap().take() else {
+            continue;
+        };
+
+        progress_events.write(MapLoadProgressMessage {
+            request: loading_task.request.clone(),
+            stage: LoadingStage::FetchingData,
+            progress: LoadingStage::FetchingData.progress(),
+            retry: Some(status),
+        });
+    }
+}
+
+/// System publishing the stages entered by running tasks as progress messages
+pub fn report_loading_stages(
+    loading_tasks: Query<&LoadingTask>,
+    mut progress_events: MessageWriter<MapLoadProgressMessage>,
+) {
+    for loading_task in &loading_tasks {
+        let Some(stage) = loading_task.stage.lock().unwrap().take() else {
+            continue;
+        };
+
+        progress_events.write(MapLoadProgressMessage {
+            request: loading_task.request.clone(),
+            stage,
+            progress: stage.progress(),
+            retry: None,
+        });
+    }
+}
+
+/// System publishing the places an ambiguous city name of a running task matched
+pub fn report_city_candidates(
+    loading_tasks: Query<&LoadingTask>,
+    mut disambiguation_events: MessageWriter<CityDisambiguationMessage>,
+) {
+    for loading_task in &loading_tasks {
+        let Some(candidates) = loading_task.candidates.lock().unwrap().take() else {
+            continue;
+        };
+
+        disambiguation_events.write(CityDisambiguationMessage {
+            request: loading_task.request.clone(),
+            candidates,
+        });
+    }
 }
 
 /// Async loading function - uses only the providers that are already WASM-compatible
 async fn load_map_async(
     request: super::super::MapLoadRequest,
     provider_type: String,
+    retry_status: Arc<Mutex<Option<RetryStatus>>>,
+    stage: Arc<Mutex<Option<LoadingStage>>>,
+    candidates: Arc<Mutex<Option<Vec<GeocodeCandidate>>>>,
+    cancellation: CancellationToken,
 ) -> crate::Result<crate::TileGrid> {
-    // Create provider (this is cheap, providers are stateless)
-    let provider: Box<dyn crate::OsmDataProvider> = match provider_type.as_str() {
-        "overpass" => Box::new(ProviderFactory::overpass()),
-        "mock" => Box::new(ProviderFactory::mock()),
-        _ => Box::new(ProviderFactory::mock()), // fallback
+    let cancelled =
+        || OsmTilesError::Config(format!("Loading {} was cancelled", request.city_name));
+
+    // Create provider (this is cheap, providers are stateless); Overpass requests are
+    // aborted as soon as the load is cancelled
+    let provider: Box<dyn OsmDataProvider> = match provider_type.as_str() {
+        "overpass" => Box::new(ProviderFactory::overpass().with_cancellation(cancellation.clone())),
+        name => ProviderFactory::create_provider(name)?,
     };
+    let provider = RetryingProvider::new(provider, RetryPolicy::default())
+        .with_observer(move |status| *retry_status.lock().unwrap() = Some(status.clone()));
 
     // Build config from request
+    let region = match &request.bounding_box {
+        Some(bbox) => Region::BoundingBox(bbox.clone()),
+        None => Region::City {
+            name: request.city_name.trim().to_string(),
+        },
+    };
+
+    // Tell the UI about other places of the same name, the load goes on with the best match
+    if request.bounding_box.is_none() {
+        match provider.resolve_region_candidates(&region).await {
+            Ok(found) if found.len() > 1 => *candidates.lock().unwrap() = Some(found),
+            Ok(_) => {}
+            Err(error) => debug!(
+                "Could not look up places named {}: {}",
+                request.city_name, error
+            ),
+        }
+    }
+
     let config = OsmConfigBuilder::new()
-        .city(&request.city_name)
+        .region(region)
         .features(request.features)
         .grid_resolution(request.grid_resolution)
         .build();
 
+    *stage.lock().unwrap() = Some(LoadingStage::FetchingData);
     // Fetch OSM data - this uses reqwest with wasm features, which is WASM-compatible
     let osm_data = provider.fetch_data(&config).await?;
+    if cancellation.is_cancelled() {
+        return Err(cancelled());
+    }
 
     // Generate grid - this is pure computation
+    *stage.lock().unwrap() = Some(LoadingStage::GeneratingGrid);
     let generator = DefaultGridGenerator::new();
     let grid = generator.generate_grid(&osm_data, &config).await?;
 
@@ -0,0 +1,559 @@
+//! `osm-tiles`: fetch OpenStreetMap data and export tile grids from the command line
+//!
+//! Install with `cargo install bevy-osm-tiles --features cli,reqwest-client`.
+//! Every command prints a summary on stdout, as JSON with `--json`; logs go
+//! to stderr and follow `RUST_LOG`.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use serde_json::{Value, json};
+use tracing_subscriber::EnvFilter;
+
+use bevy_osm_tiles::{
+    BoundingBox, CachingProvider, CsvExporter, DefaultGridGenerator, ExportMetadata, FeatureSet,
+    GeoJsonExporter, GridExporter, OsmConfig, OsmConfigBuilder, OsmData, OsmTilesError,
+    PngExporter, ProviderFactory, Region, Result, TileGrid, data_hash,
+};
+
+#[derive(Parser)]
+#[command(name = "osm-tiles", version)]
+#[command(about = "Download OpenStreetMap data and export grid-based tile maps")]
+struct Cli {
+    /// Print a JSON summary on stdout instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Log debug output to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Directory of the download cache [default: ~/.cache/osm-tiles]
+    #[arg(long, global = true)]
+    cache_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Download OSM data and save it as JSON
+    Fetch {
+        #[command(flatten)]
+        fetch: FetchArgs,
+        /// File to write the data to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Generate a tile grid and save it as JSON
+    Grid {
+        #[command(flatten)]
+        source: SourceArgs,
+        /// File to write the grid to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Generate a tile grid and render it as a PNG image
+    RenderPng {
+        #[command(flatten)]
+        source: SourceArgs,
+        /// File to write the image to
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Pixels per tile
+        #[arg(long, default_value_t = 1)]
+        scale: u32,
+    },
+    /// Generate a tile grid and export it as GeoJSON
+    ExportGeojson {
+        #[command(flatten)]
+        source: SourceArgs,
+        /// File to write the GeoJSON to
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Include the OSM tags of each tile
+        #[arg(long)]
+        with_tags: bool,
+    },
+    /// Generate a tile grid and export it as CSV with one row per tile
+    ExportCsv {
+        #[command(flatten)]
+        source: SourceArgs,
+        /// File to write the CSV to
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Tag key to write as a column (repeatable) [default: name, highway, building, landuse, natural]
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Also write per-type statistics as CSV to this file
+        #[arg(long)]
+        statistics: Option<PathBuf>,
+    },
+    /// Manage the download cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Delete all cached downloads
+    Clear,
+}
+
+/// Region and settings of a download
+#[derive(Args)]
+struct FetchArgs {
+    /// City name to load
+    #[arg(long, conflicts_with = "bbox")]
+    city: Option<String>,
+
+    /// Bounding box as south,west,north,east
+    #[arg(long, value_parser = parse_bbox, allow_hyphen_values = true)]
+    bbox: Option<BoundingBox>,
+
+    /// Configuration file (.json, .toml or .ron); flags override its values
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Feature preset: urban, transportation, natural, comprehensive, gaming [default: urban]
+    #[arg(long, value_parser = parse_features)]
+    features: Option<FeatureSet>,
+
+    /// Registered data provider, e.g. overpass or mock
+    #[arg(long, default_value = "overpass")]
+    provider: String,
+
+    /// Grid resolution in cells per degree
+    #[arg(long)]
+    resolution: Option<u32>,
+
+    /// Always download instead of using the cache
+    #[arg(long)]
+    no_cache: bool,
+}
+
+/// Where the OSM data of a grid comes from
+#[derive(Args)]
+struct SourceArgs {
+    #[command(flatten)]
+    fetch: FetchArgs,
+
+    /// Read OSM data saved by `fetch` instead of downloading it
+    #[arg(long, conflicts_with_all = ["city", "bbox"])]
+    input: Option<PathBuf>,
+}
+
+/// Parse a bounding box given as south,west,north,east
+fn parse_bbox(value: &str) -> std::result::Result<BoundingBox, String> {
+    let coordinates: Vec<f64> = value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| format!("invalid coordinate: {}", e))?;
+    match coordinates[..] {
+        [south, west, north, east] if south < north && west < east => {
+            Ok(BoundingBox::new(south, west, north, east))
+        }
+        [_, _, _, _] => Err("expected south < north and west < east".to_string()),
+        _ => Err("expected four coordinates: south,west,north,east".to_string()),
+    }
+}
+
+/// Parse the name of a feature preset
+fn parse_features(name: &str) -> std::result::Result<FeatureSet, String> {
+    FeatureSet::from_preset_name(name).ok_or_else(|| {
+        format!(
+            "unknown preset '{}', expected one of: {}",
+            name,
+            FeatureSet::preset_names().join(", ")
+        )
+    })
+}
+
+impl FetchArgs {
+    /// Build the configuration, falling back to `default_region` when no region is given
+    fn config(&self, default_region: Option<Region>) -> Result<OsmConfig> {
+        let mut config = match &self.config {
+            Some(path) => OsmConfig::from_path(path)?,
+            None => OsmConfigBuilder::new()
+                .features(self.features.clone().unwrap_or_else(FeatureSet::urban))
+                .build(),
+        };
+
+        let region = match (&self.city, &self.bbox) {
+            (Some(city), _) => Some(Region::city(city)),
+            (None, Some(bbox)) => Some(Region::BoundingBox(bbox.clone())),
+            (None, None) => default_region,
+        };
+        match region {
+            Some(region) => config.region = region,
+            None if self.config.is_none() => {
+                return Err(OsmTilesError::Config(
+                    "No region given; use --city, --bbox or --config".to_string(),
+                ));
+            }
+            None => {}
+        }
+        if let Some(features) = &self.features {
+            config.features = features.clone();
+        }
+        if let Some(resolution) = self.resolution {
+            config.grid_resolution = resolution;
+        }
+        Ok(config)
+    }
+}
+
+/// Downloads saved as JSON files, one per provider and configuration
+struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Create a cache in `dir`, or in the user's cache directory
+    fn new(dir: Option<PathBuf>) -> Self {
+        let dir = dir.unwrap_or_else(|| {
+            std::env::var_os("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+                .unwrap_or_else(std::env::temp_dir)
+                .join("osm-tiles")
+        });
+        Self { dir }
+    }
+
+    fn path(&self, provider: &str, config: &OsmConfig) -> PathBuf {
+        let key = data_hash(&CachingProvider::cache_key(config));
+        self.dir.join(format!("{}-{}.json", provider, key))
+    }
+
+    /// Load cached data that is not older than the configuration accepts
+    fn load(&self, provider: &str, config: &OsmConfig) -> Option<OsmData> {
+        let path = self.path(provider, config);
+        let source = std::fs::read_to_string(&path).ok()?;
+        let data: OsmData = match serde_json::from_str(&source) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable cache file {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        if let Some(max_age) = config.max_data_age()
+            && data.metadata.is_stale(max_age)
+        {
+            tracing::debug!("Cached data in {} is stale", path.display());
+            return None;
+        }
+        tracing::debug!("Serving {:?} from {}", config.region, path.display());
+        Some(OsmData {
+            metadata: data.metadata.clone().with_extra("cache", "hit"),
+            ..data
+        })
+    }
+
+    /// Save downloaded data; failures only cost the next run a download
+    fn store(&self, provider: &str, config: &OsmConfig, data: &OsmData) {
+        let path = self.path(provider, config);
+        let result = std::fs::create_dir_all(&self.dir).and_then(|()| {
+            let json = serde_json::to_string(data).map_err(std::io::Error::other)?;
+            std::fs::write(&path, json)
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to cache download in {}: {}", path.display(), e);
+        }
+    }
+
+    /// Delete all cached downloads, returning the number of files and bytes removed
+    fn clear(&self) -> Result<(usize, u64)> {
+        let io_error = |source| OsmTilesError::Io {
+            path: self.dir.display().to_string(),
+            source,
+        };
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+            Err(e) => return Err(io_error(e)),
+        };
+
+        let (mut files, mut bytes) = (0, 0);
+        for entry in entries {
+            let path = entry.map_err(io_error)?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                bytes += std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+                std::fs::remove_file(&path).map_err(io_error)?;
+                files += 1;
+            }
+        }
+        Ok((files, bytes))
+    }
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    std::fs::write(path, contents).map_err(|source| OsmTilesError::Io {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+fn read_data(path: &Path) -> Result<OsmData> {
+    let source = std::fs::read_to_string(path).map_err(|source| OsmTilesError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    serde_json::from_str(&source).map_err(|e| {
+        OsmTilesError::Parse(format!("{} is not a saved download: {}", path.display(), e))
+    })
+}
+
+/// Download data for the arguments, going through the cache
+async fn fetch(args: &FetchArgs, cache: &DiskCache) -> Result<(OsmConfig, OsmData)> {
+    let config = args.config(None)?;
+    if !args.no_cache
+        && let Some(data) = cache.load(&args.provider, &config)
+    {
+        return Ok((config, data));
+    }
+
+    let provider = ProviderFactory::create_provider(&args.provider)?;
+    let data = provider.fetch_data(&config).await?;
+    cache.store(&args.provider, &config, &data);
+    Ok((config, data))
+}
+
+/// Load or download the data of a grid command and generate the grid
+async fn generate(source: &SourceArgs, cache: &DiskCache) -> Result<(OsmData, TileGrid)> {
+    let (config, data) = match &source.input {
+        Some(path) => {
+            let data = read_data(path)?;
+            let config = source
+                .fetch
+                .config(Some(Region::BoundingBox(data.bounding_box.clone())))?;
+            (config, data)
+        }
+        None => fetch(&source.fetch, cache).await?,
+    };
+    let grid = DefaultGridGenerator::new().generate_grid_sync(&data, &config)?;
+    Ok((data, grid))
+}
+
+fn is_cached(data: &OsmData) -> bool {
+    data.metadata
+        .extra
+        .get("cache")
+        .is_some_and(|value| value == "hit")
+}
+
+fn grid_summary(command: &str, output: &Path, data: &OsmData, grid: &TileGrid) -> Value {
+    let (width, height) = grid.dimensions();
+    json!({
+        "command": command,
+        "output": output,
+        "width": width,
+        "height": height,
+        "meters_per_tile": grid.meters_per_tile,
+        "elements_processed": grid.metadata.elements_processed,
+        "tiles_populated": grid.metadata.tiles_populated,
+        "generation_time_ms": grid.metadata.generation_time_ms,
+        "cached": is_cached(data),
+    })
+}
+
+/// Write an export, with an attribution sidecar for formats that cannot embed it
+fn export(
+    exporter: &dyn GridExporter,
+    grid: &TileGrid,
+    output: &Path,
+    summary: &mut Value,
+) -> Result<()> {
+    let bytes = exporter.export(grid)?;
+    write_file(output, &bytes)?;
+    summary["bytes"] = bytes.len().into();
+    if !exporter.embeds_attribution() {
+        let sidecar = ExportMetadata::sidecar_path(&output.to_string_lossy());
+        write_file(
+            Path::new(&sidecar),
+            ExportMetadata::from_grid(grid).to_sidecar_json().as_bytes(),
+        )?;
+        summary["attribution"] = sidecar.into();
+    }
+    Ok(())
+}
+
+async fn run(cli: &Cli) -> Result<Value> {
+    let cache = DiskCache::new(cli.cache_dir.clone());
+    match &cli.command {
+        Command::Fetch {
+            fetch: args,
+            output,
+        } => {
+            let (_, data) = fetch(args, &cache).await?;
+            let json = serde_json::to_string(&data)
+                .map_err(|e| OsmTilesError::Export(format!("Failed to serialize data: {}", e)))?;
+            write_file(output, json.as_bytes())?;
+            let bbox = &data.bounding_box;
+            Ok(json!({
+                "command": "fetch",
+                "output": output,
+                "provider": data.metadata.provider_type,
+                "bbox": [bbox.south, bbox.west, bbox.north, bbox.east],
+                "bytes": data.raw_data.len(),
+                "elements": data.metadata.element_count,
+                "data_timestamp": data.metadata.data_timestamp,
+                "cached": is_cached(&data),
+            }))
+        }
+        Command::Grid { source, output } => {
+            let (data, grid) = generate(source, &cache).await?;
+            let json = serde_json::to_string(&grid)
+                .map_err(|e| OsmTilesError::Export(format!("Failed to serialize grid: {}", e)))?;
+            write_file(output, json.as_bytes())?;
+            let mut summary = grid_summary("grid", output, &data, &grid);
+            summary["bytes"] = json.len().into();
+            Ok(summary)
+        }
+        Command::RenderPng {
+            source,
+            output,
+            scale,
+        } => {
+            let (data, grid) = generate(source, &cache).await?;
+            let mut summary = grid_summary("render-png", output, &data, &grid);
+            let exporter = PngExporter::new().with_scale(*scale);
+            export(&exporter, &grid, output, &mut summary)?;
+            Ok(summary)
+        }
+        Command::ExportGeojson {
+            source,
+            output,
+            with_tags,
+        } => {
+            let (data, grid) = generate(source, &cache).await?;
+            let mut summary = grid_summary("export-geojson", output, &data, &grid);
+            let exporter = GeoJsonExporter::new().with_tags(*with_tags);
+            export(&exporter, &grid, output, &mut summary)?;
+            Ok(summary)
+        }
+        Command::ExportCsv {
+            source,
+            output,
+            tags,
+            statistics,
+        } => {
+            let (data, grid) = generate(source, &cache).await?;
+            let mut summary = grid_summary("export-csv", output, &data, &grid);
+            let mut exporter = CsvExporter::new();
+            if !tags.is_empty() {
+                exporter = exporter.with_tag_keys(tags.iter().cloned());
+            }
+            export(&exporter, &grid, output, &mut summary)?;
+            if let Some(path) = statistics {
+                write_file(path, exporter.statistics_csv(&grid).as_bytes())?;
+                summary["statistics"] = path.to_string_lossy().into_owned().into();
+            }
+            Ok(summary)
+        }
+        Command::Cache {
+            command: CacheCommand::Clear,
+        } => {
+            let (files, bytes) = cache.clear()?;
+            Ok(json!({
+                "command": "cache clear",
+                "cache_dir": cache.dir,
+                "files_removed": files,
+                "bytes_freed": bytes,
+            }))
+        }
+    }
+}
+
+/// Print a summary as JSON or as one `key: value` line per field
+fn print_summary(summary: &Value, as_json: bool) {
+    if as_json {
+        println!("{}", summary);
+        return;
+    }
+    if let Some(fields) = summary.as_object() {
+        for (key, value) in fields {
+            match value {
+                Value::String(text) => println!("{}: {}", key, text),
+                Value::Null => {}
+                other => println!("{}: {}", key, other),
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let default_level = if cli.verbose { "debug" } else { "warn" };
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level)),
+        )
+        .init();
+
+    match run(&cli).await {
+        Ok(summary) => {
+            print_summary(&summary, cli.json);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if cli.json {
+                println!("{}", json!({ "error": e.to_string() }));
+            } else {
+                eprintln!("error: {}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bbox() {
+        let bbox = parse_bbox("52.5, 13.4,52.51,13.41").unwrap();
+        assert_eq!((bbox.south, bbox.east), (52.5, 13.41));
+        assert!(parse_bbox("-33.9,18.4,-33.8,18.5").is_ok());
+        assert!(parse_bbox("52.51,13.4,52.5,13.41").is_err());
+        assert!(parse_bbox("52.5,13.4").is_err());
+        assert!(parse_bbox("north,13.4,52.5,13.41").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("osm-tiles-cache-{}", std::process::id()));
+        let cache = DiskCache::new(Some(dir.clone()));
+        let args = FetchArgs {
+            city: None,
+            bbox: Some(BoundingBox::new(52.5, 13.4, 52.51, 13.41)),
+            config: None,
+            features: None,
+            provider: "mock".to_string(),
+            resolution: Some(1000),
+            no_cache: false,
+        };
+
+        let (_, first) = fetch(&args, &cache).await.unwrap();
+        let (_, second) = fetch(&args, &cache).await.unwrap();
+        assert!(!is_cached(&first));
+        assert!(is_cached(&second));
+        assert_eq!(first.raw_data, second.raw_data);
+
+        assert_eq!(cache.clear().unwrap().0, 1);
+        assert_eq!(cache.clear().unwrap(), (0, 0));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+//! Blocking wrappers for applications without an async runtime
+//!
+//! The providers and generators of this crate only wait on their own
+//! callbacks and timers, so a minimal executor that parks the calling thread
+//! is enough to drive them. Providers built on `reqwest` still need a tokio
+//! runtime; use the `ehttp-client` feature or local data instead.
+
+use std::future::{Future, IntoFuture};
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use crate::{GridGenerator, OsmConfig, OsmData, OsmDataProvider, Result, TileGrid};
+
+/// Waker that unparks the thread blocked in [`block_on`]
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run a future to completion on the current thread
+///
+/// The thread sleeps while the future is pending and is woken by its waker.
+/// Must not be called from within an async task, where it would block the
+/// executor.
+pub fn block_on<F: IntoFuture>(future: F) -> F::Output {
+    let mut future = pin!(future.into_future());
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            // Spurious wakeups just poll again
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Blocking variant of [`OsmDataProvider::fetch_data`]
+pub trait BlockingOsmDataProvider {
+    /// Fetch OSM data, blocking the current thread until it arrives
+    fn fetch_data_blocking(&self, config: &OsmConfig) -> Result<OsmData>;
+}
+
+impl<P: OsmDataProvider + ?Sized> BlockingOsmDataProvider for P {
+    fn fetch_data_blocking(&self, config: &OsmConfig) -> Result<OsmData> {
+        block_on(self.fetch_data(config))
+    }
+}
+
+/// Blocking variant of [`GridGenerator::generate_grid`]
+pub trait BlockingGridGenerator {
+    /// Generate a tile grid, blocking the current thread until it is done
+    fn generate_grid_blocking(&self, osm_data: &OsmData, config: &OsmConfig) -> Result<TileGrid>;
+}
+
+impl<G: GridGenerator + ?Sized> BlockingGridGenerator for G {
+    fn generate_grid_blocking(&self, osm_data: &OsmData, config: &OsmConfig) -> Result<TileGrid> {
+        block_on(self.generate_grid(osm_data, config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultGridGenerator, MockProvider, OsmConfigBuilder};
+
+    #[test]
+    fn test_blocking_pipeline() {
+        let config = OsmConfigBuilder::new()
+            .bbox(52.5, 13.4, 52.51, 13.41)
+            .grid_resolution(2000)
+            .build();
+        // The delay waits on a timer thread, which must wake the parked caller
+        let provider: Box<dyn OsmDataProvider> = Box::new(MockProvider::new().with_delay(20));
+        let osm_data = provider.fetch_data_blocking(&config).unwrap();
+
+        let grid = DefaultGridGenerator::new()
+            .generate_grid_blocking(&osm_data, &config)
+            .unwrap();
+        assert!(grid.metadata.tiles_populated > 0);
+
+        let failing = MockProvider::new().with_failure();
+        assert!(failing.fetch_data_blocking(&config).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Compile-time configuration of the library, for bug reports and diagnostics
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Cargo features of this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CargoFeature {
+    Bevy,
+    Cli,
+    ReqwestClient,
+    EhttpClient,
+    Wasm,
+    Native,
+    PngExport,
+    GeotiffExport,
+    ConfigToml,
+    ConfigRon,
+    Blocking,
+}
+
+impl CargoFeature {
+    /// Every feature, in the order of `Cargo.toml`
+    pub const ALL: [CargoFeature; 11] = [
+        Self::Bevy,
+        Self::Cli,
+        Self::ReqwestClient,
+        Self::EhttpClient,
+        Self::Wasm,
+        Self::Native,
+        Self::PngExport,
+        Self::GeotiffExport,
+        Self::ConfigToml,
+        Self::ConfigRon,
+        Self::Blocking,
+    ];
+
+    /// Get the feature name as written in `Cargo.toml`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bevy => "bevy",
+            Self::Cli => "cli",
+            Self::ReqwestClient => "reqwest-client",
+            Self::EhttpClient => "ehttp-client",
+            Self::Wasm => "wasm",
+            Self::Native => "native",
+            Self::PngExport => "png-export",
+            Self::GeotiffExport => "geotiff-export",
+            Self::ConfigToml => "config-toml",
+            Self::ConfigRon => "config-ron",
+            Self::Blocking => "blocking",
+        }
+    }
+
+    /// Check whether the feature was enabled when the library was compiled
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Self::Bevy => cfg!(feature = "bevy"),
+            Self::Cli => cfg!(feature = "cli"),
+            Self::ReqwestClient => cfg!(feature = "reqwest-client"),
+            Self::EhttpClient => cfg!(feature = "ehttp-client"),
+            Self::Wasm => cfg!(feature = "wasm"),
+            Self::Native => cfg!(feature = "native"),
+            Self::PngExport => cfg!(feature = "png-export"),
+            Self::GeotiffExport => cfg!(feature = "geotiff-export"),
+            Self::ConfigToml => cfg!(feature = "config-toml"),
+            Self::ConfigRon => cfg!(feature = "config-ron"),
+            Self::Blocking => cfg!(feature = "blocking"),
+        }
+    }
+}
+
+impl fmt::Display for CargoFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// HTTP client used by [`crate::create_default_client`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HttpClientKind {
+    Reqwest,
+    Ehttp,
+    /// No client feature is enabled; every request fails
+    None,
+}
+
+/// Architecture family the library was compiled for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetPlatform {
+    Native,
+    Wasm,
+}
+
+/// Version, features and target the library was compiled with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// Crate version
+    pub version: String,
+    /// Enabled cargo features
+    pub features: Vec<CargoFeature>,
+    /// HTTP client selected from the enabled features
+    pub http_client: HttpClientKind,
+    /// Target architecture family
+    pub target: TargetPlatform,
+}
+
+impl BuildInfo {
+    /// Check whether a cargo feature is enabled
+    pub fn has_feature(&self, feature: CargoFeature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    /// Check whether network providers can make requests
+    pub fn has_http_client(&self) -> bool {
+        self.http_client != HttpClientKind::None
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let features: Vec<&str> = self.features.iter().map(CargoFeature::name).collect();
+        write!(
+            f,
+            "bevy-osm-tiles {} ({:?}, HTTP client: {:?}, features: [{}])",
+            self.version,
+            self.target,
+            self.http_client,
+            features.join(", ")
+        )
+    }
+}
+
+/// Report how the library was compiled; include this in bug reports
+///
+/// ```rust
+/// let info = bevy_osm_tiles::build_info();
+/// println!("{}", info);
+/// ```
+pub fn build_info() -> BuildInfo {
+    // Mirrors the precedence of `create_default_client`
+    let http_client = if cfg!(feature = "reqwest-client") {
+        HttpClientKind::Reqwest
+    } else if cfg!(feature = "ehttp-client") {
+        HttpClientKind::Ehttp
+    } else {
+        HttpClientKind::None
+    };
+
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: CargoFeature::ALL
+            .into_iter()
+            .filter(CargoFeature::is_enabled)
+            .collect(),
+        http_client,
+        target: if cfg!(target_arch = "wasm32") {
+            TargetPlatform::Wasm
+        } else {
+            TargetPlatform::Native
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_matches_features() {
+        let info = build_info();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            info.has_feature(CargoFeature::ReqwestClient),
+            cfg!(feature = "reqwest-client")
+        );
+        assert_eq!(
+            info.has_http_client(),
+            cfg!(any(feature = "reqwest-client", feature = "ehttp-client"))
+        );
+        assert!(info.to_string().starts_with("bevy-osm-tiles "));
+    }
+}
@@ -0,0 +1,410 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use super::{FeatureSet, OsmConfigBuilder, Region};
+use crate::{OsmTilesError, Result};
+
+/// A saved region with the settings it is usually loaded with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// The region to load
+    pub region: Region,
+    /// Preferred features, the builder defaults if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<FeatureSet>,
+    /// Preferred grid resolution, the builder default if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid_resolution: Option<u32>,
+}
+
+impl Bookmark {
+    /// Create a bookmark for a region
+    pub fn new(region: Region) -> Self {
+        Self {
+            region,
+            features: None,
+            grid_resolution: None,
+        }
+    }
+
+    /// Set the preferred features
+    pub fn with_features(mut self, features: FeatureSet) -> Self {
+        self.features = Some(features);
+        self
+    }
+
+    /// Set the preferred grid resolution
+    pub fn with_grid_resolution(mut self, resolution: u32) -> Self {
+        self.grid_resolution = Some(resolution);
+        self
+    }
+
+    /// Start a configuration from this bookmark
+    pub fn config_builder(&self) -> OsmConfigBuilder {
+        let mut builder = OsmConfigBuilder::new().region(self.region.clone());
+        if let Some(features) = &self.features {
+            builder = builder.features(features.clone());
+        }
+        if let Some(resolution) = self.grid_resolution {
+            builder = builder.grid_resolution(resolution);
+        }
+        builder
+    }
+}
+
+/// Bookmark names are matched case-insensitively, ignoring surrounding whitespace
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+fn installed() -> &'static RwLock<RegionBook> {
+    static BOOK: OnceLock<RwLock<RegionBook>> = OnceLock::new();
+    BOOK.get_or_init(|| RwLock::new(RegionBook::new()))
+}
+
+/// Named regions saved by the user, e.g. "home_town" or "office"
+///
+/// Books persist as JSON or, with the `config-toml` feature, as TOML with one
+/// table per bookmark; in the browser they live in local storage with the
+/// `web-storage` feature. Install a book once it is loaded so
+/// [`OsmConfigBuilder::from_bookmark`] can find its entries by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(
+    from = "BTreeMap<String, Bookmark>",
+    into = "BTreeMap<String, Bookmark>"
+)]
+pub struct RegionBook {
+    bookmarks: BTreeMap<String, Bookmark>,
+}
+
+/// Hand-edited files may spell names in any case
+impl From<BTreeMap<String, Bookmark>> for RegionBook {
+    fn from(bookmarks: BTreeMap<String, Bookmark>) -> Self {
+        Self {
+            bookmarks: bookmarks
+                .into_iter()
+                .map(|(name, bookmark)| (normalize(&name), bookmark))
+                .collect(),
+        }
+    }
+}
+
+impl From<RegionBook> for BTreeMap<String, Bookmark> {
+    fn from(book: RegionBook) -> Self {
+        book.bookmarks
+    }
+}
+
+impl RegionBook {
+    /// Create an empty book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save a bookmark under `name`, returning the one it replaces
+    pub fn insert(&mut self, name: &str, bookmark: Bookmark) -> Option<Bookmark> {
+        self.bookmarks.insert(normalize(name), bookmark)
+    }
+
+    /// Look up a bookmark by name
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.get(&normalize(name))
+    }
+
+    /// Remove a bookmark, returning it if it was saved
+    pub fn remove(&mut self, name: &str) -> Option<Bookmark> {
+        self.bookmarks.remove(&normalize(name))
+    }
+
+    /// Iterate over the bookmarks sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Bookmark)> {
+        self.bookmarks
+            .iter()
+            .map(|(name, bookmark)| (name.as_str(), bookmark))
+    }
+
+    /// Get the bookmark names in sorted order
+    pub fn names(&self) -> Vec<String> {
+        self.bookmarks.keys().cloned().collect()
+    }
+
+    /// Get the number of bookmarks
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    /// Check whether the book has no bookmarks
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    /// Make this the book [`OsmConfigBuilder::from_bookmark`] reads from
+    pub fn install(self) {
+        *installed().write().unwrap() = self;
+    }
+
+    /// Get a copy of the installed book, empty until one is installed
+    pub fn installed() -> RegionBook {
+        installed().read().unwrap().clone()
+    }
+
+    /// Read a book from a JSON document
+    pub fn from_json_str(source: &str) -> Result<Self> {
+        serde_json::from_str(source).map_err(|e| OsmTilesError::json("region book", e))
+    }
+
+    /// Write the book as a JSON document
+    pub fn to_json_string(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| OsmTilesError::json("region book", e))
+    }
+
+    /// Read a book from a TOML document with one table per bookmark
+    #[cfg(feature = "config-toml")]
+    pub fn from_toml_str(source: &str) -> Result<Self> {
+        let document = toml_edit::Document::parse(source)
+            .map_err(|e| OsmTilesError::Parse(format!("Invalid TOML region book: {}", e)))?;
+        let value = super::file::toml_table(document.as_table().iter());
+        serde_json::from_value(value).map_err(|e| OsmTilesError::json("region book", e))
+    }
+
+    /// Write the book as a TOML document with one table per bookmark
+    #[cfg(feature = "config-toml")]
+    pub fn to_toml_string(&self) -> Result<String> {
+        let mut toml = String::new();
+        for (name, bookmark) in &self.bookmarks {
+            let value = serde_json::to_value(bookmark)
+                .map_err(|e| OsmTilesError::json("region book", e))?;
+            if !toml.is_empty() {
+                toml.push('\n');
+            }
+            toml.push_str(&format!("[{}]\n", toml_key(name)));
+            if let serde_json::Value::Object(fields) = value {
+                for (key, value) in &fields {
+                    if let Some(value) = toml_inline(value) {
+                        toml.push_str(&format!("{} = {}\n", toml_key(key), value));
+                    }
+                }
+            }
+        }
+        Ok(toml)
+    }
+
+    /// Load a book file, choosing the format by extension
+    ///
+    /// `.json` is always supported, `.toml` needs the `config-toml` feature.
+    /// A missing file is an empty book, so first runs need no setup.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(source) => {
+                return Err(OsmTilesError::Io {
+                    path: path.display().to_string(),
+                    source,
+                });
+            }
+        };
+        match book_format(path)? {
+            #[cfg(feature = "config-toml")]
+            BookFormat::Toml => Self::from_toml_str(&source),
+            BookFormat::Json => Self::from_json_str(&source),
+        }
+    }
+
+    /// Save the book to a file, choosing the format by extension
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let path = path.as_ref();
+        let contents = match book_format(path)? {
+            #[cfg(feature = "config-toml")]
+            BookFormat::Toml => self.to_toml_string()?,
+            BookFormat::Json => self.to_json_string()?,
+        };
+        std::fs::write(path, contents).map_err(|source| OsmTilesError::Io {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    /// Load a book from the browser's local storage, empty if nothing is stored under `key`
+    #[cfg(feature = "web-storage")]
+    pub fn load_from_local_storage(key: &str) -> Result<Self> {
+        match local_storage()?.get_item(key) {
+            Ok(Some(source)) => Self::from_json_str(&source),
+            Ok(None) => Ok(Self::new()),
+            Err(_) => Err(OsmTilesError::Config(
+                "Local storage cannot be read".to_string(),
+            )),
+        }
+    }
+
+    /// Save the book as JSON in the browser's local storage
+    #[cfg(feature = "web-storage")]
+    pub fn save_to_local_storage(&self, key: &str) -> Result<()> {
+        local_storage()?
+            .set_item(key, &self.to_json_string()?)
+            .map_err(|_| OsmTilesError::Config("Local storage is full or disabled".to_string()))
+    }
+}
+
+impl OsmConfigBuilder {
+    /// Start a configuration from a bookmark of the installed [`RegionBook`]
+    pub fn from_bookmark(name: &str) -> Result<Self> {
+        let book = installed().read().unwrap();
+        book.get(name).map(Bookmark::config_builder).ok_or_else(|| {
+            OsmTilesError::Config(format!(
+                "Unknown bookmark '{}'; saved: {}",
+                name.trim(),
+                book.names().join(", ")
+            ))
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum BookFormat {
+    Json,
+    #[cfg(feature = "config-toml")]
+    Toml,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn book_format(path: &std::path::Path) -> Result<BookFormat> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "json" => Ok(BookFormat::Json),
+        #[cfg(feature = "config-toml")]
+        "toml" => Ok(BookFormat::Toml),
+        _ => Err(OsmTilesError::Config(format!(
+            "{}: unsupported region book format '{}'",
+            path.display(),
+            extension
+        ))),
+    }
+}
+
+#[cfg(feature = "web-storage")]
+fn local_storage() -> Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| OsmTilesError::Config("Local storage is not available".to_string()))
+}
+
+/// Write a key bare if TOML allows it, quoted otherwise
+#[cfg(feature = "config-toml")]
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
+
+#[cfg(feature = "config-toml")]
+fn toml_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Write a JSON value as an inline TOML value; TOML has no null, so nulls are left out
+#[cfg(feature = "config-toml")]
+fn toml_inline(value: &serde_json::Value) -> Option<String> {
+    use serde_json::Value;
+
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => b.to_string(),
+        // serde_json writes floats with a fraction or exponent, as TOML needs
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => toml_string(s),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter_map(toml_inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .filter_map(|(key, value)| {
+                    toml_inline(value).map(|value| format!("{} = {}", toml_key(key), value))
+                })
+                .collect();
+            if fields.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", fields.join(", "))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsmFeature;
+
+    #[test]
+    fn test_region_book() {
+        let mut book = RegionBook::new();
+        book.insert(
+            "Home_Town",
+            Bookmark::new(Region::city("Lübeck \"Altstadt\""))
+                .with_features(FeatureSet::new().with_feature(OsmFeature::Roads))
+                .with_grid_resolution(250),
+        );
+        book.insert(
+            "office",
+            Bookmark::new(Region::bbox(52.5, 13.4, 52.51, 13.41)),
+        );
+        assert_eq!(book.names(), vec!["home_town", "office"]);
+
+        let json = RegionBook::from_json_str(&book.to_json_string().unwrap()).unwrap();
+        assert_eq!(json.len(), 2);
+        let edited =
+            RegionBook::from_json_str(r#"{"Cottage": {"region": {"City": {"name": "Celle"}}}}"#)
+                .unwrap();
+        assert!(edited.get("cottage").is_some());
+        #[cfg(feature = "config-toml")]
+        {
+            let toml = book.to_toml_string().unwrap();
+            assert!(toml.contains("[office]\nregion = { BoundingBox = { "));
+            let read = RegionBook::from_toml_str(&toml).unwrap();
+            assert_eq!(read.get("office").unwrap().grid_resolution, None);
+            assert!(matches!(
+                &read.get("home_town").unwrap().region,
+                Region::City { name } if name == "Lübeck \"Altstadt\""
+            ));
+        }
+
+        book.install();
+        let config = OsmConfigBuilder::from_bookmark(" HOME_TOWN ")
+            .unwrap()
+            .build();
+        assert_eq!(config.grid_resolution, 250);
+        assert!(config.features.contains_feature(&OsmFeature::Roads));
+        let error = OsmConfigBuilder::from_bookmark("cottage").unwrap_err();
+        assert!(error.to_string().contains("home_town, office"));
+        RegionBook::new().install();
+    }
+}
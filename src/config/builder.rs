@@ -1,4 +1,5 @@
-use super::{FeatureSet, OsmConfig, OsmFeature, OsmTagQuery, Region};
+use super::{FeatureSet, MAX_SLIPPY_ZOOM, OsmConfig, OsmFeature, OsmTagQuery, Region};
+use crate::{Crs, GridOrigin};
 
 /// Builder for creating OSM configurations with a fluent API
 #[derive(Debug, Clone)]
@@ -8,6 +9,12 @@ pub struct OsmConfigBuilder {
     tile_size: Option<f32>,
     timeout_seconds: Option<u64>,
     features: FeatureSet,
+    crs: Crs,
+    grid_origin: GridOrigin,
+    memory_budget_bytes: Option<usize>,
+    slippy_zoom: Option<u8>,
+    cell_size_meters: Option<f32>,
+    max_data_age_seconds: Option<u64>,
 }
 
 impl OsmConfigBuilder {
@@ -19,6 +26,12 @@ impl OsmConfigBuilder {
             tile_size: None,
             timeout_seconds: None,
             features: FeatureSet::new(),
+            crs: Crs::default(),
+            grid_origin: GridOrigin::default(),
+            memory_budget_bytes: None,
+            slippy_zoom: None,
+            cell_size_meters: None,
+            max_data_age_seconds: None,
         }
     }
 
@@ -46,6 +59,15 @@ impl OsmConfigBuilder {
         self
     }
 
+    /// Size tiles by their edge length in meters, replacing `grid_resolution`
+    ///
+    /// The resolution is computed from the latitude of the resolved region,
+    /// see [`OsmConfig::grid_resolution_for`].
+    pub fn cell_size_meters(mut self, meters: f32) -> Self {
+        self.cell_size_meters = Some(meters);
+        self
+    }
+
     /// Set the grid resolution
     pub fn grid_resolution(mut self, resolution: u32) -> Self {
         self.grid_resolution = Some(resolution);
@@ -64,6 +86,36 @@ impl OsmConfigBuilder {
         self
     }
 
+    /// Treat OSM data older than `seconds` as stale
+    pub fn max_data_age(mut self, seconds: u64) -> Self {
+        self.max_data_age_seconds = Some(seconds);
+        self
+    }
+
+    /// Set the coordinate reference system of the generated grid
+    pub fn crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    /// Set the corner of the area at which grid row 0 starts
+    pub fn grid_origin(mut self, origin: GridOrigin) -> Self {
+        self.grid_origin = origin;
+        self
+    }
+
+    /// Set the approximate memory budget for grid generation in bytes
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget_bytes = Some(bytes);
+        self
+    }
+
+    /// Align the fetched area and generated grids to XYZ slippy tiles at a zoom level
+    pub fn slippy_zoom(mut self, zoom: u8) -> Self {
+        self.slippy_zoom = Some(zoom.min(MAX_SLIPPY_ZOOM));
+        self
+    }
+
     /// Use a predefined feature set
     pub fn features(mut self, features: FeatureSet) -> Self {
         self.features = features;
@@ -137,6 +189,12 @@ impl OsmConfigBuilder {
             tile_size: self.tile_size.unwrap_or(10.0),
             timeout_seconds: self.timeout_seconds.unwrap_or(30),
             features: self.features,
+            crs: self.crs,
+            grid_origin: self.grid_origin,
+            memory_budget_bytes: self.memory_budget_bytes,
+            slippy_zoom: self.slippy_zoom,
+            cell_size_meters: self.cell_size_meters,
+            max_data_age_seconds: self.max_data_age_seconds,
         }
     }
 }
@@ -266,11 +324,19 @@ mod tests {
             .grid_resolution(200)
             .tile_size(5.0)
             .timeout(120)
+            .crs(Crs::WebMercator)
+            .grid_origin(GridOrigin::BottomLeft)
+            .memory_budget(64 * 1024 * 1024)
+            .slippy_zoom(16)
             .build();
 
         assert_eq!(config.grid_resolution, 200);
         assert_eq!(config.tile_size, 5.0);
         assert_eq!(config.timeout_seconds, 120);
+        assert_eq!(config.crs, Crs::WebMercator);
+        assert_eq!(config.grid_origin, GridOrigin::BottomLeft);
+        assert_eq!(config.memory_budget_bytes, Some(64 * 1024 * 1024));
+        assert_eq!(config.slippy_zoom, Some(16));
     }
 
     #[test]
@@ -1,5 +1,15 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use super::BoundingBox;
+use crate::{OsmTilesError, Result, TileType};
+
+/// Assumed density of elements matched by a custom query (elements per km²)
+const CUSTOM_QUERY_DENSITY_PER_KM2: f64 = 100.0;
+
+/// Average size of one element in an Overpass JSON response with geometry (in bytes)
+const AVERAGE_ELEMENT_BYTES: u64 = 800;
 
 /// Standard OSM feature types that can be included in grid generation
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -75,6 +85,7 @@ impl OsmFeature {
             Self::Water => vec![
                 OsmTagQuery::new("natural", Some("water")),
                 OsmTagQuery::new("waterway", None::<String>),
+                OsmTagQuery::new("natural", Some("coastline")),
             ],
             Self::Rivers => vec![
                 OsmTagQuery::new("waterway", Some("river")),
@@ -136,6 +147,47 @@ impl OsmFeature {
             Self::Landuse => "General land use classifications",
         }
     }
+
+    /// Get the typical number of matching elements per km² in a dense urban area
+    ///
+    /// Used for rough cost estimates before a download; rural areas are usually far sparser.
+    pub fn typical_density_per_km2(&self) -> f64 {
+        match self {
+            Self::Roads => 150.0,
+            Self::Highways => 20.0,
+            Self::Footpaths => 200.0,
+            Self::Railways => 30.0,
+            Self::Buildings => 1500.0,
+            Self::Residential => 300.0,
+            Self::Commercial => 80.0,
+            Self::Industrial => 30.0,
+            Self::Water => 20.0,
+            Self::Rivers => 10.0,
+            Self::Lakes => 5.0,
+            Self::Forests => 10.0,
+            Self::Parks => 15.0,
+            Self::Grassland => 20.0,
+            Self::Parking => 40.0,
+            Self::Amenities => 150.0,
+            Self::Tourism => 20.0,
+            Self::PowerLines => 10.0,
+            Self::Boundaries => 2.0,
+            Self::Landuse => 80.0,
+        }
+    }
+}
+
+/// Rough estimate of the size of an Overpass query for a feature set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryCost {
+    /// Area covered by the query in square kilometers
+    pub area_km2: f64,
+    /// Number of tag filters in the generated query
+    pub query_count: usize,
+    /// Estimated number of returned elements (upper bound for dense urban areas)
+    pub estimated_elements: u64,
+    /// Estimated response size in bytes
+    pub estimated_bytes: u64,
 }
 
 /// Represents an OSM tag query
@@ -143,6 +195,15 @@ impl OsmFeature {
 pub struct OsmTagQuery {
     pub key: String,
     pub value: Option<String>,
+    /// Whether matching elements are excluded instead of included
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exclude: bool,
+    /// Whether `value` is a regular expression instead of an exact value
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub regex: bool,
+    /// Tile type for matching elements, replacing the type derived from their tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tile_type: Option<TileType>,
 }
 
 impl OsmTagQuery {
@@ -150,14 +211,120 @@ impl OsmTagQuery {
         Self {
             key: key.into(),
             value: value.map(|v| v.into()),
+            exclude: false,
+            regex: false,
+            tile_type: None,
         }
     }
 
+    /// Create a query excluding elements with `key` (set to `value`, if given)
+    pub fn exclude(key: impl Into<String>, value: Option<impl Into<String>>) -> Self {
+        Self::new(key, value).excluded()
+    }
+
+    /// Create a query matching values of `key` against a regular expression
+    pub fn regex(key: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            regex: true,
+            ..Self::new(key, Some(pattern))
+        }
+    }
+
+    /// Create a query matching any of the given values of `key`
+    pub fn any_of<V: AsRef<str>>(
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let alternatives: Vec<String> = values
+            .into_iter()
+            .map(|value| regex::escape(value.as_ref()))
+            .collect();
+        Self::regex(key, format!("^({})$", alternatives.join("|")))
+    }
+
+    /// Rasterize matching elements as `tile_type`
+    pub fn as_tile_type(mut self, tile_type: TileType) -> Self {
+        self.tile_type = Some(tile_type);
+        self
+    }
+
+    /// Turn this query into an exclusion
+    pub fn excluded(mut self) -> Self {
+        self.exclude = true;
+        self
+    }
+
+    /// Prepare this query for matching many elements
+    ///
+    /// Fails if the value is not a valid regular expression.
+    pub fn matcher(&self) -> Result<TagMatcher> {
+        let value = match (&self.value, self.regex) {
+            (None, _) => ValueMatcher::Any,
+            (Some(value), false) => ValueMatcher::Exact(value.clone()),
+            (Some(pattern), true) => ValueMatcher::Pattern(Regex::new(pattern).map_err(|e| {
+                OsmTilesError::Config(format!("Invalid pattern for tag '{}': {}", self.key, e))
+            })?),
+        };
+        Ok(TagMatcher {
+            key: self.key.clone(),
+            value,
+        })
+    }
+
+    /// Check whether an element with these tags matches the key and value
+    ///
+    /// Queries with an invalid pattern match nothing.
+    pub fn matches(&self, tags: &HashMap<String, String>) -> bool {
+        self.matcher().is_ok_and(|matcher| matcher.matches(tags))
+    }
+
     /// Convert to Overpass QL format
     pub fn to_overpass_filter(&self) -> String {
+        let key = escape_ql(&self.key);
+        match (
+            self.value.as_deref().map(escape_ql),
+            self.exclude,
+            self.regex,
+        ) {
+            (Some(value), false, false) => format!("[\"{}\"][\"{}\"]", key, value),
+            (Some(pattern), false, true) => format!("[\"{}\"~\"{}\"]", key, pattern),
+            (None, false, _) => format!("[\"{}\"]", key),
+            (Some(value), true, false) => format!("[\"{}\"!=\"{}\"]", key, value),
+            (Some(pattern), true, true) => format!("[\"{}\"!~\"{}\"]", key, pattern),
+            (None, true, _) => format!("[!\"{}\"]", key),
+        }
+    }
+}
+
+/// Escape a value for use inside a double-quoted Overpass QL string
+pub(crate) fn escape_ql(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A tag query compiled for matching
+#[derive(Debug, Clone)]
+pub struct TagMatcher {
+    key: String,
+    value: ValueMatcher,
+}
+
+#[derive(Debug, Clone)]
+enum ValueMatcher {
+    Any,
+    Exact(String),
+    Pattern(Regex),
+}
+
+impl TagMatcher {
+    /// Check whether an element with these tags matches
+    pub fn matches(&self, tags: &HashMap<String, String>) -> bool {
+        let Some(actual) = tags.get(&self.key) else {
+            return false;
+        };
         match &self.value {
-            Some(value) => format!("[\"{}\"][\"{}\"]", self.key, value),
-            None => format!("[\"{}\"]", self.key),
+            ValueMatcher::Any => true,
+            ValueMatcher::Exact(value) => actual == value,
+            ValueMatcher::Pattern(pattern) => pattern.is_match(actual),
         }
     }
 }
@@ -168,7 +335,11 @@ pub struct FeatureSet {
     /// Standard features to include
     features: HashSet<OsmFeature>,
     /// Custom OSM tag queries
+    #[serde(default)]
     custom_queries: Vec<OsmTagQuery>,
+    /// Tag queries whose matches are left out, even if a feature includes them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    exclusions: Vec<OsmTagQuery>,
 }
 
 impl FeatureSet {
@@ -177,6 +348,7 @@ impl FeatureSet {
         Self {
             features: HashSet::new(),
             custom_queries: Vec::new(),
+            exclusions: Vec::new(),
         }
     }
 
@@ -229,6 +401,13 @@ impl FeatureSet {
         ])
     }
 
+    /// Create a feature set for game maps: urban features plus amenities and tourism
+    pub fn gaming() -> Self {
+        Self::urban()
+            .with_feature(OsmFeature::Amenities)
+            .with_feature(OsmFeature::Tourism)
+    }
+
     /// Add features to this set
     pub fn with_features(mut self, features: Vec<OsmFeature>) -> Self {
         self.features.extend(features);
@@ -242,17 +421,40 @@ impl FeatureSet {
     }
 
     /// Add custom OSM tag queries
-    pub fn with_custom_queries(mut self, queries: Vec<OsmTagQuery>) -> Self {
-        self.custom_queries.extend(queries);
-        self
+    pub fn with_custom_queries(self, queries: Vec<OsmTagQuery>) -> Self {
+        queries.into_iter().fold(self, Self::with_custom_query)
     }
 
     /// Add a single custom query
+    ///
+    /// Queries created with [`OsmTagQuery::exclude`] become exclusions, e.g.
+    /// buildings without garages or roads without `highway=service`.
     pub fn with_custom_query(mut self, query: OsmTagQuery) -> Self {
-        self.custom_queries.push(query);
+        if query.exclude {
+            self.exclusions.push(query);
+        } else {
+            self.custom_queries.push(query);
+        }
         self
     }
 
+    /// Add a custom query whose matches are rasterized as `tile_type`
+    ///
+    /// Overrides apply in the order they were added; the first match wins.
+    pub fn with_custom_query_as(
+        self,
+        key: impl Into<String>,
+        value: Option<impl Into<String>>,
+        tile_type: TileType,
+    ) -> Self {
+        self.with_custom_query(OsmTagQuery::new(key, value).as_tile_type(tile_type))
+    }
+
+    /// Leave out elements with `key` (set to `value`, if given)
+    pub fn excluding(self, key: impl Into<String>, value: Option<impl Into<String>>) -> Self {
+        self.with_custom_query(OsmTagQuery::exclude(key, value))
+    }
+
     /// Remove a feature from this set
     pub fn without_feature(mut self, feature: &OsmFeature) -> Self {
         self.features.remove(feature);
@@ -274,7 +476,12 @@ impl FeatureSet {
         }
 
         // Add custom queries
-        queries.extend(self.custom_queries.clone());
+        queries.extend(
+            self.custom_queries
+                .iter()
+                .filter(|query| !query.exclude)
+                .cloned(),
+        );
 
         // Remove duplicates
         queries.sort_by(|a, b| a.key.cmp(&b.key).then(a.value.cmp(&b.value)));
@@ -283,11 +490,50 @@ impl FeatureSet {
         queries
     }
 
+    /// Estimate how expensive fetching this feature set for `bbox` will be
+    pub fn estimate_query_cost(&self, bbox: &BoundingBox) -> QueryCost {
+        let area_km2 = bbox.area_km2();
+        let density: f64 = self
+            .features
+            .iter()
+            .map(OsmFeature::typical_density_per_km2)
+            .sum::<f64>()
+            + self.custom_queries.len() as f64 * CUSTOM_QUERY_DENSITY_PER_KM2;
+        let estimated_elements = (area_km2 * density).ceil() as u64;
+
+        QueryCost {
+            area_km2,
+            query_count: self.to_osm_queries().len(),
+            estimated_elements,
+            estimated_bytes: estimated_elements * AVERAGE_ELEMENT_BYTES,
+        }
+    }
+
     /// Get the list of included features
     pub fn features(&self) -> &HashSet<OsmFeature> {
         &self.features
     }
 
+    /// Get the exclusion queries
+    pub fn exclusions(&self) -> impl Iterator<Item = &OsmTagQuery> {
+        // Config files may list exclusions among the custom queries
+        self.exclusions
+            .iter()
+            .chain(self.custom_queries.iter().filter(|query| query.exclude))
+    }
+
+    /// Check whether an element with these tags is left out by an exclusion
+    pub fn excludes(&self, tags: &HashMap<String, String>) -> bool {
+        self.exclusions().any(|exclusion| exclusion.matches(tags))
+    }
+
+    /// Get the custom queries overriding the tile type of their matches
+    pub fn tile_type_overrides(&self) -> impl Iterator<Item = (&OsmTagQuery, &TileType)> {
+        self.custom_queries
+            .iter()
+            .filter_map(|query| Some((query, query.tile_type.as_ref()?)))
+    }
+
     /// Get the custom queries
     pub fn custom_queries(&self) -> &[OsmTagQuery] {
         &self.custom_queries
@@ -425,6 +671,42 @@ mod tests {
         assert!(comprehensive.contains_feature(&OsmFeature::Water));
         assert!(comprehensive.contains_feature(&OsmFeature::Railways));
         assert!(comprehensive.contains_feature(&OsmFeature::Amenities));
+
+        let gaming = FeatureSet::gaming();
+        assert!(gaming.contains_feature(&OsmFeature::Buildings));
+        assert!(gaming.contains_feature(&OsmFeature::Tourism));
+    }
+
+    #[test]
+    fn test_regex_and_multi_value_queries() {
+        let roads = OsmTagQuery::any_of("highway", ["primary", "secondary"]);
+        assert_eq!(
+            roads.to_overpass_filter(),
+            "[\"highway\"~\"^(primary|secondary)$\"]"
+        );
+        let shops = OsmTagQuery::regex("shop", "^(kiosk|vacant)$").excluded();
+        assert_eq!(
+            shops.to_overpass_filter(),
+            "[\"shop\"!~\"^(kiosk|vacant)$\"]"
+        );
+        let dotted = OsmTagQuery::any_of("name", ["St. Mary"]);
+        assert_eq!(
+            dotted.to_overpass_filter(),
+            "[\"name\"~\"^(St\\\\. Mary)$\"]"
+        );
+
+        let tags = |value: &str| HashMap::from([("highway".to_string(), value.to_string())]);
+        assert!(roads.matches(&tags("secondary")));
+        assert!(!roads.matches(&tags("secondary_link")));
+        assert!(OsmTagQuery::regex("highway", "_link$").matches(&tags("secondary_link")));
+        assert!(OsmTagQuery::regex("highway", "(").matcher().is_err());
+
+        let set = FeatureSet::new()
+            .with_custom_query(OsmTagQuery::new("shop", None::<String>))
+            .with_custom_query(shops);
+        let restored: FeatureSet =
+            serde_json::from_str(&serde_json::to_string(&set).unwrap()).unwrap();
+        assert!(restored.excludes(&HashMap::from([("shop".to_string(), "kiosk".to_string())])));
     }
 
     #[test]
@@ -563,4 +845,21 @@ mod tests {
         assert_eq!(set.features(), deserialized.features());
         assert_eq!(set.custom_queries(), deserialized.custom_queries());
     }
+
+    #[test]
+    fn test_estimate_query_cost() {
+        let bbox = BoundingBox::new(52.50, 13.38, 52.52, 13.42);
+        let urban = FeatureSet::urban().estimate_query_cost(&bbox);
+        let comprehensive = FeatureSet::comprehensive().estimate_query_cost(&bbox);
+
+        assert!(urban.area_km2 > 5.0 && urban.area_km2 < 7.0);
+        assert!(urban.estimated_elements > 0);
+        assert!(comprehensive.estimated_elements > urban.estimated_elements);
+        assert!(comprehensive.query_count > urban.query_count);
+        assert_eq!(urban.estimated_bytes, urban.estimated_elements * 800);
+
+        let empty = FeatureSet::new().estimate_query_cost(&bbox);
+        assert_eq!(empty.estimated_elements, 0);
+        assert_eq!(empty.query_count, 0);
+    }
 }
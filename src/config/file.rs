@@ -0,0 +1,507 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{FeatureSet, MAX_SLIPPY_ZOOM, OsmConfig, Region};
+use crate::{Crs, GridOrigin, OsmTilesError, Result};
+
+/// Checks that a field value deserializes into the field's type
+type FieldCheck = fn(&Value) -> std::result::Result<(), serde_json::Error>;
+
+fn check<T: DeserializeOwned>(value: &Value) -> std::result::Result<(), serde_json::Error> {
+    T::deserialize(value).map(|_| ())
+}
+
+/// Top-level fields of a config file: name, whether it is required and its type check
+const FIELDS: [(&str, bool, FieldCheck); 11] = [
+    ("region", true, check::<Region>),
+    ("grid_resolution", true, check::<u32>),
+    ("tile_size", true, check::<f32>),
+    ("timeout_seconds", true, check::<u64>),
+    ("features", true, check::<FeatureSet>),
+    ("crs", false, check::<Crs>),
+    ("grid_origin", false, check::<GridOrigin>),
+    ("memory_budget_bytes", false, check::<Option<usize>>),
+    ("slippy_zoom", false, check::<Option<u8>>),
+    ("cell_size_meters", false, check::<Option<f32>>),
+    ("max_data_age_seconds", false, check::<Option<u64>>),
+];
+
+fn field_error(field: &str, message: impl std::fmt::Display) -> OsmTilesError {
+    OsmTilesError::Config(format!("field `{}`: {}", field, message))
+}
+
+/// Reject unknown keys, which are most likely typos of optional fields
+fn check_known_fields<'a>(keys: impl IntoIterator<Item = &'a str>) -> Result<()> {
+    for key in keys {
+        if !FIELDS.iter().any(|(name, _, _)| *name == key) {
+            let expected: Vec<&str> = FIELDS.iter().map(|(name, _, _)| *name).collect();
+            return Err(field_error(
+                key,
+                format!("unknown field, expected one of {}", expected.join(", ")),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl OsmConfig {
+    /// Load a configuration from a JSON document
+    pub fn from_json_str(source: &str) -> Result<Self> {
+        let value: Value =
+            serde_json::from_str(source).map_err(|e| OsmTilesError::json("JSON config", e))?;
+        Self::from_document(value)
+    }
+
+    /// Load a configuration from a TOML document
+    ///
+    /// Tables map to structs and enum variants use their name as key, e.g.
+    /// `region = { City = { name = "Berlin" } }`.
+    #[cfg(feature = "config-toml")]
+    pub fn from_toml_str(source: &str) -> Result<Self> {
+        let document = toml_edit::Document::parse(source)
+            .map_err(|e| OsmTilesError::Parse(format!("Invalid TOML config: {}", e)))?;
+        Self::from_document(toml_table(document.as_table().iter()))
+    }
+
+    /// Load a configuration from a RON document, e.g. `(region: City(name: "Berlin"), ...)`
+    #[cfg(feature = "config-ron")]
+    pub fn from_ron_str(source: &str) -> Result<Self> {
+        let invalid = |e: ron::error::SpannedError| {
+            OsmTilesError::Config(format!("Invalid RON config at {}", e))
+        };
+
+        if let ron::Value::Map(map) = ron::from_str::<ron::Value>(source).map_err(invalid)? {
+            check_known_fields(map.keys().filter_map(|key| match key {
+                ron::Value::String(key) => Some(key.as_str()),
+                _ => None,
+            }))?;
+        }
+
+        let config: Self = ron::from_str(source).map_err(invalid)?;
+        config.check_values()?;
+        Ok(config)
+    }
+
+    /// Load a configuration file, choosing the format by extension
+    ///
+    /// `.json` is always supported, `.toml` and `.ron` need the `config-toml`
+    /// and `config-ron` features.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| OsmTilesError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let result = match extension.as_str() {
+            "json" => Self::from_json_str(&source),
+            #[cfg(feature = "config-toml")]
+            "toml" => Self::from_toml_str(&source),
+            #[cfg(feature = "config-ron")]
+            "ron" => Self::from_ron_str(&source),
+            _ => Err(OsmTilesError::Config(format!(
+                "Unsupported config format '{}'; supported: {}",
+                extension,
+                supported_formats().join(", ")
+            ))),
+        };
+
+        // Name the file in every error so it can be found among several configs
+        result.map_err(|e| match e {
+            OsmTilesError::Config(message) => {
+                OsmTilesError::Config(format!("{}: {}", path.display(), message))
+            }
+            OsmTilesError::Parse(message) => {
+                OsmTilesError::Parse(format!("{}: {}", path.display(), message))
+            }
+            OsmTilesError::ParserError {
+                context,
+                line,
+                column,
+                source,
+            } => OsmTilesError::ParserError {
+                context: format!("{} {}", context, path.display()),
+                line,
+                column,
+                source,
+            },
+            other => other,
+        })
+    }
+
+    /// Deserialize a parsed document, reporting errors with the offending field
+    fn from_document(value: Value) -> Result<Self> {
+        let Value::Object(fields) = &value else {
+            return Err(OsmTilesError::Config(
+                "config must be a table of fields".to_string(),
+            ));
+        };
+
+        check_known_fields(fields.keys().map(String::as_str))?;
+        for (name, required, check) in FIELDS {
+            match fields.get(name) {
+                Some(field) => check(field).map_err(|e| field_error(name, e))?,
+                None if required => return Err(field_error(name, "missing required field")),
+                None => {}
+            }
+        }
+
+        let config: Self =
+            serde_json::from_value(value).map_err(|e| OsmTilesError::Config(e.to_string()))?;
+        config.check_values()?;
+        Ok(config)
+    }
+
+    /// Check value ranges that the field types cannot express
+    fn check_values(&self) -> Result<()> {
+        if self.grid_resolution == 0 {
+            return Err(field_error("grid_resolution", "must be greater than zero"));
+        }
+        if !(self.tile_size.is_finite() && self.tile_size > 0.0) {
+            return Err(field_error(
+                "tile_size",
+                format!("must be positive, got {}", self.tile_size),
+            ));
+        }
+        if let Some(size) = self.cell_size_meters
+            && !(size.is_finite() && size > 0.0)
+        {
+            return Err(field_error(
+                "cell_size_meters",
+                format!("must be positive, got {}", size),
+            ));
+        }
+        if self.timeout_seconds == 0 {
+            return Err(field_error("timeout_seconds", "must be greater than zero"));
+        }
+        if let Some(zoom) = self.slippy_zoom
+            && zoom > MAX_SLIPPY_ZOOM
+        {
+            return Err(field_error(
+                "slippy_zoom",
+                format!("must be at most {}, got {}", MAX_SLIPPY_ZOOM, zoom),
+            ));
+        }
+
+        match &self.region {
+            Region::City { name } => {
+                if name.trim().is_empty() {
+                    return Err(field_error("region.City.name", "must not be empty"));
+                }
+            }
+            Region::BoundingBox(bbox) => {
+                check_latitude("region.BoundingBox.south", bbox.south)?;
+                check_latitude("region.BoundingBox.north", bbox.north)?;
+                check_longitude("region.BoundingBox.west", bbox.west)?;
+                check_longitude("region.BoundingBox.east", bbox.east)?;
+                if bbox.south >= bbox.north {
+                    return Err(field_error(
+                        "region.BoundingBox",
+                        "south must be less than north",
+                    ));
+                }
+                if bbox.west >= bbox.east {
+                    return Err(field_error(
+                        "region.BoundingBox",
+                        "west must be less than east",
+                    ));
+                }
+            }
+            Region::CenterRadius {
+                lat,
+                lon,
+                radius_km,
+            } => {
+                check_latitude("region.CenterRadius.lat", *lat)?;
+                check_longitude("region.CenterRadius.lon", *lon)?;
+                if !(radius_km.is_finite() && *radius_km > 0.0) {
+                    return Err(field_error(
+                        "region.CenterRadius.radius_km",
+                        format!("must be positive, got {}", radius_km),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check_latitude(field: &str, lat: f64) -> Result<()> {
+    if (-90.0..=90.0).contains(&lat) {
+        Ok(())
+    } else {
+        Err(field_error(
+            field,
+            format!("latitude must be within -90..=90, got {}", lat),
+        ))
+    }
+}
+
+fn check_longitude(field: &str, lon: f64) -> Result<()> {
+    if (-180.0..=180.0).contains(&lon) {
+        Ok(())
+    } else {
+        Err(field_error(
+            field,
+            format!("longitude must be within -180..=180, got {}", lon),
+        ))
+    }
+}
+
+/// File extensions accepted by [`OsmConfig::from_path`] with the enabled features
+#[cfg(not(target_arch = "wasm32"))]
+fn supported_formats() -> Vec<&'static str> {
+    let mut formats = vec!["json"];
+    if cfg!(feature = "config-toml") {
+        formats.push("toml");
+    }
+    if cfg!(feature = "config-ron") {
+        formats.push("ron");
+    }
+    formats
+}
+
+#[cfg(feature = "config-toml")]
+pub(super) fn toml_table<'a>(
+    entries: impl Iterator<Item = (&'a str, &'a toml_edit::Item)>,
+) -> Value {
+    Value::Object(
+        entries
+            .map(|(key, item)| (key.to_string(), toml_item(item)))
+            .collect::<serde_json::Map<String, Value>>(),
+    )
+}
+
+#[cfg(feature = "config-toml")]
+fn toml_item(item: &toml_edit::Item) -> Value {
+    use toml_edit::Item;
+
+    match item {
+        Item::None => Value::Null,
+        Item::Value(value) => toml_value(value),
+        Item::Table(table) => toml_table(table.iter()),
+        Item::ArrayOfTables(tables) => Value::Array(
+            tables
+                .iter()
+                .map(|table| toml_table(table.iter()))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(feature = "config-toml")]
+fn toml_value(value: &toml_edit::Value) -> Value {
+    use toml_edit::Value as Toml;
+
+    match value {
+        Toml::String(s) => Value::String(s.value().clone()),
+        Toml::Integer(i) => Value::from(*i.value()),
+        // NaN and infinity have no JSON representation and fail the type check
+        Toml::Float(f) => serde_json::Number::from_f64(*f.value())
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        Toml::Boolean(b) => Value::Bool(*b.value()),
+        Toml::Datetime(d) => Value::String(d.value().to_string()),
+        Toml::Array(array) => Value::Array(array.iter().map(toml_value).collect()),
+        Toml::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml_value(value)))
+                .collect::<serde_json::Map<String, Value>>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_error(result: Result<OsmConfig>) -> String {
+        match result {
+            Err(OsmTilesError::Config(message)) => message,
+            other => panic!("Expected configuration error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_json_config_round_trip() {
+        let config = OsmConfig::for_city("Leipzig").with_slippy_zoom(15);
+        let loaded = OsmConfig::from_json_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert!(matches!(loaded.region, Region::City { ref name } if name == "Leipzig"));
+        assert_eq!(loaded.slippy_zoom, Some(15));
+    }
+
+    #[test]
+    fn test_schema_errors_name_the_field() {
+        let base = serde_json::to_value(OsmConfig::default()).unwrap();
+        let with = |key: &str, value: Value| {
+            let mut document = base.clone();
+            document[key] = value;
+            OsmConfig::from_json_str(&document.to_string())
+        };
+
+        let message = config_error(with("grid_resolution", Value::from("high")));
+        assert!(
+            message.starts_with("field `grid_resolution`"),
+            "{}",
+            message
+        );
+
+        let message = config_error(with("slipy_zoom", Value::from(14)));
+        assert!(
+            message.contains("`slipy_zoom`: unknown field"),
+            "{}",
+            message
+        );
+
+        let message = config_error(with("tile_size", Value::from(-1.0)));
+        assert!(
+            message.contains("`tile_size`: must be positive"),
+            "{}",
+            message
+        );
+
+        let message = config_error(with("cell_size_meters", Value::from(0.0)));
+        assert!(
+            message.contains("`cell_size_meters`: must be positive"),
+            "{}",
+            message
+        );
+
+        let message = config_error(with(
+            "region",
+            serde_json::json!({ "CenterRadius": { "lat": 95.0, "lon": 13.4, "radius_km": 2.0 } }),
+        ));
+        assert!(message.contains("`region.CenterRadius.lat`"), "{}", message);
+
+        let mut document = base.clone();
+        document.as_object_mut().unwrap().remove("features");
+        let message = config_error(OsmConfig::from_json_str(&document.to_string()));
+        assert_eq!(message, "field `features`: missing required field");
+    }
+
+    #[cfg(feature = "config-toml")]
+    #[test]
+    fn test_from_toml_str() {
+        let config = OsmConfig::from_toml_str(
+            r#"
+            grid_resolution = 200
+            tile_size = 5
+            timeout_seconds = 60
+            slippy_zoom = 16
+
+            [region.BoundingBox]
+            south = 52.50
+            west = 13.38
+            north = 52.52
+            east = 13.42
+
+            [features]
+            features = ["Roads", "Water"]
+
+            [[features.custom_queries]]
+            key = "amenity"
+            value = "cafe"
+
+            [[features.exclusions]]
+            key = "highway"
+            value = "service"
+            exclude = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.grid_resolution, 200);
+        assert_eq!(config.tile_size, 5.0);
+        assert_eq!(config.slippy_zoom, Some(16));
+        assert_eq!(config.region.bounding_box().unwrap().north, 52.52);
+        assert!(config.features.contains_feature(&crate::OsmFeature::Water));
+        assert_eq!(config.features.custom_queries().len(), 1);
+        assert_eq!(config.features.exclusions().count(), 1);
+
+        let message = config_error(OsmConfig::from_toml_str(
+            r#"
+            grid_resolution = 200
+            tile_size = 5
+            timeout_seconds = 60
+            region = { City = { name = "Berlin" } }
+            features = { features = ["Roadz"] }
+            "#,
+        ));
+        assert!(message.starts_with("field `features`"), "{}", message);
+        assert!(message.contains("Roadz"), "{}", message);
+
+        assert!(matches!(
+            OsmConfig::from_toml_str("grid_resolution = "),
+            Err(OsmTilesError::Parse(_))
+        ));
+    }
+
+    #[cfg(feature = "config-ron")]
+    #[test]
+    fn test_from_ron_str() {
+        let source = r#"(
+            region: City(name: "Hamburg"),
+            grid_resolution: 120,
+            tile_size: 7.5,
+            timeout_seconds: 30,
+            features: (features: [Roads, Buildings], custom_queries: []),
+        )"#;
+        let config = OsmConfig::from_ron_str(source).unwrap();
+        assert!(matches!(config.region, Region::City { ref name } if name == "Hamburg"));
+        assert!(
+            config
+                .features
+                .contains_feature(&crate::OsmFeature::Buildings)
+        );
+
+        let message = config_error(OsmConfig::from_ron_str(
+            &source.replace("grid_resolution", "grid_resolutoin"),
+        ));
+        assert!(
+            message.contains("`grid_resolutoin`: unknown field"),
+            "{}",
+            message
+        );
+
+        let message = config_error(OsmConfig::from_ron_str(&source.replace("120", "0")));
+        assert!(message.contains("`grid_resolution`"), "{}", message);
+    }
+
+    #[test]
+    fn test_from_path_selects_format_by_extension() {
+        let dir = std::env::temp_dir().join(format!("osm-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let json = dir.join("map.json");
+        std::fs::write(&json, serde_json::to_string(&OsmConfig::default()).unwrap()).unwrap();
+        assert_eq!(OsmConfig::from_path(&json).unwrap().grid_resolution, 100);
+
+        let yaml = dir.join("map.yaml");
+        std::fs::write(&yaml, "grid_resolution: 100").unwrap();
+        let message = config_error(OsmConfig::from_path(&yaml));
+        assert!(message.contains("map.yaml"), "{}", message);
+        assert!(
+            message.contains("Unsupported config format 'yaml'"),
+            "{}",
+            message
+        );
+
+        let missing = OsmConfig::from_path(dir.join("missing.json")).unwrap_err();
+        assert!(matches!(missing, OsmTilesError::Io { .. }), "{}", missing);
+        assert!(
+            missing.to_string().starts_with("Failed to read"),
+            "{}",
+            missing
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,13 +1,23 @@
+mod bookmarks;
 mod builder;
 mod features;
+mod file;
+mod presets;
 mod region;
+mod slippy;
+mod validation;
 
+pub use bookmarks::*;
 pub use builder::*;
 pub use features::*;
 pub use region::*;
+pub use slippy::*;
+pub use validation::*;
 
 use serde::{Deserialize, Serialize};
 
+use crate::{Crs, GridOrigin};
+
 /// Configuration for OSM data download and grid generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OsmConfig {
@@ -21,6 +31,24 @@ pub struct OsmConfig {
     pub timeout_seconds: u64,
     /// Features to include in the grid generation
     pub features: FeatureSet,
+    /// Coordinate reference system used to lay out the grid
+    #[serde(default)]
+    pub crs: Crs,
+    /// Corner of the area at which grid row 0 starts
+    #[serde(default)]
+    pub grid_origin: GridOrigin,
+    /// Approximate memory budget for grid generation (in bytes, unlimited if None)
+    #[serde(default)]
+    pub memory_budget_bytes: Option<usize>,
+    /// Align the fetched area and generated grids to XYZ slippy tiles at this zoom level
+    #[serde(default)]
+    pub slippy_zoom: Option<u8>,
+    /// Target tile edge length in meters; replaces `grid_resolution` when set
+    #[serde(default)]
+    pub cell_size_meters: Option<f32>,
+    /// Oldest acceptable OSM data (in seconds since its database snapshot, unlimited if None)
+    #[serde(default)]
+    pub max_data_age_seconds: Option<u64>,
 }
 
 impl Default for OsmConfig {
@@ -31,6 +59,12 @@ impl Default for OsmConfig {
             tile_size: 10.0,
             timeout_seconds: 30,
             features: FeatureSet::default(),
+            crs: Crs::default(),
+            grid_origin: GridOrigin::default(),
+            memory_budget_bytes: None,
+            slippy_zoom: None,
+            cell_size_meters: None,
+            max_data_age_seconds: None,
         }
     }
 }
@@ -50,6 +84,15 @@ impl OsmConfig {
         self
     }
 
+    /// Size tiles by their edge length in meters instead of cells per degree
+    ///
+    /// The grid resolution is derived from the resolved bounding box, so a
+    /// 5 m grid has 5 m tiles in Oslo as well as in Lagos.
+    pub fn with_cell_size_meters(mut self, meters: f32) -> Self {
+        self.cell_size_meters = Some(meters);
+        self
+    }
+
     /// Set the tile size in meters
     pub fn with_tile_size(mut self, size: f32) -> Self {
         self.tile_size = size;
@@ -62,12 +105,96 @@ impl OsmConfig {
         self
     }
 
+    /// Treat OSM data older than `seconds` as stale
+    pub fn with_max_data_age(mut self, seconds: u64) -> Self {
+        self.max_data_age_seconds = Some(seconds);
+        self
+    }
+
+    /// Get the maximum data age, if one is set
+    pub fn max_data_age(&self) -> Option<std::time::Duration> {
+        self.max_data_age_seconds
+            .map(std::time::Duration::from_secs)
+    }
+
     /// Configure which features to include
     pub fn with_features(mut self, features: FeatureSet) -> Self {
         self.features = features;
         self
     }
 
+    /// Set the coordinate reference system of the generated grid
+    pub fn with_crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    /// Set the corner of the area at which grid row 0 starts
+    pub fn with_grid_origin(mut self, origin: GridOrigin) -> Self {
+        self.grid_origin = origin;
+        self
+    }
+
+    /// Abort generation when estimated memory usage would exceed the budget
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget_bytes = Some(bytes);
+        self
+    }
+
+    /// Align the fetched area and generated grids to slippy tiles at the given zoom level
+    pub fn with_slippy_zoom(mut self, zoom: u8) -> Self {
+        self.slippy_zoom = Some(zoom.min(MAX_SLIPPY_ZOOM));
+        self
+    }
+
+    /// Snap a resolved bounding box outward to slippy tile boundaries if alignment is enabled
+    pub fn aligned_bounds(&self, bbox: BoundingBox) -> BoundingBox {
+        match self.slippy_zoom {
+            Some(zoom) => SlippyTile::covering_bounds(&bbox, zoom),
+            None => bbox,
+        }
+    }
+
+    /// Get the grid dimensions this configuration produces for a bounding box
+    ///
+    /// Generators may clamp the result further to their own maximum grid size.
+    pub fn grid_dimensions_for(&self, bbox: &BoundingBox) -> (usize, usize) {
+        let grid_width = match self.cell_size_meters {
+            Some(size) => (bbox.width_m() / size as f64).ceil() as usize,
+            // Grid resolution is cells per degree of longitude
+            None => (bbox.width() * self.grid_resolution as f64).ceil() as usize,
+        };
+        let grid_height = match (self.crs, self.cell_size_meters) {
+            (Crs::Wgs84, Some(size)) => (bbox.height_m() / size as f64).ceil() as usize,
+            (Crs::Wgs84, None) => (bbox.height() * self.grid_resolution as f64).ceil() as usize,
+            (Crs::WebMercator, _) => {
+                // Keep tiles square in projected space so they are square in meters
+                let projection = self.crs.projection();
+                let (min_x, min_y) = projection.project(bbox.south, bbox.west);
+                let (max_x, max_y) = projection.project(bbox.north, bbox.east);
+                let aspect = (max_y - min_y) / (max_x - min_x);
+                (grid_width as f64 * aspect).ceil() as usize
+            }
+        };
+
+        // Enforce minimum size
+        (grid_width.max(10), grid_height.max(10))
+    }
+
+    /// Get the grid resolution (cells per degree of longitude) used for a bounding box
+    ///
+    /// This is `grid_resolution`, unless a cell size in meters is set, in
+    /// which case it is derived from the bounding box's center latitude.
+    pub fn grid_resolution_for(&self, bbox: &BoundingBox) -> u32 {
+        match self.cell_size_meters {
+            Some(size) if bbox.width() > 0.0 => {
+                let meters_per_degree = bbox.width_m() / bbox.width();
+                (meters_per_degree / size as f64).round().max(1.0) as u32
+            }
+            _ => self.grid_resolution,
+        }
+    }
+
     /// Create a builder for more complex configuration
     pub fn builder() -> OsmConfigBuilder {
         OsmConfigBuilder::new()
@@ -78,6 +205,35 @@ impl OsmConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slippy_aligned_bounds() {
+        let bbox = BoundingBox::new(52.50, 13.38, 52.52, 13.42);
+        assert_eq!(OsmConfig::default().aligned_bounds(bbox.clone()), bbox);
+
+        let config = OsmConfig::default().with_slippy_zoom(16);
+        let aligned = config.aligned_bounds(bbox.clone());
+        assert!(aligned.south <= bbox.south && aligned.north >= bbox.north);
+        assert!(aligned.west <= bbox.west && aligned.east >= bbox.east);
+        assert_eq!(
+            aligned.north,
+            tile_for(bbox.north, bbox.west, 16).bounds().north
+        );
+    }
+
+    #[test]
+    fn test_cell_size_meters() {
+        let bbox = BoundingBox::new(60.0, 10.0, 60.01, 10.01);
+        let config = OsmConfig::default().with_cell_size_meters(5.0);
+
+        // A degree of longitude is about 55.6 km at 60°N, half of it at the equator
+        assert_eq!(config.grid_resolution_for(&bbox), 11_118);
+        assert_eq!(config.grid_dimensions_for(&bbox), (112, 223));
+        assert_eq!(
+            OsmConfig::default().grid_resolution_for(&bbox),
+            OsmConfig::default().grid_resolution
+        );
+    }
+
     #[test]
     fn test_osm_config_default() {
         let config = OsmConfig::default();
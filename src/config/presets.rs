@@ -0,0 +1,113 @@
+use std::sync::{OnceLock, RwLock};
+
+use super::FeatureSet;
+
+/// Named presets in registration order
+type Presets = Vec<(String, FeatureSet)>;
+
+fn presets() -> &'static RwLock<Presets> {
+    static PRESETS: OnceLock<RwLock<Presets>> = OnceLock::new();
+    PRESETS.get_or_init(|| {
+        let builtin = [
+            ("urban", FeatureSet::urban()),
+            ("transportation", FeatureSet::transportation()),
+            ("natural", FeatureSet::natural()),
+            ("comprehensive", FeatureSet::comprehensive()),
+            ("gaming", FeatureSet::gaming()),
+        ];
+        RwLock::new(
+            builtin
+                .into_iter()
+                .map(|(name, set)| (name.to_string(), set))
+                .collect(),
+        )
+    })
+}
+
+/// Preset names are matched case-insensitively, ignoring surrounding whitespace
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Process-wide registry of named feature presets
+///
+/// "urban", "transportation", "natural", "comprehensive" and "gaming" are
+/// registered from the start. Applications register their own presets once at
+/// startup so CLIs and UIs can offer them by name:
+///
+/// ```rust
+/// use bevy_osm_tiles::{FeatureSet, OsmFeature};
+///
+/// FeatureSet::register_preset("rail", FeatureSet::new().with_feature(OsmFeature::Railways));
+/// let rail = FeatureSet::from_preset_name("Rail").unwrap();
+/// assert!(rail.contains_feature(&OsmFeature::Railways));
+/// # FeatureSet::unregister_preset("rail");
+/// ```
+impl FeatureSet {
+    /// Look up a preset by name
+    pub fn from_preset_name(name: &str) -> Option<FeatureSet> {
+        let name = normalize(name);
+        presets()
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(existing, _)| *existing == name)
+            .map(|(_, set)| set.clone())
+    }
+
+    /// Get the names of all presets in registration order
+    pub fn preset_names() -> Vec<String> {
+        presets()
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Register a preset under `name`, replacing any preset of the same name
+    pub fn register_preset(name: &str, set: FeatureSet) {
+        let name = normalize(name);
+        let mut presets = presets().write().unwrap();
+        match presets.iter_mut().find(|(existing, _)| *existing == name) {
+            Some(entry) => entry.1 = set,
+            None => presets.push((name, set)),
+        }
+    }
+
+    /// Remove a preset; returns whether it was registered
+    pub fn unregister_preset(name: &str) -> bool {
+        let name = normalize(name);
+        let mut presets = presets().write().unwrap();
+        let before = presets.len();
+        presets.retain(|(existing, _)| *existing != name);
+        presets.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsmFeature;
+
+    #[test]
+    fn test_builtin_and_custom_presets() {
+        let gaming = FeatureSet::from_preset_name(" Gaming ").unwrap();
+        assert!(gaming.contains_feature(&OsmFeature::Tourism));
+        assert_eq!(FeatureSet::preset_names()[0], "urban");
+        assert!(FeatureSet::from_preset_name("preset-test").is_none());
+
+        FeatureSet::register_preset("Preset-Test", FeatureSet::natural());
+        FeatureSet::register_preset("preset-test", FeatureSet::transportation());
+        let names = FeatureSet::preset_names();
+        assert_eq!(
+            names.iter().filter(|name| *name == "preset-test").count(),
+            1
+        );
+        let custom = FeatureSet::from_preset_name("preset-test").unwrap();
+        assert!(custom.contains_feature(&OsmFeature::Railways));
+
+        assert!(FeatureSet::unregister_preset("preset-test"));
+        assert!(!FeatureSet::unregister_preset("preset-test"));
+    }
+}
@@ -1,6 +1,7 @@
-use geo::{Destination, Distance, Haversine, Point};
 use serde::{Deserialize, Serialize};
 
+use crate::geoutil::{destination, distance_m};
+
 /// Represents a geographic bounding box for OSM data requests
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BoundingBox {
@@ -42,24 +43,21 @@ impl BoundingBox {
         self.north - self.south
     }
 
-    /// Get the approximate area in square kilometers using geographic calculations
-    pub fn area_km2(&self) -> f64 {
+    /// Get the east-west extent in meters, measured along the center latitude
+    pub fn width_m(&self) -> f64 {
         let center = self.center();
+        distance_m((center.0, self.west), (center.0, self.east))
+    }
 
-        // Calculate distances using Haversine formula
-        let width_km = {
-            let west_point = Point::new(self.west, center.0);
-            let east_point = Point::new(self.east, center.0);
-            Haversine.distance(west_point, east_point) / 1000.0 // Convert to km
-        };
-
-        let height_km = {
-            let south_point = Point::new(center.1, self.south);
-            let north_point = Point::new(center.1, self.north);
-            Haversine.distance(south_point, north_point) / 1000.0 // Convert to km
-        };
+    /// Get the north-south extent in meters
+    pub fn height_m(&self) -> f64 {
+        let center = self.center();
+        distance_m((self.south, center.1), (self.north, center.1))
+    }
 
-        width_km * height_km
+    /// Get the approximate area in square kilometers using geographic calculations
+    pub fn area_km2(&self) -> f64 {
+        self.width_m() * self.height_m() / 1_000_000.0
     }
 
     /// Check if this bounding box contains a point
@@ -72,25 +70,13 @@ impl BoundingBox {
         let center = self.center();
         let distance_meters = distance_km * 1000.0;
 
-        // Calculate new bounds by moving from the current bounds outward
-        let current_north = Point::new(center.1, self.north);
-        let new_north = Haversine.destination(current_north, 0.0, distance_meters); // 0° = North
-
-        let current_south = Point::new(center.1, self.south);
-        let new_south = Haversine.destination(current_south, 180.0, distance_meters); // 180° = South
-
-        let current_east = Point::new(self.east, center.0);
-        let new_east = Haversine.destination(current_east, 90.0, distance_meters); // 90° = East
-
-        let current_west = Point::new(self.west, center.0);
-        let new_west = Haversine.destination(current_west, 270.0, distance_meters); // 270° = West
+        // Move each edge outward from its midpoint
+        let (north, _) = destination((self.north, center.1), 0.0, distance_meters);
+        let (south, _) = destination((self.south, center.1), 180.0, distance_meters);
+        let (_, east) = destination((center.0, self.east), 90.0, distance_meters);
+        let (_, west) = destination((center.0, self.west), 270.0, distance_meters);
 
-        BoundingBox::new(
-            new_south.y(), // latitude
-            new_west.x(),  // longitude
-            new_north.y(), // latitude
-            new_east.x(),  // longitude
-        )
+        BoundingBox::new(south, west, north, east)
     }
 }
 
@@ -124,6 +110,19 @@ impl Region {
             radius_km,
         }
     }
+
+    /// Get the bounding box of this region if it can be determined without geocoding
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        match self {
+            Self::City { .. } => None,
+            Self::BoundingBox(bbox) => Some(bbox.clone()),
+            Self::CenterRadius {
+                lat,
+                lon,
+                radius_km,
+            } => Some(BoundingBox::new(*lat, *lon, *lat, *lon).expand_by_km(*radius_km)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -330,4 +329,18 @@ mod tests {
         let inverted_lon = BoundingBox::new(52.0, 14.0, 53.0, 13.0); // west > east
         assert_eq!(inverted_lon.width(), -1.0);
     }
+
+    #[test]
+    fn test_region_bounding_box() {
+        assert!(Region::city("Berlin").bounding_box().is_none());
+
+        let bbox = Region::bbox(52.0, 13.0, 53.0, 14.0).bounding_box().unwrap();
+        assert_eq!(bbox, BoundingBox::new(52.0, 13.0, 53.0, 14.0));
+
+        let around = Region::center_radius(52.52, 13.405, 5.0)
+            .bounding_box()
+            .unwrap();
+        assert!(around.contains(52.52, 13.405));
+        assert!((around.height() * 111.0 - 10.0).abs() < 0.5);
+    }
 }
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use super::BoundingBox;
+
+/// Highest zoom level supported for slippy tile alignment
+pub const MAX_SLIPPY_ZOOM: u8 = 24;
+
+/// A tile in the standard XYZ ("slippy map") tile scheme
+///
+/// Tiles follow the OpenStreetMap convention: Web Mercator, `x` grows eastward
+/// from the antimeridian and `y` grows southward from the north edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SlippyTile {
+    /// Zoom level
+    pub z: u8,
+    /// Column index
+    pub x: u32,
+    /// Row index
+    pub y: u32,
+}
+
+impl SlippyTile {
+    /// Create a tile from its z/x/y indices
+    pub fn new(z: u8, x: u32, y: u32) -> Self {
+        Self { z, x, y }
+    }
+
+    /// Get the tile containing the given coordinates at zoom level `z`
+    pub fn containing(lat: f64, lon: f64, z: u8) -> Self {
+        let z = z.min(MAX_SLIPPY_ZOOM);
+        let n = tiles_per_axis(z);
+        let (x, y) = fractional_tile(lat, lon, z);
+
+        Self {
+            z,
+            x: (x.floor().max(0.0) as u32).min(n - 1),
+            y: (y.floor().max(0.0) as u32).min(n - 1),
+        }
+    }
+
+    /// Get the geographic bounds of this tile
+    pub fn bounds(&self) -> BoundingBox {
+        let n = tiles_per_axis(self.z) as f64;
+        let lon = |x: f64| x / n * 360.0 - 180.0;
+        let lat = |y: f64| (PI * (1.0 - 2.0 * y / n)).sinh().atan().to_degrees();
+
+        BoundingBox::new(
+            lat(self.y as f64 + 1.0),
+            lon(self.x as f64),
+            lat(self.y as f64),
+            lon(self.x as f64 + 1.0),
+        )
+    }
+
+    /// Get the cache key in "z/x/y" form
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.z, self.x, self.y)
+    }
+
+    /// Parse a "z/x/y" cache key
+    pub fn from_key(key: &str) -> Option<Self> {
+        let mut parts = key.split('/');
+        let z = parts.next()?.parse().ok()?;
+        let x = parts.next()?.parse().ok()?;
+        let y = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || z > MAX_SLIPPY_ZOOM {
+            return None;
+        }
+
+        let n = tiles_per_axis(z);
+        (x < n && y < n).then_some(Self { z, x, y })
+    }
+
+    /// Get all tiles at zoom level `z` that intersect the bounding box, row by row
+    pub fn covering(bbox: &BoundingBox, z: u8) -> Vec<SlippyTile> {
+        let north_west = Self::containing(bbox.north, bbox.west, z);
+        let south_east = Self::containing(bbox.south, bbox.east, z);
+
+        let mut tiles = Vec::new();
+        for y in north_west.y..=south_east.y {
+            for x in north_west.x..=south_east.x {
+                tiles.push(Self::new(north_west.z, x, y));
+            }
+        }
+        tiles
+    }
+
+    /// Get the bounding box snapped outward to tile boundaries at zoom level `z`
+    pub fn covering_bounds(bbox: &BoundingBox, z: u8) -> BoundingBox {
+        let north_west = Self::containing(bbox.north, bbox.west, z).bounds();
+        let south_east = Self::containing(bbox.south, bbox.east, z).bounds();
+        BoundingBox::new(
+            south_east.south,
+            north_west.west,
+            north_west.north,
+            south_east.east,
+        )
+    }
+}
+
+impl std::fmt::Display for SlippyTile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+/// Get the slippy tile containing the given coordinates at zoom level `z`
+pub fn tile_for(lat: f64, lon: f64, z: u8) -> SlippyTile {
+    SlippyTile::containing(lat, lon, z)
+}
+
+fn tiles_per_axis(z: u8) -> u32 {
+    1u32 << z
+}
+
+/// Get fractional tile coordinates of a point
+fn fractional_tile(lat: f64, lon: f64, z: u8) -> (f64, f64) {
+    let n = tiles_per_axis(z) as f64;
+    let lat_rad = lat.to_radians();
+    let x = (lon + 180.0) / 360.0 * n;
+    let y = (1.0 - lat_rad.tan().asinh() / PI) / 2.0 * n;
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_for_known_locations() {
+        // Berlin Brandenburg Gate at zoom 16
+        assert_eq!(
+            tile_for(52.5163, 13.3777, 16),
+            SlippyTile::new(16, 35203, 21493)
+        );
+        // Whole world at zoom 0
+        assert_eq!(tile_for(52.5163, 13.3777, 0), SlippyTile::new(0, 0, 0));
+        // Poles are clamped to the edge rows
+        assert_eq!(tile_for(89.9, 0.0, 2).y, 0);
+        assert_eq!(tile_for(-89.9, 179.9, 2), SlippyTile::new(2, 3, 3));
+    }
+
+    #[test]
+    fn test_tile_bounds_contain_point() {
+        let tile = tile_for(52.5163, 13.3777, 16);
+        let bounds = tile.bounds();
+        assert!(bounds.contains(52.5163, 13.3777));
+        assert!(bounds.north > bounds.south);
+        assert!(bounds.east > bounds.west);
+
+        // Neighbouring tiles share edges
+        let east = SlippyTile::new(tile.z, tile.x + 1, tile.y).bounds();
+        assert!((east.west - bounds.east).abs() < 1e-12);
+        let south = SlippyTile::new(tile.z, tile.x, tile.y + 1).bounds();
+        assert!((south.north - bounds.south).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_tile_keys() {
+        let tile = SlippyTile::new(16, 35203, 21493);
+        assert_eq!(tile.key(), "16/35203/21493");
+        assert_eq!(tile.to_string(), "16/35203/21493");
+        assert_eq!(SlippyTile::from_key("16/35203/21493"), Some(tile));
+        assert_eq!(SlippyTile::from_key("2/4/0"), None);
+        assert_eq!(SlippyTile::from_key("16/1"), None);
+        assert_eq!(SlippyTile::from_key("a/b/c"), None);
+    }
+
+    #[test]
+    fn test_covering_tiles() {
+        let start = SlippyTile::new(16, 35203, 21493).bounds();
+        let end = SlippyTile::new(16, 35205, 21494).bounds();
+        let bbox = BoundingBox::new(
+            end.south + 1e-6,
+            start.west + 1e-6,
+            start.north - 1e-6,
+            end.east - 1e-6,
+        );
+
+        let tiles = SlippyTile::covering(&bbox, 16);
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[0], SlippyTile::new(16, 35203, 21493));
+        assert_eq!(tiles[5], SlippyTile::new(16, 35205, 21494));
+
+        let snapped = SlippyTile::covering_bounds(&bbox, 16);
+        assert!((snapped.north - start.north).abs() < 1e-12);
+        assert!((snapped.east - end.east).abs() < 1e-12);
+    }
+}
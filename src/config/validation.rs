@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+
+use super::{BoundingBox, OsmConfig, QueryCost};
+use crate::{
+    GeneratorCapabilities, OsmTilesError, ProviderCapabilities, Result, estimate_grid_bytes,
+};
+
+/// Severity of a validation finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidationSeverity {
+    /// The request will likely succeed but may be slow or lossy
+    Warning,
+    /// The request is expected to fail or be rejected
+    Error,
+}
+
+/// A single finding of a configuration validation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// How serious the finding is
+    pub severity: ValidationSeverity,
+    /// Human-readable description including a suggested fix
+    pub message: String,
+}
+
+/// Limits a configuration is validated against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationLimits {
+    /// Area above which a warning is issued (in km²)
+    pub warn_area_km2: f64,
+    /// Area above which the request is rejected (in km²)
+    pub max_area_km2: f64,
+    /// Maximum grid size the generator produces without downscaling
+    pub max_grid_size: Option<(usize, usize)>,
+    /// Estimated element count above which a warning is issued
+    pub warn_elements: u64,
+    /// Assumed download throughput used to check the timeout (in bytes per second)
+    pub throughput_bytes_per_sec: u64,
+}
+
+impl Default for ValidationLimits {
+    fn default() -> Self {
+        Self {
+            warn_area_km2: 1000.0,
+            max_area_km2: 5000.0,
+            max_grid_size: Some((5000, 5000)),
+            warn_elements: 1_000_000,
+            throughput_bytes_per_sec: 500_000,
+        }
+    }
+}
+
+impl ValidationLimits {
+    /// Derive limits from the capabilities of a provider, without a grid size limit
+    ///
+    /// The provider's recommended area triggers a warning, and five times that
+    /// area is rejected.
+    pub fn for_provider(provider: &ProviderCapabilities) -> Self {
+        let defaults = Self::default();
+        let warn_area_km2 = provider.max_area_km2.unwrap_or(f64::INFINITY);
+        Self {
+            warn_area_km2,
+            max_area_km2: warn_area_km2 * defaults.max_area_km2 / defaults.warn_area_km2,
+            max_grid_size: None,
+            ..defaults
+        }
+    }
+
+    /// Derive limits from the capabilities of a provider and a generator
+    pub fn from_capabilities(
+        provider: &ProviderCapabilities,
+        generator: &GeneratorCapabilities,
+    ) -> Self {
+        Self {
+            max_grid_size: generator.max_grid_size,
+            ..Self::for_provider(provider)
+        }
+    }
+
+    /// Set the maximum grid size
+    pub fn with_max_grid_size(mut self, width: usize, height: usize) -> Self {
+        self.max_grid_size = Some((width, height));
+        self
+    }
+
+    /// Set the warning and rejection area thresholds
+    pub fn with_area_limits(mut self, warn_km2: f64, max_km2: f64) -> Self {
+        self.warn_area_km2 = warn_km2;
+        self.max_area_km2 = max_km2;
+        self
+    }
+}
+
+/// Result of validating a configuration before any data is downloaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Estimated query cost, if the region could be resolved offline
+    pub cost: Option<QueryCost>,
+    /// Grid dimensions the configuration would produce, before generator downscaling
+    pub grid_dimensions: Option<(usize, usize)>,
+    /// Estimated peak memory of grid generation (in bytes)
+    pub estimated_memory_bytes: Option<usize>,
+    /// Area limit (in km²) the region exceeds, if it does
+    pub exceeded_area_limit_km2: Option<f64>,
+    /// All findings in the order they were detected
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    fn new() -> Self {
+        Self {
+            cost: None,
+            grid_dimensions: None,
+            estimated_memory_bytes: None,
+            exceeded_area_limit_km2: None,
+            issues: Vec::new(),
+        }
+    }
+
+    fn warn(&mut self, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: ValidationSeverity::Warning,
+            message,
+        });
+    }
+
+    fn error(&mut self, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: ValidationSeverity::Error,
+            message,
+        });
+    }
+
+    /// Check whether no errors were found (warnings are allowed)
+    pub fn is_ok(&self) -> bool {
+        !self.has_errors()
+    }
+
+    /// Check whether any errors were found
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Iterate over warnings
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == ValidationSeverity::Warning)
+    }
+
+    /// Iterate over errors
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == ValidationSeverity::Error)
+    }
+
+    /// Log warnings and convert errors into a configuration error
+    ///
+    /// An oversized area is reported as `QueryTooLarge`, since shrinking the
+    /// region is the first thing to fix.
+    pub fn into_result(self) -> Result<Self> {
+        for warning in self.warnings() {
+            tracing::warn!("{}", warning.message);
+        }
+
+        if !self.has_errors() {
+            return Ok(self);
+        }
+        if let (Some(max_area_km2), Some(cost)) = (self.exceeded_area_limit_km2, &self.cost) {
+            return Err(OsmTilesError::QueryTooLarge {
+                area_km2: cost.area_km2,
+                max_area_km2,
+            });
+        }
+
+        let messages: Vec<&str> = self.errors().map(|issue| issue.message.as_str()).collect();
+        Err(OsmTilesError::Config(messages.join("; ")))
+    }
+}
+
+impl OsmConfig {
+    /// Validate this configuration against the default limits
+    ///
+    /// Size checks are skipped for city regions, which need geocoding to
+    /// resolve; use [`OsmConfig::validate_bounds`] once the bounds are known.
+    pub fn validate(&self) -> ValidationReport {
+        match self.region.bounding_box() {
+            Some(bbox) => self.validate_bounds(&bbox, &ValidationLimits::default()),
+            None => self.validate_settings(),
+        }
+    }
+
+    /// Validate this configuration for a resolved bounding box
+    pub fn validate_bounds(
+        &self,
+        bbox: &BoundingBox,
+        limits: &ValidationLimits,
+    ) -> ValidationReport {
+        let mut report = self.validate_settings();
+        let bbox = self.aligned_bounds(bbox.clone());
+
+        if bbox.south >= bbox.north || bbox.west >= bbox.east {
+            report.error(format!(
+                "Bounding box is empty or inverted: {},{},{},{}",
+                bbox.south, bbox.west, bbox.north, bbox.east
+            ));
+            return report;
+        }
+
+        let cost = self.features.estimate_query_cost(&bbox);
+        if cost.area_km2 > limits.max_area_km2 {
+            report.error(
+                OsmTilesError::QueryTooLarge {
+                    area_km2: cost.area_km2,
+                    max_area_km2: limits.max_area_km2,
+                }
+                .to_string(),
+            );
+            report.exceeded_area_limit_km2 = Some(limits.max_area_km2);
+        } else if cost.area_km2 > limits.warn_area_km2 {
+            report.warn(format!(
+                "Large area of {:.0} km² requested; the download may be slow or time out",
+                cost.area_km2
+            ));
+        }
+
+        if cost.estimated_elements > limits.warn_elements {
+            report.warn(format!(
+                "About {} elements expected for {} tag filters; consider fewer features",
+                cost.estimated_elements, cost.query_count
+            ));
+        }
+
+        let estimated_seconds = cost.estimated_bytes / limits.throughput_bytes_per_sec.max(1);
+        if estimated_seconds > self.timeout_seconds {
+            report.warn(format!(
+                "Estimated download time of {}s exceeds the {}s timeout",
+                estimated_seconds, self.timeout_seconds
+            ));
+        }
+
+        let (width, height) = self.grid_dimensions_for(&bbox);
+        if let Some((max_width, max_height)) = limits.max_grid_size
+            && (width > max_width || height > max_height)
+        {
+            let scale = (max_width as f64 / width as f64).min(max_height as f64 / height as f64);
+            let suggestion = match self.cell_size_meters {
+                Some(size) => format!("a cell size of {:.1} m or more", size as f64 / scale),
+                None => format!(
+                    "a grid resolution of {} or lower",
+                    (self.grid_resolution as f64 * scale).floor().max(1.0) as u32
+                ),
+            };
+            report.warn(format!(
+                "Grid of {}x{} exceeds the generator maximum of {}x{} and will be downscaled. Use {}",
+                width, height, max_width, max_height, suggestion
+            ));
+        }
+
+        // Downscaled grids are at most as large as the maximum size
+        let fitted = limits
+            .max_grid_size
+            .map_or((width, height), |(w, h)| (width.min(w), height.min(h)));
+        let estimated_memory = estimate_grid_bytes(fitted.0, fitted.1)
+            .saturating_add(cost.estimated_bytes.try_into().unwrap_or(usize::MAX));
+        if let Some(budget) = self.memory_budget_bytes
+            && estimated_memory > budget
+        {
+            report.error(format!(
+                "Estimated memory of ~{} bytes exceeds the budget of {} bytes. Lower the grid resolution or choose a smaller region",
+                estimated_memory, budget
+            ));
+        }
+
+        report.cost = Some(cost);
+        report.grid_dimensions = Some((width, height));
+        report.estimated_memory_bytes = Some(estimated_memory);
+        report
+    }
+
+    /// Validate settings that do not depend on the region size
+    fn validate_settings(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+
+        if self.grid_resolution == 0 {
+            report.error("Grid resolution must be greater than zero".to_string());
+        }
+        if self.tile_size <= 0.0 {
+            report.error(format!(
+                "Tile size must be positive, got {}",
+                self.tile_size
+            ));
+        }
+        if self.timeout_seconds == 0 {
+            report.error("Timeout must be greater than zero".to_string());
+        }
+        if self.features.is_empty() {
+            report.warn("No features selected; the generated grid will be empty".to_string());
+        }
+        let queries = self.features.to_osm_queries();
+        for query in queries.iter().chain(self.features.exclusions()) {
+            if let Err(e) = query.matcher() {
+                report.error(e.to_string());
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FeatureSet, OsmConfigBuilder};
+
+    #[test]
+    fn test_small_bbox_is_valid() {
+        let config = OsmConfigBuilder::new()
+            .bbox(52.50, 13.38, 52.52, 13.42)
+            .urban_features()
+            .build();
+        let report = config.validate();
+
+        assert!(report.is_ok());
+        assert!(report.issues.is_empty());
+        assert!(report.cost.is_some());
+        assert_eq!(report.grid_dimensions, Some((10, 10)));
+    }
+
+    #[test]
+    fn test_city_region_skips_size_checks() {
+        let report = OsmConfig::for_city("Berlin").validate();
+        assert!(report.is_ok());
+        assert!(report.cost.is_none());
+    }
+
+    #[test]
+    fn test_oversized_area_is_rejected() {
+        let config = OsmConfigBuilder::new().bbox(50.0, 10.0, 53.0, 14.0).build();
+        let report = config.validate();
+
+        assert!(report.has_errors());
+        assert!(report.errors().any(|e| e.message.contains("km²")));
+        assert!(matches!(
+            report.into_result(),
+            Err(OsmTilesError::QueryTooLarge { max_area_km2, .. }) if max_area_km2 == 5000.0
+        ));
+    }
+
+    #[test]
+    fn test_grid_size_suggests_resolution() {
+        let config = OsmConfigBuilder::new()
+            .bbox(52.0, 13.0, 52.2, 13.5)
+            .grid_resolution(10_000)
+            .build();
+        let limits = ValidationLimits::default().with_max_grid_size(1000, 1000);
+        let report = config.validate_bounds(&config.region.bounding_box().unwrap(), &limits);
+
+        assert!(report.is_ok());
+        let warning = report
+            .warnings()
+            .find(|w| w.message.contains("downscaled"))
+            .unwrap();
+        assert!(warning.message.contains("resolution of 2000"));
+    }
+
+    #[test]
+    fn test_memory_budget_and_settings_errors() {
+        let config = OsmConfigBuilder::new()
+            .bbox(52.50, 13.38, 52.52, 13.42)
+            .grid_resolution(5000)
+            .memory_budget(1024)
+            .features(FeatureSet::new())
+            .build()
+            .with_tile_size(0.0);
+        let report = config.validate();
+
+        assert_eq!(report.errors().count(), 2);
+        assert!(report.errors().any(|e| e.message.contains("budget")));
+        assert!(report.warnings().any(|w| w.message.contains("No features")));
+    }
+
+    #[test]
+    fn test_limits_from_capabilities() {
+        let provider = ProviderCapabilities {
+            max_area_km2: Some(100.0),
+            ..Default::default()
+        };
+        let generator = GeneratorCapabilities::default();
+        let limits = ValidationLimits::from_capabilities(&provider, &generator);
+
+        assert_eq!(limits.warn_area_km2, 100.0);
+        assert_eq!(limits.max_area_km2, 500.0);
+        assert_eq!(limits.max_grid_size, generator.max_grid_size);
+    }
+}
@@ -1,5 +1,8 @@
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::http::HttpError;
+
 /// Errors that can occur during OSM data processing
 #[derive(Error, Debug)]
 pub enum OsmTilesError {
@@ -7,10 +10,50 @@ pub enum OsmTilesError {
     #[error("Network error: {0}")]
     Network(#[from] NetworkError),
 
+    /// The data source answered but reported that it could not serve the request
+    #[error("Provider error: {0}")]
+    Provider(#[from] ProviderError),
+
     /// Errors parsing OSM data
     #[error("Parse error: {0}")]
     Parse(String),
 
+    /// Malformed JSON, with the position of the problem
+    #[error("Parse error in {context} at line {line}, column {column}: {source}")]
+    ParserError {
+        /// What was being parsed, e.g. "OSM JSON"
+        context: String,
+        line: usize,
+        column: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// A file could not be read
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A place name could not be resolved to a region
+    #[error("Geocoding failed for '{query}': {reason}")]
+    GeocodingFailed { query: String, reason: String },
+
+    /// The requested area exceeds what the provider accepts
+    #[error(
+        "Area of {area_km2:.0} km² exceeds the provider limit of {max_area_km2:.0} km². Split the region or choose a smaller one"
+    )]
+    QueryTooLarge { area_km2: f64, max_area_km2: f64 },
+
+    /// The server is throttling requests (HTTP 429)
+    #[error("Rate limited by the server{}", retry_after_hint(.retry_after))]
+    RateLimited {
+        /// Wait requested by the server's `Retry-After` header
+        retry_after: Option<Duration>,
+    },
+
     /// Configuration validation errors
     #[error("Configuration error: {0}")]
     Config(String),
@@ -22,6 +65,101 @@ pub enum OsmTilesError {
     /// Grid generation errors
     #[error("Grid generation error: {0}")]
     GridGeneration(String),
+
+    /// The configured resolution gives a grid larger than the generator allows
+    #[error(
+        "A {width}x{height} grid exceeds the maximum of {max_width}x{max_height} tiles. Lower the resolution to {suggested_resolution} or choose a smaller region"
+    )]
+    GridTooLarge {
+        width: usize,
+        height: usize,
+        max_width: usize,
+        max_height: usize,
+        /// Highest resolution that fits
+        suggested_resolution: u32,
+    },
+
+    /// Errors encoding grids into export formats
+    #[error("Export error: {0}")]
+    Export(String),
+
+    /// Generation would exceed the configured memory budget
+    #[error(
+        "Memory budget exceeded during {stage}: ~{estimated_bytes} bytes needed, budget is {budget_bytes} bytes ({})",
+        memory_hint(.suggested_resolution)
+    )]
+    MemoryBudgetExceeded {
+        stage: String,
+        estimated_bytes: usize,
+        budget_bytes: usize,
+        suggested_resolution: Option<u32>,
+    },
+}
+
+impl OsmTilesError {
+    /// Create a parse error from a JSON error, keeping its position and source
+    pub fn json(context: impl Into<String>, source: serde_json::Error) -> Self {
+        Self::ParserError {
+            context: context.into(),
+            line: source.line(),
+            column: source.column(),
+            source,
+        }
+    }
+
+    /// Create the error for an unsuccessful HTTP status
+    pub fn http_status(status: u16, retry_after: Option<Duration>) -> Self {
+        match status {
+            429 => Self::RateLimited { retry_after },
+            status => Self::Network(NetworkError::HttpError { status }),
+        }
+    }
+
+    /// Check whether retrying the same request may succeed
+    ///
+    /// Connection problems, timeouts, throttling, server errors (5xx) and
+    /// server-side resource limits are transient; invalid requests,
+    /// configuration and parse errors are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(NetworkError::HttpError { status }) => {
+                matches!(status, 408 | 429) || *status >= 500
+            }
+            Self::Network(
+                NetworkError::InvalidUrl { .. }
+                | NetworkError::Cancelled
+                | NetworkError::ResponseTooLarge { .. },
+            ) => false,
+            Self::Network(_) | Self::RateLimited { .. } => true,
+            Self::Provider(error) => matches!(
+                error,
+                ProviderError::Timeout { .. } | ProviderError::MemoryLimit { .. }
+            ),
+            _ => false,
+        }
+    }
+
+    /// Get how long the server asked to wait before retrying, if it did
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+fn retry_after_hint(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(delay) => format!(", retry after {}s", delay.as_secs()),
+        None => String::new(),
+    }
+}
+
+fn memory_hint(suggested_resolution: &Option<u32>) -> String {
+    match suggested_resolution {
+        Some(resolution) => format!("try a grid resolution of {} or lower", resolution),
+        None => "try a smaller region or fewer features".to_string(),
+    }
 }
 
 /// Network-specific errors
@@ -42,6 +180,160 @@ pub enum NetworkError {
     /// Invalid URL
     #[error("Invalid URL: {url}")]
     InvalidUrl { url: String },
+
+    /// The request was cancelled before it completed
+    #[error("Request was cancelled")]
+    Cancelled,
+
+    /// The response body grew past the size limit and the download was aborted
+    #[error(
+        "Response exceeded the limit of {limit_bytes} bytes ({received_bytes} bytes received). Choose a smaller area or raise the limit"
+    )]
+    ResponseTooLarge {
+        limit_bytes: usize,
+        received_bytes: usize,
+    },
+}
+
+impl From<HttpError> for NetworkError {
+    fn from(error: HttpError) -> Self {
+        match error {
+            HttpError::RequestFailed { message } | HttpError::Network { message } => {
+                Self::Connection { message }
+            }
+            HttpError::HttpStatus { status } => Self::HttpError { status },
+            HttpError::Timeout { seconds } => Self::Timeout { seconds },
+            HttpError::Cancelled => Self::Cancelled,
+            HttpError::ResponseTooLarge {
+                limit_bytes,
+                received_bytes,
+            } => Self::ResponseTooLarge {
+                limit_bytes,
+                received_bytes,
+            },
+        }
+    }
+}
+
+/// Errors reported by a data source inside an otherwise successful response
+///
+/// Overpass answers timeouts and memory exhaustion with HTTP 200 and a
+/// `remark` field, so these are detected from the payload, not the status.
+#[derive(Error, Debug)]
+pub enum ProviderError {
+    /// The query ran longer than the server allowed
+    #[error("Query timed out on the server: {remark}")]
+    Timeout { remark: String },
+
+    /// The query needed more memory than the server allowed
+    #[error("Query exceeded the server memory limit: {remark}")]
+    MemoryLimit { remark: String },
+
+    /// The server rejected the query as malformed
+    #[error("Query syntax error: {remark}")]
+    Syntax { remark: String },
+
+    /// Any other error remark
+    #[error("Server reported an error: {remark}")]
+    Other { remark: String },
+}
+
+impl ProviderError {
+    /// Classify a server remark into a typed error
+    pub fn from_remark(remark: impl Into<String>) -> Self {
+        let remark = remark.into();
+        let lower = remark.to_lowercase();
+
+        if lower.contains("timed out") || lower.contains("timeout") {
+            Self::Timeout { remark }
+        } else if lower.contains("out of memory") || lower.contains("memory") {
+            Self::MemoryLimit { remark }
+        } else if lower.contains("parse error") || lower.contains("static error") {
+            Self::Syntax { remark }
+        } else {
+            Self::Other { remark }
+        }
+    }
+
+    /// Get the remark text reported by the server
+    pub fn remark(&self) -> &str {
+        match self {
+            Self::Timeout { remark }
+            | Self::MemoryLimit { remark }
+            | Self::Syntax { remark }
+            | Self::Other { remark } => remark,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, OsmTilesError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_error_from_remark() {
+        let timeout = ProviderError::from_remark(
+            "runtime error: Query timed out in \"query\" at line 3 after 26 seconds.",
+        );
+        assert!(matches!(timeout, ProviderError::Timeout { .. }));
+        assert!(timeout.remark().contains("26 seconds"));
+
+        let memory = ProviderError::from_remark(
+            "runtime error: Query run out of memory using about 2048 MB of RAM.",
+        );
+        assert!(matches!(memory, ProviderError::MemoryLimit { .. }));
+
+        let syntax = ProviderError::from_remark("line 2: parse error: ';' expected - ')' found.");
+        assert!(matches!(syntax, ProviderError::Syntax { .. }));
+
+        let other = ProviderError::from_remark("runtime error: open64: 2 No such file");
+        assert!(matches!(other, ProviderError::Other { .. }));
+
+        let error = OsmTilesError::from(memory);
+        assert!(error.to_string().contains("2048 MB"));
+        assert!(error.is_retryable());
+        assert!(!OsmTilesError::from(syntax).is_retryable());
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        let throttled = OsmTilesError::http_status(429, Some(Duration::from_secs(30)));
+        assert!(throttled.is_retryable());
+        assert_eq!(throttled.retry_after(), Some(Duration::from_secs(30)));
+        assert!(throttled.to_string().contains("retry after 30s"));
+
+        assert!(OsmTilesError::http_status(503, None).is_retryable());
+        assert!(!OsmTilesError::http_status(404, None).is_retryable());
+        assert!(!OsmTilesError::Config("bad".into()).is_retryable());
+        assert!(
+            !OsmTilesError::GeocodingFailed {
+                query: "Atlantis".into(),
+                reason: "no results".into(),
+            }
+            .is_retryable()
+        );
+        let too_large = NetworkError::ResponseTooLarge {
+            limit_bytes: 100,
+            received_bytes: 150,
+        };
+        assert!(!OsmTilesError::from(too_large).is_retryable());
+
+        let cancelled = NetworkError::from(HttpError::Cancelled);
+        assert!(matches!(cancelled, NetworkError::Cancelled));
+        let timeout = NetworkError::from(HttpError::Timeout { seconds: 5 });
+        assert!(OsmTilesError::from(timeout).is_retryable());
+    }
+
+    #[test]
+    fn test_parser_error_keeps_source() {
+        use std::error::Error;
+
+        let source = serde_json::from_str::<serde_json::Value>("{\n  \"a\": }").unwrap_err();
+        let error = OsmTilesError::json("OSM JSON", source);
+        assert!(matches!(error, OsmTilesError::ParserError { line: 2, .. }));
+        assert!(error.to_string().contains("line 2"));
+        assert!(error.source().is_some());
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{BoundingBox, OsmMetadata, TileGrid};
+
+/// Copyright notice required by the OpenStreetMap license
+pub const OSM_COPYRIGHT: &str = "© OpenStreetMap contributors";
+/// SPDX identifier of the Open Database License
+pub const OSM_LICENSE: &str = "ODbL-1.0";
+/// URL of the OpenStreetMap copyright and license page
+pub const OSM_LICENSE_URL: &str = "https://www.openstreetmap.org/copyright";
+
+/// Source and license attribution for OSM-derived data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribution {
+    /// Data source (e.g., "overpass-api.de", "mock")
+    pub source: String,
+    /// Provider type identifier that fetched the data
+    pub provider_type: String,
+    /// Timestamp when the data was fetched
+    pub data_timestamp: Option<String>,
+    /// Copyright holder notice
+    pub copyright: String,
+    /// SPDX identifier of the data license
+    pub license: String,
+    /// URL of the full license text
+    pub license_url: String,
+}
+
+impl Attribution {
+    /// Standard OpenStreetMap attribution without source details
+    pub fn openstreetmap() -> Self {
+        Self {
+            source: "openstreetmap.org".to_string(),
+            provider_type: "unknown".to_string(),
+            data_timestamp: None,
+            copyright: OSM_COPYRIGHT.to_string(),
+            license: OSM_LICENSE.to_string(),
+            license_url: OSM_LICENSE_URL.to_string(),
+        }
+    }
+
+    /// Create the attribution for data described by the given request metadata
+    pub fn from_osm_metadata(metadata: &OsmMetadata) -> Self {
+        Self {
+            source: metadata.source.clone(),
+            provider_type: metadata.provider_type.clone(),
+            data_timestamp: Some(metadata.timestamp.clone()),
+            ..Self::openstreetmap()
+        }
+    }
+
+    /// Get a short human-readable notice suitable for map corners and credits
+    pub fn notice(&self) -> String {
+        format!(
+            "Map data {} ({}, {})",
+            self.copyright, self.license, self.license_url
+        )
+    }
+}
+
+impl Default for Attribution {
+    fn default() -> Self {
+        Self::openstreetmap()
+    }
+}
+
+/// Metadata embedded into every exported file
+///
+/// Collected once from a grid so that all exporters write the same fields,
+/// either inline (file headers, text chunks) or as a sidecar document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportMetadata {
+    /// Source and license attribution
+    pub attribution: Attribution,
+    /// Timestamp when the grid was generated
+    pub generated_at: String,
+    /// Software that produced the export
+    pub generator: String,
+    /// Geographic area covered by the export
+    pub bounding_box: BoundingBox,
+}
+
+impl ExportMetadata {
+    /// Collect export metadata from a grid
+    pub fn from_grid(grid: &TileGrid) -> Self {
+        Self {
+            attribution: grid.attribution(),
+            generated_at: grid.metadata.generated_at.clone(),
+            generator: format!("bevy-osm-tiles/{}", env!("CARGO_PKG_VERSION")),
+            bounding_box: grid.bounding_box.clone(),
+        }
+    }
+
+    /// Get the metadata as ordered key/value pairs for flat header formats
+    pub fn to_key_values(&self) -> Vec<(&'static str, String)> {
+        let bbox = &self.bounding_box;
+        let mut pairs = vec![
+            ("attribution", self.attribution.notice()),
+            ("copyright", self.attribution.copyright.clone()),
+            ("license", self.attribution.license.clone()),
+            ("license_url", self.attribution.license_url.clone()),
+            ("source", self.attribution.source.clone()),
+            ("provider", self.attribution.provider_type.clone()),
+        ];
+
+        if let Some(timestamp) = &self.attribution.data_timestamp {
+            pairs.push(("data_timestamp", timestamp.clone()));
+        }
+
+        pairs.push(("generated_at", self.generated_at.clone()));
+        pairs.push(("generator", self.generator.clone()));
+        pairs.push((
+            "bbox",
+            format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east),
+        ));
+
+        pairs
+    }
+
+    /// Serialize the metadata as a JSON sidecar document
+    ///
+    /// Used for formats that cannot carry metadata themselves; write it next to
+    /// the export using [`ExportMetadata::sidecar_path`].
+    pub fn to_sidecar_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Export metadata is always serializable")
+    }
+
+    /// Get the sidecar file path for an export path
+    pub fn sidecar_path(export_path: &str) -> String {
+        format!("{}.attribution.json", export_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileGrid};
+
+    #[test]
+    fn test_attribution_from_metadata() {
+        let metadata = OsmMetadata::new("https://overpass-api.de/api/interpreter", "overpass");
+        let attribution = Attribution::from_osm_metadata(&metadata);
+
+        assert_eq!(attribution.source, metadata.source);
+        assert_eq!(attribution.provider_type, "overpass");
+        assert_eq!(attribution.data_timestamp, Some(metadata.timestamp.clone()));
+        assert_eq!(attribution.license, OSM_LICENSE);
+        assert!(attribution.notice().contains("OpenStreetMap contributors"));
+        assert!(attribution.notice().contains("ODbL"));
+    }
+
+    #[test]
+    fn test_export_metadata_key_values() {
+        let mut grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.metadata.attribution = Some(Attribution::from_osm_metadata(&OsmMetadata::new(
+            "mock-provider",
+            "mock",
+        )));
+
+        let metadata = ExportMetadata::from_grid(&grid);
+        let pairs = metadata.to_key_values();
+        let get = |key: &str| {
+            pairs
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.clone())
+        };
+
+        assert_eq!(get("source"), Some("mock-provider".to_string()));
+        assert_eq!(get("license"), Some(OSM_LICENSE.to_string()));
+        assert_eq!(
+            get("generated_at"),
+            Some(grid.metadata.generated_at.clone())
+        );
+        assert!(get("data_timestamp").is_some());
+        assert_eq!(get("bbox"), Some("52,13,53,14".to_string()));
+    }
+
+    #[test]
+    fn test_sidecar_round_trip() {
+        let grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        let metadata = ExportMetadata::from_grid(&grid);
+
+        let json = metadata.to_sidecar_json();
+        let parsed: ExportMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.attribution, Attribution::openstreetmap());
+        assert_eq!(
+            ExportMetadata::sidecar_path("berlin.bin"),
+            "berlin.bin.attribution.json"
+        );
+    }
+}
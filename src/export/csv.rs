@@ -0,0 +1,202 @@
+use std::fmt::Write;
+
+use super::GridExporter;
+use crate::{Result, TileGrid, TileType};
+
+/// Tag keys written as columns by default
+pub const DEFAULT_CSV_TAG_KEYS: &[&str] = &["name", "highway", "building", "landuse", "natural"];
+
+/// Exports a grid as long-form CSV with one row per tile, for pandas, polars or spreadsheets
+///
+/// Columns are `x`, `y`, `lat`, `lon` (tile center), `tile_type` and one
+/// column per tag key, empty where the tile has no such tag. CSV has no
+/// place for metadata, so the attribution goes in a sidecar file.
+pub struct CsvExporter {
+    /// Whether empty tiles are written as rows
+    include_empty: bool,
+    /// OSM tag keys written as columns
+    tag_keys: Vec<String>,
+}
+
+impl CsvExporter {
+    /// Create an exporter that skips empty tiles and writes [`DEFAULT_CSV_TAG_KEYS`]
+    pub fn new() -> Self {
+        Self {
+            include_empty: false,
+            tag_keys: DEFAULT_CSV_TAG_KEYS
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+        }
+    }
+
+    /// Include empty tiles as rows
+    pub fn with_empty_tiles(mut self, include: bool) -> Self {
+        self.include_empty = include;
+        self
+    }
+
+    /// Write these tag keys as columns instead of the defaults
+    pub fn with_tag_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tag_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Build the CSV document of the tiles
+    pub fn to_csv_string(&self, grid: &TileGrid) -> String {
+        let mut csv = String::from("x,y,lat,lon,tile_type");
+        for key in &self.tag_keys {
+            csv.push(',');
+            csv.push_str(&escape(key));
+        }
+        csv.push('\n');
+
+        for (x, y, tile) in grid.iter_tiles() {
+            if tile.tile_type == TileType::Empty && !self.include_empty {
+                continue;
+            }
+            let Some((lat, lon)) = grid.grid_to_geo(x, y) else {
+                continue;
+            };
+            let _ = write!(
+                csv,
+                "{},{},{:.7},{:.7},{}",
+                x,
+                y,
+                lat,
+                lon,
+                escape(tile.tile_type.name())
+            );
+            for key in &self.tag_keys {
+                csv.push(',');
+                if let Some(value) = tile
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.tags.get(key))
+                {
+                    csv.push_str(&escape(value));
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Build a CSV table of [`TileGrid::statistics`] with one row per tile type
+    ///
+    /// Columns are `tile_type`, `tiles`, `share`, `area_km2` and
+    /// `perimeter_km`, most common type first.
+    pub fn statistics_csv(&self, grid: &TileGrid) -> String {
+        let stats = grid.statistics();
+        let mut counts: Vec<_> = stats.tile_type_counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.name().cmp(b.0.name())));
+
+        let mut csv = String::from("tile_type,tiles,share,area_km2,perimeter_km\n");
+        for (tile_type, &count) in counts {
+            if *tile_type == TileType::Empty && !self.include_empty {
+                continue;
+            }
+            let _ = writeln!(
+                csv,
+                "{},{},{:.6},{:.6},{:.6}",
+                escape(tile_type.name()),
+                count,
+                count as f64 / stats.total_tiles as f64,
+                stats.type_area_km2.get(tile_type).copied().unwrap_or(0.0),
+                stats
+                    .type_perimeter_km
+                    .get(tile_type)
+                    .copied()
+                    .unwrap_or(0.0)
+            );
+        }
+        csv
+    }
+}
+
+/// Quote a field if it contains separators, quotes or line breaks
+fn escape(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+impl Default for CsvExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for CsvExporter {
+    fn format_name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        Ok(self.to_csv_string(grid).into_bytes())
+    }
+
+    fn embeds_attribution(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile, TileMetadata};
+
+    #[test]
+    fn test_csv_export() {
+        let mut grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 52.04, 13.04), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+        let mut metadata = TileMetadata::default();
+        metadata
+            .tags
+            .insert("name".to_string(), "Café \"Zur Ecke\", Mitte".to_string());
+        metadata
+            .tags
+            .insert("building".to_string(), "yes".to_string());
+        grid.set_tile(1, 2, Tile::with_metadata(TileType::Building, metadata))
+            .unwrap();
+
+        let exporter = CsvExporter::new().with_tag_keys(["name", "building"]);
+        let csv = exporter.to_csv_string(&grid);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "x,y,lat,lon,tile_type,name,building");
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("0,0,52.0350000,13.0050000,road,"));
+        assert!(lines[2].ends_with(",building,\"Café \"\"Zur Ecke\"\", Mitte\",yes"));
+        assert!(!exporter.embeds_attribution());
+
+        let all = CsvExporter::new()
+            .with_empty_tiles(true)
+            .to_csv_string(&grid);
+        assert_eq!(all.lines().count(), 17);
+    }
+
+    #[test]
+    fn test_statistics_csv() {
+        let mut grid = TileGrid::new(2, 2, BoundingBox::new(52.0, 13.0, 52.01, 13.01), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Water)).unwrap();
+        grid.set_tile(1, 0, Tile::new(TileType::Water)).unwrap();
+        grid.set_tile(0, 1, Tile::new(TileType::Road)).unwrap();
+
+        let csv = CsvExporter::new().statistics_csv(&grid);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "tile_type,tiles,share,area_km2,perimeter_km");
+        assert!(lines[1].starts_with("water,2,0.500000,"));
+        assert!(lines[2].starts_with("road,1,0.250000,"));
+        assert_eq!(lines.len(), 3);
+    }
+}
@@ -0,0 +1,196 @@
+use serde_json::{Map, Value, json};
+
+use super::{ExportMetadata, GridExporter};
+use crate::{OsmTilesError, Result, TileGrid, TileType};
+
+/// Exports a grid as a GeoJSON FeatureCollection with one polygon per tile
+pub struct GeoJsonExporter {
+    /// Whether empty tiles are written as features
+    include_empty: bool,
+    /// Whether OSM tags are copied into feature properties
+    include_tags: bool,
+}
+
+impl GeoJsonExporter {
+    /// Create an exporter that skips empty tiles and omits tags
+    pub fn new() -> Self {
+        Self {
+            include_empty: false,
+            include_tags: false,
+        }
+    }
+
+    /// Include empty tiles as features
+    pub fn with_empty_tiles(mut self, include: bool) -> Self {
+        self.include_empty = include;
+        self
+    }
+
+    /// Copy OSM tags of contributing elements into feature properties
+    pub fn with_tags(mut self, include: bool) -> Self {
+        self.include_tags = include;
+        self
+    }
+
+    /// Build the GeoJSON document as a JSON value
+    pub fn to_value(&self, grid: &TileGrid) -> Value {
+        let metadata = ExportMetadata::from_grid(grid);
+        let bbox = &grid.bounding_box;
+
+        let mut features = Vec::new();
+        for (x, y, tile) in grid.iter_tiles() {
+            if tile.tile_type == TileType::Empty && !self.include_empty {
+                continue;
+            }
+
+            let Some(bounds) = grid.tile_bounds(x, y) else {
+                continue;
+            };
+
+            let mut properties = Map::new();
+            properties.insert("x".to_string(), json!(x));
+            properties.insert("y".to_string(), json!(y));
+            properties.insert("tile_type".to_string(), json!(tile.tile_type.name()));
+
+            if let Some(tile_metadata) = &tile.metadata {
+                properties.insert("osm_ids".to_string(), json!(tile_metadata.osm_ids));
+                if self.include_tags {
+                    properties.insert("tags".to_string(), json!(tile_metadata.tags));
+                }
+            }
+
+            features.push(json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[
+                        [bounds.west, bounds.south],
+                        [bounds.east, bounds.south],
+                        [bounds.east, bounds.north],
+                        [bounds.west, bounds.north],
+                        [bounds.west, bounds.south],
+                    ]],
+                },
+                "properties": properties,
+            }));
+        }
+
+        let attribution: Map<String, Value> = metadata
+            .to_key_values()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), Value::String(value)))
+            .collect();
+
+        json!({
+            "type": "FeatureCollection",
+            "bbox": [bbox.west, bbox.south, bbox.east, bbox.north],
+            "metadata": attribution,
+            "features": features,
+        })
+    }
+}
+
+impl Default for GeoJsonExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for GeoJsonExporter {
+    fn format_name(&self) -> &'static str {
+        "geojson"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "geojson"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        serde_json::to_vec(&self.to_value(grid))
+            .map_err(|e| OsmTilesError::Export(format!("Failed to encode GeoJSON: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Attribution, BoundingBox, OsmMetadata, Tile, TileMetadata};
+
+    fn create_test_grid() -> TileGrid {
+        let mut grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+
+        let mut metadata = TileMetadata::default();
+        metadata.osm_ids.push(42);
+        metadata
+            .tags
+            .insert("building".to_string(), "yes".to_string());
+        grid.set_tile(1, 2, Tile::with_metadata(TileType::Building, metadata))
+            .unwrap();
+
+        grid.metadata.attribution = Some(Attribution::from_osm_metadata(&OsmMetadata::new(
+            "overpass-api.de",
+            "overpass",
+        )));
+        grid
+    }
+
+    #[test]
+    fn test_geojson_export_features() {
+        let grid = create_test_grid();
+        let value = GeoJsonExporter::new().to_value(&grid);
+
+        assert_eq!(value["type"], "FeatureCollection");
+        let features = value["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+
+        let building = features
+            .iter()
+            .find(|f| f["properties"]["tile_type"] == "building")
+            .unwrap();
+        assert_eq!(building["properties"]["osm_ids"][0], 42);
+        assert!(building["properties"].get("tags").is_none());
+        assert_eq!(
+            building["geometry"]["coordinates"][0]
+                .as_array()
+                .unwrap()
+                .len(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_geojson_export_embeds_attribution() {
+        let grid = create_test_grid();
+        let bytes = GeoJsonExporter::new().export(&grid).unwrap();
+        let value: Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(value["metadata"]["source"], "overpass-api.de");
+        assert_eq!(value["metadata"]["license"], "ODbL-1.0");
+        assert!(
+            value["metadata"]["attribution"]
+                .as_str()
+                .unwrap()
+                .contains("OpenStreetMap contributors")
+        );
+        assert!(value["metadata"]["data_timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_geojson_export_options() {
+        let grid = create_test_grid();
+        let value = GeoJsonExporter::new()
+            .with_empty_tiles(true)
+            .with_tags(true)
+            .to_value(&grid);
+
+        let features = value["features"].as_array().unwrap();
+        assert_eq!(features.len(), 16);
+
+        let building = features
+            .iter()
+            .find(|f| f["properties"]["tile_type"] == "building")
+            .unwrap();
+        assert_eq!(building["properties"]["tags"]["building"], "yes");
+    }
+}
@@ -0,0 +1,370 @@
+use std::io::Cursor;
+
+use tiff::encoder::compression::DeflateLevel;
+use tiff::encoder::{Compression, TiffEncoder, colortype};
+use tiff::tags::Tag;
+
+use super::svg::escape_xml;
+use super::{ExportMetadata, GridExporter};
+use crate::{Crs, DensityFeature, DensityGenerator, OsmTilesError, Result, TileGrid, TileType};
+
+/// Tile types in the order of their classification codes
+const CLASSIFICATION: [TileType; 12] = [
+    TileType::Empty,
+    TileType::Road,
+    TileType::Building,
+    TileType::Water,
+    TileType::GreenSpace,
+    TileType::Railway,
+    TileType::Parking,
+    TileType::Amenity,
+    TileType::Tourism,
+    TileType::Industrial,
+    TileType::Residential,
+    TileType::Commercial,
+];
+
+/// Classification code written for custom tile types
+pub const CUSTOM_TILE_CODE: u8 = 255;
+
+/// GDAL's private tag holding dataset metadata as XML
+const GDAL_METADATA_TAG: u16 = 42112;
+
+/// Get the classification code a tile type is written as
+pub fn classification_code(tile_type: &TileType) -> u8 {
+    CLASSIFICATION
+        .iter()
+        .position(|known| known == tile_type)
+        .map_or(CUSTOM_TILE_CODE, |code| code as u8)
+}
+
+/// Raster written by a [`GeoTiffExporter`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoTiffLayer {
+    /// Tile type codes (see [`classification_code`]) as 8-bit integers
+    Classification,
+    /// Default rendering height of each tile type in meters
+    Height,
+    /// Share of non-empty tiles within `radius` tiles, from 0.0 to 1.0
+    Density { radius: usize },
+    /// Caller-supplied values, one per tile in row-major grid order
+    Values { name: String, values: Vec<f32> },
+}
+
+impl GeoTiffLayer {
+    /// Get the layer name stored in the file metadata
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Classification => "classification",
+            Self::Height => "height",
+            Self::Density { .. } => "density",
+            Self::Values { name, .. } => name,
+        }
+    }
+}
+
+/// Exports one layer of a grid as a georeferenced single-band GeoTIFF
+///
+/// The raster is tied to the grid's CRS through the standard GeoTIFF tags, so
+/// GIS tools place it correctly without a world file. Attribution goes into
+/// the TIFF `Copyright` tag and GDAL metadata.
+pub struct GeoTiffExporter {
+    layer: GeoTiffLayer,
+}
+
+impl GeoTiffExporter {
+    /// Create an exporter writing the given layer
+    pub fn new(layer: GeoTiffLayer) -> Self {
+        Self { layer }
+    }
+
+    /// Create an exporter writing the tile classification raster
+    pub fn classification() -> Self {
+        Self::new(GeoTiffLayer::Classification)
+    }
+
+    /// Create an exporter writing a float layer from caller-supplied values
+    pub fn values(name: impl Into<String>, values: Vec<f32>) -> Self {
+        Self::new(GeoTiffLayer::Values {
+            name: name.into(),
+            values,
+        })
+    }
+
+    /// Get the layer written by this exporter
+    pub fn layer(&self) -> &GeoTiffLayer {
+        &self.layer
+    }
+
+    /// Compute the float raster in image row order (north up)
+    fn float_pixels(&self, grid: &TileGrid) -> Result<Vec<f32>> {
+        let (width, height) = grid.dimensions();
+        let mut pixels = vec![0.0; width * height];
+
+        match &self.layer {
+            GeoTiffLayer::Classification => unreachable!("classification is not a float layer"),
+            GeoTiffLayer::Height => {
+                for (x, y, tile) in grid.iter_tiles() {
+                    let row = grid.origin.to_top_left(y, height);
+                    pixels[row * width + x] = tile.tile_type.default_height();
+                }
+            }
+            GeoTiffLayer::Density { radius } => {
+                let density = DensityGenerator::new(DensityFeature::NonEmpty)
+                    .with_radius(*radius)
+                    .generate(grid);
+                for (y, row) in density.values().chunks(width).enumerate() {
+                    let image_row = grid.origin.to_top_left(y, height);
+                    pixels[image_row * width..(image_row + 1) * width].copy_from_slice(row);
+                }
+            }
+            GeoTiffLayer::Values { name, values } => {
+                if values.len() != width * height {
+                    return Err(OsmTilesError::Export(format!(
+                        "Layer '{}' has {} values, expected {} for a {}x{} grid",
+                        name,
+                        values.len(),
+                        width * height,
+                        width,
+                        height
+                    )));
+                }
+                for (y, row) in values.chunks(width).enumerate() {
+                    let image_row = grid.origin.to_top_left(y, height);
+                    pixels[image_row * width..(image_row + 1) * width].copy_from_slice(row);
+                }
+            }
+        }
+
+        Ok(pixels)
+    }
+
+    /// Build the GeoKeyDirectory, pixel scale and tiepoint for a grid
+    fn georeference(grid: &TileGrid) -> (Vec<u16>, [f64; 3], [f64; 6]) {
+        // GeoKey IDs and values from the GeoTIFF 1.0 specification
+        const GT_MODEL_TYPE: u16 = 1024;
+        const GT_RASTER_TYPE: u16 = 1025;
+        const GEOGRAPHIC_TYPE: u16 = 2048;
+        const PROJECTED_CS_TYPE: u16 = 3072;
+        const MODEL_TYPE_PROJECTED: u16 = 1;
+        const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+        const RASTER_PIXEL_IS_AREA: u16 = 1;
+
+        let (model_type, crs_key, epsg) = match grid.crs {
+            Crs::Wgs84 => (MODEL_TYPE_GEOGRAPHIC, GEOGRAPHIC_TYPE, 4326),
+            Crs::WebMercator => (MODEL_TYPE_PROJECTED, PROJECTED_CS_TYPE, 3857),
+        };
+        let geo_keys = vec![
+            // Header: version 1.1.0 with three keys
+            1,
+            1,
+            0,
+            3,
+            GT_MODEL_TYPE,
+            0,
+            1,
+            model_type,
+            GT_RASTER_TYPE,
+            0,
+            1,
+            RASTER_PIXEL_IS_AREA,
+            crs_key,
+            0,
+            1,
+            epsg,
+        ];
+
+        let bbox = &grid.bounding_box;
+        let projection = grid.crs.projection();
+        let (min_x, min_y) = projection.project(bbox.south, bbox.west);
+        let (max_x, max_y) = projection.project(bbox.north, bbox.east);
+        let (width, height) = grid.dimensions();
+
+        let pixel_scale = [
+            (max_x - min_x) / width as f64,
+            (max_y - min_y) / height as f64,
+            0.0,
+        ];
+        // Raster (0, 0) is the north-west corner
+        let tiepoint = [0.0, 0.0, 0.0, min_x, max_y, 0.0];
+        (geo_keys, pixel_scale, tiepoint)
+    }
+
+    /// Build the GDAL metadata XML describing the layer and its attribution
+    fn gdal_metadata(&self, metadata: &ExportMetadata) -> String {
+        let mut items: Vec<(String, String)> = metadata
+            .to_key_values()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        items.push(("layer".to_string(), self.layer.name().to_string()));
+        if self.layer == GeoTiffLayer::Classification {
+            let classes: Vec<String> = CLASSIFICATION
+                .iter()
+                .enumerate()
+                .map(|(code, tile_type)| format!("{}={}", code, tile_type.name()))
+                .chain([format!("{}=custom", CUSTOM_TILE_CODE)])
+                .collect();
+            items.push(("classes".to_string(), classes.join(",")));
+        }
+
+        let mut xml = String::from("<GDALMetadata>");
+        for (key, value) in items {
+            xml.push_str(&format!(
+                "<Item name=\"{}\">{}</Item>",
+                escape_xml(&key),
+                escape_xml(&value)
+            ));
+        }
+        xml.push_str("</GDALMetadata>");
+
+        // TIFF ASCII fields are 7-bit, so other characters become character references
+        xml.chars()
+            .map(|c| {
+                if c.is_ascii() {
+                    c.to_string()
+                } else {
+                    format!("&#{};", c as u32)
+                }
+            })
+            .collect()
+    }
+}
+
+impl GridExporter for GeoTiffExporter {
+    fn format_name(&self) -> &'static str {
+        "geotiff"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "tif"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        let (width, height) = grid.dimensions();
+        let metadata = ExportMetadata::from_grid(grid);
+        let (geo_keys, pixel_scale, tiepoint) = Self::georeference(grid);
+        let gdal_metadata = self.gdal_metadata(&metadata);
+        let copyright = metadata
+            .attribution
+            .notice()
+            .replace('©', "(c)")
+            .replace(|c: char| !c.is_ascii(), "?");
+
+        let encode_error =
+            |e: tiff::TiffError| OsmTilesError::Export(format!("Failed to encode GeoTIFF: {}", e));
+
+        let mut buffer = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut buffer)
+            .map_err(encode_error)?
+            .with_compression(Compression::Deflate(DeflateLevel::Balanced));
+
+        macro_rules! write_image {
+            ($color:ty, $pixels:expr) => {{
+                let mut image = encoder
+                    .new_image::<$color>(width as u32, height as u32)
+                    .map_err(encode_error)?;
+                let tags = image.encoder();
+                tags.write_tag(Tag::ModelPixelScaleTag, &pixel_scale[..])
+                    .map_err(encode_error)?;
+                tags.write_tag(Tag::ModelTiepointTag, &tiepoint[..])
+                    .map_err(encode_error)?;
+                tags.write_tag(Tag::GeoKeyDirectoryTag, &geo_keys[..])
+                    .map_err(encode_error)?;
+                tags.write_tag(Tag::Copyright, copyright.as_str())
+                    .map_err(encode_error)?;
+                tags.write_tag(Tag::Software, metadata.generator.as_str())
+                    .map_err(encode_error)?;
+                tags.write_tag(Tag::Unknown(GDAL_METADATA_TAG), gdal_metadata.as_str())
+                    .map_err(encode_error)?;
+                image.write_data(&$pixels).map_err(encode_error)?;
+            }};
+        }
+
+        if self.layer == GeoTiffLayer::Classification {
+            let mut pixels = vec![0u8; width * height];
+            for (x, y, tile) in grid.iter_tiles() {
+                let row = grid.origin.to_top_left(y, height);
+                pixels[row * width + x] = classification_code(&tile.tile_type);
+            }
+            write_image!(colortype::Gray8, pixels);
+        } else {
+            let pixels = self.float_pixels(grid)?;
+            write_image!(colortype::Gray32Float, pixels);
+        }
+
+        Ok(buffer.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, GridOrigin, Tile};
+    use tiff::decoder::{Decoder, DecodingResult};
+
+    fn test_grid() -> TileGrid {
+        let mut grid = TileGrid::new(4, 2, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.set_tile(1, 0, Tile::new(TileType::Water)).unwrap();
+        grid.set_tile(3, 1, Tile::new(TileType::Building)).unwrap();
+        grid
+    }
+
+    fn decode(bytes: Vec<u8>) -> Decoder<Cursor<Vec<u8>>> {
+        Decoder::new(Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn test_classification_export() {
+        let bytes = GeoTiffExporter::classification()
+            .export(&test_grid())
+            .unwrap();
+        let mut decoder = decode(bytes);
+        assert_eq!(decoder.dimensions().unwrap(), (4, 2));
+
+        let scale = decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag).unwrap();
+        assert_eq!(scale, vec![0.25, 0.5, 0.0]);
+        let tiepoint = decoder.get_tag_f64_vec(Tag::ModelTiepointTag).unwrap();
+        assert_eq!(tiepoint[3..5], [13.0, 53.0]);
+        let geo_keys = decoder.get_tag_u16_vec(Tag::GeoKeyDirectoryTag).unwrap();
+        assert_eq!(geo_keys[12..], [2048, 0, 1, 4326]);
+        let copyright = decoder.get_tag_ascii_string(Tag::Copyright).unwrap();
+        assert!(copyright.contains("OpenStreetMap contributors"));
+
+        let DecodingResult::U8(pixels) = decoder.read_image().unwrap() else {
+            panic!("expected 8-bit classification");
+        };
+        assert_eq!(pixels[1], classification_code(&TileType::Water));
+        assert_eq!(pixels[7], classification_code(&TileType::Building));
+    }
+
+    #[test]
+    fn test_float_layers() {
+        let mut grid = test_grid().with_crs(Crs::WebMercator);
+        grid.set_origin(GridOrigin::BottomLeft);
+
+        // Values follow grid order, the raster is written north up
+        let values = (0..8).map(|v| v as f32).collect();
+        let bytes = GeoTiffExporter::values("elevation", values)
+            .export(&grid)
+            .unwrap();
+        let mut decoder = decode(bytes);
+        let geo_keys = decoder.get_tag_u16_vec(Tag::GeoKeyDirectoryTag).unwrap();
+        assert_eq!(geo_keys[12..], [3072, 0, 1, 3857]);
+        let DecodingResult::F32(pixels) = decoder.read_image().unwrap() else {
+            panic!("expected float layer");
+        };
+        assert_eq!(pixels[..4], [4.0, 5.0, 6.0, 7.0]);
+
+        let bytes = GeoTiffExporter::new(GeoTiffLayer::Density { radius: 1 })
+            .export(&test_grid())
+            .unwrap();
+        let DecodingResult::F32(density) = decode(bytes).read_image().unwrap() else {
+            panic!("expected float layer");
+        };
+        assert_eq!(density[0], 0.25);
+
+        let result = GeoTiffExporter::values("elevation", vec![1.0]).export(&grid);
+        assert!(result.is_err());
+    }
+}
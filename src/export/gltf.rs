@@ -0,0 +1,464 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+
+use super::{ExportMetadata, GridExporter, TilePalette};
+use crate::{OsmTilesError, Result, Tile, TileGrid, TileType};
+
+/// Assumed height of one storey when only `building:levels` is tagged (in meters)
+const LEVEL_HEIGHT: f32 = 3.0;
+
+/// glTF component type of 32-bit floats
+const FLOAT: u32 = 5126;
+/// glTF component type of 32-bit unsigned integers
+const UNSIGNED_INT: u32 = 5125;
+
+/// Exports building footprints as extruded meshes in a binary glTF (`.glb`) scene
+///
+/// The scene uses meters with Y up and north towards -Z, so it drops into
+/// Blender, Godot or Unity at real-world scale. Every chunk of tiles becomes
+/// one node, and each tile type gets its own palette-colored material.
+/// Walls between neighboring buildings of the same height are left out.
+pub struct GltfExporter {
+    /// Chunk edge length in tiles
+    chunk_size: usize,
+    /// Height of buildings without `height` or `building:levels` tags (in meters)
+    default_height: f32,
+    /// Colors of the tile types
+    palette: TilePalette,
+}
+
+impl GltfExporter {
+    /// Create an exporter with 64×64 tile chunks and 6 m default building height
+    pub fn new() -> Self {
+        Self {
+            chunk_size: 64,
+            default_height: 6.0,
+            palette: TilePalette::default(),
+        }
+    }
+
+    /// Set the chunk edge length in tiles
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Set the height of buildings without height tags (in meters)
+    pub fn with_default_height(mut self, height: f32) -> Self {
+        self.default_height = height.max(0.0);
+        self
+    }
+
+    /// Set the colors meshes are drawn with
+    pub fn with_palette(mut self, palette: TilePalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Get the extrusion height of a tile in meters, 0.0 if it is not a building
+    pub fn building_height(&self, tile: &Tile) -> f32 {
+        let tags = tile.metadata.as_ref().map(|metadata| &metadata.tags);
+        let is_building = tile.tile_type == TileType::Building
+            || tags.is_some_and(|tags| tags.contains_key("building"));
+        if !is_building {
+            return 0.0;
+        }
+
+        let tag = |key: &str| {
+            tags.and_then(|tags| tags.get(key))
+                .and_then(|value| parse_meters(value))
+        };
+        tag("height")
+            .or_else(|| tag("building:levels").map(|levels| levels * LEVEL_HEIGHT))
+            .filter(|height| *height > 0.0)
+            .unwrap_or(self.default_height)
+    }
+
+    /// Build the glTF JSON document and its binary buffer
+    fn build(&self, grid: &TileGrid) -> (Value, Vec<u8>) {
+        let (width, height) = grid.dimensions();
+        let (size_x, size_z) = (grid.meters_per_tile_x(), grid.meters_per_tile_y());
+
+        // Heights and tile types in image order (row 0 is north)
+        let mut heights = vec![0.0f32; width * height];
+        let mut types = vec![TileType::Empty; width * height];
+        for (x, y, tile) in grid.iter_tiles() {
+            let index = grid.origin.to_top_left(y, height) * width + x;
+            heights[index] = self.building_height(tile);
+            types[index] = tile.tile_type.clone();
+        }
+        let height_at = |x: isize, row: isize| {
+            if x < 0 || row < 0 || x >= width as isize || row >= height as isize {
+                0.0
+            } else {
+                heights[row as usize * width + x as usize]
+            }
+        };
+
+        let mut materials: Vec<TileType> = Vec::new();
+        let mut buffer = Vec::new();
+        let mut buffer_views = Vec::new();
+        let mut accessors = Vec::new();
+        let mut meshes = Vec::new();
+        let mut nodes = Vec::new();
+
+        for chunk_row in (0..height).step_by(self.chunk_size) {
+            for chunk_x in (0..width).step_by(self.chunk_size) {
+                let mut primitives: BTreeMap<usize, MeshData> = BTreeMap::new();
+                for row in chunk_row..(chunk_row + self.chunk_size).min(height) {
+                    for x in chunk_x..(chunk_x + self.chunk_size).min(width) {
+                        let top = heights[row * width + x];
+                        if top <= 0.0 {
+                            continue;
+                        }
+                        let tile_type = &types[row * width + x];
+                        let known = materials.iter().position(|known| known == tile_type);
+                        let material = known.unwrap_or_else(|| {
+                            materials.push(tile_type.clone());
+                            materials.len() - 1
+                        });
+                        let mesh = primitives.entry(material).or_default();
+
+                        let (x, row) = (x as isize, row as isize);
+                        let neighbors = [
+                            height_at(x, row - 1),
+                            height_at(x, row + 1),
+                            height_at(x + 1, row),
+                            height_at(x - 1, row),
+                        ];
+                        mesh.prism(
+                            (x as f32 * size_x, (x + 1) as f32 * size_x),
+                            (row as f32 * size_z, (row + 1) as f32 * size_z),
+                            top,
+                            neighbors,
+                        );
+                    }
+                }
+
+                if primitives.is_empty() {
+                    continue;
+                }
+                let primitives: Vec<Value> = primitives
+                    .into_iter()
+                    .map(|(material, mesh)| {
+                        mesh.write(material, &mut buffer, &mut buffer_views, &mut accessors)
+                    })
+                    .collect();
+                let name = format!(
+                    "chunk_{}_{}",
+                    chunk_x / self.chunk_size,
+                    chunk_row / self.chunk_size
+                );
+                nodes.push(json!({ "name": name, "mesh": meshes.len() }));
+                meshes.push(json!({ "name": name, "primitives": primitives }));
+            }
+        }
+
+        let materials: Vec<Value> = materials
+            .iter()
+            .map(|tile_type| {
+                let (r, g, b) = self.palette.color(tile_type);
+                json!({
+                    "name": tile_type.name(),
+                    "pbrMetallicRoughness": {
+                        "baseColorFactor": [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0],
+                        "metallicFactor": 0.0,
+                        "roughnessFactor": 0.9,
+                    },
+                })
+            })
+            .collect();
+
+        let metadata = ExportMetadata::from_grid(grid);
+        let mut document = json!({
+            "asset": {
+                "version": "2.0",
+                "generator": metadata.generator,
+                "copyright": metadata.attribution.notice(),
+                "extras": metadata,
+            },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+            "nodes": nodes,
+            "meshes": meshes,
+            "materials": materials,
+            "accessors": accessors,
+            "bufferViews": buffer_views,
+        });
+        if !buffer.is_empty() {
+            document["buffers"] = json!([{ "byteLength": buffer.len() }]);
+        }
+        (document, buffer)
+    }
+}
+
+impl Default for GltfExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for GltfExporter {
+    fn format_name(&self) -> &'static str {
+        "gltf"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "glb"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        let (document, mut buffer) = self.build(grid);
+        let mut json = serde_json::to_vec(&document)
+            .map_err(|e| OsmTilesError::Export(format!("Failed to encode glTF: {}", e)))?;
+
+        // Both chunks must be 4-byte aligned; JSON pads with spaces, binary with zeros
+        json.resize(json.len().next_multiple_of(4), b' ');
+        buffer.resize(buffer.len().next_multiple_of(4), 0);
+        let binary_chunk = if buffer.is_empty() {
+            0
+        } else {
+            8 + buffer.len()
+        };
+        let total = 12 + 8 + json.len() + binary_chunk;
+
+        let mut glb = Vec::with_capacity(total);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        if !buffer.is_empty() {
+            glb.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+            glb.extend_from_slice(b"BIN\0");
+            glb.extend_from_slice(&buffer);
+        }
+        Ok(glb)
+    }
+}
+
+/// Flat-shaded triangles of one primitive
+#[derive(Default)]
+struct MeshData {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl MeshData {
+    /// Add a quad whose corners run counter-clockwise when seen from outside
+    fn quad(&mut self, corners: [[f32; 3]; 4], normal: [f32; 3]) {
+        let start = self.positions.len() as u32;
+        self.positions.extend(corners);
+        self.normals.extend([normal; 4]);
+        self.indices
+            .extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+
+    /// Add a box roof and the walls rising above its north, south, east and west neighbors
+    fn prism(&mut self, (x0, x1): (f32, f32), (z0, z1): (f32, f32), top: f32, neighbors: [f32; 4]) {
+        let [north, south, east, west] = neighbors;
+        self.quad(
+            [[x0, top, z0], [x0, top, z1], [x1, top, z1], [x1, top, z0]],
+            [0.0, 1.0, 0.0],
+        );
+        if north < top {
+            self.quad(
+                [
+                    [x1, north, z0],
+                    [x0, north, z0],
+                    [x0, top, z0],
+                    [x1, top, z0],
+                ],
+                [0.0, 0.0, -1.0],
+            );
+        }
+        if south < top {
+            self.quad(
+                [
+                    [x0, south, z1],
+                    [x1, south, z1],
+                    [x1, top, z1],
+                    [x0, top, z1],
+                ],
+                [0.0, 0.0, 1.0],
+            );
+        }
+        if east < top {
+            self.quad(
+                [[x1, east, z1], [x1, east, z0], [x1, top, z0], [x1, top, z1]],
+                [1.0, 0.0, 0.0],
+            );
+        }
+        if west < top {
+            self.quad(
+                [[x0, west, z0], [x0, west, z1], [x0, top, z1], [x0, top, z0]],
+                [-1.0, 0.0, 0.0],
+            );
+        }
+    }
+
+    /// Append the data to the binary buffer and describe it as a glTF primitive
+    fn write(
+        self,
+        material: usize,
+        buffer: &mut Vec<u8>,
+        buffer_views: &mut Vec<Value>,
+        accessors: &mut Vec<Value>,
+    ) -> Value {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for position in &self.positions {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis]);
+                max[axis] = max[axis].max(position[axis]);
+            }
+        }
+
+        let mut push_view = |bytes: Vec<u8>, target: u32| {
+            buffer_views.push(json!({
+                "buffer": 0,
+                "byteOffset": buffer.len(),
+                "byteLength": bytes.len(),
+                "target": target,
+            }));
+            buffer.extend(bytes);
+            buffer_views.len() - 1
+        };
+        let floats = |vectors: &[[f32; 3]]| {
+            vectors
+                .iter()
+                .flatten()
+                .flat_map(|value| value.to_le_bytes())
+                .collect::<Vec<u8>>()
+        };
+        let positions = push_view(floats(&self.positions), 34962);
+        let normals = push_view(floats(&self.normals), 34962);
+        let indices = push_view(
+            self.indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect(),
+            34963,
+        );
+
+        let first = accessors.len();
+        accessors.push(json!({
+            "bufferView": positions,
+            "componentType": FLOAT,
+            "count": self.positions.len(),
+            "type": "VEC3",
+            "min": min,
+            "max": max,
+        }));
+        accessors.push(json!({
+            "bufferView": normals,
+            "componentType": FLOAT,
+            "count": self.normals.len(),
+            "type": "VEC3",
+        }));
+        accessors.push(json!({
+            "bufferView": indices,
+            "componentType": UNSIGNED_INT,
+            "count": self.indices.len(),
+            "type": "SCALAR",
+        }));
+
+        json!({
+            "attributes": { "POSITION": first, "NORMAL": first + 1 },
+            "indices": first + 2,
+            "material": material,
+        })
+    }
+}
+
+/// Parse an OSM length such as "12", "12.5 m" or "12m" into meters
+fn parse_meters(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let number = value.trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace());
+    number.replace(',', ".").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileMetadata};
+
+    fn building(tags: &[(&str, &str)]) -> Tile {
+        Tile::with_metadata(
+            TileType::Building,
+            TileMetadata {
+                tags: tags
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Split a GLB file into its JSON document and binary chunk
+    fn parse_glb(bytes: &[u8]) -> (Value, Vec<u8>) {
+        assert_eq!(&bytes[0..4], b"glTF");
+        assert_eq!(
+            u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize,
+            bytes.len()
+        );
+        let json_length = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        let document = serde_json::from_slice(&bytes[20..20 + json_length]).unwrap();
+        let binary = bytes.get(28 + json_length..).unwrap_or_default().to_vec();
+        (document, binary)
+    }
+
+    #[test]
+    fn test_building_heights() {
+        let exporter = GltfExporter::new();
+        assert_eq!(
+            exporter.building_height(&building(&[("height", "12.5 m")])),
+            12.5
+        );
+        assert_eq!(
+            exporter.building_height(&building(&[("building:levels", "4")])),
+            12.0
+        );
+        assert_eq!(exporter.building_height(&building(&[])), 6.0);
+        assert_eq!(exporter.building_height(&Tile::new(TileType::Road)), 0.0);
+    }
+
+    #[test]
+    fn test_gltf_export() {
+        let mut grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 52.001, 13.001), 10.0);
+        grid.set_tile(1, 1, building(&[("height", "20")])).unwrap();
+        grid.set_tile(2, 1, building(&[("height", "20")])).unwrap();
+        grid.set_tile(3, 3, Tile::new(TileType::Building)).unwrap();
+
+        let bytes = GltfExporter::new()
+            .with_chunk_size(2)
+            .export(&grid)
+            .unwrap();
+        assert_eq!(bytes.len() % 4, 0);
+        let (document, binary) = parse_glb(&bytes);
+
+        assert!(
+            document["asset"]["copyright"]
+                .as_str()
+                .unwrap()
+                .contains("OpenStreetMap contributors")
+        );
+        assert_eq!(document["buffers"][0]["byteLength"], binary.len());
+        // Tiles (1, 1) and (2, 1) fall into different chunks, plus the chunk with (3, 3)
+        assert_eq!(document["nodes"].as_array().unwrap().len(), 3);
+
+        let positions = &document["accessors"][0];
+        assert_eq!(positions["max"][1], 20.0);
+        // A top and three walls; the wall shared with (2, 1) stays hidden across the chunk border
+        assert_eq!(positions["count"], 4 * 4);
+
+        let empty = TileGrid::new(2, 2, BoundingBox::new(52.0, 13.0, 52.001, 13.001), 10.0);
+        let (document, binary) = parse_glb(&GltfExporter::new().export(&empty).unwrap());
+        assert!(binary.is_empty());
+        assert!(document["meshes"].as_array().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,292 @@
+use serde_json::{Value, json};
+
+use super::{GridExporter, TileIds, TilePalette};
+use crate::{OsmTilesError, Result, TileGrid, stable_hash};
+
+/// LDtk release whose project format is written
+const LDTK_VERSION: &str = "1.5.3";
+
+/// Layer definition and level UIDs inside the project
+const LAYER_UID: u32 = 1;
+const LEVEL_UID: u32 = 2;
+
+/// Exports a grid as an LDtk project (`.ldtk`) with one level and one IntGrid layer
+///
+/// IntGrid values are the [`TileIds`] plus one (0 is an empty cell) and are
+/// named after their tile type and colored by the palette, so auto-layer
+/// rules can be set up in LDtk on top of them. LDtk projects have no place
+/// for free-form metadata, so the attribution goes in a sidecar file.
+pub struct LdtkExporter {
+    /// Cell size in pixels
+    grid_size: u32,
+    /// Colors of the tile types
+    palette: TilePalette,
+}
+
+impl LdtkExporter {
+    /// Create an exporter with 16 pixel cells
+    pub fn new() -> Self {
+        Self {
+            grid_size: 16,
+            palette: TilePalette::default(),
+        }
+    }
+
+    /// Set the cell size in pixels
+    pub fn with_grid_size(mut self, pixels: u32) -> Self {
+        self.grid_size = pixels.max(1);
+        self
+    }
+
+    /// Set the colors of the IntGrid values
+    pub fn with_palette(mut self, palette: TilePalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Build the project document as a JSON value
+    pub fn to_value(&self, grid: &TileGrid) -> Value {
+        let (width, height) = grid.dimensions();
+        let ids = TileIds::for_grid(grid);
+        let size = self.grid_size;
+
+        // Stable instance IDs, so exporting the same area twice gives the same project
+        let bbox = &grid.bounding_box;
+        let seed = format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east);
+        let iid = |role: &str| {
+            let hex = stable_hash(&format!("{}:{}", seed, role)) + &stable_hash(role);
+            format!(
+                "{}-{}-{}-{}-{}",
+                &hex[..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..]
+            )
+        };
+
+        let values: Vec<Value> = ids
+            .tile_types()
+            .iter()
+            .enumerate()
+            .map(|(id, tile_type)| {
+                let (r, g, b) = self.palette.color(tile_type);
+                json!({
+                    "value": id + 1,
+                    "identifier": tile_type.name(),
+                    "color": format!("#{:02X}{:02X}{:02X}", r, g, b),
+                    "tile": null,
+                    "groupUid": 0,
+                })
+            })
+            .collect();
+
+        // Cells run row by row from the top left
+        let mut cells = Vec::with_capacity(width * height);
+        for image_y in 0..height {
+            for x in 0..width {
+                let value = grid
+                    .from_image_coords(x, image_y)
+                    .and_then(|(x, y)| grid.get_tile(x, y))
+                    .and_then(|tile| ids.id(&tile.tile_type))
+                    .map_or(0, |id| id + 1);
+                cells.push(value);
+            }
+        }
+
+        let layer_def = json!({
+            "__type": "IntGrid",
+            "identifier": "Tiles",
+            "type": "IntGrid",
+            "uid": LAYER_UID,
+            "doc": null,
+            "uiColor": null,
+            "gridSize": size,
+            "guideGridWid": 0,
+            "guideGridHei": 0,
+            "displayOpacity": 1,
+            "inactiveOpacity": 1,
+            "hideInList": false,
+            "hideFieldsWhenInactive": true,
+            "canSelectWhenInactive": true,
+            "renderInWorldView": true,
+            "pxOffsetX": 0,
+            "pxOffsetY": 0,
+            "parallaxFactorX": 0,
+            "parallaxFactorY": 0,
+            "parallaxScaling": true,
+            "requiredTags": [],
+            "excludedTags": [],
+            "autoTilesKilledByOtherLayerUid": null,
+            "uiFilterTags": [],
+            "useAsyncRender": false,
+            "intGridValues": values,
+            "intGridValuesGroups": [],
+            "autoRuleGroups": [],
+            "autoSourceLayerDefUid": null,
+            "tilesetDefUid": null,
+            "tilePivotX": 0,
+            "tilePivotY": 0,
+            "biomeFieldUid": null,
+        });
+
+        let layer = json!({
+            "__identifier": "Tiles",
+            "__type": "IntGrid",
+            "__cWid": width,
+            "__cHei": height,
+            "__gridSize": size,
+            "__opacity": 1,
+            "__pxTotalOffsetX": 0,
+            "__pxTotalOffsetY": 0,
+            "__tilesetDefUid": null,
+            "__tilesetRelPath": null,
+            "iid": iid("layer"),
+            "levelId": LEVEL_UID,
+            "layerDefUid": LAYER_UID,
+            "pxOffsetX": 0,
+            "pxOffsetY": 0,
+            "visible": true,
+            "optionalRules": [],
+            "intGridCsv": cells,
+            "autoLayerTiles": [],
+            "seed": 0,
+            "overrideTilesetUid": null,
+            "gridTiles": [],
+            "entityInstances": [],
+        });
+
+        let level = json!({
+            "identifier": "Level_0",
+            "iid": iid("level"),
+            "uid": LEVEL_UID,
+            "worldX": 0,
+            "worldY": 0,
+            "worldDepth": 0,
+            "pxWid": width as u32 * size,
+            "pxHei": height as u32 * size,
+            "__bgColor": "#696A79",
+            "bgColor": null,
+            "useAutoIdentifier": true,
+            "bgRelPath": null,
+            "bgPos": null,
+            "bgPivotX": 0.5,
+            "bgPivotY": 0.5,
+            "__smartColor": "#ADADB5",
+            "__bgPos": null,
+            "externalRelPath": null,
+            "fieldInstances": [],
+            "layerInstances": [layer],
+            "__neighbours": [],
+        });
+
+        json!({
+            "__header__": {
+                "fileType": "LDtk Project JSON",
+                "app": "LDtk",
+                "doc": "https://ldtk.io/json",
+                "schema": "https://ldtk.io/files/JSON_SCHEMA.json",
+                "appAuthor": "Sebastien 'deepnight' Benard",
+                "appVersion": LDTK_VERSION,
+                "url": "https://ldtk.io",
+            },
+            "iid": iid("project"),
+            "jsonVersion": LDTK_VERSION,
+            "nextUid": LEVEL_UID + 1,
+            "identifierStyle": "Capitalize",
+            "toc": [],
+            "worldLayout": "Free",
+            "worldGridWidth": width as u32 * size,
+            "worldGridHeight": height as u32 * size,
+            "defaultLevelWidth": width as u32 * size,
+            "defaultLevelHeight": height as u32 * size,
+            "defaultPivotX": 0,
+            "defaultPivotY": 0,
+            "defaultGridSize": size,
+            "defaultEntityWidth": size,
+            "defaultEntityHeight": size,
+            "bgColor": "#40465B",
+            "defaultLevelBgColor": "#696A79",
+            "minifyJson": false,
+            "externalLevels": false,
+            "exportTiled": false,
+            "simplifiedExport": false,
+            "imageExportMode": "None",
+            "exportLevelBg": true,
+            "pngFilePattern": null,
+            "backupOnSave": false,
+            "backupLimit": 10,
+            "backupRelPath": null,
+            "levelNamePattern": "Level_%idx",
+            "tutorialDesc": null,
+            "customCommands": [],
+            "flags": [],
+            "defs": {
+                "layers": [layer_def],
+                "entities": [],
+                "tilesets": [],
+                "enums": [],
+                "externalEnums": [],
+                "levelFields": [],
+            },
+            "levels": [level],
+            "worlds": [],
+            "dummyWorldIid": iid("world"),
+        })
+    }
+}
+
+impl Default for LdtkExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for LdtkExporter {
+    fn format_name(&self) -> &'static str {
+        "ldtk"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "ldtk"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        serde_json::to_vec_pretty(&self.to_value(grid))
+            .map_err(|e| OsmTilesError::Export(format!("Failed to encode LDtk project: {}", e)))
+    }
+
+    fn embeds_attribution(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile, TileType};
+
+    #[test]
+    fn test_ldtk_export() {
+        let mut grid = TileGrid::new(3, 2, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+        grid.set_tile(2, 1, Tile::new(TileType::Water)).unwrap();
+
+        let project = LdtkExporter::new().with_grid_size(8).to_value(&grid);
+        let layer = &project["levels"][0]["layerInstances"][0];
+        assert_eq!(layer["__cWid"], 3);
+        assert_eq!(layer["intGridCsv"], json!([1, 0, 0, 0, 0, 3]));
+        assert_eq!(project["levels"][0]["pxWid"], 24);
+
+        let values = &project["defs"]["layers"][0]["intGridValues"];
+        assert_eq!(values[0]["identifier"], "road");
+        assert_eq!(values[2]["color"], "#1E90FF");
+        let iid = project["iid"].as_str().unwrap();
+        assert_eq!(iid.len(), 36);
+        assert_ne!(iid, project["levels"][0]["iid"].as_str().unwrap());
+        assert_eq!(
+            project,
+            LdtkExporter::new().with_grid_size(8).to_value(&grid)
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use super::{TilePalette, escape_xml};
+use crate::{TileGrid, TileType};
+
+/// Height of one legend row in SVG user units
+const ROW_HEIGHT: f32 = 20.0;
+/// Approximate width of one character at the legend font size
+const CHAR_WIDTH: f32 = 7.0;
+
+/// A single legend row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LegendEntry {
+    /// Tile type the row describes
+    pub tile_type: TileType,
+    /// Color of the tile type under the palette the legend was built with (RGB)
+    pub color: (u8, u8, u8),
+    /// Human-readable label
+    pub label: String,
+    /// Number of tiles of this type in the grid
+    pub count: usize,
+}
+
+/// Legend of the tile types present in a grid, most common first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Legend {
+    /// Rows in display order
+    pub entries: Vec<LegendEntry>,
+}
+
+impl Legend {
+    /// Build the legend of a grid drawn with `palette`
+    pub fn for_grid(grid: &TileGrid, palette: &TilePalette) -> Self {
+        let mut entries: Vec<LegendEntry> = grid
+            .count_tiles_by_type()
+            .into_iter()
+            .map(|(tile_type, count)| LegendEntry {
+                color: palette.color(&tile_type),
+                label: tile_type.label(),
+                tile_type,
+                count,
+            })
+            .collect();
+        entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+        Self { entries }
+    }
+
+    /// Drop the row of empty tiles, which is usually just the background
+    pub fn without_empty(mut self) -> Self {
+        self.entries
+            .retain(|entry| entry.tile_type != TileType::Empty);
+        self
+    }
+
+    /// Get the row of a tile type
+    pub fn entry(&self, tile_type: &TileType) -> Option<&LegendEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.tile_type == *tile_type)
+    }
+
+    /// Get the total number of tiles covered by the legend
+    pub fn total_count(&self) -> usize {
+        self.entries.iter().map(|entry| entry.count).sum()
+    }
+
+    /// Get the number of rows
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the legend has no rows
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Render the legend as an SVG image with a swatch, label and tile count per row
+    pub fn to_svg_string(&self) -> String {
+        let rows: Vec<(String, String)> = self
+            .entries
+            .iter()
+            .map(|entry| (escape_xml(&entry.label), entry.count.to_string()))
+            .collect();
+        let label_chars = self
+            .entries
+            .iter()
+            .map(|entry| entry.label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let count_chars = rows.iter().map(|(_, count)| count.len()).max().unwrap_or(0);
+        let label_x = ROW_HEIGHT * 1.5;
+        let width = label_x + (label_chars + count_chars + 2) as f32 * CHAR_WIDTH;
+        let height = (self.entries.len() as f32 * ROW_HEIGHT).max(ROW_HEIGHT);
+
+        let mut svg = String::new();
+        // Writing into a String cannot fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+            w = width,
+            h = height
+        );
+        let _ = writeln!(svg, "  <title>Legend</title>");
+
+        for (row, (entry, (label, count))) in self.entries.iter().zip(&rows).enumerate() {
+            let y = row as f32 * ROW_HEIGHT;
+            let text_y = y + ROW_HEIGHT * 0.7;
+            let (r, g, b) = entry.color;
+            let _ = writeln!(
+                svg,
+                r#"  <rect x="4" y="{}" width="{s}" height="{s}" fill="rgb({},{},{})" stroke="black" stroke-width="0.5"/>"#,
+                y + 4.0,
+                r,
+                g,
+                b,
+                s = ROW_HEIGHT - 8.0
+            );
+            let _ = writeln!(
+                svg,
+                r#"  <text x="{}" y="{}">{}</text>"#,
+                label_x, text_y, label
+            );
+            let _ = writeln!(
+                svg,
+                r#"  <text x="{}" y="{}" text-anchor="end">{}</text>"#,
+                width - 4.0,
+                text_y,
+                count
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile};
+
+    #[test]
+    fn test_legend_for_grid() {
+        let mut grid = TileGrid::new(4, 2, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        for x in 0..3 {
+            grid.set_tile(x, 0, Tile::new(TileType::Road)).unwrap();
+        }
+        grid.set_tile(3, 0, Tile::new(TileType::GreenSpace))
+            .unwrap();
+        grid.set_tile(0, 1, Tile::new(TileType::GreenSpace))
+            .unwrap();
+
+        let palette = TilePalette::new().with_color(TileType::Road, (10, 20, 30));
+        let legend = Legend::for_grid(&grid, &palette);
+
+        assert_eq!(legend.total_count(), 8);
+        let labels: Vec<&str> = legend.entries.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, vec!["Empty", "Road", "Green space"]);
+        assert_eq!(legend.entry(&TileType::Road).unwrap().color, (10, 20, 30));
+        assert_eq!(
+            legend.entry(&TileType::GreenSpace).unwrap().color,
+            TileType::GreenSpace.default_color()
+        );
+
+        let legend = legend.without_empty();
+        assert_eq!(legend.len(), 2);
+        let svg = legend.to_svg_string();
+        assert!(svg.contains(r#"fill="rgb(10,20,30)""#));
+        assert!(svg.contains(">Green space</text>"));
+        assert!(svg.contains(r#"text-anchor="end">3</text>"#));
+    }
+}
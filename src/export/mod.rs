@@ -0,0 +1,56 @@
+mod attribution;
+mod csv;
+mod geojson;
+#[cfg(feature = "geotiff-export")]
+mod geotiff;
+mod gltf;
+mod ldtk;
+mod legend;
+mod packed;
+mod palette;
+#[cfg(feature = "png-export")]
+mod png;
+#[cfg(feature = "png-export")]
+mod png_import;
+mod svg;
+mod tiled;
+
+pub use attribution::*;
+pub use csv::*;
+pub use geojson::*;
+#[cfg(feature = "geotiff-export")]
+pub use geotiff::*;
+pub use gltf::*;
+pub use ldtk::*;
+pub use legend::*;
+pub use packed::*;
+pub use palette::*;
+#[cfg(feature = "png-export")]
+pub use png::*;
+pub use svg::*;
+pub use tiled::*;
+
+use crate::{Result, TileGrid};
+
+/// Trait for writing tile grids into file formats
+///
+/// Exporters never touch the file system themselves; they return the encoded
+/// bytes so callers can write them to disk, browser storage, or the network.
+pub trait GridExporter {
+    /// Get the format identifier (e.g., "geojson", "svg")
+    fn format_name(&self) -> &'static str;
+
+    /// Get the conventional file extension for this format
+    fn file_extension(&self) -> &'static str;
+
+    /// Encode the grid together with its attribution metadata
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>>;
+
+    /// Whether the encoded data carries the attribution itself
+    ///
+    /// When this returns false, callers redistributing the file must ship
+    /// [`ExportMetadata::to_sidecar_json`] alongside it.
+    fn embeds_attribution(&self) -> bool {
+        true
+    }
+}
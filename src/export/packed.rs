@@ -0,0 +1,243 @@
+//! Flat binary grid format for engines outside Rust
+//!
+//! All numbers are little-endian; strings are UTF-8 prefixed by their byte
+//! length.
+//!
+//! | Offset | Size | Field                                                   |
+//! |--------|------|---------------------------------------------------------|
+//! | 0      | 4    | Magic `OSMT`                                            |
+//! | 4      | 2    | Format version, currently 1                            |
+//! | 6      | 2    | Reserved, 0                                             |
+//! | 8      | 4    | Width in tiles (u32)                                    |
+//! | 12     | 4    | Height in tiles (u32)                                   |
+//! | 16     | 32   | Bounding box south, west, north, east (f64 degrees)     |
+//! | 48     | 4    | Meters per tile (f32)                                   |
+//! | 52     | 1    | Origin: 0 = row 0 is north, 1 = row 0 is south          |
+//! | 53     | 3    | Reserved, 0                                             |
+//! | 56     | 4    | Number of tile type names N (u32)                       |
+//! | 60     | …    | N names (u16 length + bytes) for tile codes 1 to N      |
+//! | …      | …    | Attribution notice (u32 length + bytes)                 |
+//! | …      | 2·W·H | Tile codes (u16) row by row, 0 for empty tiles         |
+//!
+//! Tile codes follow [`TileIds`], so the standard tile types have the same
+//! code in every file. Readers should skip files with a newer version.
+
+use super::{ExportMetadata, GridExporter, TileIds};
+use crate::{BoundingBox, GridOrigin, OsmTilesError, Result, Tile, TileGrid, TileType};
+
+/// File signature of packed grids
+pub const PACKED_MAGIC: &[u8; 4] = b"OSMT";
+
+/// Version of the packed layout written by [`PackedExporter`]
+pub const PACKED_VERSION: u16 = 1;
+
+/// Exports a grid in the flat binary layout documented in [`crate::export::packed`]
+pub struct PackedExporter;
+
+impl PackedExporter {
+    /// Create a packed exporter
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PackedExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for PackedExporter {
+    fn format_name(&self) -> &'static str {
+        "packed"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "osmt"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        let (width, height) = grid.dimensions();
+        let ids = TileIds::for_grid(grid);
+        if ids.tile_types().len() >= usize::from(u16::MAX) {
+            return Err(OsmTilesError::Export(format!(
+                "{} tile types do not fit in 16-bit tile codes",
+                ids.tile_types().len()
+            )));
+        }
+        let bbox = &grid.bounding_box;
+
+        let mut bytes = Vec::with_capacity(128 + 2 * width * height);
+        bytes.extend_from_slice(PACKED_MAGIC);
+        bytes.extend_from_slice(&PACKED_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&[0; 2]);
+        bytes.extend_from_slice(&(width as u32).to_le_bytes());
+        bytes.extend_from_slice(&(height as u32).to_le_bytes());
+        for degrees in [bbox.south, bbox.west, bbox.north, bbox.east] {
+            bytes.extend_from_slice(&degrees.to_le_bytes());
+        }
+        bytes.extend_from_slice(&grid.meters_per_tile.to_le_bytes());
+        bytes.push(match grid.origin {
+            GridOrigin::TopLeft => 0,
+            GridOrigin::BottomLeft => 1,
+        });
+        bytes.extend_from_slice(&[0; 3]);
+
+        bytes.extend_from_slice(&(ids.tile_types().len() as u32).to_le_bytes());
+        for tile_type in ids.tile_types() {
+            let name = tile_type.name().as_bytes();
+            let length = u16::try_from(name.len()).map_err(|_| {
+                OsmTilesError::Export(format!("Tile type name '{}' is too long", tile_type.name()))
+            })?;
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.extend_from_slice(name);
+        }
+
+        let notice = ExportMetadata::from_grid(grid).attribution.notice();
+        bytes.extend_from_slice(&(notice.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(notice.as_bytes());
+
+        for (_, _, tile) in grid.iter_tiles() {
+            let code = ids.id(&tile.tile_type).map_or(0, |id| id as u16 + 1);
+            bytes.extend_from_slice(&code.to_le_bytes());
+        }
+        Ok(bytes)
+    }
+}
+
+/// Reads the fields of a packed grid in order
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < count {
+            return Err(OsmTilesError::Parse("Packed grid is truncated".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn string(&mut self, length: usize) -> Result<String> {
+        String::from_utf8(self.take(length)?.to_vec())
+            .map_err(|_| OsmTilesError::Parse("Packed grid holds invalid UTF-8".to_string()))
+    }
+}
+
+impl TileGrid {
+    /// Read a grid written by [`PackedExporter`]
+    ///
+    /// Tiles get their type back; tile metadata, points of interest and
+    /// districts are not part of the format.
+    pub fn from_packed(bytes: &[u8]) -> Result<TileGrid> {
+        let mut reader = Reader { bytes };
+        if reader.take(4)? != PACKED_MAGIC {
+            return Err(OsmTilesError::Parse("Not a packed grid".to_string()));
+        }
+        let version = u16::from_le_bytes(reader.array()?);
+        if version != PACKED_VERSION {
+            return Err(OsmTilesError::Parse(format!(
+                "Unsupported packed grid version {}",
+                version
+            )));
+        }
+        reader.take(2)?;
+        let width = u32::from_le_bytes(reader.array()?) as usize;
+        let height = u32::from_le_bytes(reader.array()?) as usize;
+        let mut degrees = [0.0; 4];
+        for value in &mut degrees {
+            *value = f64::from_le_bytes(reader.array()?);
+        }
+        let meters_per_tile = f32::from_le_bytes(reader.array()?);
+        let origin = match reader.array::<4>()?[0] {
+            0 => GridOrigin::TopLeft,
+            1 => GridOrigin::BottomLeft,
+            other => {
+                return Err(OsmTilesError::Parse(format!(
+                    "Unknown packed grid origin {}",
+                    other
+                )));
+            }
+        };
+
+        let type_count = u32::from_le_bytes(reader.array()?) as usize;
+        let mut types = vec![TileType::Empty];
+        for _ in 0..type_count {
+            let length = u16::from_le_bytes(reader.array()?) as usize;
+            let name = reader.string(length)?;
+            let tile_type = TileType::STANDARD
+                .into_iter()
+                .find(|tile_type| tile_type.name() == name)
+                .unwrap_or(TileType::Custom(name));
+            types.push(tile_type);
+        }
+        let attribution_length = u32::from_le_bytes(reader.array()?) as usize;
+        reader.take(attribution_length)?;
+
+        // Read the tiles before allocating the grid, so a bogus size fails cleanly
+        let tile_bytes = width
+            .checked_mul(height)
+            .and_then(|count| count.checked_mul(2))
+            .ok_or_else(|| OsmTilesError::Parse("Packed grid is too large".to_string()))?;
+        let codes = reader.take(tile_bytes)?;
+
+        let [south, west, north, east] = degrees;
+        let mut grid = TileGrid::new(
+            width,
+            height,
+            BoundingBox::new(south, west, north, east),
+            meters_per_tile,
+        );
+        grid.origin = origin;
+        for (index, code) in codes.chunks_exact(2).enumerate() {
+            let code = u16::from_le_bytes([code[0], code[1]]) as usize;
+            let tile_type = types.get(code).ok_or_else(|| {
+                OsmTilesError::Parse(format!("Packed grid uses unknown tile code {}", code))
+            })?;
+            if *tile_type != TileType::Empty {
+                grid.set_tile(index % width, index / width, Tile::new(tile_type.clone()))
+                    .map_err(OsmTilesError::Parse)?;
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_round_trip() {
+        let mut grid = TileGrid::new(3, 2, BoundingBox::new(52.0, 13.0, 52.01, 13.02), 12.5);
+        grid.origin = GridOrigin::BottomLeft;
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+        grid.set_tile(2, 1, Tile::new(TileType::Custom("lava".into())))
+            .unwrap();
+
+        let bytes = PackedExporter::new().export(&grid).unwrap();
+        assert_eq!(&bytes[..4], PACKED_MAGIC);
+        assert_eq!(u32::from_le_bytes(bytes[8..12].try_into().unwrap()), 3);
+        // Tiles are the last 2·W·H bytes; the road is the first tile of row 0
+        let tiles = &bytes[bytes.len() - 12..];
+        assert_eq!(&tiles[..2], &[1, 0]);
+
+        let read = TileGrid::from_packed(&bytes).unwrap();
+        assert_eq!(read.dimensions(), (3, 2));
+        assert_eq!(read.origin, GridOrigin::BottomLeft);
+        assert_eq!(read.meters_per_tile, 12.5);
+        assert_eq!(read.bounding_box, grid.bounding_box);
+        for (x, y, tile) in grid.iter_tiles() {
+            assert_eq!(read.get_tile(x, y).unwrap().tile_type, tile.tile_type);
+        }
+
+        assert!(TileGrid::from_packed(&bytes[..bytes.len() - 1]).is_err());
+        assert!(TileGrid::from_packed(b"PNG!").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::TileType;
+
+/// Colors used to draw tile types, falling back to [`TileType::default_color`]
+///
+/// Exporters, legends and the Bevy renderer share a palette so a map and its
+/// legend always agree.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TilePalette {
+    /// Colors overriding the defaults (RGB)
+    colors: HashMap<TileType, (u8, u8, u8)>,
+}
+
+impl TilePalette {
+    /// Create a palette using the default color of every tile type
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the color of a tile type
+    pub fn with_color(mut self, tile_type: TileType, color: (u8, u8, u8)) -> Self {
+        self.colors.insert(tile_type, color);
+        self
+    }
+
+    /// Get the color of a tile type (RGB)
+    pub fn color(&self, tile_type: &TileType) -> (u8, u8, u8) {
+        self.colors
+            .get(tile_type)
+            .copied()
+            .unwrap_or_else(|| tile_type.default_color())
+    }
+
+    /// Get the tile type drawn in the color closest to `color`, the inverse of [`Self::color`]
+    ///
+    /// Candidates are the standard tile types and the custom types with a
+    /// color override. Tile types sharing a color resolve to the standard
+    /// type, or the custom type whose name sorts first.
+    pub fn tile_type_for(&self, color: (u8, u8, u8)) -> TileType {
+        let mut custom: Vec<&TileType> = self
+            .colors
+            .keys()
+            .filter(|tile_type| matches!(tile_type, TileType::Custom(_)))
+            .collect();
+        custom.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let distance = |(r, g, b): (u8, u8, u8)| {
+            let channel = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+            channel(r, color.0) + channel(g, color.1) + channel(b, color.2)
+        };
+        TileType::STANDARD
+            .iter()
+            .chain(custom)
+            .min_by_key(|tile_type| distance(self.color(tile_type)))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,209 @@
+use super::{ExportMetadata, GridExporter, TilePalette};
+use crate::{DensityGrid, OsmTilesError, Result, TileGrid};
+
+/// Exports a grid as an RGB PNG image
+///
+/// Attribution is stored in international text (iTXt) chunks using the
+/// standard PNG keywords, so it survives in image viewers and asset pipelines.
+pub struct PngExporter {
+    /// Pixels per tile along each axis
+    scale: u32,
+    /// Colors of the tile types
+    palette: TilePalette,
+}
+
+impl PngExporter {
+    /// Create an exporter writing one pixel per tile
+    pub fn new() -> Self {
+        Self {
+            scale: 1,
+            palette: TilePalette::default(),
+        }
+    }
+
+    /// Set the number of pixels per tile along each axis
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Set the colors tile types are drawn with
+    pub fn with_palette(mut self, palette: TilePalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Export a density layer of `grid` as a grayscale PNG, brighter where denser
+    ///
+    /// Values are clamped to `[0, 1]`, so normalize POI counts first, e.g.
+    /// with [`DensityNormalization::Max`](crate::DensityNormalization::Max).
+    pub fn export_density(&self, grid: &TileGrid, density: &DensityGrid) -> Result<Vec<u8>> {
+        if density.dimensions() != grid.dimensions() {
+            let ((width, height), (grid_width, grid_height)) =
+                (density.dimensions(), grid.dimensions());
+            return Err(OsmTilesError::Export(format!(
+                "Density layer is {}x{}, expected {}x{} to match the grid",
+                width, height, grid_width, grid_height
+            )));
+        }
+        let pixels = self.scale_pixels(grid, |index| {
+            [(density.values()[index].clamp(0.0, 1.0) * 255.0).round() as u8]
+        });
+        self.encode(grid, ::png::ColorType::Grayscale, &pixels)
+    }
+
+    /// Lay out per-tile pixels (looked up by grid storage index) north up and scaled
+    fn scale_pixels<const N: usize>(
+        &self,
+        grid: &TileGrid,
+        pixel: impl Fn(usize) -> [u8; N],
+    ) -> Vec<u8> {
+        let (width, height) = grid.dimensions();
+        let scale = self.scale as usize;
+        let image_width = width * scale;
+
+        let mut pixels = vec![0u8; image_width * height * scale * N];
+        for y in 0..height {
+            let image_y = grid.origin.to_top_left(y, height);
+            for x in 0..width {
+                let value = pixel(y * width + x);
+                for dy in 0..scale {
+                    let row = (image_y * scale + dy) * image_width;
+                    for dx in 0..scale {
+                        let offset = (row + x * scale + dx) * N;
+                        pixels[offset..offset + N].copy_from_slice(&value);
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    /// Encode scaled pixels together with the grid's attribution
+    fn encode(&self, grid: &TileGrid, color: ::png::ColorType, pixels: &[u8]) -> Result<Vec<u8>> {
+        let (width, height) = grid.dimensions();
+        let encode_error =
+            |e: ::png::EncodingError| OsmTilesError::Export(format!("Failed to encode PNG: {}", e));
+
+        let mut buffer = Vec::new();
+        {
+            let mut encoder = ::png::Encoder::new(
+                &mut buffer,
+                width as u32 * self.scale,
+                height as u32 * self.scale,
+            );
+            encoder.set_color(color);
+            encoder.set_depth(::png::BitDepth::Eight);
+
+            for (keyword, text) in Self::text_chunks(&ExportMetadata::from_grid(grid)) {
+                encoder
+                    .add_itxt_chunk(keyword, text)
+                    .map_err(encode_error)?;
+            }
+
+            let mut writer = encoder.write_header().map_err(encode_error)?;
+            writer.write_image_data(pixels).map_err(encode_error)?;
+        }
+
+        Ok(buffer)
+    }
+
+    /// Build the text chunks written into the PNG file
+    fn text_chunks(metadata: &ExportMetadata) -> Vec<(String, String)> {
+        let attribution = &metadata.attribution;
+        let mut chunks = vec![
+            ("Title".to_string(), "OSM tile grid".to_string()),
+            ("Copyright".to_string(), attribution.notice()),
+            ("Source".to_string(), attribution.source.clone()),
+            ("Software".to_string(), metadata.generator.clone()),
+            ("Creation Time".to_string(), metadata.generated_at.clone()),
+            ("License".to_string(), attribution.license_url.clone()),
+        ];
+        if let Some(timestamp) = &attribution.data_timestamp {
+            chunks.push(("Data Timestamp".to_string(), timestamp.clone()));
+        }
+        chunks
+    }
+}
+
+impl Default for PngExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for PngExporter {
+    fn format_name(&self) -> &'static str {
+        "png"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "png"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        let (width, height) = grid.dimensions();
+        let mut colors = vec![(0, 0, 0); width * height];
+        for (x, y, tile) in grid.iter_tiles() {
+            colors[y * width + x] = self.palette.color(&tile.tile_type);
+        }
+        let pixels = self.scale_pixels(grid, |index| {
+            let (r, g, b) = colors[index];
+            [r, g, b]
+        });
+        self.encode(grid, ::png::ColorType::Rgb, &pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile, TileType};
+
+    #[test]
+    fn test_png_export_round_trip() {
+        let mut grid = TileGrid::new(4, 3, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.set_tile(2, 1, Tile::new(TileType::Water)).unwrap();
+
+        let bytes = PngExporter::new().with_scale(2).export(&grid).unwrap();
+
+        let decoder = ::png::Decoder::new(std::io::Cursor::new(bytes));
+        let mut reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (8, 6));
+
+        let copyright = info
+            .utf8_text
+            .iter()
+            .find(|chunk| chunk.keyword == "Copyright")
+            .and_then(|chunk| chunk.get_text().ok())
+            .unwrap();
+        assert!(copyright.contains("OpenStreetMap contributors"));
+
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        // Pixel (4, 2) lies in the scaled water tile
+        let offset = (2 * 8 + 4) * 3;
+        assert_eq!(&pixels[offset..offset + 3], &[30, 144, 255]);
+    }
+
+    #[test]
+    fn test_density_export() {
+        let mut grid = TileGrid::new(2, 2, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.set_tile(1, 0, Tile::new(TileType::Building)).unwrap();
+        let density = crate::DensityGenerator::new(crate::DensityFeature::NonEmpty)
+            .with_radius(0)
+            .generate(&grid);
+
+        let bytes = PngExporter::new().export_density(&grid, &density).unwrap();
+        let decoder = ::png::Decoder::new(std::io::Cursor::new(bytes));
+        let mut reader = decoder.read_info().unwrap();
+        assert_eq!(reader.info().color_type, ::png::ColorType::Grayscale);
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, vec![0, 255, 0, 0]);
+
+        let other = TileGrid::new(3, 2, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        assert!(PngExporter::new().export_density(&other, &density).is_err());
+    }
+}
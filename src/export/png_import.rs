@@ -0,0 +1,109 @@
+use super::TilePalette;
+use crate::{BoundingBox, OsmTilesError, Result, Tile, TileGrid, TileType};
+
+impl TileGrid {
+    /// Build a grid from a color-coded PNG, the inverse of [`PngExporter`](super::PngExporter)
+    ///
+    /// Every pixel becomes one tile of the type whose `palette` color is
+    /// closest, so slightly off colors from anti-aliased brushes still map
+    /// to the intended type; fully transparent pixels become empty tiles.
+    /// The image is read north up and spans `bounding_box`. Grids exported
+    /// with a scale of 1 and the same palette round-trip exactly, minus
+    /// their tile metadata.
+    pub fn from_image(
+        png: &[u8],
+        palette: &TilePalette,
+        bounding_box: BoundingBox,
+    ) -> Result<TileGrid> {
+        let decode_error =
+            |e: ::png::DecodingError| OsmTilesError::Parse(format!("Failed to decode PNG: {}", e));
+
+        let mut decoder = ::png::Decoder::new(std::io::Cursor::new(png));
+        decoder.set_transformations(::png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(decode_error)?;
+        let buffer_size = reader
+            .output_buffer_size()
+            .ok_or_else(|| OsmTilesError::Parse("PNG image is too large to decode".to_string()))?;
+        let mut pixels = vec![0; buffer_size];
+        let frame = reader.next_frame(&mut pixels).map_err(decode_error)?;
+
+        // Pixels as RGB plus whether they are fully transparent
+        let channels = frame.color_type.samples();
+        let pixel = |offset: usize| -> ((u8, u8, u8), bool) {
+            let samples = &pixels[offset..offset + channels];
+            match frame.color_type {
+                ::png::ColorType::Grayscale => ((samples[0], samples[0], samples[0]), false),
+                ::png::ColorType::GrayscaleAlpha => {
+                    ((samples[0], samples[0], samples[0]), samples[1] == 0)
+                }
+                ::png::ColorType::Rgba => ((samples[0], samples[1], samples[2]), samples[3] == 0),
+                _ => ((samples[0], samples[1], samples[2]), false),
+            }
+        };
+
+        let (width, height) = (frame.width as usize, frame.height as usize);
+        let meters_per_tile = (bounding_box.width_m() / width.max(1) as f64
+            * bounding_box.height_m()
+            / height.max(1) as f64)
+            .sqrt() as f32;
+        let mut grid = TileGrid::new(width, height, bounding_box, meters_per_tile);
+
+        // The palette lookup is the slow part, and painted maps use few colors
+        let mut types = std::collections::HashMap::new();
+        for image_y in 0..height {
+            for x in 0..width {
+                let (color, transparent) = pixel(image_y * frame.line_size + x * channels);
+                let tile_type = if transparent {
+                    TileType::Empty
+                } else {
+                    types
+                        .entry(color)
+                        .or_insert_with(|| palette.tile_type_for(color))
+                        .clone()
+                };
+                if tile_type != TileType::Empty
+                    && let Some((x, y)) = grid.from_image_coords(x, image_y)
+                {
+                    grid.set_tile(x, y, Tile::new(tile_type))
+                        .map_err(OsmTilesError::GridGeneration)?;
+                }
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GridExporter, PngExporter};
+
+    #[test]
+    fn test_png_round_trip() {
+        let bbox = BoundingBox::new(52.0, 13.0, 52.01, 13.02);
+        let mut grid = TileGrid::new(6, 4, bbox.clone(), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+        grid.set_tile(5, 3, Tile::new(TileType::Water)).unwrap();
+        grid.set_tile(2, 1, Tile::new(TileType::Custom("lava".into())))
+            .unwrap();
+        let palette = TilePalette::new().with_color(TileType::Custom("lava".into()), (250, 80, 0));
+
+        let png = PngExporter::new()
+            .with_palette(palette.clone())
+            .export(&grid)
+            .unwrap();
+        let imported = TileGrid::from_image(&png, &palette, bbox).unwrap();
+        assert_eq!(imported.dimensions(), (6, 4));
+        for (x, y, tile) in grid.iter_tiles() {
+            assert_eq!(imported.get_tile(x, y).unwrap().tile_type, tile.tile_type);
+        }
+        assert!(imported.meters_per_tile > 100.0);
+
+        // Off colors snap to the nearest palette entry
+        assert_eq!(palette.tile_type_for((28, 140, 250)), TileType::Water);
+        assert!(
+            TileGrid::from_image(b"not a png", &palette, BoundingBox::new(0.0, 0.0, 1.0, 1.0))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt::Write;
+
+use super::{ExportMetadata, GridExporter, TilePalette};
+use crate::{Result, TileGrid, TileType};
+
+/// Exports a grid as an SVG image with one rectangle per non-empty tile
+pub struct SvgExporter {
+    /// Size of each tile in SVG user units
+    cell_size: f32,
+    /// Whether to draw the attribution notice onto the image
+    visible_attribution: bool,
+    /// Colors of the tile types
+    palette: TilePalette,
+}
+
+impl SvgExporter {
+    /// Create an exporter with 1-unit cells and a visible attribution notice
+    pub fn new() -> Self {
+        Self {
+            cell_size: 1.0,
+            visible_attribution: true,
+            palette: TilePalette::default(),
+        }
+    }
+
+    /// Set the size of each tile in SVG user units
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Draw the attribution notice in the bottom-right corner of the image
+    pub fn with_visible_attribution(mut self, visible: bool) -> Self {
+        self.visible_attribution = visible;
+        self
+    }
+
+    /// Set the colors tile types are drawn with
+    pub fn with_palette(mut self, palette: TilePalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Render the grid as an SVG document
+    pub fn to_svg_string(&self, grid: &TileGrid) -> String {
+        let metadata = ExportMetadata::from_grid(grid);
+        let attribution = &metadata.attribution;
+        let (width, height) = grid.dimensions();
+        let svg_width = width as f32 * self.cell_size;
+        let svg_height = height as f32 * self.cell_size;
+
+        let mut svg = String::new();
+        // Writing into a String cannot fail
+        let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:cc="http://creativecommons.org/ns#" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = svg_width,
+            h = svg_height
+        );
+        let _ = writeln!(svg, "  <title>OSM tile grid</title>");
+        let _ = writeln!(svg, "  <desc>{}</desc>", escape_xml(&attribution.notice()));
+
+        // Dublin Core / Creative Commons metadata block
+        let _ = writeln!(svg, "  <metadata>");
+        let _ = writeln!(svg, "    <rdf:RDF>");
+        let _ = writeln!(svg, r#"      <cc:Work rdf:about="">"#);
+        let _ = writeln!(
+            svg,
+            "        <dc:source>{}</dc:source>",
+            escape_xml(&attribution.source)
+        );
+        if let Some(timestamp) = &attribution.data_timestamp {
+            let _ = writeln!(
+                svg,
+                "        <dc:coverage>{}</dc:coverage>",
+                escape_xml(timestamp)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            "        <dc:date>{}</dc:date>",
+            escape_xml(&metadata.generated_at)
+        );
+        let _ = writeln!(
+            svg,
+            "        <dc:rights>{}</dc:rights>",
+            escape_xml(&attribution.copyright)
+        );
+        let _ = writeln!(
+            svg,
+            "        <dc:creator>{}</dc:creator>",
+            escape_xml(&metadata.generator)
+        );
+        let _ = writeln!(
+            svg,
+            r#"        <cc:license rdf:resource="{}"/>"#,
+            escape_xml(&attribution.license_url)
+        );
+        let _ = writeln!(svg, "      </cc:Work>");
+        let _ = writeln!(svg, "    </rdf:RDF>");
+        let _ = writeln!(svg, "  </metadata>");
+
+        let (r, g, b) = self.palette.color(&TileType::Empty);
+        let _ = writeln!(
+            svg,
+            r#"  <rect width="{}" height="{}" fill="rgb({},{},{})"/>"#,
+            svg_width, svg_height, r, g, b
+        );
+
+        for (x, y, tile) in grid.iter_tiles() {
+            if tile.tile_type == TileType::Empty {
+                continue;
+            }
+            let image_y = grid.origin.to_top_left(y, height);
+            let (r, g, b) = self.palette.color(&tile.tile_type);
+            let _ = writeln!(
+                svg,
+                r#"  <rect x="{}" y="{}" width="{s}" height="{s}" fill="rgb({},{},{})" class="{}"/>"#,
+                x as f32 * self.cell_size,
+                image_y as f32 * self.cell_size,
+                r,
+                g,
+                b,
+                escape_xml(tile.tile_type.name()),
+                s = self.cell_size
+            );
+        }
+
+        if self.visible_attribution {
+            let font_size = (svg_height * 0.02).max(2.0);
+            let _ = writeln!(
+                svg,
+                r#"  <text x="{}" y="{}" font-size="{}" text-anchor="end" fill="black" fill-opacity="0.7">{}</text>"#,
+                svg_width - font_size * 0.5,
+                svg_height - font_size * 0.5,
+                font_size,
+                escape_xml(&attribution.notice())
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl Default for SvgExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for SvgExporter {
+    fn format_name(&self) -> &'static str {
+        "svg"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "svg"
+    }
+
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        Ok(self.to_svg_string(grid).into_bytes())
+    }
+}
+
+/// Escape text for use in XML content and attribute values
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Attribution, BoundingBox, OsmMetadata, Tile};
+
+    #[test]
+    fn test_svg_export_structure() {
+        let mut grid = TileGrid::new(3, 2, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.set_tile(1, 1, Tile::new(TileType::Water)).unwrap();
+
+        let svg = SvgExporter::new().with_cell_size(10.0).to_svg_string(&grid);
+
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains(r#"width="30" height="20""#));
+        assert!(svg.contains(
+            r#"<rect x="10" y="10" width="10" height="10" fill="rgb(30,144,255)" class="water"/>"#
+        ));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_svg_export_embeds_attribution() {
+        let mut grid = TileGrid::new(3, 3, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.metadata.attribution = Some(Attribution::from_osm_metadata(&OsmMetadata::new(
+            "overpass-api.de",
+            "overpass",
+        )));
+
+        let svg = SvgExporter::new().to_svg_string(&grid);
+        assert!(svg.contains("<dc:source>overpass-api.de</dc:source>"));
+        assert!(svg.contains("<dc:rights>© OpenStreetMap contributors</dc:rights>"));
+        assert!(svg.contains("https://www.openstreetmap.org/copyright"));
+        assert!(svg.contains("<text"));
+
+        let hidden = SvgExporter::new()
+            .with_visible_attribution(false)
+            .to_svg_string(&grid);
+        assert!(!hidden.contains("<text"));
+        assert!(hidden.contains("<dc:rights>"));
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("a & <b>"), "a &amp; &lt;b&gt;");
+        assert_eq!(escape_xml(r#""x""#), "&quot;x&quot;");
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt::Write;
+
+use super::svg::escape_xml;
+use super::{ExportMetadata, GridExporter, TilePalette};
+use crate::{Result, TileGrid, TileType};
+
+/// Numbering of the tile types of a grid, shared by the Tiled and LDtk exporters
+///
+/// Standard tile types come first in [`TileType::STANDARD`] order, so their
+/// IDs are the same for every grid; custom types present in the grid follow
+/// by name. Empty tiles have no ID and are left blank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileIds {
+    types: Vec<TileType>,
+}
+
+impl TileIds {
+    /// Number the standard tile types and the custom types found in `grid`
+    pub fn for_grid(grid: &TileGrid) -> Self {
+        let mut custom: Vec<TileType> = grid
+            .count_tiles_by_type()
+            .into_keys()
+            .filter(|tile_type| matches!(tile_type, TileType::Custom(_)))
+            .collect();
+        custom.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let types = TileType::STANDARD
+            .into_iter()
+            .filter(|tile_type| *tile_type != TileType::Empty)
+            .chain(custom)
+            .collect();
+        Self { types }
+    }
+
+    /// Get the zero-based ID of a tile type, `None` for empty tiles
+    pub fn id(&self, tile_type: &TileType) -> Option<u32> {
+        self.types
+            .iter()
+            .position(|known| known == tile_type)
+            .map(|id| id as u32)
+    }
+
+    /// Get the tile types in ID order
+    pub fn tile_types(&self) -> &[TileType] {
+        &self.types
+    }
+}
+
+/// Exports a grid as a Tiled map (`.tmx`) with an external tileset (`.tsx`)
+///
+/// The map has one CSV-encoded tile layer and references the tileset
+/// `{name}.tsx`, whose tiles carry their tile type as class and palette
+/// color as property and are drawn from the image `{name}.png` (one solid
+/// square per tile type, from [`TiledExporter::tileset_png`] with the
+/// `png-export` feature). The attribution is stored in the map properties.
+pub struct TiledExporter {
+    /// Tile width and height in pixels
+    tile_size: u32,
+    /// File name of the tileset and its image, without extension
+    tileset_name: String,
+    /// Colors of the tile types
+    palette: TilePalette,
+}
+
+impl TiledExporter {
+    /// Create an exporter with 16 pixel tiles and the tileset `osm-tiles.tsx`
+    pub fn new() -> Self {
+        Self {
+            tile_size: 16,
+            tileset_name: "osm-tiles".to_string(),
+            palette: TilePalette::default(),
+        }
+    }
+
+    /// Set the tile width and height in pixels
+    pub fn with_tile_size(mut self, pixels: u32) -> Self {
+        self.tile_size = pixels.max(1);
+        self
+    }
+
+    /// Set the file name of the tileset and its image, without extension
+    pub fn with_tileset_name(mut self, name: impl Into<String>) -> Self {
+        self.tileset_name = name.into();
+        self
+    }
+
+    /// Set the colors tile types are drawn with
+    pub fn with_palette(mut self, palette: TilePalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Build the map document
+    pub fn to_tmx_string(&self, grid: &TileGrid) -> String {
+        let (width, height) = grid.dimensions();
+        let ids = TileIds::for_grid(grid);
+        let metadata = ExportMetadata::from_grid(grid);
+
+        let mut tmx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            tmx,
+            "<map version=\"1.10\" orientation=\"orthogonal\" renderorder=\"right-down\" width=\"{}\" height=\"{}\" tilewidth=\"{size}\" tileheight=\"{size}\" infinite=\"0\" nextlayerid=\"2\" nextobjectid=\"1\">",
+            width,
+            height,
+            size = self.tile_size
+        );
+        tmx.push_str(" <properties>\n");
+        for (key, value) in metadata.to_key_values() {
+            let _ = writeln!(
+                tmx,
+                "  <property name=\"{}\" value=\"{}\"/>",
+                key,
+                escape_xml(&value)
+            );
+        }
+        let _ = writeln!(
+            tmx,
+            "  <property name=\"meters_per_tile\" type=\"float\" value=\"{}\"/>",
+            grid.meters_per_tile
+        );
+        tmx.push_str(" </properties>\n");
+        let _ = writeln!(
+            tmx,
+            " <tileset firstgid=\"1\" source=\"{}.tsx\"/>",
+            escape_xml(&self.tileset_name)
+        );
+        let _ = writeln!(
+            tmx,
+            " <layer id=\"1\" name=\"tiles\" width=\"{}\" height=\"{}\">",
+            width, height
+        );
+        tmx.push_str("  <data encoding=\"csv\">\n");
+
+        // Rows run north to south; global IDs start at 1, 0 is a blank cell
+        for image_y in 0..height {
+            let row: Vec<String> = (0..width)
+                .map(|x| {
+                    grid.from_image_coords(x, image_y)
+                        .and_then(|(x, y)| grid.get_tile(x, y))
+                        .and_then(|tile| ids.id(&tile.tile_type))
+                        .map_or(0, |id| id + 1)
+                        .to_string()
+                })
+                .collect();
+            let separator = if image_y + 1 < height { "," } else { "" };
+            let _ = writeln!(tmx, "{}{}", row.join(","), separator);
+        }
+        tmx.push_str("  </data>\n </layer>\n</map>\n");
+        tmx
+    }
+
+    /// Build the tileset document for the tile types of `grid`
+    pub fn to_tsx_string(&self, grid: &TileGrid) -> String {
+        let ids = TileIds::for_grid(grid);
+        let count = ids.tile_types().len() as u32;
+
+        let mut tsx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            tsx,
+            "<tileset version=\"1.10\" name=\"{}\" tilewidth=\"{size}\" tileheight=\"{size}\" tilecount=\"{count}\" columns=\"{count}\">",
+            escape_xml(&self.tileset_name),
+            size = self.tile_size,
+            count = count
+        );
+        let _ = writeln!(
+            tsx,
+            " <image source=\"{}.png\" width=\"{}\" height=\"{}\"/>",
+            escape_xml(&self.tileset_name),
+            count * self.tile_size,
+            self.tile_size
+        );
+        for (id, tile_type) in ids.tile_types().iter().enumerate() {
+            let (r, g, b) = self.palette.color(tile_type);
+            let _ = writeln!(
+                tsx,
+                " <tile id=\"{}\" type=\"{}\">\n  <properties>\n   <property name=\"color\" type=\"color\" value=\"#ff{:02x}{:02x}{:02x}\"/>\n  </properties>\n </tile>",
+                id,
+                escape_xml(tile_type.name()),
+                r,
+                g,
+                b
+            );
+        }
+        tsx.push_str("</tileset>\n");
+        tsx
+    }
+
+    /// Render the tileset image: one solid square per tile type, in ID order
+    #[cfg(feature = "png-export")]
+    pub fn tileset_png(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        let ids = TileIds::for_grid(grid);
+        let mut strip = TileGrid::new(
+            ids.tile_types().len(),
+            1,
+            grid.bounding_box.clone(),
+            grid.meters_per_tile,
+        );
+        strip.metadata = grid.metadata.clone();
+        for (x, tile_type) in ids.tile_types().iter().enumerate() {
+            strip
+                .set_tile(x, 0, crate::Tile::new(tile_type.clone()))
+                .map_err(crate::OsmTilesError::Export)?;
+        }
+        super::PngExporter::new()
+            .with_scale(self.tile_size)
+            .with_palette(self.palette.clone())
+            .export(&strip)
+    }
+}
+
+impl Default for TiledExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridExporter for TiledExporter {
+    fn format_name(&self) -> &'static str {
+        "tiled"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "tmx"
+    }
+
+    /// Encode the map; write [`TiledExporter::to_tsx_string`] and the tileset image next to it
+    fn export(&self, grid: &TileGrid) -> Result<Vec<u8>> {
+        Ok(self.to_tmx_string(grid).into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile};
+
+    #[test]
+    fn test_tiled_export() {
+        let mut grid = TileGrid::new(3, 2, BoundingBox::new(52.0, 13.0, 53.0, 14.0), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+        grid.set_tile(2, 1, Tile::new(TileType::Custom("lava".into())))
+            .unwrap();
+
+        let ids = TileIds::for_grid(&grid);
+        assert_eq!(ids.id(&TileType::Road), Some(0));
+        assert_eq!(ids.id(&TileType::Custom("lava".into())), Some(11));
+        assert_eq!(ids.id(&TileType::Empty), None);
+
+        let exporter = TiledExporter::new().with_tileset_name("city");
+        let tmx = exporter.to_tmx_string(&grid);
+        assert!(tmx.contains("<tileset firstgid=\"1\" source=\"city.tsx\"/>"));
+        assert!(tmx.contains("OpenStreetMap contributors"));
+        assert!(tmx.contains("1,0,0,\n0,0,12\n"));
+
+        let tsx = exporter.to_tsx_string(&grid);
+        assert!(tsx.contains("tilecount=\"12\""));
+        assert!(tsx.contains("<image source=\"city.png\" width=\"192\" height=\"16\"/>"));
+        assert!(tsx.contains("<tile id=\"11\" type=\"lava\">"));
+        assert!(tsx.contains("value=\"#ff808080\""));
+
+        #[cfg(feature = "png-export")]
+        {
+            let png = exporter.tileset_png(&grid).unwrap();
+            let reader = ::png::Decoder::new(std::io::Cursor::new(png))
+                .read_info()
+                .unwrap();
+            assert_eq!((reader.info().width, reader.info().height), (192, 16));
+        }
+    }
+}
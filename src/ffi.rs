@@ -0,0 +1,223 @@
+//! C interface for engines outside Rust, such as Godot or Unity
+//!
+//! Build the crate as a shared library with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib` and declare the
+//! functions from `include/osm_tiles.h`. Every call blocks the calling thread
+//! and returns a grid in the [packed format](crate::export::packed), or null on
+//! failure, after which [`osm_tiles_last_error`] describes the problem.
+//! Buffers must be released with [`osm_tiles_free_buffer`].
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::{
+    BlockingGridGenerator, BlockingOsmDataProvider, DefaultGridGenerator, GridExporter, OsmConfig,
+    OsmConfigBuilder, OsmData, OsmTilesError, OverpassProvider, PackedExporter, Region, Result,
+    TileGrid,
+};
+
+thread_local! {
+    /// Message of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Read a required UTF-8 string argument
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+unsafe fn read_str<'a>(text: *const c_char, name: &str) -> Result<&'a str> {
+    if text.is_null() {
+        return Err(OsmTilesError::Config(format!("{} must not be null", name)));
+    }
+    // SAFETY: the caller guarantees a NUL-terminated string
+    unsafe { CStr::from_ptr(text) }
+        .to_str()
+        .map_err(|_| OsmTilesError::Config(format!("{} is not valid UTF-8", name)))
+}
+
+/// Run one generation and pack its grid into a caller-owned buffer, or record the error and return null
+///
+/// Panics are caught and reported as errors, as unwinding into the caller's
+/// stack frames is undefined behavior. Without `out_len` the caller could not
+/// release the buffer, so a null `out_len` is rejected before generating.
+///
+/// # Safety
+///
+/// `out_len` must be null or point to writable memory.
+unsafe fn generate_into(
+    out_len: *mut usize,
+    generate: impl FnOnce() -> Result<TileGrid>,
+) -> *mut u8 {
+    let result = if out_len.is_null() {
+        Err(OsmTilesError::Config(
+            "out_len must not be null".to_string(),
+        ))
+    } else {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            generate().and_then(|grid| PackedExporter::new().export(&grid))
+        }))
+        .unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+            Err(OsmTilesError::GridGeneration(format!(
+                "Generation panicked: {}",
+                message
+            )))
+        })
+    };
+
+    match result {
+        Ok(bytes) => {
+            LAST_ERROR.with(|error| error.borrow_mut().take());
+            let bytes = bytes.into_boxed_slice();
+            // SAFETY: checked for null above, the caller guarantees it is writable
+            unsafe { *out_len = bytes.len() };
+            Box::into_raw(bytes).cast()
+        }
+        Err(e) => {
+            let message =
+                CString::new(e.to_string().replace('\0', " ")).expect("NUL bytes were replaced");
+            LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+            if !out_len.is_null() {
+                // SAFETY: checked for null, the caller guarantees it is writable
+                unsafe { *out_len = 0 };
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Download the region of a JSON configuration from Overpass and generate its grid
+///
+/// # Safety
+///
+/// `config_json` must point to a NUL-terminated string and `out_len` must
+/// point to writable memory; a null `out_len` fails the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn osm_tiles_generate(
+    config_json: *const c_char,
+    out_len: *mut usize,
+) -> *mut u8 {
+    // SAFETY: forwarded from the caller
+    unsafe {
+        generate_into(out_len, || {
+            let config = OsmConfig::from_json_str(read_str(config_json, "config_json")?)?;
+            let data = OverpassProvider::new().fetch_data_blocking(&config)?;
+            DefaultGridGenerator::new().generate_grid_blocking(&data, &config)
+        })
+    }
+}
+
+/// Generate the grid of a saved download, without network access
+///
+/// `data_json` is an [`OsmData`] document as saved by `osm-tiles fetch`.
+/// `config_json` may be null to use the default settings for the
+/// downloaded bounding box.
+///
+/// # Safety
+///
+/// `data_json` must point to a NUL-terminated string, `config_json` must be
+/// null or point to one, and `out_len` must point to writable memory; a null
+/// `out_len` fails the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn osm_tiles_generate_from_data(
+    data_json: *const c_char,
+    config_json: *const c_char,
+    out_len: *mut usize,
+) -> *mut u8 {
+    // SAFETY: forwarded from the caller
+    unsafe {
+        generate_into(out_len, || {
+            let data: OsmData = serde_json::from_str(read_str(data_json, "data_json")?)
+                .map_err(|e| OsmTilesError::json("saved download", e))?;
+            let config = if config_json.is_null() {
+                OsmConfigBuilder::new()
+                    .region(Region::BoundingBox(data.bounding_box.clone()))
+                    .build()
+            } else {
+                OsmConfig::from_json_str(read_str(config_json, "config_json")?)?
+            };
+            DefaultGridGenerator::new().generate_grid_blocking(&data, &config)
+        })
+    }
+}
+
+/// Release a buffer returned by this library
+///
+/// # Safety
+///
+/// `buffer` must be null or a pointer returned by this library together with
+/// the length it reported, and must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn osm_tiles_free_buffer(buffer: *mut u8, len: usize) {
+    if !buffer.is_null() {
+        // SAFETY: the caller passes back a boxed slice created in `finish`
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(buffer, len)) });
+    }
+}
+
+/// Describe why the last call on this thread failed, or null if it succeeded
+///
+/// The string stays valid until the next call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn osm_tiles_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProvider;
+
+    #[test]
+    fn test_generate_from_data() {
+        let config = OsmConfigBuilder::new()
+            .bbox(52.5, 13.4, 52.51, 13.41)
+            .grid_resolution(2000)
+            .build();
+        let data = MockProvider::new().fetch_data_blocking(&config).unwrap();
+        let data_json = CString::new(serde_json::to_string(&data).unwrap()).unwrap();
+
+        let mut len = 0;
+        let buffer =
+            unsafe { osm_tiles_generate_from_data(data_json.as_ptr(), ptr::null(), &mut len) };
+        assert!(!buffer.is_null());
+        assert!(osm_tiles_last_error().is_null());
+        let grid =
+            TileGrid::from_packed(unsafe { std::slice::from_raw_parts(buffer, len) }).unwrap();
+        assert!(grid.dimensions().0 > 0);
+        unsafe { osm_tiles_free_buffer(buffer, len) };
+
+        let broken = CString::new("{").unwrap();
+        let buffer =
+            unsafe { osm_tiles_generate_from_data(broken.as_ptr(), ptr::null(), &mut len) };
+        assert!(buffer.is_null());
+        assert_eq!(len, 0);
+        let message = unsafe { CStr::from_ptr(osm_tiles_last_error()) };
+        assert!(message.to_str().unwrap().contains("saved download"));
+
+        // The length is needed to free the buffer, so it cannot be skipped
+        let buffer = unsafe {
+            osm_tiles_generate_from_data(data_json.as_ptr(), ptr::null(), ptr::null_mut())
+        };
+        assert!(buffer.is_null());
+        let message = unsafe { CStr::from_ptr(osm_tiles_last_error()) };
+        assert!(message.to_str().unwrap().contains("out_len"));
+    }
+
+    #[test]
+    fn test_panics_become_errors() {
+        let mut len = 1;
+        let buffer = unsafe { generate_into(&mut len, || panic!("grid exploded")) };
+        assert!(buffer.is_null());
+        assert_eq!(len, 0);
+        let message = unsafe { CStr::from_ptr(osm_tiles_last_error()) };
+        assert!(message.to_str().unwrap().contains("grid exploded"));
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{GridPostProcessor, Tile, TileGrid};
+
+/// Named numeric values attached to a tile, e.g. walking cost, land value or noise level
+///
+/// Most tiles carry no attributes, so the empty map allocates nothing. Entries are kept sorted by name and serialize as a JSON
+/// object.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TileAttributes(Option<Box<[(String, f32)]>>);
+
+impl TileAttributes {
+    /// Get the value of an attribute
+    pub fn get(&self, name: &str) -> Option<f32> {
+        let entries = self.0.as_deref()?;
+        let index = entries
+            .binary_search_by(|(key, _)| key.as_str().cmp(name))
+            .ok()?;
+        Some(entries[index].1)
+    }
+
+    /// Set an attribute, returning its previous value
+    pub fn set(&mut self, name: impl Into<String>, value: f32) -> Option<f32> {
+        let name = name.into();
+        let mut entries = self.0.take().map(Vec::from).unwrap_or_default();
+        let previous = match entries.binary_search_by(|(key, _)| key.cmp(&name)) {
+            Ok(index) => Some(std::mem::replace(&mut entries[index].1, value)),
+            Err(index) => {
+                entries.insert(index, (name, value));
+                None
+            }
+        };
+        self.0 = Some(entries.into_boxed_slice());
+        previous
+    }
+
+    /// Remove an attribute, returning its value
+    pub fn remove(&mut self, name: &str) -> Option<f32> {
+        let index = self
+            .0
+            .as_deref()?
+            .binary_search_by(|(key, _)| key.as_str().cmp(name))
+            .ok()?;
+        let mut entries = Vec::from(self.0.take()?);
+        let (_, value) = entries.remove(index);
+        self.0 = (!entries.is_empty()).then(|| entries.into_boxed_slice());
+        Some(value)
+    }
+
+    /// Iterate over the attributes sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.0
+            .iter()
+            .flat_map(|entries| entries.iter())
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Number of attributes
+    pub fn len(&self) -> usize {
+        self.0.as_ref().map_or(0, |entries| entries.len())
+    }
+
+    /// Check if no attribute is set
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl Serialize for TileAttributes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for TileAttributes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = BTreeMap::<String, f32>::deserialize(deserializer)?;
+        Ok(Self(
+            (!entries.is_empty()).then(|| entries.into_iter().collect()),
+        ))
+    }
+}
+
+impl Tile {
+    /// Get the value of a tile attribute
+    pub fn attribute(&self, name: &str) -> Option<f32> {
+        self.attributes.get(name)
+    }
+
+    /// Set a tile attribute, e.g. computed by a gameplay system
+    pub fn set_attribute(&mut self, name: impl Into<String>, value: f32) -> Option<f32> {
+        self.attributes.set(name, value)
+    }
+
+    /// Attach an attribute while building a tile
+    pub fn with_attribute(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.attributes.set(name, value);
+        self
+    }
+}
+
+/// Post-processing pass computing one attribute for every tile
+///
+/// The function sees each tile in turn; tiles it returns `None` for are left
+/// without the attribute.
+///
+/// ```
+/// use bevy_osm_tiles::{AssignAttribute, TileType};
+///
+/// let walk_cost = AssignAttribute::new("walk_cost", |tile| match tile.tile_type {
+///     TileType::Road | TileType::GreenSpace => Some(1.0),
+///     TileType::Building => Some(5.0),
+///     _ => None,
+/// });
+/// ```
+pub struct AssignAttribute {
+    name: String,
+    value: Box<AttributeFn>,
+}
+
+type AttributeFn = dyn Fn(&Tile) -> Option<f32> + Send + Sync;
+
+impl AssignAttribute {
+    /// Assign `name` from `value` for each tile
+    pub fn new(
+        name: impl Into<String>,
+        value: impl Fn(&Tile) -> Option<f32> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            value: Box::new(value),
+        }
+    }
+}
+
+impl fmt::Debug for AssignAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssignAttribute")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GridPostProcessor for AssignAttribute {
+    fn name(&self) -> &'static str {
+        "assign_attribute"
+    }
+
+    fn process(&self, grid: &mut TileGrid) -> usize {
+        let mut changed = 0;
+        for tile in grid.flat_tiles_mut() {
+            let value = (self.value)(tile);
+            let previous = match value {
+                Some(value) => tile.attributes.set(self.name.as_str(), value),
+                None => tile.attributes.remove(&self.name),
+            };
+            if previous != value {
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileType};
+
+    #[test]
+    fn test_tile_attributes() {
+        let mut tile = Tile::new(TileType::Road).with_attribute("walk_cost", 1.0);
+        assert_eq!(tile.set_attribute("noise", 0.8), None);
+        assert_eq!(tile.set_attribute("walk_cost", 1.5), Some(1.0));
+        assert_eq!(tile.attribute("walk_cost"), Some(1.5));
+        assert_eq!(
+            tile.attributes.iter().collect::<Vec<_>>(),
+            [("noise", 0.8), ("walk_cost", 1.5)]
+        );
+        assert_eq!(tile.attributes.remove("noise"), Some(0.8));
+        assert_eq!(tile.attributes.remove("walk_cost"), Some(1.5));
+        assert!(tile.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_assign_attribute_round_trip() {
+        let mut grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        grid.set_tile(1, 1, Tile::new(TileType::Building)).unwrap();
+        let land_value = AssignAttribute::new("land_value", |tile| {
+            (tile.tile_type == TileType::Building).then_some(120.0)
+        });
+        assert_eq!(land_value.process(&mut grid), 1);
+        assert_eq!(land_value.process(&mut grid), 0);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: TileGrid = serde_json::from_str(&json).unwrap();
+        let tile = loaded.get_tile(1, 1).unwrap();
+        assert_eq!(tile.attribute("land_value"), Some(120.0));
+        assert!(loaded.get_tile(0, 0).unwrap().attributes.is_empty());
+    }
+}
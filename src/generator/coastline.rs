@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+
+use super::{OsmElement, Tile, TileGrid, TileType};
+
+/// Distances (in tiles) at which both sides of a coastline segment are sampled
+const SIDE_OFFSETS: [f64; 2] = [0.75, 1.5];
+
+/// Fill the sea side of coastlines with water, returning the number of filled tiles
+///
+/// OSM coastlines keep land on their left and water on their right. The
+/// coastlines split the grid into regions; every segment votes for the
+/// regions next to its two sides, and regions with more water votes than
+/// land votes are filled. Voting keeps a single broken or clipped coastline
+/// from flooding the land. Tiles on the coastline itself stay untouched.
+pub(crate) fn fill_sea(grid: &mut TileGrid, coastlines: &[OsmElement]) -> usize {
+    let (width, height) = grid.dimensions();
+    if width == 0 || height == 0 {
+        return 0;
+    }
+
+    // Segments in fractional top-left image coordinates
+    let segments: Vec<((f64, f64), (f64, f64))> = coastlines
+        .iter()
+        .flat_map(|coastline| {
+            coastline.geometry.windows(2).map(|window| {
+                (
+                    grid.image_position(window[0].0, window[0].1),
+                    grid.image_position(window[1].0, window[1].1),
+                )
+            })
+        })
+        .collect();
+
+    let mut shore = vec![false; width * height];
+    for &(start, end) in &segments {
+        mark_segment(&mut shore, width, height, start, end);
+    }
+
+    let regions = label_regions(&shore, width, height);
+    let region_count = regions.iter().flatten().max().map_or(0, |max| max + 1);
+    let mut votes = vec![0i64; region_count];
+
+    let cell = |(x, y): (f64, f64)| {
+        (x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64)
+            .then(|| y as usize * width + x as usize)
+    };
+    for &((x1, y1), (x2, y2)) in &segments {
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            continue;
+        }
+        // With y pointing south, (-dy, dx) points to the right of the direction of travel
+        let (right_x, right_y) = (-dy / length, dx / length);
+        let (mid_x, mid_y) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+
+        for offset in SIDE_OFFSETS {
+            for (side, vote) in [(offset, 1), (-offset, -1)] {
+                let sample = (mid_x + right_x * side, mid_y + right_y * side);
+                if let Some(region) = cell(sample).and_then(|index| regions[index]) {
+                    votes[region] += vote;
+                }
+            }
+        }
+    }
+
+    let mut filled = 0;
+    let sea = Tile::new(TileType::Water);
+    for (index, region) in regions.iter().enumerate() {
+        if let Some(region) = region
+            && votes[*region] > 0
+        {
+            let x = index % width;
+            let y = grid.origin.from_top_left(index / width, height);
+            if grid.set_tile(x, y, sea.clone()).is_ok() {
+                filled += 1;
+            }
+        }
+    }
+    filled
+}
+
+/// Mark every cell a segment passes through, clipping it to the grid first
+fn mark_segment(
+    shore: &mut [bool],
+    width: usize,
+    height: usize,
+    start: (f64, f64),
+    end: (f64, f64),
+) {
+    let Some((start, end)) = clip_segment(start, end, width as f64, height as f64) else {
+        return;
+    };
+
+    // Half-cell steps never skip a cell, so the line is 8-connected and
+    // blocks the 4-connected region labeling
+    let steps = ((end.0 - start.0).abs().max((end.1 - start.1).abs()) * 2.0).ceil() as usize + 1;
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        let x = (start.0 + (end.0 - start.0) * t) as usize;
+        let y = (start.1 + (end.1 - start.1) * t) as usize;
+        shore[y.min(height - 1) * width + x.min(width - 1)] = true;
+    }
+}
+
+/// Clip a segment to the rectangle `[0, width] × [0, height]` (Liang–Barsky)
+fn clip_segment(
+    start: (f64, f64),
+    end: (f64, f64),
+    width: f64,
+    height: f64,
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let mut t0: f64 = 0.0;
+    let mut t1: f64 = 1.0;
+
+    for (p, q) in [
+        (-dx, start.0),
+        (dx, width - start.0),
+        (-dy, start.1),
+        (dy, height - start.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+
+    (t0 <= t1).then_some((
+        (start.0 + dx * t0, start.1 + dy * t0),
+        (start.0 + dx * t1, start.1 + dy * t1),
+    ))
+}
+
+/// Label the 4-connected regions of non-shore cells
+fn label_regions(shore: &[bool], width: usize, height: usize) -> Vec<Option<usize>> {
+    let mut regions = vec![None; shore.len()];
+    let mut next = 0;
+    let mut queue = VecDeque::new();
+
+    for start in 0..shore.len() {
+        if shore[start] || regions[start].is_some() {
+            continue;
+        }
+        regions[start] = Some(next);
+        queue.push_back(start);
+
+        while let Some(index) = queue.pop_front() {
+            let (x, y) = (index % width, index / width);
+            let neighbors = [
+                (x > 0).then(|| index - 1),
+                (x + 1 < width).then(|| index + 1),
+                (y > 0).then(|| index - width),
+                (y + 1 < height).then(|| index + width),
+            ];
+            for neighbor in neighbors.into_iter().flatten() {
+                if !shore[neighbor] && regions[neighbor].is_none() {
+                    regions[neighbor] = Some(next);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        next += 1;
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, GridOrigin, OsmElementType};
+
+    fn coastline(geometry: Vec<(f64, f64)>) -> OsmElement {
+        OsmElement {
+            id: 1,
+            element_type: OsmElementType::Way,
+            holes: Vec::new(),
+            tags: [("natural".to_string(), "coastline".to_string())].into(),
+            geometry,
+        }
+    }
+
+    #[test]
+    fn test_sea_side_is_filled() {
+        let mut grid = TileGrid::new(20, 20, BoundingBox::new(54.0, 10.0, 54.2, 10.2), 10.0);
+        // Running west to east through the middle keeps land to the north
+        let coast = coastline(vec![(54.1, 9.9), (54.1, 10.3)]);
+        let filled = fill_sea(&mut grid, &[coast]);
+
+        assert!(filled > 150);
+        // Grid y 0 is the northern edge with the default origin
+        assert_eq!(grid.get_tile(5, 2).unwrap().tile_type, TileType::Empty);
+        assert_eq!(grid.get_tile(5, 17).unwrap().tile_type, TileType::Water);
+    }
+
+    #[test]
+    fn test_island_interior_stays_land() {
+        let mut grid = TileGrid::new(30, 30, BoundingBox::new(54.0, 10.0, 54.3, 10.3), 10.0)
+            .with_origin(GridOrigin::BottomLeft);
+        // Islands are drawn counter-clockwise, keeping the land on the left
+        let island = coastline(vec![
+            (54.1, 10.1),
+            (54.1, 10.2),
+            (54.2, 10.2),
+            (54.2, 10.1),
+            (54.1, 10.1),
+        ]);
+        fill_sea(&mut grid, &[island]);
+
+        let center = grid.geo_to_grid(54.15, 10.15).unwrap();
+        let offshore = grid.geo_to_grid(54.02, 10.02).unwrap();
+        assert_eq!(
+            grid.get_tile(center.0, center.1).unwrap().tile_type,
+            TileType::Empty
+        );
+        assert_eq!(
+            grid.get_tile(offshore.0, offshore.1).unwrap().tile_type,
+            TileType::Water
+        );
+    }
+
+    #[test]
+    fn test_clip_segment() {
+        assert_eq!(
+            clip_segment((-5.0, 5.0), (15.0, 5.0), 10.0, 10.0),
+            Some(((0.0, 5.0), (10.0, 5.0)))
+        );
+        assert_eq!(clip_segment((-5.0, -1.0), (15.0, -1.0), 10.0, 10.0), None);
+    }
+}
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{Crs, Districts, GridMetadata, GridOrigin, PoiIndex, Tile, TileGrid, TileType};
+use crate::{BoundingBox, OsmTilesError};
+
+/// Palette-compressed tile grid storing one `u32` index per cell
+///
+/// Each distinct tile (type plus shared metadata) is stored once in the
+/// palette. Large grids are dominated by empty tiles and by cells rasterized
+/// from the same element, so the palette stays small and a 5000×5000 grid
+/// needs about 100 MB instead of the ~800 MB of full tiles.
+///
+/// Deserialization fails unless there is one cell per tile and every cell
+/// refers to a palette entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "CompactTileGridData")]
+pub struct CompactTileGrid {
+    /// Distinct tiles referenced by the cells
+    palette: Vec<Tile>,
+    /// Palette index of every cell in row-major order
+    cells: Vec<u32>,
+    /// Grid width
+    width: usize,
+    /// Grid height
+    height: usize,
+    /// Geographic bounding box this grid represents
+    pub bounding_box: BoundingBox,
+    /// Meters per tile (approximately)
+    pub meters_per_tile: f32,
+    /// Coordinate reference system the grid is laid out in
+    pub crs: Crs,
+    /// Corner row 0 starts at
+    #[serde(default)]
+    pub origin: GridOrigin,
+    /// Points of interest collected during generation
+    #[serde(default)]
+    pub pois: PoiIndex,
+    /// Named administrative areas
+    #[serde(default)]
+    pub districts: Districts,
+    /// Grid generation metadata
+    pub metadata: GridMetadata,
+}
+
+/// Unchecked serialized form of a [`CompactTileGrid`]
+#[derive(Deserialize)]
+struct CompactTileGridData {
+    palette: Vec<Tile>,
+    cells: Vec<u32>,
+    width: usize,
+    height: usize,
+    bounding_box: BoundingBox,
+    meters_per_tile: f32,
+    crs: Crs,
+    #[serde(default)]
+    origin: GridOrigin,
+    #[serde(default)]
+    pois: PoiIndex,
+    #[serde(default)]
+    districts: Districts,
+    metadata: GridMetadata,
+}
+
+impl TryFrom<CompactTileGridData> for CompactTileGrid {
+    type Error = OsmTilesError;
+
+    fn try_from(data: CompactTileGridData) -> Result<Self, Self::Error> {
+        let expected = data.width.checked_mul(data.height);
+        if expected != Some(data.cells.len()) {
+            return Err(OsmTilesError::Parse(format!(
+                "Compact grid of {}x{} tiles has {} cells",
+                data.width,
+                data.height,
+                data.cells.len()
+            )));
+        }
+        if let Some(&index) = data
+            .cells
+            .iter()
+            .find(|&&index| index as usize >= data.palette.len())
+        {
+            return Err(OsmTilesError::Parse(format!(
+                "Compact grid cell refers to palette entry {} of {}",
+                index,
+                data.palette.len()
+            )));
+        }
+
+        Ok(Self {
+            palette: data.palette,
+            cells: data.cells,
+            width: data.width,
+            height: data.height,
+            bounding_box: data.bounding_box,
+            meters_per_tile: data.meters_per_tile,
+            crs: data.crs,
+            origin: data.origin,
+            pois: data.pois,
+            districts: data.districts,
+            metadata: data.metadata,
+        })
+    }
+}
+
+impl CompactTileGrid {
+    /// Get the grid dimensions (width, height)
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get a tile at the given grid coordinates
+    pub fn get_tile(&self, x: usize, y: usize) -> Option<&Tile> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = self.cells[y * self.width + x];
+        self.palette.get(index as usize)
+    }
+
+    /// Get the distinct tiles referenced by the grid
+    pub fn palette(&self) -> &[Tile] {
+        &self.palette
+    }
+
+    /// Count tiles by type without visiting tile data per cell
+    pub fn count_tiles_by_type(&self) -> HashMap<TileType, usize> {
+        let mut per_entry = vec![0usize; self.palette.len()];
+        for &index in &self.cells {
+            per_entry[index as usize] += 1;
+        }
+
+        let mut counts = HashMap::new();
+        for (tile, count) in self.palette.iter().zip(per_entry) {
+            if count > 0 {
+                *counts.entry(tile.tile_type.clone()).or_insert(0) += count;
+            }
+        }
+        counts
+    }
+
+    /// Expand back into a regular tile grid, sharing metadata with the palette
+    pub fn to_grid(&self) -> TileGrid {
+        let mut grid = TileGrid::new(
+            self.width,
+            self.height,
+            self.bounding_box.clone(),
+            self.meters_per_tile,
+        )
+        .with_crs(self.crs)
+        .with_origin(self.origin);
+        grid.pois = self.pois.clone();
+        grid.districts = self.districts.clone();
+        grid.metadata = self.metadata.clone();
+
+        for (tile, &index) in grid.flat_tiles_mut().iter_mut().zip(&self.cells) {
+            *tile = self.palette[index as usize].clone();
+        }
+        grid
+    }
+}
+
+/// Type, metadata pointer, coverage bits and attribute bits identifying a palette entry
+type PaletteKey<'a> = (&'a TileType, usize, u32, Vec<(&'a str, u32)>);
+
+impl TileGrid {
+    /// Build a palette-compressed copy of this grid
+    ///
+    /// Tiles are deduplicated by type, coverage, attributes and identity of
+    /// their shared metadata, so tiles that only happen to carry equal
+    /// metadata stay separate palette entries.
+    pub fn to_compact(&self) -> CompactTileGrid {
+        let mut palette = Vec::new();
+        let mut lookup: HashMap<PaletteKey, u32> = HashMap::new();
+
+        let cells = self
+            .flat_tiles()
+            .iter()
+            .map(|tile| {
+                let metadata_id = tile.metadata.as_ref().map_or(0, |m| m.as_ptr() as usize);
+                let attributes = tile
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| (name, value.to_bits()))
+                    .collect();
+                *lookup
+                    .entry((
+                        &tile.tile_type,
+                        metadata_id,
+                        tile.coverage.to_bits(),
+                        attributes,
+                    ))
+                    .or_insert_with(|| {
+                        palette.push(tile.clone());
+                        (palette.len() - 1) as u32
+                    })
+            })
+            .collect();
+
+        let (width, height) = self.dimensions();
+        CompactTileGrid {
+            palette,
+            cells,
+            width,
+            height,
+            bounding_box: self.bounding_box.clone(),
+            meters_per_tile: self.meters_per_tile,
+            crs: self.crs,
+            origin: self.origin,
+            pois: self.pois.clone(),
+            districts: self.districts.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileMetadata;
+
+    fn test_grid() -> TileGrid {
+        let mut grid = TileGrid::new(50, 40, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        let park = Tile::with_metadata(
+            TileType::GreenSpace,
+            TileMetadata {
+                osm_ids: vec![7],
+                ..Default::default()
+            },
+        );
+        for y in 5..30 {
+            for x in 10..40 {
+                grid.set_tile(x, y, park.clone()).unwrap();
+            }
+        }
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+        grid
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        let grid = test_grid();
+        let compact = grid.to_compact();
+
+        // Empty, park and road
+        assert_eq!(compact.palette().len(), 3);
+        assert_eq!(compact.dimensions(), grid.dimensions());
+        assert_eq!(compact.count_tiles_by_type(), grid.count_tiles_by_type());
+        assert_eq!(
+            compact.get_tile(12, 6).unwrap().tile_type,
+            TileType::GreenSpace
+        );
+        assert!(compact.get_tile(50, 0).is_none());
+
+        let restored = compact.to_grid();
+        for ((_, _, a), (_, _, b)) in grid.iter_tiles().zip(restored.iter_tiles()) {
+            assert_eq!(a.tile_type, b.tile_type);
+            assert_eq!(
+                a.metadata.as_ref().map(|m| m.osm_ids.clone()),
+                b.metadata.as_ref().map(|m| m.osm_ids.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_compact_serialization() {
+        let compact = test_grid().to_compact();
+        let json = serde_json::to_string(&compact).unwrap();
+        let loaded: CompactTileGrid = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.count_tiles_by_type(), compact.count_tiles_by_type());
+
+        // Cell counts and palette indices are checked when loading
+        let mut value = serde_json::to_value(&compact).unwrap();
+        value["cells"].as_array_mut().unwrap().pop();
+        let error = serde_json::from_value::<CompactTileGrid>(value).unwrap_err();
+        assert!(error.to_string().contains("1999 cells"));
+
+        let mut value = serde_json::to_value(&compact).unwrap();
+        value["cells"][3] = 3.into();
+        let error = serde_json::from_value::<CompactTileGrid>(value).unwrap_err();
+        assert!(error.to_string().contains("palette entry 3 of 3"));
+    }
+}
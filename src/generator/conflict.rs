@@ -0,0 +1,403 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Tile, TileGrid, TileType};
+
+/// Outcome of resolving a conflict between an existing and an incoming tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Leave the existing tile untouched
+    KeepExisting,
+    /// Overwrite the existing tile with the incoming one
+    Replace,
+    /// Combine both tiles into a multi-type tile
+    Merge,
+}
+
+/// Callback deciding how to resolve a conflict, given the existing and the incoming tile
+pub type ConflictCallback = Arc<dyn Fn(&Tile, &Tile) -> ConflictResolution + Send + Sync>;
+
+/// How the generator resolves tiles touched by more than one OSM element
+///
+/// Empty tiles are always overwritten; the strategy only decides between two
+/// non-empty tiles.
+#[derive(Clone, Default)]
+pub enum ConflictStrategy {
+    /// Higher priority tile types overwrite lower ones (e.g., roads over parks)
+    #[default]
+    Priority,
+    /// The first element to touch a tile keeps it
+    KeepFirst,
+    /// The element enclosing the largest area keeps the tile
+    KeepLargestArea,
+    /// All types are kept: the highest priority becomes the main type, the others secondary
+    Merge,
+    /// A user-supplied callback decides
+    Custom(ConflictCallback),
+}
+
+impl ConflictStrategy {
+    /// Create a strategy from a callback
+    pub fn custom(
+        callback: impl Fn(&Tile, &Tile) -> ConflictResolution + Send + Sync + 'static,
+    ) -> Self {
+        Self::Custom(Arc::new(callback))
+    }
+
+    /// Get the strategy identifier (e.g., "priority", "merge")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Priority => "priority",
+            Self::KeepFirst => "keep_first",
+            Self::KeepLargestArea => "keep_largest_area",
+            Self::Merge => "merge",
+            Self::Custom(_) => "custom",
+        }
+    }
+
+    /// Decide how to resolve a conflict between two tiles
+    pub fn resolve(&self, existing: &Tile, incoming: &Tile) -> ConflictResolution {
+        if existing.tile_type == TileType::Empty {
+            return ConflictResolution::Replace;
+        }
+
+        match self {
+            Self::Priority => {
+                if existing.can_be_overwritten_by(incoming) {
+                    ConflictResolution::Replace
+                } else {
+                    ConflictResolution::KeepExisting
+                }
+            }
+            Self::KeepFirst => ConflictResolution::KeepExisting,
+            Self::KeepLargestArea => {
+                if element_area(incoming) > element_area(existing) {
+                    ConflictResolution::Replace
+                } else {
+                    ConflictResolution::KeepExisting
+                }
+            }
+            Self::Merge => ConflictResolution::Merge,
+            Self::Custom(callback) => callback(existing, incoming),
+        }
+    }
+}
+
+impl fmt::Debug for ConflictStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ConflictStrategy::{}", self.name())
+    }
+}
+
+fn element_area(tile: &Tile) -> f64 {
+    tile.metadata
+        .as_ref()
+        .map_or(0.0, |metadata| metadata.element_area_m2)
+}
+
+impl Tile {
+    /// Merge another tile into this one, keeping every type
+    ///
+    /// The higher priority type becomes the main type and the other is
+    /// recorded as secondary. OSM IDs are combined and existing tags win over
+    /// incoming ones. Returns false if the other tile added no new type or element.
+    pub fn merge(&mut self, other: Tile) -> bool {
+        let mut secondary = if other.tile_type.priority() > self.tile_type.priority() {
+            std::mem::replace(&mut self.tile_type, other.tile_type)
+        } else {
+            other.tile_type
+        };
+        if secondary == self.tile_type {
+            secondary = TileType::Empty;
+        }
+
+        // Check for changes first, so metadata shared with other tiles is only copied when needed
+        let current = self.metadata.as_deref();
+        let adds_type = secondary != TileType::Empty
+            && !current.is_some_and(|metadata| metadata.secondary_types.contains(&secondary));
+        let new_ids: Vec<i64> = other
+            .metadata
+            .iter()
+            .flat_map(|metadata| metadata.osm_ids.iter().copied())
+            .filter(|id| !current.is_some_and(|metadata| metadata.osm_ids.contains(id)))
+            .collect();
+        if !adds_type && new_ids.is_empty() {
+            return false;
+        }
+
+        let main_type = self.tile_type.clone();
+        let metadata = self.metadata_mut();
+        if adds_type {
+            metadata.secondary_types.push(secondary);
+        }
+        metadata.osm_ids.extend(new_ids);
+
+        if let Some(other_metadata) = other.metadata {
+            for (key, value) in &other_metadata.tags {
+                metadata
+                    .tags
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+            for tile_type in &other_metadata.secondary_types {
+                if *tile_type != main_type && !metadata.secondary_types.contains(tile_type) {
+                    metadata.secondary_types.push(tile_type.clone());
+                }
+            }
+            metadata.element_area_m2 = metadata.element_area_m2.max(other_metadata.element_area_m2);
+        }
+
+        true
+    }
+
+    /// Check whether this tile has the given type, as main or secondary type
+    pub fn has_type(&self, tile_type: &TileType) -> bool {
+        self.tile_type == *tile_type
+            || self
+                .metadata
+                .as_ref()
+                .is_some_and(|metadata| metadata.secondary_types.contains(tile_type))
+    }
+}
+
+impl TileGrid {
+    /// Set a tile, resolving conflicts with the existing tile using `strategy`
+    ///
+    /// Returns whether the tile at the position changed.
+    pub fn set_tile_with_strategy(
+        &mut self,
+        x: usize,
+        y: usize,
+        tile: Tile,
+        strategy: &ConflictStrategy,
+    ) -> Result<bool, String> {
+        let (width, height) = self.dimensions();
+        let existing = self.get_tile_mut(x, y).ok_or_else(|| {
+            format!(
+                "Coordinates ({}, {}) out of bounds for grid {}x{}",
+                x, y, width, height
+            )
+        })?;
+
+        match strategy.resolve(existing, &tile) {
+            ConflictResolution::KeepExisting => Ok(false),
+            ConflictResolution::Replace => {
+                *existing = tile;
+                Ok(true)
+            }
+            ConflictResolution::Merge => Ok(existing.merge(tile)),
+        }
+    }
+}
+
+/// Two elements of different types that claimed the same tile, and which one won
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileConflict {
+    /// Type of the element that kept the tile
+    pub winner: TileType,
+    /// OSM ID of the winning element, if known
+    pub winner_id: Option<i64>,
+    /// Type of the element that lost the tile
+    pub loser: TileType,
+    /// OSM ID of the losing element, if known
+    pub loser_id: Option<i64>,
+    /// Whether the losing type was kept as a secondary type
+    pub merged: bool,
+}
+
+/// Both sides of a conflict, captured before it is resolved
+pub(crate) struct Contest {
+    existing: (TileType, Option<i64>),
+    incoming: (TileType, Option<i64>),
+    /// Conflicts the existing tile already recorded, kept if it is replaced
+    history: Vec<TileConflict>,
+}
+
+impl Contest {
+    /// Capture a contest between two non-empty tiles of different types
+    pub(crate) fn new(existing: &Tile, incoming: &Tile) -> Option<Self> {
+        let empty = |tile: &Tile| tile.tile_type == TileType::Empty;
+        if empty(existing) || empty(incoming) || existing.tile_type == incoming.tile_type {
+            return None;
+        }
+        let side = |tile: &Tile| {
+            let id = tile
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.osm_ids.first().copied());
+            (tile.tile_type.clone(), id)
+        };
+        Some(Self {
+            existing: side(existing),
+            incoming: side(incoming),
+            history: existing
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.conflicts.clone())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl TileGrid {
+    /// Record the outcome of a contest after the incoming tile was placed at (x, y)
+    pub(crate) fn record_conflict(&mut self, x: usize, y: usize, contest: Contest) {
+        let Some(tile) = self.get_tile_mut(x, y) else {
+            return;
+        };
+        let Contest {
+            existing,
+            incoming,
+            mut history,
+        } = contest;
+        let ((winner, winner_id), (loser, loser_id)) = if tile.tile_type == incoming.0 {
+            (incoming, existing)
+        } else {
+            (existing, incoming)
+        };
+        history.push(TileConflict {
+            merged: tile.has_type(&loser),
+            winner,
+            winner_id,
+            loser,
+            loser_id,
+        });
+        tile.metadata_mut().conflicts = history;
+    }
+
+    /// Iterate over the recorded conflicts with their tile positions
+    ///
+    /// Conflicts are only recorded by generators with
+    /// [`with_conflict_audit`](crate::DefaultGridGenerator::with_conflict_audit).
+    pub fn conflicts(&self) -> impl Iterator<Item = (usize, usize, &TileConflict)> {
+        self.iter_tiles().flat_map(|(x, y, tile)| {
+            tile.metadata
+                .iter()
+                .flat_map(|metadata| metadata.conflicts.iter())
+                .map(move |conflict| (x, y, conflict))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileMetadata};
+
+    fn tile(tile_type: TileType, id: i64, area: f64) -> Tile {
+        Tile::with_metadata(
+            tile_type,
+            TileMetadata {
+                osm_ids: vec![id],
+                element_area_m2: area,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn resolve_pair(strategy: &ConflictStrategy) -> Tile {
+        let mut grid = TileGrid::new(10, 10, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        grid.set_tile_with_strategy(1, 1, tile(TileType::GreenSpace, 1, 50_000.0), strategy)
+            .unwrap();
+        grid.set_tile_with_strategy(1, 1, tile(TileType::Road, 2, 0.0), strategy)
+            .unwrap();
+        grid.get_tile(1, 1).unwrap().clone()
+    }
+
+    #[test]
+    fn test_builtin_strategies() {
+        assert_eq!(
+            resolve_pair(&ConflictStrategy::Priority).tile_type,
+            TileType::Road
+        );
+        assert_eq!(
+            resolve_pair(&ConflictStrategy::KeepFirst).tile_type,
+            TileType::GreenSpace
+        );
+        assert_eq!(
+            resolve_pair(&ConflictStrategy::KeepLargestArea).tile_type,
+            TileType::GreenSpace
+        );
+
+        let merged = resolve_pair(&ConflictStrategy::Merge);
+        assert_eq!(merged.tile_type, TileType::Road);
+        assert!(merged.has_type(&TileType::GreenSpace));
+        let metadata = merged.metadata.unwrap();
+        assert_eq!(metadata.osm_ids, vec![1, 2]);
+        assert_eq!(metadata.element_area_m2, 50_000.0);
+    }
+
+    #[test]
+    fn test_custom_strategy() {
+        // Water always wins, everything else keeps the first element
+        let strategy = ConflictStrategy::custom(|_, incoming| {
+            if incoming.tile_type == TileType::Water {
+                ConflictResolution::Replace
+            } else {
+                ConflictResolution::KeepExisting
+            }
+        });
+        assert_eq!(strategy.name(), "custom");
+        assert_eq!(resolve_pair(&strategy).tile_type, TileType::GreenSpace);
+
+        let existing = tile(TileType::Building, 1, 100.0);
+        let water = tile(TileType::Water, 2, 10.0);
+        assert_eq!(
+            strategy.resolve(&existing, &water),
+            ConflictResolution::Replace
+        );
+    }
+
+    #[test]
+    fn test_merge_same_element_is_noop() {
+        let mut merged = tile(TileType::Building, 1, 100.0);
+        assert!(!merged.merge(tile(TileType::Building, 1, 100.0)));
+        assert!(merged.metadata.unwrap().secondary_types.is_empty());
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut grid = TileGrid::new(10, 10, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        let result = grid.set_tile_with_strategy(
+            10,
+            0,
+            Tile::new(TileType::Road),
+            &ConflictStrategy::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_record_conflicts() {
+        let mut grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        let strategy = ConflictStrategy::Priority;
+        let place = |grid: &mut TileGrid, tile: Tile| {
+            let contest = Contest::new(grid.get_tile(1, 1).unwrap(), &tile);
+            grid.set_tile_with_strategy(1, 1, tile, &strategy).unwrap();
+            if let Some(contest) = contest {
+                grid.record_conflict(1, 1, contest);
+            }
+        };
+        place(&mut grid, tile(TileType::GreenSpace, 1, 5_000.0));
+        place(&mut grid, tile(TileType::Road, 2, 0.0));
+        place(&mut grid, tile(TileType::Water, 3, 800.0));
+        place(&mut grid, tile(TileType::Road, 4, 0.0));
+
+        let conflicts: Vec<_> = grid.conflicts().map(|(_, _, conflict)| conflict).collect();
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(
+            *conflicts[0],
+            TileConflict {
+                winner: TileType::Road,
+                winner_id: Some(2),
+                loser: TileType::GreenSpace,
+                loser_id: Some(1),
+                merged: false,
+            }
+        );
+        assert_eq!(conflicts[1].winner_id, Some(2));
+        assert_eq!(conflicts[1].loser_id, Some(3));
+    }
+}
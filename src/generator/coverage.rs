@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use super::TileGrid;
+
+/// Exact fraction of each tile covered by a polygon
+///
+/// Only tiles crossed by the outline are partially covered; every other tile
+/// the polygon reaches is fully inside, so coverage is clipped for outline
+/// tiles alone.
+pub(crate) struct PolygonCoverage {
+    /// Coverage of the outline tiles by grid position
+    outline: HashMap<(usize, usize), f32>,
+}
+
+impl PolygonCoverage {
+    /// Measure the outline tiles of a polygon and its holes, given as (lat, lon) vertices
+    pub(crate) fn new(grid: &TileGrid, geometry: &[(f64, f64)], holes: &[Vec<(f64, f64)>]) -> Self {
+        let (width, height) = grid.dimensions();
+        let to_image = |ring: &[(f64, f64)]| -> Vec<(f64, f64)> {
+            ring.iter()
+                .map(|&(lat, lon)| grid.image_position(lat, lon))
+                .collect()
+        };
+        let polygon = to_image(geometry);
+        let holes: Vec<Vec<(f64, f64)>> = holes.iter().map(|hole| to_image(hole)).collect();
+        let area = |x: f64, row: f64| {
+            let holes: f64 = holes.iter().map(|hole| clipped_area(hole, x, row)).sum();
+            (clipped_area(&polygon, x, row) - holes).max(0.0) as f32
+        };
+
+        let mut outline = HashMap::new();
+        if width == 0 || height == 0 {
+            return Self { outline };
+        }
+        let rings = std::iter::once(&polygon).chain(&holes);
+        for ring in rings {
+            for (index, &start) in ring.iter().enumerate() {
+                let end = ring[(index + 1) % ring.len()];
+                // Quarter-cell steps visit every tile the edge passes through
+                let steps =
+                    ((end.0 - start.0).abs().max((end.1 - start.1).abs()) * 4.0).ceil() as usize;
+                for step in 0..=steps {
+                    let t = if steps == 0 {
+                        0.0
+                    } else {
+                        step as f64 / steps as f64
+                    };
+                    let (x, row) = (
+                        start.0 + (end.0 - start.0) * t,
+                        start.1 + (end.1 - start.1) * t,
+                    );
+                    if x < 0.0 || row < 0.0 || x >= width as f64 || row >= height as f64 {
+                        continue;
+                    }
+                    let (x, row) = (x as usize, row as usize);
+                    let position = (x, grid.origin.from_top_left(row, height));
+                    outline
+                        .entry(position)
+                        .or_insert_with(|| area(x as f64, row as f64));
+                }
+            }
+        }
+
+        Self { outline }
+    }
+
+    /// Get the covered fraction of a tile the polygon was rasterized onto
+    pub(crate) fn coverage(&self, x: usize, y: usize) -> f32 {
+        self.outline.get(&(x, y)).copied().unwrap_or(1.0)
+    }
+}
+
+/// Area of a polygon inside the unit square at (x, y), in image coordinates
+fn clipped_area(polygon: &[(f64, f64)], x: f64, y: f64) -> f64 {
+    // Sutherland–Hodgman against the four edges of the square
+    let mut clipped = polygon.to_vec();
+    let edges: [(usize, f64, bool); 4] = [
+        (0, x, true),
+        (0, x + 1.0, false),
+        (1, y, true),
+        (1, y + 1.0, false),
+    ];
+    for (axis, bound, keep_above) in edges {
+        let coordinate = |point: (f64, f64)| if axis == 0 { point.0 } else { point.1 };
+        let inside = |point: (f64, f64)| (coordinate(point) >= bound) == keep_above;
+
+        let input = std::mem::take(&mut clipped);
+        for (index, &current) in input.iter().enumerate() {
+            let previous = input[(index + input.len() - 1) % input.len()];
+            if inside(current) != inside(previous) {
+                let t =
+                    (bound - coordinate(previous)) / (coordinate(current) - coordinate(previous));
+                clipped.push((
+                    previous.0 + (current.0 - previous.0) * t,
+                    previous.1 + (current.1 - previous.1) * t,
+                ));
+            }
+            if inside(current) {
+                clipped.push(current);
+            }
+        }
+        if clipped.is_empty() {
+            return 0.0;
+        }
+    }
+
+    // Shoelace formula; the sign depends on the winding order
+    let doubled: f64 = clipped
+        .iter()
+        .zip(clipped.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum();
+    (doubled.abs() / 2.0).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoundingBox;
+
+    #[test]
+    fn test_clipped_area() {
+        let square = [(0.5, 0.5), (2.0, 0.5), (2.0, 2.0), (0.5, 2.0)];
+        assert!((clipped_area(&square, 0.0, 0.0) - 0.25).abs() < 1e-9);
+        assert!((clipped_area(&square, 1.0, 1.0) - 1.0).abs() < 1e-9);
+        assert_eq!(clipped_area(&square, 5.0, 5.0), 0.0);
+
+        let triangle = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+        assert!((clipped_area(&triangle, 0.0, 0.0) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_polygon_coverage() {
+        // 10×10 grid of 0.01° tiles; the southern edge of the square halves a row of tiles
+        let grid = TileGrid::new(10, 10, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        let square = [
+            (52.045, 13.02),
+            (52.045, 13.05),
+            (52.08, 13.05),
+            (52.08, 13.02),
+            (52.045, 13.02),
+        ];
+        let coverage = PolygonCoverage::new(&grid, &square, &[]);
+
+        let (x, y) = grid.geo_to_grid(52.06, 13.03).unwrap();
+        assert_eq!(coverage.coverage(x, y), 1.0);
+        // The southern row of the square only covers the northern half of its tiles
+        let (x, y) = grid.geo_to_grid(52.0475, 13.03).unwrap();
+        assert!((coverage.coverage(x, y) - 0.5).abs() < 1e-4);
+    }
+}
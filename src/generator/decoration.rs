@@ -0,0 +1,212 @@
+use super::{TileGrid, TileType};
+
+/// Meters per degree of latitude
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Cosmetic variation of one tile, see [`DecorationGenerator`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Decoration {
+    /// Share of the tile planted with trees, from 0.0 to 1.0 (green space only)
+    pub tree_density: f32,
+    /// Relative building height offset, e.g. 0.1 for 10% taller (buildings only)
+    pub height_jitter: f32,
+    /// Uniform value from 0.0 to 1.0 for picking sprite or model variants
+    pub variant: f32,
+}
+
+/// Deterministic per-tile variation seeded from geography
+///
+/// Values are derived from the latitude and longitude of each tile center
+/// with a seeded hash, so the same place always looks the same, across
+/// reloads, resolutions and neighboring grids, without storing any
+/// randomness with the grid. Tree density and height jitter use smooth
+/// value noise, so forests thin out and skylines vary gradually.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecorationGenerator {
+    /// Seed mixed into every hash
+    pub seed: u64,
+    /// Distance in meters over which tree density changes, e.g. clearings
+    pub tree_scale_m: f64,
+    /// Distance in meters over which building heights change
+    pub height_scale_m: f64,
+    /// Largest relative height offset of buildings
+    pub max_height_jitter: f32,
+}
+
+impl Default for DecorationGenerator {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            tree_scale_m: 60.0,
+            height_scale_m: 25.0,
+            max_height_jitter: 0.3,
+        }
+    }
+}
+
+impl DecorationGenerator {
+    /// Create a generator with a custom seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// Set the largest relative height offset of buildings
+    pub fn with_max_height_jitter(mut self, jitter: f32) -> Self {
+        self.max_height_jitter = jitter;
+        self
+    }
+
+    /// Get the decoration of a tile of `tile_type` centered at (lat, lon)
+    pub fn decorate(&self, lat: f64, lon: f64, tile_type: &TileType) -> Decoration {
+        let (north, east) = (
+            lat * METERS_PER_DEGREE,
+            lon * METERS_PER_DEGREE * lat.to_radians().cos(),
+        );
+        let tree_density = match tile_type {
+            TileType::GreenSpace => self.value_noise(1, north, east, self.tree_scale_m),
+            _ => 0.0,
+        };
+        let height_jitter = match tile_type {
+            TileType::Building => {
+                let noise = self.value_noise(2, north, east, self.height_scale_m);
+                (noise * 2.0 - 1.0) * self.max_height_jitter
+            }
+            _ => 0.0,
+        };
+        // Centimeter cells, so every tile of a grid gets its own variant
+        let variant = unit(self.hash(3, (north * 100.0) as i64, (east * 100.0) as i64));
+
+        Decoration {
+            tree_density,
+            height_jitter,
+            variant,
+        }
+    }
+
+    /// Decorate every tile of `grid`
+    pub fn generate(&self, grid: &TileGrid) -> DecorationLayer {
+        let (width, height) = grid.dimensions();
+        let values = grid
+            .iter_tiles()
+            .map(|(x, y, tile)| {
+                grid.grid_to_geo(x, y)
+                    .map(|(lat, lon)| self.decorate(lat, lon, &tile.tile_type))
+                    .unwrap_or_default()
+            })
+            .collect();
+        DecorationLayer {
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// Hash a lattice point of one noise channel
+    fn hash(&self, channel: u64, x: i64, y: i64) -> u64 {
+        let mut h = self.seed ^ channel.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        for value in [x as u64, y as u64] {
+            h = splitmix64(h ^ value);
+        }
+        h
+    }
+
+    /// Smoothly interpolated lattice noise in 0.0..1.0 with cells of `scale` meters
+    fn value_noise(&self, channel: u64, north: f64, east: f64, scale: f64) -> f32 {
+        let (gx, gy) = (east / scale, north / scale);
+        let (x0, y0) = (gx.floor(), gy.floor());
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (smooth(gx - x0), smooth(gy - y0));
+        let corner =
+            |dx: i64, dy: i64| unit(self.hash(channel, x0 as i64 + dx, y0 as i64 + dy)) as f64;
+        let south = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * tx;
+        let north = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * tx;
+        (south + (north - south) * ty) as f32
+    }
+}
+
+/// Mix the bits of a 64-bit value (SplitMix64 finalizer)
+fn splitmix64(mut h: u64) -> u64 {
+    h = h.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+/// Map a hash to 0.0..1.0
+fn unit(h: u64) -> f32 {
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Decorations for every tile of a grid, kept apart from the grid itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecorationLayer {
+    width: usize,
+    height: usize,
+    values: Vec<Decoration>,
+}
+
+impl DecorationLayer {
+    /// Get the layer dimensions (width, height)
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get the decoration at the given grid coordinates
+    pub fn get(&self, x: usize, y: usize) -> Option<&Decoration> {
+        (x < self.width && y < self.height).then(|| &self.values[y * self.width + x])
+    }
+
+    /// Get the decorations in row-major order
+    pub fn values(&self) -> &[Decoration] {
+        &self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile};
+
+    #[test]
+    fn test_decorations_are_deterministic() {
+        let mut grid = TileGrid::new(20, 20, BoundingBox::new(52.50, 13.40, 52.51, 13.41), 50.0);
+        for x in 0..10 {
+            for y in 0..20 {
+                grid.set_tile(x, y, Tile::new(TileType::GreenSpace))
+                    .unwrap();
+                grid.set_tile(x + 10, y, Tile::new(TileType::Building))
+                    .unwrap();
+            }
+        }
+
+        let generator = DecorationGenerator::default();
+        let layer = generator.generate(&grid);
+        assert_eq!(layer, generator.generate(&grid.clone()));
+        assert_ne!(layer, DecorationGenerator::new(7).generate(&grid));
+
+        let park = layer.get(3, 3).unwrap();
+        let building = layer.get(13, 3).unwrap();
+        assert!((0.0..=1.0).contains(&park.tree_density));
+        assert_eq!(park.height_jitter, 0.0);
+        assert_eq!(building.tree_density, 0.0);
+        assert!(building.height_jitter.abs() <= 0.3);
+
+        // Variation, but no jumps between neighboring park tiles
+        let densities: Vec<f32> = (0..10)
+            .map(|y| layer.get(3, y).unwrap().tree_density)
+            .collect();
+        assert!(densities.iter().any(|&d| (d - densities[0]).abs() > 0.01));
+        assert!(
+            densities
+                .windows(2)
+                .all(|pair| (pair[0] - pair[1]).abs() < 0.5)
+        );
+
+        // The same place decorates the same in any grid
+        let (lat, lon) = grid.grid_to_geo(3, 3).unwrap();
+        assert_eq!(generator.decorate(lat, lon, &TileType::GreenSpace), *park);
+    }
+}
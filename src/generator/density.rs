@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+
+use super::{GridOrigin, Tile, TileGrid, TileType};
+
+/// Feature whose density is measured
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DensityFeature {
+    /// Any non-empty tile
+    NonEmpty,
+    /// Tiles of one type, e.g. building coverage
+    TileType(TileType),
+    /// Tiles rasterized from elements with a tag (any value if `value` is `None`)
+    Tag { key: String, value: Option<String> },
+    /// Points of interest of one category (e.g., "amenity"), counted per tile
+    PoiCategory(String),
+}
+
+impl DensityFeature {
+    /// Check if a tile belongs to the feature (always false for POI categories)
+    fn matches(&self, tile: &Tile) -> bool {
+        match self {
+            Self::NonEmpty => tile.tile_type != TileType::Empty,
+            Self::TileType(tile_type) => &tile.tile_type == tile_type,
+            Self::Tag { key, value } => tile
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.tags.get(key))
+                .is_some_and(|found| value.as_ref().is_none_or(|value| value == found)),
+            Self::PoiCategory(_) => false,
+        }
+    }
+}
+
+/// How density values are rescaled after measuring
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DensityNormalization {
+    /// Keep raw values: the covered fraction, or POIs per tile
+    #[default]
+    None,
+    /// Divide by the largest value, so the densest cell is 1.0
+    Max,
+    /// Map the smallest value to 0.0 and the largest to 1.0
+    MinMax,
+    /// Divide by the total, so all values sum to 1.0 (a probability distribution)
+    Sum,
+}
+
+/// Generates a [`DensityGrid`] for one feature of a tile grid
+///
+/// Each cell holds the average amount of the feature within `radius` tiles,
+/// so a radius of 0 gives a plain 0/1 mask for tile features.
+#[derive(Debug, Clone)]
+pub struct DensityGenerator {
+    feature: DensityFeature,
+    radius: usize,
+    normalization: DensityNormalization,
+}
+
+impl DensityGenerator {
+    /// Create a generator measuring `feature` within a radius of 2 tiles
+    pub fn new(feature: DensityFeature) -> Self {
+        Self {
+            feature,
+            radius: 2,
+            normalization: DensityNormalization::None,
+        }
+    }
+
+    /// Set the neighborhood radius in tiles
+    pub fn with_radius(mut self, radius: usize) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Set how values are rescaled
+    pub fn with_normalization(mut self, normalization: DensityNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Measure the feature density of every cell
+    pub fn generate(&self, grid: &TileGrid) -> DensityGrid {
+        let (width, height) = grid.dimensions();
+        let mut weights = vec![0.0f64; width * height];
+        match &self.feature {
+            DensityFeature::PoiCategory(category) => {
+                for poi in grid.pois.of_category(category) {
+                    let (x, y) = poi.grid_pos;
+                    if x < width && y < height {
+                        weights[y * width + x] += 1.0;
+                    }
+                }
+            }
+            feature => {
+                for (x, y, tile) in grid.iter_tiles() {
+                    if feature.matches(tile) {
+                        weights[y * width + x] = 1.0;
+                    }
+                }
+            }
+        }
+
+        // Summed-area table with a leading zero row and column
+        let stride = width + 1;
+        let mut sums = vec![0.0f64; stride * (height + 1)];
+        for y in 0..height {
+            for x in 0..width {
+                sums[(y + 1) * stride + x + 1] =
+                    weights[y * width + x] + sums[y * stride + x + 1] + sums[(y + 1) * stride + x]
+                        - sums[y * stride + x];
+            }
+        }
+
+        let mut values = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let (top, bottom) = (
+                y.saturating_sub(self.radius),
+                (y + self.radius + 1).min(height),
+            );
+            for x in 0..width {
+                let (left, right) = (
+                    x.saturating_sub(self.radius),
+                    (x + self.radius + 1).min(width),
+                );
+                let total = sums[bottom * stride + right]
+                    - sums[top * stride + right]
+                    - sums[bottom * stride + left]
+                    + sums[top * stride + left];
+                let cells = (bottom - top) * (right - left);
+                values.push((total / cells as f64) as f32);
+            }
+        }
+
+        let mut density = DensityGrid {
+            width,
+            height,
+            values,
+            origin: grid.origin,
+        };
+        density.normalize(self.normalization);
+        density
+    }
+}
+
+/// Per-cell float densities, stored in the row order of the source grid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DensityGrid {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+    /// Corner row 0 starts at, copied from the source grid
+    pub origin: GridOrigin,
+}
+
+impl DensityGrid {
+    /// Get the grid dimensions (width, height)
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get the density at the given grid coordinates
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        (x < self.width && y < self.height).then(|| self.values[y * self.width + x])
+    }
+
+    /// Get the values in row-major order, e.g. for `GeoTiffExporter::values`
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Get the largest value (0.0 for empty grids)
+    pub fn max(&self) -> f32 {
+        self.values.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// Get the sum of all values
+    pub fn total(&self) -> f32 {
+        self.values.iter().sum()
+    }
+
+    /// Rescale the values; grids without any density are left at zero
+    pub fn normalize(&mut self, normalization: DensityNormalization) {
+        let (offset, divisor) = match normalization {
+            DensityNormalization::None => return,
+            DensityNormalization::Max => (0.0, self.max()),
+            DensityNormalization::MinMax => {
+                let min = self.values.iter().copied().fold(f32::INFINITY, f32::min);
+                (min, self.max() - min)
+            }
+            DensityNormalization::Sum => (0.0, self.total()),
+        };
+        if divisor > 0.0 {
+            for value in &mut self.values {
+                *value = (*value - offset) / divisor;
+            }
+        }
+    }
+
+    /// Pick a cell with probability proportional to its density, e.g. for spawn points
+    ///
+    /// `sample` is a uniform random number in `[0, 1)`, so callers keep
+    /// control of their random number generator. Returns `None` if no cell
+    /// has any density.
+    pub fn weighted_cell(&self, sample: f32) -> Option<(usize, usize)> {
+        let total = self.total();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut remaining = sample.clamp(0.0, 1.0) * total;
+        let mut last = None;
+        for (index, &value) in self.values.iter().enumerate() {
+            if value <= 0.0 {
+                continue;
+            }
+            last = Some(index);
+            if remaining < value {
+                break;
+            }
+            remaining -= value;
+        }
+        last.map(|index| (index % self.width, index / self.width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoundingBox;
+
+    fn grid_with_building() -> TileGrid {
+        let mut grid = TileGrid::new(5, 5, BoundingBox::new(52.0, 13.0, 52.005, 13.005), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Building)).unwrap();
+        grid
+    }
+
+    #[test]
+    fn test_building_coverage() {
+        let grid = grid_with_building();
+        let density = DensityGenerator::new(DensityFeature::TileType(TileType::Building))
+            .with_radius(1)
+            .generate(&grid);
+
+        // The corner window only covers 4 tiles
+        assert_eq!(density.get(0, 0), Some(0.25));
+        assert_eq!(density.get(1, 1), Some(1.0 / 9.0));
+        assert_eq!(density.get(4, 4), Some(0.0));
+
+        let mask = DensityGenerator::new(DensityFeature::NonEmpty)
+            .with_radius(0)
+            .generate(&grid);
+        assert_eq!(mask.total(), 1.0);
+    }
+
+    #[test]
+    fn test_normalization_and_sampling() {
+        let grid = grid_with_building();
+        let density = DensityGenerator::new(DensityFeature::TileType(TileType::Building))
+            .with_radius(1)
+            .with_normalization(DensityNormalization::Max)
+            .generate(&grid);
+        assert_eq!(density.max(), 1.0);
+
+        let mut distribution = density.clone();
+        distribution.normalize(DensityNormalization::Sum);
+        assert!((distribution.total() - 1.0).abs() < 1e-5);
+
+        assert_eq!(density.weighted_cell(0.0), Some((0, 0)));
+        let (x, y) = density.weighted_cell(0.999).unwrap();
+        assert!(x <= 1 && y <= 1);
+
+        let empty =
+            DensityGenerator::new(DensityFeature::TileType(TileType::Water)).generate(&grid);
+        assert_eq!(empty.weighted_cell(0.5), None);
+    }
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{OsmElement, TileGrid, TileType};
+
+/// A named administrative area, e.g. a borough or neighborhood
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct District {
+    /// OSM ID of the boundary relation or way
+    pub osm_id: i64,
+    /// Value of the boundary's `name` tag
+    pub name: String,
+    /// OSM `admin_level` (higher is finer, e.g. 10 for neighborhoods), if tagged
+    pub admin_level: Option<u8>,
+    /// Number of tiles assigned to the district
+    pub tile_count: usize,
+    /// Area of the assigned tiles in square meters
+    pub area_m2: f32,
+    /// Number of assigned tiles by tile type name (e.g., "road")
+    pub tile_types: BTreeMap<String, usize>,
+}
+
+impl District {
+    /// Get the share of the district's tiles with a tile type, between 0 and 1
+    pub fn type_fraction(&self, tile_type: &TileType) -> f32 {
+        if self.tile_count == 0 {
+            return 0.0;
+        }
+        let count = self.tile_types.get(tile_type.name()).copied().unwrap_or(0);
+        count as f32 / self.tile_count as f32
+    }
+}
+
+/// Districts of a grid and the district each tile lies in
+///
+/// Built from `boundary=administrative` polygons when
+/// [`OsmFeature::Boundaries`](crate::OsmFeature::Boundaries) is enabled. A tile
+/// inside nested boundaries belongs to the finest one, so a city boundary
+/// only keeps the tiles none of its boroughs covers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Districts {
+    /// Districts covering at least one tile, in order of their OSM ID
+    districts: Vec<District>,
+    /// District index plus one for each tile in row-major order, 0 outside all districts
+    cells: Vec<u32>,
+    /// Grid width the cells are laid out with
+    width: usize,
+}
+
+impl Districts {
+    /// Assign the tiles of a grid to the named administrative boundaries among `elements`
+    ///
+    /// Outer rings sharing an OSM ID form one district.
+    pub fn from_boundaries(elements: &[OsmElement], grid: &TileGrid) -> Self {
+        let mut boundaries: Vec<&OsmElement> = elements
+            .iter()
+            .filter(|element| is_admin_boundary(element))
+            .collect();
+        if boundaries.is_empty() {
+            return Self::default();
+        }
+        // Coarse levels first, so finer boundaries overwrite them
+        boundaries.sort_by_key(|element| (admin_level(element), element.id));
+
+        let (width, height) = grid.dimensions();
+        let mut owners: Vec<Option<i64>> = vec![None; width * height];
+        for boundary in &boundaries {
+            let Some((min_lat, min_lon, max_lat, max_lon)) = boundary.bounding_box() else {
+                continue;
+            };
+            for y in 0..height {
+                for x in 0..width {
+                    let Some((lat, lon)) = grid.grid_to_geo(x, y) else {
+                        continue;
+                    };
+                    if (min_lat..=max_lat).contains(&lat)
+                        && (min_lon..=max_lon).contains(&lon)
+                        && boundary.contains(lat, lon)
+                    {
+                        owners[y * width + x] = Some(boundary.id);
+                    }
+                }
+            }
+        }
+
+        let mut by_id: BTreeMap<i64, District> = BTreeMap::new();
+        for boundary in &boundaries {
+            by_id.entry(boundary.id).or_insert_with(|| District {
+                osm_id: boundary.id,
+                name: boundary.tags["name"].clone(),
+                admin_level: admin_level(boundary),
+                tile_count: 0,
+                area_m2: 0.0,
+                tile_types: BTreeMap::new(),
+            });
+        }
+        let tile_area = grid.meters_per_tile_x() * grid.meters_per_tile_y();
+        for (tile, owner) in grid.flat_tiles().iter().zip(&owners) {
+            let Some(district) = owner.and_then(|id| by_id.get_mut(&id)) else {
+                continue;
+            };
+            district.tile_count += 1;
+            district.area_m2 += tile_area;
+            *district
+                .tile_types
+                .entry(tile.tile_type.name().to_string())
+                .or_insert(0) += 1;
+        }
+
+        let districts: Vec<District> = by_id
+            .into_values()
+            .filter(|district| district.tile_count > 0)
+            .collect();
+        let cell_of: BTreeMap<i64, u32> = districts
+            .iter()
+            .enumerate()
+            .map(|(index, district)| (district.osm_id, index as u32 + 1))
+            .collect();
+        let cells = owners
+            .iter()
+            .map(|owner| owner.and_then(|id| cell_of.get(&id).copied()).unwrap_or(0))
+            .collect();
+        Self {
+            districts,
+            cells,
+            width,
+        }
+    }
+
+    /// Get the number of districts
+    pub fn len(&self) -> usize {
+        self.districts.len()
+    }
+
+    /// Check if there are no districts
+    pub fn is_empty(&self) -> bool {
+        self.districts.is_empty()
+    }
+
+    /// Iterate over all districts
+    pub fn iter(&self) -> impl Iterator<Item = &District> {
+        self.districts.iter()
+    }
+
+    /// Get the first district with a name
+    pub fn named(&self, name: &str) -> Option<&District> {
+        self.districts.iter().find(|district| district.name == name)
+    }
+
+    /// Get the district a tile lies in
+    pub fn district_at(&self, x: usize, y: usize) -> Option<&District> {
+        if x >= self.width {
+            return None;
+        }
+        let cell = *self.cells.get(y * self.width + x)?;
+        cell.checked_sub(1)
+            .and_then(|index| self.districts.get(index as usize))
+    }
+
+    /// Get the grid positions (x, y) of a district's tiles
+    pub fn tiles_of(&self, district: &District) -> Vec<(usize, usize)> {
+        let Some(index) = self
+            .districts
+            .iter()
+            .position(|other| other.osm_id == district.osm_id)
+        else {
+            return Vec::new();
+        };
+        let cell = index as u32 + 1;
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|&(_, &other)| other == cell)
+            .map(|(i, _)| (i % self.width, i / self.width))
+            .collect()
+    }
+
+    /// Mirror the tile assignments vertically after the grid's rows were reversed
+    pub(crate) fn flip_rows(&mut self, height: usize) {
+        if self.width == 0 || self.cells.is_empty() {
+            return;
+        }
+        for y in 0..height / 2 {
+            let (top, bottom) = self.cells.split_at_mut((height - 1 - y) * self.width);
+            top[y * self.width..(y + 1) * self.width].swap_with_slice(&mut bottom[..self.width]);
+        }
+    }
+}
+
+/// Check whether an element is a named administrative boundary polygon
+pub(crate) fn is_admin_boundary(element: &OsmElement) -> bool {
+    element
+        .tags
+        .get("boundary")
+        .is_some_and(|boundary| boundary == "administrative")
+        && element.tags.contains_key("name")
+        && element.geometry.len() >= 4
+        && element.geometry.first() == element.geometry.last()
+}
+
+fn admin_level(element: &OsmElement) -> Option<u8> {
+    element.tags.get("admin_level")?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BoundingBox, DefaultGridGenerator, GridOrigin, OsmConfigBuilder, OsmData, OsmDataFormat,
+        OsmFeature, OsmMetadata,
+    };
+    use serde_json::json;
+
+    /// A city split into a northern and a southern borough, with a park in the south
+    fn city_data() -> OsmData {
+        let rectangle = |south: f64, north: f64| {
+            json!([
+                {"lat": south, "lon": 13.4},
+                {"lat": south, "lon": 13.41},
+                {"lat": north, "lon": 13.41},
+                {"lat": north, "lon": 13.4},
+                {"lat": south, "lon": 13.4},
+            ])
+        };
+        let boundary = |id: i64, level: &str, name: &str, south: f64, north: f64| {
+            json!({
+                "type": "relation",
+                "id": id,
+                "members": [{"type": "way", "role": "outer", "geometry": rectangle(south, north)}],
+                "tags": {"type": "boundary", "boundary": "administrative", "admin_level": level, "name": name},
+            })
+        };
+        let elements = json!([
+            boundary(10, "4", "City", 52.5, 52.51),
+            boundary(11, "9", "North", 52.505, 52.51),
+            boundary(12, "9", "South", 52.5, 52.505),
+            {"type": "way", "id": 1, "tags": {"leisure": "park"}, "geometry": [
+                {"lat": 52.501, "lon": 13.401},
+                {"lat": 52.501, "lon": 13.404},
+                {"lat": 52.504, "lon": 13.404},
+                {"lat": 52.504, "lon": 13.401},
+                {"lat": 52.501, "lon": 13.401},
+            ]},
+        ]);
+        OsmData {
+            raw_data: json!({"elements": elements}).to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.5, 13.4, 52.51, 13.41),
+            metadata: OsmMetadata::new("test", "test"),
+        }
+    }
+
+    #[test]
+    fn test_districts_from_boundaries() {
+        let data = city_data();
+        let config = OsmConfigBuilder::new()
+            .grid_resolution(2000)
+            .with_feature(OsmFeature::Boundaries)
+            .build();
+        let mut grid = DefaultGridGenerator::new()
+            .generate_grid_sync(&data, &config)
+            .unwrap();
+        let districts = &grid.districts;
+
+        // The boroughs cover the whole city, which keeps no tiles of its own
+        assert_eq!(districts.len(), 2);
+        assert!(districts.named("City").is_none());
+        let north = districts.named("North").unwrap();
+        let south = districts.named("South").unwrap();
+        assert_eq!(south.admin_level, Some(9));
+        assert_eq!(north.tile_count + south.tile_count, 20 * 20);
+        assert!(south.type_fraction(&TileType::GreenSpace) > 0.1);
+        assert_eq!(north.type_fraction(&TileType::GreenSpace), 0.0);
+        assert_eq!(districts.tiles_of(south).len(), south.tile_count);
+        assert_eq!(districts.district_at(0, 0).unwrap().name, "North");
+
+        // Assignments follow the rows when the origin changes
+        grid.set_origin(GridOrigin::BottomLeft);
+        assert_eq!(grid.districts.district_at(0, 0).unwrap().name, "South");
+        assert_eq!(grid.districts.district_at(0, 19).unwrap().name, "North");
+    }
+
+    #[test]
+    fn test_districts_need_boundaries_feature() {
+        let config = OsmConfigBuilder::new().grid_resolution(2000).build();
+        let grid = DefaultGridGenerator::new()
+            .generate_grid_sync(&city_data(), &config)
+            .unwrap();
+        assert!(grid.districts.is_empty());
+        assert_eq!(grid.districts.district_at(0, 0), None);
+    }
+}
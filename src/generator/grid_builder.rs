@@ -1,11 +1,45 @@
 use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
-
+use tracing::field::Empty;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use super::coastline::fill_sea;
+use super::coverage::PolygonCoverage;
+use super::districts::is_admin_boundary;
+use super::simplify::{simplify_polygon, vertex_count};
 use super::{
-    GeneratorCapabilities, GridGenerator, OsmElement, OsmParser, Tile, TileGrid, TileType,
+    ConflictStrategy, Contest, Crs, Districts, GeneratorCapabilities, GridGenerator, GridLayer,
+    GridManifest, GridPostProcessor, JsonElementStream, LayerMode, LayeredTileGrid, MemoryTracker,
+    OsmElement, OsmParser, Poi, RoadFeature, SlippyTile, Tile, TileGrid, TileType,
+    estimate_element_bytes, estimate_grid_bytes, estimate_tile_metadata_bytes, slippy_layout,
+};
+use crate::metrics::{
+    ELEMENTS_PARSED_TOTAL, GRID_GENERATION_DURATION_SECONDS, MetricsSink, TILES_RASTERIZED_TOTAL,
 };
-use crate::{OsmConfig, OsmData, OsmTilesError, Result};
+use crate::{
+    Attribution, BoundingBox, OsmConfig, OsmData, OsmFeature, OsmTagQuery, OsmTilesError, Result,
+    TagMatcher,
+};
+
+/// Get the (south, west, north, east) extent of an element's geometry
+fn element_extent(element: &OsmElement) -> Option<(f64, f64, f64, f64)> {
+    let (&(lat, lon), rest) = element.geometry.split_first()?;
+    Some(rest.iter().fold(
+        (lat, lon, lat, lon),
+        |(south, west, north, east), &(lat, lon)| {
+            (south.min(lat), west.min(lon), north.max(lat), east.max(lon))
+        },
+    ))
+}
+
+/// Check whether an element extent overlaps a bounding box
+fn overlaps((south, west, north, east): (f64, f64, f64, f64), bbox: &BoundingBox) -> bool {
+    south <= bbox.north && north >= bbox.south && west <= bbox.east && east >= bbox.west
+}
 
 /// Default grid generator implementation
 pub struct DefaultGridGenerator {
@@ -13,6 +47,92 @@ pub struct DefaultGridGenerator {
     parser: OsmParser,
     /// Maximum grid size to prevent memory issues
     max_grid_size: (usize, usize),
+    /// How tiles touched by several elements are resolved
+    conflict_strategy: ConflictStrategy,
+    /// How OSM layer, bridge and tunnel tags affect overlapping elements
+    layer_mode: LayerMode,
+    /// Cleanup passes applied after rasterization, in order
+    post_processors: Vec<Box<dyn GridPostProcessor>>,
+    /// Wall-clock budget after which remaining elements are skipped
+    time_budget: Option<Duration>,
+    /// Fill the sea side of coastlines with water before rasterizing
+    coastline_fill: bool,
+    /// Choose the row count so tiles are square in meters
+    square_cells: bool,
+    /// Sink receiving element, tile and timing metrics
+    metrics: Option<Arc<dyn MetricsSink>>,
+    /// Douglas-Peucker tolerance in tiles for water and green space polygons
+    polygon_simplification: Option<f32>,
+    /// Record contested tiles in their metadata
+    audit_conflicts: bool,
+    /// What happens when the grid would exceed `max_grid_size`
+    oversize_policy: OversizePolicy,
+}
+
+/// What the generator does when the configured resolution exceeds its maximum grid size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OversizePolicy {
+    /// Lower the resolution until the grid fits, keeping the shape of the tiles
+    ///
+    /// The grid metadata records the requested size and the effective
+    /// resolution, and a warning is logged.
+    #[default]
+    Downscale,
+    /// Fail with [`OsmTilesError::GridTooLarge`]
+    Error,
+}
+
+/// Grid dimensions fitted into the maximum grid size
+struct GridFit {
+    dimensions: (usize, usize),
+    /// Requested dimensions and effective resolution, if the grid was downscaled
+    downscaled: Option<((usize, usize), u32)>,
+}
+
+/// Order in which a time-budgeted generation rasterizes tile types
+///
+/// Roads, railways, water and buildings make a map recognizable, so they go
+/// first; large land-use fills are the most expensive and go last.
+fn budget_tier(tile_type: &TileType) -> u8 {
+    match tile_type {
+        TileType::Road | TileType::Railway | TileType::Water | TileType::Building => 0,
+        TileType::Parking | TileType::Amenity | TileType::Tourism | TileType::Custom(_) => 1,
+        TileType::GreenSpace
+        | TileType::Residential
+        | TileType::Commercial
+        | TileType::Industrial
+        | TileType::Empty => 2,
+    }
+}
+
+/// Wall-clock deadline of a time-budgeted generation, measured with `web_time` on WASM
+struct Deadline {
+    end: Instant,
+}
+
+impl Deadline {
+    fn after(budget: Duration) -> Self {
+        Self {
+            end: Instant::now() + budget,
+        }
+    }
+
+    fn expired(&self) -> bool {
+        Instant::now() >= self.end
+    }
+}
+
+/// Scale the resolution of a config by `scale`, through the cell size if one is set
+fn scaled_config(config: &OsmConfig, bbox: &BoundingBox, scale: f64) -> OsmConfig {
+    let mut scaled = config.clone();
+    match config.cell_size_meters {
+        Some(size) => scaled.cell_size_meters = Some(size / scale as f32),
+        None => {
+            let resolution = config.grid_resolution_for(bbox) as f64 * scale;
+            scaled.grid_resolution = (resolution.floor() as u32).max(1);
+        }
+    }
+    scaled
 }
 
 impl DefaultGridGenerator {
@@ -21,70 +141,212 @@ impl DefaultGridGenerator {
         Self {
             parser: OsmParser,
             max_grid_size: (5000, 5000),
+            conflict_strategy: ConflictStrategy::default(),
+            layer_mode: LayerMode::default(),
+            post_processors: Vec::new(),
+            time_budget: None,
+            coastline_fill: false,
+            square_cells: false,
+            metrics: None,
+            polygon_simplification: None,
+            audit_conflicts: false,
+            oversize_policy: OversizePolicy::default(),
         }
     }
 
     /// Create a grid generator with custom maximum grid size
     pub fn with_max_size(max_width: usize, max_height: usize) -> Self {
         Self {
-            parser: OsmParser,
             max_grid_size: (max_width, max_height),
+            ..Self::new()
         }
     }
 
-    /// Calculate grid dimensions based on config and bounding box
-    fn calculate_grid_dimensions(
-        &self,
-        config: &OsmConfig,
-        osm_data: &OsmData,
-    ) -> Result<(usize, usize)> {
-        let bbox = &osm_data.bounding_box;
+    /// Set the strategy for resolving tiles touched by several elements
+    pub fn with_conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.conflict_strategy = strategy;
+        self
+    }
 
-        // Calculate grid size based on resolution and area
-        let width_deg = bbox.width();
-        let height_deg = bbox.height();
+    /// Record every tile claimed by elements of different types in its metadata
+    ///
+    /// Each [`TileConflict`] names both types and OSM IDs and which one won,
+    /// listed by [`TileGrid::conflicts`]. Meant for auditing data quality:
+    /// contested tiles get their own metadata, which costs memory.
+    pub fn with_conflict_audit(mut self) -> Self {
+        self.audit_conflicts = true;
+        self
+    }
 
-        // Grid resolution is cells per degree
-        let grid_width = (width_deg * config.grid_resolution as f64).ceil() as usize;
-        let grid_height = (height_deg * config.grid_resolution as f64).ceil() as usize;
+    /// Set how OSM layer, bridge and tunnel tags affect overlapping elements
+    pub fn with_layer_mode(mut self, mode: LayerMode) -> Self {
+        self.layer_mode = mode;
+        self
+    }
 
-        // Enforce minimum size
-        let grid_width = grid_width.max(10);
-        let grid_height = grid_height.max(10);
+    /// Set the cleanup passes applied after rasterization, in order
+    pub fn with_post_processing(mut self, passes: Vec<Box<dyn GridPostProcessor>>) -> Self {
+        self.post_processors = passes;
+        self
+    }
 
-        // Enforce maximum size
-        let grid_width = grid_width.min(self.max_grid_size.0);
-        let grid_height = grid_height.min(self.max_grid_size.1);
+    /// Stop generating after `budget` and return the best grid produced so far
+    ///
+    /// Elements are buffered and rasterized by importance: roads, railways,
+    /// water and buildings first, then points of interest, then land-use
+    /// fills. Elements left when the budget runs out are skipped, as are the
+    /// remaining post-processing passes. The grid metadata records what was
+    /// skipped. Within a tier document order is kept, but strategies that
+    /// depend on element order (e.g., `FirstWins`) can resolve differently.
+    /// In browsers the budget is measured with `performance.now()`.
+    pub fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
 
-        Ok((grid_width, grid_height))
+    /// Fill the sea side of `natural=coastline` ways with water before rasterizing other features
+    ///
+    /// Without this, the open sea has no OSM feature of its own and stays
+    /// empty. Coastlines are fetched with [`crate::OsmFeature::Water`].
+    pub fn with_coastline_fill(mut self, enabled: bool) -> Self {
+        self.coastline_fill = enabled;
+        self
     }
 
-    /// Calculate approximate meters per tile
-    fn calculate_meters_per_tile(
-        &self,
-        config: &OsmConfig,
-        osm_data: &OsmData,
-        grid_dims: (usize, usize),
-    ) -> f32 {
-        let bbox = &osm_data.bounding_box;
+    /// Choose the number of rows so tiles are as tall as they are wide in meters
+    ///
+    /// WGS84 grids use the same number of cells per degree on both axes, so
+    /// away from the equator their tiles are taller than wide (about 1.6:1 at
+    /// 52°N). This keeps the column count and derives the row count from the
+    /// bounding box's extent in meters. Web Mercator grids are already square.
+    pub fn with_square_cells(mut self, enabled: bool) -> Self {
+        self.square_cells = enabled;
+        self
+    }
+
+    /// Report parsed elements, rasterized tiles and generation time to a [`MetricsSink`]
+    pub fn with_metrics_sink(mut self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.metrics = Some(sink);
+        self
+    }
 
-        // Use the area and grid size to estimate meters per tile
-        let area_km2 = bbox.area_km2();
-        let total_tiles = grid_dims.0 * grid_dims.1;
-        let km2_per_tile = area_km2 / total_tiles as f64;
-        let m2_per_tile = km2_per_tile * 1_000_000.0; // Convert km² to m²
-        let meters_per_tile = (m2_per_tile.sqrt()) as f32; // Approximate side length
+    /// Simplify water and green space polygons before filling them
+    ///
+    /// Vertices closer than `tolerance` tiles to the simplified outline are
+    /// dropped with Douglas-Peucker, so the tolerance scales with the cell
+    /// size. Filling large parks and lakes gets much faster, and at 0.5 tiles
+    /// or less the result is practically unchanged. Other tile types keep
+    /// their exact outlines.
+    pub fn with_polygon_simplification(mut self, tolerance: f32) -> Self {
+        self.polygon_simplification = Some(tolerance.max(0.0));
+        self
+    }
 
-        // Also consider the configured tile size as a hint
-        (meters_per_tile + config.tile_size) / 2.0
+    /// Choose what happens when the configured resolution exceeds the maximum grid size
+    pub fn with_oversize_policy(mut self, policy: OversizePolicy) -> Self {
+        self.oversize_policy = policy;
+        self
     }
 
-    /// Rasterize an OSM element onto the grid
-    fn rasterize_element(&self, element: &OsmElement, grid: &mut TileGrid) -> Result<u32> {
-        let tile_type = element.to_tile_type();
+    /// Fit the grid into the maximum size by lowering the resolution on both axes alike
+    ///
+    /// Clamping each axis on its own would stretch tiles and distort the
+    /// geo to grid mapping.
+    fn fit_grid(&self, config: &OsmConfig, bbox: &BoundingBox) -> Result<GridFit> {
+        let requested = self.requested_dimensions(config, bbox);
+        let (max_width, max_height) = self.max_grid_size;
+        let fits = |(width, height): (usize, usize)| width <= max_width && height <= max_height;
+        if fits(requested) {
+            return Ok(GridFit {
+                dimensions: requested,
+                downscaled: None,
+            });
+        }
 
-        // Skip empty tile types
+        // Start from the scale of the axis that overflows most; rounding up may need a few more steps
+        let mut scale =
+            (max_width as f64 / requested.0 as f64).min(max_height as f64 / requested.1 as f64);
+        let mut fitted = scaled_config(config, bbox, scale);
+        let mut dimensions = self.requested_dimensions(&fitted, bbox);
+        while !fits(dimensions) && fitted.grid_resolution_for(bbox) > 1 {
+            scale *= 0.98;
+            fitted = scaled_config(config, bbox, scale);
+            dimensions = self.requested_dimensions(&fitted, bbox);
+        }
+        let resolution = fitted.grid_resolution_for(bbox);
+
+        if self.oversize_policy == OversizePolicy::Error {
+            return Err(OsmTilesError::GridTooLarge {
+                width: requested.0,
+                height: requested.1,
+                max_width,
+                max_height,
+                suggested_resolution: resolution,
+            });
+        }
+        // Grids never shrink below 10 tiles, which can still exceed tiny maximum sizes
+        Ok(GridFit {
+            dimensions: (dimensions.0.min(max_width), dimensions.1.min(max_height)),
+            downscaled: Some((requested, resolution)),
+        })
+    }
+
+    /// Grid dimensions for the configured resolution, before the size limit
+    fn requested_dimensions(&self, config: &OsmConfig, bbox: &BoundingBox) -> (usize, usize) {
+        let (grid_width, mut grid_height) = config.grid_dimensions_for(bbox);
+
+        if self.square_cells {
+            // Keep the width and give rows the same height in meters as columns are wide
+            let cell_width_m = bbox.width_m() / grid_width.max(1) as f64;
+            if cell_width_m > 0.0 {
+                grid_height = ((bbox.height_m() / cell_width_m).round() as usize).max(1);
+            }
+        }
+
+        (grid_width, grid_height)
+    }
+
+    /// Calculate the mean tile side length in meters (geometric mean of both axes)
+    fn calculate_meters_per_tile(&self, bbox: &BoundingBox, grid_dims: (usize, usize)) -> f32 {
+        let x = bbox.width_m() / grid_dims.0.max(1) as f64;
+        let y = bbox.height_m() / grid_dims.1.max(1) as f64;
+        (x * y).sqrt() as f32
+    }
+
+    /// Place a tile, returning whether the grid changed
+    fn place_tile(&self, grid: &mut TileGrid, x: usize, y: usize, tile: Tile) -> Result<bool> {
+        // Tiles on different layers cover each other instead of conflicting
+        let contest = grid
+            .get_tile(x, y)
+            .filter(|_| self.audit_conflicts)
+            .filter(|existing| {
+                self.layer_mode == LayerMode::Ignore || existing.layer() == tile.layer()
+            })
+            .and_then(|existing| Contest::new(existing, &tile));
+        let changed = grid
+            .set_tile_layered(x, y, tile, &self.conflict_strategy, self.layer_mode)
+            .map_err(OsmTilesError::GridGeneration)?;
+        if let Some(contest) = contest {
+            grid.record_conflict(x, y, contest);
+        }
+        Ok(changed)
+    }
+
+    /// Rasterize an OSM element onto the grid as `tile_type`
+    fn rasterize_element(
+        &self,
+        element: &OsmElement,
+        tile_type: TileType,
+        grid: &mut TileGrid,
+    ) -> Result<u32> {
+        // Coastlines only separate land from sea, see `with_coastline_fill`
+        if element.is_coastline() {
+            return Ok(0);
+        }
+
+        // Skip empty tile types, remembering their tags so users can add rules for them
         if matches!(tile_type, TileType::Empty) {
+            grid.metadata.unclassified.record(element);
             return Ok(0);
         }
 
@@ -101,18 +363,15 @@ impl DefaultGridGenerator {
             1 => {
                 // Point geometry - place at single location
                 let (lat, lon) = element.geometry[0];
-                if let Some((x, y)) = grid.geo_to_grid(lat, lon) {
-                    if grid
-                        .set_tile_with_priority(x, y, tile)
-                        .map_err(|e| OsmTilesError::GridGeneration(e))?
-                    {
-                        tiles_updated += 1;
-                    }
+                if let Some((x, y)) = grid.geo_to_grid(lat, lon)
+                    && self.place_tile(grid, x, y, tile)?
+                {
+                    tiles_updated += 1;
                 }
             }
             _ => {
                 // Line or polygon geometry - rasterize along the path
-                tiles_updated += self.rasterize_line(&element.geometry, tile, grid)?;
+                tiles_updated += self.rasterize_line(element, tile, grid)?;
             }
         }
 
@@ -120,13 +379,9 @@ impl DefaultGridGenerator {
     }
 
     /// Rasterize a line or polygon onto the grid using Bresenham-like algorithm
-    fn rasterize_line(
-        &self,
-        geometry: &[(f64, f64)],
-        tile: Tile,
-        grid: &mut TileGrid,
-    ) -> Result<u32> {
+    fn rasterize_line(&self, element: &OsmElement, tile: Tile, grid: &mut TileGrid) -> Result<u32> {
         let mut tiles_updated = 0;
+        let geometry = &element.geometry;
 
         // For polygons (closed ways), also fill the interior for certain tile types
         let should_fill = matches!(
@@ -140,34 +395,42 @@ impl DefaultGridGenerator {
                 | TileType::Industrial
         );
 
-        // First, rasterize the outline
-        for window in geometry.windows(2) {
+        let coverage = (should_fill && geometry.len() >= 3)
+            .then(|| PolygonCoverage::new(grid, geometry, &element.holes));
+
+        // First, rasterize the outline, including the outlines of holes
+        let outlines = std::iter::once(geometry).chain(&element.holes);
+        for window in outlines.flat_map(|ring| ring.windows(2)) {
             let (lat1, lon1) = window[0];
             let (lat2, lon2) = window[1];
 
             if let (Some((x1, y1)), Some((x2, y2))) =
                 (grid.geo_to_grid(lat1, lon1), grid.geo_to_grid(lat2, lon2))
             {
-                tiles_updated += self.draw_line(x1, y1, x2, y2, tile.clone(), grid)?;
+                tiles_updated += self.draw_line(x1, y1, x2, y2, &tile, coverage.as_ref(), grid)?;
             }
         }
 
         // For filled shapes, use a simple flood fill approach
-        if should_fill && geometry.len() >= 3 {
-            tiles_updated += self.fill_polygon(geometry, tile, grid)?;
+        if let Some(coverage) = &coverage {
+            tiles_updated += self.fill_polygon(element, tile, coverage, grid)?;
         }
 
         Ok(tiles_updated)
     }
 
     /// Draw a line between two points using Bresenham's algorithm
+    ///
+    /// Outlines of filled polygons pass `coverage` to record how much of each tile they cover.
+    #[allow(clippy::too_many_arguments)]
     fn draw_line(
         &self,
         x1: usize,
         y1: usize,
         x2: usize,
         y2: usize,
-        tile: Tile,
+        tile: &Tile,
+        coverage: Option<&PolygonCoverage>,
         grid: &mut TileGrid,
     ) -> Result<u32> {
         let mut tiles_updated = 0;
@@ -185,10 +448,11 @@ impl DefaultGridGenerator {
             if x >= 0 && y >= 0 {
                 let ux = x as usize;
                 let uy = y as usize;
-                if grid
-                    .set_tile_with_priority(ux, uy, tile.clone())
-                    .map_err(|e| OsmTilesError::GridGeneration(e))?
-                {
+                let mut tile = tile.clone();
+                if let Some(coverage) = coverage {
+                    tile.coverage = coverage.coverage(ux, uy);
+                }
+                if self.place_tile(grid, ux, uy, tile)? {
                     tiles_updated += 1;
                 }
             }
@@ -211,45 +475,68 @@ impl DefaultGridGenerator {
         Ok(tiles_updated)
     }
 
-    /// Fill a polygon using scanline algorithm (simplified)
+    /// Fill a polygon row by row with an even-odd scanline over its outline and holes
+    ///
+    /// Each row is intersected with every ring edge at the latitude of its
+    /// tile centers, and the tiles whose centers lie between alternate
+    /// crossings are filled. This gives the same tiles as a point-in-polygon
+    /// test per tile center at the cost of one pass over the edges per row.
+    /// Polygons reaching past the grid are filled up to its edges.
     fn fill_polygon(
         &self,
-        geometry: &[(f64, f64)],
+        element: &OsmElement,
         tile: Tile,
+        coverage: &PolygonCoverage,
         grid: &mut TileGrid,
     ) -> Result<u32> {
         let mut tiles_updated = 0;
+        let (width, height) = grid.dimensions();
+        let Some((min_lat, _, max_lat, _)) = element.bounding_box() else {
+            return Ok(0);
+        };
 
-        // Find bounding box of polygon in grid coordinates
-        let mut min_x = usize::MAX;
-        let mut max_x = 0;
-        let mut min_y = usize::MAX;
-        let mut max_y = 0;
-
-        for (lat, lon) in geometry {
-            if let Some((x, y)) = grid.geo_to_grid(*lat, *lon) {
-                min_x = min_x.min(x);
-                max_x = max_x.max(x);
-                min_y = min_y.min(y);
-                max_y = max_y.max(y);
+        // Image rows whose tile centers lie within the polygon's latitude range
+        let (_, top) = grid.image_position(max_lat, grid.bounding_box.west);
+        let (_, bottom) = grid.image_position(min_lat, grid.bounding_box.west);
+        let first_row = (top - 0.5).ceil().max(0.0) as usize;
+        let end_row = ((bottom - 0.5).floor() + 1.0).clamp(0.0, height as f64) as usize;
+
+        let rings: Vec<&[(f64, f64)]> = std::iter::once(&element.geometry)
+            .chain(&element.holes)
+            .map(Vec::as_slice)
+            .collect();
+        let mut crossings = Vec::new();
+        for row in first_row..end_row {
+            let y = grid.origin.from_top_left(row, height);
+            let Some((lat, _)) = grid.grid_to_geo(0, y) else {
+                continue;
+            };
+
+            // Image x of every edge crossing the row, with the same half-open rule as `contains`
+            crossings.clear();
+            for ring in &rings {
+                for (i, &(lat_i, lon_i)) in ring.iter().enumerate() {
+                    let (lat_j, lon_j) = ring[(i + ring.len() - 1) % ring.len()];
+                    if (lat_i > lat) != (lat_j > lat) {
+                        let lon = (lon_j - lon_i) * (lat - lat_i) / (lat_j - lat_i) + lon_i;
+                        crossings.push(grid.image_position(lat, lon).0);
+                    }
+                }
             }
-        }
-
-        if min_x == usize::MAX {
-            return Ok(0); // No valid points
-        }
-
-        // Simple point-in-polygon test for each tile in bounding box
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                if let Some((lat, lon)) = grid.grid_to_geo(x, y) {
-                    if self.point_in_polygon(lat, lon, geometry) {
-                        if grid
-                            .set_tile_with_priority(x, y, tile.clone())
-                            .map_err(|e| OsmTilesError::GridGeneration(e))?
-                        {
-                            tiles_updated += 1;
-                        }
+            crossings.sort_by(f64::total_cmp);
+
+            // Fill the tiles whose centers lie in [start, end) of each span
+            for span in crossings.chunks_exact(2) {
+                let first = (span[0] - 0.5).ceil().clamp(0.0, width as f64) as usize;
+                let end = (span[1] - 0.5).ceil().clamp(0.0, width as f64) as usize;
+                for x in first..end {
+                    if self.place_tile(
+                        grid,
+                        x,
+                        y,
+                        tile.clone().with_coverage(coverage.coverage(x, y)),
+                    )? {
+                        tiles_updated += 1;
                     }
                 }
             }
@@ -258,89 +545,506 @@ impl DefaultGridGenerator {
         Ok(tiles_updated)
     }
 
-    /// Test if a point is inside a polygon using ray casting algorithm
-    fn point_in_polygon(&self, lat: f64, lon: f64, polygon: &[(f64, f64)]) -> bool {
-        let mut inside = false;
-        let mut j = polygon.len() - 1;
+    /// Generate a grid synchronously, without an async runtime
+    ///
+    /// This is what [`GridGenerator::generate_grid`] runs; the generator does
+    /// no I/O, so benchmarks and native tools can call it directly.
+    pub fn generate_grid_sync(&self, osm_data: &OsmData, config: &OsmConfig) -> Result<TileGrid> {
+        self.generate_layer(osm_data, config, None)
+    }
 
-        for i in 0..polygon.len() {
-            let (lat_i, lon_i) = polygon[i];
-            let (lat_j, lon_j) = polygon[j];
+    /// Generate one grid per [`GridLayer`], so a cell keeps e.g. both a road and its land use
+    ///
+    /// Each layer is rasterized in its own pass over the data, with the
+    /// conflict strategy, post-processing and budgets of this generator.
+    pub fn generate_layered_grid_sync(
+        &self,
+        osm_data: &OsmData,
+        config: &OsmConfig,
+    ) -> Result<LayeredTileGrid> {
+        GridLayer::ALL
+            .iter()
+            .map(|&layer| Ok((layer, self.generate_layer(osm_data, config, Some(layer))?)))
+            .collect()
+    }
 
-            if ((lat_i > lat) != (lat_j > lat))
-                && (lon < (lon_j - lon_i) * (lat - lat_i) / (lat_j - lat_i) + lon_i)
+    /// Generate a grid of the elements on `layer`, or of all elements
+    #[tracing::instrument(
+        name = "generate_grid",
+        skip_all,
+        fields(
+            area_km2 = osm_data.bounding_box.area_km2(),
+            layer = layer.map(|layer| layer.name()),
+            width = Empty,
+            height = Empty,
+            element_count = Empty,
+            tiles_populated = Empty,
+        )
+    )]
+    fn generate_layer(
+        &self,
+        osm_data: &OsmData,
+        config: &OsmConfig,
+        layer: Option<GridLayer>,
+    ) -> Result<TileGrid> {
+        let mut pass = GridPass::new(
+            self,
+            &osm_data.bounding_box,
+            config,
+            layer,
+            Some(osm_data.raw_data.len()),
+        )?;
+
+        // The sea is a base layer that every other feature can overwrite
+        if self.coastline_fill && pass.on_layer(GridLayer::Water) {
+            let _span = tracing::info_span!("coastline_fill").entered();
+            let mut coastlines = Vec::new();
+            self.parser.for_each_element(osm_data, |element| {
+                if element.is_coastline() {
+                    coastlines.push(element);
+                }
+                Ok(())
+            })?;
+            pass.fill_coastlines(&coastlines);
+        }
+
+        let element_count = if pass.deadline.is_none() {
+            // Stream elements from the raw data and rasterize each one as it is parsed,
+            // so parsing is part of the rasterize span
+            let _span = tracing::info_span!("rasterize", streaming = true).entered();
+            self.parser
+                .for_each_element(osm_data, |element| pass.add_element(&element))?
+        } else {
+            // Buffer all elements so the most important ones are rasterized first
+            let mut elements =
+                tracing::info_span!("parse").in_scope(|| self.parser.parse(osm_data))?;
+            pass.track_parsed(elements.iter().map(estimate_element_bytes).sum())?;
+            elements.sort_by_cached_key(|element| budget_tier(&element.to_tile_type()));
+            let _span = tracing::info_span!("rasterize", streaming = false).entered();
+            for element in &elements {
+                pass.add_element(element)?;
+            }
+            elements.len()
+        };
+        pass.finish(osm_data, element_count)
+    }
+
+    /// Generate one grid per slippy tile from a single parse of the data
+    ///
+    /// Each tile only rasterizes the elements whose extent touches it, so
+    /// the work per tile shrinks with the tile instead of staying that of
+    /// the whole data.
+    fn generate_slippy_grids_sync(
+        &self,
+        osm_data: &OsmData,
+        config: &OsmConfig,
+    ) -> Result<Vec<(SlippyTile, TileGrid)>> {
+        let (tiles, tile_config) = slippy_layout(osm_data, config)?;
+        let _span = tracing::info_span!("generate_slippy_grids", tiles = tiles.len()).entered();
+
+        let elements = tracing::info_span!("parse").in_scope(|| self.parser.parse(osm_data))?;
+        let parse_bytes = elements.iter().map(estimate_element_bytes).sum();
+        let extents: Vec<_> = elements.iter().map(element_extent).collect();
+        let coastlines: Vec<OsmElement> = if self.coastline_fill {
+            elements
+                .iter()
+                .filter(|element| element.is_coastline())
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        // With a time budget the most important elements are rasterized first
+        let mut order: Vec<usize> = (0..elements.len()).collect();
+        if self.time_budget.is_some() {
+            order.sort_by_cached_key(|&index| budget_tier(&elements[index].to_tile_type()));
+        }
+
+        let mut grids = Vec::with_capacity(tiles.len());
+        for tile in tiles {
+            let bounds = tile.bounds();
+            let _span = tracing::info_span!(
+                "generate_grid",
+                slippy_tile = tile.key(),
+                width = Empty,
+                height = Empty,
+                element_count = Empty,
+                tiles_populated = Empty,
+            )
+            .entered();
+            let mut pass = GridPass::new(
+                self,
+                &bounds,
+                &tile_config,
+                None,
+                Some(osm_data.raw_data.len()),
+            )?;
+            pass.track_parsed(parse_bytes)?;
+            if !coastlines.is_empty() {
+                let _span = tracing::info_span!("coastline_fill").entered();
+                pass.fill_coastlines(&coastlines);
+            }
+
+            let mut element_count = 0;
             {
-                inside = !inside;
+                let _span = tracing::info_span!("rasterize", streaming = false).entered();
+                for &index in &order {
+                    if extents[index].is_some_and(|extent| overlaps(extent, &bounds)) {
+                        pass.add_element(&elements[index])?;
+                        element_count += 1;
+                    }
+                }
             }
-            j = i;
+            let mut grid = pass.finish(osm_data, element_count)?;
+            grid.metadata
+                .extra
+                .insert("slippy_tile".to_string(), tile.key());
+            grids.push((tile, grid));
         }
 
-        inside
+        Ok(grids)
     }
-}
 
-impl Default for DefaultGridGenerator {
-    fn default() -> Self {
-        Self::new()
+    /// Check whether elements can be rasterized in the order they arrive
+    ///
+    /// Coastline fill needs all coastlines up front, and a time budget
+    /// reorders elements by importance, so both need the whole data first.
+    pub(crate) fn rasterizes_in_arrival_order(&self) -> bool {
+        !self.coastline_fill && self.time_budget.is_none()
+    }
+
+    /// Start a grid over `bbox` that elements are added to one at a time
+    ///
+    /// Used to rasterize elements while their data is still downloading, see
+    /// [`rasterizes_in_arrival_order`](Self::rasterizes_in_arrival_order).
+    pub(crate) fn begin_grid<'g>(
+        &'g self,
+        bbox: &BoundingBox,
+        config: &'g OsmConfig,
+    ) -> Result<GridPass<'g>> {
+        GridPass::new(self, bbox, config, None, None)
+    }
+
+    /// Parse elements from chunks of Overpass JSON as they arrive
+    pub(crate) fn element_stream(&self) -> JsonElementStream<'_> {
+        JsonElementStream::new(&self.parser)
     }
 }
 
-#[async_trait]
-impl GridGenerator for DefaultGridGenerator {
-    async fn generate_grid(&self, osm_data: &OsmData, config: &OsmConfig) -> Result<TileGrid> {
-        #[cfg(not(target_arch = "wasm32"))]
-        let start_time = Instant::now();
+/// One rasterization pass of a [`DefaultGridGenerator`] over a stream of elements
+pub(crate) struct GridPass<'g> {
+    generator: &'g DefaultGridGenerator,
+    config: &'g OsmConfig,
+    layer: Option<GridLayer>,
+    grid: TileGrid,
+    memory: MemoryTracker,
+    deadline: Option<Deadline>,
+    start_time: Instant,
+    collect_districts: bool,
+    /// Compiled exclusion queries of the feature set
+    exclusions: Vec<TagMatcher>,
+    /// Compiled custom queries overriding the tile type of their matches
+    type_overrides: Vec<(TagMatcher, TileType)>,
+    boundaries: Vec<OsmElement>,
+    road_features: Vec<((f64, f64), RoadFeature)>,
+    total_tiles_updated: u32,
+    simplified_vertices: usize,
+    elements_skipped: usize,
+    /// Estimated bytes held by parsed elements
+    parse_bytes: usize,
+    /// Whether the buffered elements were tracked before rasterization
+    parse_tracked: bool,
+    /// Whether the raw data size was tracked when the pass started
+    download_tracked: bool,
+    /// Requested dimensions and effective resolution, if the grid was downscaled
+    downscaled: Option<((usize, usize), u32)>,
+}
 
-        tracing::info!("Generating grid from OSM data");
+impl<'g> GridPass<'g> {
+    /// Allocate the grid, tracking the `download_bytes` of the raw data if already known
+    fn new(
+        generator: &'g DefaultGridGenerator,
+        bbox: &BoundingBox,
+        config: &'g OsmConfig,
+        layer: Option<GridLayer>,
+        download_bytes: Option<usize>,
+    ) -> Result<Self> {
+        let start_time = Instant::now();
 
-        // Parse OSM data
-        let elements = self.parser.parse(osm_data)?;
-        tracing::debug!("Parsed {} OSM elements", elements.len());
+        let mut memory =
+            MemoryTracker::new(config.memory_budget_bytes, config.grid_resolution_for(bbox));
+        if let Some(bytes) = download_bytes {
+            memory.track_fixed("download", bytes)?;
+        }
 
         // Calculate grid dimensions
-        let (grid_width, grid_height) = self.calculate_grid_dimensions(config, osm_data)?;
-        let meters_per_tile =
-            self.calculate_meters_per_tile(config, osm_data, (grid_width, grid_height));
+        let GridFit {
+            dimensions: (grid_width, grid_height),
+            downscaled,
+        } = generator.fit_grid(config, bbox)?;
+        if let Some(((width, height), resolution)) = downscaled {
+            tracing::warn!(
+                "A {}x{} grid exceeds the maximum of {}x{}; generating {}x{} at resolution {} instead",
+                width,
+                height,
+                generator.max_grid_size.0,
+                generator.max_grid_size.1,
+                grid_width,
+                grid_height,
+                resolution
+            );
+        }
+        let meters_per_tile = generator.calculate_meters_per_tile(bbox, (grid_width, grid_height));
 
-        tracing::info!(
-            "Creating {}x{} grid ({} tiles, ~{:.1}m per tile)",
-            grid_width,
-            grid_height,
-            grid_width * grid_height,
-            meters_per_tile
-        );
+        let span = tracing::Span::current();
+        span.record("width", grid_width);
+        span.record("height", grid_height);
+        tracing::debug!(meters_per_tile, "Allocating grid");
+
+        // Check the budget before allocating the grid
+        memory.track_grid(
+            "grid allocation",
+            estimate_grid_bytes(grid_width, grid_height),
+        )?;
 
         // Create empty grid
-        let mut grid = TileGrid::new(
-            grid_width,
-            grid_height,
-            osm_data.bounding_box.clone(),
-            meters_per_tile,
+        let grid = TileGrid::new(grid_width, grid_height, bbox.clone(), meters_per_tile)
+            .with_crs(config.crs)
+            .with_origin(config.grid_origin);
+
+        let collect_districts = config.features.contains_feature(&OsmFeature::Boundaries)
+            && layer.is_none_or(|layer| layer == GridLayer::Terrain);
+        let exclusions = config
+            .features
+            .exclusions()
+            .map(OsmTagQuery::matcher)
+            .collect::<Result<_>>()?;
+        let type_overrides = config
+            .features
+            .tile_type_overrides()
+            .map(|(query, tile_type)| Ok((query.matcher()?, tile_type.clone())))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            generator,
+            config,
+            layer,
+            grid,
+            memory,
+            deadline: generator.time_budget.map(Deadline::after),
+            start_time,
+            collect_districts,
+            exclusions,
+            type_overrides,
+            boundaries: Vec::new(),
+            road_features: Vec::new(),
+            total_tiles_updated: 0,
+            simplified_vertices: 0,
+            elements_skipped: 0,
+            parse_bytes: 0,
+            parse_tracked: false,
+            download_tracked: download_bytes.is_some(),
+            downscaled,
+        })
+    }
+
+    /// Track the `bytes` of buffered elements before any of them is rasterized
+    fn track_parsed(&mut self, bytes: usize) -> Result<()> {
+        self.memory.track_fixed("parse", bytes)?;
+        self.parse_bytes = bytes;
+        self.parse_tracked = true;
+        Ok(())
+    }
+
+    /// Fill the sea enclosed by `coastlines` as a base layer
+    fn fill_coastlines(&mut self, coastlines: &[OsmElement]) {
+        if coastlines.is_empty() {
+            return;
+        }
+        let sea_tiles = fill_sea(&mut self.grid, coastlines);
+        tracing::debug!(
+            "Filled {} sea tiles from {} coastlines",
+            sea_tiles,
+            coastlines.len()
         );
+        self.grid
+            .metadata
+            .extra
+            .insert("sea_tiles".to_string(), sea_tiles.to_string());
+    }
 
-        // Rasterize each element onto the grid
-        let mut total_tiles_updated = 0;
-        for element in &elements {
-            let tiles_updated = self.rasterize_element(element, &mut grid)?;
-            total_tiles_updated += tiles_updated;
+    /// Check whether elements of a layer are rasterized in this pass
+    fn on_layer(&self, target: GridLayer) -> bool {
+        self.layer.is_none_or(|layer| layer == target)
+    }
+
+    /// Rasterize an element, or skip it once the time budget is exhausted
+    pub(crate) fn add_element(&mut self, element: &OsmElement) -> Result<()> {
+        if self.deadline.as_ref().is_some_and(Deadline::expired) {
+            self.elements_skipped += 1;
+            return Ok(());
+        }
+        // Providers that cannot filter by tag still deliver excluded elements
+        if self
+            .exclusions
+            .iter()
+            .any(|exclusion| exclusion.matches(&element.tags))
+        {
+            return Ok(());
+        }
+        // Only one element is alive at a time while streaming
+        self.parse_bytes = self.parse_bytes.max(estimate_element_bytes(element));
+        let tile_type = self
+            .type_overrides
+            .iter()
+            .find(|(matcher, _)| matcher.matches(&element.tags))
+            .map_or_else(
+                || element.to_tile_type(),
+                |(_, tile_type)| tile_type.clone(),
+            );
+        if !self.on_layer(GridLayer::for_tile_type(&tile_type)) {
+            return Ok(());
+        }
+        let grid = &mut self.grid;
+        // Districts are assigned once the final tile types are known
+        if self.collect_districts && is_admin_boundary(element) {
+            self.boundaries.push(element.clone());
+        }
+        // Traffic signals and crossings annotate the road they lie on instead of painting tiles
+        if let Some(feature) = RoadFeature::from_element(element) {
+            self.road_features
+                .extend(element.geometry.first().map(|&point| (point, feature)));
+            return Ok(());
         }
+        if let Some(poi) = Poi::from_element(element, grid) {
+            grid.pois.insert(poi);
+        }
+        let generator = self.generator;
+        let simplified = generator
+            .polygon_simplification
+            .and_then(|tolerance| simplify_polygon(element, grid, tolerance));
+        if let Some(simplified) = &simplified {
+            self.simplified_vertices += vertex_count(element) - vertex_count(simplified);
+        }
+        let tiles_updated =
+            generator.rasterize_element(simplified.as_ref().unwrap_or(element), tile_type, grid)?;
+        self.total_tiles_updated += tiles_updated;
+        // All tiles of an element share one copy of its metadata
+        let metadata_bytes = if tiles_updated > 0 {
+            estimate_tile_metadata_bytes(element)
+        } else {
+            0
+        };
+        self.memory.track_grid("rasterization", metadata_bytes)
+    }
 
-        let generation_time = {
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                start_time.elapsed().as_millis() as u64
+    /// Post-process the grid and record its metadata, once all `element_count` elements were added
+    pub(crate) fn finish(self, osm_data: &OsmData, element_count: usize) -> Result<TileGrid> {
+        let Self {
+            generator,
+            config,
+            layer,
+            mut grid,
+            mut memory,
+            deadline,
+            start_time,
+            collect_districts: _,
+            exclusions: _,
+            type_overrides: _,
+            boundaries,
+            road_features,
+            total_tiles_updated,
+            simplified_vertices,
+            elements_skipped,
+            parse_bytes,
+            parse_tracked,
+            download_tracked,
+            downscaled,
+        } = self;
+        let span = tracing::Span::current();
+        span.record("element_count", element_count);
+        if !download_tracked {
+            memory.track_fixed("download", osm_data.raw_data.len())?;
+        }
+        if !parse_tracked {
+            memory.track_fixed("parse", parse_bytes)?;
+        }
+
+        let mut manifest = GridManifest::new(osm_data, config);
+        let mut passes_skipped = 0;
+        for pass in &generator.post_processors {
+            if deadline.as_ref().is_some_and(Deadline::expired) {
+                passes_skipped += 1;
+                continue;
             }
-            #[cfg(target_arch = "wasm32")]
-            {
-                1u64 // Default value for WASM
+            manifest.post_processing.push(pass.name().to_string());
+            let changed = tracing::info_span!("post_process", pass = pass.name())
+                .in_scope(|| pass.process(&mut grid));
+            tracing::debug!(
+                "Post-processing pass '{}' changed {} tiles",
+                pass.name(),
+                changed
+            );
+        }
+        if !road_features.is_empty() {
+            let mut unmatched = 0;
+            for ((lat, lon), feature) in road_features {
+                if grid.attach_road_feature(lat, lon, feature).is_none() {
+                    unmatched += 1;
+                }
             }
-        };
+            tracing::debug!("{} road features had no road tile nearby", unmatched);
+            grid.metadata
+                .extra
+                .insert("road_features_unmatched".to_string(), unmatched.to_string());
+        }
+
+        if !boundaries.is_empty() {
+            let _span = tracing::info_span!("districts").entered();
+            grid.districts = Districts::from_boundaries(&boundaries, &grid);
+            tracing::debug!(
+                "Assigned tiles to {} districts from {} boundaries",
+                grid.districts.len(),
+                boundaries.len()
+            );
+            grid.metadata
+                .extra
+                .insert("districts".to_string(), grid.districts.len().to_string());
+        }
+
+        let generation_time = start_time.elapsed().as_millis() as u64;
+
+        if let Some(sink) = &generator.metrics {
+            sink.increment_counter(ELEMENTS_PARSED_TOTAL, element_count as u64, &[]);
+            sink.increment_counter(TILES_RASTERIZED_TOTAL, u64::from(total_tiles_updated), &[]);
+            sink.record_histogram(
+                GRID_GENERATION_DURATION_SECONDS,
+                generation_time as f64 / 1000.0,
+                &[],
+            );
+        }
 
         // Update grid metadata
-        grid.metadata.elements_processed = elements.len() as u32;
+        let (grid_width, grid_height) = grid.dimensions();
+        let meters_per_tile = grid.meters_per_tile;
+        grid.metadata.elements_processed = element_count as u32;
         grid.metadata.tiles_populated = total_tiles_updated as usize;
         grid.metadata.generation_time_ms = generation_time;
         grid.metadata.algorithm = "default_rasterization".to_string();
+        grid.metadata.attribution = Some(Attribution::from_osm_metadata(&osm_data.metadata));
+        grid.metadata.data_timestamp = osm_data.metadata.data_timestamp;
+        grid.metadata.manifest = Some(manifest);
+        if let Some(max_age) = config.max_data_age()
+            && grid.metadata.is_stale(max_age)
+        {
+            tracing::warn!(
+                "OSM data from {} is older than the maximum age of {}s",
+                osm_data.metadata.data_timestamp.unwrap_or_default(),
+                max_age.as_secs()
+            );
+            grid.metadata
+                .extra
+                .insert("stale_data".to_string(), "true".to_string());
+        }
         grid.metadata
             .extra
             .insert("grid_width".to_string(), grid_width.to_string());
@@ -350,30 +1054,147 @@ impl GridGenerator for DefaultGridGenerator {
         grid.metadata
             .extra
             .insert("meters_per_tile".to_string(), meters_per_tile.to_string());
-
-        // Conditional logging
-        #[cfg(not(target_arch = "wasm32"))]
-        tracing::info!(
-            "Grid generation complete: {}/{} tiles populated in {:.1}s",
-            total_tiles_updated,
-            grid_width * grid_height,
-            generation_time as f64 / 1000.0
+        grid.metadata.extra.insert(
+            "meters_per_tile_x".to_string(),
+            grid.meters_per_tile_x().to_string(),
         );
-
-        #[cfg(target_arch = "wasm32")]
-        tracing::info!(
-            "Grid generation complete: {}/{} tiles populated",
-            total_tiles_updated,
-            grid_width * grid_height,
+        grid.metadata.extra.insert(
+            "meters_per_tile_y".to_string(),
+            grid.meters_per_tile_y().to_string(),
         );
+        if let Some(((width, height), resolution)) = downscaled {
+            grid.metadata
+                .extra
+                .insert("requested_grid_width".to_string(), width.to_string());
+            grid.metadata
+                .extra
+                .insert("requested_grid_height".to_string(), height.to_string());
+            grid.metadata.extra.insert(
+                "effective_grid_resolution".to_string(),
+                resolution.to_string(),
+            );
+        }
+        if let Some(target) = config.cell_size_meters {
+            // The achieved size differs from the target when the grid was downscaled
+            grid.metadata
+                .extra
+                .insert("target_cell_size_meters".to_string(), target.to_string());
+            grid.metadata.extra.insert(
+                "grid_resolution".to_string(),
+                downscaled
+                    .map_or_else(
+                        || config.grid_resolution_for(&osm_data.bounding_box),
+                        |(_, resolution)| resolution,
+                    )
+                    .to_string(),
+            );
+        }
+        if let Some(tolerance) = generator.polygon_simplification {
+            grid.metadata
+                .extra
+                .insert("polygon_simplification".to_string(), tolerance.to_string());
+            grid.metadata.extra.insert(
+                "simplified_vertices".to_string(),
+                simplified_vertices.to_string(),
+            );
+        }
+        grid.metadata.extra.insert(
+            "conflict_strategy".to_string(),
+            generator.conflict_strategy.name().to_string(),
+        );
+        if let Some(manifest) = &grid.metadata.manifest
+            && !manifest.post_processing.is_empty()
+        {
+            let passes = manifest.post_processing.join(",");
+            grid.metadata
+                .extra
+                .insert("post_processing".to_string(), passes);
+        }
+        if generator.audit_conflicts {
+            grid.metadata.extra.insert(
+                "tile_conflicts".to_string(),
+                grid.conflicts().count().to_string(),
+            );
+        }
+        grid.metadata.extra.insert(
+            "layer_mode".to_string(),
+            generator.layer_mode.name().to_string(),
+        );
+        if let Some(layer) = layer {
+            grid.metadata
+                .extra
+                .insert("grid_layer".to_string(), layer.name().to_string());
+        }
+        grid.metadata.extra.insert(
+            "estimated_peak_memory_bytes".to_string(),
+            memory.peak_bytes().to_string(),
+        );
+        if let Some(budget) = generator.time_budget {
+            let extra = &mut grid.metadata.extra;
+            extra.insert("time_budget_ms".to_string(), budget.as_millis().to_string());
+            extra.insert("elements_skipped".to_string(), elements_skipped.to_string());
+            extra.insert(
+                "post_processing_skipped".to_string(),
+                passes_skipped.to_string(),
+            );
+            let exhausted = elements_skipped > 0 || passes_skipped > 0;
+            extra.insert("budget_exhausted".to_string(), exhausted.to_string());
+            if exhausted {
+                tracing::warn!(
+                    "Time budget of {}ms exhausted: skipped {} of {} elements and {} post-processing passes",
+                    budget.as_millis(),
+                    elements_skipped,
+                    element_count,
+                    passes_skipped
+                );
+            }
+        }
+        if !grid.metadata.unclassified.is_empty() {
+            grid.metadata.extra.insert(
+                "unclassified_elements".to_string(),
+                grid.metadata.unclassified.total.to_string(),
+            );
+            tracing::debug!(
+                "{} tagged elements were not classified, most common: {:?}",
+                grid.metadata.unclassified.total,
+                grid.metadata.unclassified.most_common(5)
+            );
+        }
+
+        span.record("tiles_populated", total_tiles_updated);
+        tracing::info!(elapsed_ms = generation_time, "Generated grid");
 
         Ok(grid)
     }
+}
+
+impl Default for DefaultGridGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl GridGenerator for DefaultGridGenerator {
+    async fn generate_grid(&self, osm_data: &OsmData, config: &OsmConfig) -> Result<TileGrid> {
+        self.generate_grid_sync(osm_data, config)
+    }
+
+    async fn generate_slippy_grids(
+        &self,
+        osm_data: &OsmData,
+        config: &OsmConfig,
+    ) -> Result<Vec<(SlippyTile, TileGrid)>> {
+        self.generate_slippy_grids_sync(osm_data, config)
+    }
 
     fn capabilities(&self) -> GeneratorCapabilities {
         GeneratorCapabilities {
             max_grid_size: Some(self.max_grid_size),
-            supported_crs: vec!["EPSG:4326".to_string()],
+            supported_crs: Crs::all()
+                .iter()
+                .map(|crs| crs.code().to_string())
+                .collect(),
             supports_parallel: false,
             notes: Some("Default rasterization-based grid generator".to_string()),
         }
@@ -383,7 +1204,10 @@ impl GridGenerator for DefaultGridGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{BoundingBox, OsmConfigBuilder, OsmDataFormat, OsmMetadata};
+    use crate::{
+        BoundingBox, CloseRoadGaps, DilateTiles, FeatureSet, GridOrigin, InMemoryMetricsSink,
+        MockProvider, OsmConfigBuilder, OsmDataFormat, OsmDataProvider, OsmMetadata, tile_for,
+    };
 
     fn create_test_osm_data() -> OsmData {
         let json_data = r#"{
@@ -459,6 +1283,38 @@ mod tests {
         assert_eq!(grid.metadata.elements_processed, 4); // Updated for 4 elements
         assert!(grid.metadata.tiles_populated > 0);
         assert_eq!(grid.metadata.algorithm, "default_rasterization");
+        // The cafe node is also kept as a discrete POI
+        assert_eq!(grid.pois.len(), 1);
+        let cafe = grid.pois.get(3).unwrap();
+        assert_eq!(Some(cafe.grid_pos), grid.geo_to_grid(52.5005, 13.4005));
+        let attribution = grid.metadata.attribution.as_ref().unwrap();
+        assert_eq!(attribution.source, "test");
+        assert_eq!(
+            attribution.data_timestamp,
+            Some(osm_data.metadata.timestamp.clone())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_grid_sync() {
+        let generator = DefaultGridGenerator::new();
+        let osm_data = create_test_osm_data();
+        let config = OsmConfigBuilder::new().grid_resolution(100).build();
+
+        let sync_grid = generator.generate_grid_sync(&osm_data, &config).unwrap();
+        let async_grid = generator.generate_grid(&osm_data, &config).await.unwrap();
+        assert_eq!(sync_grid.dimensions(), async_grid.dimensions());
+        assert!(
+            sync_grid
+                .flat_tiles()
+                .iter()
+                .zip(async_grid.flat_tiles())
+                .all(|(a, b)| a.tile_type == b.tile_type)
+        );
+        assert_eq!(
+            sync_grid.metadata.tiles_populated,
+            async_grid.metadata.tiles_populated
+        );
     }
 
     #[tokio::test]
@@ -560,37 +1416,210 @@ mod tests {
     }
 
     #[test]
-    fn test_point_in_polygon() {
+    fn test_grid_dimensions_calculation() {
         let generator = DefaultGridGenerator::new();
+        let osm_data = create_test_osm_data();
+        let config = OsmConfigBuilder::new().grid_resolution(100).build();
 
-        // Square polygon
-        let polygon = vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)];
+        let (width, height) = generator
+            .fit_grid(&config, &osm_data.bounding_box)
+            .unwrap()
+            .dimensions;
 
-        // Point inside
-        assert!(generator.point_in_polygon(0.5, 0.5, &polygon));
+        // Should be reasonable size
+        assert!(width >= 10);
+        assert!(height >= 10);
+        assert!(width <= 5000);
+        assert!(height <= 5000);
+    }
 
-        // Point outside
-        assert!(!generator.point_in_polygon(1.5, 0.5, &polygon));
+    #[tokio::test]
+    async fn test_oversized_grid_is_downscaled() {
+        let osm_data = create_test_osm_data();
+        let config = OsmConfigBuilder::new().grid_resolution(10_000).build();
 
-        // Point on edge (may vary depending on implementation)
-        // assert!(!generator.point_in_polygon(0.0, 0.5, &polygon));
+        // Clamping each axis would give 50x40 and stretch the tiles
+        let grid = DefaultGridGenerator::with_max_size(50, 40)
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        let (width, height) = grid.dimensions();
+        assert_eq!(width, height);
+        assert!((38..=40).contains(&height));
+        let extra = &grid.metadata.extra;
+        assert!(extra["requested_grid_width"].parse::<usize>().unwrap() >= 200);
+        let resolution: u32 = extra["effective_grid_resolution"].parse().unwrap();
+        assert!((1900..=2000).contains(&resolution));
+
+        let error = DefaultGridGenerator::with_max_size(50, 40)
+            .with_oversize_policy(OversizePolicy::Error)
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            OsmTilesError::GridTooLarge { max_height: 40, suggested_resolution, .. }
+                if suggested_resolution == resolution
+        ));
     }
 
-    #[test]
-    fn test_grid_dimensions_calculation() {
+    #[tokio::test]
+    async fn test_web_mercator_grid_dimensions() {
         let generator = DefaultGridGenerator::new();
         let osm_data = create_test_osm_data();
-        let config = OsmConfigBuilder::new().grid_resolution(100).build();
+        let config = OsmConfigBuilder::new().grid_resolution(1000).build();
+        let mercator_config = OsmConfigBuilder::new()
+            .grid_resolution(1000)
+            .crs(Crs::WebMercator)
+            .build();
 
         let (width, height) = generator
-            .calculate_grid_dimensions(&config, &osm_data)
+            .fit_grid(&config, &osm_data.bounding_box)
+            .unwrap()
+            .dimensions;
+        let (mercator_width, mercator_height) = generator
+            .fit_grid(&mercator_config, &osm_data.bounding_box)
+            .unwrap()
+            .dimensions;
+
+        // At ~52.5°N a degree of latitude is ~1.64x taller than a degree of
+        // longitude in Mercator, so square tiles need more rows
+        assert_eq!(width, mercator_width);
+        assert_eq!(height, width);
+        assert_eq!(mercator_height, (width as f64 * 1.64).ceil() as usize);
+
+        let grid = generator
+            .generate_grid(&osm_data, &mercator_config)
+            .await
             .unwrap();
+        assert_eq!(grid.crs, Crs::WebMercator);
+        assert_eq!(grid.dimensions(), (mercator_width, mercator_height));
+    }
 
-        // Should be reasonable size
-        assert!(width >= 10);
-        assert!(height >= 10);
-        assert!(width <= 5000);
-        assert!(height <= 5000);
+    #[tokio::test]
+    async fn test_memory_budget() {
+        let generator = DefaultGridGenerator::new();
+        let osm_data = create_test_osm_data();
+
+        let config = OsmConfigBuilder::new().grid_resolution(1000).build();
+        let grid = generator.generate_grid(&osm_data, &config).await.unwrap();
+        let peak: usize = grid.metadata.extra["estimated_peak_memory_bytes"]
+            .parse()
+            .unwrap();
+        assert!(peak > 0);
+
+        // A generous budget succeeds
+        let config = OsmConfigBuilder::new()
+            .grid_resolution(1000)
+            .memory_budget(peak * 2)
+            .build();
+        assert!(generator.generate_grid(&osm_data, &config).await.is_ok());
+
+        // A budget too small for the grid fails before allocating it
+        let config = OsmConfigBuilder::new()
+            .grid_resolution(1000)
+            .memory_budget(osm_data.raw_data.len() * 4)
+            .build();
+        let error = generator
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap_err();
+        match &error {
+            OsmTilesError::MemoryBudgetExceeded {
+                stage,
+                suggested_resolution,
+                ..
+            } => {
+                assert_eq!(stage, "grid allocation");
+                assert!(suggested_resolution.is_some_and(|r| r < 1000));
+            }
+            other => panic!("Unexpected error: {other}"),
+        }
+        assert!(error.to_string().contains("grid resolution"));
+
+        // Buffered elements are checked against the budget before any is rasterized
+        let (width, height) = generator
+            .generate_grid(
+                &osm_data,
+                &OsmConfigBuilder::new().grid_resolution(1000).build(),
+            )
+            .await
+            .unwrap()
+            .dimensions();
+        let config = OsmConfigBuilder::new()
+            .grid_resolution(1000)
+            .memory_budget(osm_data.raw_data.len() + estimate_grid_bytes(width, height) + 1)
+            .build();
+        let error = DefaultGridGenerator::new()
+            .with_time_budget(Duration::from_secs(60))
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap_err();
+        match &error {
+            OsmTilesError::MemoryBudgetExceeded { stage, .. } => assert_eq!(stage, "parse"),
+            other => panic!("Unexpected error: {other}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slippy_grid_generation() {
+        let generator = DefaultGridGenerator::new();
+        let osm_data = create_test_osm_data();
+
+        let config = OsmConfigBuilder::new().grid_resolution(10_000).build();
+        assert!(
+            generator
+                .generate_slippy_grids(&osm_data, &config)
+                .await
+                .is_err()
+        );
+
+        let config = OsmConfigBuilder::new()
+            .grid_resolution(10_000)
+            .slippy_zoom(14)
+            .build();
+        let grids = generator
+            .generate_slippy_grids(&osm_data, &config)
+            .await
+            .unwrap();
+
+        let expected = SlippyTile::covering(&osm_data.bounding_box, 14);
+        assert_eq!(grids.len(), expected.len());
+        for ((tile, grid), expected_tile) in grids.iter().zip(&expected) {
+            assert_eq!(tile, expected_tile);
+            assert_eq!(grid.bounding_box, tile.bounds());
+            assert_eq!(grid.crs, Crs::WebMercator);
+            assert_eq!(grid.metadata.extra["slippy_tile"], tile.key());
+
+            // Slippy tiles are square in Mercator space, so grids are too
+            let (width, height) = grid.dimensions();
+            assert!(width.abs_diff(height) <= 1);
+        }
+
+        // The test elements end up in the tile containing them
+        let road_tile = tile_for(52.5, 13.4, 14);
+        let (_, road_grid) = grids.iter().find(|(tile, _)| *tile == road_tile).unwrap();
+        assert!(road_grid.metadata.tiles_populated > 0);
+
+        // Data is parsed once and each tile only rasterizes the elements touching it
+        assert!(grids.len() > 1);
+        for (tile, grid) in &grids {
+            let tile_data = OsmData {
+                bounding_box: tile.bounds(),
+                ..osm_data.clone()
+            };
+            let expected = generator
+                .generate_grid_sync(&tile_data, &config.clone().with_crs(Crs::WebMercator))
+                .unwrap();
+            assert_eq!(
+                grid.metadata.tiles_populated,
+                expected.metadata.tiles_populated
+            );
+            assert_eq!(grid.count_tiles_by_type(), expected.count_tiles_by_type());
+            if *tile != road_tile {
+                assert!(grid.metadata.elements_processed < expected.metadata.elements_processed);
+            }
+        }
     }
 
     #[tokio::test]
@@ -604,6 +1633,11 @@ mod tests {
                 .supported_crs
                 .contains(&"EPSG:4326".to_string())
         );
+        assert!(
+            capabilities
+                .supported_crs
+                .contains(&"EPSG:3857".to_string())
+        );
         assert!(!capabilities.supports_parallel);
     }
 
@@ -639,10 +1673,11 @@ mod tests {
 
         // Create a grid
         let (grid_width, grid_height) = generator
-            .calculate_grid_dimensions(&config, &osm_data)
-            .unwrap();
+            .fit_grid(&config, &osm_data.bounding_box)
+            .unwrap()
+            .dimensions;
         let meters_per_tile =
-            generator.calculate_meters_per_tile(&config, &osm_data, (grid_width, grid_height));
+            generator.calculate_meters_per_tile(&osm_data.bounding_box, (grid_width, grid_height));
         let mut grid = TileGrid::new(
             grid_width,
             grid_height,
@@ -655,7 +1690,9 @@ mod tests {
 
         // Test rasterizing individual elements
         for element in &elements {
-            let tiles_updated = generator.rasterize_element(element, &mut grid).unwrap();
+            let tiles_updated = generator
+                .rasterize_element(element, element.to_tile_type(), &mut grid)
+                .unwrap();
             println!("Element {} updated {} tiles", element.id, tiles_updated);
 
             if !matches!(element.to_tile_type(), TileType::Empty) {
@@ -670,4 +1707,592 @@ mod tests {
         let stats = grid.statistics();
         assert!(stats.non_empty_tiles > 0);
     }
+
+    #[tokio::test]
+    async fn test_conflict_strategies() {
+        let osm_data = create_test_osm_data();
+        let config = OsmConfigBuilder::new().grid_resolution(100).build();
+
+        let priority = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(
+            priority.metadata.extra.get("conflict_strategy"),
+            Some(&"priority".to_string())
+        );
+
+        // The road crosses the building, so merging keeps both types on shared tiles
+        let merged = DefaultGridGenerator::new()
+            .with_conflict_strategy(ConflictStrategy::Merge)
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert!(merged.iter_tiles().any(|(_, _, tile)| {
+            tile.has_type(&TileType::Road) && tile.has_type(&TileType::Building)
+        }));
+        assert_eq!(
+            merged.count_tiles_by_type().get(&TileType::Empty),
+            priority.count_tiles_by_type().get(&TileType::Empty)
+        );
+        assert_eq!(priority.conflicts().count(), 0);
+
+        let audited = DefaultGridGenerator::new()
+            .with_conflict_audit()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        let (_, _, conflict) = audited.conflicts().next().unwrap();
+        assert_eq!(conflict.winner, TileType::Building);
+        assert_eq!(conflict.loser, TileType::Road);
+        assert!(conflict.winner_id.is_some() && conflict.loser_id.is_some());
+        assert_eq!(
+            audited.metadata.extra.get("tile_conflicts"),
+            Some(&audited.conflicts().count().to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_processing() {
+        let osm_data = create_test_osm_data();
+        let config = OsmConfigBuilder::new().grid_resolution(100).build();
+
+        let plain = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert!(!plain.metadata.extra.contains_key("post_processing"));
+
+        let processed = DefaultGridGenerator::new()
+            .with_post_processing(vec![
+                Box::new(CloseRoadGaps::default()),
+                Box::new(DilateTiles::water(1)),
+            ])
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(
+            processed.metadata.extra.get("post_processing"),
+            Some(&"close_road_gaps,dilate".to_string())
+        );
+        let manifest = processed.manifest().unwrap();
+        assert_eq!(manifest.post_processing, ["close_road_gaps", "dilate"]);
+        assert_eq!(manifest.config_hash, plain.manifest().unwrap().config_hash);
+        let empty = |grid: &TileGrid| grid.count_tiles_by_type().get(&TileType::Empty).copied();
+        assert!(empty(&processed) <= empty(&plain));
+    }
+
+    #[tokio::test]
+    async fn test_time_budget() {
+        let osm_data = create_test_osm_data();
+        let config = OsmConfigBuilder::new().grid_resolution(100).build();
+
+        let plain = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        let generous = DefaultGridGenerator::new()
+            .with_time_budget(Duration::from_secs(60))
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(
+            generous.metadata.tiles_populated,
+            plain.metadata.tiles_populated
+        );
+        assert_eq!(
+            generous.metadata.extra.get("budget_exhausted"),
+            Some(&"false".to_string())
+        );
+        assert!(!plain.metadata.extra.contains_key("time_budget_ms"));
+
+        // An exhausted budget still returns a (here empty) grid instead of failing
+        let exhausted = DefaultGridGenerator::new()
+            .with_time_budget(Duration::ZERO)
+            .with_post_processing(vec![Box::new(CloseRoadGaps::default())])
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(exhausted.metadata.tiles_populated, 0);
+        assert_eq!(
+            exhausted.metadata.extra.get("elements_skipped"),
+            Some(&exhausted.metadata.elements_processed.to_string())
+        );
+        assert_eq!(
+            exhausted.metadata.extra.get("post_processing_skipped"),
+            Some(&"1".to_string())
+        );
+        assert!(!exhausted.metadata.extra.contains_key("post_processing"));
+    }
+
+    #[test]
+    fn test_scanline_fill_matches_point_in_polygon() {
+        // A concave polygon with a hole, reaching past the western edge of the grid
+        let element = OsmElement {
+            id: 1,
+            element_type: crate::OsmElementType::Way,
+            tags: [("landuse".to_string(), "residential".to_string())].into(),
+            geometry: vec![
+                (52.491, 13.385),
+                (52.491, 13.409),
+                (52.509, 13.409),
+                (52.500, 13.401),
+                (52.509, 13.391),
+                (52.491, 13.385),
+            ],
+            holes: vec![vec![
+                (52.494, 13.400),
+                (52.494, 13.405),
+                (52.497, 13.405),
+                (52.494, 13.400),
+            ]],
+        };
+        let bbox = BoundingBox::new(52.49, 13.39, 52.51, 13.41);
+        let generator = DefaultGridGenerator::new();
+
+        for (crs, origin) in [
+            (Crs::Wgs84, GridOrigin::TopLeft),
+            (Crs::WebMercator, GridOrigin::BottomLeft),
+        ] {
+            let mut grid = TileGrid::new(37, 53, bbox.clone(), 10.0)
+                .with_crs(crs)
+                .with_origin(origin);
+            let coverage = PolygonCoverage::new(&grid, &element.geometry, &element.holes);
+            let tile = Tile::new(TileType::Residential);
+            generator
+                .fill_polygon(&element, tile, &coverage, &mut grid)
+                .unwrap();
+
+            for (x, y, tile) in grid.iter_tiles() {
+                let (lat, lon) = grid.grid_to_geo(x, y).unwrap();
+                let filled = tile.tile_type == TileType::Residential;
+                assert_eq!(filled, element.contains(lat, lon), "tile ({}, {})", x, y);
+            }
+            assert!(grid.get_tile(0, 26).unwrap().tile_type == TileType::Residential);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multipolygon_holes_stay_empty() {
+        let json_data = r#"{
+            "elements": [{
+                "type": "relation",
+                "id": 1,
+                "tags": {"type": "multipolygon", "natural": "water"},
+                "members": [
+                    {"type": "way", "ref": 1, "role": "outer", "geometry": [
+                        {"lat": 52.492, "lon": 13.392}, {"lat": 52.492, "lon": 13.408},
+                        {"lat": 52.508, "lon": 13.408}, {"lat": 52.508, "lon": 13.392},
+                        {"lat": 52.492, "lon": 13.392}
+                    ]},
+                    {"type": "way", "ref": 2, "role": "inner", "geometry": [
+                        {"lat": 52.497, "lon": 13.397}, {"lat": 52.497, "lon": 13.403},
+                        {"lat": 52.503, "lon": 13.403}, {"lat": 52.503, "lon": 13.397},
+                        {"lat": 52.497, "lon": 13.397}
+                    ]}
+                ]
+            }]
+        }"#;
+        let osm_data = OsmData {
+            raw_data: json_data.to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.49, 13.39, 52.51, 13.41),
+            metadata: OsmMetadata::new("test", "test"),
+        };
+        let config = OsmConfigBuilder::new().grid_resolution(50).build();
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+
+        let tile_at = |lat, lon| {
+            let (x, y) = grid.geo_to_grid(lat, lon).unwrap();
+            grid.get_tile(x, y).unwrap().tile_type.clone()
+        };
+        assert_eq!(tile_at(52.494, 13.4), TileType::Water);
+        // The island in the middle of the lake is not flooded
+        assert_eq!(tile_at(52.5, 13.4), TileType::Empty);
+    }
+
+    #[tokio::test]
+    async fn test_square_cells() {
+        let osm_data = OsmData {
+            raw_data: r#"{"elements": []}"#.to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.0, 13.0, 52.25, 13.25),
+            metadata: OsmMetadata::new("test", "test"),
+        };
+        let config = OsmConfigBuilder::new().grid_resolution(400).build();
+
+        // The same number of cells per degree makes tiles at 52°N about 1.6 times taller than wide
+        let plain = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(plain.dimensions(), (100, 100));
+        assert!((plain.meters_per_tile_x() - 171.0).abs() < 2.0);
+        assert!((plain.meters_per_tile_y() - 278.0).abs() < 2.0);
+        assert!((plain.tile_aspect_ratio() - 0.615).abs() < 0.01);
+
+        let square = DefaultGridGenerator::new()
+            .with_square_cells(true)
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(square.dimensions().0, 100);
+        assert_eq!(square.dimensions().1, 163);
+        assert!((square.tile_aspect_ratio() - 1.0).abs() < 0.01);
+        assert!((square.meters_per_tile - square.meters_per_tile_x()).abs() < 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_tracing_spans() {
+        use std::sync::Mutex;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        /// Layer collecting the names of created spans
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanNames {
+            fn on_new_span(
+                &self,
+                attrs: &tracing::span::Attributes<'_>,
+                _id: &tracing::span::Id,
+                _ctx: Context<'_, S>,
+            ) {
+                self.0.lock().unwrap().push(attrs.metadata().name());
+            }
+        }
+
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(SpanNames(names.clone()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let config = OsmConfig::default();
+        let osm_data = MockProvider::new().fetch_data(&config).await.unwrap();
+        DefaultGridGenerator::new()
+            .with_time_budget(Duration::from_secs(60))
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+
+        let names = names.lock().unwrap();
+        for span in ["generate_grid", "parse", "rasterize"] {
+            assert!(names.contains(&span), "missing span {}: {:?}", span, names);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_sink() {
+        let sink = Arc::new(InMemoryMetricsSink::new());
+        let generator = DefaultGridGenerator::new().with_metrics_sink(sink.clone());
+        let osm_data = MockProvider::new()
+            .fetch_data(&OsmConfig::default())
+            .await
+            .unwrap();
+        let grid = generator
+            .generate_grid(&osm_data, &OsmConfig::default())
+            .await
+            .unwrap();
+
+        assert_eq!(sink.counter(ELEMENTS_PARSED_TOTAL), 4);
+        assert_eq!(
+            sink.counter(TILES_RASTERIZED_TOTAL),
+            grid.metadata.tiles_populated as u64
+        );
+        assert_eq!(sink.histogram(GRID_GENERATION_DURATION_SECONDS).0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_data_is_flagged() {
+        let timestamp = "2020-01-01T00:00:00Z".parse().unwrap();
+        let osm_data = OsmData {
+            raw_data: r#"{"elements": []}"#.to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.0, 13.0, 52.01, 13.01),
+            metadata: OsmMetadata::new("test", "test").with_data_timestamp(timestamp),
+        };
+        let generator = DefaultGridGenerator::new();
+
+        let grid = generator
+            .generate_grid(&osm_data, &OsmConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(grid.metadata.data_timestamp, Some(timestamp));
+        assert!(!grid.metadata.extra.contains_key("stale_data"));
+
+        let config = OsmConfig::default().with_max_data_age(3600);
+        let grid = generator.generate_grid(&osm_data, &config).await.unwrap();
+        assert_eq!(
+            grid.metadata.extra.get("stale_data"),
+            Some(&"true".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cell_size_meters() {
+        let osm_data = OsmData {
+            raw_data: r#"{"elements": []}"#.to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.0, 13.0, 52.01, 13.01),
+            metadata: OsmMetadata::new("test", "test"),
+        };
+        let config = OsmConfigBuilder::new().cell_size_meters(5.0).build();
+
+        // 0.01° is about 685 m of longitude and 1112 m of latitude at 52°N
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(grid.dimensions(), (137, 223));
+        assert!((grid.meters_per_tile_x() - 5.0).abs() < 0.05);
+        assert!((grid.meters_per_tile_y() - 5.0).abs() < 0.05);
+        assert_eq!(
+            grid.metadata.extra.get("target_cell_size_meters"),
+            Some(&"5".to_string())
+        );
+        assert_eq!(
+            grid.metadata.extra.get("grid_resolution"),
+            Some(&"13690".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_coastline_fill() {
+        // Land to the north of a coastline running west to east, with a road on the sea side
+        let json_data = r#"{
+            "elements": [
+                {
+                    "type": "way",
+                    "id": 1,
+                    "tags": {"natural": "coastline"},
+                    "geometry": [
+                        {"lat": 52.5, "lon": 13.38},
+                        {"lat": 52.5, "lon": 13.42}
+                    ]
+                },
+                {
+                    "type": "way",
+                    "id": 2,
+                    "tags": {"highway": "residential"},
+                    "geometry": [
+                        {"lat": 52.495, "lon": 13.395},
+                        {"lat": 52.495, "lon": 13.405}
+                    ]
+                }
+            ]
+        }"#;
+        let osm_data = OsmData {
+            raw_data: json_data.to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.49, 13.39, 52.51, 13.41),
+            metadata: OsmMetadata::new("test", "test"),
+        };
+        let config = OsmConfigBuilder::new().grid_resolution(50).build();
+
+        let plain = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert!(!plain.count_tiles_by_type().contains_key(&TileType::Water));
+        assert!(!plain.metadata.extra.contains_key("sea_tiles"));
+
+        let grid = DefaultGridGenerator::new()
+            .with_coastline_fill(true)
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        let (north, sea) = (
+            grid.geo_to_grid(52.508, 13.4).unwrap(),
+            grid.geo_to_grid(52.492, 13.392).unwrap(),
+        );
+        assert_eq!(
+            grid.get_tile(north.0, north.1).unwrap().tile_type,
+            TileType::Empty
+        );
+        assert_eq!(
+            grid.get_tile(sea.0, sea.1).unwrap().tile_type,
+            TileType::Water
+        );
+        // Features are rasterized on top of the sea
+        let road = grid.geo_to_grid(52.495, 13.4).unwrap();
+        assert_eq!(
+            grid.get_tile(road.0, road.1).unwrap().tile_type,
+            TileType::Road
+        );
+        assert!(grid.metadata.extra.contains_key("sea_tiles"));
+    }
+
+    #[test]
+    fn test_budget_tiers() {
+        assert!(budget_tier(&TileType::Road) < budget_tier(&TileType::Amenity));
+        assert!(budget_tier(&TileType::Building) < budget_tier(&TileType::GreenSpace));
+        assert!(budget_tier(&TileType::Tourism) < budget_tier(&TileType::Residential));
+    }
+
+    #[tokio::test]
+    async fn test_layer_aware_rasterization() {
+        let json_data = r#"{
+            "elements": [
+                {
+                    "type": "way",
+                    "id": 1,
+                    "tags": {"leisure": "park"},
+                    "geometry": [
+                        {"lat": 52.492, "lon": 13.392},
+                        {"lat": 52.492, "lon": 13.398},
+                        {"lat": 52.498, "lon": 13.398},
+                        {"lat": 52.498, "lon": 13.392},
+                        {"lat": 52.492, "lon": 13.392}
+                    ]
+                },
+                {
+                    "type": "way",
+                    "id": 2,
+                    "tags": {"highway": "primary", "tunnel": "yes"},
+                    "geometry": [
+                        {"lat": 52.495, "lon": 13.391},
+                        {"lat": 52.495, "lon": 13.399}
+                    ]
+                },
+                {
+                    "type": "way",
+                    "id": 3,
+                    "tags": {"highway": "primary", "bridge": "yes"},
+                    "geometry": [
+                        {"lat": 52.505, "lon": 13.401},
+                        {"lat": 52.505, "lon": 13.409}
+                    ]
+                },
+                {
+                    "type": "way",
+                    "id": 4,
+                    "tags": {"natural": "water"},
+                    "geometry": [
+                        {"lat": 52.502, "lon": 13.402},
+                        {"lat": 52.502, "lon": 13.408},
+                        {"lat": 52.508, "lon": 13.408},
+                        {"lat": 52.508, "lon": 13.402},
+                        {"lat": 52.502, "lon": 13.402}
+                    ]
+                }
+            ]
+        }"#;
+        let osm_data = OsmData {
+            raw_data: json_data.to_string(),
+            ..create_test_osm_data()
+        };
+        let config = OsmConfigBuilder::new()
+            .urban_features()
+            .grid_resolution(1000)
+            .build();
+
+        let generate = |mode| {
+            let generator = DefaultGridGenerator::new().with_layer_mode(mode);
+            let osm_data = osm_data.clone();
+            let config = config.clone();
+            async move { generator.generate_grid(&osm_data, &config).await.unwrap() }
+        };
+
+        // The tunnel stays hidden below the park, the bridge stays above the water
+        let surface = generate(LayerMode::Surface).await;
+        let (x, y) = surface.geo_to_grid(52.495, 13.395).unwrap();
+        assert_eq!(
+            surface.get_tile(x, y).unwrap().tile_type,
+            TileType::GreenSpace
+        );
+        let (x, y) = surface.geo_to_grid(52.505, 13.405).unwrap();
+        let bridge = surface.get_tile(x, y).unwrap();
+        assert_eq!(bridge.tile_type, TileType::Road);
+        assert!(bridge.is_bridge());
+
+        let stacked = generate(LayerMode::Stack).await;
+        let bridge = stacked.get_tile(x, y).unwrap();
+        assert_eq!(bridge.underlying_types(), &[TileType::Water]);
+        assert_eq!(
+            stacked.metadata.extra.get("layer_mode"),
+            Some(&"stack".to_string())
+        );
+
+        let ignored = generate(LayerMode::Ignore).await;
+        let (x, y) = ignored.geo_to_grid(52.495, 13.395).unwrap();
+        assert_eq!(ignored.get_tile(x, y).unwrap().tile_type, TileType::Road);
+    }
+
+    #[tokio::test]
+    async fn test_unclassified_tags_reported() {
+        let json_data = r#"{"elements": [
+            {"type": "node", "id": 1, "lat": 52.5, "lon": 13.4,
+             "tags": {"man_made": "mast", "name": "Mast 1"}},
+            {"type": "node", "id": 2, "lat": 52.501, "lon": 13.401,
+             "tags": {"man_made": "mast"}},
+            {"type": "node", "id": 3, "lat": 52.502, "lon": 13.402, "tags": {"amenity": "cafe"}},
+            {"type": "node", "id": 4, "lat": 52.503, "lon": 13.403}
+        ]}"#;
+        let osm_data = OsmData {
+            raw_data: json_data.to_string(),
+            ..create_test_osm_data()
+        };
+        let config = OsmConfigBuilder::new().grid_resolution(100).build();
+
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        let statistics = grid.statistics();
+        assert_eq!(statistics.unclassified_elements, 2);
+        assert_eq!(
+            statistics.top_unclassified,
+            vec![("man_made=mast".to_string(), 2)]
+        );
+        assert_eq!(
+            grid.metadata.extra.get("unclassified_elements"),
+            Some(&"2".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_excluded_elements_are_skipped() {
+        let osm_data = create_test_osm_data();
+        let config = OsmConfigBuilder::new()
+            .grid_resolution(2000)
+            .features(FeatureSet::urban().excluding("building", Some("yes")))
+            .build();
+
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert!(grid.tiles_of_type(&TileType::Building).is_empty());
+        assert!(!grid.tiles_of_type(&TileType::Road).is_empty());
+        assert!(!grid.tiles_of_type(&TileType::Water).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_query_tile_type_override() {
+        let json_data = r#"{"elements": [
+            {"type": "node", "id": 1, "lat": 52.5, "lon": 13.4, "tags": {"amenity": "cafe"}},
+            {"type": "node", "id": 2, "lat": 52.501, "lon": 13.401, "tags": {"amenity": "bank"}}
+        ]}"#;
+        let osm_data = OsmData {
+            raw_data: json_data.to_string(),
+            ..create_test_osm_data()
+        };
+        let cafe = TileType::Custom("cafe".to_string());
+        let config = OsmConfigBuilder::new()
+            .grid_resolution(2000)
+            .features(FeatureSet::urban().with_custom_query_as(
+                "amenity",
+                Some("cafe"),
+                cafe.clone(),
+            ))
+            .build();
+
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&osm_data, &config)
+            .await
+            .unwrap();
+        assert_eq!(grid.tiles_of_type(&cafe).len(), 1);
+        assert_eq!(grid.tiles_of_type(&TileType::Amenity).len(), 1);
+    }
 }
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use super::{TileGrid, TileType};
+
+/// Side length in tiles of the blocks nearest-tile searches skip over
+const BLOCK_SIZE: usize = 16;
+
+/// Positions of one tile type as a bitset, with per-block counts
+#[derive(Debug, Clone)]
+struct TypeBits {
+    /// One bit per tile, each row padded to whole words
+    bits: Vec<u64>,
+    /// Number of tiles of the type in each block, row-major
+    blocks: Vec<u32>,
+    /// Total number of tiles of the type
+    count: usize,
+}
+
+/// Per-type index over a tile grid for fast queries, see [`TileGrid::build_index`]
+///
+/// Position lookups are answered from bitsets instead of scanning the grid,
+/// and nearest-tile searches skip blocks of 16×16 tiles without a match.
+/// The index is a snapshot: rebuild it after changing the grid.
+#[derive(Debug, Clone)]
+pub struct TileIndex {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    blocks_x: usize,
+    blocks_y: usize,
+    types: HashMap<TileType, TypeBits>,
+}
+
+impl TileGrid {
+    /// Build a [`TileIndex`] of the tile positions of every type in the grid
+    pub fn build_index(&self) -> TileIndex {
+        let (width, height) = self.dimensions();
+        let words_per_row = width.div_ceil(64);
+        let blocks_x = width.div_ceil(BLOCK_SIZE);
+        let blocks_y = height.div_ceil(BLOCK_SIZE);
+
+        let mut types: HashMap<TileType, TypeBits> = HashMap::new();
+        for (x, y, tile) in self.iter_tiles() {
+            // Look up by reference so tile types are only cloned once per distinct type
+            let bits = match types.get_mut(&tile.tile_type) {
+                Some(bits) => bits,
+                None => types
+                    .entry(tile.tile_type.clone())
+                    .or_insert_with(|| TypeBits {
+                        bits: vec![0; words_per_row * height],
+                        blocks: vec![0; blocks_x * blocks_y],
+                        count: 0,
+                    }),
+            };
+            bits.bits[y * words_per_row + x / 64] |= 1 << (x % 64);
+            bits.blocks[(y / BLOCK_SIZE) * blocks_x + x / BLOCK_SIZE] += 1;
+            bits.count += 1;
+        }
+
+        TileIndex {
+            width,
+            height,
+            words_per_row,
+            blocks_x,
+            blocks_y,
+            types,
+        }
+    }
+}
+
+impl TileIndex {
+    /// Get the dimensions (width, height) of the indexed grid
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Count the tiles of a type
+    pub fn count(&self, tile_type: &TileType) -> usize {
+        self.types.get(tile_type).map_or(0, |bits| bits.count)
+    }
+
+    /// Check if the tile at the given grid coordinates has a type
+    pub fn contains(&self, tile_type: &TileType, x: usize, y: usize) -> bool {
+        x < self.width
+            && y < self.height
+            && self
+                .types
+                .get(tile_type)
+                .is_some_and(|bits| self.is_set(bits, x, y))
+    }
+
+    /// Get the positions (x, y) of all tiles of a type in row-major order
+    pub fn tiles_of_type(&self, tile_type: &TileType) -> Vec<(usize, usize)> {
+        self.in_region(tile_type, 0, 0, self.width, self.height)
+    }
+
+    /// Get the positions of the tiles of a type within a rectangle, clipped to the grid
+    pub fn in_region(
+        &self,
+        tile_type: &TileType,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut positions = Vec::new();
+        self.scan_region(tile_type, x, y, width, height, |position| {
+            positions.push(position)
+        });
+        positions
+    }
+
+    /// Count the tiles of a type within a rectangle, clipped to the grid
+    pub fn count_in_region(
+        &self,
+        tile_type: &TileType,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> usize {
+        let mut count = 0;
+        self.scan_region(tile_type, x, y, width, height, |_| count += 1);
+        count
+    }
+
+    /// Find the tile of a type closest to (x, y), measured between tile centers
+    ///
+    /// Ties are broken by row-major order. The query position may lie
+    /// outside the grid.
+    pub fn nearest(&self, tile_type: &TileType, x: usize, y: usize) -> Option<(usize, usize)> {
+        let bits = self.types.get(tile_type)?;
+        if bits.count == 0 {
+            return None;
+        }
+
+        let distance = |position: (usize, usize)| {
+            let (dx, dy) = (position.0.abs_diff(x), position.1.abs_diff(y));
+            dx * dx + dy * dy
+        };
+        let origin_block = (
+            (x / BLOCK_SIZE).min(self.blocks_x - 1),
+            (y / BLOCK_SIZE).min(self.blocks_y - 1),
+        );
+        let mut best: Option<((usize, usize), usize)> = None;
+
+        // Visit rings of blocks around the query until no ring can hold anything closer
+        for ring in 0..self.blocks_x.max(self.blocks_y) {
+            if let Some((_, best_distance)) = best {
+                let min_distance = ring.saturating_sub(1) * BLOCK_SIZE;
+                if min_distance * min_distance > best_distance {
+                    break;
+                }
+            }
+            for (block_x, block_y) in ring_blocks(origin_block, ring, self.blocks_x, self.blocks_y)
+            {
+                if bits.blocks[block_y * self.blocks_x + block_x] == 0 {
+                    continue;
+                }
+                let (left, top) = (block_x * BLOCK_SIZE, block_y * BLOCK_SIZE);
+                self.scan_bits(bits, left, top, BLOCK_SIZE, BLOCK_SIZE, |position| {
+                    let candidate = distance(position);
+                    let closer = best.is_none_or(|(found, best_distance)| {
+                        candidate < best_distance
+                            || (candidate == best_distance
+                                && (position.1, position.0) < (found.1, found.0))
+                    });
+                    if closer {
+                        best = Some((position, candidate));
+                    }
+                });
+            }
+        }
+
+        best.map(|(position, _)| position)
+    }
+
+    fn is_set(&self, bits: &TypeBits, x: usize, y: usize) -> bool {
+        bits.bits[y * self.words_per_row + x / 64] & (1 << (x % 64)) != 0
+    }
+
+    fn scan_region(
+        &self,
+        tile_type: &TileType,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        visit: impl FnMut((usize, usize)),
+    ) {
+        if let Some(bits) = self.types.get(tile_type) {
+            self.scan_bits(bits, x, y, width, height, visit);
+        }
+    }
+
+    /// Visit the set bits within a rectangle, a word at a time
+    fn scan_bits(
+        &self,
+        bits: &TypeBits,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        mut visit: impl FnMut((usize, usize)),
+    ) {
+        let (right, bottom) = (
+            x.saturating_add(width).min(self.width),
+            y.saturating_add(height).min(self.height),
+        );
+        if x >= right || y >= bottom {
+            return;
+        }
+
+        for row in y..bottom {
+            let row_start = row * self.words_per_row;
+            let words = &bits.bits[row_start + x / 64..row_start + right.div_ceil(64)];
+            for (offset, &word) in words.iter().enumerate() {
+                let start = (x / 64 + offset) * 64;
+                let mut word = word;
+                // Mask off columns outside the rectangle
+                if start < x {
+                    word &= !0 << (x - start);
+                }
+                if right < start + 64 {
+                    word &= (1 << (right - start)) - 1;
+                }
+                while word != 0 {
+                    let column = start + word.trailing_zeros() as usize;
+                    visit((column, row));
+                    word &= word - 1;
+                }
+            }
+        }
+    }
+}
+
+/// Blocks at Chebyshev distance `ring` from `center`, clipped to the block grid
+fn ring_blocks(
+    center: (usize, usize),
+    ring: usize,
+    blocks_x: usize,
+    blocks_y: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    let (left, top) = (center.0.saturating_sub(ring), center.1.saturating_sub(ring));
+    let (right, bottom) = (
+        (center.0 + ring).min(blocks_x - 1),
+        (center.1 + ring).min(blocks_y - 1),
+    );
+    (top..=bottom).flat_map(move |y| {
+        (left..=right).filter_map(move |x| {
+            (x.abs_diff(center.0).max(y.abs_diff(center.1)) == ring).then_some((x, y))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile};
+
+    fn grid(width: usize, height: usize) -> TileGrid {
+        TileGrid::new(
+            width,
+            height,
+            BoundingBox::new(52.0, 13.0, 52.1, 13.1),
+            10.0,
+        )
+    }
+
+    #[test]
+    fn test_index_queries() {
+        let mut grid = grid(100, 40);
+        for (x, y) in [(3, 2), (70, 2), (99, 39), (64, 10)] {
+            grid.set_tile(x, y, Tile::new(TileType::Road)).unwrap();
+        }
+        let index = grid.build_index();
+
+        assert_eq!(index.count(&TileType::Road), 4);
+        assert_eq!(index.count(&TileType::Empty), 3996);
+        assert_eq!(index.count(&TileType::Water), 0);
+        assert!(index.contains(&TileType::Road, 64, 10));
+        assert!(!index.contains(&TileType::Road, 63, 10));
+        assert!(!index.contains(&TileType::Road, 100, 2));
+
+        let roads: Vec<(usize, usize)> = grid
+            .tiles_of_type(&TileType::Road)
+            .into_iter()
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(index.tiles_of_type(&TileType::Road), roads);
+
+        // The region spans a word boundary at column 64
+        assert_eq!(
+            index.in_region(&TileType::Road, 60, 0, 20, 20),
+            vec![(70, 2), (64, 10)]
+        );
+        assert_eq!(index.count_in_region(&TileType::Road, 90, 30, 50, 50), 1);
+        assert_eq!(index.count_in_region(&TileType::Road, 200, 0, 5, 5), 0);
+    }
+
+    #[test]
+    fn test_nearest_matches_linear_scan() {
+        let mut grid = grid(90, 70);
+        for step in 0..40 {
+            let (x, y) = ((step * 37) % 90, (step * 53 + step / 3) % 70);
+            grid.set_tile(x, y, Tile::new(TileType::Water)).unwrap();
+        }
+        let index = grid.build_index();
+        let water = grid.tiles_of_type(&TileType::Water);
+
+        for (x, y) in [(0, 0), (45, 35), (89, 69), (10, 60), (120, 5)] {
+            let expected = water
+                .iter()
+                .map(|&(wx, wy, _)| (wx.abs_diff(x).pow(2) + wy.abs_diff(y).pow(2), (wx, wy)))
+                .min_by_key(|&(distance, (wx, wy))| (distance, wy, wx))
+                .map(|(_, position)| position);
+            assert_eq!(index.nearest(&TileType::Water, x, y), expected);
+        }
+        assert_eq!(index.nearest(&TileType::Building, 5, 5), None);
+    }
+}
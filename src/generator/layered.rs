@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Tile, TileGrid, TileType};
+
+/// Thematic layer of a [`LayeredTileGrid`]
+///
+/// Layers are ordered bottom to top, which is the default composite order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GridLayer {
+    /// Land use and green spaces
+    Terrain,
+    /// Rivers, lakes and the sea
+    Water,
+    /// Roads and railways
+    Transport,
+    /// Buildings
+    Buildings,
+    /// Amenities and tourism features
+    Pois,
+}
+
+impl GridLayer {
+    /// All layers, bottom to top
+    pub const ALL: [GridLayer; 5] = [
+        Self::Terrain,
+        Self::Water,
+        Self::Transport,
+        Self::Buildings,
+        Self::Pois,
+    ];
+
+    /// Get the layer identifier (e.g., "transport")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Terrain => "terrain",
+            Self::Water => "water",
+            Self::Transport => "transport",
+            Self::Buildings => "buildings",
+            Self::Pois => "pois",
+        }
+    }
+
+    /// Get the layer a tile type is rasterized into
+    ///
+    /// Custom and unclassified types count as terrain.
+    pub fn for_tile_type(tile_type: &TileType) -> Self {
+        match tile_type {
+            TileType::Water => Self::Water,
+            TileType::Road | TileType::Railway => Self::Transport,
+            TileType::Building => Self::Buildings,
+            TileType::Amenity | TileType::Tourism => Self::Pois,
+            TileType::Empty
+            | TileType::GreenSpace
+            | TileType::Parking
+            | TileType::Industrial
+            | TileType::Residential
+            | TileType::Commercial
+            | TileType::Custom(_) => Self::Terrain,
+        }
+    }
+}
+
+/// A set of [`TileGrid`]s of the same area, one per [`GridLayer`]
+///
+/// Generated by [`DefaultGridGenerator::generate_layered_grid_sync`](super::DefaultGridGenerator::generate_layered_grid_sync).
+#[derive(Debug, Clone, Default)]
+pub struct LayeredTileGrid {
+    layers: BTreeMap<GridLayer, TileGrid>,
+}
+
+impl LayeredTileGrid {
+    /// Create an empty layered grid
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a layer, returning the previous grid of that layer
+    pub fn insert(&mut self, layer: GridLayer, grid: TileGrid) -> Option<TileGrid> {
+        self.layers.insert(layer, grid)
+    }
+
+    /// Get the grid of a layer
+    pub fn layer(&self, layer: GridLayer) -> Option<&TileGrid> {
+        self.layers.get(&layer)
+    }
+
+    /// Get the grid of a layer mutably
+    pub fn layer_mut(&mut self, layer: GridLayer) -> Option<&mut TileGrid> {
+        self.layers.get_mut(&layer)
+    }
+
+    /// Iterate over the layers, bottom to top
+    pub fn layers(&self) -> impl Iterator<Item = (GridLayer, &TileGrid)> {
+        self.layers.iter().map(|(layer, grid)| (*layer, grid))
+    }
+
+    /// Get the dimensions (width, height) shared by all layers
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        self.layers.values().next().map(TileGrid::dimensions)
+    }
+
+    /// Get the non-empty tiles of all layers at a cell, bottom to top
+    pub fn tiles_at(&self, x: usize, y: usize) -> Vec<(GridLayer, &Tile)> {
+        self.layers()
+            .filter_map(|(layer, grid)| Some((layer, grid.get_tile(x, y)?)))
+            .filter(|(_, tile)| tile.tile_type != TileType::Empty)
+            .collect()
+    }
+
+    /// Flatten the layers into one grid, higher layers covering lower ones
+    pub fn composite(&self) -> Option<TileGrid> {
+        self.composite_with_order(&GridLayer::ALL)
+    }
+
+    /// Flatten the given layers into one grid, `order` listed bottom to top
+    ///
+    /// Missing layers are skipped; returns `None` if none of them exists.
+    pub fn composite_with_order(&self, order: &[GridLayer]) -> Option<TileGrid> {
+        let mut layers = order
+            .iter()
+            .filter_map(|layer| Some((*layer, self.layer(*layer)?)));
+        let (bottom, base) = layers.next()?;
+        let mut composite = base.clone();
+        let mut names = vec![bottom.name()];
+        for (layer, grid) in layers {
+            names.push(layer.name());
+            for (x, y, tile) in grid.iter_tiles() {
+                if tile.tile_type != TileType::Empty
+                    && let Some(target) = composite.get_tile_mut(x, y)
+                {
+                    *target = tile.clone();
+                }
+            }
+            for poi in grid.pois.iter() {
+                composite.pois.insert(poi.clone());
+            }
+            composite.metadata.tiles_populated += grid.metadata.tiles_populated;
+            composite.metadata.generation_time_ms += grid.metadata.generation_time_ms;
+        }
+
+        let extra = &mut composite.metadata.extra;
+        extra.remove("grid_layer");
+        extra.insert("composite_layers".to_string(), names.join(","));
+        Some(composite)
+    }
+}
+
+impl FromIterator<(GridLayer, TileGrid)> for LayeredTileGrid {
+    fn from_iter<I: IntoIterator<Item = (GridLayer, TileGrid)>>(iter: I) -> Self {
+        Self {
+            layers: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BoundingBox, DefaultGridGenerator, OsmConfigBuilder, OsmData, OsmDataFormat, OsmMetadata,
+    };
+    use serde_json::json;
+
+    /// A park with a road running through it and a cafe beside the road
+    fn park_data() -> OsmData {
+        let elements = json!([
+            {"type": "way", "id": 1, "tags": {"leisure": "park"}, "geometry": [
+                {"lat": 52.501, "lon": 13.401},
+                {"lat": 52.501, "lon": 13.409},
+                {"lat": 52.509, "lon": 13.409},
+                {"lat": 52.509, "lon": 13.401},
+                {"lat": 52.501, "lon": 13.401},
+            ]},
+            {"type": "way", "id": 2, "tags": {"highway": "residential"}, "geometry": [
+                {"lat": 52.5052, "lon": 13.4},
+                {"lat": 52.5052, "lon": 13.41},
+            ]},
+            {"type": "node", "id": 3, "lat": 52.5072, "lon": 13.4052, "tags": {"amenity": "cafe"}},
+        ]);
+        OsmData {
+            raw_data: json!({"elements": elements}).to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.5, 13.4, 52.51, 13.41),
+            metadata: OsmMetadata::new("test", "test"),
+        }
+    }
+
+    #[test]
+    fn test_layer_for_tile_type() {
+        assert_eq!(
+            GridLayer::for_tile_type(&TileType::Road),
+            GridLayer::Transport
+        );
+        assert_eq!(
+            GridLayer::for_tile_type(&TileType::Parking),
+            GridLayer::Terrain
+        );
+        assert_eq!(
+            GridLayer::for_tile_type(&TileType::Tourism),
+            GridLayer::Pois
+        );
+        let custom = TileType::Custom("quarry".to_string());
+        assert_eq!(GridLayer::for_tile_type(&custom), GridLayer::Terrain);
+        assert!(GridLayer::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_layered_generation() {
+        let data = park_data();
+        let config = OsmConfigBuilder::new().grid_resolution(10_000).build();
+        let generator = DefaultGridGenerator::new();
+        let layered = generator
+            .generate_layered_grid_sync(&data, &config)
+            .unwrap();
+        assert_eq!(layered.layers().count(), GridLayer::ALL.len());
+
+        let transport = layered.layer(GridLayer::Transport).unwrap();
+        let (x, y, _) = transport.tiles_of_type(&TileType::Road)[50];
+        // The park is kept beneath the road
+        let types: Vec<_> = layered
+            .tiles_at(x, y)
+            .into_iter()
+            .map(|(layer, tile)| (layer, tile.tile_type.clone()))
+            .collect();
+        assert_eq!(
+            types,
+            [
+                (GridLayer::Terrain, TileType::GreenSpace),
+                (GridLayer::Transport, TileType::Road)
+            ]
+        );
+        assert_eq!(layered.layer(GridLayer::Pois).unwrap().pois.len(), 1);
+        assert!(layered.layer(GridLayer::Terrain).unwrap().pois.is_empty());
+
+        let composite = layered.composite().unwrap();
+        let flat = generator.generate_grid_sync(&data, &config).unwrap();
+        for tile_type in [TileType::GreenSpace, TileType::Road, TileType::Amenity] {
+            let composite_tiles = composite.tiles_of_type(&tile_type).len();
+            assert_eq!(composite_tiles, flat.tiles_of_type(&tile_type).len());
+        }
+        assert_eq!(composite.pois.len(), 1);
+        assert_eq!(
+            composite.metadata.extra["composite_layers"],
+            "terrain,water,transport,buildings,pois"
+        );
+
+        // Terrain on top hides the road
+        let reordered = layered
+            .composite_with_order(&[GridLayer::Transport, GridLayer::Terrain])
+            .unwrap();
+        assert_eq!(
+            reordered.get_tile(x, y).unwrap().tile_type,
+            TileType::GreenSpace
+        );
+    }
+}
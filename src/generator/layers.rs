@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+use super::{ConflictStrategy, Tile, TileGrid, TileType};
+
+/// How the generator treats the OSM `layer`, `bridge` and `tunnel` tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum LayerMode {
+    /// Ignore layers, so elements compete through the conflict strategy alone
+    Ignore,
+    /// The element on the higher layer covers the tile (bridges over rivers, parks over tunnels)
+    #[default]
+    Surface,
+    /// Like `Surface`, and covered tile types are kept in `TileMetadata::underlying_types`
+    ///
+    /// Covering tiles get their own copy of the element metadata, so this uses more memory.
+    Stack,
+}
+
+impl LayerMode {
+    /// Get the mode identifier (e.g., "surface")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ignore => "ignore",
+            Self::Surface => "surface",
+            Self::Stack => "stack",
+        }
+    }
+}
+
+impl Tile {
+    /// Get the vertical layer of this tile (0 without metadata)
+    pub fn layer(&self) -> i8 {
+        self.metadata.as_ref().map_or(0, |metadata| metadata.layer)
+    }
+
+    /// Check whether this tile belongs to a bridge
+    pub fn is_bridge(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.bridge)
+    }
+
+    /// Check whether this tile belongs to a tunnel
+    pub fn is_tunnel(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.tunnel)
+    }
+
+    /// Get the tile types covered by this tile on lower layers
+    pub fn underlying_types(&self) -> &[TileType] {
+        self.metadata
+            .as_ref()
+            .map_or(&[], |metadata| metadata.underlying_types.as_slice())
+    }
+
+    /// Record a tile type as lying beneath this tile, returning whether it was new
+    fn push_underlying(&mut self, tile_type: &TileType) -> bool {
+        if *tile_type == TileType::Empty
+            || *tile_type == self.tile_type
+            || self.underlying_types().contains(tile_type)
+        {
+            return false;
+        }
+        self.metadata_mut().underlying_types.push(tile_type.clone());
+        true
+    }
+}
+
+impl TileGrid {
+    /// Set a tile, letting higher layers cover lower ones before applying `strategy`
+    ///
+    /// Tiles on the same layer are resolved by `strategy`. Returns whether
+    /// the tile at the position changed.
+    pub fn set_tile_layered(
+        &mut self,
+        x: usize,
+        y: usize,
+        mut tile: Tile,
+        strategy: &ConflictStrategy,
+        mode: LayerMode,
+    ) -> Result<bool, String> {
+        let existing_layer = match self.get_tile(x, y) {
+            Some(existing) if existing.tile_type != TileType::Empty => existing.layer(),
+            Some(_) => return self.set_tile_with_strategy(x, y, tile, strategy),
+            None => return Err(self.out_of_bounds(x, y)),
+        };
+        if mode == LayerMode::Ignore || existing_layer == tile.layer() {
+            return self.set_tile_with_strategy(x, y, tile, strategy);
+        }
+
+        let existing = self
+            .get_tile_mut(x, y)
+            .expect("tile position was checked above");
+        if tile.layer() > existing_layer {
+            if mode == LayerMode::Stack {
+                let covered = existing.clone();
+                tile.push_underlying(&covered.tile_type);
+                for tile_type in covered.underlying_types() {
+                    tile.push_underlying(tile_type);
+                }
+            }
+            *existing = tile;
+            Ok(true)
+        } else if mode == LayerMode::Stack {
+            Ok(existing.push_underlying(&tile.tile_type))
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileMetadata};
+
+    fn tile(tile_type: TileType, layer: i8) -> Tile {
+        Tile::with_metadata(
+            tile_type,
+            TileMetadata {
+                layer,
+                bridge: layer > 0,
+                tunnel: layer < 0,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn place(mode: LayerMode, tiles: Vec<Tile>) -> Tile {
+        let mut grid = TileGrid::new(4, 4, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        for tile in tiles {
+            grid.set_tile_layered(1, 1, tile, &ConflictStrategy::Priority, mode)
+                .unwrap();
+        }
+        grid.get_tile(1, 1).unwrap().clone()
+    }
+
+    #[test]
+    fn test_bridge_keeps_water_beneath() {
+        // Water rasterized after the bridge does not replace it
+        let surface = place(
+            LayerMode::Surface,
+            vec![tile(TileType::Road, 1), tile(TileType::Water, 0)],
+        );
+        assert_eq!(surface.tile_type, TileType::Road);
+        assert!(surface.is_bridge());
+        assert!(surface.underlying_types().is_empty());
+
+        let stacked = place(
+            LayerMode::Stack,
+            vec![tile(TileType::Water, 0), tile(TileType::Road, 1)],
+        );
+        assert_eq!(stacked.tile_type, TileType::Road);
+        assert_eq!(stacked.underlying_types(), &[TileType::Water]);
+
+        let stacked = place(
+            LayerMode::Stack,
+            vec![tile(TileType::Road, 1), tile(TileType::Water, 0)],
+        );
+        assert_eq!(stacked.underlying_types(), &[TileType::Water]);
+    }
+
+    #[test]
+    fn test_tunnel_stays_below_surface() {
+        let surface = place(
+            LayerMode::Surface,
+            vec![tile(TileType::GreenSpace, 0), tile(TileType::Railway, -1)],
+        );
+        assert_eq!(surface.tile_type, TileType::GreenSpace);
+
+        let ignored = place(
+            LayerMode::Ignore,
+            vec![tile(TileType::GreenSpace, 0), tile(TileType::Railway, -1)],
+        );
+        assert_eq!(ignored.tile_type, TileType::Railway);
+
+        let stacked = place(
+            LayerMode::Stack,
+            vec![tile(TileType::Railway, -1), tile(TileType::GreenSpace, 0)],
+        );
+        assert_eq!(stacked.tile_type, TileType::GreenSpace);
+        assert!(!stacked.is_tunnel());
+        assert_eq!(stacked.underlying_types(), &[TileType::Railway]);
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use super::TileGrid;
+use crate::{CachingProvider, OsmConfig, OsmData};
+
+/// Key of the query text hash in [`crate::OsmMetadata::extra`]
+pub const QUERY_HASH_KEY: &str = "query_hash";
+
+/// Everything needed to reproduce a grid, recorded during generation
+///
+/// Two grids with equal manifests were generated from the same request by
+/// the same pipeline, so the manifest doubles as a cache key: a change of
+/// any field means cached grids are out of date.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct GridManifest {
+    /// Provider type identifier, e.g. "overpass"
+    pub provider_type: String,
+    /// Endpoint or source the data was fetched from
+    pub endpoint: String,
+    /// Hash of the query text sent to the provider, if it uses one
+    pub query_hash: Option<String>,
+    /// Hash of the configuration the grid was generated from
+    pub config_hash: String,
+    /// Version of this crate that generated the grid
+    pub crate_version: String,
+    /// Names of the post-processing passes applied, in order
+    pub post_processing: Vec<String>,
+}
+
+impl GridManifest {
+    /// Record the provenance of a grid generated from `osm_data` with `config`
+    pub fn new(osm_data: &OsmData, config: &OsmConfig) -> Self {
+        let metadata = &osm_data.metadata;
+        Self {
+            provider_type: metadata.provider_type.clone(),
+            endpoint: metadata.source.clone(),
+            query_hash: metadata.extra.get(QUERY_HASH_KEY).cloned(),
+            config_hash: stable_hash(&CachingProvider::cache_key(config)),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            post_processing: Vec::new(),
+        }
+    }
+}
+
+impl TileGrid {
+    /// Get the provenance manifest, `None` for grids not built by a generator
+    pub fn manifest(&self) -> Option<&GridManifest> {
+        self.metadata.manifest.as_ref()
+    }
+}
+
+/// Hash text to 16 hex digits (64-bit FNV-1a), stable across builds and platforms
+pub fn stable_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, OsmConfigBuilder, OsmDataFormat, OsmMetadata};
+
+    #[test]
+    fn test_manifest_from_request() {
+        assert_eq!(stable_hash(""), "cbf29ce484222325");
+        assert_ne!(stable_hash("a"), stable_hash("b"));
+
+        let metadata = OsmMetadata::new("https://overpass.example", "overpass")
+            .with_extra(QUERY_HASH_KEY, stable_hash("node;out;"));
+        let data = OsmData {
+            raw_data: String::new(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.0, 13.0, 52.1, 13.1),
+            metadata,
+        };
+        let config = OsmConfigBuilder::new().city("Berlin").build();
+        let manifest = GridManifest::new(&data, &config);
+        assert_eq!(manifest.provider_type, "overpass");
+        assert_eq!(manifest.endpoint, "https://overpass.example");
+        assert_eq!(manifest.query_hash, Some(stable_hash("node;out;")));
+        assert_eq!(manifest.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(manifest, GridManifest::new(&data, &config.clone()));
+
+        let other = OsmConfigBuilder::new().city("Hamburg").build();
+        assert_ne!(
+            manifest.config_hash,
+            GridManifest::new(&data, &other).config_hash
+        );
+    }
+}
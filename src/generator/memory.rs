@@ -0,0 +1,221 @@
+use std::mem::size_of;
+
+use super::{OsmElement, Tile, TileMetadata};
+use crate::{OsmTilesError, Result};
+
+/// Approximate per-entry overhead of a `HashMap<String, String>` (hash, control byte, two headers)
+const MAP_ENTRY_OVERHEAD: usize = 2 * size_of::<String>() + 16;
+
+/// Strong and weak reference counts stored in front of `Arc` data
+const ARC_OVERHEAD: usize = 2 * size_of::<usize>();
+
+/// Estimate the heap and inline size of a parsed OSM element
+pub fn estimate_element_bytes(element: &OsmElement) -> usize {
+    size_of::<OsmElement>()
+        + element.geometry.len() * size_of::<(f64, f64)>()
+        + element
+            .holes
+            .iter()
+            .map(|hole| size_of::<Vec<(f64, f64)>>() + hole.len() * size_of::<(f64, f64)>())
+            .sum::<usize>()
+        + estimate_tags_bytes(element.tags.iter())
+}
+
+/// Estimate the memory of the metadata derived from `element`
+///
+/// The metadata is allocated once and shared by every tile the element covers.
+pub fn estimate_tile_metadata_bytes(element: &OsmElement) -> usize {
+    ARC_OVERHEAD
+        + size_of::<TileMetadata>()
+        + size_of::<i64>()
+        + estimate_tags_bytes(element.tags.iter())
+}
+
+/// Estimate the memory of a freshly allocated grid of empty tiles
+pub fn estimate_grid_bytes(width: usize, height: usize) -> usize {
+    width * height * size_of::<Tile>()
+}
+
+fn estimate_tags_bytes<'a>(tags: impl Iterator<Item = (&'a String, &'a String)>) -> usize {
+    tags.map(|(k, v)| k.len() + v.len() + MAP_ENTRY_OVERHEAD)
+        .sum()
+}
+
+/// Tracks approximate memory usage across generation stages against an optional budget
+///
+/// Usage is split into a fixed part (raw and parsed OSM data) and a part that
+/// scales with the grid resolution, so a budget violation can suggest a
+/// resolution that would fit.
+#[derive(Debug, Clone)]
+pub struct MemoryTracker {
+    /// Maximum number of bytes allowed, if any
+    budget: Option<usize>,
+    /// Bytes that do not depend on grid resolution
+    fixed_bytes: usize,
+    /// Bytes that scale with the number of tiles
+    grid_bytes: usize,
+    /// Highest total observed so far
+    peak_bytes: usize,
+    /// Grid resolution of the current generation (cells per degree)
+    grid_resolution: u32,
+}
+
+impl MemoryTracker {
+    /// Create a tracker for a generation run at the given grid resolution
+    pub fn new(budget: Option<usize>, grid_resolution: u32) -> Self {
+        Self {
+            budget,
+            fixed_bytes: 0,
+            grid_bytes: 0,
+            peak_bytes: 0,
+            grid_resolution,
+        }
+    }
+
+    /// Record memory that does not depend on grid resolution
+    pub fn track_fixed(&mut self, stage: &str, bytes: usize) -> Result<()> {
+        self.check(stage, self.fixed_bytes + bytes, self.grid_bytes)?;
+        self.fixed_bytes += bytes;
+        self.update_peak();
+        Ok(())
+    }
+
+    /// Record memory that scales with the number of tiles
+    pub fn track_grid(&mut self, stage: &str, bytes: usize) -> Result<()> {
+        self.check(stage, self.fixed_bytes, self.grid_bytes + bytes)?;
+        self.grid_bytes += bytes;
+        self.update_peak();
+        Ok(())
+    }
+
+    /// Get the current estimated usage in bytes
+    pub fn current_bytes(&self) -> usize {
+        self.fixed_bytes + self.grid_bytes
+    }
+
+    /// Get the highest estimated usage in bytes
+    pub fn peak_bytes(&self) -> usize {
+        self.peak_bytes
+    }
+
+    fn update_peak(&mut self) {
+        self.peak_bytes = self.peak_bytes.max(self.current_bytes());
+    }
+
+    fn check(&self, stage: &str, fixed_bytes: usize, grid_bytes: usize) -> Result<()> {
+        let Some(budget) = self.budget else {
+            return Ok(());
+        };
+
+        let estimated_bytes = fixed_bytes + grid_bytes;
+        if estimated_bytes <= budget {
+            return Ok(());
+        }
+
+        tracing::warn!(
+            "Memory budget exceeded during {}: ~{} bytes needed, budget is {} bytes",
+            stage,
+            estimated_bytes,
+            budget
+        );
+
+        Err(OsmTilesError::MemoryBudgetExceeded {
+            stage: stage.to_string(),
+            estimated_bytes,
+            budget_bytes: budget,
+            suggested_resolution: self.suggest_resolution(budget, fixed_bytes, grid_bytes),
+        })
+    }
+
+    /// Suggest a grid resolution whose grid-dependent memory fits the remaining budget
+    fn suggest_resolution(
+        &self,
+        budget: usize,
+        fixed_bytes: usize,
+        grid_bytes: usize,
+    ) -> Option<u32> {
+        if grid_bytes == 0 || fixed_bytes >= budget {
+            return None;
+        }
+
+        // Tile count grows with the square of the resolution
+        let scale = ((budget - fixed_bytes) as f64 / grid_bytes as f64).sqrt();
+        let suggested = (self.grid_resolution as f64 * scale * 0.9).floor() as u32;
+        (suggested > 0 && suggested < self.grid_resolution).then_some(suggested)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsmElementType;
+    use std::collections::HashMap;
+
+    fn test_element() -> OsmElement {
+        let mut tags = HashMap::new();
+        tags.insert("building".to_string(), "yes".to_string());
+        OsmElement {
+            id: 1,
+            element_type: OsmElementType::Way,
+            holes: Vec::new(),
+            tags,
+            geometry: vec![(52.0, 13.0); 10],
+        }
+    }
+
+    #[test]
+    fn test_estimates_grow_with_content() {
+        let element = test_element();
+        assert!(estimate_element_bytes(&element) > 10 * size_of::<(f64, f64)>());
+        assert!(estimate_tile_metadata_bytes(&element) > size_of::<TileMetadata>());
+        // Quadrupling the tile count roughly quadruples the grid estimate
+        assert!(estimate_grid_bytes(100, 100) > 3 * estimate_grid_bytes(50, 50));
+    }
+
+    #[test]
+    fn test_tracker_without_budget() {
+        let mut tracker = MemoryTracker::new(None, 100);
+        tracker.track_fixed("parse", usize::MAX / 2).unwrap();
+        assert_eq!(tracker.peak_bytes(), usize::MAX / 2);
+    }
+
+    #[test]
+    fn test_tracker_budget_exceeded_suggests_resolution() {
+        let mut tracker = MemoryTracker::new(Some(1_000), 200);
+        tracker.track_fixed("parse", 200).unwrap();
+
+        let error = tracker.track_grid("grid allocation", 3_200).unwrap_err();
+        match error {
+            OsmTilesError::MemoryBudgetExceeded {
+                stage,
+                estimated_bytes,
+                budget_bytes,
+                suggested_resolution,
+            } => {
+                assert_eq!(stage, "grid allocation");
+                assert_eq!(estimated_bytes, 3_400);
+                assert_eq!(budget_bytes, 1_000);
+                // sqrt(800 / 3200) = 0.5, with a 10% safety margin
+                assert_eq!(suggested_resolution, Some(90));
+            }
+            other => panic!("Unexpected error: {other}"),
+        }
+
+        // Failed allocations are not recorded
+        assert_eq!(tracker.current_bytes(), 200);
+    }
+
+    #[test]
+    fn test_tracker_no_suggestion_when_data_alone_exceeds_budget() {
+        let mut tracker = MemoryTracker::new(Some(1_000), 200);
+        let error = tracker.track_fixed("raw data", 5_000).unwrap_err();
+        assert!(matches!(
+            error,
+            OsmTilesError::MemoryBudgetExceeded {
+                suggested_resolution: None,
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("smaller region"));
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+
+use super::{ConflictStrategy, SharedMetadata, Tile, TileGrid, TileType};
+
+/// How [`TileGrid::merge`] combines the tiles of an overlay with the grid below
+#[derive(Debug, Clone)]
+pub enum MergeStrategy {
+    /// Resolve each tile like the generator does, e.g. roads over parks with `Priority`
+    Conflict(ConflictStrategy),
+    /// Tiles of the grid with the newer data snapshot win; unknown snapshots count as newest
+    NewestWins,
+    /// The overlay is authoritative for these types: their old tiles are cleared
+    /// and the overlay's drawn instead, while all other tiles stay untouched
+    ReplaceTypes(HashSet<TileType>),
+    /// The overlay replaces every tile it covers, empty tiles included, e.g. a regenerated chunk
+    Replace,
+}
+
+impl MergeStrategy {
+    /// Keep the higher priority tile type on each tile
+    pub fn priority() -> Self {
+        Self::Conflict(ConflictStrategy::Priority)
+    }
+
+    /// Replace the tiles of `types` with the overlay's, e.g. a refreshed transport layer
+    pub fn replace_types(types: impl IntoIterator<Item = TileType>) -> Self {
+        Self::ReplaceTypes(types.into_iter().collect())
+    }
+}
+
+/// Check whether two tiles show the same elements as the same type with the same attributes
+fn same_tile(a: &Tile, b: &Tile) -> bool {
+    let ids = |tile: &Tile| {
+        tile.metadata
+            .as_ref()
+            .map(|metadata| metadata.osm_ids.clone())
+    };
+    a.tile_type == b.tile_type
+        && a.attributes == b.attributes
+        && match (&a.metadata, &b.metadata) {
+            (Some(a), Some(b)) if SharedMetadata::ptr_eq(a, b) => true,
+            _ => ids(a) == ids(b),
+        }
+}
+
+impl TileGrid {
+    /// Overlay `other` onto this grid, returning the number of tiles that changed
+    ///
+    /// Grids with the same bounds, dimensions and layout are combined tile by
+    /// tile. Otherwise every tile takes the overlay tile at its center, so
+    /// grids of other areas, resolutions or projections can be overlaid too;
+    /// tiles outside the overlay stay untouched. Only tiles are combined,
+    /// points of interest and districts are kept as they are.
+    pub fn merge(&mut self, other: &TileGrid, strategy: &MergeStrategy) -> usize {
+        let (width, height) = self.dimensions();
+        let aligned = self.bounding_box == other.bounding_box
+            && self.dimensions() == other.dimensions()
+            && self.crs == other.crs
+            && self.origin == other.origin;
+        let overlay_newer = match (self.metadata.data_timestamp, other.metadata.data_timestamp) {
+            (Some(base), Some(overlay)) => overlay >= base,
+            _ => true,
+        };
+
+        let mut changed = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let incoming = if aligned {
+                    other.get_tile(x, y)
+                } else {
+                    self.grid_to_geo(x, y)
+                        .and_then(|(lat, lon)| other.geo_to_grid(lat, lon))
+                        .and_then(|(ox, oy)| other.get_tile(ox, oy))
+                };
+                let Some(incoming) = incoming else {
+                    continue;
+                };
+                if let MergeStrategy::Conflict(conflict) = strategy {
+                    if incoming.tile_type != TileType::Empty
+                        && self
+                            .set_tile_with_strategy(x, y, incoming.clone(), conflict)
+                            .unwrap_or(false)
+                    {
+                        changed += 1;
+                    }
+                    continue;
+                }
+                let Some(existing) = self.get_tile_mut(x, y) else {
+                    continue;
+                };
+
+                let replacement = match strategy {
+                    MergeStrategy::Conflict(_) => None,
+                    MergeStrategy::Replace => Some(incoming.clone()),
+                    MergeStrategy::NewestWins => (incoming.tile_type != TileType::Empty
+                        && (overlay_newer || existing.tile_type == TileType::Empty))
+                        .then(|| incoming.clone()),
+                    MergeStrategy::ReplaceTypes(types) => {
+                        if types.contains(&incoming.tile_type) {
+                            Some(incoming.clone())
+                        } else if types.contains(&existing.tile_type) {
+                            Some(Tile::default())
+                        } else {
+                            None
+                        }
+                    }
+                };
+                if let Some(tile) = replacement
+                    && !same_tile(existing, &tile)
+                {
+                    *existing = tile;
+                    changed += 1;
+                }
+            }
+        }
+
+        if changed > 0 {
+            self.metadata.tiles_populated = self
+                .flat_tiles()
+                .iter()
+                .filter(|tile| tile.tile_type != TileType::Empty)
+                .count();
+            // The oldest snapshot describes the combined data, like merged Overpass slices
+            self.metadata.data_timestamp =
+                match (self.metadata.data_timestamp, other.metadata.data_timestamp) {
+                    (Some(base), Some(overlay)) => Some(base.min(overlay)),
+                    (base, overlay) => base.or(overlay),
+                };
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileMetadata};
+
+    fn grid(bbox: BoundingBox, size: usize) -> TileGrid {
+        TileGrid::new(size, size, bbox, 10.0)
+    }
+
+    fn tile(tile_type: TileType, id: i64) -> Tile {
+        Tile::with_metadata(
+            tile_type,
+            TileMetadata {
+                osm_ids: vec![id],
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let bbox = BoundingBox::new(52.50, 13.40, 52.51, 13.41);
+        let mut base = grid(bbox.clone(), 4);
+        base.set_tile(0, 0, tile(TileType::Road, 1)).unwrap();
+        base.set_tile(1, 0, tile(TileType::GreenSpace, 2)).unwrap();
+        base.set_tile(2, 0, tile(TileType::Building, 3)).unwrap();
+
+        let mut overlay = grid(bbox, 4);
+        overlay.set_tile(1, 0, tile(TileType::Road, 4)).unwrap();
+        overlay
+            .set_tile(2, 0, tile(TileType::GreenSpace, 5))
+            .unwrap();
+        overlay.set_tile(3, 3, tile(TileType::Railway, 6)).unwrap();
+
+        // Priority keeps the building, but roads win over parks
+        let mut merged = base.clone();
+        assert_eq!(merged.merge(&overlay, &MergeStrategy::priority()), 2);
+        assert_eq!(merged.get_tile(1, 0).unwrap().tile_type, TileType::Road);
+        assert_eq!(merged.get_tile(2, 0).unwrap().tile_type, TileType::Building);
+        assert_eq!(merged.metadata.tiles_populated, 4);
+
+        let mut merged = base.clone();
+        merged.merge(&overlay, &MergeStrategy::NewestWins);
+        assert_eq!(
+            merged.get_tile(2, 0).unwrap().tile_type,
+            TileType::GreenSpace
+        );
+
+        // Refreshing roads drops the old road and leaves parks and buildings alone
+        let mut merged = base.clone();
+        let refresh = MergeStrategy::replace_types([TileType::Road]);
+        assert_eq!(merged.merge(&overlay, &refresh), 2);
+        assert_eq!(merged.get_tile(0, 0).unwrap().tile_type, TileType::Empty);
+        assert_eq!(merged.get_tile(1, 0).unwrap().tile_type, TileType::Road);
+        assert_eq!(merged.get_tile(2, 0).unwrap().tile_type, TileType::Building);
+        assert_eq!(merged.get_tile(3, 3).unwrap().tile_type, TileType::Empty);
+        assert_eq!(merged.merge(&overlay, &refresh), 0);
+    }
+
+    #[test]
+    fn test_merge_resamples_other_grids() {
+        let mut base = grid(BoundingBox::new(52.50, 13.40, 52.52, 13.42), 4);
+        // A finer overlay covering only the south-west quarter of the base grid
+        let mut overlay = grid(BoundingBox::new(52.50, 13.40, 52.51, 13.41), 8);
+        for x in 0..8 {
+            for y in 0..8 {
+                overlay.set_tile(x, y, tile(TileType::Water, 1)).unwrap();
+            }
+        }
+
+        assert_eq!(base.merge(&overlay, &MergeStrategy::priority()), 4);
+        assert_eq!(base.tiles_of_type(&TileType::Water).len(), 4);
+        let (lat, lon) = base.grid_to_geo(0, 3).unwrap();
+        assert!(lat < 52.51 && lon < 13.41);
+        assert_eq!(base.get_tile(0, 3).unwrap().tile_type, TileType::Water);
+    }
+
+    #[test]
+    fn test_replace_chunk() {
+        let mut base = grid(BoundingBox::new(52.50, 13.40, 52.52, 13.42), 10);
+        for x in 0..10 {
+            for y in 0..10 {
+                base.set_tile(x, y, tile(TileType::Building, 1)).unwrap();
+            }
+        }
+
+        let rect = base
+            .cells_in(&BoundingBox::new(52.5001, 13.4001, 52.5039, 13.4059))
+            .unwrap();
+        assert_eq!(rect.dimensions(), (3, 2));
+        assert!(
+            base.cells_in(&BoundingBox::new(53.0, 13.0, 53.1, 13.1))
+                .is_none()
+        );
+
+        // A regenerated chunk covering exactly these cells, now empty except for one road
+        let chunk_bounds = base.rect_bounds(&rect).unwrap();
+        let (width, height) = rect.dimensions();
+        let mut chunk = TileGrid::new(width, height, chunk_bounds, 10.0);
+        chunk.set_tile(0, 0, tile(TileType::Road, 2)).unwrap();
+        assert_eq!(base.merge(&chunk, &MergeStrategy::Replace), 6);
+        assert_eq!(base.tiles_of_type(&TileType::Building).len(), 94);
+        assert_eq!(base.tiles_of_type(&TileType::Road).len(), 1);
+        for (x, y, tile) in base.iter_tiles() {
+            assert_eq!(rect.contains(x, y), tile.tile_type != TileType::Building);
+        }
+    }
+}
@@ -1,13 +1,69 @@
+mod attributes;
+mod coastline;
+mod compact;
+mod conflict;
+mod coverage;
+mod decoration;
+mod density;
+mod districts;
 mod grid_builder;
+mod index;
+mod layered;
+mod layers;
+mod manifest;
+mod memory;
+mod merge;
+mod navigation;
+mod neighborhood;
+mod origin;
 mod osm_parser;
+mod pathfinding;
+mod poi;
+mod post_process;
+mod projection;
+mod raycast;
+mod regions;
+mod resolution;
+mod road_features;
+mod search;
+mod simplify;
+mod streets;
 mod tile_grid;
+mod unclassified;
 
+pub use attributes::*;
+pub use compact::*;
+pub use conflict::*;
+pub use decoration::*;
+pub use density::*;
+pub use districts::*;
 pub use grid_builder::*;
+pub use index::*;
+pub use layered::*;
+pub use layers::*;
+pub use manifest::*;
+pub use memory::*;
+pub use merge::*;
+pub use navigation::*;
+pub use neighborhood::*;
+pub use origin::*;
 pub use osm_parser::*;
+pub use pathfinding::*;
+pub use poi::*;
+pub use post_process::*;
+pub use projection::*;
+pub use raycast::*;
+pub use regions::*;
+pub use resolution::*;
+pub use road_features::*;
+pub use search::*;
+pub use streets::*;
 pub use tile_grid::*;
+pub use unclassified::*;
 
-use crate::{OsmConfig, OsmData, Result};
+use crate::{OsmConfig, OsmData, OsmTilesError, Result, SlippyTile};
 use async_trait::async_trait;
+use tracing::Instrument;
 
 /// Trait for generating tile grids from OSM data
 #[async_trait]
@@ -17,6 +73,54 @@ pub trait GridGenerator: Send + Sync {
 
     /// Get the generator's capabilities and settings
     fn capabilities(&self) -> GeneratorCapabilities;
+
+    /// Generate one Web Mercator grid per slippy tile at `config.slippy_zoom`
+    ///
+    /// Tiles are returned row by row and each grid covers exactly its tile's
+    /// bounds, so results can be cached and combined by [`SlippyTile::key`].
+    /// The default implementation generates every tile from the whole data;
+    /// [`DefaultGridGenerator`] parses it once and rasterizes each tile from
+    /// the elements touching it.
+    async fn generate_slippy_grids(
+        &self,
+        osm_data: &OsmData,
+        config: &OsmConfig,
+    ) -> Result<Vec<(SlippyTile, TileGrid)>> {
+        let (tiles, tile_config) = slippy_layout(osm_data, config)?;
+        let span = tracing::info_span!("generate_slippy_grids", tiles = tiles.len());
+
+        let mut grids = Vec::with_capacity(tiles.len());
+        for tile in tiles {
+            let tile_data = OsmData {
+                bounding_box: tile.bounds(),
+                ..osm_data.clone()
+            };
+            let mut grid = self
+                .generate_grid(&tile_data, &tile_config)
+                .instrument(span.clone())
+                .await?;
+            grid.metadata
+                .extra
+                .insert("slippy_tile".to_string(), tile.key());
+            grids.push((tile, grid));
+        }
+
+        Ok(grids)
+    }
+}
+
+/// Get the slippy tiles covering the data at `config.slippy_zoom` and the config to generate them with
+pub(crate) fn slippy_layout(
+    osm_data: &OsmData,
+    config: &OsmConfig,
+) -> Result<(Vec<SlippyTile>, OsmConfig)> {
+    let zoom = config.slippy_zoom.ok_or_else(|| {
+        OsmTilesError::Config("Slippy tile generation requires slippy_zoom to be set".into())
+    })?;
+    Ok((
+        SlippyTile::covering(&osm_data.bounding_box, zoom),
+        config.clone().with_crs(Crs::WebMercator),
+    ))
 }
 
 /// Describes the capabilities and settings of a grid generator
@@ -36,7 +140,7 @@ impl Default for GeneratorCapabilities {
     fn default() -> Self {
         Self {
             max_grid_size: Some((10000, 10000)),
-            supported_crs: vec!["EPSG:4326".to_string()], // WGS84
+            supported_crs: vec![Crs::Wgs84.code().to_string()],
             supports_parallel: false,
             notes: None,
         }
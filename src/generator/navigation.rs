@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Tile, TileGrid, TileType};
+use crate::{OsmTilesError, Result};
+
+/// Highway values reserved for pedestrians and cyclists
+const NON_MOTORIZED_HIGHWAYS: [&str; 7] = [
+    "footway",
+    "path",
+    "pedestrian",
+    "steps",
+    "cycleway",
+    "bridleway",
+    "corridor",
+];
+
+/// How an agent moves across the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TravelMode {
+    /// On foot; crosses open ground and parks, but slows down on rough surfaces and slopes
+    Walking,
+    /// By car; restricted to roads and parking areas open to motor traffic
+    Driving,
+}
+
+impl TravelMode {
+    /// Get the base cost of crossing a tile type (1.0 is a paved road), `None` if impassable
+    pub fn type_cost(&self, tile_type: &TileType) -> Option<f32> {
+        match (self, tile_type) {
+            (_, TileType::Road | TileType::Parking) => Some(1.0),
+            (Self::Walking, TileType::Empty) => Some(1.2),
+            (Self::Walking, TileType::GreenSpace) => Some(1.5),
+            _ => None,
+        }
+    }
+
+    /// Get the cost multiplier for an OSM `surface` value (1.0 for paved and unknown surfaces)
+    pub fn surface_factor(&self, surface: &str) -> f32 {
+        let (walking, driving) = match surface {
+            "sett" | "cobblestone" | "unhewn_cobblestone" | "paving_stones" => (1.05, 1.3),
+            "compacted" | "fine_gravel" | "wood" | "metal" => (1.1, 1.4),
+            "gravel" | "pebblestone" | "unpaved" => (1.2, 1.7),
+            "dirt" | "earth" | "ground" | "grass" | "grass_paver" => (1.3, 2.5),
+            "mud" | "sand" | "rock" => (1.8, 4.0),
+            _ => (1.0, 1.0),
+        };
+        match self {
+            Self::Walking => walking,
+            Self::Driving => driving,
+        }
+    }
+
+    /// Get the steepest slope (rise over run) this mode can climb or descend
+    pub fn max_slope(&self) -> f32 {
+        match self {
+            Self::Walking => 1.0,
+            Self::Driving => 0.25,
+        }
+    }
+
+    /// Get the cost multiplier for a signed slope (positive uphill), `None` if too steep
+    ///
+    /// Walking follows Tobler's hiking function, so gentle descents are
+    /// slightly cheaper than flat ground; driving gets slower either way.
+    pub fn slope_factor(&self, slope: f32) -> Option<f32> {
+        if slope.abs() > self.max_slope() {
+            return None;
+        }
+        Some(match self {
+            Self::Walking => (3.5 * ((slope + 0.05).abs() - 0.05)).exp(),
+            Self::Driving => 1.0 + 4.0 * slope.abs(),
+        })
+    }
+}
+
+impl Tile {
+    /// Get the `surface` tag of the element this tile was rasterized from
+    pub fn surface(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.tags.get("surface"))
+            .map(String::as_str)
+    }
+
+    /// Check if an agent using `mode` can enter this tile
+    ///
+    /// Unlike [`TileType::is_navigable`], this honors the travel mode and
+    /// keeps cars off footways, paths and steps.
+    pub fn is_navigable_for(&self, mode: TravelMode) -> bool {
+        self.movement_cost(mode).is_some()
+    }
+
+    /// Get the cost of crossing this tile on flat ground, `None` if impassable
+    pub fn movement_cost(&self, mode: TravelMode) -> Option<f32> {
+        let highway = self
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.tags.get("highway"))
+            .map(String::as_str);
+        let type_cost = match (mode, highway) {
+            (TravelMode::Driving, Some(highway)) if NON_MOTORIZED_HIGHWAYS.contains(&highway) => {
+                return None;
+            }
+            (TravelMode::Walking, Some("steps")) => 2.0,
+            _ => mode.type_cost(&self.tile_type)?,
+        };
+        let surface = self
+            .surface()
+            .map_or(1.0, |surface| mode.surface_factor(surface));
+        Some(type_cost * surface)
+    }
+}
+
+/// Terrain elevation per tile (in meters), stored in the grid's row order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElevationLayer {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl ElevationLayer {
+    /// Create a layer from row-major elevation values
+    pub fn new(width: usize, height: usize, values: Vec<f32>) -> Result<Self> {
+        if values.len() != width * height {
+            return Err(OsmTilesError::GridGeneration(format!(
+                "Elevation layer has {} values, expected {} for a {}x{} grid",
+                values.len(),
+                width * height,
+                width,
+                height
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            values,
+        })
+    }
+
+    /// Create a layer matching the dimensions of `grid`
+    pub fn for_grid(grid: &TileGrid, values: Vec<f32>) -> Result<Self> {
+        let (width, height) = grid.dimensions();
+        Self::new(width, height, values)
+    }
+
+    /// Get the layer dimensions (width, height)
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get the elevation at the given grid coordinates
+    pub fn elevation(&self, x: usize, y: usize) -> Option<f32> {
+        (x < self.width && y < self.height).then(|| self.values[y * self.width + x])
+    }
+
+    /// Get the elevation values in row-major order
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+}
+
+/// Movement costs between neighboring tiles for one travel mode
+///
+/// Costs are in meters of flat paved road, so a 50 m walk across a park
+/// costs 75.
+#[derive(Debug, Clone)]
+pub struct MovementCosts {
+    mode: TravelMode,
+    elevation: Option<ElevationLayer>,
+}
+
+impl MovementCosts {
+    /// Create movement costs for a travel mode on flat terrain
+    pub fn new(mode: TravelMode) -> Self {
+        Self {
+            mode,
+            elevation: None,
+        }
+    }
+
+    /// Account for terrain slope from an elevation layer
+    pub fn with_elevation(mut self, elevation: ElevationLayer) -> Self {
+        self.elevation = Some(elevation);
+        self
+    }
+
+    /// Get the travel mode
+    pub fn mode(&self) -> TravelMode {
+        self.mode
+    }
+
+    /// Get the cost of moving from one tile to another, `None` if the move is impossible
+    ///
+    /// The cost of the entered tile is scaled by the distance between tile
+    /// centers, using the grid's size per axis, and by the slope between
+    /// their elevations.
+    pub fn step_cost(
+        &self,
+        grid: &TileGrid,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<f32> {
+        grid.get_tile(from.0, from.1)?;
+        let tile_cost = grid.get_tile(to.0, to.1)?.movement_cost(self.mode)?;
+
+        let (dx, dy) = (
+            from.0.abs_diff(to.0) as f32 * grid.meters_per_tile_x(),
+            from.1.abs_diff(to.1) as f32 * grid.meters_per_tile_y(),
+        );
+        let distance = dx.hypot(dy);
+        if distance == 0.0 {
+            return Some(0.0);
+        }
+
+        let slope_factor = match &self.elevation {
+            Some(elevation) => {
+                let rise =
+                    elevation.elevation(to.0, to.1)? - elevation.elevation(from.0, from.1)?;
+                self.mode.slope_factor(rise / distance)?
+            }
+            None => 1.0,
+        };
+        Some(distance * tile_cost * slope_factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileMetadata};
+
+    fn tagged(tile_type: TileType, tags: &[(&str, &str)]) -> Tile {
+        Tile::with_metadata(
+            tile_type,
+            TileMetadata {
+                tags: tags
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_tile_movement_cost() {
+        let road = tagged(TileType::Road, &[("highway", "residential")]);
+        let track = tagged(
+            TileType::Road,
+            &[("highway", "track"), ("surface", "gravel")],
+        );
+        let footway = tagged(TileType::Road, &[("highway", "footway")]);
+
+        assert_eq!(road.movement_cost(TravelMode::Driving), Some(1.0));
+        assert!(track.movement_cost(TravelMode::Driving).unwrap() > 1.0);
+        assert!(
+            track.movement_cost(TravelMode::Walking).unwrap()
+                < track.movement_cost(TravelMode::Driving).unwrap()
+        );
+        assert!(!footway.is_navigable_for(TravelMode::Driving));
+        assert!(footway.is_navigable_for(TravelMode::Walking));
+        assert!(Tile::new(TileType::GreenSpace).is_navigable_for(TravelMode::Walking));
+        assert!(!Tile::new(TileType::Building).is_navigable_for(TravelMode::Walking));
+    }
+
+    #[test]
+    fn test_slope_costs() {
+        let mut grid = TileGrid::new(3, 1, BoundingBox::new(52.0, 13.0, 52.001, 13.003), 10.0);
+        for x in 0..3 {
+            grid.set_tile(x, 0, Tile::new(TileType::Road)).unwrap();
+        }
+        let elevation = ElevationLayer::for_grid(&grid, vec![0.0, 1.0, 5.0]).unwrap();
+
+        // Tiles at 52°N are about 8 m wide and 13 m tall for a mean size of 10 m
+        let step = grid.meters_per_tile_x();
+        assert!((7.0..9.0).contains(&step));
+        let flat = MovementCosts::new(TravelMode::Walking);
+        assert_eq!(flat.step_cost(&grid, (0, 0), (1, 0)), Some(step));
+
+        let walking = MovementCosts::new(TravelMode::Walking).with_elevation(elevation.clone());
+        let uphill = walking.step_cost(&grid, (0, 0), (1, 0)).unwrap();
+        let downhill = walking.step_cost(&grid, (1, 0), (0, 0)).unwrap();
+        assert!(uphill > step);
+        assert!(downhill < uphill);
+
+        // A 50% grade is still walkable but too steep to drive
+        let driving = MovementCosts::new(TravelMode::Driving).with_elevation(elevation);
+        assert!(walking.step_cost(&grid, (1, 0), (2, 0)).is_some());
+        assert_eq!(driving.step_cost(&grid, (1, 0), (2, 0)), None);
+
+        assert!(ElevationLayer::for_grid(&grid, vec![0.0]).is_err());
+    }
+}
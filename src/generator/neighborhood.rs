@@ -0,0 +1,267 @@
+use std::f32::consts::SQRT_2;
+
+use super::{Connectivity, Tile, TileGrid, TileType};
+use crate::{OsmTilesError, Result};
+
+/// Offsets of the four edge neighbors of a tile, in reading order
+const EDGE_OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets of the eight neighbors of a tile, in reading order
+const OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Iterate over the in-bounds neighbor positions of (x, y) in a `width` × `height` grid
+pub(crate) fn neighbor_positions(
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+    connectivity: Connectivity,
+) -> impl Iterator<Item = (usize, usize)> {
+    let offsets: &[(isize, isize)] = match connectivity {
+        Connectivity::Four => &EDGE_OFFSETS,
+        Connectivity::Eight => &OFFSETS,
+    };
+    offsets.iter().filter_map(move |&(dx, dy)| {
+        let nx = x.checked_add_signed(dx).filter(|&nx| nx < width)?;
+        let ny = y.checked_add_signed(dy).filter(|&ny| ny < height)?;
+        Some((nx, ny))
+    })
+}
+
+/// Square matrix of weights for [`TileGrid::convolve`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    size: usize,
+    weights: Vec<f32>,
+}
+
+impl Kernel {
+    /// Create a kernel from row-major weights; `size` must be odd
+    pub fn new(size: usize, weights: Vec<f32>) -> Result<Self> {
+        if size.is_multiple_of(2) || weights.len() != size * size {
+            return Err(OsmTilesError::GridGeneration(format!(
+                "Kernel needs an odd size and size² weights, got size {} with {} weights",
+                size,
+                weights.len()
+            )));
+        }
+        Ok(Self { size, weights })
+    }
+
+    /// Average over the square of tiles within `radius`
+    pub fn box_blur(radius: usize) -> Self {
+        let size = 2 * radius + 1;
+        Self {
+            size,
+            weights: vec![1.0 / (size * size) as f32; size * size],
+        }
+    }
+
+    /// Get the distance from the center to the kernel's edge, in tiles
+    pub fn radius(&self) -> usize {
+        self.size / 2
+    }
+}
+
+/// A number per tile derived from a grid, e.g. by [`TileGrid::convolve`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueGrid {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl ValueGrid {
+    /// Get the grid dimensions (width, height)
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get the value at the given grid coordinates
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        (x < self.width && y < self.height).then(|| self.values[y * self.width + x])
+    }
+
+    /// Get the values in row-major order
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+}
+
+impl TileGrid {
+    /// Iterate over the tiles sharing an edge with (x, y)
+    pub fn neighbors4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize, &Tile)> {
+        self.neighbors(x, y, Connectivity::Four)
+    }
+
+    /// Iterate over the tiles sharing an edge or a corner with (x, y)
+    pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize, &Tile)> {
+        self.neighbors(x, y, Connectivity::Eight)
+    }
+
+    fn neighbors(
+        &self,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (usize, usize, &Tile)> {
+        let (width, height) = self.dimensions();
+        neighbor_positions(width, height, x, y, connectivity).filter_map(|(nx, ny)| {
+            let tile = self.get_tile(nx, ny)?;
+            Some((nx, ny, tile))
+        })
+    }
+
+    /// Count the tiles of a type whose centers lie within `radius` tiles of (x, y), itself included
+    pub fn count_in_radius(
+        &self,
+        x: usize,
+        y: usize,
+        radius: usize,
+        tile_type: &TileType,
+    ) -> usize {
+        let (width, height) = self.dimensions();
+        let rows = y.saturating_sub(radius)..(y + radius + 1).min(height);
+        rows.flat_map(|ny| {
+            let columns = x.saturating_sub(radius)..(x + radius + 1).min(width);
+            columns.map(move |nx| (nx, ny))
+        })
+        .filter(|&(nx, ny)| nx.abs_diff(x).pow(2) + ny.abs_diff(y).pow(2) <= radius * radius)
+        .filter(|&(nx, ny)| {
+            self.get_tile(nx, ny)
+                .is_some_and(|tile| tile.tile_type == *tile_type)
+        })
+        .count()
+    }
+
+    /// Apply `kernel` to a number taken from every tile, e.g. 1.0 for trees and 0.0 elsewhere
+    ///
+    /// Tiles beyond the grid edge count as 0.0.
+    pub fn convolve(&self, kernel: &Kernel, value: impl Fn(&Tile) -> f32) -> ValueGrid {
+        let (width, height) = self.dimensions();
+        let source: Vec<f32> = self.flat_tiles().iter().map(value).collect();
+        let radius = kernel.radius() as isize;
+
+        let mut values = vec![0.0; width * height];
+        for (index, result) in values.iter_mut().enumerate() {
+            let (x, y) = ((index % width) as isize, (index / width) as isize);
+            for (k, weight) in kernel.weights.iter().enumerate() {
+                let nx = x + (k % kernel.size) as isize - radius;
+                let ny = y + (k / kernel.size) as isize - radius;
+                if (0..width as isize).contains(&nx) && (0..height as isize).contains(&ny) {
+                    *result += weight * source[ny as usize * width + nx as usize];
+                }
+            }
+        }
+        ValueGrid {
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// Get the distance in tiles from every tile to the nearest tile matching `target`
+    ///
+    /// Distances are approximately Euclidean (steps of 1 and √2), so
+    /// multiplying by `meters_per_tile` gives meters. Without any target
+    /// tile every distance is infinite.
+    pub fn distance_to(&self, target: impl Fn(&Tile) -> bool) -> ValueGrid {
+        let (width, height) = self.dimensions();
+        let mut values: Vec<f32> = self
+            .flat_tiles()
+            .iter()
+            .map(|tile| if target(tile) { 0.0 } else { f32::INFINITY })
+            .collect();
+
+        // Two chamfer passes: from the top left, then back from the bottom right
+        let relax = |values: &mut Vec<f32>, x: usize, y: usize, offsets: &[(isize, isize)]| {
+            for &(dx, dy) in offsets {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                if nx >= width || ny >= height {
+                    continue;
+                }
+                let step = if dx != 0 && dy != 0 { SQRT_2 } else { 1.0 };
+                let candidate = values[ny * width + nx] + step;
+                if candidate < values[y * width + x] {
+                    values[y * width + x] = candidate;
+                }
+            }
+        };
+        for y in 0..height {
+            for x in 0..width {
+                relax(&mut values, x, y, &OFFSETS[..4]);
+            }
+        }
+        for y in (0..height).rev() {
+            for x in (0..width).rev() {
+                relax(&mut values, x, y, &OFFSETS[4..]);
+            }
+        }
+
+        ValueGrid {
+            width,
+            height,
+            values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoundingBox;
+
+    fn grid() -> TileGrid {
+        let mut grid = TileGrid::new(7, 5, BoundingBox::new(52.0, 13.0, 52.01, 13.01), 10.0);
+        for y in 0..5 {
+            grid.set_tile(0, y, Tile::new(TileType::Road)).unwrap();
+        }
+        grid.set_tile(4, 2, Tile::new(TileType::GreenSpace))
+            .unwrap();
+        grid
+    }
+
+    #[test]
+    fn test_neighbors_and_radius() {
+        let grid = grid();
+        assert_eq!(grid.neighbors4(0, 0).count(), 2);
+        assert_eq!(grid.neighbors8(3, 2).count(), 8);
+        let roads = grid
+            .neighbors8(1, 2)
+            .filter(|(_, _, tile)| tile.tile_type == TileType::Road)
+            .count();
+        assert_eq!(roads, 3);
+
+        assert_eq!(grid.count_in_radius(0, 2, 2, &TileType::Road), 5);
+        assert_eq!(grid.count_in_radius(1, 2, 1, &TileType::Road), 1);
+        assert_eq!(grid.count_in_radius(6, 2, 2, &TileType::GreenSpace), 1);
+    }
+
+    #[test]
+    fn test_kernels_and_distances() {
+        let grid = grid();
+        let green = |tile: &Tile| f32::from(tile.tile_type == TileType::GreenSpace);
+        let blurred = grid.convolve(&Kernel::box_blur(1), green);
+        assert!((blurred.get(3, 1).unwrap() - 1.0 / 9.0).abs() < 1e-6);
+        assert_eq!(blurred.get(1, 1), Some(0.0));
+        assert!(Kernel::new(2, vec![1.0; 4]).is_err());
+
+        let distance = grid.distance_to(|tile| tile.tile_type == TileType::Road);
+        assert_eq!(distance.get(0, 3), Some(0.0));
+        assert_eq!(distance.get(6, 4), Some(6.0));
+        let none = grid.distance_to(|tile| tile.tile_type == TileType::Water);
+        assert!(none.values().iter().all(|d| d.is_infinite()));
+    }
+}
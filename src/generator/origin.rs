@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Corner of the geographic area at which grid row `y = 0` starts
+///
+/// `TopLeft` matches image coordinates (y grows southward), `BottomLeft`
+/// matches math and GIS conventions (y grows northward). Column `x` always
+/// grows eastward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GridOrigin {
+    /// Row 0 is the northern edge, y grows southward (image coordinates)
+    #[default]
+    TopLeft,
+    /// Row 0 is the southern edge, y grows northward
+    BottomLeft,
+}
+
+impl GridOrigin {
+    /// Get the origin identifier (e.g., "top_left")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TopLeft => "top_left",
+            Self::BottomLeft => "bottom_left",
+        }
+    }
+
+    /// Check whether y grows northward
+    pub fn is_y_up(&self) -> bool {
+        matches!(self, Self::BottomLeft)
+    }
+
+    /// Convert a row index in this convention to the given convention
+    pub fn convert_y(&self, y: usize, height: usize, target: GridOrigin) -> usize {
+        if *self == target { y } else { height - 1 - y }
+    }
+
+    /// Convert a row index in this convention to a top-left (image) row index
+    pub fn to_top_left(&self, y: usize, height: usize) -> usize {
+        self.convert_y(y, height, GridOrigin::TopLeft)
+    }
+
+    /// Convert a top-left (image) row index to this convention
+    pub fn from_top_left(&self, y: usize, height: usize) -> usize {
+        GridOrigin::TopLeft.convert_y(y, height, *self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_y() {
+        assert_eq!(GridOrigin::TopLeft.to_top_left(2, 10), 2);
+        assert_eq!(GridOrigin::BottomLeft.to_top_left(0, 10), 9);
+        assert_eq!(GridOrigin::BottomLeft.from_top_left(9, 10), 0);
+        assert_eq!(
+            GridOrigin::TopLeft.convert_y(3, 10, GridOrigin::BottomLeft),
+            6
+        );
+        assert!(GridOrigin::BottomLeft.is_y_up());
+        assert!(!GridOrigin::default().is_y_up());
+    }
+}
@@ -1,9 +1,15 @@
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
 
 use super::{TileMetadata, TileType};
 use crate::{OsmData, OsmDataFormat, OsmTilesError, Result};
 
+/// Tag recording which data source an element came from when sources are combined
+pub const SOURCE_TAG: &str = "osm_tiles:source";
+
 /// Represents a parsed OSM element
 #[derive(Debug, Clone)]
 pub struct OsmElement {
@@ -11,6 +17,8 @@ pub struct OsmElement {
     pub element_type: OsmElementType,
     pub tags: HashMap<String, String>,
     pub geometry: Vec<(f64, f64)>, // (lat, lon) pairs
+    /// Inner rings of multipolygons (courtyards, islands), filled with the even-odd rule
+    pub holes: Vec<Vec<(f64, f64)>>,
 }
 
 /// Type of OSM element
@@ -89,13 +97,106 @@ impl OsmElement {
 
     /// Create tile metadata from this element
     pub fn to_tile_metadata(&self) -> TileMetadata {
+        let mut tags = self.tags.clone();
+        let source = tags.remove(SOURCE_TAG);
         TileMetadata {
             osm_ids: vec![self.id],
-            tags: self.tags.clone(),
+            tags,
+            source,
             confidence: 1.0,
+            element_area_m2: self.area_m2(),
+            layer: self.layer(),
+            bridge: self.is_bridge(),
+            tunnel: self.is_tunnel(),
+            ..Default::default()
+        }
+    }
+
+    /// Get the data source this element was merged from, if tracked
+    pub fn source(&self) -> Option<&str> {
+        self.tags.get(SOURCE_TAG).map(|source| source.as_str())
+    }
+
+    /// Check whether this element is a coastline, which has land on its left side
+    pub fn is_coastline(&self) -> bool {
+        self.tags
+            .get("natural")
+            .is_some_and(|natural| natural == "coastline")
+    }
+
+    /// Check whether this element is a bridge (`bridge=*` other than "no")
+    pub fn is_bridge(&self) -> bool {
+        self.tags.get("bridge").is_some_and(|value| value != "no")
+    }
+
+    /// Check whether this element is a tunnel or covered passage (`tunnel=*` other than "no")
+    pub fn is_tunnel(&self) -> bool {
+        self.tags.get("tunnel").is_some_and(|value| value != "no")
+    }
+
+    /// Get the vertical layer of this element
+    ///
+    /// Uses the `layer` tag if present; otherwise bridges are on layer 1,
+    /// tunnels on layer -1 and everything else on the ground (layer 0).
+    pub fn layer(&self) -> i8 {
+        if let Some(layer) = self
+            .tags
+            .get("layer")
+            .and_then(|value| value.trim().parse::<i8>().ok())
+        {
+            return layer.clamp(-5, 5);
+        }
+
+        if self.is_bridge() {
+            1
+        } else if self.is_tunnel() {
+            -1
+        } else {
+            0
         }
     }
 
+    /// Get the approximate area enclosed by this element in square meters
+    ///
+    /// Open ways, points and degenerate rings have no area.
+    pub fn area_m2(&self) -> f64 {
+        if self.geometry.len() < 4 || self.geometry.first() != self.geometry.last() {
+            return 0.0;
+        }
+
+        // Shoelace formula on a local equirectangular projection
+        let (lat0, lon0) = self.geometry[0];
+        let meters_per_deg_lon = 111_320.0 * lat0.to_radians().cos();
+        let to_local =
+            |(lat, lon): (f64, f64)| ((lon - lon0) * meters_per_deg_lon, (lat - lat0) * 110_540.0);
+        let ring_area = |ring: &[(f64, f64)]| {
+            let doubled: f64 = ring
+                .windows(2)
+                .map(|w| {
+                    let (x1, y1) = to_local(w[0]);
+                    let (x2, y2) = to_local(w[1]);
+                    x1 * y2 - x2 * y1
+                })
+                .sum();
+            doubled.abs() / 2.0
+        };
+
+        let holes: f64 = self.holes.iter().map(|hole| ring_area(hole)).sum();
+        (ring_area(&self.geometry) - holes).max(0.0)
+    }
+
+    /// Check if a location lies inside the element's area, excluding its holes
+    ///
+    /// Uses the even-odd rule over the outline and all holes, so an island
+    /// inside a lake's hole would count as inside again.
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        let crossings = std::iter::once(&self.geometry)
+            .chain(&self.holes)
+            .filter(|ring| ring_contains(ring, lat, lon))
+            .count();
+        crossings % 2 == 1
+    }
+
     /// Get the center point of this element's geometry
     pub fn center_point(&self) -> Option<(f64, f64)> {
         if self.geometry.is_empty() {
@@ -131,41 +232,132 @@ impl OsmElement {
     }
 }
 
+/// Test if a point is inside a ring using ray casting
+pub(crate) fn ring_contains(ring: &[(f64, f64)], lat: f64, lon: f64) -> bool {
+    if ring.is_empty() {
+        return false;
+    }
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+
+    for i in 0..ring.len() {
+        let (lat_i, lon_i) = ring[i];
+        let (lat_j, lon_j) = ring[j];
+
+        if ((lat_i > lat) != (lat_j > lat))
+            && (lon < (lon_j - lon_i) * (lat - lat_i) / (lat_j - lat_i) + lon_i)
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
+
+/// Join way segments into rings by matching their end points
+///
+/// Multipolygon members often split one ring across several ways, in any
+/// direction. Chains that cannot be closed are returned as they are.
+fn assemble_rings(mut segments: Vec<Vec<(f64, f64)>>) -> Vec<Vec<(f64, f64)>> {
+    segments.retain(|segment| !segment.is_empty());
+    let mut rings = Vec::new();
+
+    while let Some(mut ring) = segments.pop() {
+        while ring.first() != ring.last() {
+            let end = *ring.last().unwrap();
+            let Some(index) = segments
+                .iter()
+                .position(|segment| segment.first() == Some(&end) || segment.last() == Some(&end))
+            else {
+                break;
+            };
+            let mut next = segments.swap_remove(index);
+            if next.first() != Some(&end) {
+                next.reverse();
+            }
+            ring.extend(next.into_iter().skip(1));
+        }
+        rings.push(ring);
+    }
+
+    rings
+}
+
 /// Parser for OSM data
 pub struct OsmParser;
 
 impl OsmParser {
     /// Parse OSM data into a list of elements
     pub fn parse(&self, osm_data: &OsmData) -> Result<Vec<OsmElement>> {
+        let mut elements = Vec::new();
+        self.for_each_element(osm_data, |element| {
+            elements.push(element);
+            Ok(())
+        })?;
+        Ok(elements)
+    }
+
+    /// Parse OSM data, handing each element to `callback` as soon as it is read
+    ///
+    /// JSON input is streamed one element at a time, so peak memory stays
+    /// close to the size of the raw data instead of doubling for a full DOM.
+    /// An error returned by `callback` stops parsing and is passed through.
+    /// Returns the number of elements produced.
+    pub fn for_each_element(
+        &self,
+        osm_data: &OsmData,
+        mut callback: impl FnMut(OsmElement) -> Result<()>,
+    ) -> Result<usize> {
         match osm_data.format {
-            OsmDataFormat::Json => self.parse_json(&osm_data.raw_data),
-            OsmDataFormat::Xml => self.parse_xml(&osm_data.raw_data),
+            OsmDataFormat::Json => self.stream_json(&osm_data.raw_data, &mut callback),
+            OsmDataFormat::Xml => {
+                let elements = self.parse_xml(&osm_data.raw_data)?;
+                let count = elements.len();
+                elements.into_iter().try_for_each(callback)?;
+                Ok(count)
+            }
         }
     }
 
-    /// Parse Overpass JSON format
-    fn parse_json(&self, json_data: &str) -> Result<Vec<OsmElement>> {
-        let parsed: Value = serde_json::from_str(json_data)
-            .map_err(|e| OsmTilesError::Parse(format!("Invalid JSON: {}", e)))?;
+    /// Stream Overpass JSON, materializing only one element at a time
+    fn stream_json(
+        &self,
+        json_data: &str,
+        callback: &mut dyn FnMut(OsmElement) -> Result<()>,
+    ) -> Result<usize> {
+        let mut state = StreamState {
+            parser: self,
+            callback,
+            count: 0,
+            found_elements: false,
+            error: None,
+        };
 
-        let elements = parsed
-            .get("elements")
-            .and_then(|e| e.as_array())
-            .ok_or_else(|| OsmTilesError::Parse("No 'elements' array found in JSON".to_string()))?;
+        let mut deserializer = serde_json::Deserializer::from_str(json_data);
+        let result = DocumentSeed(&mut state)
+            .deserialize(&mut deserializer)
+            .and_then(|_| deserializer.end());
 
-        let mut osm_elements = Vec::new();
+        if let Some(error) = state.error {
+            return Err(error);
+        }
+        result.map_err(|e| OsmTilesError::json("OSM JSON", e))?;
 
-        for element in elements {
-            if let Some(osm_element) = self.parse_json_element(element)? {
-                osm_elements.push(osm_element);
-            }
+        if !state.found_elements {
+            return Err(OsmTilesError::Parse(
+                "No 'elements' array found in JSON".to_string(),
+            ));
         }
 
-        Ok(osm_elements)
+        Ok(state.count)
     }
 
     /// Parse a single JSON element
-    fn parse_json_element(&self, element: &Value) -> Result<Option<OsmElement>> {
+    ///
+    /// Multipolygon relations yield one element per outer ring, each with
+    /// the inner rings it contains.
+    pub(crate) fn parse_json_element(&self, element: &Value) -> Result<Vec<OsmElement>> {
         let id = element
             .get("id")
             .and_then(|v| v.as_i64())
@@ -180,7 +372,7 @@ impl OsmParser {
             "node" => OsmElementType::Node,
             "way" => OsmElementType::Way,
             "relation" => OsmElementType::Relation,
-            _ => return Ok(None), // Skip unknown types
+            _ => return Ok(Vec::new()), // Skip unknown types
         };
 
         // Parse tags
@@ -207,20 +399,21 @@ impl OsmParser {
                     .ok_or_else(|| OsmTilesError::Parse("Node missing 'lon'".to_string()))?;
                 vec![(lat, lon)]
             }
+            OsmElementType::Relation
+                if matches!(
+                    tags.get("type").map(String::as_str),
+                    Some("multipolygon" | "boundary")
+                ) && element.get("geometry").is_none() =>
+            {
+                if let Some(members) = element.get("members").and_then(|v| v.as_array()) {
+                    return self.parse_multipolygon(id, tags, members);
+                }
+                Vec::new()
+            }
             OsmElementType::Way | OsmElementType::Relation => {
                 // For ways and relations, use geometry array
                 if let Some(geometry_array) = element.get("geometry").and_then(|v| v.as_array()) {
-                    let mut coords = Vec::new();
-                    for coord in geometry_array {
-                        let lat = coord.get("lat").and_then(|v| v.as_f64()).ok_or_else(|| {
-                            OsmTilesError::Parse("Geometry point missing 'lat'".to_string())
-                        })?;
-                        let lon = coord.get("lon").and_then(|v| v.as_f64()).ok_or_else(|| {
-                            OsmTilesError::Parse("Geometry point missing 'lon'".to_string())
-                        })?;
-                        coords.push((lat, lon));
-                    }
-                    coords
+                    Self::parse_geometry(geometry_array)?
                 } else {
                     // If no geometry, try to use lat/lon (for some nodes)
                     if let (Some(lat), Some(lon)) = (
@@ -237,15 +430,78 @@ impl OsmParser {
 
         // Skip elements without geometry or tags
         if geometry.is_empty() && tags.is_empty() {
-            return Ok(None);
+            return Ok(Vec::new());
         }
 
-        Ok(Some(OsmElement {
+        Ok(vec![OsmElement {
             id,
             element_type,
             tags,
             geometry,
-        }))
+            holes: Vec::new(),
+        }])
+    }
+
+    /// Parse a `geometry` array of `{"lat", "lon"}` points
+    fn parse_geometry(geometry_array: &[Value]) -> Result<Vec<(f64, f64)>> {
+        geometry_array
+            .iter()
+            .map(|coord| {
+                let lat = coord.get("lat").and_then(|v| v.as_f64()).ok_or_else(|| {
+                    OsmTilesError::Parse("Geometry point missing 'lat'".to_string())
+                })?;
+                let lon = coord.get("lon").and_then(|v| v.as_f64()).ok_or_else(|| {
+                    OsmTilesError::Parse("Geometry point missing 'lon'".to_string())
+                })?;
+                Ok((lat, lon))
+            })
+            .collect()
+    }
+
+    /// Build multipolygon or boundary areas from the `outer` and `inner` way members of a relation
+    fn parse_multipolygon(
+        &self,
+        id: i64,
+        tags: HashMap<String, String>,
+        members: &[Value],
+    ) -> Result<Vec<OsmElement>> {
+        let (mut outer, mut inner) = (Vec::new(), Vec::new());
+        for member in members {
+            if member.get("type").and_then(|v| v.as_str()) != Some("way") {
+                continue;
+            }
+            let Some(geometry_array) = member.get("geometry").and_then(|v| v.as_array()) else {
+                continue;
+            };
+            let geometry = Self::parse_geometry(geometry_array)?;
+            // An empty role is an old way of tagging outer rings
+            match member.get("role").and_then(|v| v.as_str()).unwrap_or("") {
+                "inner" => inner.push(geometry),
+                _ => outer.push(geometry),
+            }
+        }
+
+        let mut elements: Vec<OsmElement> = assemble_rings(outer)
+            .into_iter()
+            .map(|geometry| OsmElement {
+                id,
+                element_type: OsmElementType::Relation,
+                tags: tags.clone(),
+                geometry,
+                holes: Vec::new(),
+            })
+            .collect();
+        for hole in assemble_rings(inner) {
+            let &(lat, lon) = &hole[0];
+            if let Some(element) = elements
+                .iter_mut()
+                .find(|element| ring_contains(&element.geometry, lat, lon))
+            {
+                element.holes.push(hole);
+            }
+        }
+
+        Ok(elements)
     }
 
     /// Parse XML format (basic implementation)
@@ -258,6 +514,210 @@ impl OsmParser {
     }
 }
 
+/// State shared by the streaming JSON visitors
+struct StreamState<'a> {
+    parser: &'a OsmParser,
+    callback: &'a mut dyn FnMut(OsmElement) -> Result<()>,
+    count: usize,
+    found_elements: bool,
+    /// Error raised outside of serde (bad element or callback), passed through unchanged
+    error: Option<OsmTilesError>,
+}
+
+/// Visits the top-level Overpass document, skipping everything except `elements`
+struct DocumentSeed<'s, 'a>(&'s mut StreamState<'a>);
+
+impl<'de> DeserializeSeed<'de> for DocumentSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DocumentSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an Overpass JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        while let Some(key) = map.next_key::<Cow<'de, str>>()? {
+            if key == "elements" {
+                self.0.found_elements = true;
+                map.next_value_seed(ElementsSeed(&mut *self.0))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Visits the `elements` array, parsing and handing out one element at a time
+struct ElementsSeed<'s, 'a>(&'s mut StreamState<'a>);
+
+impl<'de> DeserializeSeed<'de> for ElementsSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ElementsSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of OSM elements")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let state = self.0;
+        while let Some(value) = seq.next_element::<Value>()? {
+            let result = state
+                .parser
+                .parse_json_element(&value)
+                .and_then(|elements| {
+                    elements.into_iter().try_for_each(|element| {
+                        state.count += 1;
+                        (state.callback)(element)
+                    })
+                });
+
+            if let Err(error) = result {
+                state.error = Some(error);
+                return Err(de::Error::custom("parsing stopped"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Push parser splitting Overpass JSON into elements as chunks of it arrive
+///
+/// Only the element currently being received is buffered. Chunks may end
+/// anywhere, including inside strings and multi-byte characters.
+pub(crate) struct JsonElementStream<'p> {
+    parser: &'p OsmParser,
+    /// Nesting depth of objects and arrays
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Last string read at the top level, the key of the value that follows
+    key: Vec<u8>,
+    /// Whether the scanner is inside the top-level `elements` array
+    in_elements: bool,
+    found_elements: bool,
+    /// Bytes of the element being received, empty between elements
+    element: Vec<u8>,
+    count: usize,
+}
+
+impl<'p> JsonElementStream<'p> {
+    pub(crate) fn new(parser: &'p OsmParser) -> Self {
+        Self {
+            parser,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            key: Vec::new(),
+            in_elements: false,
+            found_elements: false,
+            element: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Scan a chunk, passing each element completed by it to `callback`
+    pub(crate) fn feed(
+        &mut self,
+        chunk: &[u8],
+        callback: &mut dyn FnMut(OsmElement) -> Result<()>,
+    ) -> Result<()> {
+        for &byte in chunk {
+            let in_element = !self.element.is_empty();
+            if in_element {
+                self.element.push(byte);
+            }
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                if self.in_string && self.depth == 1 {
+                    self.key.push(byte);
+                }
+                continue;
+            }
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    if self.depth == 1 {
+                        self.key.clear();
+                    }
+                }
+                b'{' | b'[' => {
+                    if byte == b'{' && self.in_elements && self.depth == 2 {
+                        self.element.push(byte);
+                    }
+                    if byte == b'[' && self.depth == 1 && self.key == b"elements" {
+                        self.in_elements = true;
+                        self.found_elements = true;
+                    }
+                    self.depth += 1;
+                }
+                b'}' | b']' => {
+                    self.depth = self.depth.checked_sub(1).ok_or_else(|| {
+                        OsmTilesError::Parse("Unbalanced brackets in OSM JSON".to_string())
+                    })?;
+                    if in_element && self.depth == 2 {
+                        self.emit(callback)?;
+                    } else if self.in_elements && self.depth == 1 {
+                        self.in_elements = false;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the document is complete, returning the number of elements parsed
+    pub(crate) fn finish(self) -> Result<usize> {
+        if self.depth != 0 || self.in_string {
+            return Err(OsmTilesError::Parse("Truncated OSM JSON".to_string()));
+        }
+        if !self.found_elements {
+            return Err(OsmTilesError::Parse(
+                "No 'elements' array found in JSON".to_string(),
+            ));
+        }
+        Ok(self.count)
+    }
+
+    /// Parse the buffered element and hand out what it yields
+    fn emit(&mut self, callback: &mut dyn FnMut(OsmElement) -> Result<()>) -> Result<()> {
+        let value: Value = serde_json::from_slice(&self.element)
+            .map_err(|e| OsmTilesError::json("OSM JSON element", e))?;
+        self.element.clear();
+        for element in self.parser.parse_json_element(&value)? {
+            self.count += 1;
+            callback(element)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -347,11 +807,114 @@ mod tests {
         assert_eq!(building.geometry.len(), 5); // Closed polygon
     }
 
+    #[test]
+    fn test_streaming_parse() {
+        let mut osm_data = create_test_osm_data();
+        // Metadata before and after the elements array is skipped
+        osm_data.raw_data = format!(
+            r#"{{"version": 0.6, "osm3s": {{"copyright": "ODbL"}}, {}, "remark": null}}"#,
+            osm_data
+                .raw_data
+                .trim()
+                .trim_start_matches('{')
+                .trim_end_matches('}')
+        );
+
+        let mut ids = Vec::new();
+        let count = OsmParser
+            .for_each_element(&osm_data, |element| {
+                ids.push(element.id);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(ids, vec![1001, 2001, 3001]);
+
+        // Callback errors stop parsing and are passed through unchanged
+        let mut seen = 0;
+        let error = OsmParser
+            .for_each_element(&osm_data, |_| {
+                seen += 1;
+                Err(OsmTilesError::GridGeneration("stop".to_string()))
+            })
+            .unwrap_err();
+        assert_eq!(seen, 1);
+        assert!(matches!(error, OsmTilesError::GridGeneration(_)));
+    }
+
+    #[test]
+    fn test_element_stream_chunks() {
+        // Keys and braces inside strings must not confuse the scanner
+        let raw_data = r#"{"osm3s": {"note": "see \"elements\": [{"}, "elements": [
+            {"type": "node", "id": 1, "lat": 52.5, "lon": 13.4, "tags": {"amenity": "café", "name": "{Ecke} \\"}},
+            {"type": "way", "id": 2, "tags": {"highway": "residential"}, "geometry": [{"lat": 52.5, "lon": 13.4}, {"lat": 52.6, "lon": 13.5}]}
+        ], "remark": "]"}"#;
+
+        for chunk_size in [1, 7, raw_data.len()] {
+            let mut stream = JsonElementStream::new(&OsmParser);
+            let mut elements = Vec::new();
+            for chunk in raw_data.as_bytes().chunks(chunk_size) {
+                stream
+                    .feed(chunk, &mut |element| {
+                        elements.push(element);
+                        Ok(())
+                    })
+                    .unwrap();
+            }
+            assert_eq!(stream.finish().unwrap(), 2);
+            assert_eq!(elements[0].tags["name"], "{Ecke} \\");
+            assert_eq!(elements[0].tags["amenity"], "café");
+            assert_eq!(elements[1].geometry.len(), 2);
+        }
+
+        let mut stream = JsonElementStream::new(&OsmParser);
+        stream
+            .feed(br#"{"elements": [{"type": "#, &mut |_| Ok(()))
+            .unwrap();
+        assert!(
+            stream
+                .finish()
+                .unwrap_err()
+                .to_string()
+                .contains("Truncated")
+        );
+        let mut stream = JsonElementStream::new(&OsmParser);
+        stream
+            .feed(br#"{"version": 0.6}"#, &mut |_| Ok(()))
+            .unwrap();
+        assert!(
+            stream
+                .finish()
+                .unwrap_err()
+                .to_string()
+                .contains("No 'elements'")
+        );
+    }
+
+    #[test]
+    fn test_streaming_parse_errors() {
+        let mut osm_data = create_test_osm_data();
+        for (raw_data, expected) in [
+            (r#"{"version": 0.6}"#, "No 'elements'"),
+            (r#"{"elements": {}}"#, "OSM JSON at line 1"),
+            (r#"{"elements": []} trailing"#, "OSM JSON at line 1"),
+            (
+                r#"{"elements": [{"type": "node", "lat": 1.0}]}"#,
+                "missing 'id'",
+            ),
+        ] {
+            osm_data.raw_data = raw_data.to_string();
+            let error = OsmParser.parse(&osm_data).unwrap_err().to_string();
+            assert!(error.contains(expected), "{raw_data}: {error}");
+        }
+    }
+
     #[test]
     fn test_tile_type_mapping() {
         let mut element = OsmElement {
             id: 1,
             element_type: OsmElementType::Way,
+            holes: Vec::new(),
             tags: HashMap::new(),
             geometry: vec![(52.5, 13.4)],
         };
@@ -390,11 +953,63 @@ mod tests {
         assert_eq!(element.to_tile_type(), TileType::Amenity);
     }
 
+    #[test]
+    fn test_element_area() {
+        // Roughly 100m x 100m square in Berlin
+        let square = OsmElement {
+            id: 1,
+            element_type: OsmElementType::Way,
+            holes: Vec::new(),
+            tags: HashMap::new(),
+            geometry: vec![
+                (52.5, 13.4),
+                (52.5, 13.40148),
+                (52.5009, 13.40148),
+                (52.5009, 13.4),
+                (52.5, 13.4),
+            ],
+        };
+        let area = square.area_m2();
+        assert!((area - 10_000.0).abs() < 200.0, "area was {area}");
+        assert_eq!(square.to_tile_metadata().element_area_m2, area);
+
+        let line = OsmElement {
+            geometry: square.geometry[..3].to_vec(),
+            ..square
+        };
+        assert_eq!(line.area_m2(), 0.0);
+    }
+
+    #[test]
+    fn test_element_layer() {
+        let element = |tags: &[(&str, &str)]| OsmElement {
+            id: 1,
+            element_type: OsmElementType::Way,
+            holes: Vec::new(),
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            geometry: vec![(52.0, 13.0), (52.1, 13.1)],
+        };
+
+        assert_eq!(element(&[]).layer(), 0);
+        assert_eq!(element(&[("bridge", "viaduct")]).layer(), 1);
+        assert_eq!(element(&[("tunnel", "yes"), ("layer", "-2")]).layer(), -2);
+        assert_eq!(element(&[("bridge", "no")]).layer(), 0);
+        assert_eq!(element(&[("layer", "99")]).layer(), 5);
+
+        let metadata = element(&[("tunnel", "yes")]).to_tile_metadata();
+        assert!(metadata.tunnel && !metadata.bridge);
+        assert_eq!(metadata.layer, -1);
+    }
+
     #[test]
     fn test_element_center_point() {
         let element = OsmElement {
             id: 1,
             element_type: OsmElementType::Way,
+            holes: Vec::new(),
             tags: HashMap::new(),
             geometry: vec![(52.0, 13.0), (52.1, 13.1), (52.2, 13.2)],
         };
@@ -409,6 +1024,7 @@ mod tests {
         let element = OsmElement {
             id: 1,
             element_type: OsmElementType::Way,
+            holes: Vec::new(),
             tags: HashMap::new(),
             geometry: vec![(52.0, 13.0), (52.2, 13.2), (52.1, 13.1)],
         };
@@ -416,4 +1032,57 @@ mod tests {
         let bbox = element.bounding_box().unwrap();
         assert_eq!(bbox, (52.0, 13.0, 52.2, 13.2)); // (min_lat, min_lon, max_lat, max_lon)
     }
+
+    #[test]
+    fn test_ring_contains() {
+        let square = vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)];
+        assert!(ring_contains(&square, 0.5, 0.5));
+        assert!(!ring_contains(&square, 1.5, 0.5));
+    }
+
+    #[test]
+    fn test_multipolygon_with_courtyard() {
+        // The outer ring is split across two ways, one of them drawn backwards
+        let json_data = r#"{
+            "elements": [{
+                "type": "relation",
+                "id": 9,
+                "tags": {"type": "multipolygon", "building": "yes"},
+                "members": [
+                    {"type": "way", "ref": 1, "role": "outer", "geometry": [
+                        {"lat": 0.0, "lon": 0.0}, {"lat": 0.0, "lon": 3.0}, {"lat": 3.0, "lon": 3.0}
+                    ]},
+                    {"type": "way", "ref": 2, "role": "outer", "geometry": [
+                        {"lat": 0.0, "lon": 0.0}, {"lat": 3.0, "lon": 0.0}, {"lat": 3.0, "lon": 3.0}
+                    ]},
+                    {"type": "way", "ref": 3, "role": "inner", "geometry": [
+                        {"lat": 1.0, "lon": 1.0}, {"lat": 1.0, "lon": 2.0}, {"lat": 2.0, "lon": 2.0},
+                        {"lat": 2.0, "lon": 1.0}, {"lat": 1.0, "lon": 1.0}
+                    ]},
+                    {"type": "node", "ref": 4, "role": "label", "lat": 1.5, "lon": 1.5}
+                ]
+            }]
+        }"#;
+        let osm_data = OsmData {
+            raw_data: json_data.to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: crate::BoundingBox::new(0.0, 0.0, 3.0, 3.0),
+            metadata: OsmMetadata::new("test", "test"),
+        };
+
+        let elements = OsmParser.parse(&osm_data).unwrap();
+        assert_eq!(elements.len(), 1);
+        let building = &elements[0];
+        assert_eq!(building.geometry.len(), 5);
+        assert_eq!(building.geometry.first(), building.geometry.last());
+        assert_eq!(building.holes.len(), 1);
+
+        assert!(building.contains(0.5, 1.5));
+        assert!(!building.contains(1.5, 1.5));
+        let solid = OsmElement {
+            holes: Vec::new(),
+            ..building.clone()
+        };
+        assert!((building.area_m2() / solid.area_m2() - 8.0 / 9.0).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,414 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use super::{MovementCosts, Tile, TileGrid};
+
+/// Neighbor offsets, orthogonal moves first
+const NEIGHBORS: [(isize, isize); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+/// A path across a tile grid found by [`TileGrid::find_path`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TilePath {
+    /// Every tile (x, y) from start to goal, both included
+    pub tiles: Vec<(usize, usize)>,
+    /// Total cost, in meters of flat paved road
+    pub cost: f32,
+    /// Tiles where the smoothed path turns, from start to goal
+    pub waypoints: Vec<(usize, usize)>,
+    /// Geographic centers (lat, lon) of the waypoints
+    pub polyline: Vec<(f64, f64)>,
+}
+
+/// Open-set entry ordered so the heap pops the lowest estimate first
+struct Candidate {
+    estimate: f32,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl TileGrid {
+    /// Find the cheapest path over [navigable](super::TileType::is_navigable) tiles
+    pub fn find_navigable_path(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+    ) -> Option<TilePath> {
+        self.find_path(start, goal, |tile| {
+            tile.tile_type.is_navigable().then_some(1.0)
+        })
+    }
+
+    /// Find the cheapest path with A*, moving to any of the 8 neighbors
+    ///
+    /// `cost_fn` gives the cost of entering a tile per meter, or `None` if it
+    /// is impassable. Like [`TravelMode::type_cost`](super::TravelMode::type_cost),
+    /// costs should be at least 1.0 (a paved road); cheaper tiles still
+    /// yield valid paths, but not necessarily the cheapest one. Diagonal
+    /// moves never cut the corner of an impassable tile.
+    pub fn find_path(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+        cost_fn: impl Fn(&Tile) -> Option<f32>,
+    ) -> Option<TilePath> {
+        let passable = |x: usize, y: usize| self.get_tile(x, y).and_then(&cost_fn).is_some();
+        let step = |from: (usize, usize), to: (usize, usize)| {
+            let cost = cost_fn(self.get_tile(to.0, to.1)?)?;
+            Some(self.step_distance(from, to) * cost)
+        };
+        self.search(start, goal, step, passable, 1.0)
+    }
+
+    /// Find the cheapest path for the travel mode, surfaces and slopes of `costs`
+    pub fn find_path_with_costs(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+        costs: &MovementCosts,
+    ) -> Option<TilePath> {
+        let mode = costs.mode();
+        let passable = |x: usize, y: usize| {
+            self.get_tile(x, y)
+                .is_some_and(|tile| tile.is_navigable_for(mode))
+        };
+        // Gentle descents are the cheapest moves, so they bound the heuristic
+        let min_factor = mode.slope_factor(-0.05).unwrap_or(1.0).min(1.0);
+        self.search(
+            start,
+            goal,
+            |from, to| costs.step_cost(self, from, to),
+            passable,
+            min_factor,
+        )
+    }
+
+    /// Distance in meters between two tile centers
+    fn step_distance(&self, from: (usize, usize), to: (usize, usize)) -> f32 {
+        let dx = from.0.abs_diff(to.0) as f32 * self.meters_per_tile_x();
+        let dy = from.1.abs_diff(to.1) as f32 * self.meters_per_tile_y();
+        dx.hypot(dy)
+    }
+
+    fn search(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+        step: impl Fn((usize, usize), (usize, usize)) -> Option<f32>,
+        passable: impl Fn(usize, usize) -> bool,
+        heuristic_factor: f32,
+    ) -> Option<TilePath> {
+        let (width, height) = self.dimensions();
+        if !passable(start.0, start.1) || !passable(goal.0, goal.1) {
+            return None;
+        }
+
+        let heuristic = |position| self.step_distance(position, goal) * heuristic_factor;
+        let mut costs = vec![f32::INFINITY; width * height];
+        let mut came_from = vec![usize::MAX; width * height];
+        let mut open = BinaryHeap::new();
+        let start_index = start.1 * width + start.0;
+        costs[start_index] = 0.0;
+        open.push(Candidate {
+            estimate: heuristic(start),
+            index: start_index,
+        });
+
+        while let Some(Candidate { estimate, index }) = open.pop() {
+            let current = (index % width, index / width);
+            if current == goal {
+                let mut tiles = vec![current];
+                let mut index = index;
+                while came_from[index] != usize::MAX {
+                    index = came_from[index];
+                    tiles.push((index % width, index / width));
+                }
+                tiles.reverse();
+                return Some(self.path(tiles, costs[goal.1 * width + goal.0], step, passable));
+            }
+            // Skip entries superseded by a cheaper route
+            if estimate > costs[index] + heuristic(current) {
+                continue;
+            }
+
+            for (dx, dy) in NEIGHBORS {
+                let (Some(x), Some(y)) = (
+                    current.0.checked_add_signed(dx),
+                    current.1.checked_add_signed(dy),
+                ) else {
+                    continue;
+                };
+                if x >= width || y >= height || !passable(x, y) {
+                    continue;
+                }
+                if dx != 0 && dy != 0 && !(passable(x, current.1) && passable(current.0, y)) {
+                    continue;
+                }
+                let Some(cost) = step(current, (x, y)) else {
+                    continue;
+                };
+
+                let neighbor = y * width + x;
+                let total = costs[index] + cost;
+                if total < costs[neighbor] {
+                    costs[neighbor] = total;
+                    came_from[neighbor] = index;
+                    open.push(Candidate {
+                        estimate: total + heuristic((x, y)),
+                        index: neighbor,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Build the result, smoothing the tile path by skipping waypoints in line of sight
+    ///
+    /// A straight shortcut replaces part of the path only if it is passable
+    /// and no more expensive than the tiles it skips, so smoothing never
+    /// crosses e.g. water the path went around.
+    fn path(
+        &self,
+        tiles: Vec<(usize, usize)>,
+        cost: f32,
+        step: impl Fn((usize, usize), (usize, usize)) -> Option<f32>,
+        passable: impl Fn(usize, usize) -> bool,
+    ) -> TilePath {
+        // Cost of the path from the start to each of its tiles
+        let mut reached = vec![0.0; tiles.len()];
+        for index in 1..tiles.len() {
+            reached[index] =
+                reached[index - 1] + step(tiles[index - 1], tiles[index]).unwrap_or(f32::INFINITY);
+        }
+        let shortcut = |from: usize, to: usize| {
+            // Allow for rounding when the shortcut runs along the path
+            let original = (reached[to] - reached[from]) * 1.001;
+            self.line_cost(tiles[from], tiles[to], &step, &passable)
+                .is_some_and(|cost| cost <= original)
+        };
+
+        let mut waypoints = vec![tiles[0]];
+        let mut anchor = 0;
+        while anchor + 1 < tiles.len() {
+            // Walk ahead as far as the straight line from the anchor stays passable and cheap
+            let mut next = anchor + 1;
+            while next + 1 < tiles.len() && shortcut(anchor, next + 1) {
+                next += 1;
+            }
+            waypoints.push(tiles[next]);
+            anchor = next;
+        }
+
+        let polyline = waypoints
+            .iter()
+            .filter_map(|&(x, y)| self.grid_to_geo(x, y))
+            .collect();
+        TilePath {
+            tiles,
+            cost,
+            waypoints,
+            polyline,
+        }
+    }
+
+    /// Cost of the straight segment between two tile centers, if every tile it touches is passable
+    ///
+    /// The segment is walked as a supercover line: it visits every tile it
+    /// crosses, and passing exactly through a corner needs both tiles beside
+    /// it, like diagonal moves of the search. Each tile is charged the cost
+    /// per meter of entering it for the length of the segment inside it; the
+    /// first tile is charged like the tile entered after it.
+    fn line_cost(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        step: impl Fn((usize, usize), (usize, usize)) -> Option<f32>,
+        passable: impl Fn(usize, usize) -> bool,
+    ) -> Option<f32> {
+        if !passable(from.0, from.1) {
+            return None;
+        }
+        let (nx, ny) = (from.0.abs_diff(to.0), from.1.abs_diff(to.1));
+        let sx = if to.0 >= from.0 { 1 } else { -1 };
+        let sy = if to.1 >= from.1 { 1 } else { -1 };
+        let length = self.step_distance(from, to);
+
+        let mut current = from;
+        // Position along the segment where the current tile was entered, and its cost per meter
+        let mut entered = 0.0;
+        let mut rate = None;
+        let mut cost = 0.0;
+        let (mut ix, mut iy) = (0, 0);
+        while ix < nx || iy < ny {
+            // The next vertical and horizontal tile borders are crossed at (2i + 1) / 2n
+            let decision = ((2 * ix + 1) * ny) as i64 - ((2 * iy + 1) * nx) as i64;
+            let (next, crossed) = match decision.cmp(&0) {
+                Ordering::Less => {
+                    let crossed = (2 * ix + 1) as f32 / (2 * nx) as f32;
+                    ix += 1;
+                    ((current.0.checked_add_signed(sx)?, current.1), crossed)
+                }
+                Ordering::Greater => {
+                    let crossed = (2 * iy + 1) as f32 / (2 * ny) as f32;
+                    iy += 1;
+                    ((current.0, current.1.checked_add_signed(sy)?), crossed)
+                }
+                Ordering::Equal => {
+                    let crossed = (2 * ix + 1) as f32 / (2 * nx) as f32;
+                    let x = current.0.checked_add_signed(sx)?;
+                    let y = current.1.checked_add_signed(sy)?;
+                    if !(passable(x, current.1) && passable(current.0, y)) {
+                        return None;
+                    }
+                    ix += 1;
+                    iy += 1;
+                    ((x, y), crossed)
+                }
+            };
+            if !passable(next.0, next.1) {
+                return None;
+            }
+            let next_rate = step(current, next)? / self.step_distance(current, next);
+            cost += (crossed - entered) * length * rate.unwrap_or(next_rate);
+            current = next;
+            entered = crossed;
+            rate = Some(next_rate);
+        }
+        Some(cost + (1.0 - entered) * length * rate.unwrap_or(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileType, TravelMode};
+
+    /// A 10x10 grid of open ground with a wall of buildings at x = 5, open at the bottom row
+    fn walled_grid() -> TileGrid {
+        let mut grid = TileGrid::new(10, 10, BoundingBox::new(52.0, 13.0, 52.001, 13.0016), 10.0);
+        for y in 0..9 {
+            grid.set_tile(5, y, Tile::new(TileType::Building)).unwrap();
+        }
+        grid
+    }
+
+    #[test]
+    fn test_find_path_around_wall() {
+        let grid = walled_grid();
+        let path = grid.find_navigable_path((0, 0), (9, 0)).unwrap();
+
+        assert_eq!(path.tiles.first(), Some(&(0, 0)));
+        assert_eq!(path.tiles.last(), Some(&(9, 0)));
+        assert!(path.tiles.contains(&(5, 9)));
+        assert!(path.tiles.iter().all(|&(x, y)| x != 5 || y == 9));
+        assert!(path.cost > 2.0 * 8.0 * grid.meters_per_tile_y());
+
+        // Smoothing keeps the start, the goal and the turns around the gap
+        assert!(path.waypoints.len() < path.tiles.len());
+        assert_eq!(path.waypoints.first(), Some(&(0, 0)));
+        assert_eq!(path.waypoints.last(), Some(&(9, 0)));
+        assert_eq!(path.polyline.len(), path.waypoints.len());
+        assert_eq!(path.polyline[0], grid.grid_to_geo(0, 0).unwrap());
+
+        assert_eq!(grid.find_navigable_path((0, 0), (5, 0)), None);
+        assert_eq!(grid.find_navigable_path((5, 0), (0, 0)), None);
+    }
+
+    #[test]
+    fn test_smoothing_keeps_detours() {
+        // Shortcuts may not cut the corner of a building
+        let mut grid = TileGrid::new(3, 3, BoundingBox::new(52.0, 13.0, 52.0003, 13.0005), 10.0);
+        grid.set_tile(1, 0, Tile::new(TileType::Building)).unwrap();
+        let path = grid.find_navigable_path((0, 0), (2, 2)).unwrap();
+        assert!(path.waypoints.len() > 2);
+
+        // Nor cross water the path went around
+        let mut grid = TileGrid::new(10, 10, BoundingBox::new(52.0, 13.0, 52.001, 13.0016), 10.0);
+        for x in 3..7 {
+            for y in 0..8 {
+                grid.set_tile(x, y, Tile::new(TileType::Water)).unwrap();
+            }
+        }
+        let path = grid
+            .find_path((0, 0), (9, 0), |tile| match tile.tile_type {
+                TileType::Water => Some(10.0),
+                _ => Some(1.0),
+            })
+            .unwrap();
+        assert!(
+            path.tiles
+                .iter()
+                .all(|&(x, y)| grid.get_tile(x, y).unwrap().tile_type != TileType::Water)
+        );
+        assert!(path.waypoints.len() > 2);
+
+        // The smoothed polyline costs no more than the tiles it replaces
+        let step = |from: (usize, usize), to: (usize, usize)| {
+            let rate = match grid.get_tile(to.0, to.1)?.tile_type {
+                TileType::Water => 10.0,
+                _ => 1.0,
+            };
+            Some(grid.step_distance(from, to) * rate)
+        };
+        let smoothed: f32 = path
+            .waypoints
+            .windows(2)
+            .map(|pair| grid.line_cost(pair[0], pair[1], step, |_, _| true).unwrap())
+            .sum();
+        assert!(smoothed <= path.cost * 1.001);
+    }
+
+    #[test]
+    fn test_custom_costs() {
+        let mut grid = walled_grid();
+        grid.set_tile(5, 9, Tile::new(TileType::Water)).unwrap();
+        assert_eq!(grid.find_navigable_path((0, 0), (9, 0)), None);
+
+        // Wading through water is allowed, but expensive
+        let path = grid
+            .find_path((0, 9), (9, 9), |tile| match tile.tile_type {
+                TileType::Water => Some(10.0),
+                TileType::Building => None,
+                _ => Some(1.0),
+            })
+            .unwrap();
+        assert_eq!(path.tiles.len(), 10);
+        assert_eq!(path.waypoints, vec![(0, 9), (9, 9)]);
+
+        let walking = MovementCosts::new(TravelMode::Walking);
+        let driving = MovementCosts::new(TravelMode::Driving);
+        assert!(
+            grid.find_path_with_costs((0, 0), (4, 4), &walking)
+                .is_some()
+        );
+        assert_eq!(grid.find_path_with_costs((0, 0), (4, 4), &driving), None);
+    }
+}
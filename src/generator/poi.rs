@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{OsmElement, OsmElementType, TileGrid};
+
+/// Tag keys whose nodes are collected as points of interest
+pub const POI_CATEGORIES: [&str; 3] = ["amenity", "tourism", "shop"];
+
+/// A named point of interest kept as a discrete object next to the grid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Poi {
+    /// OSM node ID
+    pub osm_id: i64,
+    /// Value of the `name` tag
+    pub name: Option<String>,
+    /// Tag key the POI was collected for (e.g., "amenity")
+    pub category: String,
+    /// Value of the category tag (e.g., "cafe")
+    pub kind: String,
+    /// Latitude of the node
+    pub lat: f64,
+    /// Longitude of the node
+    pub lon: f64,
+    /// Grid coordinates (x, y) of the tile containing the node
+    pub grid_pos: (usize, usize),
+    /// All OSM tags of the node
+    pub tags: HashMap<String, String>,
+}
+
+impl Poi {
+    /// Build a POI from an amenity, tourism or shop node inside the grid
+    pub fn from_element(element: &OsmElement, grid: &TileGrid) -> Option<Self> {
+        if element.element_type != OsmElementType::Node {
+            return None;
+        }
+        let (category, kind) = POI_CATEGORIES
+            .iter()
+            .find_map(|key| element.tags.get(*key).map(|value| (*key, value)))?;
+        let &(lat, lon) = element.geometry.first()?;
+        let grid_pos = grid.geo_to_grid(lat, lon)?;
+
+        Some(Self {
+            osm_id: element.id,
+            name: element.tags.get("name").cloned(),
+            category: category.to_string(),
+            kind: kind.clone(),
+            lat,
+            lon,
+            grid_pos,
+            tags: element.tags.clone(),
+        })
+    }
+
+    /// Get the distance in meters from this POI to a geographic position
+    pub fn distance_to(&self, lat: f64, lon: f64) -> f64 {
+        crate::geoutil::distance_m((self.lat, self.lon), (lat, lon))
+    }
+}
+
+/// Points of interest collected while generating a grid
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoiIndex {
+    /// POIs in the order they were parsed
+    pois: Vec<Poi>,
+}
+
+impl PoiIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a POI to the index
+    pub fn insert(&mut self, poi: Poi) {
+        self.pois.push(poi);
+    }
+
+    /// Get the number of POIs
+    pub fn len(&self) -> usize {
+        self.pois.len()
+    }
+
+    /// Check whether the index holds no POIs
+    pub fn is_empty(&self) -> bool {
+        self.pois.is_empty()
+    }
+
+    /// Iterate over all POIs
+    pub fn iter(&self) -> impl Iterator<Item = &Poi> {
+        self.pois.iter()
+    }
+
+    /// Get a POI by its OSM node ID
+    pub fn get(&self, osm_id: i64) -> Option<&Poi> {
+        self.pois.iter().find(|poi| poi.osm_id == osm_id)
+    }
+
+    /// Get all POIs of a category (e.g., "shop")
+    pub fn of_category(&self, category: &str) -> Vec<&Poi> {
+        self.pois
+            .iter()
+            .filter(|poi| poi.category == category)
+            .collect()
+    }
+
+    /// Get all POIs of a category and kind (e.g., "amenity", "cafe")
+    pub fn of_kind(&self, category: &str, kind: &str) -> Vec<&Poi> {
+        self.pois
+            .iter()
+            .filter(|poi| poi.category == category && poi.kind == kind)
+            .collect()
+    }
+
+    /// Get all POIs carrying a name
+    pub fn named(&self) -> Vec<&Poi> {
+        self.pois.iter().filter(|poi| poi.name.is_some()).collect()
+    }
+
+    /// Get all POIs at the given grid coordinates
+    pub fn at_tile(&self, x: usize, y: usize) -> Vec<&Poi> {
+        self.pois
+            .iter()
+            .filter(|poi| poi.grid_pos == (x, y))
+            .collect()
+    }
+
+    /// Get the POIs within `radius_m` meters of a position, nearest first
+    pub fn within_radius(&self, lat: f64, lon: f64, radius_m: f64) -> Vec<&Poi> {
+        let mut found: Vec<(f64, &Poi)> = self
+            .pois
+            .iter()
+            .map(|poi| (poi.distance_to(lat, lon), poi))
+            .filter(|(distance, _)| *distance <= radius_m)
+            .collect();
+        found.sort_by(|a, b| a.0.total_cmp(&b.0));
+        found.into_iter().map(|(_, poi)| poi).collect()
+    }
+
+    /// Get the POI nearest to a position
+    pub fn nearest(&self, lat: f64, lon: f64) -> Option<&Poi> {
+        self.pois
+            .iter()
+            .min_by(|a, b| a.distance_to(lat, lon).total_cmp(&b.distance_to(lat, lon)))
+    }
+
+    /// Move every POI to the row it occupies after the grid rows were flipped
+    pub(crate) fn flip_rows(&mut self, height: usize) {
+        for poi in &mut self.pois {
+            poi.grid_pos.1 = height - 1 - poi.grid_pos.1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoundingBox;
+
+    fn node(id: i64, lat: f64, lon: f64, tags: &[(&str, &str)]) -> OsmElement {
+        OsmElement {
+            id,
+            element_type: OsmElementType::Node,
+            holes: Vec::new(),
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            geometry: vec![(lat, lon)],
+        }
+    }
+
+    fn test_index() -> PoiIndex {
+        let grid = TileGrid::new(100, 100, BoundingBox::new(52.0, 13.0, 52.1, 13.1), 10.0);
+        let elements = [
+            node(
+                1,
+                52.05,
+                13.05,
+                &[("amenity", "cafe"), ("name", "Kiez Kaffee")],
+            ),
+            node(2, 52.051, 13.05, &[("shop", "bakery")]),
+            node(
+                3,
+                52.09,
+                13.09,
+                &[("tourism", "museum"), ("name", "Museum")],
+            ),
+            node(4, 52.05, 13.05, &[("highway", "bus_stop")]),
+            node(5, 53.0, 13.05, &[("amenity", "cafe")]),
+        ];
+
+        let mut index = PoiIndex::new();
+        for element in &elements {
+            if let Some(poi) = Poi::from_element(element, &grid) {
+                index.insert(poi);
+            }
+        }
+        index
+    }
+
+    #[test]
+    fn test_collect_pois() {
+        let index = test_index();
+        // Nodes without a POI tag or outside the grid are skipped
+        assert_eq!(index.len(), 3);
+
+        let cafe = index.get(1).unwrap();
+        assert_eq!(cafe.name.as_deref(), Some("Kiez Kaffee"));
+        assert_eq!(
+            (cafe.category.as_str(), cafe.kind.as_str()),
+            ("amenity", "cafe")
+        );
+        assert_eq!(cafe.grid_pos, (50, 50));
+        assert_eq!(index.at_tile(50, 50).len(), 1);
+
+        assert_eq!(index.of_category("shop").len(), 1);
+        assert_eq!(index.of_kind("tourism", "museum")[0].osm_id, 3);
+        assert_eq!(index.named().len(), 2);
+    }
+
+    #[test]
+    fn test_radius_queries() {
+        let index = test_index();
+        let ids: Vec<i64> = index
+            .within_radius(52.0511, 13.05, 500.0)
+            .iter()
+            .map(|poi| poi.osm_id)
+            .collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert!(index.within_radius(52.0, 13.0, 100.0).is_empty());
+        assert_eq!(index.nearest(52.1, 13.1).unwrap().osm_id, 3);
+    }
+}
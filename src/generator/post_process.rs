@@ -0,0 +1,559 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::neighborhood::neighbor_positions;
+use super::{Connectivity, Tile, TileGrid, TileMetadata, TileType};
+
+/// A cleanup pass applied to a generated grid
+///
+/// Passes run in order after rasterization, so each sees the output of the
+/// previous one.
+pub trait GridPostProcessor: Send + Sync {
+    /// Get the pass identifier (e.g., "remove_noise")
+    fn name(&self) -> &'static str;
+
+    /// Apply the pass, returning the number of changed tiles
+    fn process(&self, grid: &mut TileGrid) -> usize;
+}
+
+/// Get the storage indices of the in-bounds edge neighbors of (x, y)
+fn edge_neighbors(width: usize, height: usize, x: usize, y: usize) -> impl Iterator<Item = usize> {
+    neighbor_positions(width, height, x, y, Connectivity::Four).map(move |(nx, ny)| ny * width + nx)
+}
+
+/// Iterate over the in-bounds neighbors of (x, y) in a row-major tile slice
+fn neighbors(
+    tiles: &[Tile],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+) -> impl Iterator<Item = &Tile> {
+    neighbor_positions(width, height, x, y, Connectivity::Eight)
+        .map(move |(nx, ny)| &tiles[ny * width + nx])
+}
+
+/// Get the most common type among `tiles` with its first tile, if it occurs at least `min` times
+fn majority<'a>(tiles: impl Iterator<Item = &'a Tile>, min: usize) -> Option<&'a Tile> {
+    let mut counts: HashMap<&TileType, (usize, &Tile)> = HashMap::new();
+    for tile in tiles {
+        counts.entry(&tile.tile_type).or_insert((0, tile)).0 += 1;
+    }
+    counts
+        .into_values()
+        .filter(|(count, _)| *count >= min)
+        .max_by_key(|(count, tile)| (*count, tile.tile_type.priority()))
+        .map(|(_, tile)| tile)
+}
+
+/// Closes short horizontal and vertical gaps between road tiles
+///
+/// Thin diagonal roads often rasterize with missing tiles; gaps of up to
+/// `max_gap` tiles are filled if the road may overwrite every tile in them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloseRoadGaps {
+    /// Longest gap (in tiles) that gets filled
+    pub max_gap: usize,
+}
+
+impl Default for CloseRoadGaps {
+    fn default() -> Self {
+        Self { max_gap: 1 }
+    }
+}
+
+impl CloseRoadGaps {
+    /// Close gaps of up to `max_gap` tiles
+    pub fn new(max_gap: usize) -> Self {
+        Self { max_gap }
+    }
+
+    /// Fill gaps along one line of tiles, given as storage indices
+    fn close_line(&self, grid: &mut TileGrid, line: &[usize]) -> usize {
+        let mut changed = 0;
+        let mut last_road: Option<usize> = None;
+
+        for (position, &index) in line.iter().enumerate() {
+            if grid.flat_tiles()[index].tile_type != TileType::Road {
+                continue;
+            }
+            if let Some(start) = last_road {
+                let gap = &line[start + 1..position];
+                let road = grid.flat_tiles()[line[start]].clone();
+                if !gap.is_empty()
+                    && gap.len() <= self.max_gap
+                    && gap
+                        .iter()
+                        .all(|&i| grid.flat_tiles()[i].can_be_overwritten_by(&road))
+                {
+                    for &i in gap {
+                        grid.flat_tiles_mut()[i] = road.clone();
+                    }
+                    changed += gap.len();
+                }
+            }
+            last_road = Some(position);
+        }
+
+        changed
+    }
+}
+
+impl GridPostProcessor for CloseRoadGaps {
+    fn name(&self) -> &'static str {
+        "close_road_gaps"
+    }
+
+    fn process(&self, grid: &mut TileGrid) -> usize {
+        let (width, height) = grid.dimensions();
+        let mut changed = 0;
+
+        for y in 0..height {
+            let line: Vec<usize> = (0..width).map(|x| y * width + x).collect();
+            changed += self.close_line(grid, &line);
+        }
+        for x in 0..width {
+            let line: Vec<usize> = (0..height).map(|y| y * width + x).collect();
+            changed += self.close_line(grid, &line);
+        }
+
+        changed
+    }
+}
+
+/// Replaces isolated tiles by their most common neighbor
+///
+/// A tile is isolated when fewer than `min_same_neighbors` of its eight
+/// neighbors share its type. Point features such as amenities are usually a
+/// single tile, so they are protected by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoveNoise {
+    /// Minimum number of same-type neighbors a tile needs to survive
+    pub min_same_neighbors: usize,
+    /// Tile types that are never removed
+    pub protected: Vec<TileType>,
+}
+
+impl Default for RemoveNoise {
+    fn default() -> Self {
+        Self {
+            min_same_neighbors: 1,
+            protected: vec![TileType::Amenity, TileType::Tourism],
+        }
+    }
+}
+
+impl RemoveNoise {
+    /// Set the tile types that are never removed
+    pub fn with_protected(mut self, protected: Vec<TileType>) -> Self {
+        self.protected = protected;
+        self
+    }
+}
+
+impl GridPostProcessor for RemoveNoise {
+    fn name(&self) -> &'static str {
+        "remove_noise"
+    }
+
+    fn process(&self, grid: &mut TileGrid) -> usize {
+        let (width, height) = grid.dimensions();
+        let source = grid.flat_tiles().to_vec();
+        let mut changed = 0;
+
+        for (index, tile) in source.iter().enumerate() {
+            if tile.tile_type == TileType::Empty || self.protected.contains(&tile.tile_type) {
+                continue;
+            }
+            let (x, y) = (index % width, index / width);
+            let same = neighbors(&source, width, height, x, y)
+                .filter(|neighbor| neighbor.tile_type == tile.tile_type)
+                .count();
+            if same >= self.min_same_neighbors {
+                continue;
+            }
+
+            let replacement = majority(neighbors(&source, width, height, x, y), 1)
+                .cloned()
+                .unwrap_or_default();
+            grid.flat_tiles_mut()[index] = replacement;
+            changed += 1;
+        }
+
+        changed
+    }
+}
+
+/// Grows tiles of one type into adjacent empty tiles
+///
+/// Useful for closing gaps in rivers and lakes drawn from thin outlines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DilateTiles {
+    /// Type to grow
+    pub tile_type: TileType,
+    /// Number of tiles to grow by
+    pub radius: usize,
+}
+
+impl DilateTiles {
+    /// Grow tiles of `tile_type` by `radius` tiles
+    pub fn new(tile_type: TileType, radius: usize) -> Self {
+        Self { tile_type, radius }
+    }
+
+    /// Grow water bodies by `radius` tiles
+    pub fn water(radius: usize) -> Self {
+        Self::new(TileType::Water, radius)
+    }
+}
+
+impl GridPostProcessor for DilateTiles {
+    fn name(&self) -> &'static str {
+        "dilate"
+    }
+
+    fn process(&self, grid: &mut TileGrid) -> usize {
+        let (width, height) = grid.dimensions();
+        let mut changed = 0;
+
+        for _ in 0..self.radius {
+            let source = grid.flat_tiles().to_vec();
+            let mut grown = 0;
+            for (index, tile) in source.iter().enumerate() {
+                if tile.tile_type != TileType::Empty {
+                    continue;
+                }
+                let source_tile = neighbors(&source, width, height, index % width, index / width)
+                    .find(|neighbor| neighbor.tile_type == self.tile_type);
+                if let Some(source_tile) = source_tile {
+                    grid.flat_tiles_mut()[index] = source_tile.clone();
+                    grown += 1;
+                }
+            }
+            if grown == 0 {
+                break;
+            }
+            changed += grown;
+        }
+
+        changed
+    }
+}
+
+/// Majority filter smoothing the boundaries between landuse areas
+///
+/// A landuse or empty tile switches to the landuse type shared by at least
+/// `threshold` of its eight neighbors. Other tile types are left untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothBoundaries {
+    /// Tile types treated as landuse
+    pub tile_types: Vec<TileType>,
+    /// Number of agreeing neighbors (out of 8) needed to switch a tile
+    pub threshold: usize,
+}
+
+impl Default for SmoothBoundaries {
+    fn default() -> Self {
+        Self {
+            tile_types: vec![
+                TileType::GreenSpace,
+                TileType::Residential,
+                TileType::Commercial,
+                TileType::Industrial,
+            ],
+            threshold: 5,
+        }
+    }
+}
+
+impl SmoothBoundaries {
+    /// Set the number of agreeing neighbors needed to switch a tile
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+impl GridPostProcessor for SmoothBoundaries {
+    fn name(&self) -> &'static str {
+        "smooth_boundaries"
+    }
+
+    fn process(&self, grid: &mut TileGrid) -> usize {
+        let (width, height) = grid.dimensions();
+        let source = grid.flat_tiles().to_vec();
+        let mut changed = 0;
+
+        for (index, tile) in source.iter().enumerate() {
+            if tile.tile_type != TileType::Empty && !self.tile_types.contains(&tile.tile_type) {
+                continue;
+            }
+            let landuse = neighbors(&source, width, height, index % width, index / width)
+                .filter(|neighbor| self.tile_types.contains(&neighbor.tile_type));
+            if let Some(majority) = majority(landuse, self.threshold)
+                && majority.tile_type != tile.tile_type
+            {
+                grid.flat_tiles_mut()[index] = majority.clone();
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+}
+
+/// Fills empty areas with plausible landuse where OSM has no data
+///
+/// Sparsely mapped suburbs leave large empty regions. Empty tiles within
+/// `river_bank_width` tiles of water become green space, then closed empty
+/// blocks of up to `max_block_tiles` tiles whose border is mostly road become
+/// residential. Inferred tiles are flagged with `source = "inferred"` and a
+/// `confidence` below 1.0 in their metadata, and the grid records their count
+/// in the `inferred_tiles` metadata extra.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InferUnmapped {
+    /// Largest empty block (in tiles) that is filled as residential
+    pub max_block_tiles: usize,
+    /// Share of a block's border that must be road, from 0.0 to 1.0
+    pub min_road_fraction: f32,
+    /// Width of the green strip along water, 0 to disable
+    pub river_bank_width: usize,
+}
+
+impl Default for InferUnmapped {
+    fn default() -> Self {
+        Self {
+            max_block_tiles: 400,
+            min_road_fraction: 0.6,
+            river_bank_width: 2,
+        }
+    }
+}
+
+impl InferUnmapped {
+    /// Source recorded in the metadata of inferred tiles
+    pub const SOURCE: &'static str = "inferred";
+
+    /// Set the largest empty block filled as residential
+    pub fn with_max_block_tiles(mut self, max_block_tiles: usize) -> Self {
+        self.max_block_tiles = max_block_tiles;
+        self
+    }
+
+    /// Set the width of the green strip along water
+    pub fn with_river_bank_width(mut self, width: usize) -> Self {
+        self.river_bank_width = width;
+        self
+    }
+
+    fn inferred(tile_type: TileType, confidence: f32) -> Tile {
+        Tile::with_metadata(
+            tile_type,
+            TileMetadata {
+                confidence,
+                source: Some(Self::SOURCE.to_string()),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Turn empty tiles near water into green space, nearer tiles with more confidence
+    fn fill_river_banks(&self, grid: &mut TileGrid) -> usize {
+        let (width, height) = grid.dimensions();
+        let mut distance = vec![usize::MAX; width * height];
+        let mut queue = VecDeque::new();
+        for (index, tile) in grid.flat_tiles().iter().enumerate() {
+            if tile.tile_type == TileType::Water {
+                distance[index] = 0;
+                queue.push_back(index);
+            }
+        }
+
+        let banks: Vec<Tile> = (1..=self.river_bank_width)
+            .map(|d| Self::inferred(TileType::GreenSpace, 0.5 / d as f32))
+            .collect();
+        let mut changed = 0;
+        while let Some(index) = queue.pop_front() {
+            let next = distance[index] + 1;
+            if next > self.river_bank_width {
+                continue;
+            }
+            for neighbor in edge_neighbors(width, height, index % width, index / width) {
+                if distance[neighbor] != usize::MAX
+                    || grid.flat_tiles()[neighbor].tile_type != TileType::Empty
+                {
+                    continue;
+                }
+                distance[neighbor] = next;
+                grid.flat_tiles_mut()[neighbor] = banks[next - 1].clone();
+                changed += 1;
+                queue.push_back(neighbor);
+            }
+        }
+        changed
+    }
+
+    /// Turn closed empty blocks bordered by roads into residential areas
+    fn fill_blocks(&self, grid: &mut TileGrid) -> usize {
+        let (width, height) = grid.dimensions();
+        let mut visited = vec![false; width * height];
+        let mut changed = 0;
+
+        for start in 0..width * height {
+            if visited[start] || grid.flat_tiles()[start].tile_type != TileType::Empty {
+                continue;
+            }
+            visited[start] = true;
+            let mut block = vec![start];
+            let (mut open, mut border, mut roads) = (false, 0usize, 0usize);
+            let mut next = 0;
+            while next < block.len() {
+                let index = block[next];
+                next += 1;
+                let (x, y) = (index % width, index / width);
+                open |= x == 0 || y == 0 || x + 1 == width || y + 1 == height;
+                for neighbor in edge_neighbors(width, height, x, y) {
+                    let tile_type = &grid.flat_tiles()[neighbor].tile_type;
+                    if *tile_type != TileType::Empty {
+                        border += 1;
+                        roads += usize::from(*tile_type == TileType::Road);
+                    } else if !visited[neighbor] {
+                        visited[neighbor] = true;
+                        block.push(neighbor);
+                    }
+                }
+            }
+
+            let road_fraction = roads as f32 / border.max(1) as f32;
+            if open || block.len() > self.max_block_tiles || road_fraction < self.min_road_fraction
+            {
+                continue;
+            }
+            let tile = Self::inferred(TileType::Residential, 0.6 * road_fraction);
+            for &index in &block {
+                grid.flat_tiles_mut()[index] = tile.clone();
+            }
+            changed += block.len();
+        }
+        changed
+    }
+}
+
+impl GridPostProcessor for InferUnmapped {
+    fn name(&self) -> &'static str {
+        "infer_unmapped"
+    }
+
+    fn process(&self, grid: &mut TileGrid) -> usize {
+        let changed = self.fill_river_banks(grid) + self.fill_blocks(grid);
+        if changed > 0 {
+            grid.metadata.tiles_populated += changed;
+            let inferred = grid
+                .metadata
+                .extra
+                .get("inferred_tiles")
+                .and_then(|count| count.parse::<usize>().ok())
+                .unwrap_or(0);
+            grid.metadata.extra.insert(
+                "inferred_tiles".to_string(),
+                (inferred + changed).to_string(),
+            );
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoundingBox;
+
+    fn grid_from(rows: &[&str]) -> TileGrid {
+        let mut grid = TileGrid::new(
+            rows[0].len(),
+            rows.len(),
+            BoundingBox::new(52.0, 13.0, 52.1, 13.1),
+            10.0,
+        );
+        for (y, row) in rows.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                let tile_type = match symbol {
+                    'R' => TileType::Road,
+                    'W' => TileType::Water,
+                    'G' => TileType::GreenSpace,
+                    'H' => TileType::Residential,
+                    'B' => TileType::Building,
+                    'A' => TileType::Amenity,
+                    _ => TileType::Empty,
+                };
+                grid.set_tile(x, y, Tile::new(tile_type)).unwrap();
+            }
+        }
+        grid
+    }
+
+    fn type_at(grid: &TileGrid, x: usize, y: usize) -> TileType {
+        grid.get_tile(x, y).unwrap().tile_type.clone()
+    }
+
+    #[test]
+    fn test_close_road_gaps() {
+        let mut grid = grid_from(&["R.R..R", "......", "RBR..."]);
+        assert_eq!(CloseRoadGaps::default().process(&mut grid), 3);
+        assert_eq!(type_at(&grid, 1, 0), TileType::Road);
+        // Gap too long, and buildings are not overwritten
+        assert_eq!(type_at(&grid, 3, 0), TileType::Empty);
+        assert_eq!(type_at(&grid, 1, 2), TileType::Building);
+        // Vertical gaps between the first and the last row
+        assert_eq!(type_at(&grid, 0, 1), TileType::Road);
+        assert_eq!(type_at(&grid, 2, 1), TileType::Road);
+    }
+
+    #[test]
+    fn test_remove_noise() {
+        let mut grid = grid_from(&["GGG..", "GBG.A", "GGG.."]);
+        assert_eq!(RemoveNoise::default().process(&mut grid), 1);
+        assert_eq!(type_at(&grid, 1, 1), TileType::GreenSpace);
+        assert_eq!(type_at(&grid, 4, 1), TileType::Amenity);
+
+        let mut grid = grid_from(&["G...", "...."]);
+        assert_eq!(RemoveNoise::default().process(&mut grid), 1);
+        assert_eq!(type_at(&grid, 0, 0), TileType::Empty);
+    }
+
+    #[test]
+    fn test_dilate_water() {
+        let mut grid = grid_from(&[".....", "..W..", ".R..."]);
+        assert_eq!(DilateTiles::water(1).process(&mut grid), 7);
+        assert_eq!(type_at(&grid, 0, 0), TileType::Empty);
+        assert_eq!(type_at(&grid, 3, 2), TileType::Water);
+        assert_eq!(type_at(&grid, 1, 2), TileType::Road);
+    }
+
+    #[test]
+    fn test_smooth_boundaries() {
+        let mut grid = grid_from(&["GGGHH", "GHGHH", "GGGHH"]);
+        let changed = SmoothBoundaries::default().process(&mut grid);
+        assert_eq!(changed, 1);
+        assert_eq!(type_at(&grid, 1, 1), TileType::GreenSpace);
+        assert_eq!(type_at(&grid, 3, 1), TileType::Residential);
+    }
+
+    #[test]
+    fn test_infer_unmapped() {
+        let mut grid = grid_from(&["........", ".RRRR...", ".R..R...", ".RRRR...", "......WW"]);
+        assert_eq!(InferUnmapped::default().process(&mut grid), 9);
+        assert_eq!(grid.metadata.extra["inferred_tiles"], "9");
+
+        let block = grid.get_tile(2, 2).unwrap();
+        assert_eq!(block.tile_type, TileType::Residential);
+        let metadata = block.metadata.as_ref().unwrap();
+        assert_eq!(metadata.source.as_deref(), Some(InferUnmapped::SOURCE));
+        assert!(metadata.confidence < 1.0);
+
+        // Two tiles of bank along the water, the open area beyond stays empty
+        assert_eq!(type_at(&grid, 6, 3), TileType::GreenSpace);
+        assert_eq!(type_at(&grid, 7, 2), TileType::GreenSpace);
+        assert_eq!(type_at(&grid, 7, 1), TileType::Empty);
+        assert_eq!(type_at(&grid, 0, 0), TileType::Empty);
+    }
+}
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Radius of the spherical earth model used by Web Mercator (meters)
+pub const WEB_MERCATOR_EARTH_RADIUS: f64 = 6_378_137.0;
+
+/// Maximum latitude representable in Web Mercator (degrees)
+pub const WEB_MERCATOR_MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Trait for mapping geographic coordinates onto a planar grid space
+///
+/// Grids are laid out linearly in projected space, so the projection decides
+/// how tiles relate to meters on the ground.
+pub trait Projection: Send + Sync {
+    /// Get the EPSG code of this projection (e.g., "EPSG:3857")
+    fn crs_code(&self) -> &'static str;
+
+    /// Project geographic coordinates (lat, lon) to planar coordinates (x, y)
+    ///
+    /// `x` grows eastward and `y` grows northward.
+    fn project(&self, lat: f64, lon: f64) -> (f64, f64);
+
+    /// Convert planar coordinates (x, y) back to geographic coordinates (lat, lon)
+    fn unproject(&self, x: f64, y: f64) -> (f64, f64);
+}
+
+/// Plate carrée projection using raw WGS84 degrees (EPSG:4326)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Wgs84Projection;
+
+impl Projection for Wgs84Projection {
+    fn crs_code(&self) -> &'static str {
+        "EPSG:4326"
+    }
+
+    fn project(&self, lat: f64, lon: f64) -> (f64, f64) {
+        (lon, lat)
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        (y, x)
+    }
+}
+
+/// Spherical Web Mercator projection in meters (EPSG:3857)
+///
+/// Conformal, so tiles stay square on the ground at any latitude.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebMercatorProjection;
+
+impl Projection for WebMercatorProjection {
+    fn crs_code(&self) -> &'static str {
+        "EPSG:3857"
+    }
+
+    fn project(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let lat = lat.clamp(-WEB_MERCATOR_MAX_LATITUDE, WEB_MERCATOR_MAX_LATITUDE);
+        let x = WEB_MERCATOR_EARTH_RADIUS * lon.to_radians();
+        let y = WEB_MERCATOR_EARTH_RADIUS * (PI / 4.0 + lat.to_radians() / 2.0).tan().ln();
+        (x, y)
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        let lon = (x / WEB_MERCATOR_EARTH_RADIUS).to_degrees();
+        let lat = (2.0 * (y / WEB_MERCATOR_EARTH_RADIUS).exp().atan() - PI / 2.0).to_degrees();
+        (lat, lon)
+    }
+}
+
+/// Coordinate reference systems supported by tile grids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Crs {
+    /// WGS84 geographic degrees (EPSG:4326)
+    #[default]
+    Wgs84,
+    /// Web Mercator (EPSG:3857)
+    WebMercator,
+}
+
+impl Crs {
+    /// Get all supported coordinate reference systems
+    pub fn all() -> &'static [Crs] {
+        &[Crs::Wgs84, Crs::WebMercator]
+    }
+
+    /// Get the projection implementing this CRS
+    pub fn projection(&self) -> &'static dyn Projection {
+        match self {
+            Crs::Wgs84 => &Wgs84Projection,
+            Crs::WebMercator => &WebMercatorProjection,
+        }
+    }
+
+    /// Get the EPSG code of this CRS
+    pub fn code(&self) -> &'static str {
+        self.projection().crs_code()
+    }
+
+    /// Look up a CRS by its EPSG code
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|crs| crs.code().eq_ignore_ascii_case(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wgs84_is_identity() {
+        let projection = Wgs84Projection;
+        assert_eq!(projection.project(52.5, 13.4), (13.4, 52.5));
+        assert_eq!(projection.unproject(13.4, 52.5), (52.5, 13.4));
+    }
+
+    #[test]
+    fn test_web_mercator_round_trip() {
+        let projection = WebMercatorProjection;
+
+        let (x, y) = projection.project(0.0, 0.0);
+        assert!(x.abs() < 1e-6 && y.abs() < 1e-6);
+
+        // Known value: lon 180° maps to half the equator circumference
+        let (x, _) = projection.project(0.0, 180.0);
+        assert!((x - 20_037_508.342_789_244).abs() < 1e-3);
+
+        for &(lat, lon) in &[(52.52, 13.405), (-33.86, 151.21), (69.65, 18.96)] {
+            let (x, y) = projection.project(lat, lon);
+            let (lat2, lon2) = projection.unproject(x, y);
+            assert!((lat - lat2).abs() < 1e-9);
+            assert!((lon - lon2).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_crs_lookup() {
+        assert_eq!(Crs::default(), Crs::Wgs84);
+        assert_eq!(Crs::WebMercator.code(), "EPSG:3857");
+        assert_eq!(Crs::from_code("epsg:4326"), Some(Crs::Wgs84));
+        assert_eq!(Crs::from_code("EPSG:27700"), None);
+    }
+}
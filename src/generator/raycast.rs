@@ -0,0 +1,131 @@
+use std::cmp::Ordering;
+
+use super::{Tile, TileGrid};
+
+/// Result of [`TileGrid::raycast`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raycast {
+    /// Cells the ray entered, from the start cell up to the blocking cell or the target
+    pub cells: Vec<(usize, usize)>,
+    /// First blocking cell, `None` if the target is visible
+    pub blocked_at: Option<(usize, usize)>,
+}
+
+impl Raycast {
+    /// Check whether the ray reached its target unblocked
+    pub fn is_clear(&self) -> bool {
+        self.blocked_at.is_none()
+    }
+}
+
+impl TileGrid {
+    /// Trace a ray between two tile centers, stopping at the first tile for which `blocking` holds
+    ///
+    /// Every cell the segment passes through is visited in order (DDA
+    /// traversal), so walls cannot be seen through diagonally as with
+    /// Bresenham lines; a ray passing exactly through a corner steps
+    /// diagonally. The start cell is never tested, so viewers may stand on
+    /// blocking tiles, but the target is. Returns `None` if either cell is
+    /// outside the grid.
+    ///
+    /// ```
+    /// use bevy_osm_tiles::{BoundingBox, Tile, TileGrid, TileType};
+    ///
+    /// let mut grid = TileGrid::new(8, 8, BoundingBox::new(52.0, 13.0, 52.01, 13.01), 10.0);
+    /// grid.set_tile(3, 3, Tile::new(TileType::Building)).unwrap();
+    ///
+    /// let walls = |tile: &Tile| tile.tile_type == TileType::Building;
+    /// let ray = grid.raycast((0, 0), (6, 6), walls).unwrap();
+    /// assert_eq!(ray.blocked_at, Some((3, 3)));
+    /// assert!(grid.raycast((0, 7), (7, 7), walls).unwrap().is_clear());
+    /// ```
+    pub fn raycast(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        mut blocking: impl FnMut(&Tile) -> bool,
+    ) -> Option<Raycast> {
+        self.get_tile(from.0, from.1)?;
+        self.get_tile(to.0, to.1)?;
+
+        let (dx, dy) = (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64);
+        let (step_x, step_y) = (dx.signum(), dy.signum());
+        let (nx, ny) = (dx.unsigned_abs(), dy.unsigned_abs());
+        let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+        let mut cells = vec![from];
+
+        // After i steps in x the ray crosses the next vertical edge at t = (2i + 1) / 2nx,
+        // compared in integers so corners are hit exactly
+        let (mut i, mut j) = (0u64, 0u64);
+        while i < nx || j < ny {
+            match ((2 * i + 1) * ny).cmp(&((2 * j + 1) * nx)) {
+                Ordering::Less => {
+                    x += step_x;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    y += step_y;
+                    j += 1;
+                }
+                Ordering::Equal => {
+                    x += step_x;
+                    y += step_y;
+                    i += 1;
+                    j += 1;
+                }
+            }
+
+            let cell = (x as usize, y as usize);
+            cells.push(cell);
+            if self.get_tile(cell.0, cell.1).is_some_and(&mut blocking) {
+                return Some(Raycast {
+                    cells,
+                    blocked_at: Some(cell),
+                });
+            }
+        }
+
+        Some(Raycast {
+            cells,
+            blocked_at: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, TileType};
+
+    fn walls(tile: &Tile) -> bool {
+        tile.tile_type == TileType::Building
+    }
+
+    #[test]
+    fn test_raycast() {
+        let mut grid = TileGrid::new(10, 10, BoundingBox::new(52.0, 13.0, 52.01, 13.01), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Building)).unwrap();
+        grid.set_tile(5, 2, Tile::new(TileType::Building)).unwrap();
+
+        // Shallow line: every cell touches the previous one along an edge
+        let ray = grid.raycast((0, 0), (9, 4), walls).unwrap();
+        assert_eq!(ray.blocked_at, Some((5, 2)));
+        assert_eq!(ray.cells.first(), Some(&(0, 0)));
+        assert_eq!(ray.cells.last(), Some(&(5, 2)));
+        for pair in ray.cells.windows(2) {
+            let step = pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1);
+            assert_eq!(step, 1);
+        }
+
+        // The viewer stands on a building and the target is clear
+        let ray = grid.raycast((0, 0), (9, 0), walls).unwrap();
+        assert!(ray.is_clear());
+        assert_eq!(ray.cells.len(), 10);
+
+        // Exact diagonals step through corners
+        let ray = grid.raycast((2, 2), (6, 6), walls).unwrap();
+        assert_eq!(ray.cells, [(2, 2), (3, 3), (4, 4), (5, 5), (6, 6)]);
+        assert_eq!(grid.raycast((3, 3), (3, 3), walls).unwrap().cells, [(3, 3)]);
+        assert!(grid.raycast((0, 0), (10, 3), walls).is_none());
+    }
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{Tile, TileGrid, TileType};
+use crate::BoundingBox;
+
+/// Which neighbors count as connected when grouping tiles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Connectivity {
+    /// Tiles sharing an edge
+    #[default]
+    Four,
+    /// Tiles sharing an edge or a corner, so diagonal streets stay in one piece
+    Eight,
+}
+
+impl Connectivity {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Four => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Self::Eight => &[
+                (-1, 0),
+                (1, 0),
+                (0, -1),
+                (0, 1),
+                (-1, -1),
+                (1, -1),
+                (-1, 1),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// A connected group of tiles of one type, e.g. a single park or lake
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileRegion {
+    /// Index of the region in the order regions were found (row by row)
+    pub label: usize,
+    /// Tile type shared by all tiles of the region
+    pub tile_type: TileType,
+    /// Grid positions (x, y) of the tiles
+    pub tiles: Vec<(usize, usize)>,
+    /// Smallest grid coordinates (x, y) of the region
+    pub min: (usize, usize),
+    /// Largest grid coordinates (x, y) of the region
+    pub max: (usize, usize),
+    /// Geographic bounds of the region's tiles
+    pub bounding_box: BoundingBox,
+    /// Covered area in square meters, counting partially covered tiles by their coverage
+    pub area_m2: f32,
+}
+
+impl TileRegion {
+    /// Get the number of tiles in the region
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Get the mean grid position of the region's tiles
+    pub fn centroid(&self) -> (f32, f32) {
+        let count = self.tiles.len().max(1) as f32;
+        let (sum_x, sum_y) = self.tiles.iter().fold((0.0, 0.0), |(sx, sy), &(x, y)| {
+            (sx + x as f32, sy + y as f32)
+        });
+        (sum_x / count, sum_y / count)
+    }
+}
+
+impl TileGrid {
+    /// Find the edge-connected regions of a tile type, see [`Self::connected_components_with`]
+    pub fn connected_components(&self, tile_type: &TileType) -> Vec<TileRegion> {
+        self.connected_components_with(tile_type, Connectivity::Four)
+    }
+
+    /// Find the connected regions of a tile type, labeled in row-major order
+    pub fn connected_components_with(
+        &self,
+        tile_type: &TileType,
+        connectivity: Connectivity,
+    ) -> Vec<TileRegion> {
+        let (width, height) = self.dimensions();
+        let mut visited = vec![false; width * height];
+        let mut regions = Vec::new();
+
+        for (x, y, tile) in self.iter_tiles() {
+            if visited[y * width + x] || tile.tile_type != *tile_type {
+                continue;
+            }
+            let tiles = self.fill_from(x, y, connectivity, &mut visited);
+            regions.push(self.region(regions.len(), tile_type, tiles));
+        }
+        regions
+    }
+
+    /// Get the region of same-typed tiles connected to (x, y) by their edges
+    pub fn flood_fill(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.flood_fill_with(x, y, Connectivity::Four)
+    }
+
+    /// Get the region of same-typed tiles connected to (x, y), empty if outside the grid
+    pub fn flood_fill_with(
+        &self,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+    ) -> Vec<(usize, usize)> {
+        if self.get_tile(x, y).is_none() {
+            return Vec::new();
+        }
+        let (width, height) = self.dimensions();
+        let mut visited = vec![false; width * height];
+        self.fill_from(x, y, connectivity, &mut visited)
+    }
+
+    /// Replace the region connected to (x, y) with copies of `tile`, returning the number of tiles changed
+    pub fn flood_fill_replace(
+        &mut self,
+        x: usize,
+        y: usize,
+        tile: Tile,
+        connectivity: Connectivity,
+    ) -> usize {
+        let region = self.flood_fill_with(x, y, connectivity);
+        for &(x, y) in &region {
+            if let Some(target) = self.get_tile_mut(x, y) {
+                *target = tile.clone();
+            }
+        }
+        region.len()
+    }
+
+    /// Collect the tiles of the type at (x, y) reachable from it, marking them visited
+    fn fill_from(
+        &self,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+        visited: &mut [bool],
+    ) -> Vec<(usize, usize)> {
+        let (width, height) = self.dimensions();
+        let tile_type = &self.flat_tiles()[y * width + x].tile_type;
+        let mut tiles = Vec::new();
+        let mut queue = VecDeque::from([(x, y)]);
+        visited[y * width + x] = true;
+
+        while let Some((x, y)) = queue.pop_front() {
+            tiles.push((x, y));
+            for &(dx, dy) in connectivity.offsets() {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                let index = ny * width + nx;
+                if nx < width
+                    && ny < height
+                    && !visited[index]
+                    && self.flat_tiles()[index].tile_type == *tile_type
+                {
+                    visited[index] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        tiles.sort_by_key(|&(x, y)| (y, x));
+        tiles
+    }
+
+    fn region(&self, label: usize, tile_type: &TileType, tiles: Vec<(usize, usize)>) -> TileRegion {
+        let min = tiles
+            .iter()
+            .fold((usize::MAX, usize::MAX), |(mx, my), &(x, y)| {
+                (mx.min(x), my.min(y))
+            });
+        let max = tiles
+            .iter()
+            .fold((0, 0), |(mx, my), &(x, y)| (mx.max(x), my.max(y)));
+
+        // Opposite corners of the region cover its geographic extent in any grid origin
+        let corners = [
+            self.tile_bounds(min.0, min.1),
+            self.tile_bounds(max.0, max.1),
+        ];
+        let bounding_box = corners
+            .into_iter()
+            .flatten()
+            .reduce(|a, b| {
+                BoundingBox::new(
+                    a.south.min(b.south),
+                    a.west.min(b.west),
+                    a.north.max(b.north),
+                    a.east.max(b.east),
+                )
+            })
+            .unwrap_or_else(|| self.bounding_box.clone());
+
+        let covered: f32 = tiles
+            .iter()
+            .filter_map(|&(x, y)| self.get_tile(x, y))
+            .map(|tile| tile.coverage)
+            .sum();
+
+        TileRegion {
+            label,
+            tile_type: tile_type.clone(),
+            tiles,
+            min,
+            max,
+            bounding_box,
+            area_m2: covered * self.meters_per_tile_x() * self.meters_per_tile_y(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three parks, the single tile at (3, 2) touching the first only at a corner
+    fn grid_with_parks() -> TileGrid {
+        let mut grid = TileGrid::new(10, 10, BoundingBox::new(52.0, 13.0, 52.01, 13.01), 10.0);
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (3, 2), (7, 7), (8, 7)] {
+            grid.set_tile(x, y, Tile::new(TileType::GreenSpace))
+                .unwrap();
+        }
+        grid
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let grid = grid_with_parks();
+        let parks = grid.connected_components(&TileType::GreenSpace);
+
+        assert_eq!(parks.len(), 3);
+        assert_eq!(parks[0].label, 0);
+        assert_eq!(parks[0].tiles, vec![(1, 1), (2, 1), (1, 2)]);
+        assert_eq!((parks[0].min, parks[0].max), ((1, 1), (2, 2)));
+        assert_eq!(parks[1].tile_count(), 1);
+        assert_eq!(parks[2].centroid(), (7.5, 7.0));
+
+        let tile_area = grid.meters_per_tile_x() * grid.meters_per_tile_y();
+        assert!((parks[0].area_m2 - 3.0 * tile_area).abs() < 1e-3);
+        let bbox = &parks[2].bounding_box;
+        assert!((bbox.west - 13.007).abs() < 1e-9 && (bbox.east - 13.009).abs() < 1e-9);
+
+        let diagonal = grid.connected_components_with(&TileType::GreenSpace, Connectivity::Eight);
+        assert_eq!(diagonal.len(), 2);
+        assert_eq!(diagonal[0].tile_count(), 4);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut grid = grid_with_parks();
+        assert_eq!(grid.flood_fill(2, 1), vec![(1, 1), (2, 1), (1, 2)]);
+        assert_eq!(grid.flood_fill(0, 0).len(), 94);
+        assert!(grid.flood_fill(10, 0).is_empty());
+
+        let changed = grid.flood_fill_replace(7, 7, Tile::new(TileType::Water), Connectivity::Four);
+        assert_eq!(changed, 2);
+        assert_eq!(grid.get_tile(8, 7).unwrap().tile_type, TileType::Water);
+        assert_eq!(grid.connected_components(&TileType::GreenSpace).len(), 2);
+    }
+}
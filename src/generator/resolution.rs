@@ -0,0 +1,259 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+use super::{GridGenerator, GridStatistics, TileType};
+use crate::{OsmConfig, OsmData, OsmTilesError, Result};
+
+/// Statistics of a grid generated at one resolution
+#[derive(Debug, Clone)]
+pub struct ResolutionSample {
+    /// Grid resolution the grid was generated with (cells per degree)
+    pub grid_resolution: u32,
+    /// Statistics of the generated grid
+    pub statistics: GridStatistics,
+    /// Generation time in milliseconds
+    pub generation_time_ms: u64,
+}
+
+impl ResolutionSample {
+    /// Get the share of tiles with the given type, from 0.0 to 1.0
+    pub fn type_share(&self, tile_type: &TileType) -> f64 {
+        let total = self.statistics.total_tiles.max(1) as f64;
+        self.statistics
+            .tile_type_counts
+            .get(tile_type)
+            .map_or(0.0, |&count| count as f64 / total)
+    }
+}
+
+/// Comparison of the same region generated at several resolutions
+///
+/// The highest resolution serves as the reference. Drift is the total
+/// variation distance between a sample's type distribution and the
+/// reference's: 0.0 means identical shares, 1.0 means nothing in common.
+#[derive(Debug, Clone)]
+pub struct ResolutionComparison {
+    /// Samples ordered from lowest to highest resolution
+    pub samples: Vec<ResolutionSample>,
+}
+
+impl ResolutionComparison {
+    /// Generate `osm_data` at each resolution and collect the grid statistics
+    pub async fn generate(
+        generator: &dyn GridGenerator,
+        osm_data: &OsmData,
+        config: &OsmConfig,
+        resolutions: &[u32],
+    ) -> Result<Self> {
+        let resolutions: BTreeSet<u32> = resolutions.iter().copied().collect();
+        if resolutions.is_empty() {
+            return Err(OsmTilesError::Config(
+                "Resolution comparison requires at least one resolution".into(),
+            ));
+        }
+
+        let mut samples = Vec::with_capacity(resolutions.len());
+        for grid_resolution in resolutions {
+            let sample_config = config.clone().with_grid_resolution(grid_resolution);
+            let grid = generator.generate_grid(osm_data, &sample_config).await?;
+            tracing::debug!(
+                "Generated {}x{} grid at resolution {}",
+                grid.cols(),
+                grid.rows(),
+                grid_resolution
+            );
+            samples.push(ResolutionSample {
+                grid_resolution,
+                statistics: grid.statistics(),
+                generation_time_ms: grid.metadata.generation_time_ms,
+            });
+        }
+
+        Ok(Self { samples })
+    }
+
+    /// Get the highest resolution sample, used as the reference
+    pub fn reference(&self) -> Option<&ResolutionSample> {
+        self.samples.last()
+    }
+
+    /// Get the sample generated at the given resolution
+    pub fn sample(&self, grid_resolution: u32) -> Option<&ResolutionSample> {
+        self.samples
+            .iter()
+            .find(|sample| sample.grid_resolution == grid_resolution)
+    }
+
+    /// Get the drift of a sample's type distribution from the reference
+    pub fn drift(&self, sample: &ResolutionSample) -> f64 {
+        let Some(reference) = self.reference() else {
+            return 0.0;
+        };
+
+        let types: HashSet<&TileType> = sample
+            .statistics
+            .tile_type_counts
+            .keys()
+            .chain(reference.statistics.tile_type_counts.keys())
+            .collect();
+
+        types
+            .into_iter()
+            .map(|tile_type| (sample.type_share(tile_type) - reference.type_share(tile_type)).abs())
+            .sum::<f64>()
+            / 2.0
+    }
+
+    /// Get the non-empty types present in the reference but lost in a sample
+    pub fn missing_types(&self, sample: &ResolutionSample) -> Vec<TileType> {
+        let Some(reference) = self.reference() else {
+            return Vec::new();
+        };
+
+        let mut missing: Vec<TileType> = reference
+            .statistics
+            .tile_type_counts
+            .keys()
+            .filter(|tile_type| {
+                **tile_type != TileType::Empty
+                    && !sample.statistics.tile_type_counts.contains_key(*tile_type)
+            })
+            .cloned()
+            .collect();
+        missing.sort_by_key(|tile_type| tile_type.priority());
+        missing
+    }
+
+    /// Get the lowest resolution whose drift stays within `max_drift` and that keeps every type
+    pub fn lowest_adequate(&self, max_drift: f64) -> Option<u32> {
+        self.samples
+            .iter()
+            .find(|sample| self.drift(sample) <= max_drift && self.missing_types(sample).is_empty())
+            .map(|sample| sample.grid_resolution)
+    }
+
+    /// Render the comparison as a plain-text table
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        let _ = writeln!(
+            summary,
+            "{:>10} {:>11} {:>9} {:>8} {:>8}  missing",
+            "resolution", "grid", "m/tile", "coverage", "drift"
+        );
+        for sample in &self.samples {
+            let (width, height) = sample.statistics.dimensions;
+            let missing: Vec<String> = self
+                .missing_types(sample)
+                .iter()
+                .map(|tile_type| tile_type.name().to_string())
+                .collect();
+            let _ = writeln!(
+                summary,
+                "{:>10} {:>11} {:>9.1} {:>7.1}% {:>8.3}  {}",
+                sample.grid_resolution,
+                format!("{}x{}", width, height),
+                sample.statistics.meters_per_tile,
+                sample.statistics.coverage_ratio * 100.0,
+                self.drift(sample),
+                if missing.is_empty() {
+                    "-".to_string()
+                } else {
+                    missing.join(", ")
+                }
+            );
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, DefaultGridGenerator, OsmConfigBuilder, OsmDataFormat, OsmMetadata};
+
+    fn test_data() -> OsmData {
+        let json_data = r#"{
+            "elements": [
+                {
+                    "type": "way",
+                    "id": 1,
+                    "tags": {"highway": "residential"},
+                    "geometry": [
+                        {"lat": 52.492, "lon": 13.392},
+                        {"lat": 52.508, "lon": 13.408}
+                    ]
+                },
+                {
+                    "type": "way",
+                    "id": 2,
+                    "tags": {"building": "yes"},
+                    "geometry": [
+                        {"lat": 52.5001, "lon": 13.4001},
+                        {"lat": 52.5001, "lon": 13.4004},
+                        {"lat": 52.5004, "lon": 13.4004},
+                        {"lat": 52.5004, "lon": 13.4001},
+                        {"lat": 52.5001, "lon": 13.4001}
+                    ]
+                },
+                {
+                    "type": "node",
+                    "id": 3,
+                    "lat": 52.5015,
+                    "lon": 13.4015,
+                    "tags": {"amenity": "cafe"}
+                }
+            ]
+        }"#;
+
+        OsmData {
+            raw_data: json_data.to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.49, 13.39, 52.51, 13.41),
+            metadata: OsmMetadata::new("test", "test"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_resolutions() {
+        let config = OsmConfigBuilder::new().urban_features().build();
+        let comparison = ResolutionComparison::generate(
+            &DefaultGridGenerator::new(),
+            &test_data(),
+            &config,
+            &[2000, 10, 500],
+        )
+        .await
+        .unwrap();
+
+        let resolutions: Vec<u32> = comparison
+            .samples
+            .iter()
+            .map(|sample| sample.grid_resolution)
+            .collect();
+        assert_eq!(resolutions, vec![10, 500, 2000]);
+
+        let reference = comparison.reference().unwrap();
+        assert_eq!(comparison.drift(reference), 0.0);
+
+        // Coarse tiles hold the small building and the cafe at once, and the cafe wins
+        let coarse = comparison.sample(10).unwrap();
+        assert_eq!(coarse.statistics.total_tiles, 100);
+        assert_eq!(comparison.missing_types(coarse), vec![TileType::Building]);
+        assert!(comparison.drift(coarse) > 0.0);
+
+        assert_eq!(comparison.lowest_adequate(1.0), Some(2000));
+        assert_eq!(comparison.summary().lines().count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_requires_resolutions() {
+        let result = ResolutionComparison::generate(
+            &DefaultGridGenerator::new(),
+            &test_data(),
+            &OsmConfig::default(),
+            &[],
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
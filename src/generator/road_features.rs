@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+use super::{OsmElement, OsmElementType, Tile, TileGrid, TileType};
+
+/// Distance in tiles within which a road feature node snaps to a road tile
+pub const ROAD_FEATURE_SNAP_RADIUS: usize = 2;
+
+/// Traffic control or crossing on a road, parsed from a `highway=*` node
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RoadFeature {
+    /// Traffic lights (`highway=traffic_signals`)
+    TrafficSignals,
+    /// Pedestrian crossing (`highway=crossing`), with its `crossing=*` type if tagged (e.g., "zebra")
+    Crossing(Option<String>),
+    /// Stop sign (`highway=stop`)
+    Stop,
+}
+
+impl RoadFeature {
+    /// Parse a road feature from a node, `None` for other elements
+    pub fn from_element(element: &OsmElement) -> Option<Self> {
+        if element.element_type != OsmElementType::Node {
+            return None;
+        }
+        match element.tags.get("highway")?.as_str() {
+            "traffic_signals" => Some(Self::TrafficSignals),
+            "crossing" => Some(Self::Crossing(element.tags.get("crossing").cloned())),
+            "stop" => Some(Self::Stop),
+            _ => None,
+        }
+    }
+
+    /// Get the feature identifier (e.g., "traffic_signals")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TrafficSignals => "traffic_signals",
+            Self::Crossing(_) => "crossing",
+            Self::Stop => "stop",
+        }
+    }
+}
+
+impl Tile {
+    /// Get the traffic signals, crossings and stop signs annotated on this tile
+    pub fn road_features(&self) -> &[RoadFeature] {
+        self.metadata
+            .as_ref()
+            .map_or(&[], |metadata| metadata.road_features.as_slice())
+    }
+}
+
+impl TileGrid {
+    /// Get all road features with the grid position (x, y) of their road tile
+    pub fn road_features(&self) -> Vec<(usize, usize, &RoadFeature)> {
+        self.iter_tiles()
+            .flat_map(|(x, y, tile)| tile.road_features().iter().map(move |f| (x, y, f)))
+            .collect()
+    }
+
+    /// Annotate the road tile nearest to (lat, lon) with a feature
+    ///
+    /// Only road tiles within [`ROAD_FEATURE_SNAP_RADIUS`] are considered.
+    /// Returns the position of the annotated tile, or `None` if no road is
+    /// close enough. A tile keeps each distinct feature once.
+    pub fn attach_road_feature(
+        &mut self,
+        lat: f64,
+        lon: f64,
+        feature: RoadFeature,
+    ) -> Option<(usize, usize)> {
+        let (x, y) = self.geo_to_grid(lat, lon)?;
+        let (width, height) = self.dimensions();
+        let radius = ROAD_FEATURE_SNAP_RADIUS;
+        let (x, y) = (y.saturating_sub(radius)..(y + radius + 1).min(height))
+            .flat_map(|ty| {
+                (x.saturating_sub(radius)..(x + radius + 1).min(width)).map(move |tx| (tx, ty))
+            })
+            .filter(|&(tx, ty)| {
+                self.get_tile(tx, ty)
+                    .is_some_and(|tile| tile.tile_type == TileType::Road)
+            })
+            .min_by_key(|&(tx, ty)| {
+                let (dx, dy) = (tx.abs_diff(x), ty.abs_diff(y));
+                dx * dx + dy * dy
+            })?;
+
+        // Road metadata is shared along the whole way, so the tile gets its own copy
+        let metadata = self.get_tile_mut(x, y)?.metadata_mut();
+        if !metadata.road_features.contains(&feature) {
+            metadata.road_features.push(feature);
+        }
+        Some((x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BoundingBox, DefaultGridGenerator, OsmConfigBuilder, OsmData, OsmDataFormat, OsmMetadata,
+    };
+    use serde_json::json;
+
+    /// A street with traffic lights at one end, a zebra crossing and a stop sign far off the road
+    fn street_data() -> OsmData {
+        let elements = json!([
+            {"type": "node", "id": 1, "lat": 52.5051, "lon": 13.402, "tags": {"highway": "traffic_signals"}},
+            {"type": "node", "id": 2, "lat": 52.5049, "lon": 13.406, "tags": {"highway": "crossing", "crossing": "zebra"}},
+            {"type": "node", "id": 3, "lat": 52.508, "lon": 13.406, "tags": {"highway": "stop"}},
+            {"type": "way", "id": 10, "tags": {"highway": "residential", "name": "Lindenweg"}, "geometry": [
+                {"lat": 52.505, "lon": 13.4},
+                {"lat": 52.505, "lon": 13.41},
+            ]},
+        ]);
+        OsmData {
+            raw_data: json!({"elements": elements}).to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.5, 13.4, 52.51, 13.41),
+            metadata: OsmMetadata::new("test", "test"),
+        }
+    }
+
+    #[test]
+    fn test_road_features_attached_to_roads() {
+        let config = OsmConfigBuilder::new().grid_resolution(10_000).build();
+        let grid = DefaultGridGenerator::new()
+            .generate_grid_sync(&street_data(), &config)
+            .unwrap();
+
+        let features = grid.road_features();
+        assert_eq!(features.len(), 2);
+        let (x, y, signals) = features
+            .iter()
+            .find(|(_, _, feature)| **feature == RoadFeature::TrafficSignals)
+            .unwrap();
+        assert_eq!(signals.name(), "traffic_signals");
+        let tile = grid.get_tile(*x, *y).unwrap();
+        assert_eq!(tile.tile_type, TileType::Road);
+        assert_eq!(tile.metadata.as_ref().unwrap().tags["name"], "Lindenweg");
+        assert!(
+            features
+                .iter()
+                .any(|(_, _, feature)| **feature == RoadFeature::Crossing(Some("zebra".into())))
+        );
+
+        // Only the annotated tiles got their own copy of the road metadata
+        let annotated = grid
+            .tiles_of_type(&TileType::Road)
+            .into_iter()
+            .filter(|(_, _, tile)| !tile.road_features().is_empty())
+            .count();
+        assert_eq!(annotated, 2);
+        // Feature nodes do not paint road tiles of their own
+        let (stop_x, stop_y) = grid.geo_to_grid(52.508, 13.406).unwrap();
+        assert_eq!(
+            grid.get_tile(stop_x, stop_y).unwrap().tile_type,
+            TileType::Empty
+        );
+        assert_eq!(grid.metadata.extra["road_features_unmatched"], "1");
+    }
+}
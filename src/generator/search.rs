@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use super::{TileGrid, TileType};
+
+/// Minimum similarity score for a fuzzy name match to be reported
+pub const DEFAULT_MIN_NAME_SCORE: f32 = 0.6;
+
+/// Tag keys searched for feature names, in order of preference
+const NAME_KEYS: [&str; 5] = [
+    "name",
+    "official_name",
+    "alt_name",
+    "short_name",
+    "old_name",
+];
+
+/// A named feature found in a tile grid
+#[derive(Debug, Clone)]
+pub struct NameMatch {
+    /// Name of the feature as stored in the OSM tags
+    pub name: String,
+    /// Similarity to the query, from 0.0 to 1.0 (1.0 is an exact match)
+    pub score: f32,
+    /// Tile type of the matching tiles
+    pub tile_type: TileType,
+    /// OSM element IDs carrying this name
+    pub osm_ids: Vec<i64>,
+    /// All grid positions (x, y) covered by the feature
+    pub tiles: Vec<(usize, usize)>,
+    /// Grid position closest to the center of the feature
+    pub center: (usize, usize),
+    /// Geographic location (lat, lon) of the center tile
+    pub location: (f64, f64),
+}
+
+impl TileGrid {
+    /// Find named features (POIs, streets, buildings) matching `query`
+    ///
+    /// Matching ignores case, accents and punctuation, and tolerates typos.
+    /// Results are sorted by score, best first.
+    pub fn find_by_name(&self, query: &str) -> Vec<NameMatch> {
+        self.find_by_name_with_score(query, DEFAULT_MIN_NAME_SCORE)
+    }
+
+    /// Find named features matching `query` with at least the given similarity score
+    pub fn find_by_name_with_score(&self, query: &str, min_score: f32) -> Vec<NameMatch> {
+        let query = normalize_name(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        // Group tiles of the same feature so a long street yields one result
+        let mut matches: HashMap<(String, TileType), NameMatch> = HashMap::new();
+        for (x, y, tile) in self.iter_tiles() {
+            let Some(metadata) = &tile.metadata else {
+                continue;
+            };
+
+            let best = NAME_KEYS
+                .iter()
+                .filter_map(|key| metadata.tags.get(*key))
+                .map(|name| (name, name_score(&query, &normalize_name(name))))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((name, score)) = best.filter(|(_, score)| *score >= min_score) else {
+                continue;
+            };
+
+            let entry = matches
+                .entry((name.clone(), tile.tile_type.clone()))
+                .or_insert_with(|| NameMatch {
+                    name: name.clone(),
+                    score,
+                    tile_type: tile.tile_type.clone(),
+                    osm_ids: Vec::new(),
+                    tiles: Vec::new(),
+                    center: (x, y),
+                    location: (0.0, 0.0),
+                });
+            entry.tiles.push((x, y));
+            for id in &metadata.osm_ids {
+                if !entry.osm_ids.contains(id) {
+                    entry.osm_ids.push(*id);
+                }
+            }
+        }
+
+        let mut results: Vec<NameMatch> = matches
+            .into_values()
+            .map(|mut found| {
+                found.center = central_tile(&found.tiles);
+                found.location = self
+                    .grid_to_geo(found.center.0, found.center.1)
+                    .unwrap_or_default();
+                found
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.tiles.len().cmp(&a.tiles.len()))
+                .then(a.name.cmp(&b.name))
+        });
+        results
+    }
+}
+
+/// Get the tile of a feature closest to its centroid
+fn central_tile(tiles: &[(usize, usize)]) -> (usize, usize) {
+    let count = tiles.len().max(1) as f64;
+    let cx = tiles.iter().map(|(x, _)| *x as f64).sum::<f64>() / count;
+    let cy = tiles.iter().map(|(_, y)| *y as f64).sum::<f64>() / count;
+
+    tiles
+        .iter()
+        .copied()
+        .min_by(|a, b| {
+            let da = (a.0 as f64 - cx).powi(2) + (a.1 as f64 - cy).powi(2);
+            let db = (b.0 as f64 - cx).powi(2) + (b.1 as f64 - cy).powi(2);
+            da.total_cmp(&db)
+        })
+        .unwrap_or_default()
+}
+
+/// Lowercase a name, fold common accents and collapse punctuation into single spaces
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ä' | 'à' | 'á' | 'â' | 'ã' | 'å' => normalized.push('a'),
+            'ö' | 'ò' | 'ó' | 'ô' | 'õ' | 'ø' => normalized.push('o'),
+            'ü' | 'ù' | 'ú' | 'û' => normalized.push('u'),
+            'è' | 'é' | 'ê' | 'ë' => normalized.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => normalized.push('i'),
+            'ç' => normalized.push('c'),
+            'ñ' => normalized.push('n'),
+            'ß' => normalized.push_str("ss"),
+            // Combining accents of decomposed text (e.g., "u\u{308}"), folded like the composed ones
+            '\u{300}'..='\u{36f}' => {}
+            c if c.is_alphanumeric() => normalized.push(c),
+            _ => {
+                if !normalized.is_empty() && !normalized.ends_with(' ') {
+                    normalized.push(' ');
+                }
+            }
+        }
+    }
+    normalized.trim_end().to_string()
+}
+
+/// Score how well a normalized name matches a normalized query
+fn name_score(query: &str, name: &str) -> f32 {
+    if name.is_empty() {
+        return 0.0;
+    }
+    if name == query {
+        return 1.0;
+    }
+    if name.starts_with(query) {
+        return 0.9;
+    }
+    if name.contains(query) {
+        return 0.8;
+    }
+
+    // Typo tolerance against the whole name and against each word
+    let whole = similarity(query, name);
+    let best_word = name
+        .split(' ')
+        .map(|word| similarity(query, word))
+        .fold(0.0, f32::max);
+    whole.max(best_word) * 0.75
+}
+
+/// Similarity from 0.0 to 1.0 based on the Levenshtein distance
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile, TileMetadata};
+
+    fn named_tile(tile_type: TileType, id: i64, name: &str) -> Tile {
+        let mut metadata = TileMetadata {
+            osm_ids: vec![id],
+            ..Default::default()
+        };
+        metadata.tags.insert("name".to_string(), name.to_string());
+        Tile::with_metadata(tile_type, metadata)
+    }
+
+    fn test_grid() -> TileGrid {
+        let bbox = BoundingBox::new(52.50, 13.38, 52.53, 13.43);
+        let mut grid = TileGrid::new(20, 20, bbox, 10.0);
+        for x in 2..9 {
+            grid.set_tile(x, 5, named_tile(TileType::Road, 1, "Karl-Marx-Allee"))
+                .unwrap();
+        }
+        grid.set_tile(12, 12, named_tile(TileType::Amenity, 2, "Alexanderplatz"))
+            .unwrap();
+        grid.set_tile(13, 12, named_tile(TileType::Amenity, 2, "Alexanderplatz"))
+            .unwrap();
+        grid.set_tile(3, 15, named_tile(TileType::Tourism, 3, "Fernsehturm"))
+            .unwrap();
+        grid.set_tile(4, 15, named_tile(TileType::Building, 4, "Müllerstraße 5"))
+            .unwrap();
+        grid
+    }
+
+    #[test]
+    fn test_exact_match_groups_tiles() {
+        let grid = test_grid();
+        let results = grid.find_by_name("Alexanderplatz");
+
+        assert_eq!(results.len(), 1);
+        let found = &results[0];
+        assert_eq!(found.score, 1.0);
+        assert_eq!(found.tile_type, TileType::Amenity);
+        assert_eq!(found.osm_ids, vec![2]);
+        assert_eq!(found.tiles.len(), 2);
+        assert!(
+            grid.bounding_box
+                .contains(found.location.0, found.location.1)
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_matching() {
+        let grid = test_grid();
+
+        // Case, punctuation and accents are ignored
+        assert_eq!(grid.find_by_name("karl marx allee")[0].score, 1.0);
+        assert_eq!(grid.find_by_name("mullerstrasse")[0].osm_ids, vec![4]);
+        // Prefixes and typos still match
+        assert_eq!(grid.find_by_name("Alex")[0].name, "Alexanderplatz");
+        assert_eq!(grid.find_by_name("Fernsehtrum")[0].name, "Fernsehturm");
+        // Unrelated queries do not
+        assert!(grid.find_by_name("Potsdamer Platz").is_empty());
+        assert!(grid.find_by_name("").is_empty());
+    }
+
+    #[test]
+    fn test_center_of_long_feature() {
+        let grid = test_grid();
+        let street = &grid.find_by_name("Karl-Marx-Allee")[0];
+        assert_eq!(street.tiles.len(), 7);
+        assert_eq!(street.center, (5, 5));
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("  Karl-Marx-Allee "), "karl marx allee");
+        assert_eq!(normalize_name("Straße des 17. Juni"), "strasse des 17 juni");
+        assert_eq!(normalize_name("Mu\u{308}nchen"), normalize_name("München"));
+        assert_eq!(normalize_name("Ελλάδα, Αθήνα"), "ελλάδα αθήνα");
+    }
+}
@@ -0,0 +1,138 @@
+use geo::{LineString, SimplifyIdx};
+
+use super::{OsmElement, TileGrid, TileType};
+
+/// Check if polygons of a tile type are simplified before filling
+///
+/// Parks and lakes are large and drawn with many vertices, while the exact
+/// outline of buildings and small land uses matters at any resolution.
+pub(crate) fn is_simplified_type(tile_type: &TileType) -> bool {
+    matches!(tile_type, TileType::Water | TileType::GreenSpace)
+}
+
+/// Simplify a water or green space polygon with Douglas-Peucker, `tolerance` in tiles
+///
+/// Vertices are measured in grid image space, so the tolerance is the same
+/// fraction of a tile on both axes and in every projection. Returns `None`
+/// when the element is not such a polygon or no vertex can be dropped.
+pub(crate) fn simplify_polygon(
+    element: &OsmElement,
+    grid: &TileGrid,
+    tolerance: f32,
+) -> Option<OsmElement> {
+    let geometry = &element.geometry;
+    if geometry.len() < 5
+        || geometry.first() != geometry.last()
+        || !is_simplified_type(&element.to_tile_type())
+    {
+        return None;
+    }
+
+    let outline = simplify_ring(geometry, grid, tolerance)?;
+    let holes: Vec<Vec<(f64, f64)>> = element
+        .holes
+        .iter()
+        .map(|hole| simplify_ring(hole, grid, tolerance).unwrap_or_else(|| hole.clone()))
+        .collect();
+    Some(OsmElement {
+        geometry: outline,
+        holes,
+        ..element.clone()
+    })
+}
+
+/// Get the vertex count of an element's outline and holes
+pub(crate) fn vertex_count(element: &OsmElement) -> usize {
+    element.geometry.len() + element.holes.iter().map(Vec::len).sum::<usize>()
+}
+
+/// Keep the vertices of a closed ring that matter at `tolerance` tiles
+///
+/// Returns `None` if nothing would be removed or the ring would collapse.
+fn simplify_ring(ring: &[(f64, f64)], grid: &TileGrid, tolerance: f32) -> Option<Vec<(f64, f64)>> {
+    let projected: LineString<f64> = ring
+        .iter()
+        .map(|&(lat, lon)| grid.image_position(lat, lon))
+        .collect::<Vec<_>>()
+        .into();
+    let kept = projected.simplify_idx(f64::from(tolerance));
+    // A closed ring needs at least a triangle plus the closing vertex
+    (kept.len() >= 4 && kept.len() < ring.len()).then(|| kept.iter().map(|&i| ring[i]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BoundingBox, DefaultGridGenerator, OsmConfigBuilder, OsmData, OsmDataFormat, OsmMetadata,
+    };
+    use serde_json::json;
+
+    /// A lake drawn as a circle with 2000 vertices, and a small square building
+    fn lake_data() -> OsmData {
+        let ring: Vec<_> = (0..=2000)
+            .map(|step| {
+                let angle = step as f64 / 2000.0 * std::f64::consts::TAU;
+                json!({"lat": 52.505 + 0.004 * angle.sin(), "lon": 13.405 + 0.004 * angle.cos()})
+            })
+            .collect();
+        let square = |lat: f64, lon: f64| {
+            json!([
+                {"lat": lat, "lon": lon},
+                {"lat": lat, "lon": lon + 0.0003},
+                {"lat": lat + 0.0002, "lon": lon + 0.0003},
+                {"lat": lat + 0.0002, "lon": lon + 0.0001},
+                {"lat": lat + 0.0002, "lon": lon},
+                {"lat": lat, "lon": lon},
+            ])
+        };
+        let elements = json!([
+            {"type": "way", "id": 1, "tags": {"natural": "water"}, "geometry": ring},
+            {"type": "way", "id": 2, "tags": {"building": "yes"}, "geometry": square(52.5095, 13.401)},
+        ]);
+        OsmData {
+            raw_data: json!({"elements": elements}).to_string(),
+            format: OsmDataFormat::Json,
+            bounding_box: BoundingBox::new(52.5, 13.4, 52.51, 13.41),
+            metadata: OsmMetadata::new("test", "test"),
+        }
+    }
+
+    #[test]
+    fn test_simplify_polygon() {
+        let data = lake_data();
+        let grid = TileGrid::new(100, 100, data.bounding_box.clone(), 10.0);
+        let elements = crate::OsmParser.parse(&data).unwrap();
+
+        let lake = simplify_polygon(&elements[0], &grid, 0.5).unwrap();
+        assert!(vertex_count(&lake) < 200);
+        assert_eq!(lake.geometry.first(), lake.geometry.last());
+        // Buildings keep their vertices, even redundant ones
+        assert!(simplify_polygon(&elements[1], &grid, 0.5).is_none());
+    }
+
+    #[test]
+    fn test_simplified_generation() {
+        let data = lake_data();
+        let config = OsmConfigBuilder::new().grid_resolution(10_000).build();
+        let exact = DefaultGridGenerator::new()
+            .generate_grid_sync(&data, &config)
+            .unwrap();
+        let simplified = DefaultGridGenerator::new()
+            .with_polygon_simplification(0.5)
+            .generate_grid_sync(&data, &config)
+            .unwrap();
+
+        let water = |grid: &TileGrid| grid.tiles_of_type(&TileType::Water).len() as f64;
+        assert!((water(&simplified) - water(&exact)).abs() / water(&exact) < 0.01);
+        assert_eq!(
+            exact.tiles_of_type(&TileType::Building).len(),
+            simplified.tiles_of_type(&TileType::Building).len()
+        );
+        let removed: usize = simplified.metadata.extra["simplified_vertices"]
+            .parse()
+            .unwrap();
+        assert!(removed > 1800);
+        assert!(!exact.metadata.extra.contains_key("simplified_vertices"));
+    }
+}
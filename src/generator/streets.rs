@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use super::{TileGrid, TileType, normalize_name};
+
+/// A named street and the road tiles it covers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Street {
+    /// Value of the road's `name` tag
+    pub name: String,
+    /// OSM way IDs of the road segments carrying the name
+    pub osm_ids: Vec<i64>,
+    /// Grid positions (x, y) of the street's tiles, row by row
+    pub tiles: Vec<(usize, usize)>,
+}
+
+/// A house number on a street, from `addr:street` and `addr:housenumber` tags
+#[derive(Debug, Clone, PartialEq)]
+pub struct Address {
+    /// Value of the `addr:street` tag
+    pub street: String,
+    /// Value of the `addr:housenumber` tag
+    pub housenumber: String,
+    /// OSM ID of the building or node carrying the address
+    pub osm_id: i64,
+    /// Grid positions (x, y) of the tiles carrying the address
+    pub tiles: Vec<(usize, usize)>,
+}
+
+/// Lookup table of street names and addresses, see [`TileGrid::build_street_index`]
+///
+/// Names are matched after [`normalize_name`], so case and accents don't
+/// matter. The index is a snapshot: rebuild it after changing the grid.
+#[derive(Debug, Clone, Default)]
+pub struct StreetIndex {
+    streets: Vec<Street>,
+    addresses: Vec<Address>,
+    /// Index into `streets` by normalized name
+    by_name: HashMap<String, usize>,
+}
+
+impl TileGrid {
+    /// Build a [`StreetIndex`] of the named roads and addresses in the grid
+    ///
+    /// Addresses come from tiles and POIs tagged with both `addr:street` and
+    /// `addr:housenumber`.
+    pub fn build_street_index(&self) -> StreetIndex {
+        let mut index = StreetIndex::default();
+        let mut addresses: HashMap<i64, usize> = HashMap::new();
+        for (x, y, tile) in self.iter_tiles() {
+            let Some(metadata) = &tile.metadata else {
+                continue;
+            };
+            let osm_id = metadata.osm_ids.first().copied().unwrap_or_default();
+
+            if tile.tile_type == TileType::Road
+                && let Some(name) = metadata.tags.get("name")
+            {
+                let slot = match index.by_name.entry(normalize_name(name)) {
+                    Entry::Occupied(entry) => *entry.get(),
+                    Entry::Vacant(entry) => {
+                        index.streets.push(Street {
+                            name: name.clone(),
+                            osm_ids: Vec::new(),
+                            tiles: Vec::new(),
+                        });
+                        *entry.insert(index.streets.len() - 1)
+                    }
+                };
+                let street = &mut index.streets[slot];
+                street.tiles.push((x, y));
+                for id in &metadata.osm_ids {
+                    if !street.osm_ids.contains(id) {
+                        street.osm_ids.push(*id);
+                    }
+                }
+            }
+
+            if let Some((street, housenumber)) = address_tags(&metadata.tags) {
+                let slot = *addresses.entry(osm_id).or_insert_with(|| {
+                    index.addresses.push(Address {
+                        street: street.clone(),
+                        housenumber: housenumber.clone(),
+                        osm_id,
+                        tiles: Vec::new(),
+                    });
+                    index.addresses.len() - 1
+                });
+                index.addresses[slot].tiles.push((x, y));
+            }
+        }
+
+        for poi in self.pois.iter() {
+            if addresses.contains_key(&poi.osm_id) {
+                continue;
+            }
+            if let Some((street, housenumber)) = address_tags(&poi.tags) {
+                index.addresses.push(Address {
+                    street: street.clone(),
+                    housenumber: housenumber.clone(),
+                    osm_id: poi.osm_id,
+                    tiles: vec![poi.grid_pos],
+                });
+            }
+        }
+        index
+    }
+}
+
+impl StreetIndex {
+    /// Get the number of named streets
+    pub fn len(&self) -> usize {
+        self.streets.len()
+    }
+
+    /// Check if the index holds no streets
+    pub fn is_empty(&self) -> bool {
+        self.streets.is_empty()
+    }
+
+    /// Iterate over all named streets
+    pub fn streets(&self) -> impl Iterator<Item = &Street> {
+        self.streets.iter()
+    }
+
+    /// Iterate over all addresses
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.addresses.iter()
+    }
+
+    /// Get a street by name
+    pub fn street(&self, name: &str) -> Option<&Street> {
+        self.by_name
+            .get(&normalize_name(name))
+            .map(|&slot| &self.streets[slot])
+    }
+
+    /// Get all addresses on a street, whether or not its road is in the grid
+    pub fn addresses_on(&self, street: &str) -> Vec<&Address> {
+        let street = normalize_name(street);
+        self.addresses
+            .iter()
+            .filter(|address| normalize_name(&address.street) == street)
+            .collect()
+    }
+
+    /// Get the address with a street name and house number
+    pub fn address(&self, street: &str, housenumber: &str) -> Option<&Address> {
+        self.addresses_on(street)
+            .into_iter()
+            .find(|address| address.housenumber.eq_ignore_ascii_case(housenumber))
+    }
+
+    /// Find the named street closest to (x, y) and its tile nearest to the query
+    ///
+    /// Distances are measured between tile centers; ties go to the street
+    /// found first.
+    pub fn nearest_street(&self, x: usize, y: usize) -> Option<(&Street, (usize, usize))> {
+        let distance = |(tx, ty): (usize, usize)| {
+            let (dx, dy) = (tx.abs_diff(x), ty.abs_diff(y));
+            dx * dx + dy * dy
+        };
+        self.streets
+            .iter()
+            .filter_map(|street| {
+                let nearest = street
+                    .tiles
+                    .iter()
+                    .copied()
+                    .min_by_key(|&tile| distance(tile))?;
+                Some((street, nearest))
+            })
+            .min_by_key(|&(_, tile)| distance(tile))
+    }
+}
+
+/// Get the street and house number of an address, if both are tagged
+fn address_tags(tags: &HashMap<String, String>) -> Option<(&String, &String)> {
+    Some((tags.get("addr:street")?, tags.get("addr:housenumber")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Tile, TileMetadata};
+
+    fn tile(tile_type: TileType, osm_id: i64, tags: &[(&str, &str)]) -> Tile {
+        let tags = tags
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let metadata = TileMetadata {
+            osm_ids: vec![osm_id],
+            tags,
+            ..Default::default()
+        };
+        Tile::with_metadata(tile_type, metadata)
+    }
+
+    /// Two streets crossing, and a house on one of them
+    fn grid() -> TileGrid {
+        let mut grid = TileGrid::new(20, 20, BoundingBox::new(52.0, 13.0, 52.01, 13.01), 10.0);
+        for x in 0..20 {
+            let road = tile(
+                TileType::Road,
+                1,
+                &[("highway", "primary"), ("name", "Hauptstraße")],
+            );
+            grid.set_tile(x, 5, road).unwrap();
+        }
+        for y in 6..20 {
+            let road = tile(
+                TileType::Road,
+                2,
+                &[("highway", "residential"), ("name", "Gartenweg")],
+            );
+            grid.set_tile(10, y, road).unwrap();
+        }
+        let house = tile(
+            TileType::Building,
+            3,
+            &[
+                ("building", "house"),
+                ("addr:street", "Gartenweg"),
+                ("addr:housenumber", "7a"),
+            ],
+        );
+        for (x, y) in [(12, 14), (13, 14)] {
+            grid.set_tile(x, y, house.clone()).unwrap();
+        }
+        grid
+    }
+
+    #[test]
+    fn test_street_lookup() {
+        let index = grid().build_street_index();
+        assert_eq!(index.len(), 2);
+
+        let main = index.street("hauptstrasse").unwrap();
+        assert_eq!(main.name, "Hauptstraße");
+        assert_eq!(main.osm_ids, [1]);
+        assert_eq!(main.tiles.len(), 20);
+        assert!(index.street("Bahnhofstraße").is_none());
+
+        let house = index.address("gartenweg", "7A").unwrap();
+        assert_eq!(house.osm_id, 3);
+        assert_eq!(house.tiles, [(12, 14), (13, 14)]);
+        assert_eq!(index.addresses_on("Gartenweg").len(), 1);
+        assert!(index.address("Gartenweg", "8").is_none());
+    }
+
+    #[test]
+    fn test_nearest_street() {
+        let index = grid().build_street_index();
+        let (street, tile) = index.nearest_street(13, 14).unwrap();
+        assert_eq!(street.name, "Gartenweg");
+        assert_eq!(tile, (10, 14));
+
+        let (street, tile) = index.nearest_street(2, 3).unwrap();
+        assert_eq!(street.name, "Hauptstraße");
+        assert_eq!(tile, (2, 5));
+
+        assert!(StreetIndex::default().nearest_street(0, 0).is_none());
+    }
+}
@@ -1,12 +1,21 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, Serializer};
 use std::collections::HashMap;
+use std::ops::{Deref, DerefMut, Index, IndexMut};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::BoundingBox;
+use super::{
+    Crs, Districts, GridManifest, GridOrigin, PoiIndex, RoadFeature, TileAttributes, TileConflict,
+    UnclassifiedTags,
+};
+use crate::{Attribution, BoundingBox, OsmTilesError};
 
 /// Represents a single tile in the grid
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TileType {
     /// Empty/unknown tile
+    #[default]
     Empty,
     /// Road or highway
     Road,
@@ -34,13 +43,23 @@ pub enum TileType {
     Custom(String),
 }
 
-impl Default for TileType {
-    fn default() -> Self {
-        Self::Empty
-    }
-}
-
 impl TileType {
+    /// Every tile type except `Custom`, in declaration order
+    pub const STANDARD: [TileType; 12] = [
+        Self::Empty,
+        Self::Road,
+        Self::Building,
+        Self::Water,
+        Self::GreenSpace,
+        Self::Railway,
+        Self::Parking,
+        Self::Amenity,
+        Self::Tourism,
+        Self::Industrial,
+        Self::Residential,
+        Self::Commercial,
+    ];
+
     /// Get a human-readable name for this tile type
     pub fn name(&self) -> &str {
         match self {
@@ -60,6 +79,16 @@ impl TileType {
         }
     }
 
+    /// Get a label for legends and UIs, e.g. "Green space" for `GreenSpace`
+    pub fn label(&self) -> String {
+        let name = self.name().replace('_', " ");
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => name,
+        }
+    }
+
     /// Get a suggested color for this tile type (RGB)
     pub fn default_color(&self) -> (u8, u8, u8) {
         match self {
@@ -79,7 +108,29 @@ impl TileType {
         }
     }
 
+    /// Get a suggested extrusion height for 3D rendering (in meters)
+    pub fn default_height(&self) -> f32 {
+        match self {
+            Self::Empty => 0.0,
+            Self::Road => 0.1,
+            Self::Building => 2.0,
+            Self::Water => 0.05,
+            Self::GreenSpace => 0.2,
+            Self::Railway => 0.15,
+            Self::Parking => 0.05,
+            Self::Amenity => 1.0,
+            Self::Tourism => 1.5,
+            Self::Industrial => 3.0,
+            Self::Residential => 1.8,
+            Self::Commercial => 2.5,
+            Self::Custom(_) => 0.8,
+        }
+    }
+
     /// Check if this tile type represents a navigable area
+    ///
+    /// See [`Tile::is_navigable_for`] and [`MovementCosts`](super::MovementCosts) for costs that
+    /// depend on the travel mode, surface and slope.
     pub fn is_navigable(&self) -> bool {
         matches!(self, Self::Road | Self::Empty | Self::Parking)
     }
@@ -118,6 +169,33 @@ pub struct TileMetadata {
     pub tags: HashMap<String, String>,
     /// Confidence score (0.0 to 1.0)
     pub confidence: f32,
+    /// Approximate area of the contributing element in m² (0 for points and lines)
+    #[serde(default)]
+    pub element_area_m2: f64,
+    /// Further tile types merged into this tile besides its main type
+    #[serde(default)]
+    pub secondary_types: Vec<TileType>,
+    /// Vertical layer of the contributing element (negative below ground)
+    #[serde(default)]
+    pub layer: i8,
+    /// Whether the contributing element is a bridge
+    #[serde(default)]
+    pub bridge: bool,
+    /// Whether the contributing element is a tunnel
+    #[serde(default)]
+    pub tunnel: bool,
+    /// Tile types covered by this tile on lower layers (e.g., water beneath a bridge)
+    #[serde(default)]
+    pub underlying_types: Vec<TileType>,
+    /// Data source of the contributing element when several sources were combined
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Traffic signals, crossings and stop signs on this road tile
+    #[serde(default)]
+    pub road_features: Vec<RoadFeature>,
+    /// Elements of other types that contested this tile, see [`TileGrid::conflicts`]
+    #[serde(default)]
+    pub conflicts: Vec<TileConflict>,
 }
 
 impl Default for TileMetadata {
@@ -126,15 +204,91 @@ impl Default for TileMetadata {
             osm_ids: Vec::new(),
             tags: HashMap::new(),
             confidence: 1.0,
+            element_area_m2: 0.0,
+            secondary_types: Vec::new(),
+            layer: 0,
+            bridge: false,
+            tunnel: false,
+            underlying_types: Vec::new(),
+            source: None,
+            road_features: Vec::new(),
+            conflicts: Vec::new(),
         }
     }
 }
 
+/// Tile metadata that tiles rasterized from the same element share
+///
+/// Derefs to [`TileMetadata`], so its fields read and write as usual. Writing
+/// to metadata shared with other tiles copies it first, so they are
+/// unaffected.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SharedMetadata(Arc<TileMetadata>);
+
+impl SharedMetadata {
+    /// Wrap metadata for sharing
+    pub fn new(metadata: TileMetadata) -> Self {
+        Self(Arc::new(metadata))
+    }
+
+    /// Check whether both refer to the same allocation
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+
+    /// Get the address of the shared allocation, to group tiles sharing it
+    pub(crate) fn as_ptr(&self) -> *const TileMetadata {
+        Arc::as_ptr(&self.0)
+    }
+
+    /// Take the metadata, copying it if other tiles still share it
+    pub fn into_inner(self) -> TileMetadata {
+        Arc::unwrap_or_clone(self.0)
+    }
+}
+
+impl Deref for SharedMetadata {
+    type Target = TileMetadata;
+
+    fn deref(&self) -> &TileMetadata {
+        &self.0
+    }
+}
+
+impl DerefMut for SharedMetadata {
+    fn deref_mut(&mut self) -> &mut TileMetadata {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+impl From<TileMetadata> for SharedMetadata {
+    fn from(metadata: TileMetadata) -> Self {
+        Self::new(metadata)
+    }
+}
+
 /// A tile with its type and optional metadata
+///
+/// Metadata is a [`SharedMetadata`], so all tiles rasterized from the same
+/// OSM element share a single copy of its tags.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tile {
     pub tile_type: TileType,
-    pub metadata: Option<TileMetadata>,
+    pub metadata: Option<SharedMetadata>,
+    /// Fraction of the tile covered by its feature, from 0.0 to 1.0
+    ///
+    /// Polygon tiles on the outline are partially covered; points, lines and
+    /// empty tiles always report 1.0.
+    #[serde(default = "full_coverage")]
+    pub coverage: f32,
+    /// Numeric attributes set by post-processors or gameplay code, see [`TileAttributes`]
+    #[serde(default, skip_serializing_if = "TileAttributes::is_empty")]
+    pub attributes: TileAttributes,
+}
+
+fn full_coverage() -> f32 {
+    1.0
 }
 
 impl Default for Tile {
@@ -142,6 +296,8 @@ impl Default for Tile {
         Self {
             tile_type: TileType::Empty,
             metadata: None,
+            coverage: 1.0,
+            attributes: TileAttributes::default(),
         }
     }
 }
@@ -152,17 +308,44 @@ impl Tile {
         Self {
             tile_type,
             metadata: None,
+            coverage: 1.0,
+            attributes: TileAttributes::default(),
         }
     }
 
     /// Create a new tile with type and metadata
     pub fn with_metadata(tile_type: TileType, metadata: TileMetadata) -> Self {
+        Self::with_shared_metadata(tile_type, SharedMetadata::new(metadata))
+    }
+
+    /// Create a new tile sharing metadata with other tiles
+    pub fn with_shared_metadata(tile_type: TileType, metadata: SharedMetadata) -> Self {
         Self {
             tile_type,
             metadata: Some(metadata),
+            coverage: 1.0,
+            attributes: TileAttributes::default(),
         }
     }
 
+    /// Set the fraction of the tile covered by its feature
+    pub fn with_coverage(mut self, coverage: f32) -> Self {
+        self.coverage = coverage.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Check if the feature covers the whole tile
+    pub fn is_fully_covered(&self) -> bool {
+        self.coverage >= 1.0
+    }
+
+    /// Get mutable access to the metadata, creating it if missing
+    ///
+    /// Metadata shared with other tiles is copied first, so they are unaffected.
+    pub fn metadata_mut(&mut self) -> &mut TileMetadata {
+        self.metadata.get_or_insert_with(Default::default)
+    }
+
     /// Check if this tile can be overwritten by another tile
     pub fn can_be_overwritten_by(&self, other: &Tile) -> bool {
         other.tile_type.priority() > self.tile_type.priority()
@@ -170,22 +353,56 @@ impl Tile {
 }
 
 /// A grid of tiles representing a geographic area
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Deserialization fails unless there are `height` rows of `width` tiles.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "TileGridData")]
 pub struct TileGrid {
-    /// The actual grid data (stored as Vec<Vec<Tile>> for better serialization)
-    tiles: Vec<Vec<Tile>>,
+    /// Tiles in row-major order, indexed by `y * width + x` (serialized as rows)
+    tiles: Vec<Tile>,
     /// Grid width
     width: usize,
     /// Grid height
     height: usize,
     /// Geographic bounding box this grid represents
     pub bounding_box: BoundingBox,
-    /// Meters per tile (approximately)
+    /// Mean side length of a tile in meters, see [`Self::meters_per_tile_x`] for each axis
     pub meters_per_tile: f32,
+    /// Coordinate reference system the grid is laid out in
+    #[serde(default)]
+    pub crs: Crs,
+    /// Corner row 0 starts at (rows are stored and serialized in this order)
+    #[serde(default)]
+    pub origin: GridOrigin,
+    /// Points of interest collected during generation
+    #[serde(default)]
+    pub pois: PoiIndex,
+    /// Named administrative areas, see [`OsmFeature::Boundaries`](crate::OsmFeature::Boundaries)
+    #[serde(default)]
+    pub districts: Districts,
     /// Grid generation metadata
     pub metadata: GridMetadata,
 }
 
+/// A rectangle of grid cells from `min` to `max`, both inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridRect {
+    pub min: (usize, usize),
+    pub max: (usize, usize),
+}
+
+impl GridRect {
+    /// Get the number of columns and rows in the rectangle
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.max.0 - self.min.0 + 1, self.max.1 - self.min.1 + 1)
+    }
+
+    /// Check whether the cell at (x, y) lies inside the rectangle
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+}
+
 /// Metadata about grid generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GridMetadata {
@@ -201,6 +418,30 @@ pub struct GridMetadata {
     pub algorithm: String,
     /// Additional metadata
     pub extra: HashMap<String, String>,
+    /// Source and license attribution of the underlying OSM data
+    #[serde(default)]
+    pub attribution: Option<Attribution>,
+    /// Tagged elements no classification rule recognized
+    #[serde(default)]
+    pub unclassified: UnclassifiedTags,
+    /// Time of the OSM database snapshot the grid was generated from, if known
+    #[serde(default)]
+    pub data_timestamp: Option<DateTime<Utc>>,
+    /// Provenance of the grid for reproducing it, see [`TileGrid::manifest`]
+    #[serde(default)]
+    pub manifest: Option<GridManifest>,
+}
+
+impl GridMetadata {
+    /// Get how old the underlying OSM data is, `None` if the snapshot time is unknown
+    pub fn data_age(&self) -> Option<Duration> {
+        crate::provider::data_age(self.data_timestamp)
+    }
+
+    /// Check if the underlying data is older than `max_age` (unknown ages are never stale)
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.data_age().is_some_and(|age| age > max_age)
+    }
 }
 
 impl TileGrid {
@@ -211,22 +452,16 @@ impl TileGrid {
         bounding_box: BoundingBox,
         meters_per_tile: f32,
     ) -> Self {
-        // Initialize grid with empty tiles
-        let mut tiles = Vec::with_capacity(height);
-        for _ in 0..height {
-            let mut row = Vec::with_capacity(width);
-            for _ in 0..width {
-                row.push(Tile::default());
-            }
-            tiles.push(row);
-        }
-
         Self {
-            tiles,
+            tiles: vec![Tile::default(); width * height],
             width,
             height,
             bounding_box,
             meters_per_tile,
+            crs: Crs::default(),
+            origin: GridOrigin::default(),
+            pois: PoiIndex::default(),
+            districts: Districts::default(),
             metadata: GridMetadata {
                 generated_at: chrono::Utc::now().to_rfc3339(),
                 elements_processed: 0,
@@ -234,6 +469,10 @@ impl TileGrid {
                 generation_time_ms: 0,
                 algorithm: "default".to_string(),
                 extra: HashMap::new(),
+                attribution: None,
+                unclassified: UnclassifiedTags::default(),
+                data_timestamp: None,
+                manifest: None,
             },
         }
     }
@@ -258,34 +497,38 @@ impl TileGrid {
         self.width
     }
 
+    /// Get the storage index of the tile at (x, y), if it is inside the grid
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+
     /// Get a tile at the given grid coordinates
     pub fn get_tile(&self, x: usize, y: usize) -> Option<&Tile> {
-        if x < self.width && y < self.height {
-            Some(&self.tiles[y][x])
-        } else {
-            None
-        }
+        self.index(x, y).map(|index| &self.tiles[index])
     }
 
     /// Get a mutable reference to a tile at the given grid coordinates
     pub fn get_tile_mut(&mut self, x: usize, y: usize) -> Option<&mut Tile> {
-        if x < self.width && y < self.height {
-            Some(&mut self.tiles[y][x])
-        } else {
-            None
-        }
+        self.index(x, y).map(|index| &mut self.tiles[index])
+    }
+
+    /// Describe an out-of-bounds access
+    pub(crate) fn out_of_bounds(&self, x: usize, y: usize) -> String {
+        format!(
+            "Coordinates ({}, {}) out of bounds for grid {}x{}",
+            x, y, self.width, self.height
+        )
+    }
+
+    /// Get one row of tiles
+    pub fn row(&self, y: usize) -> Option<&[Tile]> {
+        (y < self.height).then(|| &self.tiles[y * self.width..(y + 1) * self.width])
     }
 
     /// Set a tile at the given grid coordinates
     pub fn set_tile(&mut self, x: usize, y: usize, tile: Tile) -> Result<(), String> {
-        if x >= self.width || y >= self.height {
-            return Err(format!(
-                "Coordinates ({}, {}) out of bounds for grid {}x{}",
-                x, y, self.width, self.height
-            ));
-        }
-
-        self.tiles[y][x] = tile;
+        let index = self.index(x, y).ok_or_else(|| self.out_of_bounds(x, y))?;
+        self.tiles[index] = tile;
         Ok(())
     }
 
@@ -296,90 +539,198 @@ impl TileGrid {
         y: usize,
         tile: Tile,
     ) -> Result<bool, String> {
-        if x >= self.width || y >= self.height {
-            return Err(format!(
-                "Coordinates ({}, {}) out of bounds for grid {}x{}",
-                x, y, self.width, self.height
-            ));
-        }
-
-        let current_tile = &self.tiles[y][x];
-        if current_tile.can_be_overwritten_by(&tile) {
-            self.tiles[y][x] = tile;
+        let index = self.index(x, y).ok_or_else(|| self.out_of_bounds(x, y))?;
+        if self.tiles[index].can_be_overwritten_by(&tile) {
+            self.tiles[index] = tile;
             Ok(true)
         } else {
             Ok(false)
         }
     }
 
+    /// Lay the grid out in the given coordinate reference system
+    pub fn with_crs(mut self, crs: Crs) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    /// Set the corner row 0 starts at, without moving any tiles
+    pub fn with_origin(mut self, origin: GridOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Switch to another origin, reordering rows so every tile keeps its geographic position
+    pub fn set_origin(&mut self, origin: GridOrigin) {
+        if self.origin == origin {
+            return;
+        }
+        for y in 0..self.height / 2 {
+            let (top, bottom) = self.tiles.split_at_mut((self.height - 1 - y) * self.width);
+            top[y * self.width..(y + 1) * self.width].swap_with_slice(&mut bottom[..self.width]);
+        }
+        self.pois.flip_rows(self.height);
+        self.districts.flip_rows(self.height);
+        self.origin = origin;
+    }
+
+    /// Convert grid coordinates to image coordinates (row 0 at the top)
+    pub fn to_image_coords(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        self.index(x, y)
+            .map(|_| (x, self.origin.to_top_left(y, self.height)))
+    }
+
+    /// Convert image coordinates (row 0 at the top) to grid coordinates
+    pub fn from_image_coords(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        self.index(x, y)
+            .map(|_| (x, self.origin.from_top_left(y, self.height)))
+    }
+
+    /// Get the bounding box in projected coordinates (min_x, min_y, max_x, max_y)
+    fn projected_bounds(&self) -> (f64, f64, f64, f64) {
+        let projection = self.crs.projection();
+        let (min_x, min_y) = projection.project(self.bounding_box.south, self.bounding_box.west);
+        let (max_x, max_y) = projection.project(self.bounding_box.north, self.bounding_box.east);
+        (min_x, min_y, max_x, max_y)
+    }
+
+    /// Get the fractional top-left image position of a location, which may lie outside the grid
+    pub(crate) fn image_position(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let (min_x, min_y, max_x, max_y) = self.projected_bounds();
+        let (px, py) = self.crs.projection().project(lat, lon);
+
+        let x_ratio = (px - min_x) / (max_x - min_x);
+        let y_ratio = (max_y - py) / (max_y - min_y); // Flip Y axis
+
+        (x_ratio * self.width as f64, y_ratio * self.height as f64)
+    }
+
     /// Convert geographic coordinates (lat, lon) to grid coordinates (x, y)
     pub fn geo_to_grid(&self, lat: f64, lon: f64) -> Option<(usize, usize)> {
         if !self.bounding_box.contains(lat, lon) {
             return None;
         }
 
-        let width_deg = self.bounding_box.width();
-        let height_deg = self.bounding_box.height();
-
-        let x_ratio = (lon - self.bounding_box.west) / width_deg;
-        let y_ratio = (self.bounding_box.north - lat) / height_deg; // Flip Y axis
-
-        let x = (x_ratio * self.width as f64) as usize;
-        let y = (y_ratio * self.height as f64) as usize;
+        let (x, y) = self.image_position(lat, lon);
+        let x = x as usize;
+        let y = y as usize;
 
         // Clamp to grid bounds
         let x = x.min(self.width - 1);
         let y = y.min(self.height - 1);
 
-        Some((x, y))
+        Some((x, self.origin.from_top_left(y, self.height)))
     }
 
     /// Convert grid coordinates (x, y) to geographic coordinates (lat, lon)
     pub fn grid_to_geo(&self, x: usize, y: usize) -> Option<(f64, f64)> {
-        if x >= self.width || y >= self.height {
-            return None;
-        }
+        let (x, y) = self.to_image_coords(x, y)?;
 
-        let width_deg = self.bounding_box.width();
-        let height_deg = self.bounding_box.height();
+        let (min_x, min_y, max_x, max_y) = self.projected_bounds();
 
         let x_ratio = (x as f64 + 0.5) / self.width as f64; // Center of tile
         let y_ratio = (y as f64 + 0.5) / self.height as f64;
 
-        let lon = self.bounding_box.west + x_ratio * width_deg;
-        let lat = self.bounding_box.north - y_ratio * height_deg; // Flip Y axis
+        let px = min_x + x_ratio * (max_x - min_x);
+        let py = max_y - y_ratio * (max_y - min_y); // Flip Y axis
 
-        Some((lat, lon))
+        Some(self.crs.projection().unproject(px, py))
     }
 
-    /// Get all tiles of a specific type
-    pub fn tiles_of_type(&self, tile_type: &TileType) -> Vec<(usize, usize, &Tile)> {
-        let mut results = Vec::new();
+    /// Get the geographic bounds covered by the tile at (x, y)
+    pub fn tile_bounds(&self, x: usize, y: usize) -> Option<BoundingBox> {
+        let (x, y) = self.to_image_coords(x, y)?;
 
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let tile = &self.tiles[y][x];
-                if tile.tile_type == *tile_type {
-                    results.push((x, y, tile));
-                }
-            }
+        let (min_x, min_y, max_x, max_y) = self.projected_bounds();
+        let tile_width = (max_x - min_x) / self.width as f64;
+        let tile_height = (max_y - min_y) / self.height as f64;
+
+        let left = min_x + x as f64 * tile_width;
+        let top = max_y - y as f64 * tile_height; // Flip Y axis
+
+        let projection = self.crs.projection();
+        let (south, west) = projection.unproject(left, top - tile_height);
+        let (north, east) = projection.unproject(left + tile_width, top);
+
+        Some(BoundingBox::new(south, west, north, east))
+    }
+
+    /// Get the rectangle of cells overlapping `bbox`, or `None` if it lies outside the grid
+    pub fn cells_in(&self, bbox: &BoundingBox) -> Option<GridRect> {
+        let bounds = &self.bounding_box;
+        let south = bbox.south.max(bounds.south);
+        let west = bbox.west.max(bounds.west);
+        let north = bbox.north.min(bounds.north);
+        let east = bbox.east.min(bounds.east);
+        if south > north || west > east {
+            return None;
         }
 
-        results
+        let (x1, y1) = self.geo_to_grid(south, west)?;
+        let (x2, y2) = self.geo_to_grid(north, east)?;
+        Some(GridRect {
+            min: (x1.min(x2), y1.min(y2)),
+            max: (x1.max(x2), y1.max(y2)),
+        })
+    }
+
+    /// Get the geographic bounds covered by the cells of `rect`
+    pub fn rect_bounds(&self, rect: &GridRect) -> Option<BoundingBox> {
+        let first = self.tile_bounds(rect.min.0, rect.min.1)?;
+        let last = self.tile_bounds(rect.max.0, rect.max.1)?;
+        Some(BoundingBox::new(
+            first.south.min(last.south),
+            first.west.min(last.west),
+            first.north.max(last.north),
+            first.east.max(last.east),
+        ))
+    }
+
+    /// Get all tiles of a specific type
+    pub fn tiles_of_type(&self, tile_type: &TileType) -> Vec<(usize, usize, &Tile)> {
+        self.iter_tiles()
+            .filter(|(_, _, tile)| tile.tile_type == *tile_type)
+            .collect()
     }
 
     /// Count tiles by type
     pub fn count_tiles_by_type(&self) -> HashMap<TileType, usize> {
-        let mut counts = HashMap::new();
-
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let tile_type = &self.tiles[y][x].tile_type;
-                *counts.entry(tile_type.clone()).or_insert(0) += 1;
-            }
+        // Count by reference so tile types are only cloned once per distinct type
+        let mut counts: HashMap<&TileType, usize> = HashMap::new();
+        for tile in &self.tiles {
+            *counts.entry(&tile.tile_type).or_insert(0) += 1;
         }
 
         counts
+            .into_iter()
+            .map(|(tile_type, count)| (tile_type.clone(), count))
+            .collect()
+    }
+
+    /// Get the east-west size of a tile in meters at the grid's center latitude
+    ///
+    /// Degrees of longitude shrink towards the poles, so away from the
+    /// equator tiles of a WGS84 grid are narrower than they are tall. Both
+    /// axes are derived from `meters_per_tile` and [`Self::tile_aspect_ratio`],
+    /// so their geometric mean stays `meters_per_tile`.
+    pub fn meters_per_tile_x(&self) -> f32 {
+        self.meters_per_tile * self.tile_aspect_ratio().sqrt()
+    }
+
+    /// Get the north-south size of a tile in meters, see [`Self::meters_per_tile_x`]
+    pub fn meters_per_tile_y(&self) -> f32 {
+        self.meters_per_tile / self.tile_aspect_ratio().sqrt()
+    }
+
+    /// Get the ratio of tile width to tile height in meters (1.0 for square tiles)
+    pub fn tile_aspect_ratio(&self) -> f32 {
+        let width = self.bounding_box.width_m() / self.width.max(1) as f64;
+        let height = self.bounding_box.height_m() / self.height.max(1) as f64;
+        if width > 0.0 && height > 0.0 {
+            (width / height) as f32
+        } else {
+            1.0
+        }
     }
 
     /// Get statistics about the grid
@@ -388,6 +739,45 @@ impl TileGrid {
         let total_tiles = self.tile_count();
         let non_empty_tiles = total_tiles - counts.get(&TileType::Empty).unwrap_or(&0);
 
+        let tile_x_m = f64::from(self.meters_per_tile_x());
+        let tile_y_m = f64::from(self.meters_per_tile_y());
+        let tile_area_m2 = tile_x_m * tile_y_m;
+        let water_tiles = *counts.get(&TileType::Water).unwrap_or(&0);
+        let type_area_km2: HashMap<TileType, f64> = counts
+            .iter()
+            .map(|(tile_type, &count)| (tile_type.clone(), count as f64 * tile_area_m2 / 1e6))
+            .collect();
+
+        // Boundary edges count towards the perimeter of their tile type, links
+        // between road tiles towards the road length
+        let mut type_perimeter_km: HashMap<TileType, f64> = HashMap::new();
+        let mut road_length_m = 0.0;
+        for (x, y, tile) in self.iter_tiles() {
+            if tile.tile_type == TileType::Empty {
+                continue;
+            }
+            // (neighbor, length of the shared edge, distance between centers if counted here)
+            let sides = [
+                (x.checked_sub(1).map(|nx| (nx, y)), tile_y_m, None),
+                (Some((x + 1, y)), tile_y_m, Some(tile_x_m)),
+                (y.checked_sub(1).map(|ny| (x, ny)), tile_x_m, None),
+                (Some((x, y + 1)), tile_x_m, Some(tile_y_m)),
+            ];
+            for (neighbor, edge_m, link_m) in sides {
+                match neighbor.and_then(|(nx, ny)| self.get_tile(nx, ny)) {
+                    Some(other) if other.tile_type == tile.tile_type => {
+                        if tile.tile_type == TileType::Road {
+                            road_length_m += link_m.unwrap_or(0.0);
+                        }
+                    }
+                    _ => {
+                        *type_perimeter_km.entry(tile.tile_type.clone()).or_default() +=
+                            edge_m / 1000.0;
+                    }
+                }
+            }
+        }
+
         GridStatistics {
             total_tiles,
             non_empty_tiles,
@@ -396,12 +786,27 @@ impl TileGrid {
             dimensions: self.dimensions(),
             area_km2: self.bounding_box.area_km2(),
             meters_per_tile: self.meters_per_tile,
+            tile_area_m2,
+            building_footprint_km2: type_area_km2
+                .get(&TileType::Building)
+                .copied()
+                .unwrap_or(0.0),
+            water_coverage_ratio: water_tiles as f64 / total_tiles as f64,
+            type_area_km2,
+            type_perimeter_km,
+            road_length_km: road_length_m / 1000.0,
+            unclassified_elements: self.metadata.unclassified.total,
+            top_unclassified: self.metadata.unclassified.most_common(10),
         }
     }
 
     /// Iterate over all tiles with their coordinates
     pub fn iter_tiles(&self) -> impl Iterator<Item = (usize, usize, &Tile)> {
-        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| (x, y, &self.tiles[y][x])))
+        let width = self.width.max(1);
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(move |(index, tile)| (index % width, index / width, tile))
     }
 
     /// Get a slice of the grid for a specific area
@@ -420,7 +825,7 @@ impl TileGrid {
         for y in y_start..y_start + height {
             let mut row = Vec::with_capacity(width);
             for x in x_start..x_start + width {
-                row.push(&self.tiles[y][x]);
+                row.push(&self.tiles[y * self.width + x]);
             }
             result.push(row);
         }
@@ -428,17 +833,226 @@ impl TileGrid {
         Some(result)
     }
 
-    /// Get raw access to the tiles data (for advanced use)
-    pub fn tiles(&self) -> &Vec<Vec<Tile>> {
+    /// Get the attribution for this grid's data
+    ///
+    /// Falls back to the standard OpenStreetMap attribution when the grid
+    /// carries no recorded source, since all OSM-derived data requires it.
+    pub fn attribution(&self) -> Attribution {
+        self.metadata
+            .attribution
+            .clone()
+            .unwrap_or_else(Attribution::openstreetmap)
+    }
+
+    /// Get raw access to the tiles data, indexed as `tiles()[y][x]` (for advanced use)
+    pub fn tiles(&self) -> TileRows<'_> {
+        TileRows {
+            tiles: &self.tiles,
+            width: self.width,
+        }
+    }
+
+    /// Get mutable raw access to the tiles data, indexed as `tiles_mut()[y][x]` (for advanced use)
+    pub fn tiles_mut(&mut self) -> TileRowsMut<'_> {
+        TileRowsMut {
+            tiles: &mut self.tiles,
+            width: self.width,
+        }
+    }
+
+    /// Get the tiles in row-major order, indexed by `y * width + x`
+    pub fn flat_tiles(&self) -> &[Tile] {
         &self.tiles
     }
 
-    /// Get mutable raw access to the tiles data (for advanced use)
-    pub fn tiles_mut(&mut self) -> &mut Vec<Vec<Tile>> {
+    /// Get mutable access to the tiles in row-major order, indexed by `y * width + x`
+    pub fn flat_tiles_mut(&mut self) -> &mut [Tile] {
         &mut self.tiles
     }
 }
 
+/// Row view of the tiles of a grid, see [`TileGrid::tiles`]
+#[derive(Debug, Clone, Copy)]
+pub struct TileRows<'a> {
+    tiles: &'a [Tile],
+    width: usize,
+}
+
+impl<'a> TileRows<'a> {
+    /// Get the number of rows
+    pub fn len(&self) -> usize {
+        self.tiles.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    /// Check if there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get one row, `None` if it is outside the grid
+    pub fn get(&self, y: usize) -> Option<&'a [Tile]> {
+        (y < self.len()).then(|| &self.tiles[y * self.width..(y + 1) * self.width])
+    }
+
+    /// Iterate over the rows
+    pub fn iter(&self) -> std::slice::Chunks<'a, Tile> {
+        self.tiles.chunks(self.width.max(1))
+    }
+}
+
+impl Index<usize> for TileRows<'_> {
+    type Output = [Tile];
+
+    fn index(&self, y: usize) -> &[Tile] {
+        &self.tiles[y * self.width..(y + 1) * self.width]
+    }
+}
+
+impl<'a> IntoIterator for TileRows<'a> {
+    type Item = &'a [Tile];
+    type IntoIter = std::slice::Chunks<'a, Tile>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Mutable row view of the tiles of a grid, see [`TileGrid::tiles_mut`]
+#[derive(Debug)]
+pub struct TileRowsMut<'a> {
+    tiles: &'a mut [Tile],
+    width: usize,
+}
+
+impl TileRowsMut<'_> {
+    /// Get the number of rows
+    pub fn len(&self) -> usize {
+        self.tiles.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    /// Check if there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the rows
+    pub fn iter_mut(&mut self) -> std::slice::ChunksMut<'_, Tile> {
+        self.tiles.chunks_mut(self.width.max(1))
+    }
+}
+
+impl Index<usize> for TileRowsMut<'_> {
+    type Output = [Tile];
+
+    fn index(&self, y: usize) -> &[Tile] {
+        &self.tiles[y * self.width..(y + 1) * self.width]
+    }
+}
+
+impl IndexMut<usize> for TileRowsMut<'_> {
+    fn index_mut(&mut self, y: usize) -> &mut [Tile] {
+        &mut self.tiles[y * self.width..(y + 1) * self.width]
+    }
+}
+
+impl<'a> IntoIterator for TileRowsMut<'a> {
+    type Item = &'a mut [Tile];
+    type IntoIter = std::slice::ChunksMut<'a, Tile>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tiles.chunks_mut(self.width.max(1))
+    }
+}
+
+// Tiles are serialized as nested rows, keeping the format of grids saved by earlier versions
+impl Serialize for TileGrid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("TileGrid", 10)?;
+        state.serialize_field("tiles", &Rows(self))?;
+        state.serialize_field("width", &self.width)?;
+        state.serialize_field("height", &self.height)?;
+        state.serialize_field("bounding_box", &self.bounding_box)?;
+        state.serialize_field("meters_per_tile", &self.meters_per_tile)?;
+        state.serialize_field("crs", &self.crs)?;
+        state.serialize_field("origin", &self.origin)?;
+        state.serialize_field("pois", &self.pois)?;
+        state.serialize_field("districts", &self.districts)?;
+        state.serialize_field("metadata", &self.metadata)?;
+        state.end()
+    }
+}
+
+/// Serializes the tiles of a grid as a sequence of rows without copying them
+struct Rows<'a>(&'a TileGrid);
+
+impl Serialize for Rows<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq((0..self.0.height).filter_map(|y| self.0.row(y)))
+    }
+}
+
+/// Unchecked serialized form of a [`TileGrid`]
+#[derive(Deserialize)]
+struct TileGridData {
+    tiles: Vec<Vec<Tile>>,
+    width: usize,
+    height: usize,
+    bounding_box: BoundingBox,
+    meters_per_tile: f32,
+    #[serde(default)]
+    crs: Crs,
+    #[serde(default)]
+    origin: GridOrigin,
+    #[serde(default)]
+    pois: PoiIndex,
+    #[serde(default)]
+    districts: Districts,
+    metadata: GridMetadata,
+}
+
+impl TryFrom<TileGridData> for TileGrid {
+    type Error = OsmTilesError;
+
+    fn try_from(data: TileGridData) -> Result<Self, Self::Error> {
+        if data.tiles.len() != data.height {
+            return Err(OsmTilesError::Parse(format!(
+                "Grid of {}x{} tiles has {} rows",
+                data.width,
+                data.height,
+                data.tiles.len()
+            )));
+        }
+        if let Some((y, row)) = data
+            .tiles
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != data.width)
+        {
+            return Err(OsmTilesError::Parse(format!(
+                "Row {} of a grid {} tiles wide has {} tiles",
+                y,
+                data.width,
+                row.len()
+            )));
+        }
+
+        Ok(Self {
+            tiles: data.tiles.into_iter().flatten().collect(),
+            width: data.width,
+            height: data.height,
+            bounding_box: data.bounding_box,
+            meters_per_tile: data.meters_per_tile,
+            crs: data.crs,
+            origin: data.origin,
+            pois: data.pois,
+            districts: data.districts,
+            metadata: data.metadata,
+        })
+    }
+}
+
 /// Statistics about a tile grid
 #[derive(Debug, Clone)]
 pub struct GridStatistics {
@@ -456,6 +1070,25 @@ pub struct GridStatistics {
     pub area_km2: f64,
     /// Approximate meters per tile
     pub meters_per_tile: f32,
+    /// Ground area of one tile in m²
+    pub tile_area_m2: f64,
+    /// Ground area covered by each tile type in km²
+    pub type_area_km2: HashMap<TileType, f64>,
+    /// Length in km of the outlines of each non-empty tile type's regions, grid edges included
+    pub type_perimeter_km: HashMap<TileType, f64>,
+    /// Estimated road length in km: the distance between the centers of edge-connected road tiles
+    ///
+    /// Exact for roads one tile wide; corridors several tiles wide count each
+    /// lane of tiles, and diagonal roads their staircase steps.
+    pub road_length_km: f64,
+    /// Ground area covered by buildings in km²
+    pub building_footprint_km2: f64,
+    /// Ratio of water tiles to total tiles
+    pub water_coverage_ratio: f64,
+    /// Number of tagged elements no classification rule recognized
+    pub unclassified_elements: usize,
+    /// Most common tag combinations of unclassified elements, most frequent first
+    pub top_unclassified: Vec<(String, usize)>,
 }
 
 #[cfg(test)]
@@ -517,6 +1150,13 @@ mod tests {
         assert!(!building.can_be_overwritten_by(&road));
     }
 
+    #[test]
+    fn test_default_heights() {
+        assert_eq!(TileType::Empty.default_height(), 0.0);
+        assert!(TileType::Building.default_height() > TileType::Road.default_height());
+        assert!(TileType::Custom("tower".to_string()).default_height() > 0.0);
+    }
+
     #[test]
     fn test_set_tile_with_priority() {
         let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
@@ -570,6 +1210,47 @@ mod tests {
         assert_eq!(stats.dimensions, (10, 10));
     }
 
+    #[test]
+    fn test_grid_physical_statistics() {
+        let bbox = BoundingBox::new(52.0, 13.0, 52.01, 13.01);
+        let mut grid = TileGrid::new(10, 10, bbox, 10.0);
+        for x in 0..3 {
+            grid.set_tile(x, 5, Tile::new(TileType::Road)).unwrap();
+        }
+        for (x, y) in [(5, 5), (6, 5), (5, 6), (6, 6)] {
+            grid.set_tile(x, y, Tile::new(TileType::Building)).unwrap();
+        }
+        grid.set_tile(9, 9, Tile::new(TileType::Water)).unwrap();
+
+        let stats = grid.statistics();
+        let (tile_x_km, tile_y_km) = (
+            f64::from(grid.meters_per_tile_x()) / 1000.0,
+            f64::from(grid.meters_per_tile_y()) / 1000.0,
+        );
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!((stats.tile_area_m2 - 100.0).abs() < 1e-3);
+        assert!(close(
+            stats.building_footprint_km2,
+            4.0 * stats.tile_area_m2 / 1e6
+        ));
+        assert!(close(
+            stats.type_area_km2[&TileType::Road],
+            3.0 * stats.tile_area_m2 / 1e6
+        ));
+        assert!(close(stats.road_length_km, 2.0 * tile_x_km));
+        assert!(close(
+            stats.type_perimeter_km[&TileType::Building],
+            4.0 * tile_x_km + 4.0 * tile_y_km
+        ));
+        // The road touches the grid's west edge, which bounds it too
+        assert!(close(
+            stats.type_perimeter_km[&TileType::Road],
+            6.0 * tile_x_km + 2.0 * tile_y_km
+        ));
+        assert!(!stats.type_perimeter_km.contains_key(&TileType::Empty));
+        assert_eq!(stats.water_coverage_ratio, 0.01);
+    }
+
     #[test]
     fn test_serialization() {
         let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
@@ -595,4 +1276,142 @@ mod tests {
             TileType::Road
         );
     }
+
+    #[test]
+    fn test_serialized_rows_and_shared_metadata() {
+        let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
+        let mut grid = TileGrid::new(3, 2, bbox, 10.0);
+        let tile = Tile::with_metadata(TileType::Water, TileMetadata::default());
+        grid.set_tile(2, 1, tile.clone()).unwrap();
+        grid.set_tile(0, 1, tile).unwrap();
+
+        // Tiles are stored flat but share one metadata allocation
+        let shared = |grid: &TileGrid, x, y| grid.get_tile(x, y).unwrap().metadata.clone().unwrap();
+        assert!(SharedMetadata::ptr_eq(
+            &shared(&grid, 0, 1),
+            &shared(&grid, 2, 1)
+        ));
+        assert_eq!(grid.row(1).unwrap()[2].tile_type, TileType::Water);
+        assert!(grid.row(2).is_none());
+
+        // Row views index like nested rows, flat access by row-major index
+        assert_eq!(grid.tiles().len(), 2);
+        assert_eq!(grid.tiles()[1][2].tile_type, TileType::Water);
+        assert_eq!(grid.tiles().iter().map(<[Tile]>::len).sum::<usize>(), 6);
+        assert_eq!(grid.flat_tiles()[5].tile_type, TileType::Water);
+        grid.tiles_mut()[0][1] = Tile::new(TileType::Road);
+        assert_eq!(grid.flat_tiles()[1].tile_type, TileType::Road);
+
+        // Copy-on-write keeps other tiles untouched
+        grid.get_tile_mut(0, 1).unwrap().metadata_mut().confidence = 0.5;
+        assert_eq!(shared(&grid, 2, 1).confidence, 1.0);
+        let metadata = grid.get_tile_mut(2, 1).unwrap().metadata.as_mut().unwrap();
+        metadata.tags.insert("name".into(), "Spree".into());
+        assert!(shared(&grid, 0, 1).tags.is_empty());
+
+        // The serialized layout is still one array per row
+        let value = serde_json::to_value(&grid).unwrap();
+        let rows = value["tiles"].as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].as_array().unwrap().len(), 3);
+
+        let loaded: TileGrid = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(loaded.get_tile(2, 1).unwrap().tile_type, TileType::Water);
+        assert_eq!(loaded.count_tiles_by_type(), grid.count_tiles_by_type());
+
+        // Row counts and widths are checked when loading
+        let mut ragged = value.clone();
+        let tile = ragged["tiles"][1][0].clone();
+        ragged["tiles"][0].as_array_mut().unwrap().pop();
+        ragged["tiles"][1].as_array_mut().unwrap().push(tile);
+        let error = serde_json::from_value::<TileGrid>(ragged).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Row 0 of a grid 3 tiles wide has 2 tiles")
+        );
+
+        let mut short = value;
+        short["tiles"].as_array_mut().unwrap().pop();
+        let error = serde_json::from_value::<TileGrid>(short).unwrap_err();
+        assert!(error.to_string().contains("3x2 tiles has 1 rows"));
+    }
+
+    #[test]
+    fn test_grid_origin() {
+        let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
+        let mut grid = TileGrid::new(10, 4, bbox.clone(), 10.0);
+        let (x, y) = grid.geo_to_grid(52.9, 13.05).unwrap();
+        assert_eq!((x, y), (0, 0));
+        grid.set_tile(x, y, Tile::new(TileType::Water)).unwrap();
+
+        // Flipping keeps the tile at its geographic position
+        grid.set_origin(GridOrigin::BottomLeft);
+        assert_eq!(grid.geo_to_grid(52.9, 13.05), Some((0, 3)));
+        assert_eq!(grid.get_tile(0, 3).unwrap().tile_type, TileType::Water);
+        assert_eq!(grid.to_image_coords(0, 3), Some((0, 0)));
+        assert_eq!(grid.from_image_coords(0, 0), Some((0, 3)));
+
+        let (lat, _) = grid.grid_to_geo(0, 0).unwrap();
+        assert!(lat < 52.5);
+        assert!(grid.tile_bounds(0, 3).unwrap().contains(52.9, 13.05));
+
+        let fresh = TileGrid::new(10, 4, bbox, 10.0).with_origin(GridOrigin::BottomLeft);
+        assert_eq!(fresh.geo_to_grid(52.9, 13.05), Some((0, 3)));
+    }
+
+    #[test]
+    fn test_tile_bounds() {
+        let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
+        let grid = TileGrid::new(10, 10, bbox, 10.0);
+
+        // Top-left tile touches the north-west corner
+        let bounds = grid.tile_bounds(0, 0).unwrap();
+        assert!((bounds.north - 53.0).abs() < 1e-9);
+        assert!((bounds.west - 13.0).abs() < 1e-9);
+        assert!((bounds.south - 52.9).abs() < 1e-9);
+        assert!((bounds.east - 13.1).abs() < 1e-9);
+
+        // Tile center matches grid_to_geo
+        let (lat, lon) = grid.grid_to_geo(3, 7).unwrap();
+        assert!(grid.tile_bounds(3, 7).unwrap().contains(lat, lon));
+
+        assert!(grid.tile_bounds(10, 0).is_none());
+    }
+
+    #[test]
+    fn test_web_mercator_grid_coordinates() {
+        // High-latitude area, where northern degrees of latitude are stretched
+        let bbox = BoundingBox::new(60.0, 18.0, 75.0, 20.0);
+        let grid = TileGrid::new(100, 100, bbox.clone(), 10.0).with_crs(Crs::WebMercator);
+
+        // Corners still map to the grid corners
+        assert_eq!(grid.geo_to_grid(75.0, 18.0), Some((0, 0)));
+        assert_eq!(grid.geo_to_grid(60.0, 20.0), Some((99, 99)));
+
+        // The latitude midpoint lies south of the projected midpoint
+        let (_, y) = grid.geo_to_grid(67.5, 19.0).unwrap();
+        assert!(y > 55);
+
+        // Round trip through tile centers
+        let (lat, lon) = grid.grid_to_geo(25, 75).unwrap();
+        assert_eq!(grid.geo_to_grid(lat, lon), Some((25, 75)));
+        assert!(grid.tile_bounds(25, 75).unwrap().contains(lat, lon));
+
+        let plain = TileGrid::new(100, 100, bbox, 10.0);
+        assert_ne!(plain.grid_to_geo(25, 75), grid.grid_to_geo(25, 75));
+    }
+
+    #[test]
+    fn test_attribution_fallback() {
+        let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
+        let mut grid = TileGrid::new(5, 5, bbox, 10.0);
+
+        // Grids without recorded attribution still carry the OSM license
+        assert_eq!(grid.attribution(), Attribution::openstreetmap());
+
+        let metadata = crate::OsmMetadata::new("overpass-api.de", "overpass");
+        grid.metadata.attribution = Some(Attribution::from_osm_metadata(&metadata));
+        assert_eq!(grid.attribution().source, "overpass-api.de");
+    }
 }
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{OsmElement, SOURCE_TAG};
+
+/// Tags describing an element rather than its kind; they are left out of tag combinations
+const DESCRIPTIVE_TAGS: [&str; 9] = [
+    "name",
+    "source",
+    "note",
+    "description",
+    "fixme",
+    "ref",
+    "wikidata",
+    "wikipedia",
+    SOURCE_TAG,
+];
+
+/// Prefixes of descriptive tags (e.g., `addr:street`, `name:de`)
+const DESCRIPTIVE_PREFIXES: [&str; 6] = ["addr:", "name:", "source:", "note:", "ref:", "contact:"];
+
+/// Tagged elements that no classification rule recognized, grouped by tag combination
+///
+/// A combination lists the element's `key=value` pairs in key order, without
+/// descriptive tags such as names and addresses. The most common combinations
+/// are good candidates for custom rules.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnclassifiedTags {
+    /// Number of unclassified elements carrying at least one tag
+    pub total: usize,
+    /// Number of elements per tag combination
+    pub combinations: HashMap<String, usize>,
+}
+
+impl UnclassifiedTags {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the tag combination of an element, or `None` if only descriptive tags are set
+    pub fn combination(element: &OsmElement) -> Option<String> {
+        let mut pairs: Vec<String> = element
+            .tags
+            .iter()
+            .filter(|(key, _)| {
+                !DESCRIPTIVE_TAGS.contains(&key.as_str())
+                    && !DESCRIPTIVE_PREFIXES
+                        .iter()
+                        .any(|prefix| key.starts_with(prefix))
+            })
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        if pairs.is_empty() {
+            return None;
+        }
+        pairs.sort();
+        Some(pairs.join(";"))
+    }
+
+    /// Record an element that was classified as empty
+    pub fn record(&mut self, element: &OsmElement) {
+        if let Some(combination) = Self::combination(element) {
+            self.total += 1;
+            *self.combinations.entry(combination).or_insert(0) += 1;
+        }
+    }
+
+    /// Get the `limit` most common combinations, most frequent first
+    pub fn most_common(&self, limit: usize) -> Vec<(String, usize)> {
+        let mut combinations: Vec<(String, usize)> = self
+            .combinations
+            .iter()
+            .map(|(combination, count)| (combination.clone(), *count))
+            .collect();
+        combinations.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        combinations.truncate(limit);
+        combinations
+    }
+
+    /// Check whether no unclassified elements were recorded
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsmElementType;
+
+    fn element(tags: &[(&str, &str)]) -> OsmElement {
+        OsmElement {
+            id: 1,
+            element_type: OsmElementType::Node,
+            holes: Vec::new(),
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            geometry: vec![(52.5, 13.4)],
+        }
+    }
+
+    #[test]
+    fn test_unclassified_combinations() {
+        let mut unclassified = UnclassifiedTags::new();
+        let tower = element(&[
+            ("tower:type", "communication"),
+            ("man_made", "tower"),
+            ("name", "Funkturm"),
+        ]);
+        unclassified.record(&tower);
+        unclassified.record(&tower);
+        unclassified.record(&element(&[("barrier", "fence")]));
+        // Only descriptive tags: nothing to suggest a rule for
+        unclassified.record(&element(&[("name", "Somewhere"), ("addr:street", "Main")]));
+
+        assert_eq!(unclassified.total, 3);
+        assert_eq!(
+            unclassified.most_common(10),
+            vec![
+                ("man_made=tower;tower:type=communication".to_string(), 2),
+                ("barrier=fence".to_string(), 1),
+            ]
+        );
+        assert_eq!(unclassified.most_common(1).len(), 1);
+    }
+}
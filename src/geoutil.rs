@@ -0,0 +1,82 @@
+//! Great-circle math on `(lat, lon)` pairs in degrees
+//!
+//! The crate measures distances on a sphere with the mean Earth radius
+//! (haversine), which is accurate to about 0.5% and plenty for laying out
+//! game maps. These are the same helpers the providers and grids use, so
+//! results match the crate's own measurements.
+
+use geo::{Bearing, Destination, Distance, Haversine, Point};
+
+use crate::BoundingBox;
+
+fn point((lat, lon): (f64, f64)) -> Point {
+    Point::new(lon, lat)
+}
+
+/// Get the great-circle distance between two positions in meters
+pub fn distance_m(a: (f64, f64), b: (f64, f64)) -> f64 {
+    Haversine.distance(point(a), point(b))
+}
+
+/// Get the initial bearing from `a` towards `b` in degrees clockwise from north, in `[0, 360)`
+pub fn bearing(a: (f64, f64), b: (f64, f64)) -> f64 {
+    Haversine.bearing(point(a), point(b))
+}
+
+/// Get the position reached by traveling `distance_m` meters from `start` along `bearing_deg`
+pub fn destination(start: (f64, f64), bearing_deg: f64, distance_m: f64) -> (f64, f64) {
+    let end = Haversine.destination(point(start), bearing_deg, distance_m);
+    (end.y(), end.x())
+}
+
+/// Get the smallest bounding box containing all positions, `None` if there are none
+pub fn bbox_from_points(points: impl IntoIterator<Item = (f64, f64)>) -> Option<BoundingBox> {
+    points.into_iter().fold(None, |bbox, (lat, lon)| {
+        Some(match bbox {
+            None => BoundingBox::new(lat, lon, lat, lon),
+            Some(bbox) => BoundingBox::new(
+                bbox.south.min(lat),
+                bbox.west.min(lon),
+                bbox.north.max(lat),
+                bbox.east.max(lon),
+            ),
+        })
+    })
+}
+
+/// Get the length of one degree of latitude and of longitude at `lat`, in meters
+///
+/// Returns `(north_south, east_west)`; the east-west length shrinks with the
+/// cosine of the latitude and is zero at the poles.
+pub fn meters_per_degree(lat: f64) -> (f64, f64) {
+    let north_south = Haversine.radius() * std::f64::consts::PI / 180.0;
+    (north_south, north_south * lat.to_radians().cos().max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_great_circle_helpers() {
+        let berlin = (52.52, 13.405);
+        let hamburg = (53.5511, 9.9937);
+        let distance = distance_m(berlin, hamburg);
+        assert!((distance - 255_000.0).abs() < 2_000.0);
+
+        let heading = bearing(berlin, hamburg);
+        assert!((290.0..320.0).contains(&heading));
+        let (lat, lon) = destination(berlin, heading, distance);
+        assert!((lat - hamburg.0).abs() < 1e-6 && (lon - hamburg.1).abs() < 1e-6);
+        assert_eq!(bearing(berlin, (53.0, 13.405)), 0.0);
+
+        let bbox = bbox_from_points([berlin, hamburg, (52.0, 11.0)]).unwrap();
+        assert_eq!(bbox, BoundingBox::new(52.0, 9.9937, 53.5511, 13.405));
+        assert!(bbox_from_points(std::iter::empty()).is_none());
+
+        let (north_south, east_west) = meters_per_degree(60.0);
+        assert!((north_south - 111_195.0).abs() < 1.0);
+        assert!((east_west - north_south / 2.0).abs() < 1e-6);
+        assert!((distance_m((0.0, 0.0), (1.0, 0.0)) - north_south).abs() < 1e-6);
+    }
+}
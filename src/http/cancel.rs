@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Handle for aborting requests from elsewhere, e.g. when the player leaves a loading screen
+///
+/// Clones share their state: cancelling one cancels every request that
+/// was given any clone. A cancelled token stays cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<Mutex<TokenState>>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: bool,
+    /// Wakers of the pending `Cancelled` futures, by future
+    wakers: HashMap<u64, Waker>,
+    next_id: u64,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every request using this token
+    pub fn cancel(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.cancelled = true;
+        for (_, waker) in state.wakers.drain() {
+            waker.wake();
+        }
+    }
+
+    /// Check whether the token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// Get a future that resolves once the token is cancelled
+    pub fn cancelled(&self) -> Cancelled {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        Cancelled {
+            state: self.state.clone(),
+            id: state.next_id,
+        }
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`]
+pub struct Cancelled {
+    state: Arc<Mutex<TokenState>>,
+    id: u64,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+        state.wakers.insert(self.id, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        // Long-lived tokens must not collect the wakers of finished requests
+        self.state.lock().unwrap().wakers.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{HttpError, HttpResult, RequestOptions};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let waiting = token.cancelled();
+        assert!(!token.is_cancelled());
+
+        let clone = token.clone();
+        std::thread::spawn(move || clone.cancel());
+        waiting.await;
+        assert!(token.is_cancelled());
+        // Futures created after cancelling resolve right away
+        token.cancelled().await;
+        assert!(token.state.lock().unwrap().wakers.is_empty());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_request_options_abort_stuck_requests() {
+        let stuck = || std::future::pending::<HttpResult<()>>();
+
+        let options = RequestOptions::new().with_timeout(Duration::from_millis(20));
+        let result = options.run(stuck()).await;
+        assert!(matches!(result, Err(HttpError::Timeout { seconds: 1 })));
+
+        let token = CancellationToken::new();
+        let options = RequestOptions::new().with_cancellation(token.clone());
+        let clone = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            clone.cancel();
+        });
+        assert!(matches!(
+            options.run(stuck()).await,
+            Err(HttpError::Cancelled)
+        ));
+
+        // Finished requests are passed through
+        assert!(RequestOptions::new().run(async { Ok(()) }).await.is_ok());
+    }
+}
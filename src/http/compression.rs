@@ -0,0 +1,277 @@
+use flate2::{read, write};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use super::{HttpError, HttpResult};
+
+/// Value sent in the `Accept-Encoding` header when compression is enabled
+pub const ACCEPT_ENCODING: &str = "gzip";
+
+/// Size of a response on the wire and after decompression
+///
+/// Transports that decompress on their own (browsers, ureq) strip the
+/// `Content-Encoding` header, so for them `content_encoding` is `None` and
+/// both sizes are equal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferStats {
+    /// Encoding the body was transferred with (e.g., "gzip")
+    pub content_encoding: Option<String>,
+    /// Bytes received on the wire
+    pub wire_bytes: usize,
+    /// Bytes after decompression
+    pub body_bytes: usize,
+}
+
+impl TransferStats {
+    /// Get the ratio of decompressed to transferred bytes (1.0 when uncompressed)
+    pub fn compression_ratio(&self) -> f64 {
+        if self.wire_bytes == 0 {
+            1.0
+        } else {
+            self.body_bytes as f64 / self.wire_bytes as f64
+        }
+    }
+}
+
+/// Decompress a response body according to its `Content-Encoding` header
+///
+/// The encoding and length headers are removed after decompression, since
+/// they no longer describe the returned body. Decompression stops with
+/// [`HttpError::ResponseTooLarge`] as soon as the body exceeds `limit` bytes.
+pub fn decode_body(
+    body: Vec<u8>,
+    headers: &mut HashMap<String, String>,
+    limit: Option<usize>,
+) -> HttpResult<(Vec<u8>, TransferStats)> {
+    let wire_bytes = body.len();
+    let encoding = content_encoding(headers);
+
+    let body = match encoding.as_deref() {
+        None | Some("") | Some("identity") => {
+            return Ok((
+                body,
+                TransferStats {
+                    content_encoding: None,
+                    wire_bytes,
+                    body_bytes: wire_bytes,
+                },
+            ));
+        }
+        Some("gzip") | Some("x-gzip") => {
+            let capacity = wire_bytes.saturating_mul(4);
+            let mut decoded =
+                Vec::with_capacity(limit.map_or(capacity, |limit| capacity.min(limit)));
+            // One byte past the limit tells a body at the limit from a larger one
+            let max_bytes = limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+            read::MultiGzDecoder::new(body.as_slice())
+                .take(max_bytes)
+                .read_to_end(&mut decoded)
+                .map_err(gzip_error)?;
+            if let Some(limit_bytes) = limit
+                && decoded.len() > limit_bytes
+            {
+                return Err(HttpError::ResponseTooLarge {
+                    limit_bytes,
+                    received_bytes: decoded.len(),
+                });
+            }
+            decoded
+        }
+        Some(other) => return Err(unsupported_encoding(other)),
+    };
+
+    strip_encoding_headers(headers);
+
+    let stats = TransferStats {
+        content_encoding: encoding,
+        wire_bytes,
+        body_bytes: body.len(),
+    };
+    Ok((body, stats))
+}
+
+/// Incremental counterpart of [`decode_body`] for bodies read in chunks
+pub struct BodyDecoder {
+    encoding: Option<String>,
+    gzip: Option<write::MultiGzDecoder<Vec<u8>>>,
+    body: Vec<u8>,
+    wire_bytes: usize,
+}
+
+impl BodyDecoder {
+    /// Create a decoder for a response with these headers
+    pub fn new(headers: &HashMap<String, String>) -> HttpResult<Self> {
+        let encoding = content_encoding(headers);
+        let gzip = match encoding.as_deref() {
+            None | Some("") | Some("identity") => None,
+            Some("gzip") | Some("x-gzip") => Some(write::MultiGzDecoder::new(Vec::new())),
+            Some(other) => return Err(unsupported_encoding(other)),
+        };
+        Ok(Self {
+            encoding: encoding.filter(|_| gzip.is_some()),
+            gzip,
+            body: Vec::new(),
+            wire_bytes: 0,
+        })
+    }
+
+    /// Decode a chunk as received on the wire, returning the body bytes it yields
+    pub fn push(&mut self, chunk: &[u8]) -> HttpResult<&[u8]> {
+        self.wire_bytes += chunk.len();
+        let start = self.body.len();
+        match &mut self.gzip {
+            Some(decoder) => {
+                decoder
+                    .write_all(chunk)
+                    .and_then(|()| decoder.flush())
+                    .map_err(gzip_error)?;
+                self.body.append(decoder.get_mut());
+            }
+            None => self.body.extend_from_slice(chunk),
+        }
+        Ok(&self.body[start..])
+    }
+
+    /// Get the number of body bytes decoded so far
+    pub fn decoded_len(&self) -> usize {
+        self.body.len()
+    }
+
+    /// Check the end of the body and return it, removing the encoding headers like [`decode_body`]
+    pub fn finish(
+        mut self,
+        headers: &mut HashMap<String, String>,
+    ) -> HttpResult<(Vec<u8>, TransferStats)> {
+        if let Some(mut decoder) = self.gzip.take() {
+            decoder.try_finish().map_err(gzip_error)?;
+            self.body.append(decoder.get_mut());
+            strip_encoding_headers(headers);
+        }
+        let stats = TransferStats {
+            content_encoding: self.encoding,
+            wire_bytes: self.wire_bytes,
+            body_bytes: self.body.len(),
+        };
+        Ok((self.body, stats))
+    }
+}
+
+fn content_encoding(headers: &HashMap<String, String>) -> Option<String> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-encoding"))
+        .map(|(_, value)| value.trim().to_lowercase())
+}
+
+fn strip_encoding_headers(headers: &mut HashMap<String, String>) {
+    headers.retain(|key, _| {
+        !key.eq_ignore_ascii_case("content-encoding") && !key.eq_ignore_ascii_case("content-length")
+    });
+}
+
+fn gzip_error(error: std::io::Error) -> HttpError {
+    HttpError::RequestFailed {
+        message: format!("Failed to decompress gzip response: {}", error),
+    }
+}
+
+fn unsupported_encoding(encoding: &str) -> HttpError {
+    HttpError::RequestFailed {
+        message: format!("Unsupported content encoding '{}'", encoding),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decode_gzip_body() {
+        let json = r#"{"elements":[]}"#.repeat(100);
+        let compressed = gzip(json.as_bytes());
+        let mut headers = HashMap::from([
+            ("Content-Encoding".to_string(), "gzip".to_string()),
+            ("content-length".to_string(), compressed.len().to_string()),
+            ("content-type".to_string(), "application/json".to_string()),
+        ]);
+
+        let encoded_headers = headers.clone();
+        let (body, stats) = decode_body(compressed.clone(), &mut headers, None).unwrap();
+        assert_eq!(body, json.as_bytes());
+        assert_eq!(stats.content_encoding.as_deref(), Some("gzip"));
+        assert_eq!(stats.wire_bytes, compressed.len());
+        assert_eq!(stats.body_bytes, json.len());
+        assert!(stats.compression_ratio() > 10.0);
+        assert_eq!(headers.len(), 1);
+
+        // The limit applies to the decompressed body, which is not inflated past it
+        let (body, _) = decode_body(
+            compressed.clone(),
+            &mut encoded_headers.clone(),
+            Some(json.len()),
+        )
+        .unwrap();
+        assert_eq!(body.len(), json.len());
+        let error = decode_body(compressed, &mut encoded_headers.clone(), Some(100)).unwrap_err();
+        assert!(matches!(
+            error,
+            HttpError::ResponseTooLarge {
+                limit_bytes: 100,
+                received_bytes: 101
+            }
+        ));
+    }
+
+    #[test]
+    fn test_decode_gzip_in_chunks() {
+        let json = r#"{"elements":[]}"#.repeat(100);
+        let compressed = gzip(json.as_bytes());
+        let mut headers = HashMap::from([("Content-Encoding".to_string(), "gzip".to_string())]);
+
+        let mut decoder = BodyDecoder::new(&headers).unwrap();
+        let mut streamed = Vec::new();
+        for chunk in compressed.chunks(5) {
+            streamed.extend_from_slice(decoder.push(chunk).unwrap());
+        }
+        let tail = decoder.decoded_len();
+        let (body, stats) = decoder.finish(&mut headers).unwrap();
+        streamed.extend_from_slice(&body[tail..]);
+        assert_eq!(streamed, json.as_bytes());
+        assert_eq!(body, json.as_bytes());
+        assert_eq!(stats.wire_bytes, compressed.len());
+        assert!(headers.is_empty());
+
+        // Truncated streams fail once they end
+        let mut decoder = BodyDecoder::new(&HashMap::from([(
+            "content-encoding".to_string(),
+            "gzip".to_string(),
+        )]))
+        .unwrap();
+        decoder.push(&compressed[..compressed.len() / 2]).unwrap();
+        assert!(decoder.finish(&mut HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_identity_and_unsupported_encodings() {
+        let mut headers = HashMap::new();
+        let (body, stats) = decode_body(b"plain".to_vec(), &mut headers, None).unwrap();
+        assert_eq!(body, b"plain");
+        assert_eq!(stats.content_encoding, None);
+        assert_eq!(stats.compression_ratio(), 1.0);
+
+        let mut headers = HashMap::from([("content-encoding".to_string(), "br".to_string())]);
+        assert!(decode_body(b"data".to_vec(), &mut headers, None).is_err());
+
+        let mut headers = HashMap::from([("content-encoding".to_string(), "gzip".to_string())]);
+        assert!(decode_body(b"not gzip".to_vec(), &mut headers, None).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+mod cancel;
+mod compression;
+mod pending;
+mod query_log;
 mod traits;
 
 #[cfg(feature = "reqwest-client")]
@@ -6,6 +10,10 @@ mod reqwest_client;
 #[cfg(feature = "ehttp-client")]
 mod ehttp_client;
 
+pub use cancel::*;
+pub use compression::*;
+pub use pending::*;
+pub use query_log::*;
 pub use traits::*;
 
 #[cfg(feature = "reqwest-client")]
@@ -55,6 +63,7 @@ pub fn create_client_with_config(config: HttpConfig) -> Result<Arc<dyn HttpClien
 
     #[cfg(not(any(feature = "reqwest-client", feature = "ehttp-client")))]
     {
+        let _ = config;
         Err(
             "No HTTP client feature enabled. Enable either 'reqwest-client' or 'ehttp-client'"
                 .to_string(),
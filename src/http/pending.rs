@@ -0,0 +1,267 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Create a response slot for HTTP clients whose backend reports results through a callback
+///
+/// The [`ResponseSender`] is moved into the callback and the [`PendingResponse`]
+/// is awaited by the request. Dropping the `PendingResponse` cancels the
+/// request: the sender's result is discarded, the waker is released and
+/// [`ResponseSender::is_cancelled`] lets long-running work stop early.
+pub fn pending_response<T>() -> (ResponseSender<T>, PendingResponse<T>) {
+    let slot = Arc::new(Mutex::new(Slot {
+        value: None,
+        waker: None,
+        closed: false,
+        cancelled: false,
+        senders: 1,
+        #[cfg(not(target_arch = "wasm32"))]
+        timer: None,
+    }));
+    (
+        ResponseSender { slot: slot.clone() },
+        PendingResponse { slot },
+    )
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    waker: Option<Waker>,
+    /// Set once a value was sent or the receiver was dropped
+    closed: bool,
+    /// Set when the receiver was dropped before a value was sent
+    cancelled: bool,
+    /// Number of live senders
+    senders: usize,
+    /// Deadline thread to wake up when the slot closes early
+    #[cfg(not(target_arch = "wasm32"))]
+    timer: Option<std::thread::Thread>,
+}
+
+impl<T> Slot<T> {
+    fn close(&mut self) {
+        self.closed = true;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(timer) = self.timer.take() {
+            timer.unpark();
+        }
+    }
+}
+
+/// Completing half of [`pending_response`]; the first value sent wins
+pub struct ResponseSender<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Clone for ResponseSender<T> {
+    fn clone(&self) -> Self {
+        self.slot.lock().unwrap().senders += 1;
+        Self {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T> ResponseSender<T> {
+    /// Complete the request; returns `false` if it was already completed or cancelled
+    pub fn send(&self, value: T) -> bool {
+        let mut slot = self.slot.lock().unwrap();
+        if slot.closed {
+            return false;
+        }
+        slot.value = Some(value);
+        slot.close();
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+        true
+    }
+
+    /// Check whether the waiting future was dropped before completion
+    pub fn is_cancelled(&self) -> bool {
+        self.slot.lock().unwrap().cancelled
+    }
+
+    /// Check whether the request was completed or cancelled
+    pub fn is_closed(&self) -> bool {
+        self.slot.lock().unwrap().closed
+    }
+
+    /// Complete the request with `on_timeout()` unless it closes within `timeout`
+    ///
+    /// The deadline runs on a parked background thread that exits as soon as
+    /// the request completes or is cancelled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn expire_after(
+        &self,
+        timeout: std::time::Duration,
+        on_timeout: impl FnOnce() -> T + Send + 'static,
+    ) where
+        T: Send + 'static,
+    {
+        let sender = self.clone();
+        let deadline = std::time::Instant::now() + timeout;
+        let handle = std::thread::spawn(move || {
+            loop {
+                if sender.is_closed() {
+                    return;
+                }
+                let now = std::time::Instant::now();
+                if now >= deadline {
+                    sender.send(on_timeout());
+                    return;
+                }
+                std::thread::park_timeout(deadline - now);
+            }
+        });
+        let mut slot = self.slot.lock().unwrap();
+        if slot.closed {
+            handle.thread().unpark();
+        } else {
+            slot.timer = Some(handle.thread().clone());
+        }
+    }
+}
+
+/// Future resolving to the value of the matching [`ResponseSender`]
+///
+/// Resolves to `None` if every sender was dropped without sending.
+pub struct PendingResponse<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Future for PendingResponse<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap();
+        if let Some(value) = slot.value.take() {
+            return Poll::Ready(Some(value));
+        }
+        // Every sender is gone, so nothing can complete it anymore
+        if slot.senders == 0 {
+            return Poll::Ready(None);
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for PendingResponse<T> {
+    fn drop(&mut self) {
+        let mut slot = self.slot.lock().unwrap();
+        if !slot.closed {
+            slot.cancelled = true;
+            slot.close();
+        }
+        slot.value = None;
+        slot.waker = None;
+    }
+}
+
+impl<T> Drop for ResponseSender<T> {
+    fn drop(&mut self) {
+        let mut slot = self.slot.lock().unwrap();
+        slot.senders -= 1;
+        // The last sender going away must wake the future so it resolves to `None`
+        if slot.senders == 0
+            && let Some(waker) = slot.waker.take()
+        {
+            waker.wake();
+        }
+    }
+}
+
+/// Wait without blocking the executor
+///
+/// Natively a deadline thread is the timer; on WASM it is the `setTimeout`
+/// of the global scope, so delays work in pages and workers alike.
+pub(crate) async fn sleep(delay_ms: u64) {
+    let (sender, timer) = pending_response();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    sender.expire_after(std::time::Duration::from_millis(delay_ms), || ());
+
+    // Without a timer the wait ends at once
+    #[cfg(target_arch = "wasm32")]
+    {
+        let timeout_sender = sender.clone();
+        let scheduled = set_timeout(delay_ms, move || {
+            timeout_sender.send(());
+        });
+        if !scheduled {
+            sender.send(());
+        }
+    }
+
+    timer.await;
+}
+
+/// Run `callback` after `delay_ms` with the global `setTimeout`, returning whether it was scheduled
+#[cfg(target_arch = "wasm32")]
+fn set_timeout(delay_ms: u64, callback: impl FnOnce() + 'static) -> bool {
+    use wasm_bindgen::{JsCast, JsValue, closure::Closure};
+
+    let global = js_sys::global();
+    let Ok(set_timeout) = js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout"))
+        .and_then(|function| function.dyn_into::<js_sys::Function>())
+    else {
+        tracing::warn!("No setTimeout available, not waiting {} ms", delay_ms);
+        return false;
+    };
+    let callback = Closure::once_into_js(callback);
+    set_timeout
+        .call2(&global, &callback, &JsValue::from_f64(delay_ms as f64))
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_pending_response_completes_from_callback() {
+        let (sender, pending) = pending_response();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            assert!(sender.send(42));
+        });
+        assert_eq!(pending.await, Some(42));
+
+        let (sender, pending) = pending_response::<u32>();
+        drop(sender);
+        assert_eq!(pending.await, None);
+    }
+
+    #[test]
+    fn test_dropped_response_cancels_request() {
+        let (sender, pending) = pending_response();
+        let slot = Arc::downgrade(&sender.slot);
+        assert!(!sender.is_cancelled());
+
+        drop(pending);
+        assert!(sender.is_cancelled());
+        // A late callback is ignored instead of storing its result
+        assert!(!sender.send(String::from("late response")));
+        assert!(slot.upgrade().unwrap().lock().unwrap().value.is_none());
+
+        drop(sender);
+        assert!(slot.upgrade().is_none());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_expire_after() {
+        let (sender, pending) = pending_response();
+        sender.expire_after(Duration::from_millis(10), || "timeout");
+        assert_eq!(pending.await, Some("timeout"));
+
+        // Completing early stops the deadline thread
+        let (sender, pending) = pending_response();
+        sender.expire_after(Duration::from_secs(60), || "timeout");
+        assert!(sender.send("response"));
+        assert_eq!(pending.await, Some("response"));
+    }
+}
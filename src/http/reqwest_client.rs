@@ -1,10 +1,17 @@
-use super::{HttpClient, HttpConfig, HttpError, HttpResponse, HttpResult};
+use super::{
+    ACCEPT_ENCODING, BodyDecoder, HttpBytesResponse, HttpClient, HttpConfig, HttpError,
+    HttpResponse, HttpResult, RedirectPolicy, RequestOptions, decode_body,
+};
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::Mutex;
 
 /// Standard reqwest-based HTTP client for general use
 pub struct ReqwestClient {
     client: reqwest::Client,
+    config: HttpConfig,
+    /// reqwest sets redirects per client, so requests with a policy get a client of their own
+    redirect_clients: Mutex<HashMap<RedirectPolicy, reqwest::Client>>,
 }
 
 impl ReqwestClient {
@@ -15,6 +22,18 @@ impl ReqwestClient {
 
     /// Create a new reqwest client with custom configuration
     pub fn with_config(config: HttpConfig) -> HttpResult<Self> {
+        Ok(Self {
+            client: Self::build_client(&config, None)?,
+            config,
+            redirect_clients: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Build a reqwest client from the configuration
+    fn build_client(
+        config: &HttpConfig,
+        redirects: Option<RedirectPolicy>,
+    ) -> HttpResult<reqwest::Client> {
         #[cfg(not(target_arch = "wasm32"))]
         let timeout = std::time::Duration::from_secs(config.timeout_seconds);
 
@@ -42,15 +61,74 @@ impl ReqwestClient {
             headers.insert(header_name, header_value);
         }
 
+        // reqwest is built without its own decompression, so gzip is handled in `read_response`
+        if config.accept_compression {
+            headers.insert(
+                reqwest::header::ACCEPT_ENCODING,
+                reqwest::header::HeaderValue::from_static(ACCEPT_ENCODING),
+            );
+        }
+
         if !headers.is_empty() {
             builder = builder.default_headers(headers);
         }
 
-        let client = builder.build().map_err(|e| HttpError::RequestFailed {
+        // Browsers follow redirects themselves
+        #[cfg(not(target_arch = "wasm32"))]
+        match redirects {
+            Some(RedirectPolicy::None) => {
+                builder = builder.redirect(reqwest::redirect::Policy::none())
+            }
+            Some(RedirectPolicy::Limited(max)) => {
+                builder = builder.redirect(reqwest::redirect::Policy::limited(max))
+            }
+            None => {}
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = redirects;
+
+        builder.build().map_err(|e| HttpError::RequestFailed {
             message: format!("Failed to create HTTP client: {}", e),
-        })?;
+        })
+    }
+
+    /// Get the client following `redirects`, building it on first use
+    fn client_for(&self, redirects: Option<RedirectPolicy>) -> HttpResult<reqwest::Client> {
+        let Some(policy) = redirects else {
+            return Ok(self.client.clone());
+        };
+        let mut clients = self.redirect_clients.lock().unwrap();
+        if let Some(client) = clients.get(&policy) {
+            return Ok(client.clone());
+        }
+        let client = Self::build_client(&self.config, Some(policy))?;
+        clients.insert(policy, client.clone());
+        Ok(client)
+    }
 
-        Ok(Self { client })
+    /// Send a request with per-request options applied
+    async fn send_with(
+        request: reqwest::RequestBuilder,
+        options: &RequestOptions,
+    ) -> HttpResult<HttpResponse> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let request = match options.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        };
+
+        let response = async {
+            let response = request.send().await.map_err(|err| match options.timeout {
+                Some(timeout) if err.is_timeout() => HttpError::Timeout {
+                    seconds: timeout.as_secs(),
+                },
+                _ => Self::convert_error(err),
+            })?;
+            Self::read_limited(response, options.max_response_bytes, &mut |_| {})
+                .await?
+                .into_text()
+        };
+        options.run_cancellable(response).await
     }
 
     /// Convert reqwest error to our error type
@@ -72,28 +150,95 @@ impl ReqwestClient {
         }
     }
 
-    /// Convert reqwest response to our response type
-    async fn convert_response(response: reqwest::Response) -> HttpResult<HttpResponse> {
-        let status = response.status().as_u16();
-
-        // Extract headers
+    /// Extract response headers
+    fn convert_headers(response: &reqwest::Response) -> HashMap<String, String> {
         let mut headers = HashMap::new();
         for (name, value) in response.headers() {
             if let Ok(value_str) = value.to_str() {
                 headers.insert(name.to_string(), value_str.to_string());
             }
         }
+        headers
+    }
+
+    /// Read the full response body, decompressing it if needed
+    async fn read_response(response: reqwest::Response) -> HttpResult<HttpBytesResponse> {
+        let status = response.status().as_u16();
+        let mut headers = Self::convert_headers(&response);
+
+        let body = response.bytes().await.map_err(Self::convert_error)?;
+        let (body, transfer) = decode_body(body.to_vec(), &mut headers, None)?;
+
+        Ok(HttpBytesResponse {
+            status,
+            body,
+            headers,
+            transfer: Some(transfer),
+        })
+    }
+
+    /// Read the body in chunks, passing the decompressed bytes to `on_chunk` and failing once they exceed `limit`
+    ///
+    /// Bodies announced larger than the limit by `Content-Length` are
+    /// refused before downloading. Dropping the response aborts the transfer.
+    async fn read_limited(
+        mut response: reqwest::Response,
+        limit: Option<usize>,
+        on_chunk: &mut (dyn for<'c> FnMut(&'c [u8]) + Send),
+    ) -> HttpResult<HttpBytesResponse> {
+        let check = |received_bytes: usize| match limit {
+            Some(limit_bytes) if received_bytes > limit_bytes => Err(HttpError::ResponseTooLarge {
+                limit_bytes,
+                received_bytes,
+            }),
+            _ => Ok(()),
+        };
+        if let Some(length) = response.content_length() {
+            check(usize::try_from(length).unwrap_or(usize::MAX))?;
+        }
 
-        // Get body
-        let body = response.text().await.map_err(Self::convert_error)?;
+        let status = response.status().as_u16();
+        let mut headers = Self::convert_headers(&response);
+        let mut decoder = BodyDecoder::new(&headers)?;
+        while let Some(chunk) = response.chunk().await.map_err(Self::convert_error)? {
+            on_chunk(decoder.push(&chunk)?);
+            check(decoder.decoded_len())?;
+        }
+        let streamed = decoder.decoded_len();
+        let (body, transfer) = decoder.finish(&mut headers)?;
+        check(body.len())?;
+        if body.len() > streamed {
+            on_chunk(&body[streamed..]);
+        }
 
-        Ok(HttpResponse {
+        Ok(HttpBytesResponse {
             status,
             body,
             headers,
+            transfer: Some(transfer),
         })
     }
 
+    /// Send a form POST, streaming successful response bodies to `on_chunk`
+    async fn stream_form(
+        request: reqwest::RequestBuilder,
+        limit: Option<usize>,
+        on_chunk: &mut (dyn for<'c> FnMut(&'c [u8]) + Send),
+    ) -> HttpResult<HttpResponse> {
+        let response = request.send().await.map_err(Self::convert_error)?;
+        if response.status().is_success() {
+            Self::read_limited(response, limit, on_chunk).await
+        } else {
+            Self::read_limited(response, limit, &mut |_| {}).await
+        }?
+        .into_text()
+    }
+
+    /// Convert reqwest response to our response type
+    async fn convert_response(response: reqwest::Response) -> HttpResult<HttpResponse> {
+        Self::read_response(response).await?.into_text()
+    }
+
     /// Get access to the underlying reqwest client
     pub fn reqwest_client(&self) -> &reqwest::Client {
         &self.client
@@ -115,6 +260,19 @@ impl HttpClient for ReqwestClient {
         Self::convert_response(response).await
     }
 
+    async fn get_bytes(&self, url: &str) -> HttpResult<HttpBytesResponse> {
+        tracing::debug!("GET {} (binary)", url);
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(Self::convert_error)?;
+
+        Self::read_response(response).await
+    }
+
     async fn post_form(&self, url: &str, form_data: &[(&str, &str)]) -> HttpResult<HttpResponse> {
         tracing::debug!("POST {} (form data with {} fields)", url, form_data.len());
 
@@ -129,6 +287,81 @@ impl HttpClient for ReqwestClient {
         Self::convert_response(response).await
     }
 
+    async fn post_form_streaming(
+        &self,
+        url: &str,
+        form_data: &[(&str, &str)],
+        on_chunk: &mut (dyn for<'c> FnMut(&'c [u8]) + Send),
+    ) -> HttpResult<HttpResponse> {
+        tracing::debug!(
+            "POST {} (form data with {} fields, streaming)",
+            url,
+            form_data.len()
+        );
+
+        let request = self.client.post(url).form(form_data);
+        Self::stream_form(request, None, on_chunk).await
+    }
+
+    async fn post_form_streaming_with(
+        &self,
+        url: &str,
+        form_data: &[(&str, &str)],
+        options: &RequestOptions,
+        on_chunk: &mut (dyn for<'c> FnMut(&'c [u8]) + Send),
+    ) -> HttpResult<HttpResponse> {
+        tracing::debug!(
+            "POST {} (form data with {} fields, streaming, {:?})",
+            url,
+            form_data.len(),
+            options
+        );
+
+        let request = self
+            .client_for(options.redirects)?
+            .post(url)
+            .form(form_data);
+        #[cfg(not(target_arch = "wasm32"))]
+        let request = match options.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        };
+        options
+            .run_cancellable(Self::stream_form(
+                request,
+                options.max_response_bytes,
+                on_chunk,
+            ))
+            .await
+    }
+
+    async fn get_with(&self, url: &str, options: &RequestOptions) -> HttpResult<HttpResponse> {
+        tracing::debug!("GET {} ({:?})", url, options);
+
+        let request = self.client_for(options.redirects)?.get(url);
+        Self::send_with(request, options).await
+    }
+
+    async fn post_form_with(
+        &self,
+        url: &str,
+        form_data: &[(&str, &str)],
+        options: &RequestOptions,
+    ) -> HttpResult<HttpResponse> {
+        tracing::debug!(
+            "POST {} (form data with {} fields, {:?})",
+            url,
+            form_data.len(),
+            options
+        );
+
+        let request = self
+            .client_for(options.redirects)?
+            .post(url)
+            .form(form_data);
+        Self::send_with(request, options).await
+    }
+
     async fn post_json(&self, url: &str, json: &str) -> HttpResult<HttpResponse> {
         tracing::debug!("POST {} (JSON, {} bytes)", url, json.len());
 
@@ -169,3 +402,41 @@ impl Default for ReqwestClient {
         Self::new().expect("Failed to create default reqwest client")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_dropped_request_closes_connection() {
+        // A server that reads the request but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut buffer = [0u8; 1024];
+            loop {
+                match stream.read(&mut buffer) {
+                    // The client hung up
+                    Ok(0) => return true,
+                    Ok(_) => continue,
+                    Err(_) => return false,
+                }
+            }
+        });
+
+        let client = ReqwestClient::new().unwrap();
+        let result = tokio::time::timeout(Duration::from_millis(200), client.get(&url)).await;
+        assert!(result.is_err());
+
+        let closed = tokio::task::spawn_blocking(move || server.join().unwrap())
+            .await
+            .unwrap();
+        assert!(closed, "dropping the request future must close its socket");
+    }
+}
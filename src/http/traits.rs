@@ -1,5 +1,11 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use super::{CancellationToken, TransferStats};
 
 /// Result type for HTTP operations
 pub type HttpResult<T> = Result<T, HttpError>;
@@ -18,6 +24,15 @@ pub enum HttpError {
 
     #[error("Network error: {message}")]
     Network { message: String },
+
+    #[error("Request was cancelled")]
+    Cancelled,
+
+    #[error("Response exceeded {limit_bytes} bytes ({received_bytes} bytes received)")]
+    ResponseTooLarge {
+        limit_bytes: usize,
+        received_bytes: usize,
+    },
 }
 
 /// HTTP response
@@ -26,17 +41,266 @@ pub struct HttpResponse {
     pub status: u16,
     pub body: String,
     pub headers: HashMap<String, String>,
+    /// Transfer sizes, if the client measured them
+    pub transfer: Option<TransferStats>,
+}
+
+impl HttpResponse {
+    /// Get the wait requested by a `Retry-After` header in seconds
+    pub fn retry_after(&self) -> Option<Duration> {
+        retry_after(&self.headers)
+    }
+}
+
+/// HTTP response with a binary body (e.g., vector tiles or images)
+#[derive(Debug, Clone)]
+pub struct HttpBytesResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub headers: HashMap<String, String>,
+    /// Transfer sizes, if the client measured them
+    pub transfer: Option<TransferStats>,
+}
+
+impl HttpBytesResponse {
+    /// Get the wait requested by a `Retry-After` header in seconds
+    pub fn retry_after(&self) -> Option<Duration> {
+        retry_after(&self.headers)
+    }
+
+    /// Decode the body as UTF-8 text
+    pub fn into_text(self) -> HttpResult<HttpResponse> {
+        let body = String::from_utf8(self.body).map_err(|e| HttpError::RequestFailed {
+            message: format!("Failed to decode response body as UTF-8: {}", e),
+        })?;
+
+        Ok(HttpResponse {
+            status: self.status,
+            body,
+            headers: self.headers,
+            transfer: self.transfer,
+        })
+    }
+}
+
+/// Parse a `Retry-After` header; HTTP dates are not supported and ignored
+fn retry_after(headers: &HashMap<String, String>) -> Option<Duration> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("retry-after"))
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// How a request follows HTTP redirects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedirectPolicy {
+    /// Return redirect responses (3xx) as they are
+    None,
+    /// Follow at most this many redirects, then fail
+    Limited(usize),
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::Limited(10)
+    }
+}
+
+/// Per-request settings overriding the client configuration
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Give up after this long instead of the client's timeout
+    pub timeout: Option<Duration>,
+    /// Abort the request once this token is cancelled
+    pub cancellation: Option<CancellationToken>,
+    /// Redirect handling, `None` for the client's default
+    pub redirects: Option<RedirectPolicy>,
+    /// Abort once the decompressed body grows past this many bytes
+    pub max_response_bytes: Option<usize>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn with_redirects(mut self, policy: RedirectPolicy) -> Self {
+        self.redirects = Some(policy);
+        self
+    }
+
+    pub fn with_max_response_bytes(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self
+    }
+
+    /// Fail if `received_bytes` of body exceed the size limit
+    pub fn check_size(&self, received_bytes: usize) -> HttpResult<()> {
+        match self.max_response_bytes {
+            Some(limit_bytes) if received_bytes > limit_bytes => Err(HttpError::ResponseTooLarge {
+                limit_bytes,
+                received_bytes,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Run a request, failing once the timeout expires or the token is cancelled
+    ///
+    /// The request future is dropped on timeout or cancellation, which aborts
+    /// it for cancellation-safe clients. Timeouts are not enforced on WASM,
+    /// where no timer is available; browsers apply their own.
+    pub async fn run<T>(&self, request: impl Future<Output = HttpResult<T>>) -> HttpResult<T> {
+        self.race(request, self.timeout).await
+    }
+
+    /// Run a request, failing once the token is cancelled, for clients enforcing the timeout themselves
+    pub async fn run_cancellable<T>(
+        &self,
+        request: impl Future<Output = HttpResult<T>>,
+    ) -> HttpResult<T> {
+        self.race(request, None).await
+    }
+
+    async fn race<T>(
+        &self,
+        request: impl Future<Output = HttpResult<T>>,
+        timeout: Option<Duration>,
+    ) -> HttpResult<T> {
+        if timeout.is_none() && self.cancellation.is_none() {
+            return request.await;
+        }
+
+        let mut request = std::pin::pin!(request);
+        let mut cancelled = self.cancellation.as_ref().map(CancellationToken::cancelled);
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut deadline = timeout.map(|timeout| {
+            let (sender, deadline) = super::pending_response();
+            sender.expire_after(timeout, || ());
+            deadline
+        });
+        #[cfg(target_arch = "wasm32")]
+        let mut deadline: Option<super::PendingResponse<()>> = None;
+
+        std::future::poll_fn(|cx| {
+            if let Some(cancelled) = cancelled.as_mut()
+                && Pin::new(cancelled).poll(cx).is_ready()
+            {
+                return Poll::Ready(Err(HttpError::Cancelled));
+            }
+            if let Poll::Ready(result) = request.as_mut().poll(cx) {
+                return Poll::Ready(result);
+            }
+            if let (Some(deadline), Some(timeout)) = (deadline.as_mut(), timeout)
+                && Pin::new(deadline).poll(cx).is_ready()
+            {
+                return Poll::Ready(Err(HttpError::Timeout {
+                    seconds: timeout.as_secs_f64().ceil() as u64,
+                }));
+            }
+            Poll::Pending
+        })
+        .await
+    }
 }
 
 /// Trait for HTTP clients that can be used in different environments
+///
+/// Request futures must be cancellation-safe: dropping one before it completes
+/// cancels the request without leaking sockets, threads or callback state, so
+/// dropped loading tasks release their requests. Clients built on callbacks
+/// can use [`pending_response`](super::pending_response) for this.
 #[async_trait]
 pub trait HttpClient: Send + Sync {
     /// Make a GET request
     async fn get(&self, url: &str) -> HttpResult<HttpResponse>;
 
+    /// Make a GET request and return the raw response body
+    ///
+    /// The default implementation goes through [`HttpClient::get`], so clients
+    /// that only handle text should override it to support binary payloads.
+    async fn get_bytes(&self, url: &str) -> HttpResult<HttpBytesResponse> {
+        let response = self.get(url).await?;
+        Ok(HttpBytesResponse {
+            status: response.status,
+            body: response.body.into_bytes(),
+            headers: response.headers,
+            transfer: response.transfer,
+        })
+    }
+
     /// Make a POST request with form data
     async fn post_form(&self, url: &str, form_data: &[(&str, &str)]) -> HttpResult<HttpResponse>;
 
+    /// Make a POST request with form data, passing the body to `on_chunk` while it downloads
+    ///
+    /// Only bodies of successful (2xx) responses are streamed, decompressed
+    /// and in order; the returned response still holds the whole body. The
+    /// default implementation waits for [`HttpClient::post_form`] and passes
+    /// the body as one chunk.
+    async fn post_form_streaming(
+        &self,
+        url: &str,
+        form_data: &[(&str, &str)],
+        on_chunk: &mut (dyn for<'c> FnMut(&'c [u8]) + Send),
+    ) -> HttpResult<HttpResponse> {
+        let response = self.post_form(url, form_data).await?;
+        if (200..300).contains(&response.status) {
+            on_chunk(response.body.as_bytes());
+        }
+        Ok(response)
+    }
+
+    /// Make a GET request with a per-request timeout, cancellation, redirect policy or size limit
+    ///
+    /// The default implementation enforces the timeout and cancellation
+    /// around [`HttpClient::get`] and leaves redirects to the client. It
+    /// checks the size limit only once the whole body has arrived; clients
+    /// reading bodies in chunks should abort as soon as it is exceeded.
+    async fn get_with(&self, url: &str, options: &RequestOptions) -> HttpResult<HttpResponse> {
+        let response = options.run(self.get(url)).await?;
+        options.check_size(response.body.len())?;
+        Ok(response)
+    }
+
+    /// Make a POST request with form data and per-request options, see [`HttpClient::get_with`]
+    async fn post_form_with(
+        &self,
+        url: &str,
+        form_data: &[(&str, &str)],
+        options: &RequestOptions,
+    ) -> HttpResult<HttpResponse> {
+        let response = options.run(self.post_form(url, form_data)).await?;
+        options.check_size(response.body.len())?;
+        Ok(response)
+    }
+
+    /// Stream a POST request with form data and per-request options, see [`HttpClient::post_form_streaming`]
+    async fn post_form_streaming_with(
+        &self,
+        url: &str,
+        form_data: &[(&str, &str)],
+        options: &RequestOptions,
+        on_chunk: &mut (dyn for<'c> FnMut(&'c [u8]) + Send),
+    ) -> HttpResult<HttpResponse> {
+        let response = options
+            .run(self.post_form_streaming(url, form_data, on_chunk))
+            .await?;
+        options.check_size(response.body.len())?;
+        Ok(response)
+    }
+
     /// Make a POST request with JSON body
     async fn post_json(&self, url: &str, json: &str) -> HttpResult<HttpResponse>;
 
@@ -50,6 +314,8 @@ pub struct HttpConfig {
     pub timeout_seconds: u64,
     pub user_agent: String,
     pub default_headers: HashMap<String, String>,
+    /// Request gzip-compressed responses and decompress them transparently
+    pub accept_compression: bool,
 }
 
 impl Default for HttpConfig {
@@ -58,6 +324,7 @@ impl Default for HttpConfig {
             timeout_seconds: 60,
             user_agent: format!("bevy-osm-tiles/{}", env!("CARGO_PKG_VERSION")),
             default_headers: HashMap::new(),
+            accept_compression: true,
         }
     }
 }
@@ -81,4 +348,9 @@ impl HttpConfig {
         self.default_headers.insert(key.into(), value.into());
         self
     }
+
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.accept_compression = enabled;
+        self
+    }
 }
@@ -6,14 +6,33 @@
 
 #[cfg(feature = "bevy")]
 pub mod bevy_plugin;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+pub mod build_info;
 pub mod config;
 pub mod error;
+pub mod export;
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+pub mod ffi;
 pub mod generator;
+pub mod geoutil;
 pub mod http;
+pub mod loader;
+pub mod metrics;
 pub mod provider;
+pub mod session;
+#[cfg(feature = "wasm-bindings")]
+pub mod wasm_bindings;
 
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use blocking::*;
+pub use build_info::*;
 pub use config::*;
 pub use error::*;
+pub use export::*;
 pub use generator::*;
 pub use http::*;
+pub use loader::*;
+pub use metrics::*;
 pub use provider::*;
+pub use session::*;
@@ -0,0 +1,291 @@
+//! Pipelined loading: fetch, parse and rasterize in one pass
+
+use tracing::field::Empty;
+
+use crate::generator::{GridPass, JsonElementStream};
+use crate::{
+    BoundingBox, ChunkSink, DefaultGridGenerator, FeatureSet, OsmConfig, OsmConfigBuilder,
+    OsmDataFormat, OsmDataProvider, OsmTilesError, ProviderFactory, Result, TileGrid,
+};
+
+/// Loads a region into a grid, rasterizing elements while the response downloads
+///
+/// Providers that stream their response (see
+/// [`OsmDataProvider::fetch_data_streaming`]) hand out body chunks as they
+/// arrive; each chunk is split into elements, which are rasterized right
+/// away, so network and CPU time overlap instead of adding up. Grids carry a
+/// `streamed = true` metadata extra when this happened. With coastline fill
+/// or a time budget, or when the provider answers with XML, the data is
+/// fetched first and generated as usual.
+pub struct OsmTilesLoader {
+    provider: Box<dyn OsmDataProvider>,
+    generator: DefaultGridGenerator,
+}
+
+impl OsmTilesLoader {
+    /// Create a loader fetching from `provider` with the default generator
+    pub fn new(provider: impl OsmDataProvider + 'static) -> Self {
+        Self::from_boxed(Box::new(provider))
+    }
+
+    /// Create a loader for a boxed provider, e.g. one built with [`ProviderFactory::builder`](crate::ProviderFactory::builder)
+    pub fn from_boxed(provider: Box<dyn OsmDataProvider>) -> Self {
+        Self {
+            provider,
+            generator: DefaultGridGenerator::new(),
+        }
+    }
+
+    /// Rasterize with a custom generator
+    pub fn with_generator(mut self, generator: DefaultGridGenerator) -> Self {
+        self.generator = generator;
+        self
+    }
+
+    /// Fetch the region of `config` and generate its grid
+    #[tracing::instrument(
+        name = "load",
+        skip_all,
+        fields(
+            region = ?config.region,
+            width = Empty,
+            height = Empty,
+            element_count = Empty,
+            tiles_populated = Empty,
+        )
+    )]
+    pub async fn load(&self, config: &OsmConfig) -> Result<TileGrid> {
+        if !self.generator.rasterizes_in_arrival_order() {
+            let data = self.provider.fetch_data(config).await?;
+            return self.generator.generate_grid_sync(&data, config);
+        }
+
+        // The grid is allocated before the first chunk arrives
+        let bbox = config.aligned_bounds(self.provider.resolve_region(&config.region).await?);
+        let mut sink = GridSink {
+            generator: &self.generator,
+            config,
+            pass: self.generator.begin_grid(&bbox, config)?,
+            stream: self.generator.element_stream(),
+            bbox,
+            error: None,
+        };
+        let data = self
+            .provider
+            .fetch_data_streaming(config, &mut sink)
+            .await?;
+
+        if data.format != OsmDataFormat::Json || data.bounding_box != sink.bbox {
+            tracing::debug!("Provider data cannot be streamed, generating from the full response");
+            return self.generator.generate_grid_sync(&data, config);
+        }
+        if let Some(error) = sink.error {
+            return Err(error);
+        }
+        let element_count = sink.stream.finish()?;
+        let mut grid = sink.pass.finish(&data, element_count)?;
+        grid.metadata
+            .extra
+            .insert("streamed".to_string(), "true".to_string());
+        Ok(grid)
+    }
+}
+
+/// Sink rasterizing the elements of each chunk into a grid pass
+struct GridSink<'g> {
+    generator: &'g DefaultGridGenerator,
+    config: &'g OsmConfig,
+    bbox: BoundingBox,
+    pass: GridPass<'g>,
+    stream: JsonElementStream<'g>,
+    /// First error, after which chunks are ignored
+    error: Option<OsmTilesError>,
+}
+
+impl ChunkSink for GridSink<'_> {
+    fn chunk(&mut self, chunk: &[u8]) {
+        if self.error.is_some() {
+            return;
+        }
+        let pass = &mut self.pass;
+        let result = self
+            .stream
+            .feed(chunk, &mut |element| pass.add_element(&element));
+        self.error = result.err();
+    }
+
+    fn restart(&mut self) {
+        // Elements of the failed attempt would be rasterized twice
+        match self.generator.begin_grid(&self.bbox, self.config) {
+            Ok(pass) => {
+                self.pass = pass;
+                self.stream = self.generator.element_stream();
+                self.error = None;
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+}
+
+/// Fetch the region of `config` from `provider` and rasterize it with `generator`
+pub async fn load(
+    config: &OsmConfig,
+    provider: impl OsmDataProvider + 'static,
+    generator: DefaultGridGenerator,
+) -> Result<TileGrid> {
+    OsmTilesLoader::new(provider)
+        .with_generator(generator)
+        .load(config)
+        .await
+}
+
+/// Load a city from Overpass with urban features and the default resolution and generator
+///
+/// ```no_run
+/// # async fn run() -> bevy_osm_tiles::Result<()> {
+/// let grid = bevy_osm_tiles::load_city("Berlin").await?;
+/// println!("{}x{} tiles", grid.dimensions().0, grid.dimensions().1);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn load_city(name: impl Into<String>) -> Result<TileGrid> {
+    let config = OsmConfigBuilder::new()
+        .city(name)
+        .features(FeatureSet::urban())
+        .build();
+    load(
+        &config,
+        ProviderFactory::overpass(),
+        DefaultGridGenerator::new(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        MockProvider, NetworkError, OsmConfigBuilder, OsmData, ProviderCapabilities, Region,
+        RetryPolicy, TileType,
+    };
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Provider streaming the mock data in small chunks
+    struct ChunkedProvider {
+        inner: MockProvider,
+        chunks: Arc<AtomicUsize>,
+        /// Number of downloads that break off halfway
+        failures: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl OsmDataProvider for ChunkedProvider {
+        fn provider_type(&self) -> &'static str {
+            "chunked"
+        }
+
+        async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+            self.inner.fetch_data(config).await
+        }
+
+        async fn fetch_data_streaming(
+            &self,
+            config: &OsmConfig,
+            on_chunk: &mut dyn ChunkSink,
+        ) -> Result<OsmData> {
+            let data = self.inner.fetch_data(config).await?;
+            let fail = self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            for (index, chunk) in data.raw_data.as_bytes().chunks(64).enumerate() {
+                if fail && index == 10 {
+                    return Err(NetworkError::Timeout { seconds: 1 }.into());
+                }
+                self.chunks.fetch_add(1, Ordering::SeqCst);
+                on_chunk.chunk(chunk);
+            }
+            Ok(data)
+        }
+
+        async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+            self.inner.resolve_region(region).await
+        }
+
+        async fn test_availability(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            self.inner.capabilities()
+        }
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[tokio::test]
+    async fn test_streamed_load_matches_generation() {
+        let config = OsmConfigBuilder::new()
+            .city("test")
+            .grid_resolution(200)
+            .build();
+        let data = MockProvider::new().fetch_data(&config).await.unwrap();
+        let expected = DefaultGridGenerator::new()
+            .generate_grid_sync(&data, &config)
+            .unwrap();
+
+        let chunks = Arc::new(AtomicUsize::new(0));
+        let loader = OsmTilesLoader::new(ChunkedProvider {
+            inner: MockProvider::new(),
+            chunks: chunks.clone(),
+            failures: AtomicUsize::new(0),
+        });
+        let pending = loader.load(&config);
+        // Loads can run on multi-threaded executors and Bevy task pools
+        assert_send(&pending);
+        let grid = pending.await.unwrap();
+        assert!(chunks.load(Ordering::SeqCst) > 10);
+        assert_eq!(grid.metadata.extra["streamed"], "true");
+        assert_eq!(grid.dimensions(), expected.dimensions());
+        assert_eq!(
+            grid.metadata.elements_processed,
+            expected.metadata.elements_processed
+        );
+        for tile_type in [TileType::Road, TileType::Building, TileType::Water] {
+            assert_eq!(
+                grid.tiles_of_type(&tile_type).len(),
+                expected.tiles_of_type(&tile_type).len()
+            );
+        }
+
+        // A retried download starts the grid over instead of rasterizing elements twice
+        let loader = OsmTilesLoader::from_boxed(
+            ProviderFactory::builder()
+                .with_retry_policy(RetryPolicy::default().with_delays(0, 0))
+                .build(ChunkedProvider {
+                    inner: MockProvider::new(),
+                    chunks: chunks.clone(),
+                    failures: AtomicUsize::new(1),
+                }),
+        );
+        let grid = loader.load(&config).await.unwrap();
+        assert_eq!(grid.metadata.extra["streamed"], "true");
+        assert_eq!(
+            grid.metadata.elements_processed,
+            expected.metadata.elements_processed
+        );
+
+        // Coastline fill needs the whole response before rasterizing
+        let grid = load(
+            &config,
+            MockProvider::new(),
+            DefaultGridGenerator::new().with_coastline_fill(true),
+        )
+        .await
+        .unwrap();
+        assert!(!grid.metadata.extra.contains_key("streamed"));
+        assert!(!grid.tiles_of_type(&TileType::Road).is_empty());
+    }
+}
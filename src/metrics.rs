@@ -0,0 +1,152 @@
+//! Metrics hooks for monitoring the OSM pipeline in long-running applications
+//!
+//! Providers and generators report counters and histograms to a
+//! [`MetricsSink`], which applications forward to their metrics backend.
+//! Metric names follow Prometheus conventions.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Fetches started, labeled by `provider`
+pub const REQUESTS_TOTAL: &str = "osm_tiles_requests_total";
+/// Fetches that failed, labeled by `provider` and `retryable`
+pub const REQUEST_FAILURES_TOTAL: &str = "osm_tiles_request_failures_total";
+/// Fetch durations in seconds, labeled by `provider`
+pub const REQUEST_DURATION_SECONDS: &str = "osm_tiles_request_duration_seconds";
+/// Bytes received over the network (cache hits excluded), labeled by `provider`
+pub const BYTES_DOWNLOADED_TOTAL: &str = "osm_tiles_bytes_downloaded_total";
+/// OSM elements parsed by grid generators
+pub const ELEMENTS_PARSED_TOTAL: &str = "osm_tiles_elements_parsed_total";
+/// Tiles written while rasterizing elements
+pub const TILES_RASTERIZED_TOTAL: &str = "osm_tiles_tiles_rasterized_total";
+/// Grid generation durations in seconds
+pub const GRID_GENERATION_DURATION_SECONDS: &str = "osm_tiles_grid_generation_duration_seconds";
+
+/// Receiver of pipeline metrics, e.g. an adapter to the `metrics` or `prometheus` crates
+///
+/// Labels are (name, value) pairs. Implementations must be cheap and must
+/// not block, since they are called on the fetch and generation paths.
+pub trait MetricsSink: Send + Sync {
+    /// Add `value` to a counter
+    fn increment_counter(&self, name: &'static str, value: u64, labels: &[(&'static str, &str)]);
+
+    /// Record one observation of a histogram
+    fn record_histogram(&self, name: &'static str, value: f64, labels: &[(&'static str, &str)]);
+}
+
+/// Metric name and its labels rendered in Prometheus syntax
+type SeriesKey = (&'static str, String);
+
+/// Sink keeping all metrics in memory, for tests and simple `/metrics` endpoints
+#[derive(Debug, Default)]
+pub struct InMemoryMetricsSink {
+    /// Counter values
+    counters: Mutex<BTreeMap<SeriesKey, u64>>,
+    /// Observation count and sum of each histogram
+    histograms: Mutex<BTreeMap<SeriesKey, (u64, f64)>>,
+}
+
+impl InMemoryMetricsSink {
+    /// Create an empty sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the total of a counter across all label values
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((counter, _), _)| *counter == name)
+            .map(|(_, value)| value)
+            .sum()
+    }
+
+    /// Get the number of observations and their sum for a histogram across all label values
+    pub fn histogram(&self, name: &str) -> (u64, f64) {
+        self.histograms
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((histogram, _), _)| *histogram == name)
+            .fold((0, 0.0), |(count, sum), (_, (n, s))| (count + n, sum + s))
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    ///
+    /// Histograms are exposed as summaries with `_count` and `_sum` only.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for ((name, labels), value) in self.counters.lock().unwrap().iter() {
+            let _ = writeln!(output, "{}{} {}", name, labels, value);
+        }
+        for ((name, labels), (count, sum)) in self.histograms.lock().unwrap().iter() {
+            let _ = writeln!(output, "{}_count{} {}", name, labels, count);
+            let _ = writeln!(output, "{}_sum{} {}", name, labels, sum);
+        }
+        output
+    }
+}
+
+impl MetricsSink for InMemoryMetricsSink {
+    fn increment_counter(&self, name: &'static str, value: u64, labels: &[(&'static str, &str)]) {
+        *self
+            .counters
+            .lock()
+            .unwrap()
+            .entry((name, label_key(labels)))
+            .or_default() += value;
+    }
+
+    fn record_histogram(&self, name: &'static str, value: f64, labels: &[(&'static str, &str)]) {
+        let mut histograms = self.histograms.lock().unwrap();
+        let entry = histograms.entry((name, label_key(labels))).or_default();
+        entry.0 += 1;
+        entry.1 += value;
+    }
+}
+
+/// Render labels in Prometheus syntax, sorted by name
+fn label_key(labels: &[(&'static str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let mut labels = labels.to_vec();
+    labels.sort();
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "{}=\"{}\"",
+                name,
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_sink() {
+        let sink = InMemoryMetricsSink::new();
+        sink.increment_counter(REQUESTS_TOTAL, 1, &[("provider", "overpass")]);
+        sink.increment_counter(REQUESTS_TOTAL, 2, &[("provider", "mock")]);
+        sink.record_histogram(REQUEST_DURATION_SECONDS, 0.5, &[("provider", "mock")]);
+        sink.record_histogram(REQUEST_DURATION_SECONDS, 1.5, &[("provider", "mock")]);
+
+        assert_eq!(sink.counter(REQUESTS_TOTAL), 3);
+        assert_eq!(sink.counter(TILES_RASTERIZED_TOTAL), 0);
+        assert_eq!(sink.histogram(REQUEST_DURATION_SECONDS), (2, 2.0));
+
+        let text = sink.render();
+        assert!(text.contains("osm_tiles_requests_total{provider=\"overpass\"} 1\n"));
+        assert!(text.contains("osm_tiles_request_duration_seconds_count{provider=\"mock\"} 2\n"));
+        assert!(text.contains("osm_tiles_request_duration_seconds_sum{provider=\"mock\"} 2\n"));
+    }
+}
@@ -0,0 +1,361 @@
+//! Minimal reader for Overpass augmented diffs (`[adiff:...]` queries, XML output)
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value, json};
+
+use crate::{OsmElement, OsmParser, OsmTilesError, ProviderError, Result};
+
+/// Changes to the elements of an area between two points in time
+#[derive(Debug, Clone, Default)]
+pub struct OsmDiff {
+    /// Elements that entered the area or were created
+    pub created: Vec<OsmElement>,
+    /// Current versions of changed elements
+    pub modified: Vec<OsmElement>,
+    /// Versions of changed elements before the change, to clear what they covered
+    pub previous: Vec<OsmElement>,
+    /// Last versions of elements that were deleted or left the area
+    pub deleted: Vec<OsmElement>,
+    /// Time of the database snapshot the diff runs up to, if reported
+    pub data_timestamp: Option<DateTime<Utc>>,
+}
+
+impl OsmDiff {
+    /// Check if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// One XML tag: `<name attr="value">`, `<name/>` or `</name>`
+struct XmlTag<'a> {
+    name: &'a str,
+    attributes: HashMap<&'a str, String>,
+    closing: bool,
+    self_closing: bool,
+}
+
+impl XmlTag<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    fn number(&self, name: &str) -> Option<Value> {
+        let value = self.attribute(name)?;
+        value
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| value.parse::<f64>().map(Value::from))
+            .ok()
+    }
+}
+
+/// Parse the tag between `<` and `>`
+fn parse_tag(text: &str) -> Result<XmlTag<'_>> {
+    let closing = text.starts_with('/');
+    let self_closing = text.ends_with('/');
+    let body = text.trim_start_matches('/').trim_end_matches('/').trim();
+    let name_end = body.find(char::is_whitespace).unwrap_or(body.len());
+    let (name, mut rest) = body.split_at(name_end);
+
+    let mut attributes = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        let Some(eq) = rest.find('=') else { break };
+        let key = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| OsmTilesError::Parse(format!("Unquoted XML attribute '{}'", key)))?;
+        let end = value[1..]
+            .find(quote)
+            .ok_or_else(|| OsmTilesError::Parse(format!("Unterminated XML attribute '{}'", key)))?;
+        attributes.insert(key, unescape(&value[1..end + 1]));
+        rest = &value[end + 2..];
+    }
+
+    Ok(XmlTag {
+        name,
+        attributes,
+        closing,
+        self_closing,
+    })
+}
+
+/// Replace XML entities and character references
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else { break };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Where the element being read ends up
+#[derive(Clone, Copy, PartialEq)]
+enum Action {
+    Create,
+    Modify,
+    Delete,
+}
+
+/// Decode an augmented diff into lists of created, modified and deleted elements
+///
+/// Each element is converted to its Overpass JSON form and read by
+/// [`OsmParser`], so classification and multipolygon assembly match full
+/// loads. The diff must have been requested with `out geom`.
+pub fn decode_augmented_diff(xml: &str) -> Result<OsmDiff> {
+    let parser = OsmParser;
+    let mut diff = OsmDiff::default();
+    let mut action = None;
+    let mut old = false;
+    // Element being read, and the member whose geometry it is collecting
+    let mut element: Option<Map<String, Value>> = None;
+    let mut member: Option<Map<String, Value>> = None;
+
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let end = rest[start..]
+            .find('>')
+            .map(|end| start + end)
+            .ok_or_else(|| OsmTilesError::Parse("Unterminated XML tag".to_string()))?;
+        let text = &rest[start + 1..end];
+        let content = &rest[end + 1..];
+        rest = content;
+        if text.starts_with('?') || text.starts_with('!') {
+            continue;
+        }
+
+        let tag = parse_tag(text)?;
+        match (tag.name, tag.closing) {
+            ("remark", false) => {
+                let remark = unescape(content[..content.find('<').unwrap_or(0)].trim());
+                let lower = remark.to_lowercase();
+                if lower.contains("error") || lower.contains("timed out") {
+                    return Err(ProviderError::from_remark(remark).into());
+                }
+            }
+            ("meta", false) => {
+                diff.data_timestamp = tag
+                    .attribute("osm_base")
+                    .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                    .map(|timestamp| timestamp.with_timezone(&Utc));
+            }
+            ("action", false) => {
+                action = match tag.attribute("type") {
+                    Some("create") => Some(Action::Create),
+                    Some("modify") => Some(Action::Modify),
+                    Some("delete") => Some(Action::Delete),
+                    other => {
+                        return Err(OsmTilesError::Parse(format!(
+                            "Unknown augmented diff action {:?}",
+                            other
+                        )));
+                    }
+                };
+            }
+            ("action", true) => action = None,
+            ("old", closing) => old = !closing,
+            ("node" | "way" | "relation", false) => {
+                let mut fields = Map::new();
+                fields.insert("type".to_string(), tag.name.into());
+                for key in ["id", "lat", "lon"] {
+                    if let Some(value) = tag.number(key) {
+                        fields.insert(key.to_string(), value);
+                    }
+                }
+                fields.insert("tags".to_string(), json!({}));
+                if tag.name != "node" {
+                    fields.insert("geometry".to_string(), json!([]));
+                }
+                element = Some(fields);
+            }
+            ("tag", false) => {
+                if let (Some(fields), Some(key), Some(value)) =
+                    (element.as_mut(), tag.attribute("k"), tag.attribute("v"))
+                {
+                    fields["tags"][key] = value.into();
+                }
+            }
+            ("nd", false) => {
+                if let (Some(lat), Some(lon)) = (tag.number("lat"), tag.number("lon")) {
+                    let point = json!({ "lat": lat, "lon": lon });
+                    match (member.as_mut(), element.as_mut()) {
+                        (Some(fields), _) | (None, Some(fields)) => {
+                            if let Some(Value::Array(points)) = fields.get_mut("geometry") {
+                                points.push(point);
+                            }
+                        }
+                        (None, None) => {}
+                    }
+                }
+            }
+            ("member", false) => {
+                let mut fields = Map::new();
+                for key in ["type", "role"] {
+                    fields.insert(key.to_string(), tag.attribute(key).unwrap_or("").into());
+                }
+                for key in ["ref", "lat", "lon"] {
+                    if let Some(value) = tag.number(key) {
+                        fields.insert(key.to_string(), value);
+                    }
+                }
+                fields.insert("geometry".to_string(), json!([]));
+                member = Some(fields);
+            }
+            _ => {}
+        }
+
+        // Members and elements end at their closing tag or right away when self-closing
+        let ended = tag.closing || tag.self_closing;
+        if ended && tag.name == "member" {
+            if let (Some(done), Some(fields)) = (member.take(), element.as_mut())
+                && let Value::Array(members) = fields.entry("members").or_insert_with(|| json!([]))
+            {
+                members.push(Value::Object(done));
+            }
+        } else if ended && matches!(tag.name, "node" | "way" | "relation") {
+            let Some(mut fields) = element.take() else {
+                continue;
+            };
+            let target = match (action, old) {
+                (Some(Action::Create), false) => &mut diff.created,
+                (Some(Action::Modify), false) => &mut diff.modified,
+                (Some(Action::Modify), true) => &mut diff.previous,
+                (Some(Action::Delete), true) => &mut diff.deleted,
+                // Deleted elements' new versions carry no geometry
+                _ => continue,
+            };
+            if fields.contains_key("members") {
+                // Relations are assembled from their members' geometry
+                fields.remove("geometry");
+            }
+            target.extend(parser.parse_json_element(&Value::Object(fields))?);
+        }
+    }
+
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileType;
+
+    const ADIFF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osm version="0.6" generator="Overpass API">
+<meta osm_base="2024-05-01T12:00:00Z"/>
+<action type="create">
+  <node id="1" lat="52.5" lon="13.4" version="1">
+    <tag k="amenity" v="caf&#233;"/>
+    <tag k="name" v="Tom &amp; Jerry"/>
+  </node>
+</action>
+<action type="modify">
+  <old>
+    <way id="10" version="1">
+      <bounds minlat="52.5" minlon="13.4" maxlat="52.501" maxlon="13.401"/>
+      <nd ref="1" lat="52.5" lon="13.4"/>
+      <nd ref="2" lat="52.501" lon="13.401"/>
+      <tag k="highway" v="residential"/>
+    </way>
+  </old>
+  <new>
+    <way id="10" version="2">
+      <nd ref="1" lat="52.5" lon="13.4"/>
+      <nd ref="3" lat="52.502" lon="13.402"/>
+      <tag k="highway" v="residential"/>
+    </way>
+  </new>
+</action>
+<action type="delete">
+  <old>
+    <relation id="20" version="3">
+      <member type="way" ref="30" role="outer">
+        <nd lat="52.5" lon="13.4"/>
+        <nd lat="52.5" lon="13.41"/>
+        <nd lat="52.51" lon="13.41"/>
+        <nd lat="52.5" lon="13.4"/>
+      </member>
+      <tag k="type" v="multipolygon"/>
+      <tag k="leisure" v="park"/>
+    </relation>
+  </old>
+  <new>
+    <relation id="20" visible="false" version="4"/>
+  </new>
+</action>
+</osm>"#;
+
+    #[test]
+    fn test_decode_augmented_diff() {
+        let diff = decode_augmented_diff(ADIFF).unwrap();
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.data_timestamp.unwrap().to_rfc3339(),
+            "2024-05-01T12:00:00+00:00"
+        );
+
+        assert_eq!(diff.created.len(), 1);
+        let cafe = &diff.created[0];
+        assert_eq!(cafe.geometry, [(52.5, 13.4)]);
+        assert_eq!(cafe.tags["amenity"], "café");
+        assert_eq!(cafe.tags["name"], "Tom & Jerry");
+
+        assert_eq!(diff.previous[0].geometry[1], (52.501, 13.401));
+        assert_eq!(diff.modified[0].geometry[1], (52.502, 13.402));
+        assert_eq!(diff.modified[0].to_tile_type(), TileType::Road);
+
+        assert_eq!(diff.deleted.len(), 1);
+        assert_eq!(diff.deleted[0].id, 20);
+        assert_eq!(diff.deleted[0].geometry.len(), 4);
+        assert_eq!(diff.deleted[0].to_tile_type(), TileType::GreenSpace);
+    }
+
+    #[test]
+    fn test_decode_augmented_diff_errors() {
+        let timeout =
+            r#"<osm><remark> runtime error: Query timed out in "query" at line 3 </remark></osm>"#;
+        assert!(matches!(
+            decode_augmented_diff(timeout),
+            Err(OsmTilesError::Provider(ProviderError::Timeout { .. }))
+        ));
+        assert!(decode_augmented_diff("<osm><node id=1></osm>").is_err());
+        assert!(decode_augmented_diff("<osm></osm>").unwrap().is_empty());
+    }
+}
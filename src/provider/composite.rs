@@ -0,0 +1,404 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value, json};
+use std::collections::HashSet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+use tracing::field::Empty;
+
+use super::{ChunkSink, GeocodeCandidate, OsmData, OsmDataProvider, ProviderCapabilities};
+use crate::{
+    BoundingBox, FeatureSet, OsmConfig, OsmDataFormat, OsmElement, OsmElementType, OsmMetadata,
+    OsmParser, OsmTilesError, Region, Result, SOURCE_TAG,
+};
+
+/// One provider contributing elements to a [`CompositeProvider`]
+pub struct DataSource {
+    /// Name recorded in the metadata of tiles rasterized from this source
+    pub name: String,
+    /// Provider the elements are fetched from
+    pub provider: Box<dyn OsmDataProvider>,
+    /// Features requested from this source instead of the request's features
+    pub features: Option<FeatureSet>,
+}
+
+/// Provider merging the elements of several providers into one data set
+///
+/// Every source is fetched for the same bounding box and its elements are
+/// concatenated in source order, so later sources are rasterized on top of
+/// earlier ones within the conflict strategy. Each element is tagged with
+/// [`SOURCE_TAG`], which ends up as `TileMetadata::source`. The region is
+/// resolved by the first source able to resolve it.
+///
+/// Streamed fetches pass the elements of each source on as soon as they are
+/// merged, so the first sources are rasterized while later ones download.
+pub struct CompositeProvider {
+    sources: Vec<DataSource>,
+    /// Drop elements with the same type, ID and geometry as an earlier one
+    pub deduplicate: bool,
+}
+
+impl Default for CompositeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompositeProvider {
+    /// Create a provider without sources
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            deduplicate: true,
+        }
+    }
+
+    /// Add a source fetched with the request's features
+    pub fn with_source(
+        mut self,
+        name: impl Into<String>,
+        provider: impl OsmDataProvider + 'static,
+    ) -> Self {
+        self.sources.push(DataSource {
+            name: name.into(),
+            provider: Box::new(provider),
+            features: None,
+        });
+        self
+    }
+
+    /// Add a source fetched with its own features (e.g., only roads from a local extract)
+    pub fn with_source_features(
+        mut self,
+        name: impl Into<String>,
+        provider: impl OsmDataProvider + 'static,
+        features: FeatureSet,
+    ) -> Self {
+        self.sources.push(DataSource {
+            name: name.into(),
+            provider: Box::new(provider),
+            features: Some(features),
+        });
+        self
+    }
+
+    /// Set whether duplicate elements from several sources are dropped
+    pub fn with_deduplication(mut self, deduplicate: bool) -> Self {
+        self.deduplicate = deduplicate;
+        self
+    }
+
+    /// Get the configured sources
+    pub fn sources(&self) -> &[DataSource] {
+        &self.sources
+    }
+
+    /// Fetch and merge all sources, streaming the merged data to `on_chunk` if given
+    #[tracing::instrument(
+        name = "fetch_data",
+        skip_all,
+        fields(provider = "composite", region = ?config.region, element_count = Empty, sources = self.sources.len())
+    )]
+    async fn fetch(
+        &self,
+        config: &OsmConfig,
+        mut on_chunk: Option<&mut dyn ChunkSink>,
+    ) -> Result<OsmData> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_time = Instant::now();
+
+        let bbox = config.aligned_bounds(self.resolve_region(&config.region).await?);
+
+        // Written as Overpass JSON, passing each source's elements on once they are merged
+        let mut raw_data = String::from(r#"{"elements":["#);
+        let mut sent = 0;
+        let mut element_count = 0u32;
+        let mut seen = HashSet::new();
+        let mut metadata = OsmMetadata::new("composite", self.provider_type());
+        for source in &self.sources {
+            let mut source_config = config.clone();
+            source_config.region = Region::BoundingBox(bbox.clone());
+            if let Some(features) = &source.features {
+                source_config.features = features.clone();
+            }
+
+            let data = source.provider.fetch_data(&source_config).await?;
+            let mut kept = 0;
+            OsmParser.for_each_element(&data, |mut element| {
+                if self.deduplicate && !seen.insert(element_key(&element)) {
+                    return Ok(());
+                }
+                // Keep the innermost source when composites are nested
+                element
+                    .tags
+                    .entry(SOURCE_TAG.to_string())
+                    .or_insert_with(|| source.name.clone());
+                if element_count > 0 {
+                    raw_data.push(',');
+                }
+                raw_data.push_str(&element_json(&element).to_string());
+                element_count += 1;
+                kept += 1;
+                Ok(())
+            })?;
+            if let Some(on_chunk) = on_chunk.as_deref_mut() {
+                on_chunk.chunk(&raw_data.as_bytes()[sent..]);
+                sent = raw_data.len();
+            }
+
+            tracing::debug!("Merged {} elements from source '{}'", kept, source.name);
+            metadata = metadata.with_extra(format!("source:{}", source.name), kept.to_string());
+        }
+
+        raw_data.push_str("]}");
+        if let Some(on_chunk) = on_chunk {
+            on_chunk.chunk(&raw_data.as_bytes()[sent..]);
+        }
+
+        let processing_time = {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                start_time.elapsed().as_millis() as u64
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                1u64 // Default value for WASM
+            }
+        };
+
+        let names: Vec<&str> = self.sources.iter().map(|s| s.name.as_str()).collect();
+        let metadata = metadata
+            .with_element_count(element_count)
+            .with_processing_time(processing_time)
+            .with_extra("sources", names.join(","));
+
+        tracing::Span::current().record("element_count", element_count);
+        tracing::info!("Combined provider sources");
+
+        Ok(OsmData {
+            raw_data,
+            format: OsmDataFormat::Json,
+            bounding_box: bbox,
+            metadata,
+        })
+    }
+}
+
+/// Key identifying an element independently of the source it came from
+fn element_key(element: &OsmElement) -> (u8, i64, Vec<(u64, u64)>) {
+    let element_type = match element.element_type {
+        OsmElementType::Node => 0,
+        OsmElementType::Way => 1,
+        OsmElementType::Relation => 2,
+    };
+    let geometry = element
+        .geometry
+        .iter()
+        .map(|(lat, lon)| (lat.to_bits(), lon.to_bits()))
+        .collect();
+    (element_type, element.id, geometry)
+}
+
+/// Convert an element back into Overpass JSON
+fn element_json(element: &OsmElement) -> Value {
+    let tags: Map<String, Value> = element
+        .tags
+        .iter()
+        .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+        .collect();
+
+    match element.element_type {
+        OsmElementType::Node => {
+            let (lat, lon) = element.geometry.first().copied().unwrap_or_default();
+            json!({"type": "node", "id": element.id, "lat": lat, "lon": lon, "tags": tags})
+        }
+        OsmElementType::Way | OsmElementType::Relation => {
+            let element_type = if element.element_type == OsmElementType::Way {
+                "way"
+            } else {
+                "relation"
+            };
+            let geometry: Vec<Value> = element
+                .geometry
+                .iter()
+                .map(|(lat, lon)| json!({"lat": lat, "lon": lon}))
+                .collect();
+            json!({"type": element_type, "id": element.id, "geometry": geometry, "tags": tags})
+        }
+    }
+}
+
+#[async_trait]
+impl OsmDataProvider for CompositeProvider {
+    fn provider_type(&self) -> &'static str {
+        "composite"
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        self.fetch(config, None).await
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        self.fetch(config, Some(on_chunk)).await
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        let mut last_error = OsmTilesError::Config("Composite provider has no sources".into());
+        for source in &self.sources {
+            match source.provider.resolve_region(region).await {
+                Ok(bbox) => return Ok(bbox),
+                Err(error) => last_error = error,
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        let mut last_error = OsmTilesError::Config("Composite provider has no sources".into());
+        for source in &self.sources {
+            match source.provider.resolve_region_candidates(region).await {
+                Ok(candidates) => return Ok(candidates),
+                Err(error) => last_error = error,
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        for source in &self.sources {
+            source.provider.test_availability().await?;
+        }
+        Ok(())
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        let all: Vec<ProviderCapabilities> = self
+            .sources
+            .iter()
+            .map(|s| s.provider.capabilities())
+            .collect();
+
+        ProviderCapabilities {
+            supports_real_time: all.iter().any(|c| c.supports_real_time),
+            requires_network: all.iter().any(|c| c.requires_network),
+            supports_geocoding: all.iter().any(|c| c.supports_geocoding),
+            max_area_km2: all.iter().filter_map(|c| c.max_area_km2).reduce(f64::min),
+            supported_formats: vec![OsmDataFormat::Json],
+            rate_limit_rpm: all.iter().filter_map(|c| c.rate_limit_rpm).min(),
+            wasm_compatible: all.iter().all(|c| c.wasm_compatible),
+            notes: Some(format!(
+                "Combines {} sources: {}",
+                self.sources.len(),
+                self.sources
+                    .iter()
+                    .map(|s| s.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultGridGenerator, GridGenerator, MockProvider, OsmConfigBuilder, TileType};
+
+    fn roads() -> MockProvider {
+        MockProvider::with_data(
+            r#"{"elements": [
+                {"type": "way", "id": 1, "tags": {"highway": "primary"},
+                 "geometry": [{"lat": 52.495, "lon": 13.391}, {"lat": 52.495, "lon": 13.409}]},
+                {"type": "node", "id": 7, "lat": 52.505, "lon": 13.405, "tags": {"amenity": "cafe"}}
+            ]}"#,
+        )
+    }
+
+    fn amenities() -> MockProvider {
+        MockProvider::with_data(
+            r#"{"elements": [
+                {"type": "node", "id": 7, "lat": 52.505, "lon": 13.405, "tags": {"amenity": "cafe"}},
+                {"type": "node", "id": 8, "lat": 52.502, "lon": 13.395, "tags": {"amenity": "bank"}}
+            ]}"#,
+        )
+    }
+
+    fn config() -> OsmConfig {
+        OsmConfigBuilder::new()
+            .bbox(52.49, 13.39, 52.51, 13.41)
+            .urban_features()
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_merge_sources() {
+        let provider = CompositeProvider::new()
+            .with_source("local", roads())
+            .with_source("overpass", amenities());
+
+        let data = provider.fetch_data(&config()).await.unwrap();
+        assert_eq!(data.metadata.provider_type, "composite");
+        // The cafe is delivered by both sources but kept once
+        assert_eq!(data.metadata.element_count, Some(3));
+        assert_eq!(
+            data.metadata.extra.get("source:local"),
+            Some(&"2".to_string())
+        );
+        assert_eq!(
+            data.metadata.extra.get("source:overpass"),
+            Some(&"1".to_string())
+        );
+
+        let elements = OsmParser.parse(&data).unwrap();
+        let bank = elements.iter().find(|e| e.id == 8).unwrap();
+        assert_eq!(bank.source(), Some("overpass"));
+
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&data, &config())
+            .await
+            .unwrap();
+        let road = grid
+            .iter_tiles()
+            .find(|(_, _, tile)| tile.tile_type == TileType::Road)
+            .map(|(_, _, tile)| tile.metadata.clone().unwrap())
+            .unwrap();
+        assert_eq!(road.source.as_deref(), Some("local"));
+        assert!(!road.tags.contains_key(SOURCE_TAG));
+
+        let merged = CompositeProvider::new()
+            .with_source("local", roads())
+            .with_source("overpass", amenities())
+            .with_deduplication(false)
+            .fetch_data(&config())
+            .await
+            .unwrap();
+        assert_eq!(merged.metadata.element_count, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_streams_each_source() {
+        let provider = CompositeProvider::new()
+            .with_source("local", roads())
+            .with_source("overpass", amenities());
+
+        let mut chunks = Vec::new();
+        let data = provider
+            .fetch_data_streaming(&config(), &mut |chunk: &[u8]| chunks.push(chunk.to_vec()))
+            .await
+            .unwrap();
+        // One chunk per source plus the closing brackets
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), data.raw_data.as_bytes());
+        assert_eq!(OsmParser.parse(&data).unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_requires_sources() {
+        let provider = CompositeProvider::new();
+        assert!(provider.fetch_data(&config()).await.is_err());
+        assert!(provider.capabilities().wasm_compatible);
+    }
+}
@@ -0,0 +1,830 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::http::HttpClient;
+use crate::{BoundingBox, NetworkError, OsmTilesError, Result, normalize_name};
+
+/// Cities shipped with the crate: `country;name|alternate names;south;west;north;east`
+const EMBEDDED_CITIES: &str = include_str!("gazetteer/cities.csv");
+/// Country codes and names accepted in `"place, country"` queries
+const EMBEDDED_COUNTRIES: &str = include_str!("gazetteer/countries.csv");
+
+/// A place a geocoding query may refer to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeocodeCandidate {
+    /// Human-readable name (e.g., "Frankfurt (Oder), Brandenburg, Deutschland")
+    pub display_name: String,
+    /// ISO 3166-1 alpha-2 country code, if known
+    pub country: Option<String>,
+    /// Extent of the place
+    pub bounding_box: BoundingBox,
+}
+
+/// Trait for resolving place names to bounding boxes
+///
+/// Used by [`OverpassProvider`](super::OverpassProvider) for
+/// [`Region::City`](crate::Region::City) regions.
+#[async_trait]
+pub trait Geocoder: Send + Sync {
+    /// Resolve a place name (e.g., "Berlin" or "Cambridge, US") to its extent
+    async fn geocode(&self, query: &str) -> Result<BoundingBox>;
+
+    /// Find every place a name may refer to, best match first
+    ///
+    /// The first candidate is the place [`geocode`](Self::geocode) resolves
+    /// to. The default implementation only returns that one.
+    async fn geocode_candidates(&self, query: &str) -> Result<Vec<GeocodeCandidate>> {
+        let bounding_box = self.geocode(query).await?;
+        Ok(vec![GeocodeCandidate {
+            display_name: query.trim().to_string(),
+            country: None,
+            bounding_box,
+        }])
+    }
+}
+
+/// Number of places requested from Nominatim for [`Geocoder::geocode_candidates`]
+const NOMINATIM_CANDIDATES: usize = 5;
+
+/// Geocoder using the Nominatim search API
+pub struct NominatimGeocoder {
+    pub base_url: String,
+    http_client: Arc<dyn HttpClient>,
+}
+
+impl NominatimGeocoder {
+    /// Create a geocoder for the public Nominatim instance
+    pub fn new(http_client: Arc<dyn HttpClient>) -> Self {
+        Self::with_base_url("https://nominatim.openstreetmap.org/search", http_client)
+    }
+
+    /// Create a geocoder for a custom Nominatim search endpoint
+    pub fn with_base_url(base_url: impl Into<String>, http_client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            http_client,
+        }
+    }
+
+    /// Search for up to `limit` places matching a query
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<GeocodeCandidate>> {
+        tracing::debug!("Geocoding '{}' with Nominatim", query);
+
+        let url = format!(
+            "{}?q={}&format=json&limit={}&addressdetails=1",
+            self.base_url,
+            urlencoding::encode(query.trim()),
+            limit
+        );
+
+        let response = self
+            .http_client
+            .get(&url)
+            .await
+            .map_err(NetworkError::from)?;
+
+        if response.status != 200 {
+            return Err(OsmTilesError::http_status(
+                response.status,
+                response.retry_after(),
+            ));
+        }
+
+        let geocode_results: Vec<serde_json::Value> = serde_json::from_str(&response.body)
+            .map_err(|e| OsmTilesError::json("geocoding response", e))?;
+        let candidates = parse_nominatim_results(query, &geocode_results)?;
+
+        for candidate in &candidates {
+            let bbox = &candidate.bounding_box;
+            tracing::debug!(
+                "Geocoded '{}' to '{}' at bbox: {},{},{},{}",
+                query,
+                candidate.display_name,
+                bbox.south,
+                bbox.west,
+                bbox.north,
+                bbox.east
+            );
+        }
+        Ok(candidates)
+    }
+}
+
+/// Read the places of a Nominatim search response, failing if there are none
+fn parse_nominatim_results(
+    query: &str,
+    results: &[serde_json::Value],
+) -> Result<Vec<GeocodeCandidate>> {
+    let failed = |reason: &str| OsmTilesError::GeocodingFailed {
+        query: query.to_string(),
+        reason: reason.to_string(),
+    };
+    if results.is_empty() {
+        return Err(failed("no matching place found"));
+    }
+
+    results
+        .iter()
+        .map(|result| {
+            let bbox_array = result["boundingbox"]
+                .as_array()
+                .filter(|bbox| bbox.len() == 4)
+                .ok_or_else(|| failed("result has no valid bounding box"))?;
+
+            let parse_coord = |idx: usize, coord_type: &str| -> Result<f64> {
+                bbox_array[idx]
+                    .as_str()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(|| failed(&format!("invalid {} in bounding box", coord_type)))
+            };
+
+            let south = parse_coord(0, "south latitude")?;
+            let north = parse_coord(1, "north latitude")?;
+            let west = parse_coord(2, "west longitude")?;
+            let east = parse_coord(3, "east longitude")?;
+
+            Ok(GeocodeCandidate {
+                display_name: result["display_name"]
+                    .as_str()
+                    .map_or_else(|| query.trim().to_string(), str::to_string),
+                country: result["address"]["country_code"]
+                    .as_str()
+                    .map(str::to_uppercase),
+                bounding_box: BoundingBox::new(south, west, north, east),
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Geocoder for NominatimGeocoder {
+    async fn geocode(&self, query: &str) -> Result<BoundingBox> {
+        let mut candidates = self.search(query, 1).await?;
+        Ok(candidates.swap_remove(0).bounding_box)
+    }
+
+    async fn geocode_candidates(&self, query: &str) -> Result<Vec<GeocodeCandidate>> {
+        self.search(query, NOMINATIM_CANDIDATES).await
+    }
+}
+
+/// A place known to a [`Gazetteer`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GazetteerPlace {
+    /// Display name (e.g., "München")
+    pub name: String,
+    /// Other accepted names (e.g., "Munich")
+    pub alternate_names: Vec<String>,
+    /// ISO 3166-1 alpha-2 country code (e.g., "DE")
+    pub country: String,
+    /// Approximate extent of the place
+    pub bounding_box: BoundingBox,
+}
+
+/// Offline geocoder answering from a table of places
+///
+/// [`Gazetteer::embedded`] knows about 1600 world cities, so common
+/// regions resolve without network access, e.g. in browsers where Nominatim
+/// blocks cross-origin requests. Queries are matched after [`normalize_name`]
+/// and may name the country after a comma, by ISO code or English name
+/// ("Cambridge, US", "Cambridge, United Kingdom"). Without a country, the
+/// place listed first wins.
+#[derive(Debug, Clone, Default)]
+pub struct Gazetteer {
+    places: Vec<GazetteerPlace>,
+    /// Indices into `places` by normalized name
+    by_name: HashMap<String, Vec<usize>>,
+    /// Country code by normalized code or country name
+    countries: HashMap<String, String>,
+}
+
+impl Gazetteer {
+    /// Create an empty gazetteer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a gazetteer of the cities embedded in the crate
+    ///
+    /// The tables are parsed once per process and copied from there.
+    pub fn embedded() -> Self {
+        Self::shared_embedded().as_ref().clone()
+    }
+
+    /// Get the process-wide embedded gazetteer, parsing it on first use
+    fn shared_embedded() -> Arc<Self> {
+        static EMBEDDED: OnceLock<Arc<Gazetteer>> = OnceLock::new();
+        EMBEDDED
+            .get_or_init(|| Arc::new(Self::parse_embedded()))
+            .clone()
+    }
+
+    fn parse_embedded() -> Self {
+        let mut gazetteer = Self::new();
+        for (code, names) in data_lines(EMBEDDED_COUNTRIES).filter_map(|line| line.split_once(';'))
+        {
+            gazetteer
+                .countries
+                .insert(normalize_name(code), code.into());
+            for name in names.split('|') {
+                gazetteer
+                    .countries
+                    .insert(normalize_name(name), code.into());
+            }
+        }
+        for place in data_lines(EMBEDDED_CITIES).filter_map(parse_place) {
+            gazetteer.insert(place);
+        }
+        gazetteer
+    }
+
+    /// Add a place, after the places already known
+    pub fn with_place(
+        mut self,
+        name: impl Into<String>,
+        country: impl Into<String>,
+        bounding_box: BoundingBox,
+    ) -> Self {
+        let country = country.into();
+        self.countries
+            .entry(normalize_name(&country))
+            .or_insert_with(|| country.clone());
+        self.insert(GazetteerPlace {
+            name: name.into(),
+            alternate_names: Vec::new(),
+            country,
+            bounding_box,
+        });
+        self
+    }
+
+    /// Get the number of places
+    pub fn len(&self) -> usize {
+        self.places.len()
+    }
+
+    /// Check if the gazetteer knows no places
+    pub fn is_empty(&self) -> bool {
+        self.places.is_empty()
+    }
+
+    /// Find the place a query names, `None` if it is unknown
+    pub fn lookup(&self, query: &str) -> Option<&GazetteerPlace> {
+        self.lookup_all(query).into_iter().next()
+    }
+
+    /// Find every place a query may name, in the order they were added
+    pub fn lookup_all(&self, query: &str) -> Vec<&GazetteerPlace> {
+        let (place, country) = match query.rsplit_once(',') {
+            Some((place, country)) => match self.countries.get(&normalize_name(country)) {
+                Some(country) => (place, Some(country)),
+                None => return Vec::new(),
+            },
+            None => (query, None),
+        };
+        self.by_name
+            .get(&normalize_name(place))
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.places[index])
+            .filter(|place| country.is_none_or(|country| place.country == *country))
+            .collect()
+    }
+
+    fn insert(&mut self, place: GazetteerPlace) {
+        let index = self.places.len();
+        for name in std::iter::once(&place.name).chain(&place.alternate_names) {
+            let indices = self.by_name.entry(normalize_name(name)).or_default();
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+        self.places.push(place);
+    }
+}
+
+#[async_trait]
+impl Geocoder for Gazetteer {
+    async fn geocode(&self, query: &str) -> Result<BoundingBox> {
+        self.lookup(query)
+            .map(|place| place.bounding_box.clone())
+            .ok_or_else(|| not_in_gazetteer(query))
+    }
+
+    async fn geocode_candidates(&self, query: &str) -> Result<Vec<GeocodeCandidate>> {
+        let candidates: Vec<GeocodeCandidate> = self
+            .lookup_all(query)
+            .into_iter()
+            .map(|place| GeocodeCandidate {
+                display_name: format!("{}, {}", place.name, place.country),
+                country: Some(place.country.clone()),
+                bounding_box: place.bounding_box.clone(),
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(not_in_gazetteer(query));
+        }
+        Ok(candidates)
+    }
+}
+
+fn not_in_gazetteer(query: &str) -> OsmTilesError {
+    OsmTilesError::GeocodingFailed {
+        query: query.to_string(),
+        reason: "place is not in the gazetteer".to_string(),
+    }
+}
+
+/// Skip blank lines and `#` comments of an embedded table
+fn data_lines(table: &str) -> impl Iterator<Item = &str> {
+    table
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+fn parse_place(line: &str) -> Option<GazetteerPlace> {
+    let mut fields = line.split(';');
+    let country = fields.next()?.to_string();
+    let mut names = fields.next()?.split('|').map(str::to_string);
+    let name = names.next()?;
+    let mut coord = || fields.next()?.parse::<f64>().ok();
+    let (south, west, north, east) = (coord()?, coord()?, coord()?, coord()?);
+    Some(GazetteerPlace {
+        name,
+        alternate_names: names.collect(),
+        country,
+        bounding_box: BoundingBox::new(south, west, north, east),
+    })
+}
+
+/// Geocoder wrapper remembering recent results
+///
+/// Queries are keyed after [`normalize_name`], so "Berlin" and "berlin" share
+/// an entry. Extents and candidate lists are cached; only successful lookups
+/// are, and the least recently used entry is evicted once the capacity is
+/// reached.
+pub struct CachingGeocoder {
+    inner: Arc<dyn Geocoder>,
+    capacity: usize,
+    entries: Arc<CacheEntries>,
+}
+
+/// What is known about a cached query
+#[derive(Clone)]
+struct CacheEntry {
+    bounding_box: BoundingBox,
+    /// Every candidate, once `geocode_candidates` was asked for the query
+    candidates: Option<Vec<GeocodeCandidate>>,
+}
+
+/// Cached entries by normalized query, and the queries from least to most recently used
+type CacheEntries = Mutex<(HashMap<String, CacheEntry>, VecDeque<String>)>;
+
+impl CachingGeocoder {
+    /// Wrap a geocoder with a cache of `capacity` queries
+    pub fn new(inner: Arc<dyn Geocoder>, capacity: usize) -> Self {
+        Self::with_entries(inner, capacity, Arc::default())
+    }
+
+    fn with_entries(inner: Arc<dyn Geocoder>, capacity: usize, entries: Arc<CacheEntries>) -> Self {
+        Self {
+            inner,
+            capacity: capacity.max(1),
+            entries,
+        }
+    }
+
+    /// Get the number of cached queries
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().0.len()
+    }
+
+    /// Check whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all cached queries
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.0.clear();
+        entries.1.clear();
+    }
+
+    /// Look up a cached query, marking it as most recently used
+    fn cached(&self, key: &str) -> Option<CacheEntry> {
+        let mut entries = self.entries.lock().unwrap();
+        let (map, order) = &mut *entries;
+        let entry = map.get(key).cloned()?;
+        if let Some(position) = order.iter().position(|other| other == key) {
+            order.remove(position);
+        }
+        order.push_back(key.to_string());
+        Some(entry)
+    }
+
+    /// Store what is known about a query, evicting the least recently used entry if full
+    fn remember(&self, key: String, entry: CacheEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        let (map, order) = &mut *entries;
+        if map.insert(key.clone(), entry).is_none() {
+            order.push_back(key);
+        }
+        while order.len() > self.capacity {
+            if let Some(oldest) = order.pop_front() {
+                map.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Geocoder for CachingGeocoder {
+    async fn geocode(&self, query: &str) -> Result<BoundingBox> {
+        let key = normalize_name(query);
+        if let Some(entry) = self.cached(&key) {
+            tracing::debug!("Serving '{}' from the geocoder cache", query);
+            return Ok(entry.bounding_box);
+        }
+
+        let bounding_box = self.inner.geocode(query).await?;
+        self.remember(
+            key,
+            CacheEntry {
+                bounding_box: bounding_box.clone(),
+                candidates: None,
+            },
+        );
+        Ok(bounding_box)
+    }
+
+    async fn geocode_candidates(&self, query: &str) -> Result<Vec<GeocodeCandidate>> {
+        let key = normalize_name(query);
+        if let Some(candidates) = self.cached(&key).and_then(|entry| entry.candidates) {
+            tracing::debug!("Serving candidates for '{}' from the geocoder cache", query);
+            return Ok(candidates);
+        }
+
+        let candidates = self.inner.geocode_candidates(query).await?;
+        if let Some(best) = candidates.first() {
+            self.remember(
+                key,
+                CacheEntry {
+                    bounding_box: best.bounding_box.clone(),
+                    candidates: Some(candidates.clone()),
+                },
+            );
+        }
+        Ok(candidates)
+    }
+}
+
+/// Geocoder trying several geocoders in order until one resolves the query
+///
+/// Fails with the error of the last geocoder if none does. Candidates are
+/// collected the same way: later geocoders are only asked while fewer than
+/// [`with_min_candidates`](Self::with_min_candidates) places (one by default)
+/// were found, leaving out places an earlier geocoder already found.
+pub struct GeocoderChain {
+    geocoders: Vec<Arc<dyn Geocoder>>,
+    min_candidates: usize,
+}
+
+impl Default for GeocoderChain {
+    fn default() -> Self {
+        Self {
+            geocoders: Vec::new(),
+            min_candidates: 1,
+        }
+    }
+}
+
+impl GeocoderChain {
+    /// Create an empty chain
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep asking later geocoders for candidates until `count` places are found
+    ///
+    /// `usize::MAX` asks every geocoder, e.g. to list Nominatim's matches
+    /// next to the gazetteer's at the cost of a request per query.
+    pub fn with_min_candidates(mut self, count: usize) -> Self {
+        self.min_candidates = count.max(1);
+        self
+    }
+
+    /// Create the default chain: the embedded [`Gazetteer`], then cached Nominatim lookups
+    ///
+    /// Every default chain shares one gazetteer and one Nominatim cache, so
+    /// providers created per load neither parse the tables nor repeat
+    /// lookups again.
+    pub fn with_defaults(http_client: Arc<dyn HttpClient>) -> Self {
+        static NOMINATIM_CACHE: OnceLock<Arc<CacheEntries>> = OnceLock::new();
+        let nominatim = Arc::new(NominatimGeocoder::new(http_client));
+        let entries = NOMINATIM_CACHE.get_or_init(Arc::default).clone();
+        Self {
+            geocoders: vec![
+                Gazetteer::shared_embedded(),
+                Arc::new(CachingGeocoder::with_entries(nominatim, 64, entries)),
+            ],
+            ..Self::default()
+        }
+    }
+
+    /// Add a geocoder tried after the existing ones
+    pub fn with_geocoder(mut self, geocoder: impl Geocoder + 'static) -> Self {
+        self.geocoders.push(Arc::new(geocoder));
+        self
+    }
+
+    /// Get the number of geocoders
+    pub fn len(&self) -> usize {
+        self.geocoders.len()
+    }
+
+    /// Check if the chain has no geocoders
+    pub fn is_empty(&self) -> bool {
+        self.geocoders.is_empty()
+    }
+}
+
+#[async_trait]
+impl Geocoder for GeocoderChain {
+    async fn geocode(&self, query: &str) -> Result<BoundingBox> {
+        let mut last_error = None;
+        for geocoder in &self.geocoders {
+            match geocoder.geocode(query).await {
+                Ok(bbox) => return Ok(bbox),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(
+            last_error.unwrap_or_else(|| OsmTilesError::GeocodingFailed {
+                query: query.to_string(),
+                reason: "no geocoder configured".to_string(),
+            }),
+        )
+    }
+
+    async fn geocode_candidates(&self, query: &str) -> Result<Vec<GeocodeCandidate>> {
+        let mut candidates: Vec<GeocodeCandidate> = Vec::new();
+        let mut last_error = None;
+        for geocoder in &self.geocoders {
+            if candidates.len() >= self.min_candidates {
+                break;
+            }
+            match geocoder.geocode_candidates(query).await {
+                Ok(found) => {
+                    for candidate in found {
+                        // The same place found twice has its center inside the earlier extent
+                        let (lat, lon) = candidate.bounding_box.center();
+                        if !candidates
+                            .iter()
+                            .any(|known| known.bounding_box.contains(lat, lon))
+                        {
+                            candidates.push(candidate);
+                        }
+                    }
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+        if !candidates.is_empty() {
+            return Ok(candidates);
+        }
+        Err(
+            last_error.unwrap_or_else(|| OsmTilesError::GeocodingFailed {
+                query: query.to_string(),
+                reason: "no geocoder configured".to_string(),
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Geocoder counting its lookups, resolving everything but "Atlantis"
+    #[derive(Default)]
+    struct CountingGeocoder {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Geocoder for CountingGeocoder {
+        async fn geocode(&self, query: &str) -> Result<BoundingBox> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if query == "Atlantis" {
+                return Err(OsmTilesError::GeocodingFailed {
+                    query: query.to_string(),
+                    reason: "no matching place found".to_string(),
+                });
+            }
+            Ok(BoundingBox::new(1.0, 2.0, 3.0, 4.0))
+        }
+    }
+
+    /// Nominatim stand-in answering one place, or failing when `broken`
+    #[derive(Default)]
+    struct NominatimServer {
+        broken: bool,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpClient for NominatimServer {
+        async fn get(&self, _url: &str) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.broken {
+                return Err(HttpError::HttpStatus { status: 503 });
+            }
+            Ok(crate::http::HttpResponse {
+                status: 200,
+                body: r#"[{"display_name": "Kleinkleckersdorf", "boundingbox": ["1", "2", "3", "4"]}]"#
+                    .to_string(),
+                headers: HashMap::new(),
+                transfer: None,
+            })
+        }
+
+        async fn post_form(
+            &self,
+            _url: &str,
+            _form_data: &[(&str, &str)],
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 405 })
+        }
+
+        async fn post_json(
+            &self,
+            _url: &str,
+            _json: &str,
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 405 })
+        }
+
+        async fn test_connectivity(&self, _url: &str) -> crate::http::HttpResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_gazetteer_lookup() {
+        let gazetteer = Gazetteer::embedded();
+        assert!(gazetteer.len() >= 1600);
+
+        let berlin = gazetteer.lookup("Berlin").unwrap();
+        assert_eq!(berlin.country, "DE");
+        assert!(berlin.bounding_box.contains(52.52, 13.405));
+
+        let munich = gazetteer.lookup("munich").unwrap();
+        assert_eq!(munich.name, "München");
+        assert_eq!(gazetteer.lookup("MÜNCHEN, Germany"), Some(munich));
+
+        let uk = gazetteer.lookup("Cambridge").unwrap();
+        assert_eq!(uk.country, "GB");
+        assert_eq!(gazetteer.lookup("Cambridge, US").unwrap().country, "US");
+        assert_eq!(gazetteer.lookup("Cambridge, United Kingdom"), Some(uk));
+
+        assert!(gazetteer.lookup("Atlantis").is_none());
+        assert!(gazetteer.lookup("Berlin, Atlantis").is_none());
+
+        assert_eq!(
+            gazetteer.lookup("Frankfurt").unwrap().name,
+            "Frankfurt am Main"
+        );
+        let oder = gazetteer.lookup("Frankfurt (Oder)").unwrap();
+        assert!(oder.bounding_box.contains(52.35, 14.55));
+        assert!(gazetteer.lookup("Berlin, FR").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_caching_geocoder_chain() {
+        let counting = Arc::new(CountingGeocoder::default());
+        let cache = CachingGeocoder::new(counting.clone(), 2);
+        for query in ["Berlin", "berlin", "BERLIN"] {
+            assert!(cache.geocode(query).await.is_ok());
+        }
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 1);
+
+        // Failures are not cached
+        assert!(cache.geocode("Atlantis").await.is_err());
+        assert!(cache.geocode("Atlantis").await.is_err());
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 3);
+
+        // Reading Berlin keeps it, so Paris is evicted by Rome
+        cache.geocode("Paris").await.unwrap();
+        cache.geocode("Berlin").await.unwrap();
+        cache.geocode("Rome").await.unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.geocode("Berlin").await.unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 5);
+        cache.geocode("Paris").await.unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 6);
+
+        // The gazetteer answers known places before the fallback is asked
+        let fallback = Arc::new(CountingGeocoder::default());
+        let chain = GeocoderChain::new()
+            .with_geocoder(Gazetteer::embedded())
+            .with_geocoder(CachingGeocoder::new(fallback.clone(), 8));
+        let berlin = chain.geocode("Berlin").await.unwrap();
+        assert!(berlin.contains(52.52, 13.405));
+        assert_eq!(fallback.calls.load(Ordering::SeqCst), 0);
+        assert!(chain.geocode("Kleinkleckersdorf").await.is_ok());
+        assert_eq!(fallback.calls.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            chain.geocode("Atlantis").await,
+            Err(OsmTilesError::GeocodingFailed { .. })
+        ));
+        assert!(GeocoderChain::new().geocode("Berlin").await.is_err());
+
+        // Default chains share their Nominatim cache
+        let server = Arc::new(NominatimServer::default());
+        let first = GeocoderChain::with_defaults(server.clone());
+        let query = "Kleinkleckersdorf (shared cache test)";
+        assert!(first.geocode(query).await.is_ok());
+        assert_eq!(server.calls.load(Ordering::SeqCst), 1);
+        let broken = Arc::new(NominatimServer {
+            broken: true,
+            ..Default::default()
+        });
+        let second = GeocoderChain::with_defaults(broken.clone());
+        assert_eq!(
+            second.geocode(query).await.unwrap(),
+            BoundingBox::new(1.0, 3.0, 2.0, 4.0)
+        );
+        assert_eq!(broken.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_geocode_candidates() {
+        let oder = BoundingBox::new(52.25, 14.39, 52.43, 14.6);
+        let gazetteer = Gazetteer::new()
+            .with_place("Frankfurt", "DE", BoundingBox::new(50.0, 8.5, 50.2, 8.8))
+            .with_place(
+                "Frankfurt",
+                "US",
+                BoundingBox::new(38.1, -84.95, 38.25, -84.8),
+            )
+            .with_place("Frankfurt", "DE", oder.clone());
+        let candidates = gazetteer.geocode_candidates(" FRANKFURT ").await.unwrap();
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0].display_name, "Frankfurt, DE");
+        assert_eq!(candidates[1].country.as_deref(), Some("US"));
+        assert_eq!(gazetteer.lookup_all("Frankfurt, DE").len(), 2);
+        assert!(gazetteer.geocode_candidates("Frankfurt, FR").await.is_err());
+
+        let results: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[
+                {"display_name": "Frankfurt am Main, Hessen, Deutschland",
+                 "boundingbox": ["50.0", "50.2", "8.5", "8.8"],
+                 "address": {"country_code": "de"}},
+                {"display_name": "Frankfurt (Oder), Brandenburg, Deutschland",
+                 "boundingbox": ["52.25", "52.43", "14.39", "14.6"],
+                 "address": {"country_code": "de"}}
+            ]"#,
+        )
+        .unwrap();
+        let nominatim = parse_nominatim_results("Frankfurt", &results).unwrap();
+        assert_eq!(nominatim[1].bounding_box, oder);
+        assert_eq!(nominatim[1].country.as_deref(), Some("DE"));
+        assert!(parse_nominatim_results("Atlantis", &[]).is_err());
+
+        // Later geocoders are only asked when the earlier ones found nothing
+        let fallback = Arc::new(CountingGeocoder::default());
+        let chain = GeocoderChain::new()
+            .with_geocoder(Gazetteer::embedded())
+            .with_geocoder(CachingGeocoder::new(fallback.clone(), 8));
+        assert_eq!(
+            chain.geocode_candidates("Frankfurt").await.unwrap().len(),
+            2
+        );
+        assert_eq!(fallback.calls.load(Ordering::SeqCst), 0);
+        for query in ["Kleinkleckersdorf", "KLEINKLECKERSDORF"] {
+            assert_eq!(chain.geocode_candidates(query).await.unwrap().len(), 1);
+        }
+        chain.geocode("kleinkleckersdorf").await.unwrap();
+        assert_eq!(fallback.calls.load(Ordering::SeqCst), 1);
+
+        // Places found by an earlier geocoder are not repeated, failing geocoders are skipped
+        let chain = GeocoderChain::new()
+            .with_geocoder(Gazetteer::embedded())
+            .with_geocoder(CountingGeocoder::default())
+            .with_geocoder(Gazetteer::new().with_place("Frankfurt", "DE", oder))
+            .with_min_candidates(usize::MAX);
+        let merged = chain.geocode_candidates("Frankfurt").await.unwrap();
+        let names: Vec<&str> = merged.iter().map(|c| c.display_name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Frankfurt am Main, DE", "Frankfurt (Oder), DE", "Frankfurt"]
+        );
+        assert!(chain.geocode_candidates("Kleinkleckersdorf").await.is_ok());
+    }
+}
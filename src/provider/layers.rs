@@ -0,0 +1,920 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use super::{
+    ChunkSink, GeocodeCandidate, OsmData, OsmDataProvider, ProviderCapabilities, RetryPolicy,
+    RetryingProvider,
+};
+use crate::metrics::{
+    BYTES_DOWNLOADED_TOTAL, MetricsSink, REQUEST_DURATION_SECONDS, REQUEST_FAILURES_TOTAL,
+    REQUESTS_TOTAL,
+};
+use crate::{BoundingBox, OsmConfig, Region, Result};
+
+/// Decorator wrapping a provider with cross-cutting behavior
+///
+/// Layers are stacked with [`ProviderBuilder`]; the first layer added is the
+/// outermost one and sees every call first.
+pub trait ProviderLayer: Send + Sync {
+    /// Wrap `inner` with this layer
+    fn layer(&self, inner: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider>;
+}
+
+/// Builder stacking layers around a provider
+///
+/// ```rust,no_run
+/// use bevy_osm_tiles::ProviderFactory;
+///
+/// let provider = ProviderFactory::builder()
+///     .with_logging()
+///     .with_cache()
+///     .with_retry(3)
+///     .build(ProviderFactory::overpass());
+/// ```
+#[derive(Default)]
+pub struct ProviderBuilder {
+    layers: Vec<Box<dyn ProviderLayer>>,
+}
+
+impl ProviderBuilder {
+    /// Create a builder without layers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a custom layer
+    pub fn with_layer(mut self, layer: impl ProviderLayer + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    /// Log every fetch with its duration and element count
+    pub fn with_logging(self) -> Self {
+        self.with_layer(LoggingLayer)
+    }
+
+    /// Cache fetched data in memory, keeping the 16 most recent requests
+    pub fn with_cache(self) -> Self {
+        self.with_cache_capacity(16)
+    }
+
+    /// Cache fetched data in memory, keeping the `capacity` most recent requests
+    pub fn with_cache_capacity(self, capacity: usize) -> Self {
+        self.with_layer(CacheLayer { capacity })
+    }
+
+    /// Retry transient failures with up to `max_attempts` attempts and the default backoff
+    pub fn with_retry(self, max_attempts: u32) -> Self {
+        self.with_retry_policy(RetryPolicy::default().with_max_attempts(max_attempts))
+    }
+
+    /// Retry transient failures with a custom policy
+    pub fn with_retry_policy(self, policy: RetryPolicy) -> Self {
+        self.with_layer(RetryLayer { policy })
+    }
+
+    /// Space fetches so that at most `requests_per_minute` are started
+    pub fn with_rate_limit(self, requests_per_minute: u32) -> Self {
+        self.with_layer(RateLimitLayer {
+            requests_per_minute,
+        })
+    }
+
+    /// Record request counts and timings into `metrics`
+    pub fn with_metrics(self, metrics: ProviderMetrics) -> Self {
+        self.with_layer(MetricsLayer { metrics })
+    }
+
+    /// Report request counts, durations and downloaded bytes to a [`MetricsSink`]
+    pub fn with_metrics_sink(self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.with_layer(MetricsSinkLayer { sink })
+    }
+
+    /// Get the number of layers
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Check whether no layers were added
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Wrap `provider` with all layers
+    pub fn build(self, provider: impl OsmDataProvider + 'static) -> Box<dyn OsmDataProvider> {
+        self.build_boxed(Box::new(provider))
+    }
+
+    /// Wrap an already boxed provider with all layers
+    pub fn build_boxed(self, provider: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider> {
+        self.layers
+            .iter()
+            .rev()
+            .fold(provider, |inner, layer| layer.layer(inner))
+    }
+}
+
+/// Layer logging every fetch
+pub struct LoggingLayer;
+
+impl ProviderLayer for LoggingLayer {
+    fn layer(&self, inner: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider> {
+        Box::new(LoggingProvider { inner })
+    }
+}
+
+/// Provider wrapper logging the outcome of every fetch
+pub struct LoggingProvider {
+    inner: Box<dyn OsmDataProvider>,
+}
+
+#[async_trait]
+impl OsmDataProvider for LoggingProvider {
+    fn provider_type(&self) -> &'static str {
+        self.inner.provider_type()
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        self.logged(config, self.inner.fetch_data(config)).await
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        self.logged(config, self.inner.fetch_data_streaming(config, on_chunk))
+            .await
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        self.inner.resolve_region(region).await
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        self.inner.resolve_region_candidates(region).await
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        self.inner.test_availability().await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+}
+
+impl LoggingProvider {
+    /// Run a fetch of the inner provider and log its outcome
+    async fn logged(
+        &self,
+        config: &OsmConfig,
+        fetch: impl Future<Output = Result<OsmData>>,
+    ) -> Result<OsmData> {
+        tracing::debug!(
+            "Fetching {:?} from the {} provider",
+            config.region,
+            self.provider_type()
+        );
+        let (result, elapsed_ms) = timed(fetch).await;
+
+        match &result {
+            Ok(data) => tracing::info!(
+                "{} provider returned {} elements ({} bytes) in {} ms",
+                self.provider_type(),
+                data.metadata.element_count.unwrap_or(0),
+                data.raw_data.len(),
+                elapsed_ms
+            ),
+            Err(error) => tracing::warn!(
+                "{} provider failed after {} ms: {}",
+                self.provider_type(),
+                elapsed_ms,
+                error
+            ),
+        }
+        result
+    }
+}
+
+/// Layer caching fetched data in memory
+pub struct CacheLayer {
+    /// Number of requests kept
+    pub capacity: usize,
+}
+
+impl ProviderLayer for CacheLayer {
+    fn layer(&self, inner: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider> {
+        Box::new(CachingProvider::new(inner, self.capacity))
+    }
+}
+
+/// Provider wrapper answering repeated requests from memory
+///
+/// Only successful fetches are cached; the oldest entry is evicted once the
+/// capacity is reached. Cached responses carry a `cache = hit` metadata extra. Entries whose data is
+/// older than the request's `max_data_age_seconds` are fetched again.
+pub struct CachingProvider {
+    inner: Box<dyn OsmDataProvider>,
+    capacity: usize,
+    entries: Mutex<(HashMap<String, OsmData>, VecDeque<String>)>,
+}
+
+impl CachingProvider {
+    /// Wrap a provider with a cache of `capacity` requests
+    pub fn new(inner: Box<dyn OsmDataProvider>, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity: capacity.max(1),
+            entries: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    /// Get the number of cached requests
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().0.len()
+    }
+
+    /// Check whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all cached requests
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.0.clear();
+        entries.1.clear();
+    }
+
+    /// Get the key identifying the data a configuration fetches
+    ///
+    /// Configurations that only differ in the accepted data age share a key.
+    pub fn cache_key(config: &OsmConfig) -> String {
+        let mut queries: Vec<String> = config
+            .features
+            .to_osm_queries()
+            .iter()
+            .chain(config.features.exclusions())
+            .map(|query| format!("{:?}", query))
+            .collect();
+        queries.sort();
+
+        // Feature sets serialize in hash order, so they are keyed by their sorted queries
+        let mut value = serde_json::to_value(config).unwrap_or_default();
+        value["features"] = queries.into();
+        // The accepted data age does not change what is fetched
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("max_data_age_seconds");
+        }
+        value.to_string()
+    }
+}
+
+#[async_trait]
+impl OsmDataProvider for CachingProvider {
+    fn provider_type(&self) -> &'static str {
+        self.inner.provider_type()
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        if let Some(data) = self.cached(config) {
+            return Ok(data);
+        }
+        let data = self.inner.fetch_data(config).await?;
+        self.remember(config, &data);
+        Ok(data)
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        if let Some(data) = self.cached(config) {
+            on_chunk.chunk(data.raw_data.as_bytes());
+            return Ok(data);
+        }
+        let data = self.inner.fetch_data_streaming(config, on_chunk).await?;
+        self.remember(config, &data);
+        Ok(data)
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        self.inner.resolve_region(region).await
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        self.inner.resolve_region_candidates(region).await
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        self.inner.test_availability().await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+}
+
+impl CachingProvider {
+    /// Get the cached data of a configuration, marked as a cache hit
+    fn cached(&self, config: &OsmConfig) -> Option<OsmData> {
+        let key = Self::cache_key(config);
+        let cached = self.entries.lock().unwrap().0.get(&key).cloned();
+        // Stale entries are fetched again and replaced
+        let data = cached.filter(|data| {
+            config
+                .max_data_age()
+                .is_none_or(|max_age| !data.metadata.is_stale(max_age))
+        })?;
+        tracing::debug!("Serving {:?} from the provider cache", config.region);
+        Some(OsmData {
+            metadata: data.metadata.clone().with_extra("cache", "hit"),
+            ..data
+        })
+    }
+
+    /// Cache freshly fetched data, evicting the oldest entries beyond the capacity
+    fn remember(&self, config: &OsmConfig, data: &OsmData) {
+        let key = Self::cache_key(config);
+        let mut entries = self.entries.lock().unwrap();
+        let (map, order) = &mut *entries;
+        if map.insert(key.clone(), data.clone()).is_none() {
+            order.push_back(key);
+        }
+        while order.len() > self.capacity {
+            if let Some(oldest) = order.pop_front() {
+                map.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Layer retrying transient failures, see [`RetryingProvider`]
+pub struct RetryLayer {
+    /// Retry policy applied to every fetch
+    pub policy: RetryPolicy,
+}
+
+impl ProviderLayer for RetryLayer {
+    fn layer(&self, inner: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider> {
+        Box::new(RetryingProvider::new(inner, self.policy.clone()))
+    }
+}
+
+/// Layer limiting how often fetches are started
+pub struct RateLimitLayer {
+    /// Maximum number of fetches started per minute
+    pub requests_per_minute: u32,
+}
+
+impl ProviderLayer for RateLimitLayer {
+    fn layer(&self, inner: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider> {
+        Box::new(RateLimitedProvider::new(inner, self.requests_per_minute))
+    }
+}
+
+/// Provider wrapper spacing fetches evenly to stay below a request rate
+///
+/// Waiting fetches do not block the executor; in browsers they wait with
+/// `setTimeout`.
+pub struct RateLimitedProvider {
+    inner: Box<dyn OsmDataProvider>,
+    interval_ms: u64,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimitedProvider {
+    /// Wrap a provider allowing `requests_per_minute` fetches per minute
+    pub fn new(inner: Box<dyn OsmDataProvider>, requests_per_minute: u32) -> Self {
+        Self {
+            inner,
+            interval_ms: 60_000 / u64::from(requests_per_minute.max(1)),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Reserve the next free slot and get how long to wait for it
+    fn reserve(&self) -> u64 {
+        let now = Instant::now();
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let slot = next_slot.map_or(now, |next| next.max(now));
+        *next_slot = Some(slot + Duration::from_millis(self.interval_ms));
+        (slot - now).as_micros().div_ceil(1000) as u64
+    }
+
+    /// Wait until the next fetch may start
+    async fn wait_for_slot(&self) {
+        let wait_ms = self.reserve();
+        if wait_ms > 0 {
+            tracing::debug!("Rate limit: waiting {} ms before fetching", wait_ms);
+            crate::http::sleep(wait_ms).await;
+        }
+    }
+}
+
+#[async_trait]
+impl OsmDataProvider for RateLimitedProvider {
+    fn provider_type(&self) -> &'static str {
+        self.inner.provider_type()
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        self.wait_for_slot().await;
+        self.inner.fetch_data(config).await
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        self.wait_for_slot().await;
+        self.inner.fetch_data_streaming(config, on_chunk).await
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        self.inner.resolve_region(region).await
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        self.inner.resolve_region_candidates(region).await
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        self.inner.test_availability().await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        let rate_limit = (60_000 / self.interval_ms.max(1)) as u32;
+        ProviderCapabilities {
+            rate_limit_rpm: Some(
+                self.inner
+                    .capabilities()
+                    .rate_limit_rpm
+                    .map_or(rate_limit, |rpm| rpm.min(rate_limit)),
+            ),
+            ..self.inner.capabilities()
+        }
+    }
+}
+
+/// Shared counters filled by [`MetricsProvider`]; clones refer to the same counters
+#[derive(Debug, Clone, Default)]
+pub struct ProviderMetrics {
+    inner: Arc<MetricsCounters>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsCounters {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    elements: AtomicU64,
+    bytes: AtomicU64,
+    total_time_ms: AtomicU64,
+}
+
+/// Point-in-time copy of [`ProviderMetrics`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MetricsSnapshot {
+    /// Number of fetches started
+    pub requests: u64,
+    /// Number of fetches that failed
+    pub failures: u64,
+    /// Elements returned by successful fetches
+    pub elements: u64,
+    /// Bytes of raw data returned by successful fetches
+    pub bytes: u64,
+    /// Total time spent fetching in milliseconds
+    pub total_time_ms: u64,
+}
+
+impl MetricsSnapshot {
+    /// Get the average fetch duration in milliseconds
+    pub fn average_time_ms(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.total_time_ms as f64 / self.requests as f64
+        }
+    }
+}
+
+impl ProviderMetrics {
+    /// Create empty metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the current counters
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let counters = &self.inner;
+        MetricsSnapshot {
+            requests: counters.requests.load(Ordering::Relaxed),
+            failures: counters.failures.load(Ordering::Relaxed),
+            elements: counters.elements.load(Ordering::Relaxed),
+            bytes: counters.bytes.load(Ordering::Relaxed),
+            total_time_ms: counters.total_time_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Layer recording request metrics
+pub struct MetricsLayer {
+    /// Counters updated by the wrapped provider
+    pub metrics: ProviderMetrics,
+}
+
+impl ProviderLayer for MetricsLayer {
+    fn layer(&self, inner: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider> {
+        Box::new(MetricsProvider {
+            inner,
+            metrics: self.metrics.clone(),
+        })
+    }
+}
+
+/// Provider wrapper counting fetches, failures, elements and time spent
+pub struct MetricsProvider {
+    inner: Box<dyn OsmDataProvider>,
+    metrics: ProviderMetrics,
+}
+
+#[async_trait]
+impl OsmDataProvider for MetricsProvider {
+    fn provider_type(&self) -> &'static str {
+        self.inner.provider_type()
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        self.recorded(self.inner.fetch_data(config)).await
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        self.recorded(self.inner.fetch_data_streaming(config, on_chunk))
+            .await
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        self.inner.resolve_region(region).await
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        self.inner.resolve_region_candidates(region).await
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        self.inner.test_availability().await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+}
+
+impl MetricsProvider {
+    /// Run a fetch of the inner provider and count its outcome
+    async fn recorded(&self, fetch: impl Future<Output = Result<OsmData>>) -> Result<OsmData> {
+        let (result, elapsed_ms) = timed(fetch).await;
+
+        let counters = &self.metrics.inner;
+        counters.requests.fetch_add(1, Ordering::Relaxed);
+        counters
+            .total_time_ms
+            .fetch_add(elapsed_ms, Ordering::Relaxed);
+        match &result {
+            Ok(data) => {
+                let elements = data.metadata.element_count.unwrap_or(0);
+                counters
+                    .elements
+                    .fetch_add(u64::from(elements), Ordering::Relaxed);
+                counters
+                    .bytes
+                    .fetch_add(data.raw_data.len() as u64, Ordering::Relaxed);
+            }
+            Err(_) => {
+                counters.failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        result
+    }
+}
+
+/// Layer reporting request metrics to a [`MetricsSink`]
+pub struct MetricsSinkLayer {
+    /// Sink receiving the metrics
+    pub sink: Arc<dyn MetricsSink>,
+}
+
+impl ProviderLayer for MetricsSinkLayer {
+    fn layer(&self, inner: Box<dyn OsmDataProvider>) -> Box<dyn OsmDataProvider> {
+        Box::new(MetricsSinkProvider {
+            inner,
+            sink: self.sink.clone(),
+        })
+    }
+}
+
+/// Provider wrapper reporting fetches, failures, durations and downloaded bytes
+///
+/// Downloaded bytes are the compressed size on the wire when the provider
+/// reports it; responses served from a cache below this layer count as
+/// requests but not as downloads.
+pub struct MetricsSinkProvider {
+    inner: Box<dyn OsmDataProvider>,
+    sink: Arc<dyn MetricsSink>,
+}
+
+#[async_trait]
+impl OsmDataProvider for MetricsSinkProvider {
+    fn provider_type(&self) -> &'static str {
+        self.inner.provider_type()
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        self.reported(self.inner.fetch_data(config)).await
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        self.reported(self.inner.fetch_data_streaming(config, on_chunk))
+            .await
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        self.inner.resolve_region(region).await
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        self.inner.resolve_region_candidates(region).await
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        self.inner.test_availability().await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+}
+
+impl MetricsSinkProvider {
+    /// Run a fetch of the inner provider and report its outcome to the sink
+    async fn reported(&self, fetch: impl Future<Output = Result<OsmData>>) -> Result<OsmData> {
+        let (result, elapsed_ms) = timed(fetch).await;
+
+        let labels = [("provider", self.inner.provider_type())];
+        self.sink.increment_counter(REQUESTS_TOTAL, 1, &labels);
+        self.sink.record_histogram(
+            REQUEST_DURATION_SECONDS,
+            elapsed_ms as f64 / 1000.0,
+            &labels,
+        );
+        match &result {
+            Ok(data)
+                if data
+                    .metadata
+                    .extra
+                    .get("cache")
+                    .is_none_or(|cache| cache != "hit") =>
+            {
+                let bytes = data
+                    .metadata
+                    .extra
+                    .get("wire_bytes")
+                    .and_then(|bytes| bytes.parse().ok())
+                    .unwrap_or(data.raw_data.len() as u64);
+                self.sink
+                    .increment_counter(BYTES_DOWNLOADED_TOTAL, bytes, &labels);
+            }
+            Ok(_) => {}
+            Err(error) => {
+                let retryable = if error.is_retryable() {
+                    "true"
+                } else {
+                    "false"
+                };
+                self.sink.increment_counter(
+                    REQUEST_FAILURES_TOTAL,
+                    1,
+                    &[labels[0], ("retryable", retryable)],
+                );
+            }
+        }
+        result
+    }
+}
+
+/// Await `future` and measure how long it took in milliseconds
+async fn timed<T>(future: impl std::future::Future<Output = T>) -> (T, u64) {
+    let start_time = Instant::now();
+    let output = future.await;
+    (output, start_time.elapsed().as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemoryMetricsSink, MockProvider, OsmConfigBuilder, ProviderFactory};
+
+    fn config() -> OsmConfig {
+        OsmConfigBuilder::new()
+            .bbox(52.49, 13.39, 52.51, 13.41)
+            .urban_features()
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_layer_stack() {
+        let metrics = ProviderMetrics::new();
+        let provider = ProviderFactory::builder()
+            .with_logging()
+            .with_cache()
+            .with_metrics(metrics.clone())
+            .with_retry_policy(RetryPolicy::default().with_delays(0, 0))
+            .build(MockProvider::new().with_status_sequence([503]));
+        assert_eq!(provider.provider_type(), "mock");
+
+        // The first fetch is retried below the metrics layer
+        let first = provider.fetch_data(&config()).await.unwrap();
+        assert_eq!(first.metadata.extra.get("attempts"), Some(&"2".to_string()));
+        assert!(!first.metadata.extra.contains_key("cache"));
+
+        // The second one is served by the cache above it
+        let second = provider.fetch_data(&config()).await.unwrap();
+        assert_eq!(second.metadata.extra.get("cache"), Some(&"hit".to_string()));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 1);
+        assert_eq!(snapshot.failures, 0);
+        assert_eq!(snapshot.elements, 4);
+    }
+
+    #[tokio::test]
+    async fn test_cache_eviction_and_keys() {
+        let cache = CachingProvider::new(Box::new(MockProvider::new()), 1);
+        cache.fetch_data(&config()).await.unwrap();
+        cache.fetch_data(&config()).await.unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let other = OsmConfigBuilder::new()
+            .bbox(52.49, 13.39, 52.51, 13.41)
+            .grid_resolution(2000)
+            .urban_features()
+            .build();
+        assert_ne!(
+            CachingProvider::cache_key(&config()),
+            CachingProvider::cache_key(&other)
+        );
+        cache.fetch_data(&other).await.unwrap();
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_sink() {
+        let sink = Arc::new(InMemoryMetricsSink::new());
+        let provider = ProviderFactory::builder()
+            .with_metrics_sink(sink.clone())
+            .with_cache()
+            .build(MockProvider::new());
+        let first = provider.fetch_data(&config()).await.unwrap();
+        provider.fetch_data(&config()).await.unwrap();
+
+        assert_eq!(sink.counter(REQUESTS_TOTAL), 2);
+        assert_eq!(sink.histogram(REQUEST_DURATION_SECONDS).0, 2);
+        // The cache hit is not downloaded again
+        assert_eq!(
+            sink.counter(BYTES_DOWNLOADED_TOTAL),
+            first.raw_data.len() as u64
+        );
+
+        let failing = ProviderFactory::builder()
+            .with_metrics_sink(sink.clone())
+            .build(MockProvider::new().with_status_sequence([404]));
+        assert!(failing.fetch_data(&config()).await.is_err());
+        assert!(
+            sink.render().contains(
+                "osm_tiles_request_failures_total{provider=\"mock\",retryable=\"false\"} 1"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_refetches_stale_data() {
+        let old = r#"{"osm3s": {"timestamp_osm_base": "2020-01-01T00:00:00Z"}, "elements": []}"#;
+        let cache = CachingProvider::new(Box::new(MockProvider::with_data(old)), 4);
+        cache.fetch_data(&config()).await.unwrap();
+
+        let hit = cache.fetch_data(&config()).await.unwrap();
+        assert_eq!(hit.metadata.extra.get("cache"), Some(&"hit".to_string()));
+
+        let strict = config().with_max_data_age(24 * 3600);
+        let fresh = cache.fetch_data(&strict).await.unwrap();
+        assert!(!fresh.metadata.extra.contains_key("cache"));
+        assert!(fresh.metadata.is_stale(Duration::from_secs(24 * 3600)));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_spaces_requests() {
+        let provider = ProviderFactory::builder()
+            .with_rate_limit(1200)
+            .build(MockProvider::new());
+        assert_eq!(provider.capabilities().rate_limit_rpm, Some(1200));
+
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            provider.fetch_data(&config()).await.unwrap();
+        }
+        // 1200 requests per minute leave 50 ms between fetches
+        assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+    }
+
+    /// Provider streaming the mock data in small chunks
+    struct ChunkedProvider(MockProvider);
+
+    #[async_trait]
+    impl OsmDataProvider for ChunkedProvider {
+        fn provider_type(&self) -> &'static str {
+            "chunked"
+        }
+
+        async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+            self.0.fetch_data(config).await
+        }
+
+        async fn fetch_data_streaming(
+            &self,
+            config: &OsmConfig,
+            on_chunk: &mut dyn ChunkSink,
+        ) -> Result<OsmData> {
+            let data = self.0.fetch_data(config).await?;
+            for chunk in data.raw_data.as_bytes().chunks(64) {
+                on_chunk.chunk(chunk);
+            }
+            Ok(data)
+        }
+
+        async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+            self.0.resolve_region(region).await
+        }
+
+        async fn test_availability(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            self.0.capabilities()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_layers_forward_streaming() {
+        let metrics = ProviderMetrics::new();
+        let provider = ProviderFactory::builder()
+            .with_logging()
+            .with_metrics_sink(Arc::new(InMemoryMetricsSink::new()))
+            .with_cache()
+            .with_metrics(metrics.clone())
+            .with_rate_limit(6000)
+            .with_retry(2)
+            .build(ChunkedProvider(MockProvider::new()));
+
+        let mut chunks = Vec::new();
+        let data = provider
+            .fetch_data_streaming(&config(), &mut |chunk: &[u8]| chunks.push(chunk.to_vec()))
+            .await
+            .unwrap();
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), data.raw_data.as_bytes());
+        assert_eq!(metrics.snapshot().requests, 1);
+
+        // Cache hits arrive as a single chunk
+        let mut chunks = Vec::new();
+        provider
+            .fetch_data_streaming(&config(), &mut |chunk: &[u8]| chunks.push(chunk.to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), data.raw_data.as_bytes());
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(metrics.snapshot().requests, 1);
+    }
+}
@@ -1,7 +1,15 @@
 use async_trait::async_trait;
-
-use super::{OsmData, OsmDataProvider, ProviderCapabilities};
-use crate::{BoundingBox, OsmConfig, OsmDataFormat, OsmMetadata, OsmTilesError, Region, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{
+    OsmData, OsmDataProvider, OverpassProvider, ProviderCapabilities, SyntheticCity,
+    synthetic::SplitMix64,
+};
+use crate::error::NetworkError;
+use crate::{
+    BoundingBox, OsmConfig, OsmDataFormat, OsmMetadata, OsmTilesError, Region, Result,
+    normalize_name,
+};
 
 /// WASM-compatible mock provider for testing and development
 ///
@@ -12,6 +20,18 @@ pub struct MockProvider {
     mock_data: String,
     /// Whether to simulate failures
     simulate_failure: bool,
+    /// Generator replacing the predefined data with a city in the requested area
+    synthetic: Option<SyntheticCity>,
+    /// Simulated latency of each fetch in milliseconds
+    delay_ms: u64,
+    /// Probability of a fetch failing with a connection error
+    failure_rate: f64,
+    /// Seed deciding which fetches fail intermittently
+    failure_seed: u64,
+    /// HTTP statuses answered by consecutive fetches
+    statuses: Vec<u16>,
+    /// Number of fetches so far
+    calls: AtomicUsize,
 }
 
 impl MockProvider {
@@ -25,6 +45,23 @@ impl MockProvider {
         Self {
             mock_data: data.into(),
             simulate_failure: false,
+            synthetic: None,
+            delay_ms: 0,
+            failure_rate: 0.0,
+            failure_seed: 0,
+            statuses: Vec::new(),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    /// Create a mock provider generating a synthetic city of `density` elements
+    ///
+    /// The city is generated inside the requested bounding box and is the
+    /// same for equal seeds, which makes it suitable for benchmarks.
+    pub fn synthetic(seed: u64, density: usize) -> Self {
+        Self {
+            synthetic: Some(SyntheticCity::new(seed, density)),
+            ..Self::with_data(String::new())
         }
     }
 
@@ -34,6 +71,56 @@ impl MockProvider {
         self
     }
 
+    /// Delay every fetch by `delay_ms` milliseconds without blocking the executor
+    ///
+    /// In browsers the delay is waited out with `setTimeout`, so loading UIs
+    /// can be tested there too.
+    pub fn with_delay(mut self, delay_ms: u64) -> Self {
+        self.delay_ms = delay_ms;
+        self
+    }
+
+    /// Fail fetches with a connection error with probability `rate` (0.0 to 1.0)
+    ///
+    /// Which calls fail is decided by `seed` and the call number, so a test
+    /// sees the same failures on every run.
+    pub fn with_failure_rate(mut self, rate: f64, seed: u64) -> Self {
+        self.failure_rate = rate.clamp(0.0, 1.0);
+        self.failure_seed = seed;
+        self
+    }
+
+    /// Answer consecutive fetches with these HTTP statuses
+    ///
+    /// Non-2xx statuses fail with `NetworkError::HttpError`; fetches beyond
+    /// the end of the sequence succeed.
+    pub fn with_status_sequence(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Get the number of `fetch_data` calls so far
+    pub fn call_count(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Decide the injected error of fetch number `call`, if any
+    fn injected_error(&self, call: usize) -> Option<OsmTilesError> {
+        if let Some(&status) = self.statuses.get(call)
+            && !(200..300).contains(&status)
+        {
+            return Some(NetworkError::HttpError { status }.into());
+        }
+
+        let roll = SplitMix64::new(self.failure_seed ^ call as u64).range(0.0, 1.0);
+        (roll < self.failure_rate).then(|| {
+            NetworkError::Connection {
+                message: format!("Simulated intermittent failure (call {})", call + 1),
+            }
+            .into()
+        })
+    }
+
     /// Get default test data with various OSM features
     fn default_test_data() -> String {
         r#"{
@@ -109,29 +196,54 @@ impl OsmDataProvider for MockProvider {
     }
 
     async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        let call = self.calls.fetch_add(1, Ordering::Relaxed);
+        if self.delay_ms > 0 {
+            crate::http::sleep(self.delay_ms).await;
+        }
+
         // Simulate failure if configured
         if self.simulate_failure {
             return Err(OsmTilesError::Network(crate::NetworkError::Connection {
                 message: "Simulated network failure".to_string(),
             }));
         }
+        if let Some(error) = self.injected_error(call) {
+            tracing::debug!(
+                "Mock provider injecting error on call {}: {}",
+                call + 1,
+                error
+            );
+            return Err(error);
+        }
 
-        let bbox = self.resolve_region(&config.region).await?;
+        let bbox = config.aligned_bounds(self.resolve_region(&config.region).await?);
 
-        let metadata = OsmMetadata::new("mock-provider", self.provider_type())
-            .with_element_count(4) // Matches the default test data
-            .with_processing_time(1)
+        let (raw_data, element_count) = match &self.synthetic {
+            Some(city) => (city.generate(&bbox), city.element_count as u32),
+            None => (self.mock_data.clone(), 4), // Matches the default test data
+        };
+
+        let mut metadata = OsmMetadata::new("mock-provider", self.provider_type())
+            .with_element_count(element_count)
+            .with_processing_time(self.delay_ms.max(1))
             .with_extra("simulated", "true")
             .with_extra("wasm_compatible", "true")
             .with_extra("test_data", "true");
+        if let Some(city) = &self.synthetic {
+            metadata = metadata.with_extra("seed", city.seed.to_string());
+        }
+        // Custom data may carry an Overpass header with the snapshot time
+        if let Some(timestamp) = OverpassProvider::parse_data_timestamp(&raw_data) {
+            metadata = metadata.with_data_timestamp(timestamp);
+        }
 
         tracing::debug!(
             "Mock provider returning {} bytes of test data",
-            self.mock_data.len()
+            raw_data.len()
         );
 
         Ok(OsmData {
-            raw_data: self.mock_data.clone(),
+            raw_data,
             format: OsmDataFormat::Json,
             bounding_box: bbox,
             metadata,
@@ -157,16 +269,16 @@ impl OsmDataProvider for MockProvider {
             }
             Region::City { name } => {
                 // Mock geocoding for common test cities
-                let bbox = match name.to_lowercase().as_str() {
+                let bbox = match normalize_name(name).as_str() {
                     "berlin" => BoundingBox::new(52.3, 13.0, 52.7, 13.8),
-                    "munich" | "münchen" => BoundingBox::new(48.0, 11.3, 48.3, 11.8),
+                    "munich" | "munchen" => BoundingBox::new(48.0, 11.3, 48.3, 11.8),
                     "hamburg" => BoundingBox::new(53.4, 9.7, 53.8, 10.3),
                     "test" | "testcity" | "mock" => BoundingBox::new(52.4, 13.3, 52.6, 13.5),
                     _ => {
-                        return Err(OsmTilesError::Geographic(format!(
-                            "Mock provider doesn't know city: '{}'. Try: berlin, munich, hamburg, or test",
-                            name
-                        )));
+                        return Err(OsmTilesError::GeocodingFailed {
+                            query: name.clone(),
+                            reason: "Mock provider doesn't know this city. Try: berlin, munich, hamburg, or test".to_string(),
+                        });
                     }
                 };
                 Ok(bbox)
@@ -210,7 +322,6 @@ impl Default for MockProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::error::NetworkError;
     use crate::{FeatureSet, OsmConfigBuilder, OsmDataFormat};
 
     #[tokio::test]
@@ -286,10 +397,11 @@ mod tests {
         let result = provider.resolve_region(&unknown_region).await;
         assert!(result.is_err());
 
-        if let Err(OsmTilesError::Geographic(msg)) = result {
-            assert!(msg.contains("Mock provider doesn't know city"));
+        if let Err(OsmTilesError::GeocodingFailed { query, reason }) = result {
+            assert_eq!(query, "unknown_city");
+            assert!(reason.contains("Mock provider doesn't know this city"));
         } else {
-            panic!("Expected Geographic error");
+            panic!("Expected GeocodingFailed error");
         }
     }
 
@@ -357,4 +469,73 @@ mod tests {
         assert!(element_types.contains(&"way"));
         assert!(element_types.contains(&"node"));
     }
+
+    #[tokio::test]
+    async fn test_mock_provider_synthetic_city() {
+        use crate::{DefaultGridGenerator, GridGenerator, OsmParser, TileType};
+
+        let provider = MockProvider::synthetic(7, 300);
+        let config = OsmConfigBuilder::new()
+            .bbox(52.49, 13.39, 52.51, 13.41)
+            .urban_features()
+            .grid_resolution(2000)
+            .build();
+
+        let data = provider.fetch_data(&config).await.unwrap();
+        assert_eq!(data.metadata.element_count, Some(300));
+        assert_eq!(data.metadata.extra.get("seed"), Some(&"7".to_string()));
+        assert_eq!(OsmParser.parse(&data).unwrap().len(), 300);
+
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&data, &config)
+            .await
+            .unwrap();
+        let counts = grid.statistics().tile_type_counts;
+        for tile_type in [TileType::Road, TileType::Building, TileType::GreenSpace] {
+            assert!(counts.contains_key(&tile_type), "missing {:?}", tile_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_latency_and_error_injection() {
+        let config = OsmConfigBuilder::new().city("test").build();
+
+        let provider = MockProvider::new().with_delay(30);
+        let start = std::time::Instant::now();
+        let data = provider.fetch_data(&config).await.unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(30));
+        assert_eq!(data.metadata.processing_time_ms, Some(30));
+
+        let provider = MockProvider::new().with_status_sequence([503, 429, 200]);
+        for expected in [Some(503), Some(429), None, None] {
+            let status = match provider.fetch_data(&config).await {
+                Err(OsmTilesError::Network(NetworkError::HttpError { status })) => Some(status),
+                Err(error) => panic!("unexpected error {}", error),
+                Ok(_) => None,
+            };
+            assert_eq!(status, expected);
+        }
+        assert_eq!(provider.call_count(), 4);
+
+        let provider = MockProvider::new().with_failure_rate(0.5, 3);
+        let mut failures = Vec::new();
+        for _ in 0..40 {
+            failures.push(provider.fetch_data(&config).await.is_err());
+        }
+        let failed = failures.iter().filter(|&&f| f).count();
+        assert!((5..35).contains(&failed), "{} of 40 calls failed", failed);
+
+        // The same seed fails the same calls
+        let replay = MockProvider::new().with_failure_rate(0.5, 3);
+        for &expected in &failures {
+            assert_eq!(replay.fetch_data(&config).await.is_err(), expected);
+        }
+        assert!(
+            MockProvider::new()
+                .with_failure_rate(1.0, 0)
+                .fetch_data(&config)
+                .await
+                .is_err()
+        );
+    }
 }
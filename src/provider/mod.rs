@@ -1,14 +1,40 @@
+mod adiff_decoder;
+mod composite;
+mod geocoder;
+#[cfg(test)]
 mod integration_tests;
+mod layers;
 mod mock;
+mod mvt;
+mod mvt_decoder;
 mod overpass;
-
+mod registry;
+mod retry;
+mod shapefile;
+mod shapefile_decoder;
+mod synthetic;
+
+pub use adiff_decoder::*;
+pub use composite::*;
+pub use geocoder::*;
+pub use layers::*;
 pub use mock::*;
+pub use mvt::*;
+pub use mvt_decoder::*;
 pub use overpass::*;
+pub use registry::*;
+pub use retry::*;
+pub use shapefile::*;
+pub use shapefile_decoder::*;
+pub use synthetic::*;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
+use crate::http::TransferStats;
 use crate::{BoundingBox, OsmConfig, Region, Result};
 
 /// Raw OSM data response from a provider
@@ -48,6 +74,9 @@ pub struct OsmMetadata {
     pub processing_time_ms: Option<u64>,
     /// Additional metadata from the API/source
     pub extra: HashMap<String, String>,
+    /// Time of the OSM database snapshot the data was read from, if the source reports it
+    #[serde(default)]
+    pub data_timestamp: Option<DateTime<Utc>>,
 }
 
 impl OsmMetadata {
@@ -60,6 +89,7 @@ impl OsmMetadata {
             element_count: None,
             processing_time_ms: None,
             extra: HashMap::new(),
+            data_timestamp: None,
         }
     }
 
@@ -75,11 +105,46 @@ impl OsmMetadata {
         self
     }
 
+    /// Set the time of the OSM database snapshot
+    pub fn with_data_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.data_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Get how old the data is, `None` if the snapshot time is unknown
+    pub fn data_age(&self) -> Option<Duration> {
+        data_age(self.data_timestamp)
+    }
+
+    /// Check if the data is older than `max_age` (data of unknown age is never stale)
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.data_age().is_some_and(|age| age > max_age)
+    }
+
     /// Add extra metadata
     pub fn with_extra(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.extra.insert(key.into(), value.into());
         self
     }
+
+    /// Record how many bytes were transferred and how well they compressed
+    pub fn with_transfer_stats(self, stats: &TransferStats) -> Self {
+        self.with_extra(
+            "content_encoding",
+            stats.content_encoding.as_deref().unwrap_or("identity"),
+        )
+        .with_extra("wire_bytes", stats.wire_bytes.to_string())
+        .with_extra("decompressed_bytes", stats.body_bytes.to_string())
+        .with_extra(
+            "compression_ratio",
+            format!("{:.2}", stats.compression_ratio()),
+        )
+    }
+}
+
+/// Get the age of a database snapshot (zero for snapshots ahead of the local clock)
+pub(crate) fn data_age(timestamp: Option<DateTime<Utc>>) -> Option<Duration> {
+    timestamp.map(|timestamp| (Utc::now() - timestamp).to_std().unwrap_or_default())
 }
 
 /// Trait for providing OpenStreetMap data from various WASM-compatible sources
@@ -100,12 +165,41 @@ pub trait OsmDataProvider: Send + Sync {
     /// - Return structured OSM data with proper metadata
     async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData>;
 
+    /// Fetch OSM data, passing the raw response to `on_chunk` while it downloads
+    ///
+    /// The chunks received since the last [`ChunkSink::restart`] add up to
+    /// the returned `raw_data`. Providers that cannot stream pass all of it
+    /// as one chunk once it is fetched, which is what the default
+    /// implementation does; wrappers forward the sink to the provider they
+    /// wrap.
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        let data = self.fetch_data(config).await?;
+        on_chunk.chunk(data.raw_data.as_bytes());
+        Ok(data)
+    }
+
     /// Resolve a region to a concrete bounding box
     ///
     /// For city names, this typically involves geocoding.
     /// For other region types, this may involve coordinate transformation.
     async fn resolve_region(&self, region: &Region) -> Result<BoundingBox>;
 
+    /// Resolve a region to every place it may refer to, best match first
+    ///
+    /// City names like "Frankfurt" can name several places; UIs can offer
+    /// the candidates and load the chosen one as a
+    /// [`Region::BoundingBox`]. The first candidate is the extent
+    /// [`resolve_region`](Self::resolve_region) returns, which is the only
+    /// one the default implementation reports.
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        let bounding_box = self.resolve_region(region).await?;
+        Ok(vec![region_candidate(region, bounding_box)])
+    }
+
     /// Test connectivity/availability of the data source
     ///
     /// This might ping an API, check cache status, or validate configuration
@@ -115,6 +209,42 @@ pub trait OsmDataProvider: Send + Sync {
     fn capabilities(&self) -> ProviderCapabilities;
 }
 
+/// Receiver of a raw response while it downloads, see [`OsmDataProvider::fetch_data_streaming`]
+///
+/// Closures taking a byte slice are sinks that ignore restarts.
+pub trait ChunkSink: Send {
+    /// Receive the next part of the response
+    fn chunk(&mut self, chunk: &[u8]);
+
+    /// Drop everything received so far, as the download starts over
+    ///
+    /// Called before an attempt that follows a failed one which already
+    /// delivered chunks, e.g. by [`RetryingProvider`].
+    fn restart(&mut self) {}
+}
+
+impl<F: for<'c> FnMut(&'c [u8]) + Send> ChunkSink for F {
+    fn chunk(&mut self, chunk: &[u8]) {
+        self(chunk)
+    }
+}
+
+/// Describe a resolved region as its only candidate
+pub(crate) fn region_candidate(region: &Region, bounding_box: BoundingBox) -> GeocodeCandidate {
+    let display_name = match region {
+        Region::City { name } => name.trim().to_string(),
+        _ => format!(
+            "{:.4},{:.4},{:.4},{:.4}",
+            bounding_box.south, bounding_box.west, bounding_box.north, bounding_box.east
+        ),
+    };
+    GeocodeCandidate {
+        display_name,
+        country: None,
+        bounding_box,
+    }
+}
+
 /// Describes the capabilities and limitations of a data provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderCapabilities {
@@ -165,6 +295,30 @@ impl ProviderFactory {
         OverpassProvider::with_base_url(url)
     }
 
+    /// Create a Mapbox Vector Tile provider for a `{z}/{x}/{y}` URL template
+    pub fn mvt(url_template: impl Into<String>) -> MvtProvider {
+        MvtProvider::new(url_template)
+    }
+
+    /// Create a provider reading an in-memory shapefile (`.shp` and optional `.dbf` contents)
+    pub fn shapefile(
+        name: impl Into<String>,
+        shp: Vec<u8>,
+        dbf: Option<Vec<u8>>,
+    ) -> ShapefileProvider {
+        ShapefileProvider::from_bytes(name, shp, dbf)
+    }
+
+    /// Create a provider combining the elements of several sources
+    pub fn composite() -> CompositeProvider {
+        CompositeProvider::new()
+    }
+
+    /// Start stacking layers (caching, retries, rate limiting, ...) around a provider
+    pub fn builder() -> ProviderBuilder {
+        ProviderBuilder::new()
+    }
+
     /// Create a mock provider for testing
     pub fn mock() -> MockProvider {
         MockProvider::new()
@@ -175,22 +329,19 @@ impl ProviderFactory {
         MockProvider::with_data(data)
     }
 
-    /// Get a list of all available provider types
-    pub fn available_providers() -> Vec<&'static str> {
-        vec!["overpass", "mock"]
+    /// Create a mock provider answering after `delay_ms` milliseconds
+    pub fn mock_with_delay(delay_ms: u64) -> MockProvider {
+        MockProvider::new().with_delay(delay_ms)
     }
 
-    /// Create a provider by name with default settings
+    /// Get the names of all registered providers, see [`ProviderRegistry`]
+    pub fn available_providers() -> Vec<String> {
+        ProviderRegistry::names()
+    }
+
+    /// Create a registered provider by name with default settings
     pub fn create_provider(name: &str) -> Result<Box<dyn OsmDataProvider>> {
-        match name {
-            "overpass" => Ok(Box::new(Self::overpass())),
-            "mock" => Ok(Box::new(Self::mock())),
-            _ => Err(crate::OsmTilesError::Config(format!(
-                "Unknown provider: '{}'. Available providers: {:?}",
-                name,
-                Self::available_providers()
-            ))),
-        }
+        ProviderRegistry::create(name)
     }
 }
 
@@ -225,6 +376,30 @@ mod tests {
         assert_eq!(metadata.extra.get("key2"), Some(&"value2".to_string()));
     }
 
+    #[test]
+    fn test_osm_metadata_transfer_stats() {
+        let stats = TransferStats {
+            content_encoding: Some("gzip".to_string()),
+            wire_bytes: 1000,
+            body_bytes: 8000,
+        };
+        let metadata = OsmMetadata::new("source", "provider").with_transfer_stats(&stats);
+
+        assert_eq!(
+            metadata.extra.get("content_encoding"),
+            Some(&"gzip".to_string())
+        );
+        assert_eq!(metadata.extra.get("wire_bytes"), Some(&"1000".to_string()));
+        assert_eq!(
+            metadata.extra.get("decompressed_bytes"),
+            Some(&"8000".to_string())
+        );
+        assert_eq!(
+            metadata.extra.get("compression_ratio"),
+            Some(&"8.00".to_string())
+        );
+    }
+
     #[test]
     fn test_provider_capabilities_default() {
         let capabilities = ProviderCapabilities::default();
@@ -241,17 +416,20 @@ mod tests {
 
     #[test]
     fn test_provider_factory_available_providers() {
+        // Built-in providers come first; other tests may register more concurrently
         let providers = ProviderFactory::available_providers();
-        assert_eq!(providers, vec!["overpass", "mock"]);
+        assert_eq!(providers[..2], ["overpass", "mock"]);
     }
 
     #[test]
+    #[cfg(any(feature = "reqwest-client", feature = "ehttp-client"))]
     fn test_provider_factory_create_overpass() {
         let provider = ProviderFactory::overpass();
         assert_eq!(provider.provider_type(), "overpass");
     }
 
     #[test]
+    #[cfg(any(feature = "reqwest-client", feature = "ehttp-client"))]
     fn test_provider_factory_create_overpass_with_url() {
         let custom_url = "https://custom.overpass.api/interpreter";
         let provider = ProviderFactory::overpass_with_url(custom_url);
@@ -275,9 +453,12 @@ mod tests {
 
     #[test]
     fn test_provider_factory_create_provider_by_name() {
-        // Test valid provider names
-        let overpass = ProviderFactory::create_provider("overpass").unwrap();
-        assert_eq!(overpass.provider_type(), "overpass");
+        // Test valid provider names, Overpass needs an HTTP client
+        #[cfg(any(feature = "reqwest-client", feature = "ehttp-client"))]
+        {
+            let overpass = ProviderFactory::create_provider("overpass").unwrap();
+            assert_eq!(overpass.provider_type(), "overpass");
+        }
 
         let mock = ProviderFactory::create_provider("mock").unwrap();
         assert_eq!(mock.provider_type(), "mock");
@@ -0,0 +1,673 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value, json};
+use std::f64::consts::PI;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+use tracing::field::Empty;
+
+use super::mvt_decoder::{MvtFeature, MvtGeomType, MvtLayer, decode_vector_tile, ring_area};
+use super::{
+    GeocodeCandidate, Geocoder, GeocoderChain, OsmData, OsmDataProvider, ProviderCapabilities,
+    region_candidate,
+};
+use crate::http::{HttpClient, LoggingHttpClient, QueryLogger};
+use crate::{
+    BoundingBox, NetworkError, OsmConfig, OsmDataFormat, OsmMetadata, OsmTilesError, Region,
+    Result, SlippyTile,
+};
+
+/// Provider fetching Mapbox Vector Tiles from an XYZ tile server
+///
+/// Every tile covering the requested region is downloaded and decoded, and the
+/// road, building, water and landuse layers are translated into OSM tags. The
+/// result is emitted as Overpass-style JSON so the regular parser and grid
+/// generator can consume it unchanged. Layer names of the OpenMapTiles and
+/// Mapbox Streets schemas are recognized.
+pub struct MvtProvider {
+    /// Tile URL template with `{z}`, `{x}` and `{y}` placeholders
+    pub url_template: String,
+    /// Zoom level at which tiles are fetched
+    pub zoom: u8,
+    /// Maximum number of tiles a single request may download
+    pub max_tiles: usize,
+    http_client: Arc<dyn HttpClient>,
+    /// Geocoder resolving city names, as tile servers have no geocoding
+    geocoder: Arc<dyn Geocoder>,
+    /// Whether the geocoder was set with `with_geocoder` rather than built from the HTTP client
+    custom_geocoder: bool,
+}
+
+impl MvtProvider {
+    /// Create a provider for a tile server URL template like `https://host/{z}/{x}/{y}.pbf`
+    pub fn new(url_template: impl Into<String>) -> Self {
+        let http_client = crate::http::create_default_client()
+            .expect("Failed to create HTTP client - check that either 'reqwest-client' or 'ehttp-client' feature is enabled");
+
+        Self::with_http_client(url_template, http_client)
+    }
+
+    /// Create a provider with a custom HTTP client
+    pub fn with_http_client(
+        url_template: impl Into<String>,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Self {
+        Self {
+            url_template: url_template.into(),
+            zoom: 14,
+            max_tiles: 64,
+            geocoder: Arc::new(GeocoderChain::with_defaults(http_client.clone())),
+            custom_geocoder: false,
+            http_client,
+        }
+    }
+
+    /// Set the zoom level at which tiles are fetched
+    pub fn with_zoom(mut self, zoom: u8) -> Self {
+        self.zoom = zoom.min(crate::MAX_SLIPPY_ZOOM);
+        self
+    }
+
+    /// Set the maximum number of tiles a single request may download
+    pub fn with_max_tiles(mut self, max_tiles: usize) -> Self {
+        self.max_tiles = max_tiles;
+        self
+    }
+
+    /// Log every tile request through `logger`, redacting access tokens in the URLs
+    pub fn with_query_log(mut self, logger: QueryLogger) -> Self {
+        self.http_client = Arc::new(LoggingHttpClient::new(self.http_client, logger));
+        if !self.custom_geocoder {
+            self.geocoder = Arc::new(GeocoderChain::with_defaults(self.http_client.clone()));
+        }
+        self
+    }
+
+    /// Resolve city names with a custom geocoder instead of the gazetteer and Nominatim
+    pub fn with_geocoder(mut self, geocoder: impl Geocoder + 'static) -> Self {
+        self.geocoder = Arc::new(geocoder);
+        self.custom_geocoder = true;
+        self
+    }
+
+    /// Build the URL of a single tile
+    pub fn tile_url(&self, tile: &SlippyTile) -> String {
+        self.url_template
+            .replace("{z}", &tile.z.to_string())
+            .replace("{x}", &tile.x.to_string())
+            .replace("{y}", &tile.y.to_string())
+    }
+
+    /// Download and decode one tile; servers answer empty tiles with 204 or 404
+    async fn fetch_tile(&self, tile: &SlippyTile) -> Result<Vec<MvtLayer>> {
+        let url = self.tile_url(tile);
+        let response = self
+            .http_client
+            .get_bytes(&url)
+            .await
+            .map_err(NetworkError::from)?;
+
+        match response.status {
+            200 => decode_vector_tile(&response.body),
+            204 | 404 => Ok(Vec::new()),
+            status => Err(OsmTilesError::http_status(status, response.retry_after())),
+        }
+    }
+
+    /// Convert the layers of one tile into Overpass JSON elements
+    fn tile_elements(tile: &SlippyTile, layers: &[MvtLayer], next_id: &mut i64) -> Vec<Value> {
+        let mut elements = Vec::new();
+
+        for layer in layers {
+            for feature in &layer.features {
+                let Some(tags) = Self::feature_tags(&layer.name, feature) else {
+                    continue;
+                };
+
+                let to_geo = |(x, y): (i32, i32)| tile_to_geo(tile, layer.extent, x, y);
+                let to_geometry = |part: &[(i32, i32)]| -> Vec<Value> {
+                    part.iter()
+                        .map(|point| {
+                            let (lat, lon) = to_geo(*point);
+                            json!({"lat": lat, "lon": lon})
+                        })
+                        .collect()
+                };
+
+                // Polygons with holes become multipolygon relations, whose
+                // inner rings the parser assigns to the outer ring containing them
+                if feature.geom_type == MvtGeomType::Polygon
+                    && Self::feature_parts(feature).any(|ring| ring_area(ring) < 0.0)
+                {
+                    let members: Vec<Value> = Self::feature_parts(feature)
+                        .map(|ring| {
+                            let role = if ring_area(ring) > 0.0 {
+                                "outer"
+                            } else {
+                                "inner"
+                            };
+                            json!({"type": "way", "role": role, "geometry": to_geometry(ring)})
+                        })
+                        .collect();
+                    let mut tags = tags;
+                    tags.insert("type".to_string(), json!("multipolygon"));
+                    *next_id += 1;
+                    elements.push(
+                        json!({"type": "relation", "id": *next_id, "members": members, "tags": tags}),
+                    );
+                    continue;
+                }
+
+                for part in Self::feature_parts(feature) {
+                    *next_id += 1;
+
+                    let element = if feature.geom_type == MvtGeomType::Point {
+                        let (lat, lon) = to_geo(part[0]);
+                        json!({"type": "node", "id": *next_id, "lat": lat, "lon": lon, "tags": tags})
+                    } else {
+                        let geometry = to_geometry(part);
+                        json!({"type": "way", "id": *next_id, "geometry": geometry, "tags": tags})
+                    };
+                    elements.push(element);
+                }
+            }
+        }
+
+        elements
+    }
+
+    /// Get the geometry parts of a feature, with the holes of polygons as rings of negative area
+    fn feature_parts(feature: &MvtFeature) -> impl Iterator<Item = &Vec<(i32, i32)>> {
+        feature
+            .parts
+            .iter()
+            .filter(move |part| match feature.geom_type {
+                MvtGeomType::Point => !part.is_empty(),
+                MvtGeomType::LineString => part.len() >= 2,
+                MvtGeomType::Polygon => part.len() >= 4 && ring_area(part) != 0.0,
+                MvtGeomType::Unknown => false,
+            })
+    }
+
+    /// Translate a feature of a known layer into OSM tags
+    fn feature_tags(layer: &str, feature: &MvtFeature) -> Option<Map<String, Value>> {
+        let class = feature
+            .properties
+            .get("class")
+            .map(|value| value.to_tag_string());
+        let class_or = |default: &str| class.clone().unwrap_or_else(|| default.to_string());
+
+        let (key, value) = match layer {
+            // Rail and ferry lines share the layer with roads
+            "transportation" | "road" | "roads" => match class.as_deref() {
+                Some("rail" | "transit") => {
+                    let subclass = feature
+                        .properties
+                        .get("subclass")
+                        .map(|value| value.to_tag_string());
+                    ("railway", subclass.unwrap_or_else(|| "rail".to_string()))
+                }
+                Some("major_rail" | "minor_rail" | "service_rail") => {
+                    ("railway", "rail".to_string())
+                }
+                Some("ferry") => ("route", "ferry".to_string()),
+                _ => ("highway", class_or("road")),
+            },
+            "building" | "buildings" => ("building", "yes".to_string()),
+            "water" => ("natural", "water".to_string()),
+            "waterway" => ("waterway", class_or("river")),
+            "park" => ("leisure", "park".to_string()),
+            "landuse" | "landcover" => match class.as_deref() {
+                Some("park" | "garden") => ("leisure", class_or("park")),
+                Some("wood" | "forest") => ("natural", "wood".to_string()),
+                Some(_) => ("landuse", class_or("")),
+                None => return None,
+            },
+            _ => return None,
+        };
+
+        let mut tags: Map<String, Value> = feature
+            .properties
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.to_tag_string())))
+            .collect();
+        tags.insert(key.to_string(), Value::String(value));
+        Some(tags)
+    }
+}
+
+/// Convert tile-local coordinates to (lat, lon)
+fn tile_to_geo(tile: &SlippyTile, extent: u32, x: i32, y: i32) -> (f64, f64) {
+    let n = (1u64 << tile.z) as f64;
+    let extent = extent.max(1) as f64;
+    let fx = tile.x as f64 + x as f64 / extent;
+    let fy = tile.y as f64 + y as f64 / extent;
+
+    let lon = fx / n * 360.0 - 180.0;
+    let lat = (PI * (1.0 - 2.0 * fy / n)).sinh().atan().to_degrees();
+    (lat, lon)
+}
+
+#[async_trait]
+impl OsmDataProvider for MvtProvider {
+    fn provider_type(&self) -> &'static str {
+        "mvt"
+    }
+
+    #[tracing::instrument(
+        name = "fetch_data",
+        skip_all,
+        fields(provider = "mvt", region = ?config.region, element_count = Empty, tiles = Empty)
+    )]
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_time = Instant::now();
+
+        let bbox = config.aligned_bounds(self.resolve_region(&config.region).await?);
+        let tiles = SlippyTile::covering(&bbox, self.zoom);
+        if tiles.len() > self.max_tiles {
+            return Err(OsmTilesError::Config(format!(
+                "Region needs {} vector tiles at zoom {}, limit is {}. Use a lower zoom or a smaller region",
+                tiles.len(),
+                self.zoom,
+                self.max_tiles
+            )));
+        }
+
+        tracing::Span::current().record("tiles", tiles.len());
+        tracing::debug!(zoom = self.zoom, url = %self.url_template, "Fetching vector tiles");
+
+        let mut elements = Vec::new();
+        let mut next_id = 0;
+        for tile in &tiles {
+            let layers = self.fetch_tile(tile).await?;
+            elements.extend(Self::tile_elements(tile, &layers, &mut next_id));
+        }
+
+        let element_count = elements.len() as u32;
+        let raw_data = json!({ "elements": elements }).to_string();
+
+        let processing_time = {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                start_time.elapsed().as_millis() as u64
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                1u64 // Default value for WASM
+            }
+        };
+
+        let metadata = OsmMetadata::new(&self.url_template, self.provider_type())
+            .with_element_count(element_count)
+            .with_processing_time(processing_time)
+            .with_extra("tile_count", tiles.len().to_string())
+            .with_extra("zoom", self.zoom.to_string())
+            .with_extra(
+                "bbox",
+                format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east),
+            );
+
+        tracing::Span::current().record("element_count", element_count);
+        tracing::info!("Decoded vector tiles");
+
+        Ok(OsmData {
+            raw_data,
+            format: OsmDataFormat::Json,
+            bounding_box: bbox,
+            metadata,
+        })
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        match region {
+            Region::City { name } => self.geocoder.geocode(name).await,
+            _ => region.bounding_box().ok_or_else(|| {
+                OsmTilesError::Config(format!("Region {:?} has no bounding box", region))
+            }),
+        }
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        match region {
+            Region::City { name } => self.geocoder.geocode_candidates(name).await,
+            _ => Ok(vec![region_candidate(
+                region,
+                self.resolve_region(region).await?,
+            )]),
+        }
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        self.fetch_tile(&SlippyTile::new(0, 0, 0)).await.map(|_| ())
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supports_real_time: false,
+            requires_network: true,
+            supports_geocoding: true,
+            max_area_km2: None,
+            supported_formats: vec![OsmDataFormat::Json],
+            rate_limit_rpm: None,
+            wasm_compatible: true,
+            notes: Some(format!(
+                "Mapbox Vector Tiles at zoom {}, up to {} tiles per request",
+                self.zoom, self.max_tiles
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mvt_decoder::encode::*;
+    use super::*;
+    use crate::Gazetteer;
+    use crate::http::{HttpBytesResponse, HttpError, HttpResponse, HttpResult};
+    use crate::{OsmConfigBuilder, OsmElement, OsmParser, TileType};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// HTTP client serving the same vector tile for every request
+    struct TileServer {
+        tile: Vec<u8>,
+        requests: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for TileServer {
+        async fn get(&self, _url: &str) -> HttpResult<HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn get_bytes(&self, url: &str) -> HttpResult<HttpBytesResponse> {
+            self.requests.lock().unwrap().push(url.to_string());
+            Ok(HttpBytesResponse {
+                status: 200,
+                body: self.tile.clone(),
+                headers: HashMap::new(),
+                transfer: None,
+            })
+        }
+
+        async fn post_form(&self, _url: &str, _form: &[(&str, &str)]) -> HttpResult<HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn post_json(&self, _url: &str, _json: &str) -> HttpResult<HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn test_connectivity(&self, _url: &str) -> HttpResult<()> {
+            Ok(())
+        }
+    }
+
+    fn test_tile() -> Vec<u8> {
+        let road = vec![
+            command(1, 1),
+            zigzag(0),
+            zigzag(2048),
+            command(2, 1),
+            zigzag(4096),
+            zigzag(0),
+        ];
+        let building = vec![
+            command(1, 1),
+            zigzag(100),
+            zigzag(100),
+            command(2, 3),
+            zigzag(200),
+            zigzag(0),
+            zigzag(0),
+            zigzag(200),
+            zigzag(-200),
+            zigzag(0),
+            command(7, 1),
+        ];
+
+        let mut tile = layer("transportation", &[(2, &[("class", "primary")], road)]);
+        tile.extend(layer("building", &[(3, &[], building)]));
+        tile.extend(layer(
+            "poi",
+            &[(1, &[("class", "cafe")], vec![command(1, 1), 0, 0])],
+        ));
+        tile
+    }
+
+    fn test_provider() -> (MvtProvider, Arc<TileServer>) {
+        let server = Arc::new(TileServer {
+            tile: test_tile(),
+            requests: Mutex::new(Vec::new()),
+        });
+        let provider = MvtProvider::with_http_client(
+            "https://tiles.example.com/{z}/{x}/{y}.pbf",
+            server.clone(),
+        );
+        (provider, server)
+    }
+
+    #[test]
+    fn test_tile_url() {
+        let (provider, _) = test_provider();
+        assert_eq!(
+            provider.tile_url(&SlippyTile::new(14, 8800, 5373)),
+            "https://tiles.example.com/14/8800/5373.pbf"
+        );
+    }
+
+    #[test]
+    fn test_tile_to_geo_matches_tile_bounds() {
+        let tile = SlippyTile::new(14, 8800, 5373);
+        let bounds = tile.bounds();
+
+        let (north, west) = tile_to_geo(&tile, 4096, 0, 0);
+        let (south, east) = tile_to_geo(&tile, 4096, 4096, 4096);
+        assert!((north - bounds.north).abs() < 1e-9);
+        assert!((west - bounds.west).abs() < 1e-9);
+        assert!((south - bounds.south).abs() < 1e-9);
+        assert!((east - bounds.east).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_converts_layers_to_osm_elements() {
+        let (provider, server) = test_provider();
+        let tile = SlippyTile::new(14, 8802, 5373);
+        let bounds = tile.bounds();
+        let inner = BoundingBox::new(
+            bounds.south + 1e-4,
+            bounds.west + 1e-4,
+            bounds.north - 1e-4,
+            bounds.east - 1e-4,
+        );
+        let config = OsmConfigBuilder::new()
+            .region(Region::BoundingBox(inner))
+            .build();
+
+        let data = provider.fetch_data(&config).await.unwrap();
+        assert_eq!(server.requests.lock().unwrap().len(), 1);
+        assert_eq!(data.metadata.provider_type, "mvt");
+        assert_eq!(
+            data.metadata.extra.get("tile_count"),
+            Some(&"1".to_string())
+        );
+        // The unknown "poi" layer is skipped
+        assert_eq!(data.metadata.element_count, Some(2));
+
+        let elements = OsmParser.parse(&data).unwrap();
+        let road = elements
+            .iter()
+            .find(|e| e.tags.get("highway") == Some(&"primary".to_string()))
+            .unwrap();
+        assert_eq!(road.to_tile_type(), TileType::Road);
+        assert_eq!(road.geometry.len(), 2);
+        assert!((road.geometry[0].1 - bounds.west).abs() < 1e-9);
+
+        let building = elements
+            .iter()
+            .find(|e| e.tags.contains_key("building"))
+            .unwrap();
+        assert_eq!(building.to_tile_type(), TileType::Building);
+        assert_eq!(building.geometry.len(), 5);
+        assert!(bounds.contains(building.geometry[0].0, building.geometry[0].1));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_too_many_tiles() {
+        let (provider, server) = test_provider();
+        let provider = provider.with_max_tiles(2);
+        let config = OsmConfigBuilder::new().bbox(52.4, 13.3, 52.6, 13.5).build();
+
+        let result = provider.fetch_data(&config).await;
+        assert!(matches!(result, Err(OsmTilesError::Config(_))));
+        assert!(server.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolves_cities_with_its_geocoder() {
+        let (provider, server) = test_provider();
+        // The embedded gazetteer answers without a request to the tile server
+        let berlin = provider
+            .resolve_region(&Region::city("Berlin"))
+            .await
+            .unwrap();
+        assert!(berlin.contains(52.52, 13.40));
+        let candidates = provider
+            .resolve_region_candidates(&Region::city("Frankfurt"))
+            .await
+            .unwrap();
+        assert!(candidates.len() > 1);
+        assert!(server.requests.lock().unwrap().is_empty());
+
+        let provider = provider.with_geocoder(Gazetteer::new());
+        assert!(
+            provider
+                .resolve_region(&Region::city("Berlin"))
+                .await
+                .is_err()
+        );
+        let area = provider
+            .resolve_region(&Region::center_radius(52.5, 13.4, 1.0))
+            .await
+            .unwrap();
+        assert!(area.contains(52.5, 13.4));
+    }
+
+    #[test]
+    fn test_polygon_holes_are_kept() {
+        // A square courtyard drawn against the winding of the outer ring
+        let building = vec![
+            command(1, 1),
+            zigzag(100),
+            zigzag(100),
+            command(2, 3),
+            zigzag(200),
+            zigzag(0),
+            zigzag(0),
+            zigzag(200),
+            zigzag(-200),
+            zigzag(0),
+            command(7, 1),
+            command(1, 1),
+            zigzag(50),
+            zigzag(-150),
+            command(2, 3),
+            zigzag(0),
+            zigzag(100),
+            zigzag(100),
+            zigzag(0),
+            zigzag(0),
+            zigzag(-100),
+            command(7, 1),
+        ];
+        let layers = decode_vector_tile(&layer("building", &[(3, &[], building)])).unwrap();
+        let tile = SlippyTile::new(14, 8802, 5373);
+
+        let mut next_id = 0;
+        let json = MvtProvider::tile_elements(&tile, &layers, &mut next_id);
+        assert_eq!(json.len(), 1);
+        let elements = OsmParser.parse_json_element(&json[0]).unwrap();
+        assert_eq!(elements.len(), 1);
+        let building = &elements[0];
+        assert_eq!(building.to_tile_type(), TileType::Building);
+        assert_eq!(building.holes.len(), 1);
+
+        let (lat, lon) = tile_to_geo(&tile, 4096, 200, 200);
+        assert!(!building.contains(lat, lon));
+        let (lat, lon) = tile_to_geo(&tile, 4096, 125, 200);
+        assert!(building.contains(lat, lon));
+    }
+
+    #[test]
+    fn test_landuse_classes() {
+        let feature = |class: &str| MvtFeature {
+            id: None,
+            geom_type: MvtGeomType::Polygon,
+            properties: HashMap::from([(
+                "class".to_string(),
+                super::super::mvt_decoder::MvtValue::String(class.to_string()),
+            )]),
+            parts: Vec::new(),
+        };
+
+        let tags = MvtProvider::feature_tags("landcover", &feature("wood")).unwrap();
+        assert_eq!(tags["natural"], "wood");
+        let tags = MvtProvider::feature_tags("landuse", &feature("park")).unwrap();
+        assert_eq!(tags["leisure"], "park");
+        let tags = MvtProvider::feature_tags("landuse", &feature("residential")).unwrap();
+        assert_eq!(tags["landuse"], "residential");
+        assert!(MvtProvider::feature_tags("housenumber", &feature("x")).is_none());
+    }
+
+    #[test]
+    fn test_transportation_classes() {
+        let feature = |properties: &[(&str, &str)]| MvtFeature {
+            id: None,
+            geom_type: MvtGeomType::LineString,
+            properties: properties
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        key.to_string(),
+                        super::super::mvt_decoder::MvtValue::String(value.to_string()),
+                    )
+                })
+                .collect(),
+            parts: Vec::new(),
+        };
+        let element = |tags: Map<String, Value>| OsmElement {
+            id: 1,
+            element_type: crate::OsmElementType::Way,
+            tags: tags
+                .into_iter()
+                .map(|(k, v)| (k, v.as_str().unwrap().to_string()))
+                .collect(),
+            geometry: Vec::new(),
+            holes: Vec::new(),
+        };
+
+        let tags = MvtProvider::feature_tags("transportation", &feature(&[("class", "primary")]));
+        assert_eq!(tags.as_ref().unwrap()["highway"], "primary");
+        assert_eq!(element(tags.unwrap()).to_tile_type(), TileType::Road);
+
+        let tags = MvtProvider::feature_tags(
+            "transportation",
+            &feature(&[("class", "transit"), ("subclass", "tram")]),
+        )
+        .unwrap();
+        assert_eq!(tags["railway"], "tram");
+        assert!(!tags.contains_key("highway"));
+        assert_eq!(element(tags).to_tile_type(), TileType::Railway);
+        let tags =
+            MvtProvider::feature_tags("transportation", &feature(&[("class", "rail")])).unwrap();
+        assert_eq!(tags["railway"], "rail");
+        let tags = MvtProvider::feature_tags("road", &feature(&[("class", "major_rail")])).unwrap();
+        assert_eq!(tags["railway"], "rail");
+
+        let tags =
+            MvtProvider::feature_tags("transportation", &feature(&[("class", "ferry")])).unwrap();
+        assert_eq!(tags["route"], "ferry");
+        assert_ne!(element(tags).to_tile_type(), TileType::Road);
+    }
+}
@@ -0,0 +1,486 @@
+//! Minimal decoder for Mapbox Vector Tiles (MVT 2.x protobuf encoding)
+
+use std::collections::HashMap;
+
+use crate::{OsmTilesError, Result};
+
+/// Geometry type of a vector tile feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MvtGeomType {
+    Unknown,
+    Point,
+    LineString,
+    Polygon,
+}
+
+/// A property value attached to a vector tile feature
+#[derive(Debug, Clone, PartialEq)]
+pub enum MvtValue {
+    String(String),
+    Float(f32),
+    Double(f64),
+    Int(i64),
+    Uint(u64),
+    Bool(bool),
+}
+
+impl MvtValue {
+    /// Render the value as an OSM-style tag string
+    pub fn to_tag_string(&self) -> String {
+        match self {
+            Self::String(value) => value.clone(),
+            Self::Float(value) => value.to_string(),
+            Self::Double(value) => value.to_string(),
+            Self::Int(value) => value.to_string(),
+            Self::Uint(value) => value.to_string(),
+            Self::Bool(value) => if *value { "yes" } else { "no" }.to_string(),
+        }
+    }
+}
+
+/// A decoded vector tile feature with geometry in tile-local coordinates
+#[derive(Debug, Clone)]
+pub struct MvtFeature {
+    /// Feature identifier, if the tile provides one
+    pub id: Option<u64>,
+    /// Geometry type
+    pub geom_type: MvtGeomType,
+    /// Feature properties
+    pub properties: HashMap<String, MvtValue>,
+    /// Geometry parts (points, lines or rings) in tile coordinates, y pointing down
+    pub parts: Vec<Vec<(i32, i32)>>,
+}
+
+/// A decoded vector tile layer
+#[derive(Debug, Clone)]
+pub struct MvtLayer {
+    /// Layer name (e.g., "transportation", "building")
+    pub name: String,
+    /// Size of the tile coordinate space
+    pub extent: u32,
+    /// Features in this layer
+    pub features: Vec<MvtFeature>,
+}
+
+/// Decode a vector tile into its layers
+pub fn decode_vector_tile(data: &[u8]) -> Result<Vec<MvtLayer>> {
+    let mut reader = ProtoReader::new(data);
+    let mut layers = Vec::new();
+
+    while let Some((field, wire_type)) = reader.read_key()? {
+        match (field, wire_type) {
+            (3, WIRE_LEN) => layers.push(decode_layer(reader.read_bytes()?)?),
+            _ => reader.skip(wire_type)?,
+        }
+    }
+
+    Ok(layers)
+}
+
+fn decode_layer(data: &[u8]) -> Result<MvtLayer> {
+    let mut reader = ProtoReader::new(data);
+    let mut name = String::new();
+    let mut extent = 4096;
+    let mut keys = Vec::new();
+    let mut values = Vec::new();
+    let mut raw_features = Vec::new();
+
+    while let Some((field, wire_type)) = reader.read_key()? {
+        match (field, wire_type) {
+            (1, WIRE_LEN) => name = reader.read_string()?,
+            (2, WIRE_LEN) => raw_features.push(reader.read_bytes()?),
+            (3, WIRE_LEN) => keys.push(reader.read_string()?),
+            (4, WIRE_LEN) => values.push(decode_value(reader.read_bytes()?)?),
+            (5, WIRE_VARINT) => extent = reader.read_varint()? as u32,
+            _ => reader.skip(wire_type)?,
+        }
+    }
+
+    let features = raw_features
+        .into_iter()
+        .map(|data| decode_feature(data, &keys, &values))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(MvtLayer {
+        name,
+        extent,
+        features,
+    })
+}
+
+fn decode_value(data: &[u8]) -> Result<MvtValue> {
+    let mut reader = ProtoReader::new(data);
+    let mut value = MvtValue::String(String::new());
+
+    while let Some((field, wire_type)) = reader.read_key()? {
+        value = match (field, wire_type) {
+            (1, WIRE_LEN) => MvtValue::String(reader.read_string()?),
+            (2, WIRE_FIXED32) => MvtValue::Float(f32::from_bits(reader.read_fixed32()?)),
+            (3, WIRE_FIXED64) => MvtValue::Double(f64::from_bits(reader.read_fixed64()?)),
+            (4, WIRE_VARINT) => MvtValue::Int(reader.read_varint()? as i64),
+            (5, WIRE_VARINT) => MvtValue::Uint(reader.read_varint()?),
+            (6, WIRE_VARINT) => MvtValue::Int(zigzag_decode(reader.read_varint()?)),
+            (7, WIRE_VARINT) => MvtValue::Bool(reader.read_varint()? != 0),
+            _ => {
+                reader.skip(wire_type)?;
+                continue;
+            }
+        };
+    }
+
+    Ok(value)
+}
+
+fn decode_feature(data: &[u8], keys: &[String], values: &[MvtValue]) -> Result<MvtFeature> {
+    let mut reader = ProtoReader::new(data);
+    let mut id = None;
+    let mut geom_type = MvtGeomType::Unknown;
+    let mut tags = Vec::new();
+    let mut geometry = Vec::new();
+
+    while let Some((field, wire_type)) = reader.read_key()? {
+        match (field, wire_type) {
+            (1, WIRE_VARINT) => id = Some(reader.read_varint()?),
+            (2, WIRE_LEN) => tags = reader.read_packed_varints()?,
+            (3, WIRE_VARINT) => {
+                geom_type = match reader.read_varint()? {
+                    1 => MvtGeomType::Point,
+                    2 => MvtGeomType::LineString,
+                    3 => MvtGeomType::Polygon,
+                    _ => MvtGeomType::Unknown,
+                }
+            }
+            (4, WIRE_LEN) => geometry = reader.read_packed_varints()?,
+            _ => reader.skip(wire_type)?,
+        }
+    }
+
+    let mut properties = HashMap::new();
+    for pair in tags.chunks_exact(2) {
+        let key = keys
+            .get(pair[0] as usize)
+            .ok_or_else(|| invalid("tag key index"))?;
+        let value = values
+            .get(pair[1] as usize)
+            .ok_or_else(|| invalid("tag value index"))?;
+        properties.insert(key.clone(), value.clone());
+    }
+
+    Ok(MvtFeature {
+        id,
+        geom_type,
+        properties,
+        parts: decode_geometry(&geometry)?,
+    })
+}
+
+/// Decode the MoveTo/LineTo/ClosePath command stream into coordinate parts
+fn decode_geometry(commands: &[u64]) -> Result<Vec<Vec<(i32, i32)>>> {
+    let mut parts: Vec<Vec<(i32, i32)>> = Vec::new();
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut index = 0;
+
+    while index < commands.len() {
+        let command = commands[index];
+        index += 1;
+        let id = command & 0x7;
+        let count = (command >> 3) as usize;
+
+        match id {
+            // MoveTo starts a new part per point, LineTo extends the current part
+            1 | 2 => {
+                if index + count * 2 > commands.len() {
+                    return Err(invalid("geometry command length"));
+                }
+                for _ in 0..count {
+                    x += zigzag_decode(commands[index]);
+                    y += zigzag_decode(commands[index + 1]);
+                    index += 2;
+
+                    let point = (x as i32, y as i32);
+                    match (id, parts.last_mut()) {
+                        (2, Some(part)) => part.push(point),
+                        (2, None) => return Err(invalid("LineTo before MoveTo")),
+                        _ => parts.push(vec![point]),
+                    }
+                }
+            }
+            // ClosePath repeats the first point of the current ring
+            7 => {
+                if let Some(part) = parts.last_mut()
+                    && let Some(&first) = part.first()
+                {
+                    part.push(first);
+                }
+            }
+            _ => return Err(invalid("geometry command")),
+        }
+    }
+
+    Ok(parts)
+}
+
+/// Signed area of a ring in tile coordinates; positive for exterior rings
+pub fn ring_area(ring: &[(i32, i32)]) -> f64 {
+    ring.windows(2)
+        .map(|w| w[0].0 as f64 * w[1].1 as f64 - w[1].0 as f64 * w[0].1 as f64)
+        .sum::<f64>()
+        / 2.0
+}
+
+fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn invalid(what: &str) -> OsmTilesError {
+    OsmTilesError::Parse(format!("Invalid vector tile: bad {}", what))
+}
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+/// Cursor over protobuf wire-format data
+struct ProtoReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn read_key(&mut self) -> Result<Option<(u32, u8)>> {
+        if self.position >= self.data.len() {
+            return Ok(None);
+        }
+        let key = self.read_varint()?;
+        Ok(Some(((key >> 3) as u32, (key & 0x7) as u8)))
+    }
+
+    fn read_varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or_else(|| invalid("varint"))?;
+            self.position += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint"))
+    }
+
+    fn read_fixed32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("4 bytes")))
+    }
+
+    fn read_fixed64(&mut self) -> Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8]> {
+        let length = self.read_varint()? as usize;
+        self.take(length)
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        String::from_utf8(self.read_bytes()?.to_vec()).map_err(|_| invalid("UTF-8 string"))
+    }
+
+    fn read_packed_varints(&mut self) -> Result<Vec<u64>> {
+        let mut packed = ProtoReader::new(self.read_bytes()?);
+        let mut values = Vec::new();
+        while packed.position < packed.data.len() {
+            values.push(packed.read_varint()?);
+        }
+        Ok(values)
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("field length"))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, wire_type: u8) -> Result<()> {
+        match wire_type {
+            WIRE_VARINT => self.read_varint().map(|_| ()),
+            WIRE_FIXED64 => self.take(8).map(|_| ()),
+            WIRE_LEN => self.read_bytes().map(|_| ()),
+            WIRE_FIXED32 => self.take(4).map(|_| ()),
+            _ => Err(invalid("wire type")),
+        }
+    }
+}
+
+/// Protobuf encoding helpers for building vector tiles in tests
+#[cfg(test)]
+pub(crate) mod encode {
+    pub fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    pub fn key(field: u32, wire_type: u8, out: &mut Vec<u8>) {
+        varint(((field as u64) << 3) | wire_type as u64, out);
+    }
+
+    pub fn bytes(field: u32, data: &[u8], out: &mut Vec<u8>) {
+        key(field, 2, out);
+        varint(data.len() as u64, out);
+        out.extend_from_slice(data);
+    }
+
+    pub fn packed(field: u32, values: &[u64], out: &mut Vec<u8>) {
+        let mut data = Vec::new();
+        for value in values {
+            varint(*value, &mut data);
+        }
+        bytes(field, &data, out);
+    }
+
+    pub fn zigzag(value: i64) -> u64 {
+        ((value << 1) ^ (value >> 63)) as u64
+    }
+
+    pub fn command(id: u64, count: u64) -> u64 {
+        (count << 3) | id
+    }
+
+    /// A test feature as (geom_type, string tags, geometry commands)
+    pub type TestFeature<'a> = (u64, &'a [(&'a str, &'a str)], Vec<u64>);
+
+    /// Encode a single-layer tile from test features
+    pub fn layer(name: &str, features: &[TestFeature]) -> Vec<u8> {
+        let mut keys: Vec<&str> = Vec::new();
+        let mut values: Vec<&str> = Vec::new();
+        let mut layer = Vec::new();
+        bytes(1, name.as_bytes(), &mut layer);
+
+        for (index, (geom_type, tags, geometry)) in features.iter().enumerate() {
+            let mut feature = Vec::new();
+            key(1, 0, &mut feature);
+            varint(index as u64 + 1, &mut feature);
+
+            let mut tag_indices = Vec::new();
+            for (k, v) in tags.iter() {
+                let ki = keys.iter().position(|x| x == k).unwrap_or_else(|| {
+                    keys.push(k);
+                    keys.len() - 1
+                });
+                let vi = values.iter().position(|x| x == v).unwrap_or_else(|| {
+                    values.push(v);
+                    values.len() - 1
+                });
+                tag_indices.push(ki as u64);
+                tag_indices.push(vi as u64);
+            }
+            packed(2, &tag_indices, &mut feature);
+            key(3, 0, &mut feature);
+            varint(*geom_type, &mut feature);
+            packed(4, geometry, &mut feature);
+            bytes(2, &feature, &mut layer);
+        }
+
+        for k in keys {
+            bytes(3, k.as_bytes(), &mut layer);
+        }
+        for v in values {
+            let mut value = Vec::new();
+            bytes(1, v.as_bytes(), &mut value);
+            bytes(4, &value, &mut layer);
+        }
+        key(5, 0, &mut layer);
+        varint(4096, &mut layer);
+        key(15, 0, &mut layer);
+        varint(2, &mut layer);
+
+        let mut tile = Vec::new();
+        bytes(3, &layer, &mut tile);
+        tile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode::*;
+    use super::*;
+
+    #[test]
+    fn test_decode_line_feature() {
+        // MoveTo(10, 20), LineTo(+5, 0), LineTo(0, +5)
+        let geometry = vec![
+            command(1, 1),
+            zigzag(10),
+            zigzag(20),
+            command(2, 2),
+            zigzag(5),
+            zigzag(0),
+            zigzag(0),
+            zigzag(5),
+        ];
+        let tile = layer("transportation", &[(2, &[("class", "primary")], geometry)]);
+
+        let layers = decode_vector_tile(&tile).unwrap();
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].name, "transportation");
+        assert_eq!(layers[0].extent, 4096);
+
+        let feature = &layers[0].features[0];
+        assert_eq!(feature.id, Some(1));
+        assert_eq!(feature.geom_type, MvtGeomType::LineString);
+        assert_eq!(
+            feature.properties["class"],
+            MvtValue::String("primary".to_string())
+        );
+        assert_eq!(feature.parts, vec![vec![(10, 20), (15, 20), (15, 25)]]);
+    }
+
+    #[test]
+    fn test_decode_polygon_ring() {
+        // Clockwise square in tile coordinates, closed with ClosePath
+        let geometry = vec![
+            command(1, 1),
+            zigzag(0),
+            zigzag(0),
+            command(2, 3),
+            zigzag(10),
+            zigzag(0),
+            zigzag(0),
+            zigzag(10),
+            zigzag(-10),
+            zigzag(0),
+            command(7, 1),
+        ];
+        let tile = layer("building", &[(3, &[], geometry)]);
+
+        let layers = decode_vector_tile(&tile).unwrap();
+        let ring = &layers[0].features[0].parts[0];
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring.first(), ring.last());
+        assert!(ring_area(ring) > 0.0);
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_data() {
+        let mut tile = layer("water", &[(3, &[], vec![command(1, 1), 0, 0])]);
+        tile.truncate(tile.len() - 3);
+        assert!(decode_vector_tile(&tile).is_err());
+    }
+}
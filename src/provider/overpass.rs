@@ -1,22 +1,89 @@
 use async_trait::async_trait;
-use geo::{Destination, Haversine, Point};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
-use super::{OsmData, OsmDataProvider, ProviderCapabilities};
-use crate::http::{HttpClient, HttpConfig, HttpError};
+use super::{
+    ChunkSink, GeocodeCandidate, Geocoder, GeocoderChain, OsmData, OsmDataProvider, OsmDiff,
+    ProviderCapabilities, decode_augmented_diff, region_candidate,
+};
+use crate::geoutil::destination;
+use crate::http::{
+    CancellationToken, HttpClient, HttpConfig, LoggingHttpClient, QueryLogger, RequestOptions,
+    TransferStats,
+};
 use crate::{
-    BoundingBox, NetworkError, OsmConfig, OsmDataFormat, OsmMetadata, OsmTilesError, Region, Result,
+    BoundingBox, NetworkError, OsmConfig, OsmDataFormat, OsmMetadata, OsmTagQuery, OsmTilesError,
+    ProviderError, QUERY_HASH_KEY, Region, Result, ValidationLimits, escape_ql, stable_hash,
 };
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use tracing::field::Empty;
 
 /// WASM-compatible HTTP-based provider using the Overpass API
 pub struct OverpassProvider {
     pub base_url: String,
     http_client: Arc<dyn HttpClient>,
     custom_timeout: Option<u64>, // Changed from Duration to u64
+    max_elements_per_query: Option<u64>,
+    /// Size past which responses are aborted, `None` for no limit
+    max_response_bytes: Option<usize>,
+    geocoder: Arc<dyn Geocoder>,
+    /// Whether the geocoder was set with `with_geocoder` rather than built from the HTTP client
+    custom_geocoder: bool,
+    /// Whether city regions are queried within their administrative area
+    city_areas: bool,
+    /// `admin_level` the city area must be tagged with, if any
+    city_admin_level: Option<u8>,
+    /// Further endpoints probed by `select_fastest_endpoint`
+    endpoints: Vec<String>,
+    /// Endpoint picked by the last probe, used instead of `base_url`
+    selected_endpoint: Mutex<Option<String>>,
+    /// URL template every request is routed through, for browsers blocked by CORS
+    proxy_template: Option<String>,
+    /// Token aborting running queries
+    cancellation: Option<CancellationToken>,
+}
+
+/// Seconds to wait beyond the query's `[timeout:...]` before abandoning a request
+const QUERY_TIMEOUT_MARGIN_SECS: u64 = 30;
+
+/// Default response size limit: far above city-sized responses, well below a mistaken world-scale query
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 150 * 1024 * 1024;
+
+/// Public Overpass API instances, e.g. for [`OverpassProvider::with_endpoints`]
+pub const PUBLIC_OVERPASS_ENDPOINTS: &[&str] = &[
+    "https://overpass-api.de/api/interpreter",
+    "https://overpass.kumi.systems/api/interpreter",
+    "https://overpass.private.coffee/api/interpreter",
+];
+
+/// Tiny query checking that an endpoint answers
+const AVAILABILITY_QUERY: &str = "[out:json][timeout:5];\nnode(0,0,0.001,0.001);\nout;";
+
+/// Outcome of probing one Overpass endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointHealth {
+    /// Endpoint URL (without proxy)
+    pub url: String,
+    /// Round-trip time of the probe query, if it succeeded
+    pub latency_ms: Option<u64>,
+    /// Why the probe failed, if it did
+    pub error: Option<String>,
+}
+
+impl EndpointHealth {
+    /// Check whether the endpoint answered the probe
+    pub fn is_healthy(&self) -> bool {
+        self.latency_ms.is_some()
+    }
 }
 
+/// How often a slice that hit the element limit is split into quadrants
+const MAX_SLICE_DEPTH: usize = 4;
+
 impl OverpassProvider {
     /// Create a new Overpass API provider with default client
     pub fn new() -> Self {
@@ -28,11 +95,7 @@ impl OverpassProvider {
         let http_client = crate::http::create_default_client()
             .expect("Failed to create HTTP client - check that either 'reqwest-client' or 'ehttp-client' feature is enabled");
 
-        Self {
-            base_url: base_url.into(),
-            http_client,
-            custom_timeout: None,
-        }
+        Self::with_http_client(base_url, http_client)
     }
 
     /// Create a new provider with custom configuration
@@ -40,19 +103,28 @@ impl OverpassProvider {
         let http_client = crate::http::create_client_with_config(config)
             .expect("Failed to create HTTP client with config");
 
-        Self {
-            base_url: base_url.into(),
-            http_client,
-            custom_timeout: None,
-        }
+        Self::with_http_client(base_url, http_client)
     }
 
     /// Create a provider with a custom HTTP client
+    ///
+    /// City names are resolved by the embedded [`Gazetteer`](super::Gazetteer)
+    /// first, then by Nominatim through the same client.
     pub fn with_http_client(base_url: impl Into<String>, http_client: Arc<dyn HttpClient>) -> Self {
         Self {
             base_url: base_url.into(),
+            geocoder: Arc::new(GeocoderChain::with_defaults(http_client.clone())),
             http_client,
             custom_timeout: None,
+            max_elements_per_query: None,
+            max_response_bytes: Some(DEFAULT_MAX_RESPONSE_BYTES),
+            custom_geocoder: false,
+            city_areas: false,
+            city_admin_level: None,
+            endpoints: Vec::new(),
+            selected_endpoint: Mutex::new(None),
+            proxy_template: None,
+            cancellation: None,
         }
     }
 
@@ -62,11 +134,7 @@ impl OverpassProvider {
         let config = HttpConfig::default();
         let http_client = Arc::new(crate::http::EhttpClient::with_config(config));
 
-        Self {
-            base_url: "https://overpass-api.de/api/interpreter".to_string(),
-            http_client,
-            custom_timeout: None,
-        }
+        Self::with_http_client("https://overpass-api.de/api/interpreter", http_client)
     }
 
     /// Create a provider optimized for native environments
@@ -78,11 +146,7 @@ impl OverpassProvider {
                 .expect("Failed to create reqwest client"),
         );
 
-        Self {
-            base_url: "https://overpass-api.de/api/interpreter".to_string(),
-            http_client,
-            custom_timeout: None,
-        }
+        Self::with_http_client("https://overpass-api.de/api/interpreter", http_client)
     }
 
     /// Set a custom timeout for requests
@@ -91,148 +155,349 @@ impl OverpassProvider {
         self
     }
 
-    /// Build an Overpass QL query for the given bounding box and features
-    fn build_overpass_query(&self, bbox: &BoundingBox, config: &OsmConfig) -> String {
-        let bbox_str = format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east);
+    /// Fetch in slices of at most `max` elements instead of one large query
+    ///
+    /// The tag filters are split into per-feature sub-queries, and the area
+    /// into a grid of cells, until the estimated element count of each slice
+    /// fits. Every slice asks for at most `max` elements; slices that still
+    /// hit the limit are split into quadrants and fetched again. The partial
+    /// responses are merged, dropping elements returned by several slices.
+    pub fn with_max_elements_per_query(mut self, max: u64) -> Self {
+        self.max_elements_per_query = Some(max.max(1));
+        self
+    }
 
-        let timeout = self.custom_timeout.unwrap_or(config.timeout_seconds);
+    /// Abort downloads once the decompressed response exceeds `bytes`
+    ///
+    /// The default is [`DEFAULT_MAX_RESPONSE_BYTES`]. Exceeding the limit fails
+    /// with [`NetworkError::ResponseTooLarge`] before any parsing; the limit
+    /// applies to each slice with [`with_max_elements_per_query`](Self::with_max_elements_per_query).
+    pub fn with_max_response_bytes(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self
+    }
 
-        let mut query = format!("[out:json][timeout:{}];\n(\n", timeout);
+    /// Download responses of any size
+    pub fn without_response_limit(mut self) -> Self {
+        self.max_response_bytes = None;
+        self
+    }
 
-        // Get all OSM tag queries from the feature set
-        let tag_queries = config.features.to_osm_queries();
+    /// Log every outgoing query and geocoding URL through `logger`
+    pub fn with_query_log(mut self, logger: QueryLogger) -> Self {
+        self.http_client = Arc::new(LoggingHttpClient::new(self.http_client, logger));
+        if !self.custom_geocoder {
+            self.geocoder = Arc::new(GeocoderChain::with_defaults(self.http_client.clone()));
+        }
+        self
+    }
 
-        for tag_query in tag_queries {
-            // Build the filter string
-            let filter = match &tag_query.value {
-                Some(value) => format!("[\"{}\"][\"{}\"]", tag_query.key, value),
-                None => format!("[\"{}\"]", tag_query.key),
-            };
+    /// Resolve city names with a custom geocoder instead of the gazetteer and Nominatim
+    pub fn with_geocoder(mut self, geocoder: impl Geocoder + 'static) -> Self {
+        self.geocoder = Arc::new(geocoder);
+        self.custom_geocoder = true;
+        self
+    }
 
-            // Add way queries
-            query.push_str(&format!("  way{}({});\n", filter, bbox_str));
+    /// Only fetch features inside a city's administrative boundary
+    ///
+    /// [`Region::City`] queries select the `boundary=administrative` area with
+    /// the city's name and filter every statement by both that area and the
+    /// geocoded bounding box, which also tells apart places sharing a name.
+    /// The surroundings included in the bounding box are not downloaded.
+    pub fn with_city_areas(mut self) -> Self {
+        self.city_areas = true;
+        self
+    }
 
-            // Add relation queries for some feature types that commonly use relations
-            if self.should_include_relations(&tag_query.key) {
-                query.push_str(&format!("  relation{}({});\n", filter, bbox_str));
-            }
+    /// Only fetch features inside a city boundary with this `admin_level` (e.g., 4 for Berlin)
+    ///
+    /// Implies [`with_city_areas`](Self::with_city_areas).
+    pub fn with_city_admin_level(mut self, admin_level: u8) -> Self {
+        self.city_admin_level = Some(admin_level);
+        self.with_city_areas()
+    }
 
-            // Add node queries for specific features like amenities
-            if self.should_include_nodes(&tag_query.key) {
-                query.push_str(&format!("  node{}({});\n", filter, bbox_str));
+    /// Add endpoints to choose from with [`select_fastest_endpoint`](Self::select_fastest_endpoint)
+    ///
+    /// The base URL stays in use until a probe picks another endpoint.
+    pub fn with_endpoints<I, S>(mut self, endpoints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for endpoint in endpoints {
+            let endpoint = endpoint.into();
+            if endpoint != self.base_url && !self.endpoints.contains(&endpoint) {
+                self.endpoints.push(endpoint);
             }
         }
-
-        query.push_str(");\nout geom;");
-        query
+        self
     }
 
-    /// Determine if relations should be included for a given OSM key
-    fn should_include_relations(&self, key: &str) -> bool {
-        matches!(
-            key,
-            "building" | "natural" | "landuse" | "leisure" | "boundary" | "waterway"
-        )
+    /// Route every request through a proxy, e.g. `https://proxy.example/?url={url}`
+    ///
+    /// Browsers refuse responses from Overpass instances that do not send
+    /// CORS headers for the page's origin. `{url}` is replaced by the
+    /// percent-encoded endpoint; templates without it get the endpoint
+    /// appended as is (`https://proxy.example/` becomes
+    /// `https://proxy.example/https://overpass-api.de/...`). Geocoding is not
+    /// proxied.
+    pub fn with_proxy(mut self, template: impl Into<String>) -> Self {
+        self.proxy_template = Some(template.into());
+        self
     }
 
-    /// Determine if nodes should be included for a given OSM key
-    fn should_include_nodes(&self, key: &str) -> bool {
-        matches!(key, "amenity" | "tourism" | "power")
+    /// Abort running queries once `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
     }
 
-    /// Convert a radius in kilometers to a bounding box around a center point
-    fn radius_to_bbox(center_lat: f64, center_lon: f64, radius_km: f64) -> BoundingBox {
-        let center = Point::new(center_lon, center_lat); // Point uses (lon, lat)
-        let distance_meters = radius_km * 1000.0;
-
-        // Calculate the four corner points by moving in cardinal directions
-        let north_point = Haversine.destination(center, 0.0, distance_meters);
-        let south_point = Haversine.destination(center, 180.0, distance_meters);
-        let east_point = Haversine.destination(center, 90.0, distance_meters);
-        let west_point = Haversine.destination(center, 270.0, distance_meters);
-
-        BoundingBox::new(
-            south_point.y(), // latitude
-            west_point.x(),  // longitude
-            north_point.y(), // latitude
-            east_point.x(),  // longitude
-        )
+    /// Get the endpoint requests currently go to, before proxying
+    pub fn endpoint(&self) -> String {
+        self.selected_endpoint
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| self.base_url.clone())
     }
 
-    /// Parse element count from Overpass JSON response
-    fn parse_element_count(json_data: &str) -> Option<u32> {
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(json_data) {
-            if let Some(elements) = value.get("elements").and_then(|e| e.as_array()) {
-                return Some(elements.len() as u32);
+    /// Get the URL a request to `endpoint` is sent to
+    fn request_url(&self, endpoint: &str) -> String {
+        match &self.proxy_template {
+            Some(template) if template.contains("{url}") => {
+                template.replace("{url}", &urlencoding::encode(endpoint))
             }
+            Some(template) => format!("{}{}", template, endpoint),
+            None => endpoint.to_string(),
         }
-        None
     }
 
-    /// Convert HTTP error to our network error
-    fn convert_http_error(err: HttpError) -> NetworkError {
-        match err {
-            HttpError::RequestFailed { message } => NetworkError::Connection { message },
-            HttpError::HttpStatus { status } => NetworkError::HttpError { status },
-            HttpError::Timeout { seconds } => NetworkError::Timeout { seconds },
-            HttpError::Network { message } => NetworkError::Connection { message },
+    /// Send the availability query to one endpoint
+    async fn check_endpoint(&self, endpoint: &str) -> Result<()> {
+        let response = self
+            .http_client
+            .post_form(&self.request_url(endpoint), &[("data", AVAILABILITY_QUERY)])
+            .await
+            .map_err(NetworkError::from)?;
+
+        if response.status == 200 {
+            Ok(())
+        } else {
+            Err(OsmTilesError::http_status(
+                response.status,
+                response.retry_after(),
+            ))
         }
     }
-}
 
-#[async_trait]
-impl OsmDataProvider for OverpassProvider {
-    fn provider_type(&self) -> &'static str {
-        "overpass"
+    /// Probe the base URL and every added endpoint, one after the other
+    pub async fn probe_endpoints(&self) -> Vec<EndpointHealth> {
+        let mut results = Vec::new();
+        for url in std::iter::once(&self.base_url).chain(&self.endpoints) {
+            // chrono's clock also works on WASM, where `Instant` panics
+            let start = Utc::now();
+            let result = self.check_endpoint(url).await;
+            let elapsed = (Utc::now() - start).num_milliseconds().max(0) as u64;
+            tracing::debug!("Probed Overpass endpoint {}: {:?}", url, result);
+            results.push(EndpointHealth {
+                url: url.clone(),
+                latency_ms: result.is_ok().then_some(elapsed),
+                error: result.err().map(|error| error.to_string()),
+            });
+        }
+        results
     }
 
-    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
-        // Conditional timing for non-WASM targets
-        #[cfg(not(target_arch = "wasm32"))]
-        let start_time = Instant::now();
+    /// Probe all endpoints and send further requests to the fastest healthy one
+    ///
+    /// Returns the chosen endpoint, or the last probe error if none answered.
+    pub async fn select_fastest_endpoint(&self) -> Result<String> {
+        let results = self.probe_endpoints().await;
+        let Some(fastest) = results
+            .iter()
+            .filter(|health| health.is_healthy())
+            .min_by_key(|health| health.latency_ms)
+        else {
+            let error = results
+                .into_iter()
+                .filter_map(|health| health.error)
+                .next_back()
+                .unwrap_or_default();
+            return Err(OsmTilesError::Network(NetworkError::Connection {
+                message: format!("No Overpass endpoint is available: {}", error),
+            }));
+        };
+        tracing::info!("Using Overpass endpoint {}", fastest.url);
+        *self.selected_endpoint.lock().unwrap() = Some(fastest.url.clone());
+        Ok(fastest.url.clone())
+    }
 
-        tracing::info!(
-            "Fetching OSM data via Overpass API with config: {:?}",
-            config
+    /// Get the Overpass area statement selecting the city of the region, if areas are enabled
+    fn search_area(&self, region: &Region) -> Option<String> {
+        let Region::City { name } = region else {
+            return None;
+        };
+        if !self.city_areas {
+            return None;
+        }
+        // Qualifiers like ", US" are left to the geocoded bounding box
+        let name = name.split(',').next().unwrap_or_default().trim();
+        let mut area = format!(
+            "area[\"name\"=\"{}\"][\"boundary\"=\"administrative\"]",
+            escape_ql(name)
         );
+        if let Some(level) = self.city_admin_level {
+            area.push_str(&format!("[\"admin_level\"=\"{}\"]", level));
+        }
+        area.push_str("->.searchArea;\n");
+        Some(area)
+    }
 
-        // Resolve the region to a bounding box
-        let bbox = self.resolve_region(&config.region).await?;
-        tracing::debug!("Resolved region to bounding box: {:?}", bbox);
+    /// Build an Overpass QL query for the given bounding box and features
+    fn build_overpass_query(&self, bbox: &BoundingBox, config: &OsmConfig) -> String {
+        self.build_query(bbox, &config.features.to_osm_queries(), config, None)
+    }
 
-        // Validate bounding box size for Overpass API limits
-        let area_km2 = bbox.area_km2();
-        if area_km2 > 1000.0 {
-            tracing::warn!(
-                "Large area requested: {:.2} km² - this may take a while or fail",
-                area_km2
-            );
+    /// Build an Overpass QL query for some tag filters, returning at most `limit` elements
+    fn build_query(
+        &self,
+        bbox: &BoundingBox,
+        tag_queries: &[OsmTagQuery],
+        config: &OsmConfig,
+        limit: Option<u64>,
+    ) -> String {
+        let bbox_str = format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east);
+
+        let timeout = self.custom_timeout.unwrap_or(config.timeout_seconds);
+
+        let mut query = format!("[out:json][timeout:{}];\n", timeout);
+        // Statements are filtered by the bounding box, and by the city area if there is one
+        let spatial = match self.search_area(&config.region) {
+            Some(area) => {
+                query.push_str(&area);
+                format!("(area.searchArea)({})", bbox_str)
+            }
+            None => format!("({})", bbox_str),
+        };
+        query.push_str("(\n");
+
+        // Exclusions narrow every statement, so excluded elements are never downloaded
+        let exclusions: String = config
+            .features
+            .exclusions()
+            .map(OsmTagQuery::to_overpass_filter)
+            .collect();
+
+        for tag_query in merge_values(tag_queries) {
+            // Build the filter string
+            let filter = format!("{}{}", tag_query.to_overpass_filter(), exclusions);
+
+            // Add way queries
+            query.push_str(&format!("  way{}{};\n", filter, spatial));
+
+            // Add relation queries for some feature types that commonly use relations
+            if self.should_include_relations(&tag_query.key) {
+                query.push_str(&format!("  relation{}{};\n", filter, spatial));
+            }
+
+            // Add node queries for specific features like amenities
+            if self.should_include_nodes(&tag_query.key) {
+                query.push_str(&format!("  node{}{};\n", filter, spatial));
+            }
         }
-        if area_km2 > 5000.0 {
-            return Err(OsmTilesError::Config(format!(
-                "Area too large: {:.2} km². Overpass API typically limits requests to ~1000 km²",
-                area_km2
-            )));
+
+        match limit {
+            Some(limit) => query.push_str(&format!(");\nout geom {};", limit)),
+            None => query.push_str(");\nout geom;"),
         }
+        query
+    }
 
-        // Build the Overpass query
-        let query = self.build_overpass_query(&bbox, config);
-        tracing::debug!("Overpass query: {}", query);
+    /// Fetch the changes to the configured area since `since` as an augmented diff
+    ///
+    /// Runs the same query as a full load with `[adiff:...]`, so only the
+    /// elements that changed are downloaded. Poll with the previous diff's
+    /// `data_timestamp` to keep a long-running map up to date.
+    #[tracing::instrument(name = "fetch_diff", skip_all, fields(provider = "overpass", %since))]
+    pub async fn fetch_diff(&self, config: &OsmConfig, since: DateTime<Utc>) -> Result<OsmDiff> {
+        let bbox = config.aligned_bounds(self.resolve_region(&config.region).await?);
+        config
+            .validate_bounds(&bbox, &ValidationLimits::for_provider(&self.capabilities()))
+            .into_result()?;
+
+        // Augmented diffs are only available as XML
+        let query = self.build_overpass_query(&bbox, config).replacen(
+            "[out:json]",
+            &format!(
+                "[out:xml][adiff:\"{}\"]",
+                since.format("%Y-%m-%dT%H:%M:%SZ")
+            ),
+            1,
+        );
+        let (raw_data, _) = self.run_query(&query, config, None).await?;
+        let diff = decode_augmented_diff(&raw_data)?;
+        tracing::debug!(
+            "Augmented diff: {} created, {} modified, {} deleted",
+            diff.created.len(),
+            diff.modified.len(),
+            diff.deleted.len()
+        );
+        Ok(diff)
+    }
 
-        // Make the HTTP request using our trait
-        let response = self
-            .http_client
-            .post_form(&self.base_url, &[("data", &query)])
-            .await
-            .map_err(Self::convert_http_error)?;
+    /// Fetch the data of a configuration, streaming the response to `on_chunk` if given
+    #[tracing::instrument(
+        name = "fetch_data",
+        skip_all,
+        fields(
+            provider = "overpass",
+            region = ?config.region,
+            bbox = Empty,
+            area_km2 = Empty,
+            element_count = Empty,
+            bytes = Empty,
+            slices = Empty,
+        )
+    )]
+    async fn fetch(
+        &self,
+        config: &OsmConfig,
+        on_chunk: Option<&mut dyn ChunkSink>,
+    ) -> Result<OsmData> {
+        // Conditional timing for non-WASM targets
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_time = Instant::now();
 
-        if response.status != 200 {
-            return Err(OsmTilesError::Network(NetworkError::HttpError {
-                status: response.status,
-            }));
-        }
+        // Resolve the region to a bounding box
+        let bbox = config.aligned_bounds(self.resolve_region(&config.region).await?);
+        let span = tracing::Span::current();
+        span.record("bbox", format_bbox(&bbox));
+
+        // Reject requests that exceed Overpass API limits before downloading anything
+        config
+            .validate_bounds(&bbox, &ValidationLimits::for_provider(&self.capabilities()))
+            .into_result()?;
+        let area_km2 = bbox.area_km2();
+        span.record("area_km2", area_km2);
 
-        let raw_data = response.body;
+        // Sliced fetches are identified by the query they split
+        let query = self.build_overpass_query(&bbox, config);
+        let (raw_data, transfer, slices) = match self.max_elements_per_query {
+            Some(max) => {
+                let (raw_data, transfer, slices) = self.fetch_slices(&bbox, config, max).await?;
+                // Slices are merged before anything can be passed on
+                if let Some(on_chunk) = on_chunk {
+                    on_chunk.chunk(raw_data.as_bytes());
+                }
+                (raw_data, transfer, slices)
+            }
+            None => {
+                let (raw_data, transfer) = self.run_query(&query, config, on_chunk).await?;
+                (raw_data, transfer, 1)
+            }
+        };
 
         // Calculate processing time conditionally
         let processing_time = {
@@ -247,9 +512,15 @@ impl OsmDataProvider for OverpassProvider {
         };
 
         let element_count = Self::parse_element_count(&raw_data);
-
-        let mut metadata = OsmMetadata::new(&self.base_url, self.provider_type())
-            .with_processing_time(processing_time);
+        span.record("element_count", element_count.unwrap_or(0));
+        span.record("bytes", raw_data.len());
+        span.record("slices", slices);
+
+        let mut metadata =
+            OsmMetadata::new(self.endpoint(), "overpass").with_processing_time(processing_time);
+        if let Some(timestamp) = Self::parse_data_timestamp(&raw_data) {
+            metadata = metadata.with_data_timestamp(timestamp);
+        }
 
         if let Some(count) = element_count {
             metadata = metadata.with_element_count(count);
@@ -257,28 +528,22 @@ impl OsmDataProvider for OverpassProvider {
 
         metadata = metadata
             .with_extra("query_size", raw_data.len().to_string())
+            .with_extra(QUERY_HASH_KEY, stable_hash(&query))
             .with_extra("area_km2", format!("{:.2}", area_km2))
-            .with_extra(
-                "bbox",
-                format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east),
-            )
-            .with_extra("http_client", "trait_based");
+            .with_extra("bbox", format_bbox(&bbox))
+            .with_extra("http_client", "trait_based")
+            .with_extra("slices", slices.to_string());
+        if self.city_areas
+            && let Region::City { name } = &config.region
+        {
+            metadata = metadata.with_extra("search_area", name.clone());
+        }
 
-        // Conditional logging with timing info
-        #[cfg(not(target_arch = "wasm32"))]
-        tracing::info!(
-            "Successfully fetched OSM data: {} elements, {:.2} KB, {:.1}s",
-            element_count.unwrap_or(0),
-            raw_data.len() as f64 / 1024.0,
-            processing_time as f64 / 1000.0
-        );
+        if let Some(transfer) = &transfer {
+            metadata = metadata.with_transfer_stats(transfer);
+        }
 
-        #[cfg(target_arch = "wasm32")]
-        tracing::info!(
-            "Successfully fetched OSM data: {} elements, {:.2} KB",
-            element_count.unwrap_or(0),
-            raw_data.len() as f64 / 1024.0,
-        );
+        tracing::info!(elapsed_ms = processing_time, "Fetched OSM data");
 
         Ok(OsmData {
             raw_data,
@@ -288,106 +553,326 @@ impl OsmDataProvider for OverpassProvider {
         })
     }
 
-    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
-        match region {
-            Region::BoundingBox(bbox) => Ok(bbox.clone()),
+    /// Get the options of query requests: the server-side timeout plus a margin, the size limit and the cancellation token
+    ///
+    /// A server that stops answering would otherwise hold the request until
+    /// the HTTP client's own timeout, which may be far longer.
+    fn request_options(&self, config: &OsmConfig) -> RequestOptions {
+        let seconds = self.custom_timeout.unwrap_or(config.timeout_seconds);
+        let mut options = RequestOptions::new()
+            .with_timeout(Duration::from_secs(seconds + QUERY_TIMEOUT_MARGIN_SECS));
+        if let Some(token) = &self.cancellation {
+            options = options.with_cancellation(token.clone());
+        }
+        if let Some(bytes) = self.max_response_bytes {
+            options = options.with_max_response_bytes(bytes);
+        }
+        options
+    }
 
-            Region::CenterRadius {
-                lat,
-                lon,
-                radius_km,
-            } => Ok(Self::radius_to_bbox(*lat, *lon, *radius_km)),
+    /// Send a query and check the response for HTTP errors and error remarks
+    #[tracing::instrument(
+        name = "overpass_query",
+        skip_all,
+        fields(query_bytes = query.len(), lines = query.lines().count())
+    )]
+    async fn run_query(
+        &self,
+        query: &str,
+        config: &OsmConfig,
+        on_chunk: Option<&mut dyn ChunkSink>,
+    ) -> Result<(String, Option<TransferStats>)> {
+        let form = [("data", query)];
+        let url = self.request_url(&self.endpoint());
+        let options = self.request_options(config);
+        let response = match on_chunk {
+            Some(on_chunk) => {
+                self.http_client
+                    .post_form_streaming_with(&url, &form, &options, &mut |chunk| {
+                        on_chunk.chunk(chunk)
+                    })
+                    .await
+            }
+            None => self.http_client.post_form_with(&url, &form, &options).await,
+        }
+        .map_err(NetworkError::from)?;
 
-            Region::City { name } => {
-                tracing::debug!("Geocoding city: {}", name);
+        if response.status != 200 {
+            // Malformed queries are answered with 400 and an HTML page describing the error
+            if let Some(message) = Self::parse_error_page(&response.body) {
+                return Err(ProviderError::from_remark(message).into());
+            }
+            return Err(OsmTilesError::http_status(
+                response.status,
+                response.retry_after(),
+            ));
+        }
 
-                let nominatim_url = format!(
-                    "https://nominatim.openstreetmap.org/search?q={}&format=json&limit=1&addressdetails=1",
-                    urlencoding::encode(name)
-                );
+        // Timeouts and memory exhaustion still return 200, flagged by a remark
+        if let Some(remark) = Self::parse_remark(&response.body) {
+            tracing::warn!("Overpass API returned an error remark: {}", remark);
+            return Err(ProviderError::from_remark(remark).into());
+        }
 
-                let response = self
-                    .http_client
-                    .get(&nominatim_url)
-                    .await
-                    .map_err(Self::convert_http_error)?;
+        Ok((response.body, response.transfer))
+    }
 
-                if response.status != 200 {
-                    return Err(OsmTilesError::Network(NetworkError::HttpError {
-                        status: response.status,
-                    }));
-                }
+    /// Fetch the area in slices of at most `max` elements and merge them into one response
+    async fn fetch_slices(
+        &self,
+        bbox: &BoundingBox,
+        config: &OsmConfig,
+        max: u64,
+    ) -> Result<(String, Option<TransferStats>, usize)> {
+        let tag_queries = config.features.to_osm_queries();
+        let estimated = config.features.estimate_query_cost(bbox).estimated_elements;
+        let pieces = estimated.div_ceil(max).max(1) as usize;
+
+        // Split by feature first, then by area for what still does not fit
+        let groups = pieces.min(tag_queries.len()).max(1);
+        let group_size = tag_queries.len().div_ceil(groups).max(1);
+        let cells_per_side = (pieces.div_ceil(groups) as f64).sqrt().ceil() as usize;
+
+        let mut pending = Vec::new();
+        for group in tag_queries.chunks(group_size) {
+            for cell in split_bbox(bbox, cells_per_side) {
+                pending.push((cell, group, 0));
+            }
+        }
+        tracing::info!(
+            estimated_elements = estimated,
+            slices = pending.len(),
+            max_elements = max,
+            "Fetching Overpass data in slices"
+        );
 
-                let geocode_results: Vec<serde_json::Value> = serde_json::from_str(&response.body)
-                    .map_err(|e| {
-                        OsmTilesError::Parse(format!("Failed to parse geocoding response: {}", e))
-                    })?;
+        let mut elements = Vec::new();
+        let mut seen = HashSet::new();
+        // The header of the slice with the oldest snapshot describes the merged data
+        let mut header: Option<(DateTime<Utc>, Value)> = None;
+        let mut transfer: Option<TransferStats> = None;
+        let mut slices = 0;
+        while let Some((cell, group, depth)) = pending.pop() {
+            let query = self.build_query(&cell, group, config, Some(max));
+            let (body, slice_transfer) = self.run_query(&query, config, None).await?;
+            let mut page: Value = serde_json::from_str(&body)
+                .map_err(|e| OsmTilesError::json("Overpass response", e))?;
+            let Value::Array(page_elements) = page["elements"].take() else {
+                return Err(OsmTilesError::Parse(
+                    "No 'elements' array found in Overpass response".to_string(),
+                ));
+            };
+            slices += 1;
+            if let Some(timestamp) = page["osm3s"]["timestamp_osm_base"]
+                .as_str()
+                .and_then(parse_timestamp)
+                && header
+                    .as_ref()
+                    .is_none_or(|(oldest, _)| timestamp < *oldest)
+            {
+                header = Some((timestamp, page["osm3s"].take()));
+            }
 
-                if geocode_results.is_empty() {
-                    return Err(OsmTilesError::Geographic(format!(
-                        "Could not find city: {}",
-                        name
-                    )));
+            if page_elements.len() as u64 >= max {
+                if depth < MAX_SLICE_DEPTH {
+                    // The slice was cut off at the limit, so fetch its quadrants instead
+                    tracing::debug!("Slice hit the limit of {} elements, splitting it", max);
+                    pending.extend(
+                        split_bbox(&cell, 2)
+                            .into_iter()
+                            .map(|q| (q, group, depth + 1)),
+                    );
+                    continue;
                 }
+                tracing::warn!(
+                    "Slice {},{},{},{} still has {} elements or more; data may be incomplete",
+                    cell.south,
+                    cell.west,
+                    cell.north,
+                    cell.east,
+                    max
+                );
+            }
 
-                let result = &geocode_results[0];
-                let bbox_array = result["boundingbox"].as_array().ok_or_else(|| {
-                    OsmTilesError::Geographic(format!("No bounding box found for city: {}", name))
-                })?;
-
-                if bbox_array.len() != 4 {
-                    return Err(OsmTilesError::Geographic(
-                        "Invalid bounding box format from geocoding service".to_string(),
-                    ));
+            for element in page_elements {
+                let key = (
+                    element["type"].as_str().unwrap_or_default().to_string(),
+                    element["id"].as_i64().unwrap_or_default(),
+                );
+                // Ways crossing slice borders are returned by every slice they touch
+                if seen.insert(key) {
+                    elements.push(element);
                 }
+            }
 
-                let parse_coord = |idx: usize, coord_type: &str| -> Result<f64> {
-                    bbox_array[idx]
-                        .as_str()
-                        .ok_or_else(|| OsmTilesError::Parse(format!("Invalid {}", coord_type)))?
-                        .parse()
-                        .map_err(|_| OsmTilesError::Parse(format!("Invalid {} format", coord_type)))
-                };
-
-                let south = parse_coord(0, "south latitude")?;
-                let north = parse_coord(1, "north latitude")?;
-                let west = parse_coord(2, "west longitude")?;
-                let east = parse_coord(3, "east longitude")?;
-
-                tracing::debug!(
-                    "Geocoded '{}' to bbox: {},{},{},{}",
-                    name,
-                    south,
-                    west,
-                    north,
-                    east
-                );
-                Ok(BoundingBox::new(south, west, north, east))
+            if let Some(slice_transfer) = slice_transfer {
+                let total = transfer.get_or_insert(TransferStats {
+                    content_encoding: slice_transfer.content_encoding.clone(),
+                    wire_bytes: 0,
+                    body_bytes: 0,
+                });
+                total.wire_bytes += slice_transfer.wire_bytes;
+                total.body_bytes += slice_transfer.body_bytes;
             }
         }
+
+        let mut merged = json!({ "elements": elements });
+        if let Some((_, osm3s)) = header {
+            merged["osm3s"] = osm3s;
+        }
+        Ok((merged.to_string(), transfer, slices))
     }
 
-    async fn test_availability(&self) -> Result<()> {
-        tracing::debug!("Testing Overpass API availability");
+    /// Determine if relations should be included for a given OSM key
+    fn should_include_relations(&self, key: &str) -> bool {
+        matches!(
+            key,
+            "building" | "natural" | "landuse" | "leisure" | "boundary" | "waterway"
+        )
+    }
 
-        let test_query = "[out:json][timeout:5];\nnode(0,0,0.001,0.001);\nout;";
+    /// Determine if nodes should be included for a given OSM key
+    fn should_include_nodes(&self, key: &str) -> bool {
+        matches!(key, "amenity" | "tourism" | "power")
+    }
 
-        let response = self
-            .http_client
-            .post_form(&self.base_url, &[("data", test_query)])
-            .await
-            .map_err(Self::convert_http_error)?;
+    /// Convert a radius in kilometers to a bounding box around a center point
+    fn radius_to_bbox(center_lat: f64, center_lon: f64, radius_km: f64) -> BoundingBox {
+        let center = (center_lat, center_lon);
+        let distance_meters = radius_km * 1000.0;
 
-        if response.status == 200 {
-            tracing::debug!("Overpass API is available");
-            Ok(())
-        } else {
-            Err(OsmTilesError::Network(NetworkError::HttpError {
-                status: response.status,
-            }))
+        // Move from the center in the four cardinal directions
+        let (north, _) = destination(center, 0.0, distance_meters);
+        let (south, _) = destination(center, 180.0, distance_meters);
+        let (_, east) = destination(center, 90.0, distance_meters);
+        let (_, west) = destination(center, 270.0, distance_meters);
+
+        BoundingBox::new(south, west, north, east)
+    }
+
+    /// Parse element count from Overpass JSON response
+    fn parse_element_count(json_data: &str) -> Option<u32> {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(json_data)
+            && let Some(elements) = value.get("elements").and_then(|e| e.as_array())
+        {
+            return Some(elements.len() as u32);
+        }
+        None
+    }
+
+    /// Extract the `osm3s.timestamp_osm_base` snapshot time from an Overpass JSON response
+    pub(crate) fn parse_data_timestamp(json_data: &str) -> Option<DateTime<Utc>> {
+        // The osm3s header precedes the elements, so avoid parsing the whole response
+        const KEY: &str = "\"timestamp_osm_base\"";
+        let start = json_data.find(KEY)? + KEY.len();
+        let value = json_data[start..]
+            .trim_start()
+            .strip_prefix(':')?
+            .trim_start()
+            .strip_prefix('"')?;
+        parse_timestamp(&value[..value.find('"')?])
+    }
+
+    /// Extract an error remark from an Overpass JSON response
+    fn parse_remark(json_data: &str) -> Option<String> {
+        // Avoid parsing large successful responses a second time
+        if !json_data.contains("\"remark\"") {
+            return None;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Remark {
+            remark: Option<String>,
+        }
+
+        let remark = serde_json::from_str::<Remark>(json_data).ok()?.remark?;
+        let lower = remark.to_lowercase();
+        (lower.contains("error") || lower.contains("timed out") || lower.contains("out of memory"))
+            .then_some(remark)
+    }
+
+    /// Extract the error messages from an Overpass HTML error page
+    fn parse_error_page(body: &str) -> Option<String> {
+        let messages: Vec<String> = body
+            .split("Error</strong>:")
+            .skip(1)
+            .filter_map(|part| part.split("</p>").next())
+            .map(|message| {
+                let mut text = String::new();
+                let mut in_tag = false;
+                for c in message.chars() {
+                    match c {
+                        '<' => in_tag = true,
+                        '>' => in_tag = false,
+                        c if !in_tag => text.push(c),
+                        _ => {}
+                    }
+                }
+                text.replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&amp;", "&")
+                    .trim()
+                    .to_string()
+            })
+            .filter(|message| !message.is_empty())
+            .collect();
+
+        (!messages.is_empty()).then(|| messages.join("; "))
+    }
+}
+
+#[async_trait]
+impl OsmDataProvider for OverpassProvider {
+    fn provider_type(&self) -> &'static str {
+        "overpass"
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        self.fetch(config, None).await
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        self.fetch(config, Some(on_chunk)).await
+    }
+
+    #[tracing::instrument(name = "resolve_region", skip_all, fields(region = ?region))]
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        match region {
+            Region::BoundingBox(bbox) => Ok(bbox.clone()),
+
+            Region::CenterRadius {
+                lat,
+                lon,
+                radius_km,
+            } => Ok(Self::radius_to_bbox(*lat, *lon, *radius_km)),
+
+            Region::City { name } => self.geocoder.geocode(name).await,
+        }
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        match region {
+            Region::City { name } => self.geocoder.geocode_candidates(name).await,
+            _ => Ok(vec![region_candidate(
+                region,
+                self.resolve_region(region).await?,
+            )]),
         }
     }
 
+    async fn test_availability(&self) -> Result<()> {
+        tracing::debug!("Testing Overpass API availability");
+        self.check_endpoint(&self.endpoint()).await?;
+        tracing::debug!("Overpass API is available");
+        Ok(())
+    }
+
     fn capabilities(&self) -> ProviderCapabilities {
         ProviderCapabilities {
             supports_real_time: true,
@@ -402,6 +887,61 @@ impl OsmDataProvider for OverpassProvider {
     }
 }
 
+/// Format a bounding box as `south,west,north,east`
+fn format_bbox(bbox: &BoundingBox) -> String {
+    format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east)
+}
+
+/// Parse an Overpass timestamp such as `2024-05-01T12:00:00Z`
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Split a bounding box into `n`×`n` equal cells
+fn split_bbox(bbox: &BoundingBox, n: usize) -> Vec<BoundingBox> {
+    let n = n.max(1);
+    let (lat_step, lon_step) = (bbox.height() / n as f64, bbox.width() / n as f64);
+    (0..n * n)
+        .map(|index| {
+            let (row, column) = ((index / n) as f64, (index % n) as f64);
+            BoundingBox::new(
+                bbox.south + row * lat_step,
+                bbox.west + column * lon_step,
+                bbox.south + (row + 1.0) * lat_step,
+                bbox.west + (column + 1.0) * lon_step,
+            )
+        })
+        .collect()
+}
+
+/// Combine exact values of the same key into one `~` query, e.g. the road types of a feature
+fn merge_values(tag_queries: &[OsmTagQuery]) -> Vec<OsmTagQuery> {
+    let mergeable = |query: &OsmTagQuery| query.value.is_some() && !query.regex && !query.exclude;
+    let mut merged = Vec::new();
+    let mut done = HashSet::new();
+    for query in tag_queries {
+        if !mergeable(query) {
+            merged.push(query.clone());
+            continue;
+        }
+        if !done.insert(query.key.as_str()) {
+            continue;
+        }
+        let values: Vec<&str> = tag_queries
+            .iter()
+            .filter(|other| mergeable(other) && other.key == query.key)
+            .filter_map(|other| other.value.as_deref())
+            .collect();
+        merged.push(match values[..] {
+            [_] => query.clone(),
+            _ => OsmTagQuery::any_of(&query.key, values),
+        });
+    }
+    merged
+}
+
 impl Default for OverpassProvider {
     fn default() -> Self {
         Self::new()
@@ -411,11 +951,22 @@ impl Default for OverpassProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::http::HttpError;
     use crate::{FeatureSet, OsmConfigBuilder, OsmFeature};
 
+    /// Provider whose requests go to an [`EndpointServer`], for tests without an HTTP backend
+    fn offline_provider() -> OverpassProvider {
+        OverpassProvider::with_http_client(
+            "https://overpass-api.de/api/interpreter",
+            Arc::new(EndpointServer {
+                urls: Default::default(),
+            }),
+        )
+    }
+
     #[test]
     fn test_overpass_provider_basic() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
         assert_eq!(provider.provider_type(), "overpass");
         assert_eq!(provider.base_url, "https://overpass-api.de/api/interpreter");
 
@@ -429,6 +980,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(any(feature = "reqwest-client", feature = "ehttp-client"))]
     fn test_overpass_provider_custom_url() {
         let custom_url = "https://lz4.overpass-api.de/api/interpreter";
         let provider = OverpassProvider::with_base_url(custom_url);
@@ -437,7 +989,7 @@ mod tests {
 
     #[test]
     fn test_build_overpass_query() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
         let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
         let config = OsmConfigBuilder::new()
             .features(FeatureSet::urban())
@@ -451,16 +1003,17 @@ mod tests {
         assert!(query.contains("52,13,53,14")); // bbox coordinates
         assert!(query.contains("out geom"));
 
-        // Should contain feature queries
-        assert!(query.contains("way[\"highway\"]"));
+        // Should contain feature queries, with the road types in one statement
+        assert!(query.contains("way[\"highway\"~\"^("));
+        assert_eq!(query.matches("way[\"highway\"").count(), 1);
         assert!(query.contains("way[\"building\"]"));
-        assert!(query.contains("way[\"leisure\"]"));
-        assert!(query.contains("way[\"natural\"]"));
+        assert!(query.contains("way[\"leisure\""));
+        assert!(query.contains("way[\"natural\""));
     }
 
     #[test]
     fn test_build_overpass_query_with_custom_features() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
         let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
         let config = OsmConfigBuilder::new()
             .features(
@@ -483,9 +1036,65 @@ mod tests {
         assert!(!query.contains("way[\"highway\"]"));
     }
 
+    #[test]
+    fn test_build_overpass_query_with_exclusions() {
+        let provider = offline_provider();
+        let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
+        let config = OsmConfigBuilder::new()
+            .features(
+                FeatureSet::new()
+                    .with_feature(OsmFeature::Buildings)
+                    .with_custom_query(OsmTagQuery::exclude("building", Some("garage")))
+                    .excluding("disused", None::<String>),
+            )
+            .build();
+
+        let query = provider.build_overpass_query(&bbox, &config);
+        assert!(
+            query.contains(
+                "way[\"building\"][\"building\"!=\"garage\"][!\"disused\"](52,13,53,14);"
+            )
+        );
+        assert!(config.features.custom_queries().is_empty());
+    }
+
+    #[test]
+    fn test_build_overpass_query_with_city_area() {
+        let bbox = BoundingBox::new(52.0, 13.0, 53.0, 14.0);
+        let config = OsmConfigBuilder::new()
+            .city("Berlin, DE")
+            .features(FeatureSet::new().with_feature(OsmFeature::Roads))
+            .build();
+
+        let query = offline_provider()
+            .with_city_admin_level(4)
+            .build_overpass_query(&bbox, &config);
+        assert!(query.contains(
+            "area[\"name\"=\"Berlin\"][\"boundary\"=\"administrative\"][\"admin_level\"=\"4\"]->.searchArea;"
+        ));
+        assert!(query.contains("(area.searchArea)(52,13,53,14);"));
+
+        // Areas are opt-in and only apply to city regions
+        let plain = offline_provider().build_overpass_query(&bbox, &config);
+        assert!(!plain.contains("searchArea"));
+        let bbox_config = OsmConfigBuilder::new().bbox(52.0, 13.0, 53.0, 14.0).build();
+        let query = offline_provider()
+            .with_city_areas()
+            .build_overpass_query(&bbox, &bbox_config);
+        assert!(!query.contains("searchArea"));
+
+        let quoted = OsmConfigBuilder::new().city("Sankt \"Wendel\"").build();
+        let query = offline_provider()
+            .with_city_areas()
+            .build_overpass_query(&bbox, &quoted);
+        assert!(query.contains(
+            "area[\"name\"=\"Sankt \\\"Wendel\\\"\"][\"boundary\"=\"administrative\"]->"
+        ));
+    }
+
     #[test]
     fn test_should_include_relations() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
 
         // These should include relations
         assert!(provider.should_include_relations("building"));
@@ -503,7 +1112,7 @@ mod tests {
 
     #[test]
     fn test_should_include_nodes() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
 
         // These should include nodes
         assert!(provider.should_include_nodes("amenity"));
@@ -566,7 +1175,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_resolve_region_bounding_box() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
         let region = Region::bbox(52.0, 13.0, 53.0, 14.0);
 
         let result = provider.resolve_region(&region).await.unwrap();
@@ -578,7 +1187,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_resolve_region_center_radius() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
         let region = Region::center_radius(52.5, 13.4, 5.0);
 
         let result = provider.resolve_region(&region).await.unwrap();
@@ -605,9 +1214,183 @@ mod tests {
         assert!(area > 5000.0); // Should trigger our validation
     }
 
+    #[test]
+    fn test_parse_data_timestamp() {
+        let response = r#"{
+            "version": 0.6,
+            "osm3s": {
+                "timestamp_osm_base": "2024-05-01T12:30:00Z",
+                "copyright": "The data included in this document is from www.openstreetmap.org."
+            },
+            "elements": []
+        }"#;
+        let timestamp = OverpassProvider::parse_data_timestamp(response).unwrap();
+        assert_eq!(timestamp.to_rfc3339(), "2024-05-01T12:30:00+00:00");
+        assert_eq!(
+            OverpassProvider::parse_data_timestamp(r#"{"elements": []}"#),
+            None
+        );
+
+        let metadata = OsmMetadata::new("test", "overpass").with_data_timestamp(timestamp);
+        assert!(metadata.data_age().unwrap() > std::time::Duration::from_secs(3600));
+        assert!(metadata.is_stale(std::time::Duration::from_secs(3600)));
+        assert!(!OsmMetadata::new("test", "overpass").is_stale(std::time::Duration::ZERO));
+    }
+
+    #[test]
+    fn test_parse_remark() {
+        let timeout = r#"{
+            "version": 0.6,
+            "elements": [],
+            "remark": "runtime error: Query timed out in \"query\" at line 3 after 26 seconds."
+        }"#;
+        let remark = OverpassProvider::parse_remark(timeout).unwrap();
+        assert!(remark.contains("timed out"));
+
+        // Informational remarks and normal responses pass through
+        let info = r#"{"elements": [], "remark": "Data may be incomplete near the poles"}"#;
+        assert_eq!(OverpassProvider::parse_remark(info), None);
+        assert_eq!(OverpassProvider::parse_remark(r#"{"elements": []}"#), None);
+    }
+
+    #[test]
+    fn test_parse_error_page() {
+        let page = r#"<body>
+<p>The data included in this document is from www.openstreetmap.org.</p>
+<p><strong style="color:#FF0000">Error</strong>: line 2: parse error: &apos;;&apos; expected - &apos;)&apos; found. </p>
+<p><strong style="color:#FF0000">Error</strong>: line 3: static error: Unknown type "wy" </p>
+</body>"#;
+        let message = OverpassProvider::parse_error_page(page).unwrap();
+        assert_eq!(
+            message,
+            "line 2: parse error: ';' expected - ')' found.; line 3: static error: Unknown type \"wy\""
+        );
+        assert!(matches!(
+            ProviderError::from_remark(message),
+            ProviderError::Syntax { .. }
+        ));
+
+        assert_eq!(
+            OverpassProvider::parse_error_page("Too Many Requests"),
+            None
+        );
+    }
+
+    /// Overpass stand-in answering the full area with a truncated page
+    struct SlicingServer {
+        full_bbox: String,
+        queries: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for SlicingServer {
+        async fn get(&self, _url: &str) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn post_form(
+            &self,
+            _url: &str,
+            form_data: &[(&str, &str)],
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            let query = form_data[0].1.to_string();
+            let mut queries = self.queries.lock().unwrap();
+            queries.push(query.clone());
+
+            let node = |id: usize| json!({"type": "node", "id": id, "lat": 52.0005, "lon": 13.0005, "tags": {"amenity": "cafe"}});
+            // Three elements reach the limit; quadrants share node 1
+            let elements: Vec<Value> = if query.contains(&self.full_bbox) {
+                (1..=3).map(node).collect()
+            } else {
+                vec![node(1), node(100 + queries.len())]
+            };
+            Ok(crate::http::HttpResponse {
+                status: 200,
+                body: json!({ "elements": elements }).to_string(),
+                headers: std::collections::HashMap::new(),
+                transfer: None,
+            })
+        }
+
+        async fn post_json(
+            &self,
+            _url: &str,
+            _json: &str,
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn test_connectivity(&self, _url: &str) -> crate::http::HttpResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_in_slices() {
+        let server = Arc::new(SlicingServer {
+            full_bbox: "52,13,52.001,13.001".to_string(),
+            queries: std::sync::Mutex::new(Vec::new()),
+        });
+        let provider = OverpassProvider::with_http_client("http://overpass.test", server.clone())
+            .with_max_elements_per_query(3);
+        let config = OsmConfigBuilder::new()
+            .bbox(52.0, 13.0, 52.001, 13.001)
+            .features(FeatureSet::new().with_feature(OsmFeature::Amenities))
+            .build();
+
+        let data = provider.fetch_data(&config).await.unwrap();
+        let queries = server.queries.lock().unwrap();
+        // The truncated full query is replaced by its four quadrants
+        assert_eq!(queries.len(), 5);
+        assert!(queries.iter().all(|query| query.contains("out geom 3;")));
+        assert_eq!(data.metadata.element_count, Some(5));
+        assert_eq!(data.metadata.extra.get("slices"), Some(&"5".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_response_size_limit() {
+        let server = Arc::new(SlicingServer {
+            full_bbox: "52,13,52.001,13.001".to_string(),
+            queries: std::sync::Mutex::new(Vec::new()),
+        });
+        let provider = OverpassProvider::with_http_client("http://overpass.test", server.clone())
+            .with_max_response_bytes(100);
+        let config = OsmConfigBuilder::new()
+            .bbox(52.0, 13.0, 52.001, 13.001)
+            .build();
+
+        let error = provider.fetch_data(&config).await.unwrap_err();
+        assert!(matches!(
+            error,
+            OsmTilesError::Network(NetworkError::ResponseTooLarge {
+                limit_bytes: 100,
+                ..
+            })
+        ));
+        assert!(!error.is_retryable());
+        let streamed = provider
+            .fetch_data_streaming(&config, &mut |_: &[u8]| {})
+            .await;
+        assert!(streamed.is_err());
+
+        let unlimited = OverpassProvider::with_http_client("http://overpass.test", server)
+            .with_max_response_bytes(100)
+            .without_response_limit();
+        assert!(unlimited.fetch_data(&config).await.is_ok());
+    }
+
+    #[test]
+    fn test_split_bbox() {
+        let cells = split_bbox(&BoundingBox::new(52.0, 13.0, 53.0, 14.0), 2);
+        assert_eq!(cells.len(), 4);
+        let area: f64 = cells.iter().map(|cell| cell.height() * cell.width()).sum();
+        assert!((area - 1.0).abs() < 1e-9);
+        assert!(cells.iter().any(|cell| cell.contains(52.9, 13.9)));
+    }
+
     #[test]
     fn test_timeout_calculation() {
-        let provider = OverpassProvider::new();
+        let provider = offline_provider();
         let config = OsmConfigBuilder::new().timeout(120).build();
         let bbox = BoundingBox::new(52.0, 13.0, 52.1, 13.1);
 
@@ -615,8 +1398,147 @@ mod tests {
         assert!(query.contains("[timeout:120]"));
 
         // Test custom timeout override
-        let provider_with_timeout = OverpassProvider::new().with_timeout_secs(90);
+        let provider_with_timeout = offline_provider().with_timeout_secs(90);
         let query = provider_with_timeout.build_overpass_query(&bbox, &config);
         assert!(query.contains("[timeout:90]"));
     }
+
+    /// Overpass stand-in answering every query with one created node
+    struct DiffServer {
+        queries: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for DiffServer {
+        async fn get(&self, _url: &str) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn post_form(
+            &self,
+            _url: &str,
+            form_data: &[(&str, &str)],
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            self.queries
+                .lock()
+                .unwrap()
+                .push(form_data[0].1.to_string());
+            let body = r#"<osm><meta osm_base="2024-05-02T00:00:00Z"/><action type="create">
+                <node id="1" lat="52.0005" lon="13.0005"><tag k="amenity" v="cafe"/></node>
+                </action></osm>"#;
+            Ok(crate::http::HttpResponse {
+                status: 200,
+                body: body.to_string(),
+                headers: std::collections::HashMap::new(),
+                transfer: None,
+            })
+        }
+
+        async fn post_json(
+            &self,
+            _url: &str,
+            _json: &str,
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn test_connectivity(&self, _url: &str) -> crate::http::HttpResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_diff() {
+        let server = Arc::new(DiffServer {
+            queries: std::sync::Mutex::new(Vec::new()),
+        });
+        let provider = OverpassProvider::with_http_client("http://overpass.test", server.clone());
+        let config = OsmConfigBuilder::new()
+            .bbox(52.0, 13.0, 52.001, 13.001)
+            .features(FeatureSet::new().with_feature(OsmFeature::Amenities))
+            .build();
+        let since = parse_timestamp("2024-05-01T00:00:00Z").unwrap();
+
+        let diff = provider.fetch_diff(&config, since).await.unwrap();
+        let queries = server.queries.lock().unwrap();
+        assert!(queries[0].starts_with("[out:xml][adiff:\"2024-05-01T00:00:00Z\"][timeout:"));
+        assert!(queries[0].contains("out geom;"));
+        assert_eq!(diff.created.len(), 1);
+        assert_eq!(diff.data_timestamp, parse_timestamp("2024-05-02T00:00:00Z"));
+    }
+
+    /// Overpass stand-in where every URL containing "down" fails
+    struct EndpointServer {
+        urls: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for EndpointServer {
+        async fn get(&self, _url: &str) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn post_form(
+            &self,
+            url: &str,
+            _form_data: &[(&str, &str)],
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            self.urls.lock().unwrap().push(url.to_string());
+            if url.contains("down") {
+                return Err(HttpError::HttpStatus { status: 504 });
+            }
+            Ok(crate::http::HttpResponse {
+                status: 200,
+                body: json!({ "elements": [] }).to_string(),
+                headers: std::collections::HashMap::new(),
+                transfer: None,
+            })
+        }
+
+        async fn post_json(
+            &self,
+            _url: &str,
+            _json: &str,
+        ) -> crate::http::HttpResult<crate::http::HttpResponse> {
+            Err(HttpError::HttpStatus { status: 400 })
+        }
+
+        async fn test_connectivity(&self, _url: &str) -> crate::http::HttpResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_probing_and_proxy() {
+        let server = Arc::new(EndpointServer {
+            urls: std::sync::Mutex::new(Vec::new()),
+        });
+        let provider = OverpassProvider::with_http_client("http://down.test/api", server.clone())
+            .with_endpoints(["http://down.test/api", "http://up.test/api"])
+            .with_proxy("https://proxy.test/?url={url}");
+
+        let health = provider.probe_endpoints().await;
+        assert_eq!(health.len(), 2);
+        assert!(!health[0].is_healthy() && health[0].error.is_some());
+        assert!(health[1].is_healthy());
+
+        assert_eq!(provider.endpoint(), "http://down.test/api");
+        assert_eq!(
+            provider.select_fastest_endpoint().await.unwrap(),
+            "http://up.test/api"
+        );
+        assert!(provider.test_availability().await.is_ok());
+        assert_eq!(
+            server.urls.lock().unwrap().last().unwrap(),
+            "https://proxy.test/?url=http%3A%2F%2Fup.test%2Fapi"
+        );
+
+        let appended = OverpassProvider::with_http_client("http://down.test/api", server.clone())
+            .with_proxy("https://proxy.test/");
+        assert_eq!(
+            appended.request_url("http://up.test/api"),
+            "https://proxy.test/http://up.test/api"
+        );
+        assert!(appended.select_fastest_endpoint().await.is_err());
+    }
 }
@@ -0,0 +1,137 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+use super::{OsmDataProvider, ProviderFactory};
+use crate::{OsmTilesError, Result};
+
+/// Function creating a fresh provider instance
+pub type ProviderConstructor = Arc<dyn Fn() -> Box<dyn OsmDataProvider> + Send + Sync>;
+
+/// Process-wide registry of named providers
+///
+/// "overpass" and "mock" are registered from the start. Downstream crates
+/// register their own providers once at startup, after which CLIs and the
+/// Bevy plugin can create them from configuration strings:
+///
+/// ```rust
+/// use bevy_osm_tiles::{MockProvider, ProviderRegistry};
+///
+/// ProviderRegistry::register("my-tiles", || Box::new(MockProvider::synthetic(1, 200)));
+/// let provider = ProviderRegistry::create("my-tiles").unwrap();
+/// # ProviderRegistry::unregister("my-tiles");
+/// ```
+pub struct ProviderRegistry;
+
+/// Registered constructors in registration order
+type Entries = Vec<(String, ProviderConstructor)>;
+
+fn entries() -> &'static RwLock<Entries> {
+    static ENTRIES: OnceLock<RwLock<Entries>> = OnceLock::new();
+    ENTRIES.get_or_init(|| {
+        let builtin: [(&str, ProviderConstructor); 2] = [
+            (
+                "overpass",
+                Arc::new(|| Box::new(ProviderFactory::overpass())),
+            ),
+            ("mock", Arc::new(|| Box::new(ProviderFactory::mock()))),
+        ];
+        RwLock::new(
+            builtin
+                .into_iter()
+                .map(|(name, constructor)| (name.to_string(), constructor))
+                .collect(),
+        )
+    })
+}
+
+impl ProviderRegistry {
+    /// Register a provider under `name`, replacing any provider of the same name
+    pub fn register(
+        name: impl Into<String>,
+        constructor: impl Fn() -> Box<dyn OsmDataProvider> + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        let constructor: ProviderConstructor = Arc::new(constructor);
+        let mut entries = entries().write().unwrap();
+        match entries.iter_mut().find(|(existing, _)| *existing == name) {
+            Some(entry) => entry.1 = constructor,
+            None => entries.push((name, constructor)),
+        }
+    }
+
+    /// Remove a provider; returns whether it was registered
+    pub fn unregister(name: &str) -> bool {
+        let mut entries = entries().write().unwrap();
+        let before = entries.len();
+        entries.retain(|(existing, _)| existing != name);
+        entries.len() != before
+    }
+
+    /// Check whether a provider is registered under `name`
+    pub fn is_registered(name: &str) -> bool {
+        entries()
+            .read()
+            .unwrap()
+            .iter()
+            .any(|(existing, _)| existing == name)
+    }
+
+    /// Get the names of all registered providers in registration order
+    pub fn names() -> Vec<String> {
+        entries()
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Create a new instance of the provider registered under `name`
+    pub fn create(name: &str) -> Result<Box<dyn OsmDataProvider>> {
+        // Release the lock before running user code, which may register providers itself
+        let constructor = entries()
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, constructor)| constructor.clone());
+
+        match constructor {
+            Some(constructor) => Ok(constructor()),
+            None => Err(OsmTilesError::Config(format!(
+                "Unknown provider: '{}'. Available providers: {:?}",
+                name,
+                Self::names()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockProvider;
+
+    #[test]
+    fn test_register_and_create() {
+        assert!(ProviderRegistry::is_registered("overpass"));
+        assert!(!ProviderRegistry::is_registered("registry-test"));
+        assert!(ProviderRegistry::create("registry-test").is_err());
+
+        ProviderRegistry::register("registry-test", || Box::new(MockProvider::new()));
+        assert!(ProviderRegistry::names().contains(&"registry-test".to_string()));
+        let provider = ProviderFactory::create_provider("registry-test").unwrap();
+        assert_eq!(provider.provider_type(), "mock");
+
+        // Registering again replaces the constructor instead of adding a second entry
+        ProviderRegistry::register("registry-test", || Box::new(MockProvider::synthetic(1, 8)));
+        let count = ProviderRegistry::names()
+            .iter()
+            .filter(|name| *name == "registry-test")
+            .count();
+        assert_eq!(count, 1);
+
+        assert!(ProviderRegistry::unregister("registry-test"));
+        assert!(!ProviderRegistry::unregister("registry-test"));
+        assert!(ProviderRegistry::create("registry-test").is_err());
+    }
+}
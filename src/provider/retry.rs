@@ -0,0 +1,449 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+use super::{ChunkSink, GeocodeCandidate, OsmData, OsmDataProvider, ProviderCapabilities};
+use crate::{BoundingBox, OsmConfig, OsmTilesError, Region, Result};
+
+/// Exponential backoff settings for retrying failed downloads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry (in milliseconds)
+    pub initial_delay_ms: u64,
+    /// Upper bound for any single delay (in milliseconds)
+    pub max_delay_ms: u64,
+    /// Factor the delay grows by after each failed attempt
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 2_000,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Set the total number of attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the initial and maximum delay (in milliseconds)
+    pub fn with_delays(mut self, initial_ms: u64, max_ms: u64) -> Self {
+        self.initial_delay_ms = initial_ms;
+        self.max_delay_ms = max_ms;
+        self
+    }
+
+    /// Get the delay before retrying after the given failed attempt (1-based)
+    pub fn delay_after_attempt(&self, attempt: u32) -> u64 {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(attempt.saturating_sub(1) as i32);
+        ((self.initial_delay_ms as f64 * factor) as u64).min(self.max_delay_ms)
+    }
+
+    /// Get the delay before retrying `error` after the given failed attempt (1-based)
+    ///
+    /// Never sooner than a throttling server's `Retry-After`; `None` if the
+    /// server asks for a longer wait than `max_delay_ms`, so the error is
+    /// reported instead of stalling the load.
+    pub fn retry_delay(&self, attempt: u32, error: &OsmTilesError) -> Option<u64> {
+        let backoff = self.delay_after_attempt(attempt);
+        match error.retry_after() {
+            Some(wait) => {
+                let wait_ms = u64::try_from(wait.as_millis()).unwrap_or(u64::MAX);
+                (wait_ms <= self.max_delay_ms).then(|| backoff.max(wait_ms))
+            }
+            None => Some(backoff),
+        }
+    }
+}
+
+/// Structured status of a retry, for display in loading screens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryStatus {
+    /// Attempt that just failed (1-based)
+    pub attempt: u32,
+    /// Total number of attempts allowed
+    pub max_attempts: u32,
+    /// Time until the next attempt starts (in milliseconds)
+    pub next_retry_in_ms: u64,
+    /// Error that caused the retry
+    pub error: String,
+}
+
+impl RetryStatus {
+    /// Get the time until the next attempt in seconds
+    pub fn next_retry_in_secs(&self) -> f32 {
+        self.next_retry_in_ms as f32 / 1000.0
+    }
+}
+
+impl fmt::Display for RetryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Attempt {} of {} failed, retrying in {:.0}s",
+            self.attempt,
+            self.max_attempts,
+            self.next_retry_in_secs().ceil()
+        )
+    }
+}
+
+/// Callback notified before each retry
+pub type RetryObserver = Arc<dyn Fn(&RetryStatus) + Send + Sync>;
+
+/// Provider wrapper retrying transient failures with exponential backoff
+///
+/// Errors for which [`OsmTilesError::is_retryable`] holds are retried,
+/// waiting at least as long as a `Retry-After` header asks, unless that is
+/// longer than the policy's `max_delay_ms`; configuration
+/// and parse errors fail immediately. The delay does not block the executor;
+/// in browsers it is waited out with `setTimeout`.
+///
+/// Streamed fetches are retried as a whole: the sink is restarted before an
+/// attempt that follows a failed one which already delivered chunks.
+pub struct RetryingProvider {
+    inner: Box<dyn OsmDataProvider>,
+    policy: RetryPolicy,
+    observer: Option<RetryObserver>,
+}
+
+impl RetryingProvider {
+    /// Wrap a provider with the given retry policy
+    pub fn new(inner: Box<dyn OsmDataProvider>, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            observer: None,
+        }
+    }
+
+    /// Set a callback notified before each retry
+    pub fn with_observer(
+        mut self,
+        observer: impl Fn(&RetryStatus) + Send + Sync + 'static,
+    ) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Get the retry policy
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Fetch with retries, streaming every attempt to `on_chunk` if given
+    async fn fetch(
+        &self,
+        config: &OsmConfig,
+        mut on_chunk: Option<&mut dyn ChunkSink>,
+    ) -> Result<OsmData> {
+        let max_attempts = self.policy.max_attempts.max(1);
+        let mut attempt = 1;
+
+        loop {
+            let mut received = false;
+            let result = match on_chunk.as_deref_mut() {
+                Some(on_chunk) => {
+                    let mut sink = TrackingSink {
+                        inner: on_chunk,
+                        received: false,
+                    };
+                    let result = self.inner.fetch_data_streaming(config, &mut sink).await;
+                    received = sink.received;
+                    result
+                }
+                None => self.inner.fetch_data(config).await,
+            };
+            let error = match result {
+                Ok(data) => {
+                    let data = if attempt > 1 {
+                        OsmData {
+                            metadata: data.metadata.with_extra("attempts", attempt.to_string()),
+                            ..data
+                        }
+                    } else {
+                        data
+                    };
+                    return Ok(data);
+                }
+                Err(error) => error,
+            };
+
+            if attempt >= max_attempts || !error.is_retryable() {
+                return Err(error);
+            }
+            let Some(delay_ms) = self.policy.retry_delay(attempt, &error) else {
+                tracing::warn!("Server asked to wait longer than the retry policy allows");
+                return Err(error);
+            };
+
+            let status = RetryStatus {
+                attempt,
+                max_attempts,
+                next_retry_in_ms: delay_ms,
+                error: error.to_string(),
+            };
+            tracing::warn!("{}: {}", status, status.error);
+            if let Some(observer) = &self.observer {
+                observer(&status);
+            }
+
+            crate::http::sleep(status.next_retry_in_ms).await;
+            // The next attempt starts over, so what the failed one delivered is dropped
+            if received && let Some(on_chunk) = on_chunk.as_deref_mut() {
+                on_chunk.restart();
+            }
+            attempt += 1;
+        }
+    }
+}
+
+/// Sink forwarding chunks and remembering whether any arrived since the last restart
+struct TrackingSink<'a> {
+    inner: &'a mut dyn ChunkSink,
+    received: bool,
+}
+
+impl ChunkSink for TrackingSink<'_> {
+    fn chunk(&mut self, chunk: &[u8]) {
+        self.received = true;
+        self.inner.chunk(chunk);
+    }
+
+    fn restart(&mut self) {
+        self.received = false;
+        self.inner.restart();
+    }
+}
+
+#[async_trait]
+impl OsmDataProvider for RetryingProvider {
+    fn provider_type(&self) -> &'static str {
+        self.inner.provider_type()
+    }
+
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        self.fetch(config, None).await
+    }
+
+    async fn fetch_data_streaming(
+        &self,
+        config: &OsmConfig,
+        on_chunk: &mut dyn ChunkSink,
+    ) -> Result<OsmData> {
+        self.fetch(config, Some(on_chunk)).await
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        self.inner.resolve_region(region).await
+    }
+
+    async fn resolve_region_candidates(&self, region: &Region) -> Result<Vec<GeocodeCandidate>> {
+        self.inner.resolve_region_candidates(region).await
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        self.inner.test_availability().await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MockProvider, NetworkError};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    /// Provider failing a fixed number of times before succeeding
+    struct FlakyProvider {
+        failures: u32,
+        permanent: bool,
+        calls: Arc<AtomicU32>,
+        mock: MockProvider,
+    }
+
+    #[async_trait]
+    impl OsmDataProvider for FlakyProvider {
+        fn provider_type(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                if self.permanent {
+                    return Err(OsmTilesError::Config("Invalid query".to_string()));
+                }
+                return Err(NetworkError::HttpError { status: 429 }.into());
+            }
+            self.mock.fetch_data(config).await
+        }
+
+        async fn fetch_data_streaming(
+            &self,
+            config: &OsmConfig,
+            on_chunk: &mut dyn ChunkSink,
+        ) -> Result<OsmData> {
+            // Failing attempts break off after the start of the response
+            let data = self.mock.fetch_data(config).await?;
+            let (head, tail) = data.raw_data.as_bytes().split_at(10);
+            on_chunk.chunk(head);
+            let data = self.fetch_data(config).await?;
+            on_chunk.chunk(tail);
+            Ok(data)
+        }
+
+        async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+            self.mock.resolve_region(region).await
+        }
+
+        async fn test_availability(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities::default()
+        }
+    }
+
+    fn flaky(failures: u32, permanent: bool) -> (Box<dyn OsmDataProvider>, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let provider = FlakyProvider {
+            failures,
+            permanent,
+            calls: calls.clone(),
+            mock: MockProvider::new(),
+        };
+        (Box::new(provider), calls)
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::default().with_delays(1, 5)
+    }
+
+    #[test]
+    fn test_backoff_delays() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay_after_attempt(1), 2_000);
+        assert_eq!(policy.delay_after_attempt(2), 4_000);
+        assert_eq!(policy.delay_after_attempt(10), 30_000);
+
+        let status = RetryStatus {
+            attempt: 2,
+            max_attempts: 3,
+            next_retry_in_ms: 3_500,
+            error: "busy".to_string(),
+        };
+        assert_eq!(status.to_string(), "Attempt 2 of 3 failed, retrying in 4s");
+
+        let throttled = |secs| OsmTilesError::RateLimited {
+            retry_after: Some(Duration::from_secs(secs)),
+        };
+        assert_eq!(policy.retry_delay(1, &throttled(10)), Some(10_000));
+        assert_eq!(policy.retry_delay(3, &throttled(1)), Some(8_000));
+        assert_eq!(policy.retry_delay(1, &throttled(3_600)), None);
+        let timeout = NetworkError::Timeout { seconds: 5 }.into();
+        assert_eq!(policy.retry_delay(1, &timeout), Some(2_000));
+    }
+
+    #[tokio::test]
+    async fn test_retries_report_status() {
+        let (inner, calls) = flaky(2, false);
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let observed = statuses.clone();
+        let provider = RetryingProvider::new(inner, fast_policy())
+            .with_observer(move |status| observed.lock().unwrap().push(status.clone()));
+
+        let data = provider
+            .fetch_data(&OsmConfig::for_city("Berlin"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(data.metadata.extra.get("attempts"), Some(&"3".to_string()));
+
+        let statuses = statuses.lock().unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!((statuses[0].attempt, statuses[0].max_attempts), (1, 3));
+        assert_eq!(statuses[1].next_retry_in_ms, 2);
+        assert!(statuses[1].error.contains("429"));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let (inner, calls) = flaky(5, false);
+        let provider = RetryingProvider::new(inner, fast_policy().with_max_attempts(2));
+
+        let result = provider.fetch_data(&OsmConfig::for_city("Berlin")).await;
+        assert!(matches!(result, Err(OsmTilesError::Network(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let (inner, calls) = flaky(1, true);
+        let provider = RetryingProvider::new(inner, fast_policy());
+
+        let result = provider.fetch_data(&OsmConfig::for_city("Berlin")).await;
+        assert!(matches!(result, Err(OsmTilesError::Config(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// Sink collecting the chunks received since the last restart
+    #[derive(Default)]
+    struct CollectingSink {
+        body: Vec<u8>,
+        restarts: usize,
+    }
+
+    impl ChunkSink for CollectingSink {
+        fn chunk(&mut self, chunk: &[u8]) {
+            self.body.extend_from_slice(chunk);
+        }
+
+        fn restart(&mut self) {
+            self.body.clear();
+            self.restarts += 1;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_retries_restart_the_sink() {
+        let (inner, calls) = flaky(2, false);
+        let provider = RetryingProvider::new(inner, fast_policy());
+
+        let mut sink = CollectingSink::default();
+        let data = provider
+            .fetch_data_streaming(&OsmConfig::for_city("Berlin"), &mut sink)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(sink.restarts, 2);
+        assert_eq!(sink.body, data.raw_data.as_bytes());
+    }
+}
@@ -0,0 +1,417 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+use tracing::field::Empty;
+
+use super::shapefile_decoder::{
+    ShapeRecord, ShapeType, decode_dbf, decode_shapes, shape_ring_area,
+};
+use super::{OsmData, OsmDataProvider, ProviderCapabilities};
+use crate::{
+    BoundingBox, OsmConfig, OsmDataFormat, OsmMetadata, OsmTagQuery, OsmTilesError, Region, Result,
+};
+
+/// Maps a dataset attribute to an OSM tag
+///
+/// The rule matches rows whose `field` equals `value` (any non-empty value
+/// when `value` is `None`, compared case-insensitively) and adds `tag`. A tag
+/// without a value copies the attribute value, so `ROADTYPE=primary` can
+/// become `highway=primary`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeRule {
+    /// Attribute field name (case-insensitive)
+    pub field: String,
+    /// Attribute value the rule matches, or `None` for any value
+    pub value: Option<String>,
+    /// OSM tag added to matching rows
+    pub tag: OsmTagQuery,
+}
+
+impl AttributeRule {
+    /// Create a rule adding `tag` to rows whose `field` matches `value`
+    pub fn new(
+        field: impl Into<String>,
+        value: Option<impl Into<String>>,
+        tag: OsmTagQuery,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            value: value.map(|v| v.into()),
+            tag,
+        }
+    }
+
+    /// Create a rule copying the value of `field` into the OSM tag `key`
+    pub fn copy(field: impl Into<String>, key: impl Into<String>) -> Self {
+        Self::new(field, None::<String>, OsmTagQuery::new(key, None::<String>))
+    }
+
+    /// Get the tag this rule adds for a row, if it matches
+    pub fn apply(&self, attributes: &HashMap<String, String>) -> Option<(String, String)> {
+        let actual = attributes
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(&self.field))
+            .map(|(_, value)| value)?;
+        if let Some(expected) = &self.value
+            && !actual.eq_ignore_ascii_case(expected)
+        {
+            return None;
+        }
+
+        let value = self.tag.value.clone().unwrap_or_else(|| actual.clone());
+        Some((self.tag.key.clone(), value))
+    }
+}
+
+/// Provider reading a local ESRI shapefile instead of querying Overpass
+///
+/// Every record whose attributes match at least one [`AttributeRule`] is
+/// emitted as an Overpass-style JSON element, so the regular parser and grid
+/// generator can consume proprietary datasets unchanged. Coordinates must be
+/// WGS84 longitude/latitude (EPSG:4326); reproject other datasets first.
+/// Polygon holes are dropped.
+pub struct ShapefileProvider {
+    /// Dataset name reported as the data source
+    pub name: String,
+    /// Attribute rules deciding which records are kept and how they are tagged
+    pub rules: Vec<AttributeRule>,
+    /// Whether unmapped attributes are kept as extra tags
+    pub keep_attributes: bool,
+    shp: Vec<u8>,
+    dbf: Option<Vec<u8>>,
+}
+
+impl ShapefileProvider {
+    /// Create a provider from the contents of a `.shp` and optional `.dbf` file
+    pub fn from_bytes(name: impl Into<String>, shp: Vec<u8>, dbf: Option<Vec<u8>>) -> Self {
+        Self {
+            name: name.into(),
+            rules: Vec::new(),
+            keep_attributes: false,
+            shp,
+            dbf,
+        }
+    }
+
+    /// Read a `.shp` file and the `.dbf` file next to it, if present
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let read = |path: &std::path::Path| {
+            std::fs::read(path).map_err(|source| OsmTilesError::Io {
+                path: path.display().to_string(),
+                source,
+            })
+        };
+
+        let shp = read(path)?;
+        let dbf_path = path.with_extension("dbf");
+        let dbf = if dbf_path.exists() {
+            Some(read(&dbf_path)?)
+        } else {
+            None
+        };
+        Ok(Self::from_bytes(path.display().to_string(), shp, dbf))
+    }
+
+    /// Add an attribute rule
+    pub fn with_rule(mut self, rule: AttributeRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Replace all attribute rules
+    pub fn with_rules(mut self, rules: Vec<AttributeRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Keep unmapped attributes as extra tags (e.g., `name`)
+    pub fn with_attributes(mut self, keep_attributes: bool) -> Self {
+        self.keep_attributes = keep_attributes;
+        self
+    }
+
+    /// Build the OSM tags of a record, or `None` if no rule matches
+    fn record_tags(&self, attributes: &HashMap<String, String>) -> Option<Map<String, Value>> {
+        let mapped: Vec<(String, String)> = self
+            .rules
+            .iter()
+            .filter_map(|rule| rule.apply(attributes))
+            .collect();
+        if mapped.is_empty() {
+            return None;
+        }
+
+        let mut tags = Map::new();
+        if self.keep_attributes {
+            for (field, value) in attributes {
+                tags.insert(field.to_lowercase(), Value::String(value.clone()));
+            }
+        }
+        for (key, value) in mapped {
+            tags.insert(key, Value::String(value));
+        }
+        Some(tags)
+    }
+
+    /// Convert one record into Overpass JSON elements
+    fn record_elements(
+        record: &ShapeRecord,
+        tags: &Map<String, Value>,
+        next_id: &mut i64,
+    ) -> Vec<Value> {
+        let mut elements = Vec::new();
+        for part in &record.parts {
+            let keep = match record.shape_type {
+                ShapeType::Point | ShapeType::MultiPoint => !part.is_empty(),
+                ShapeType::PolyLine => part.len() >= 2,
+                ShapeType::Polygon => part.len() >= 4 && shape_ring_area(part) < 0.0,
+                ShapeType::Null => false,
+            };
+            if !keep {
+                continue;
+            }
+
+            *next_id += 1;
+            let element = if part.len() == 1 {
+                let (lon, lat) = part[0];
+                json!({"type": "node", "id": *next_id, "lat": lat, "lon": lon, "tags": tags})
+            } else {
+                let geometry: Vec<Value> = part
+                    .iter()
+                    .map(|(lon, lat)| json!({"lat": lat, "lon": lon}))
+                    .collect();
+                json!({"type": "way", "id": *next_id, "geometry": geometry, "tags": tags})
+            };
+            elements.push(element);
+        }
+        elements
+    }
+}
+
+/// Check whether a record extent overlaps a bounding box
+fn overlaps(record: &ShapeRecord, bbox: &BoundingBox) -> bool {
+    record
+        .extent()
+        .is_some_and(|(min_lon, min_lat, max_lon, max_lat)| {
+            min_lon <= bbox.east
+                && max_lon >= bbox.west
+                && min_lat <= bbox.north
+                && max_lat >= bbox.south
+        })
+}
+
+#[async_trait]
+impl OsmDataProvider for ShapefileProvider {
+    fn provider_type(&self) -> &'static str {
+        "shapefile"
+    }
+
+    #[tracing::instrument(
+        name = "fetch_data",
+        skip_all,
+        fields(provider = "shapefile", region = ?config.region, element_count = Empty, records = Empty)
+    )]
+    async fn fetch_data(&self, config: &OsmConfig) -> Result<OsmData> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_time = Instant::now();
+
+        let bbox = config.aligned_bounds(self.resolve_region(&config.region).await?);
+        let records = decode_shapes(&self.shp)?;
+        let rows = match &self.dbf {
+            Some(dbf) => decode_dbf(dbf)?,
+            None => Vec::new(),
+        };
+        let no_attributes = HashMap::new();
+
+        let mut elements = Vec::new();
+        let mut next_id = 0;
+        for (index, record) in records.iter().enumerate() {
+            if !overlaps(record, &bbox) {
+                continue;
+            }
+            let attributes = rows.get(index).unwrap_or(&no_attributes);
+            let Some(tags) = self.record_tags(attributes) else {
+                continue;
+            };
+            elements.extend(Self::record_elements(record, &tags, &mut next_id));
+        }
+
+        let element_count = elements.len() as u32;
+        let raw_data = json!({ "elements": elements }).to_string();
+
+        let processing_time = {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                start_time.elapsed().as_millis() as u64
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                1u64 // Default value for WASM
+            }
+        };
+
+        let metadata = OsmMetadata::new(&self.name, self.provider_type())
+            .with_element_count(element_count)
+            .with_processing_time(processing_time)
+            .with_extra("record_count", records.len().to_string())
+            .with_extra(
+                "bbox",
+                format!("{},{},{},{}", bbox.south, bbox.west, bbox.north, bbox.east),
+            );
+
+        let span = tracing::Span::current();
+        span.record("element_count", element_count);
+        span.record("records", records.len());
+        tracing::info!("Mapped shapefile records");
+
+        Ok(OsmData {
+            raw_data,
+            format: OsmDataFormat::Json,
+            bounding_box: bbox,
+            metadata,
+        })
+    }
+
+    async fn resolve_region(&self, region: &Region) -> Result<BoundingBox> {
+        region.bounding_box().ok_or_else(|| {
+            OsmTilesError::Geographic(
+                "Shapefile provider cannot geocode city names, use a bounding box".into(),
+            )
+        })
+    }
+
+    async fn test_availability(&self) -> Result<()> {
+        decode_shapes(&self.shp).map(|_| ())
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supports_real_time: false,
+            requires_network: false,
+            supports_geocoding: false,
+            max_area_km2: None,
+            supported_formats: vec![OsmDataFormat::Json],
+            rate_limit_rpm: None,
+            wasm_compatible: true,
+            notes: Some(format!(
+                "Local shapefile '{}' with {} attribute rules",
+                self.name,
+                self.rules.len()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::shapefile_decoder::encode::*;
+    use super::*;
+    use crate::{DefaultGridGenerator, GridGenerator, OsmConfigBuilder, OsmParser, TileType};
+
+    fn test_provider() -> ShapefileProvider {
+        let block = vec![
+            (13.401, 52.501),
+            (13.401, 52.503),
+            (13.403, 52.503),
+            (13.403, 52.501),
+            (13.401, 52.501),
+        ];
+        let shp = shp(&[
+            (3, vec![vec![(13.39, 52.5), (13.41, 52.5)]]),
+            (5, vec![block]),
+            (1, vec![vec![(13.395, 52.495)]]),
+            (1, vec![vec![(14.5, 53.5)]]),
+        ]);
+        let dbf = dbf(
+            &[("KIND", 8), ("CLASS", 10), ("NAME", 12)],
+            &[
+                &["ROAD", "primary", "Hauptstr"],
+                &["BLDG", "", ""],
+                &["TREE", "", ""],
+                &["ROAD", "primary", "Far away"],
+            ],
+        );
+
+        ShapefileProvider::from_bytes("city.shp", shp, Some(dbf)).with_rules(vec![
+            AttributeRule::new(
+                "kind",
+                Some("road"),
+                OsmTagQuery::new("highway", Some("road")),
+            ),
+            AttributeRule::copy("class", "highway"),
+            AttributeRule::new(
+                "KIND",
+                Some("BLDG"),
+                OsmTagQuery::new("building", Some("yes")),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_attribute_rules() {
+        let row = HashMap::from([("ROADTYPE".to_string(), "primary".to_string())]);
+        assert_eq!(
+            AttributeRule::copy("roadtype", "highway").apply(&row),
+            Some(("highway".to_string(), "primary".to_string()))
+        );
+        let rule = AttributeRule::new(
+            "ROADTYPE",
+            Some("secondary"),
+            OsmTagQuery::new("highway", Some("secondary")),
+        );
+        assert_eq!(rule.apply(&row), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_maps_records_to_osm_elements() {
+        let provider = test_provider().with_attributes(true);
+        let config = OsmConfigBuilder::new()
+            .bbox(52.49, 13.39, 52.51, 13.41)
+            .urban_features()
+            .build();
+
+        let data = provider.fetch_data(&config).await.unwrap();
+        assert_eq!(data.metadata.provider_type, "shapefile");
+        assert_eq!(
+            data.metadata.extra.get("record_count"),
+            Some(&"4".to_string())
+        );
+        // The unmapped tree and the record outside the region are skipped
+        assert_eq!(data.metadata.element_count, Some(2));
+
+        let elements = OsmParser.parse(&data).unwrap();
+        let road = elements
+            .iter()
+            .find(|e| e.tags.contains_key("highway"))
+            .unwrap();
+        assert_eq!(road.tags.get("highway"), Some(&"primary".to_string()));
+        assert_eq!(road.tags.get("name"), Some(&"Hauptstr".to_string()));
+        assert_eq!(road.geometry[0], (52.5, 13.39));
+
+        let grid = DefaultGridGenerator::new()
+            .generate_grid(&data, &config)
+            .await
+            .unwrap();
+        let stats = grid.statistics();
+        assert!(stats.tile_type_counts.contains_key(&TileType::Road));
+        assert!(stats.tile_type_counts.contains_key(&TileType::Building));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_region_without_geocoding() {
+        let provider = test_provider();
+        assert!(
+            provider
+                .resolve_region(&Region::city("Berlin"))
+                .await
+                .is_err()
+        );
+        assert!(provider.test_availability().await.is_ok());
+        assert!(!provider.capabilities().requires_network);
+    }
+}
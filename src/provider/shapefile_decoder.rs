@@ -0,0 +1,391 @@
+//! Minimal reader for ESRI shapefiles (.shp geometry and .dbf attributes)
+
+use std::collections::HashMap;
+
+use crate::{OsmTilesError, Result};
+
+/// Geometry type of a shapefile record; Z and M variants are read as 2D
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeType {
+    Null,
+    Point,
+    PolyLine,
+    Polygon,
+    MultiPoint,
+}
+
+impl ShapeType {
+    fn from_code(code: i32) -> Result<Self> {
+        match code {
+            0 => Ok(Self::Null),
+            1 | 11 | 21 => Ok(Self::Point),
+            3 | 13 | 23 => Ok(Self::PolyLine),
+            5 | 15 | 25 => Ok(Self::Polygon),
+            8 | 18 | 28 => Ok(Self::MultiPoint),
+            code => Err(OsmTilesError::Parse(format!(
+                "Unsupported shapefile shape type {}",
+                code
+            ))),
+        }
+    }
+}
+
+/// A decoded shapefile record with coordinates as (x, y) = (lon, lat)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeRecord {
+    /// Geometry type
+    pub shape_type: ShapeType,
+    /// Geometry parts (points, lines or rings)
+    pub parts: Vec<Vec<(f64, f64)>>,
+}
+
+impl ShapeRecord {
+    /// Get the bounding box (min_x, min_y, max_x, max_y) of all parts
+    pub fn extent(&self) -> Option<(f64, f64, f64, f64)> {
+        self.parts.iter().flatten().fold(None, |extent, &(x, y)| {
+            let (min_x, min_y, max_x, max_y) = extent.unwrap_or((x, y, x, y));
+            Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)))
+        })
+    }
+}
+
+/// Decode the records of a `.shp` file
+pub fn decode_shapes(data: &[u8]) -> Result<Vec<ShapeRecord>> {
+    if data.len() < 100 || read_i32_be(data, 0)? != 9994 {
+        return Err(invalid("shp header"));
+    }
+
+    let mut records = Vec::new();
+    let mut offset = 100;
+    while offset + 8 <= data.len() {
+        // Lengths count 16-bit words
+        let content_length = usize::try_from(read_i32_be(data, offset + 4)?)
+            .ok()
+            .and_then(|words| words.checked_mul(2))
+            .ok_or_else(|| invalid("shp record length"))?;
+        let end = (offset + 8)
+            .checked_add(content_length)
+            .ok_or_else(|| invalid("shp record length"))?;
+        let content = data
+            .get(offset + 8..end)
+            .ok_or_else(|| invalid("shp record length"))?;
+        records.push(decode_record(content)?);
+        offset = end;
+    }
+
+    Ok(records)
+}
+
+fn decode_record(content: &[u8]) -> Result<ShapeRecord> {
+    let shape_type = ShapeType::from_code(read_i32_le(content, 0)?)?;
+    let parts = match shape_type {
+        ShapeType::Null => Vec::new(),
+        ShapeType::Point => vec![vec![read_point(content, 4)?]],
+        ShapeType::MultiPoint => {
+            // Skip the record bounding box
+            let num_points = read_count(content, 36)?;
+            (0..num_points)
+                .map(|i| read_point(content, element_offset(40, i, 16)?).map(|point| vec![point]))
+                .collect::<Result<_>>()?
+        }
+        ShapeType::PolyLine | ShapeType::Polygon => {
+            let num_parts = read_count(content, 36)?;
+            let num_points = read_count(content, 40)?;
+            let points_offset = element_offset(44, num_parts, 4)?;
+
+            let mut starts = (0..num_parts)
+                .map(|i| read_count(content, element_offset(44, i, 4)?))
+                .collect::<Result<Vec<_>>>()?;
+            starts.push(num_points);
+
+            starts
+                .windows(2)
+                .map(|w| {
+                    if w[0] > w[1] {
+                        return Err(invalid("shp part index"));
+                    }
+                    (w[0]..w[1])
+                        .map(|i| read_point(content, element_offset(points_offset, i, 16)?))
+                        .collect()
+                })
+                .collect::<Result<_>>()?
+        }
+    };
+
+    Ok(ShapeRecord { shape_type, parts })
+}
+
+/// Signed area of a ring with y pointing up; negative for exterior (clockwise) rings
+pub fn shape_ring_area(ring: &[(f64, f64)]) -> f64 {
+    ring.windows(2)
+        .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Decode the rows of a `.dbf` attribute table, skipping deleted rows
+///
+/// Values are trimmed; empty values are left out of the row.
+pub fn decode_dbf(data: &[u8]) -> Result<Vec<HashMap<String, String>>> {
+    if data.len() < 32 {
+        return Err(invalid("dbf header"));
+    }
+    let record_count = u32::from_le_bytes(read_array(data, 4)?) as usize;
+    let header_length = u16::from_le_bytes(read_array(data, 8)?) as usize;
+    let record_length = u16::from_le_bytes(read_array(data, 10)?) as usize;
+    // Every record starts with its deletion flag
+    if record_length == 0 {
+        return Err(invalid("dbf record length"));
+    }
+
+    // Field descriptors follow the header until the 0x0D terminator
+    let mut fields = Vec::new();
+    let mut offset = 32;
+    while offset + 32 <= header_length && data.get(offset) != Some(&0x0D) {
+        let descriptor = data
+            .get(offset..offset + 32)
+            .ok_or_else(|| invalid("dbf field descriptor"))?;
+        let name_end = descriptor[..11].iter().position(|&b| b == 0).unwrap_or(11);
+        let name = String::from_utf8_lossy(&descriptor[..name_end]).to_string();
+        fields.push((name, descriptor[16] as usize));
+        offset += 32;
+    }
+
+    // The record count comes from the file, so it only bounds what is allocated
+    let mut rows = Vec::with_capacity(record_count.min(data.len() / record_length));
+    for index in 0..record_count {
+        let record = element_offset(header_length, index, record_length)
+            .ok()
+            .and_then(|start| data.get(start..start.checked_add(record_length)?))
+            .ok_or_else(|| invalid("dbf record length"))?;
+        if record[0] == b'*' {
+            continue;
+        }
+
+        let mut row = HashMap::new();
+        let mut position = 1;
+        for (name, length) in &fields {
+            let raw = record
+                .get(position..position + length)
+                .ok_or_else(|| invalid("dbf field length"))?;
+            let value = String::from_utf8_lossy(raw).trim().to_string();
+            if !value.is_empty() {
+                row.insert(name.clone(), value);
+            }
+            position += length;
+        }
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Get the offset of element `index` of `size` bytes in an array starting at `base`
+fn element_offset(base: usize, index: usize, size: usize) -> Result<usize> {
+    index
+        .checked_mul(size)
+        .and_then(|offset| offset.checked_add(base))
+        .ok_or_else(|| invalid("offset"))
+}
+
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    offset
+        .checked_add(N)
+        .and_then(|end| data.get(offset..end))
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid("length"))
+}
+
+fn read_i32_be(data: &[u8], offset: usize) -> Result<i32> {
+    read_array(data, offset).map(i32::from_be_bytes)
+}
+
+fn read_i32_le(data: &[u8], offset: usize) -> Result<i32> {
+    read_array(data, offset).map(i32::from_le_bytes)
+}
+
+fn read_count(data: &[u8], offset: usize) -> Result<usize> {
+    usize::try_from(read_i32_le(data, offset)?).map_err(|_| invalid("count"))
+}
+
+fn read_point(data: &[u8], offset: usize) -> Result<(f64, f64)> {
+    let x = f64::from_le_bytes(read_array(data, offset)?);
+    let y = f64::from_le_bytes(read_array(data, offset + 8)?);
+    Ok((x, y))
+}
+
+fn invalid(what: &str) -> OsmTilesError {
+    OsmTilesError::Parse(format!("Invalid shapefile: bad {}", what))
+}
+
+#[cfg(test)]
+pub(crate) mod encode {
+    pub type Part = Vec<(f64, f64)>;
+
+    /// Build a `.shp` file from (shape type code, parts) records
+    pub fn shp(records: &[(i32, Vec<Part>)]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (number, (shape_type, parts)) in records.iter().enumerate() {
+            let mut content = shape_type.to_le_bytes().to_vec();
+            if *shape_type == 1 {
+                let (x, y) = parts[0][0];
+                content.extend(x.to_le_bytes());
+                content.extend(y.to_le_bytes());
+            } else {
+                content.extend([0u8; 32]);
+                let points: Vec<&(f64, f64)> = parts.iter().flatten().collect();
+                content.extend((parts.len() as i32).to_le_bytes());
+                content.extend((points.len() as i32).to_le_bytes());
+                let mut start = 0i32;
+                for part in parts {
+                    content.extend(start.to_le_bytes());
+                    start += part.len() as i32;
+                }
+                for (x, y) in points {
+                    content.extend(x.to_le_bytes());
+                    content.extend(y.to_le_bytes());
+                }
+            }
+            body.extend((number as i32 + 1).to_be_bytes());
+            body.extend((content.len() as i32 / 2).to_be_bytes());
+            body.extend(content);
+        }
+
+        let mut file = vec![0u8; 100];
+        file[0..4].copy_from_slice(&9994i32.to_be_bytes());
+        file[24..28].copy_from_slice(&((100 + body.len()) as i32 / 2).to_be_bytes());
+        file[28..32].copy_from_slice(&1000i32.to_le_bytes());
+        file.extend(body);
+        file
+    }
+
+    /// Build a `.dbf` file with character fields of the given widths
+    pub fn dbf(fields: &[(&str, u8)], rows: &[&[&str]]) -> Vec<u8> {
+        let header_length = 32 + fields.len() * 32 + 1;
+        let record_length = 1 + fields.iter().map(|(_, w)| *w as usize).sum::<usize>();
+
+        let mut file = vec![0u8; 32];
+        file[0] = 3;
+        file[4..8].copy_from_slice(&(rows.len() as u32).to_le_bytes());
+        file[8..10].copy_from_slice(&(header_length as u16).to_le_bytes());
+        file[10..12].copy_from_slice(&(record_length as u16).to_le_bytes());
+        for (name, width) in fields {
+            let mut descriptor = [0u8; 32];
+            descriptor[..name.len()].copy_from_slice(name.as_bytes());
+            descriptor[11] = b'C';
+            descriptor[16] = *width;
+            file.extend(descriptor);
+        }
+        file.push(0x0D);
+        for row in rows {
+            file.push(b' ');
+            for ((_, width), value) in fields.iter().zip(row.iter()) {
+                file.extend(format!("{:<w$}", value, w = *width as usize).bytes());
+            }
+        }
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode::*;
+    use super::*;
+
+    #[test]
+    fn test_decode_shapes() {
+        let ring = vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)];
+        let data = shp(&[
+            (1, vec![vec![(13.4, 52.5)]]),
+            (
+                3,
+                vec![vec![(0.0, 0.0), (1.0, 1.0)], vec![(2.0, 2.0), (3.0, 3.0)]],
+            ),
+            (5, vec![ring.clone()]),
+        ]);
+
+        let records = decode_shapes(&data).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].shape_type, ShapeType::Point);
+        assert_eq!(records[0].parts, vec![vec![(13.4, 52.5)]]);
+        assert_eq!(records[1].shape_type, ShapeType::PolyLine);
+        assert_eq!(records[1].parts.len(), 2);
+        assert_eq!(records[1].extent(), Some((0.0, 0.0, 3.0, 3.0)));
+        assert_eq!(records[2].parts, vec![ring.clone()]);
+        // Shapefile exterior rings run clockwise
+        assert!(shape_ring_area(&ring) < 0.0);
+
+        assert!(decode_shapes(&data[..50]).is_err());
+        assert!(decode_shapes(&data[..data.len() - 4]).is_err());
+    }
+
+    #[test]
+    fn test_corrupted_shapes_are_errors() {
+        let data = shp(&[(3, vec![vec![(0.0, 0.0), (1.0, 1.0)]])]);
+
+        // Negative record length
+        let mut corrupted = data.clone();
+        corrupted[104..108].copy_from_slice(&(-1i32).to_be_bytes());
+        assert!(decode_shapes(&corrupted).is_err());
+
+        // Record length beyond the file
+        let mut corrupted = data.clone();
+        corrupted[104..108].copy_from_slice(&i32::MAX.to_be_bytes());
+        assert!(decode_shapes(&corrupted).is_err());
+
+        // Part and point counts beyond the record
+        for count_offset in [108 + 36, 108 + 40] {
+            let mut corrupted = data.clone();
+            corrupted[count_offset..count_offset + 4].copy_from_slice(&i32::MAX.to_le_bytes());
+            assert!(decode_shapes(&corrupted).is_err());
+        }
+
+        // Truncated records are errors, never partial geometry
+        for length in 0..data.len() {
+            if let Ok(records) = decode_shapes(&data[..length]) {
+                assert!(records.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_corrupted_dbf_is_an_error() {
+        let data = dbf(&[("NAME", 10)], &[&["Main St"], &["Park"]]);
+
+        // Zero record length
+        let mut corrupted = data.clone();
+        corrupted[10..12].copy_from_slice(&0u16.to_le_bytes());
+        assert!(decode_dbf(&corrupted).is_err());
+
+        // Record count far beyond the file
+        let mut corrupted = data.clone();
+        corrupted[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode_dbf(&corrupted).is_err());
+
+        // Header length beyond the file
+        let mut corrupted = data.clone();
+        corrupted[8..10].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(decode_dbf(&corrupted).is_err());
+
+        for length in 0..data.len() {
+            assert!(decode_dbf(&data[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_dbf() {
+        let mut data = dbf(
+            &[("NAME", 10), ("TYPE", 6)],
+            &[&["Main St", "road"], &["", "park"], &["Gone", "x"]],
+        );
+        // Mark the last row as deleted
+        let last = data.len() - 17;
+        data[last] = b'*';
+
+        let rows = decode_dbf(&data).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("NAME"), Some(&"Main St".to_string()));
+        assert_eq!(rows[0].get("TYPE"), Some(&"road".to_string()));
+        assert!(!rows[1].contains_key("NAME"));
+    }
+}
@@ -0,0 +1,245 @@
+use serde_json::{Value, json};
+
+use crate::BoundingBox;
+
+/// Deterministic generator of plausible city data for benchmarks and stress tests
+///
+/// Produces a road grid, a meandering river, parks, building blocks and
+/// named amenities inside any bounding box. The same seed, element count and
+/// bounding box always yield the same Overpass JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticCity {
+    /// Seed of the pseudo-random generator
+    pub seed: u64,
+    /// Number of elements to generate (at least 8)
+    pub element_count: usize,
+}
+
+/// Smallest city that still contains every kind of feature
+const MIN_ELEMENTS: usize = 8;
+
+const AMENITIES: [&str; 6] = [
+    "cafe",
+    "restaurant",
+    "school",
+    "pharmacy",
+    "bank",
+    "library",
+];
+
+const BUILDINGS: [&str; 4] = ["yes", "residential", "commercial", "apartments"];
+
+impl SyntheticCity {
+    /// Create a generator for about `element_count` elements
+    pub fn new(seed: u64, element_count: usize) -> Self {
+        Self {
+            seed,
+            element_count: element_count.max(MIN_ELEMENTS),
+        }
+    }
+
+    /// Generate the city inside `bbox` as Overpass JSON
+    pub fn generate(&self, bbox: &BoundingBox) -> String {
+        json!({
+            "version": 0.6,
+            "generator": "Synthetic City",
+            "elements": self.elements(bbox),
+        })
+        .to_string()
+    }
+
+    /// Generate the city elements inside `bbox`
+    pub fn elements(&self, bbox: &BoundingBox) -> Vec<Value> {
+        let total = self.element_count.max(MIN_ELEMENTS);
+        let grid = ((total as f64).sqrt() / 4.0).round().max(2.0) as usize;
+        let parks = (total / 20).max(1);
+        let amenities = (total / 10).max(1);
+        let buildings = total - 2 * grid - 1 - parks - amenities;
+
+        let mut rng = SplitMix64::new(self.seed);
+        let mut builder = ElementBuilder::default();
+        let cell_lat = bbox.height() / grid as f64;
+        let cell_lon = bbox.width() / grid as f64;
+
+        // Road lines in the middle of each row and column, with some jitter
+        let lats: Vec<f64> = (0..grid)
+            .map(|i| bbox.south + (i as f64 + 0.5 + rng.range(-0.1, 0.1)) * cell_lat)
+            .collect();
+        let lons: Vec<f64> = (0..grid)
+            .map(|i| bbox.west + (i as f64 + 0.5 + rng.range(-0.1, 0.1)) * cell_lon)
+            .collect();
+        let road_class = |i: usize| if i % 3 == 1 { "primary" } else { "residential" };
+        for (i, &lat) in lats.iter().enumerate() {
+            builder.way(
+                json!({"highway": road_class(i), "name": format!("Street {}", i + 1)}),
+                &[(lat, bbox.west), (lat, bbox.east)],
+            );
+        }
+        for (i, &lon) in lons.iter().enumerate() {
+            builder.way(
+                json!({"highway": road_class(i), "name": format!("Avenue {}", i + 1)}),
+                &[(bbox.south, lon), (bbox.north, lon)],
+            );
+        }
+
+        // A river meandering from west to east
+        let river_lat = bbox.south + rng.range(0.2, 0.8) * bbox.height();
+        let amplitude = rng.range(0.02, 0.08) * bbox.height();
+        let phase = rng.range(0.0, std::f64::consts::TAU);
+        let river: Vec<(f64, f64)> = (0..=16)
+            .map(|i| {
+                let t = i as f64 / 16.0;
+                let lat = river_lat + amplitude * (t * 3.0 * std::f64::consts::TAU + phase).sin();
+                (lat, bbox.west + t * bbox.width())
+            })
+            .collect();
+        builder.way(
+            json!({"waterway": "river", "name": "Synthetic River"}),
+            &river,
+        );
+
+        // Blocks are the cells of the road grid, shrunk to stay off the roads
+        let block = |rng: &mut SplitMix64, fill: f64| {
+            let (row, col) = (rng.index(grid), rng.index(grid));
+            let south = bbox.south + row as f64 * cell_lat;
+            let west = bbox.west + col as f64 * cell_lon;
+            let height = cell_lat * fill * rng.range(0.3, 1.0);
+            let width = cell_lon * fill * rng.range(0.3, 1.0);
+            let lat = south + rng.range(0.0, cell_lat - height);
+            let lon = west + rng.range(0.0, cell_lon - width);
+            rectangle(lat, lon, lat + height, lon + width)
+        };
+
+        for i in 0..parks {
+            let park = block(&mut rng, 0.6);
+            builder.way(
+                json!({"leisure": "park", "name": format!("Park {}", i + 1)}),
+                &park,
+            );
+        }
+        for _ in 0..buildings {
+            let building = block(&mut rng, 0.15);
+            let kind = BUILDINGS[rng.index(BUILDINGS.len())];
+            builder.way(json!({"building": kind}), &building);
+        }
+        for i in 0..amenities {
+            let kind = AMENITIES[rng.index(AMENITIES.len())];
+            let lat = bbox.south + rng.range(0.0, 1.0) * bbox.height();
+            let lon = bbox.west + rng.range(0.0, 1.0) * bbox.width();
+            builder.node(
+                json!({"amenity": kind, "name": format!("Synthetic {} {}", kind, i + 1)}),
+                lat,
+                lon,
+            );
+        }
+
+        builder.elements
+    }
+}
+
+/// Closed rectangular ring from south-west to north-east corners
+fn rectangle(south: f64, west: f64, north: f64, east: f64) -> Vec<(f64, f64)> {
+    vec![
+        (south, west),
+        (north, west),
+        (north, east),
+        (south, east),
+        (south, west),
+    ]
+}
+
+/// Collects Overpass JSON elements with sequential IDs
+#[derive(Default)]
+struct ElementBuilder {
+    elements: Vec<Value>,
+}
+
+impl ElementBuilder {
+    fn next_id(&self) -> usize {
+        self.elements.len() + 1
+    }
+
+    fn way(&mut self, tags: Value, points: &[(f64, f64)]) {
+        let geometry: Vec<Value> = points
+            .iter()
+            .map(|(lat, lon)| json!({"lat": lat, "lon": lon}))
+            .collect();
+        let id = self.next_id();
+        self.elements
+            .push(json!({"type": "way", "id": id, "geometry": geometry, "tags": tags}));
+    }
+
+    fn node(&mut self, tags: Value, lat: f64, lon: f64) {
+        let id = self.next_id();
+        self.elements
+            .push(json!({"type": "node", "id": id, "lat": lat, "lon": lon, "tags": tags}));
+    }
+}
+
+/// Small deterministic PRNG (SplitMix64), identical on every platform
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `[min, max)`
+    pub(crate) fn range(&mut self, min: f64, max: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        min + unit * (max - min)
+    }
+
+    /// Uniform index in `0..len`
+    fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_city_is_deterministic() {
+        let bbox = BoundingBox::new(52.49, 13.39, 52.51, 13.41);
+        let city = SyntheticCity::new(42, 500);
+
+        let elements = city.elements(&bbox);
+        assert_eq!(elements.len(), 500);
+        assert_eq!(city.generate(&bbox), city.generate(&bbox));
+        assert_ne!(
+            city.generate(&bbox),
+            SyntheticCity::new(7, 500).generate(&bbox)
+        );
+
+        let has = |key: &str| elements.iter().any(|e| e["tags"].get(key).is_some());
+        assert!(has("highway") && has("waterway") && has("leisure"));
+        assert!(has("building") && has("amenity"));
+
+        // Everything stays inside the requested area
+        for element in &elements {
+            let points = match element["geometry"].as_array() {
+                Some(geometry) => geometry.clone(),
+                None => vec![element.clone()],
+            };
+            for point in points {
+                let (lat, lon) = (
+                    point["lat"].as_f64().unwrap(),
+                    point["lon"].as_f64().unwrap(),
+                );
+                assert!(bbox.contains(lat, lon), "({}, {}) outside bbox", lat, lon);
+            }
+        }
+
+        assert_eq!(SyntheticCity::new(1, 0).elements(&bbox).len(), MIN_ELEMENTS);
+    }
+}
@@ -0,0 +1,279 @@
+//! Reproducible snapshots of a full download and generation run
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use crate::{GridGenerator, OsmConfig, OsmData, OsmDataProvider, OsmTilesError, Result, TileGrid};
+
+/// Version of the session file layout
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+/// Time spent in each stage of a session (in milliseconds)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionTimings {
+    /// Time spent fetching OSM data
+    pub fetch_ms: Option<u64>,
+    /// Time spent generating all grids
+    pub generation_ms: Option<u64>,
+    /// Wall-clock time of the whole run
+    pub total_ms: Option<u64>,
+}
+
+/// A pipeline run bundling configuration, input data and generated grids
+///
+/// Sessions serialize to a single JSON document. Attaching one to a bug report
+/// lets the run be replayed on another machine with [`Session::regenerate`]
+/// against the exact same input, without hitting the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Session file layout version
+    pub format_version: u32,
+    /// Crate version that created the session
+    pub crate_version: String,
+    /// Timestamp when the session was created
+    pub created_at: String,
+    /// Configuration used for the run
+    pub config: OsmConfig,
+    /// Hash of the raw OSM data, identifying the input even when it is not embedded
+    pub data_hash: String,
+    /// Size of the raw OSM data in bytes
+    pub data_size: usize,
+    /// The raw OSM data, unless the session only references it by hash
+    pub data: Option<OsmData>,
+    /// Generated grids
+    pub grids: Vec<TileGrid>,
+    /// Stage timings
+    pub timings: SessionTimings,
+    /// Free-form notes (e.g., platform, reproduction steps)
+    pub extra: HashMap<String, String>,
+}
+
+impl Session {
+    /// Create a session embedding the given configuration and data
+    pub fn new(config: OsmConfig, data: OsmData) -> Self {
+        Self {
+            format_version: SESSION_FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            config,
+            data_hash: data_hash(&data.raw_data),
+            data_size: data.raw_data.len(),
+            timings: SessionTimings {
+                fetch_ms: data.metadata.processing_time_ms,
+                ..Default::default()
+            },
+            data: Some(data),
+            grids: Vec::new(),
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Run the full pipeline and record everything needed to reproduce it
+    pub async fn capture(
+        provider: &dyn OsmDataProvider,
+        generator: &dyn GridGenerator,
+        config: OsmConfig,
+    ) -> Result<Self> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start_time = Instant::now();
+
+        let data = provider.fetch_data(&config).await?;
+        let grid = generator.generate_grid(&data, &config).await?;
+
+        let mut session = Self::new(config, data)
+            .with_grid(grid)
+            .with_extra("provider", provider.provider_type());
+
+        session.timings.total_ms = {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                Some(start_time.elapsed().as_millis() as u64)
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                None
+            }
+        };
+
+        Ok(session)
+    }
+
+    /// Add a generated grid and account for its generation time
+    pub fn with_grid(mut self, grid: TileGrid) -> Self {
+        let generation_ms = self.timings.generation_ms.unwrap_or(0);
+        self.timings.generation_ms = Some(generation_ms + grid.metadata.generation_time_ms);
+        self.grids.push(grid);
+        self
+    }
+
+    /// Add a free-form note
+    pub fn with_extra(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+
+    /// Drop the embedded data, keeping only its hash as a reference
+    pub fn without_data(mut self) -> Self {
+        self.data = None;
+        self
+    }
+
+    /// Check whether the given data is the input this session was created from
+    pub fn matches_data(&self, data: &OsmData) -> bool {
+        data.raw_data.len() == self.data_size && data_hash(&data.raw_data) == self.data_hash
+    }
+
+    /// Attach the input data to a session that only references it
+    pub fn attach_data(&mut self, data: OsmData) -> Result<()> {
+        if !self.matches_data(&data) {
+            return Err(OsmTilesError::Config(format!(
+                "Data does not match the session input (expected hash {}, got {})",
+                self.data_hash,
+                data_hash(&data.raw_data)
+            )));
+        }
+        self.data = Some(data);
+        Ok(())
+    }
+
+    /// Re-run grid generation on the recorded data and configuration
+    pub async fn regenerate(&self, generator: &dyn GridGenerator) -> Result<TileGrid> {
+        let data = self.data.as_ref().ok_or_else(|| {
+            OsmTilesError::Config(format!(
+                "Session references data {} without embedding it; attach the data first",
+                self.data_hash
+            ))
+        })?;
+        generator.generate_grid(data, &self.config).await
+    }
+
+    /// Serialize the session to a JSON document
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| OsmTilesError::Export(format!("Failed to serialize session: {}", e)))
+    }
+
+    /// Load a session from a JSON document, verifying its version and embedded data
+    pub fn from_json(json: &str) -> Result<Self> {
+        let session: Self =
+            serde_json::from_str(json).map_err(|e| OsmTilesError::json("session file", e))?;
+
+        if session.format_version > SESSION_FORMAT_VERSION {
+            return Err(OsmTilesError::Parse(format!(
+                "Session format version {} is newer than the supported version {}",
+                session.format_version, SESSION_FORMAT_VERSION
+            )));
+        }
+
+        if let Some(data) = &session.data
+            && !session.matches_data(data)
+        {
+            return Err(OsmTilesError::Parse(
+                "Session data does not match its recorded hash".to_string(),
+            ));
+        }
+
+        Ok(session)
+    }
+}
+
+/// Compute a stable 64-bit FNV-1a hash of raw data as a hex string
+///
+/// Unlike `std`'s default hasher, the result is identical across platforms
+/// and compiler versions, so hashes in session files stay comparable.
+pub fn data_hash(raw_data: &str) -> String {
+    let hash = raw_data
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    format!("fnv1a64:{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultGridGenerator, MockProvider, OsmConfigBuilder};
+
+    async fn captured_session() -> Session {
+        let config = OsmConfigBuilder::new()
+            .bbox(52.51, 13.39, 52.53, 13.42)
+            .urban_features()
+            .build();
+        Session::capture(&MockProvider::new(), &DefaultGridGenerator::new(), config)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_data_hash_is_stable() {
+        assert_eq!(data_hash(""), "fnv1a64:cbf29ce484222325");
+        assert_eq!(data_hash("a"), "fnv1a64:af63dc4c8601ec8c");
+        assert_ne!(data_hash("{}"), data_hash("{ }"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_and_round_trip() {
+        let session = captured_session().await;
+        assert_eq!(session.grids.len(), 1);
+        assert!(session.timings.generation_ms.is_some());
+        assert!(session.timings.total_ms.is_some());
+        assert_eq!(session.extra.get("provider"), Some(&"mock".to_string()));
+
+        let loaded = Session::from_json(&session.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.data_hash, session.data_hash);
+        assert_eq!(loaded.grids[0].dimensions(), session.grids[0].dimensions());
+
+        // Replaying the recorded input reproduces the same grid
+        let replayed = loaded
+            .regenerate(&DefaultGridGenerator::new())
+            .await
+            .unwrap();
+        assert_eq!(replayed.dimensions(), session.grids[0].dimensions());
+        assert_eq!(
+            replayed.count_tiles_by_type(),
+            session.grids[0].count_tiles_by_type()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reference_only_session() {
+        let session = captured_session().await;
+        let data = session.data.clone().unwrap();
+        let mut reference = Session::from_json(&session.without_data().to_json().unwrap()).unwrap();
+
+        assert!(
+            reference
+                .regenerate(&DefaultGridGenerator::new())
+                .await
+                .is_err()
+        );
+
+        let mut other = data.clone();
+        other.raw_data.push(' ');
+        assert!(reference.attach_data(other).is_err());
+
+        reference.attach_data(data).unwrap();
+        assert!(
+            reference
+                .regenerate(&DefaultGridGenerator::new())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_rejects_tampered_or_newer_sessions() {
+        let mut session = captured_session().await;
+        session.data.as_mut().unwrap().raw_data.push(' ');
+        assert!(Session::from_json(&session.to_json().unwrap()).is_err());
+
+        let mut session = captured_session().await;
+        session.format_version = SESSION_FORMAT_VERSION + 1;
+        assert!(Session::from_json(&session.to_json().unwrap()).is_err());
+
+        assert!(Session::from_json("not json").is_err());
+    }
+}
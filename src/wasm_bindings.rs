@@ -0,0 +1,164 @@
+//! JavaScript bindings for web map and game projects
+//!
+//! Build for `wasm32-unknown-unknown` with the `wasm-bindings` feature and
+//! generate the glue with `wasm-bindgen --target web`. The module then exports
+//! `loadCity`:
+//!
+//! ```js
+//! const map = await loadCity("Berlin");
+//! const map = await loadCity({ region: { City: { name: "Hamburg" } }, grid_resolution: 200 });
+//! ```
+//!
+//! The promise resolves to `{ width, height, tiles, palette, boundingBox,
+//! metersPerTile, attribution }`. `tiles` is a `Uint16Array` with one code per
+//! tile, row by row from the north-west corner; 0 is an empty tile and every
+//! other code indexes `palette`, a list of `{ code, name, color }` entries.
+
+use js_sys::{JSON, Reflect, Uint16Array};
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    DefaultGridGenerator, ExportMetadata, GridGenerator, OsmConfig, OsmDataProvider, OsmTilesError,
+    OverpassProvider, Result, TileGrid, TileIds, TilePalette,
+};
+
+/// Download a city or configured region from Overpass and generate its grid
+///
+/// `config` is a city name or a config file document as a JS object; fields
+/// it leaves out take their default values.
+#[wasm_bindgen(js_name = loadCity)]
+pub async fn load_city(config: JsValue) -> std::result::Result<JsValue, JsValue> {
+    let result = async {
+        let config = parse_config(&config)?;
+        let data = OverpassProvider::new().fetch_data(&config).await?;
+        DefaultGridGenerator::new()
+            .generate_grid(&data, &config)
+            .await
+    }
+    .await;
+    result
+        .and_then(|grid| to_js(&grid))
+        .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+}
+
+/// Read the `loadCity` argument into a configuration
+fn parse_config(config: &JsValue) -> Result<OsmConfig> {
+    if let Some(city) = config.as_string() {
+        return Ok(OsmConfig::for_city(city));
+    }
+    let json = JSON::stringify(config)
+        .ok()
+        .and_then(|json| json.as_string())
+        .ok_or_else(|| {
+            OsmTilesError::Config("config must be a city name or an object".to_string())
+        })?;
+    let document = serde_json::from_str(&json).map_err(|e| OsmTilesError::json("config", e))?;
+    config_with_defaults(document)
+}
+
+/// Fill the fields a config document leaves out with their defaults
+fn config_with_defaults(document: Value) -> Result<OsmConfig> {
+    let Value::Object(fields) = document else {
+        return Err(OsmTilesError::Config(
+            "config must be a city name or an object".to_string(),
+        ));
+    };
+    let mut merged = serde_json::to_value(OsmConfig::default())
+        .map_err(|e| OsmTilesError::json("default config", e))?;
+    if let Value::Object(defaults) = &mut merged {
+        defaults.extend(fields);
+    }
+    OsmConfig::from_json_str(&merged.to_string())
+}
+
+/// Tile codes row by row from the north-west corner, and the description of the grid
+fn grid_view(grid: &TileGrid, palette: &TilePalette) -> (Vec<u16>, Value) {
+    let (width, height) = grid.dimensions();
+    let ids = TileIds::for_grid(grid);
+
+    let mut tiles = Vec::with_capacity(width * height);
+    for image_y in 0..height {
+        for x in 0..width {
+            let code = grid
+                .from_image_coords(x, image_y)
+                .and_then(|(x, y)| grid.get_tile(x, y))
+                .and_then(|tile| ids.id(&tile.tile_type))
+                .map_or(0, |id| id as u16 + 1);
+            tiles.push(code);
+        }
+    }
+
+    let palette: Vec<Value> = ids
+        .tile_types()
+        .iter()
+        .enumerate()
+        .map(|(id, tile_type)| {
+            let (r, g, b) = palette.color(tile_type);
+            json!({
+                "code": id + 1,
+                "name": tile_type.name(),
+                "color": format!("#{:02x}{:02x}{:02x}", r, g, b),
+            })
+        })
+        .collect();
+    let bbox = &grid.bounding_box;
+    let view = json!({
+        "width": width,
+        "height": height,
+        "palette": palette,
+        "boundingBox": {
+            "south": bbox.south,
+            "west": bbox.west,
+            "north": bbox.north,
+            "east": bbox.east,
+        },
+        "metersPerTile": grid.meters_per_tile,
+        "attribution": ExportMetadata::from_grid(grid).attribution.notice(),
+    });
+    (tiles, view)
+}
+
+/// Build the object `loadCity` resolves to
+fn to_js(grid: &TileGrid) -> Result<JsValue> {
+    let (tiles, view) = grid_view(grid, &TilePalette::default());
+    let js_error = |e: JsValue| OsmTilesError::Export(format!("Failed to build JS value: {:?}", e));
+    let object = JSON::parse(&view.to_string()).map_err(js_error)?;
+    Reflect::set(
+        &object,
+        &JsValue::from_str("tiles"),
+        &Uint16Array::from(&tiles[..]),
+    )
+    .map_err(js_error)?;
+    Ok(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Region, Tile, TileType};
+
+    #[test]
+    fn test_grid_view() {
+        let config = config_with_defaults(json!({ "grid_resolution": 250 })).unwrap();
+        assert_eq!(config.grid_resolution, 250);
+        assert!(matches!(config.region, Region::City { .. }));
+        assert!(config_with_defaults(json!({ "grid_resolutoin": 250 })).is_err());
+
+        let mut grid = TileGrid::new(2, 2, BoundingBox::new(52.0, 13.0, 52.01, 13.01), 10.0);
+        grid.set_tile(0, 0, Tile::new(TileType::Road)).unwrap();
+        grid.set_tile(1, 1, Tile::new(TileType::Water)).unwrap();
+
+        let (tiles, view) = grid_view(&grid, &TilePalette::default());
+        assert_eq!(tiles, vec![1, 0, 0, 3]);
+        assert_eq!(view["width"], 2);
+        assert_eq!(view["palette"][2]["name"], "water");
+        assert_eq!(view["palette"][2]["color"], "#1e90ff");
+        assert!(
+            view["attribution"]
+                .as_str()
+                .unwrap()
+                .contains("OpenStreetMap")
+        );
+    }
+}